@@ -0,0 +1,81 @@
+//!
+//! Measures attribute lookup on elements with many attributes, as found in SVG or OOXML
+//! documents. Run with `cargo bench --bench attributes`; the time per lookup should not grow with
+//! the number of attributes on the element.
+//!
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::*;
+
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+const LOOKUPS: usize = 10_000;
+
+fn create_element(attribute_count: usize) -> (RefNode, RefNode) {
+    let document_node = get_implementation()
+        .create_document(Some(SVG_NS), Some("svg"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+    for index in 0..attribute_count / 2 {
+        element_node
+            .set_attribute(&format!("data-{}", index), "value")
+            .unwrap();
+        element_node
+            .set_attribute_ns(XLINK_NS, &format!("xlink:data-{}", index), "link")
+            .unwrap();
+    }
+    (document_node, element_node)
+}
+
+fn time_lookups(element_node: &RefNode, attribute_count: usize) -> Duration {
+    let names: Vec<String> = (0..LOOKUPS)
+        .map(|index| format!("data-{}", (index * 7) % (attribute_count / 2)))
+        .collect();
+    let start = Instant::now();
+    for name in &names {
+        let _ = black_box(element_node.get_attribute_node_ns(XLINK_NS, name));
+        let _ = black_box(element_node.get_attribute_node(name));
+    }
+    start.elapsed()
+}
+
+#[cfg(feature = "quick_parser")]
+fn time_parse(attribute_count: usize) -> Duration {
+    let attributes: String = (0..attribute_count / 2)
+        .map(|index| format!(r#" data-{0}="{0}" xlink:data-{0}="link""#, index))
+        .collect();
+    let element = format!("<path{}/>", attributes);
+    let xml = format!(
+        r#"<svg xmlns="{}" xmlns:xlink="{}">{}</svg>"#,
+        SVG_NS,
+        XLINK_NS,
+        element.repeat(20)
+    );
+    let start = Instant::now();
+    let _ = black_box(xml_dom::parser::read_xml(&xml).unwrap());
+    start.elapsed()
+}
+
+fn main() {
+    println!(
+        "{:>10} {:>16} {:>16}",
+        "attributes", "ns/lookup", "parse (20 elems)"
+    );
+    for attribute_count in [10, 100, 500, 2_000] {
+        let (_document_node, element_node) = create_element(attribute_count);
+        let elapsed = time_lookups(&element_node, attribute_count);
+        #[cfg(feature = "quick_parser")]
+        let parse = format!("{:?}", time_parse(attribute_count));
+        #[cfg(not(feature = "quick_parser"))]
+        let parse = String::from("-");
+        println!(
+            "{:>10} {:>16.1} {:>16}",
+            attribute_count,
+            elapsed.as_nanos() as f64 / (2 * LOOKUPS) as f64,
+            parse
+        );
+    }
+}
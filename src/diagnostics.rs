@@ -0,0 +1,442 @@
+/*!
+Provides a catalog of the diagnostics reported by this crate, each with a stable code, so that
+errors returned to, or logged for, users may be mapped back to their cause programmatically.
+
+Each DOM [`Error`](../level2/enum.Error.html) has a code in the range `E0001` to `E0099`, which is
+included in its `Display` output and returned by [`Error::code`](../level2/enum.Error.html#method.code).
+The messages logged by DOM operations, before they fail or return an empty result, have codes
+from `E0101`, and name the `Error` they accompany; these messages are also prefixed with their
+code. Codes are never reused or renumbered, new diagnostics are added with new codes.
+
+# Example
+
+```rust
+use xml_dom::diagnostics::{self, Diagnostic};
+use xml_dom::level2::Error;
+
+let error = Error::WrongDocument;
+assert_eq!(error.code(), "E0002");
+assert!(error.to_string().starts_with("E0002: "));
+
+let diagnostic: &Diagnostic = diagnostics::lookup("E0002").unwrap();
+assert_eq!(diagnostic.error(), &error);
+assert_eq!(diagnostic, &diagnostics::WRONG_DOCUMENT);
+```
+
+*/
+
+use crate::shared::error::Error;
+use std::fmt::{Display, Formatter, Result};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An entry in the diagnostics catalog: a stable code, the DOM error raised by, or accompanying,
+/// the diagnostic, and its message. A `Diagnostic` is displayed as `code: message`.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    code: &'static str,
+    error: Error,
+    message: &'static str,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Values -- DOM Errors
+// ------------------------------------------------------------------------------------------------
+
+///
+/// `E0001` -- a node was inserted somewhere it does not belong; for example, an attribute as a
+/// child, a second document element, an element into a text node, or a node into one of its own
+/// descendants.
+///
+pub const HIERARCHY_REQUEST: Diagnostic = Diagnostic::new(
+    "E0001",
+    Error::HierarchyRequest,
+    "An attempt insert a node somewhere it doesn't belong",
+);
+
+///
+/// `E0002` -- a node was inserted into, or an attribute set on, a node of a different document
+/// than the one that created it; use `import_node` to copy nodes between documents.
+///
+pub const WRONG_DOCUMENT: Diagnostic = Diagnostic::new(
+    "E0002",
+    Error::WrongDocument,
+    "An attempt to use a node in a different document than the one that created it",
+);
+
+///
+/// `E0003` -- an offset or count given to a character data operation, such as `substring_data`
+/// or `delete_data`, is outside of the node's data.
+///
+pub const INDEX_SIZE: Diagnostic = Diagnostic::new(
+    "E0003",
+    Error::IndexSize,
+    "Either `index` or `size` is negative, or greater than the allowed value",
+);
+
+///
+/// `E0004` -- the result of an operation would not fit into a string.
+///
+pub const STRING_SIZE: Diagnostic = Diagnostic::new(
+    "E0004",
+    Error::StringSize,
+    "The specified range of text does not fit into a DOMString",
+);
+
+///
+/// `E0005` -- a name is not a valid XML name, or is not allowed by the `strict_names` processing
+/// option; or, with the `fail_on_invalid_character` option, content contains a character not
+/// allowed by the document's XML version.
+///
+pub const INVALID_CHARACTER: Diagnostic = Diagnostic::new(
+    "E0005",
+    Error::InvalidCharacter,
+    "An invalid or illegal character was specified, such as in a name",
+);
+
+///
+/// `E0006` -- data was given for a node that does not have data.
+///
+pub const NO_DATA_ALLOWED: Diagnostic = Diagnostic::new(
+    "E0006",
+    Error::NoDataAllowed,
+    "An attempt to add data for a node which does not support data",
+);
+
+///
+/// `E0007` -- a node that is read-only, such as a frozen node or an entity, was modified.
+///
+pub const NO_MODIFICATION_ALLOWED: Diagnostic = Diagnostic::new(
+    "E0007",
+    Error::NoModificationAllowed,
+    "An attempt is made to modify an object where modifications are not allowed",
+);
+
+///
+/// `E0008` -- a node given as the reference for an operation, such as the child to remove or
+/// replace, is not where the operation expects it to be.
+///
+pub const NOT_FOUND: Diagnostic = Diagnostic::new(
+    "E0008",
+    Error::NotFound,
+    "An attempt is made to reference a node in a context where it does not exist",
+);
+
+///
+/// `E0009` -- the operation, or the requested type of node, is not supported; for example,
+/// cloning a document or document type node.
+///
+pub const NOT_SUPPORTED: Diagnostic = Diagnostic::new(
+    "E0009",
+    Error::NotSupported,
+    "The implementation does not support the requested type of object or operation",
+);
+
+///
+/// `E0010` -- an attribute that is already owned by another element was added; or, with the
+/// `fail_on_duplicate_attribute` processing option, an attribute would duplicate the qualified
+/// or expanded name of another.
+///
+pub const IN_USE_ATTRIBUTE: Diagnostic = Diagnostic::new(
+    "E0010",
+    Error::InUseAttribute,
+    "An attempt was made to add an attribute that is already in use elsewhere",
+);
+
+///
+/// `E0011` -- a node is not of the type expected by the operation, or its internal state does
+/// not match its type; see also the messages from `E0101`.
+///
+pub const INVALID_STATE: Diagnostic = Diagnostic::new(
+    "E0011",
+    Error::InvalidState,
+    "An attempt is made to use an object that is not, or is no longer, usable",
+);
+
+///
+/// `E0012` -- a string argument is not valid, for example a processing instruction target of
+/// `xml`, or a duplicate ID value.
+///
+pub const SYNTAX: Diagnostic = Diagnostic::new(
+    "E0012",
+    Error::Syntax,
+    "An invalid or illegal string was specified",
+);
+
+///
+/// `E0013` -- an operation would change the type of a node.
+///
+pub const INVALID_MODIFICATION: Diagnostic = Diagnostic::new(
+    "E0013",
+    Error::InvalidModification,
+    "An attempt was made to modify the type of the underlying object",
+);
+
+///
+/// `E0014` -- a qualified name and namespace URI are inconsistent, such as a prefix without a
+/// namespace or a reserved prefix bound to the wrong namespace; or, with the
+/// `validate_namespace_uris` processing option, a namespace URI is not a valid URI.
+///
+pub const NAMESPACE: Diagnostic = Diagnostic::new(
+    "E0014",
+    Error::Namespace,
+    "An attempt was made to create or change an object in a way which is incorrect with regard to namespaces",
+);
+
+///
+/// `E0015` -- a parameter, or the operation, is not supported by the node it was used on.
+///
+pub const INVALID_ACCESS: Diagnostic = Diagnostic::new(
+    "E0015",
+    Error::InvalidAccess,
+    "A parameter or an operation is not supported by the underlying object",
+);
+
+// ------------------------------------------------------------------------------------------------
+// Public Values -- Logged Messages
+// ------------------------------------------------------------------------------------------------
+
+///
+/// `E0101` -- a method was called on a node of the wrong type, for example an element method on
+/// a text node; the method fails with `InvalidState`, or returns an empty result.
+///
+pub const INVALID_NODE_TYPE: Diagnostic = Diagnostic::new(
+    "E0101",
+    Error::InvalidState,
+    "The node `self` is not of the type expected by this method.",
+);
+
+///
+/// `E0102` -- the internal data of a node does not match its node type; this indicates a bug in
+/// this crate rather than in the caller.
+///
+pub const INVALID_EXTENSION: Diagnostic = Diagnostic::new(
+    "E0102",
+    Error::InvalidState,
+    "This node's extension does not match it's node type.",
+);
+
+///
+/// `E0103` -- a name given to a lookup, such as `get_attribute`, is not a valid name; the lookup
+/// returns an empty result.
+///
+pub const INVALID_NAME: Diagnostic = Diagnostic::new(
+    "E0103",
+    Error::InvalidCharacter,
+    "The provided value could not be parsed into a `Name`.",
+);
+
+///
+/// `E0104` -- an operation that requires a parent, such as `next_sibling`, was used on a node
+/// that has none.
+///
+pub const NO_PARENT_NODE: Diagnostic = Diagnostic::new(
+    "E0104",
+    Error::NotFound,
+    "This node is missing a `parent_node` value.",
+);
+
+///
+/// `E0105` -- a child node created by a different document was appended or inserted.
+///
+pub const WRONG_DOCUMENT_CHILD: Diagnostic = Diagnostic::new(
+    "E0105",
+    Error::WrongDocument,
+    "Cannot append or insert a child node created in a different document.",
+);
+
+///
+/// `E0106` -- an offset or count given to a character data operation is outside of the node's
+/// data.
+///
+pub const INDEX_OUT_OF_RANGE: Diagnostic = Diagnostic::new(
+    "E0106",
+    Error::IndexSize,
+    "Either `offset` or `count` invalid for string operation.",
+);
+
+///
+/// `E0107` -- a node refers to a parent, or owner, that has been dropped; keep a reference to
+/// the document while its nodes are in use.
+///
+pub const WEAK_REF: Diagnostic = Diagnostic::new(
+    "E0107",
+    Error::InvalidState,
+    "Could not upgrade a weak reference.",
+);
+
+///
+/// `E0108` -- an `xml:id`, or other ID, attribute was given a value already used by another
+/// element in the document.
+///
+pub const DUPLICATE_ID: Diagnostic = Diagnostic::new(
+    "E0108",
+    Error::Syntax,
+    "Violation of `xml:id` §4, attempt to insert duplicate ID value.",
+);
+
+///
+/// `E0109` -- a node covered by a freeze, see the `freeze` module, was modified.
+///
+pub const FROZEN: Diagnostic = Diagnostic::new(
+    "E0109",
+    Error::NoModificationAllowed,
+    "This node is frozen, and may not be modified.",
+);
+
+///
+/// All the diagnostics in the catalog, in order of code.
+///
+pub const CATALOG: &[Diagnostic] = &[
+    HIERARCHY_REQUEST,
+    WRONG_DOCUMENT,
+    INDEX_SIZE,
+    STRING_SIZE,
+    INVALID_CHARACTER,
+    NO_DATA_ALLOWED,
+    NO_MODIFICATION_ALLOWED,
+    NOT_FOUND,
+    NOT_SUPPORTED,
+    IN_USE_ATTRIBUTE,
+    INVALID_STATE,
+    SYNTAX,
+    INVALID_MODIFICATION,
+    NAMESPACE,
+    INVALID_ACCESS,
+    INVALID_NODE_TYPE,
+    INVALID_EXTENSION,
+    INVALID_NAME,
+    NO_PARENT_NODE,
+    WRONG_DOCUMENT_CHILD,
+    INDEX_OUT_OF_RANGE,
+    WEAK_REF,
+    DUPLICATE_ID,
+    FROZEN,
+];
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the diagnostic with the code `code`, such as `E0002`, if there is one.
+///
+pub fn lookup(code: &str) -> Option<&'static Diagnostic> {
+    CATALOG
+        .iter()
+        .find(|diagnostic| diagnostic.code.eq_ignore_ascii_case(code))
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Diagnostic {
+    const fn new(code: &'static str, error: Error, message: &'static str) -> Self {
+        Self {
+            code,
+            error,
+            message,
+        }
+    }
+    ///
+    /// Returns the stable code of this diagnostic, such as `E0002`.
+    ///
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+    ///
+    /// Returns the DOM error raised by, or accompanying, this diagnostic.
+    ///
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+    ///
+    /// Returns the message of this diagnostic, without its code.
+    ///
+    pub fn message(&self) -> &'static str {
+        self.message
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Error {
+    ///
+    /// Returns the catalog entry for this error.
+    ///
+    pub fn diagnostic(&self) -> &'static Diagnostic {
+        match self {
+            Error::HierarchyRequest => &HIERARCHY_REQUEST,
+            Error::WrongDocument => &WRONG_DOCUMENT,
+            Error::IndexSize => &INDEX_SIZE,
+            Error::StringSize => &STRING_SIZE,
+            Error::InvalidCharacter => &INVALID_CHARACTER,
+            Error::NoDataAllowed => &NO_DATA_ALLOWED,
+            Error::NoModificationAllowed => &NO_MODIFICATION_ALLOWED,
+            Error::NotFound => &NOT_FOUND,
+            Error::NotSupported => &NOT_SUPPORTED,
+            Error::InUseAttribute => &IN_USE_ATTRIBUTE,
+            Error::InvalidState => &INVALID_STATE,
+            Error::Syntax => &SYNTAX,
+            Error::InvalidModification => &INVALID_MODIFICATION,
+            Error::Namespace => &NAMESPACE,
+            Error::InvalidAccess => &INVALID_ACCESS,
+        }
+    }
+    ///
+    /// Returns the stable code of this error, such as `E0002`, see the
+    /// [`diagnostics`](../diagnostics/index.html) module.
+    ///
+    pub fn code(&self) -> &'static str {
+        self.diagnostic().code()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_unique() {
+        for (index, diagnostic) in CATALOG.iter().enumerate() {
+            assert!(
+                CATALOG[index + 1..]
+                    .iter()
+                    .all(|other| other.code() != diagnostic.code()),
+                "{}",
+                diagnostic.code()
+            );
+            assert_eq!(lookup(diagnostic.code()), Some(diagnostic));
+        }
+        assert!(lookup("E9999").is_none());
+    }
+
+    #[test]
+    fn test_error_codes() {
+        for diagnostic in &CATALOG[..15] {
+            let error = diagnostic.error();
+            assert_eq!(error.diagnostic(), diagnostic);
+            assert_eq!(error.to_string(), diagnostic.to_string());
+        }
+        assert_eq!(Error::HierarchyRequest.code(), "E0001");
+        assert_eq!(
+            FROZEN.to_string(),
+            "E0109: This node is frozen, and may not be modified."
+        );
+    }
+}
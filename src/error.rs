@@ -0,0 +1,266 @@
+/*!
+Provides a single [`XmlError`](enum.XmlError.html) type covering both the DOM and the parser.
+
+The DOM API returns [`level2::Error`](../level2/enum.Error.html), which corresponds to the DOM
+`DOMException` codes, and the parser returns [`parser::Error`](../parser/enum.Error.html). Both
+convert into `XmlError`, which groups errors into a small set of categories; the original error
+is always available from `source()`, so that detail is not lost.
+
+# Example
+
+```rust
+use xml_dom::error::{XmlError, XmlResult};
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::parser::read_xml;
+
+fn first_child_name(xml: &str) -> XmlResult<String> {
+    let document_node = read_xml(xml)?;
+    let document = as_document(&document_node)?;
+    let element = document.document_element().ok_or(Error::NotFound)?;
+    Ok(element.node_name().to_string())
+}
+
+assert_eq!(first_child_name("<root/>").unwrap(), "root");
+assert!(matches!(first_child_name("<root>"), Err(XmlError::Syntax(_))));
+```
+
+*/
+
+use crate::shared::error::Error as DOMError;
+use std::error::Error as StdError;
+use std::result::Result as StdResult;
+use thiserror::Error as E;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The boxed error carried by each `XmlError` category, and returned by its `source()`.
+///
+pub type BoxedError = Box<dyn StdError + Send + Sync>;
+
+///
+/// A categorized error for any operation in this crate. Each variant carries the original error,
+/// which is returned by `source()`.
+///
+#[derive(Debug, E)]
+#[non_exhaustive]
+pub enum XmlError {
+    /// The input, or a provided name or value, is not well-formed.
+    #[error("syntax error: {0}")]
+    Syntax(#[source] BoxedError),
+    /// A namespace constraint was violated.
+    #[error("namespace error: {0}")]
+    Namespace(#[source] BoxedError),
+    /// An operation would create an invalid tree, or referenced a node that is not usable here.
+    #[error("hierarchy error: {0}")]
+    Hierarchy(#[source] BoxedError),
+    /// Content could not be read, or loaded.
+    #[error("I/O error: {0}")]
+    IO(#[source] BoxedError),
+    /// An index, or size, is outside of the allowed range.
+    #[error("limit exceeded: {0}")]
+    Limit(#[source] BoxedError),
+    /// The requested operation, or feature, is not supported.
+    #[error("unsupported: {0}")]
+    Unsupported(#[source] BoxedError),
+}
+
+///
+/// Result type using the unified `XmlError`.
+///
+pub type XmlResult<T> = StdResult<T, XmlError>;
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl From<DOMError> for XmlError {
+    fn from(error: DOMError) -> Self {
+        dom_category(&error)(Box::new(error))
+    }
+}
+
+#[cfg(feature = "quick_parser")]
+impl From<crate::parser::Error> for XmlError {
+    fn from(error: crate::parser::Error) -> Self {
+        use crate::parser::Error as ParserError;
+        match error {
+            ParserError::DOMError(error) => error.into(),
+            ParserError::QuickXMLError(error) => quick_xml_category(&error)(Box::new(error)),
+            // a positioned error is kept whole, so that its position is not lost.
+            _ => parser_category(error.kind())(Box::new(error)),
+        }
+    }
+}
+
+impl XmlError {
+    ///
+    /// Returns the original DOM error, if this error was raised by a DOM operation.
+    ///
+    pub fn dom_error(&self) -> Option<&DOMError> {
+        #[cfg(feature = "quick_parser")]
+        if let Some(crate::parser::Error::DOMError(error)) =
+            self.parser_error().map(crate::parser::Error::kind)
+        {
+            return Some(error);
+        }
+        self.inner().downcast_ref::<DOMError>()
+    }
+
+    ///
+    /// Returns the original parser error, if this error was raised by the parser and not by a DOM
+    /// operation, or by quick-xml, alone.
+    ///
+    #[cfg(feature = "quick_parser")]
+    pub fn parser_error(&self) -> Option<&crate::parser::Error> {
+        self.inner().downcast_ref::<crate::parser::Error>()
+    }
+
+    ///
+    /// Returns the position in the input at which the error was detected, if this error was
+    /// raised by the parser and has one; see
+    /// [`parser::Error::source_snippet`](../parser/enum.Error.html#method.source_snippet) to show
+    /// the offending line.
+    ///
+    #[cfg(feature = "quick_parser")]
+    pub fn position(&self) -> Option<crate::parser::Position> {
+        self.parser_error()?.position()
+    }
+
+    fn inner(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        match self {
+            XmlError::Syntax(error)
+            | XmlError::Namespace(error)
+            | XmlError::Hierarchy(error)
+            | XmlError::IO(error)
+            | XmlError::Limit(error)
+            | XmlError::Unsupported(error) => error.as_ref(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn dom_category(error: &DOMError) -> fn(BoxedError) -> XmlError {
+    match error {
+        DOMError::IndexSize | DOMError::StringSize => XmlError::Limit,
+        DOMError::InvalidCharacter | DOMError::NoDataAllowed | DOMError::Syntax => XmlError::Syntax,
+        DOMError::Namespace => XmlError::Namespace,
+        DOMError::NotSupported | DOMError::InvalidAccess => XmlError::Unsupported,
+        DOMError::HierarchyRequest
+        | DOMError::WrongDocument
+        | DOMError::NoModificationAllowed
+        | DOMError::NotFound
+        | DOMError::InUseAttribute
+        | DOMError::InvalidState
+        | DOMError::InvalidModification => XmlError::Hierarchy,
+    }
+}
+
+#[cfg(feature = "quick_parser")]
+fn quick_xml_category(error: &quick_xml::Error) -> fn(BoxedError) -> XmlError {
+    match error {
+        quick_xml::Error::Io(_) => XmlError::IO,
+        _ => XmlError::Syntax,
+    }
+}
+
+///
+/// Returns the category of `error`, which is not positioned.
+///
+#[cfg(feature = "quick_parser")]
+fn parser_category(error: &crate::parser::Error) -> fn(BoxedError) -> XmlError {
+    use crate::parser::Error as ParserError;
+    match error {
+        ParserError::InvalidCharacter
+        | ParserError::Malformed
+        | ParserError::EncodingMismatch { .. }
+        | ParserError::Source(_)
+        | ParserError::Invalid(_)
+        | ParserError::Positioned { .. } => XmlError::Syntax,
+        ParserError::DOMError(error) => dom_category(error),
+        ParserError::QuickXMLError(error) => quick_xml_category(error),
+        ParserError::InvalidName { source, .. } => match source {
+            DOMError::Namespace => XmlError::Namespace,
+            _ => XmlError::Syntax,
+        },
+        ParserError::DepthExceeded(_) | ParserError::NodeLimitExceeded(_) => XmlError::Limit,
+        ParserError::UnsupportedEncoding(_) => XmlError::Unsupported,
+        ParserError::Load { .. } | ParserError::Loader(_) => XmlError::IO,
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dom_error_categories() {
+        assert!(matches!(
+            XmlError::from(DOMError::IndexSize),
+            XmlError::Limit(_)
+        ));
+        assert!(matches!(
+            XmlError::from(DOMError::Namespace),
+            XmlError::Namespace(_)
+        ));
+        assert!(matches!(
+            XmlError::from(DOMError::HierarchyRequest),
+            XmlError::Hierarchy(_)
+        ));
+        assert!(matches!(
+            XmlError::from(DOMError::NotSupported),
+            XmlError::Unsupported(_)
+        ));
+
+        let error = XmlError::from(DOMError::InvalidCharacter);
+        assert!(matches!(error, XmlError::Syntax(_)));
+        assert_eq!(error.dom_error(), Some(&DOMError::InvalidCharacter));
+    }
+
+    #[cfg(feature = "quick_parser")]
+    #[test]
+    fn test_parser_error_source() {
+        let error: XmlError = crate::parser::read_xml("<root></other>")
+            .unwrap_err()
+            .into();
+        assert!(matches!(error, XmlError::Syntax(_)));
+        assert!(error.dom_error().is_none());
+        let source = error.source().unwrap();
+        let parser_error = source.downcast_ref::<crate::parser::Error>().unwrap();
+        assert!(matches!(
+            parser_error.kind(),
+            crate::parser::Error::QuickXMLError(_)
+        ));
+    }
+
+    #[cfg(feature = "quick_parser")]
+    #[test]
+    fn test_parser_error_position() {
+        fn parse(xml: &str) -> XmlResult<crate::level2::RefNode> {
+            Ok(crate::parser::read_xml(xml)?)
+        }
+        let xml = "<root>\n  <a></b>\n</root>";
+        let error = parse(xml).unwrap_err();
+        assert!(matches!(error, XmlError::Syntax(_)));
+        let position = error.position().unwrap();
+        assert_eq!(position.line(), 2);
+        assert_eq!(
+            error.parser_error().unwrap().source_snippet(xml),
+            position.snippet(xml)
+        );
+        assert!(error.to_string().ends_with(&format!("at {}", position)));
+
+        let error: XmlError = crate::parser::read_xml("<a>&bad;</a>").unwrap_err().into();
+        assert!(error.position().is_some());
+    }
+}
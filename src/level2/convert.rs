@@ -8,9 +8,10 @@ Note that all of the `as_{name}` functions work as follows.
 * If the `node_type` is not implemented it returns `Error::NotSupported`.
 
 */
+use crate::diagnostics::INVALID_NODE_TYPE;
 use crate::level2::node_impl::*;
 use crate::level2::traits::*;
-use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE};
+use crate::shared::error::{Error, Result};
 
 use crate::{make_is_as_functions, make_ref_type};
 
@@ -95,7 +96,7 @@ pub fn as_character_data(ref_node: &RefNode) -> Result<RefCharacterData<'_>> {
             Ok(ref_node as RefCharacterData<'_>)
         }
         _ => {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn!("{}", INVALID_NODE_TYPE);
             Err(Error::InvalidState)
         }
     }
@@ -112,7 +113,7 @@ pub fn as_character_data_mut(ref_node: &mut RefNode) -> Result<MutRefCharacterDa
             Ok(ref_node as MutRefCharacterData<'_>)
         }
         _ => {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn!("{}", INVALID_NODE_TYPE);
             Err(Error::InvalidState)
         }
     }
@@ -1,6 +1,7 @@
 /*!
 This module implements certain capabilities required by, but not specified by, the DOM Core.
 */
+use crate::level2::ext::options::ProcessingOptions;
 use crate::level2::node_impl::RefNode;
 use crate::level2::traits::DOMImplementation;
 
@@ -8,18 +9,43 @@ use crate::level2::traits::DOMImplementation;
 // Public Types
 // ------------------------------------------------------------------------------------------------
 
-#[doc(hidden)]
-#[derive(Clone, Debug)]
-pub(crate) struct Implementation {}
+///
+/// The concrete `DOMImplementation` provided by this crate.
+///
+/// While [`get_implementation`](fn.get_implementation.html) returns a shared instance, clients may
+/// construct independent instances, each with their own default [`ProcessingOptions`](ext/options/struct.ProcessingOptions.html)
+/// used by `create_document`. Each document records the implementation that created it, this is
+/// then returned by the document's [`implementation`](trait.Document.html#tymethod.implementation)
+/// method so that further documents created from it share the same defaults.
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::ext::ProcessingOptions;
+///
+/// let mut options = ProcessingOptions::new();
+/// options.set_assume_ids();
+///
+/// let implementation = Implementation::with_default_options(options);
+/// let document_node = implementation
+///     .create_document(None, Some("root"), None)
+///     .unwrap();
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Implementation {
+    default_options: ProcessingOptions,
+}
 
 // ------------------------------------------------------------------------------------------------
 // Public Functions
 // ------------------------------------------------------------------------------------------------
 
-const THIS_IMPLEMENTATION: &'static dyn DOMImplementation<NodeRef = RefNode> = &Implementation {};
+static THIS_IMPLEMENTATION: Implementation = Implementation::new();
 
 ///
-/// Return a reference to an instance of this `DOMImplementation` implementation.
+/// Return a reference to the shared instance of this `DOMImplementation` implementation.
 ///
 /// This function gets around the DOM bootstrap issue, the `implementation` method on the
 /// [`Document`](trait.Document.html) trait requires an instance of `Document`; however, the
@@ -37,7 +63,11 @@ const THIS_IMPLEMENTATION: &'static dyn DOMImplementation<NodeRef = RefNode> = &
 /// ```
 ///
 pub fn get_implementation() -> &'static dyn DOMImplementation<NodeRef = RefNode> {
-    THIS_IMPLEMENTATION
+    &THIS_IMPLEMENTATION
+}
+
+pub(crate) fn this_implementation() -> &'static Implementation {
+    &THIS_IMPLEMENTATION
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -52,3 +82,39 @@ const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub fn get_implementation_version() -> String {
     format!("{}:{}", CRATE_NAME, CRATE_VERSION)
 }
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for Implementation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Implementation {
+    ///
+    /// Construct a new implementation with the same defaults as the shared instance returned by
+    /// `get_implementation`; i.e. `create_document` will set the `AddNamespaces` option.
+    ///
+    pub const fn new() -> Self {
+        let mut default_options = ProcessingOptions::new();
+        default_options.set_add_namespaces();
+        Self { default_options }
+    }
+
+    ///
+    /// Construct a new implementation where `create_document` will use the provided options.
+    ///
+    pub const fn with_default_options(default_options: ProcessingOptions) -> Self {
+        Self { default_options }
+    }
+
+    ///
+    /// The options used by `create_document` for documents created by this implementation.
+    ///
+    pub fn default_options(&self) -> ProcessingOptions {
+        self.default_options.clone()
+    }
+}
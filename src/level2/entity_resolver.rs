@@ -3,7 +3,7 @@ Implementation of `EntityResolver` that navigates the `Document` structure.
 */
 
 use crate::level2::convert::{as_document, as_document_type};
-use crate::level2::{Name, Node, NodeType, RefNode};
+use crate::level2::{Name, NodeType, RefNode};
 use crate::shared::text::EntityResolver;
 use std::str::FromStr;
 
@@ -31,7 +31,9 @@ impl EntityResolver for RefNode {
             None => None,
             Some(doc_type) => {
                 let doc_type = as_document_type(&doc_type).unwrap();
-                let name = Name::from_str(entity).unwrap();
+                // the reference may include the delimiters, as in `&name;`.
+                let name = entity.trim_start_matches(['&', '%']).trim_end_matches(';');
+                let name = Name::from_str(name).ok()?;
                 match doc_type.entities().get(&name) {
                     None => None,
                     Some(entity) => entity.node_value(),
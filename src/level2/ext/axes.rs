@@ -0,0 +1,480 @@
+/*!
+This module provides the iterator types returned by the [`Axes`](../trait.Axes.html),
+[`NodeIterators`](../trait.NodeIterators.html), and [`TypedChildren`](../trait.TypedChildren.html)
+traits.
+
+All of the node iterators are lazy, they hold only the position within the tree and do not copy
+the child lists of the nodes they visit.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::Axes;
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml("<a><b><c/></b><d/></a>").unwrap();
+let document = as_document(&document_node).unwrap();
+let c = document.get_elements_by_tag_name("c").remove(0);
+
+let names: Vec<String> = c.ancestor().map(|node| node.node_name().to_string()).collect();
+assert_eq!(names, vec!["b", "a", "#document"]);
+
+let names: Vec<String> = c.following().map(|node| node.node_name().to_string()).collect();
+assert_eq!(names, vec!["d"]);
+```
+
+*/
+
+use crate::level2::convert::is_attribute;
+use crate::level2::ext::traits::{Axes, NodeIterators, TypedChildren};
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::level2::traits::NodeType;
+use crate::shared::syntax::{XML_NS_ATTRIBUTE, XML_NS_URI};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An iterator over a node and its ancestors, nearest first; returned by the `ancestor` and
+/// `ancestor_or_self` axes.
+///
+#[derive(Clone, Debug)]
+pub struct Ancestors {
+    next: Option<RefNode>,
+}
+
+///
+/// An iterator over a node's descendants in document order; returned by the `descendant` and
+/// `descendant_or_self` axes.
+///
+#[derive(Clone, Debug)]
+pub struct Descendants {
+    include_self: Option<RefNode>,
+    stack: Vec<(RefNode, usize)>,
+}
+
+///
+/// An iterator over a run of the children of a single parent node; returned by the `child`,
+/// `following_sibling`, and `preceding_sibling` axes.
+///
+#[derive(Clone, Debug)]
+pub struct Siblings {
+    parent: Option<RefNode>,
+    next: usize,
+    end: usize,
+    reverse: bool,
+}
+
+///
+/// An iterator over the children of a node that have a given node type; returned by the
+/// `TypedChildren` methods.
+///
+#[derive(Clone, Debug)]
+pub struct ChildrenOfType {
+    children: Siblings,
+    node_type: NodeType,
+}
+
+///
+/// An iterator over all nodes after a node in document order, excluding its descendants;
+/// returned by the `following` axis.
+///
+pub struct Following {
+    inner: Box<dyn Iterator<Item = RefNode>>,
+}
+
+///
+/// An iterator over all nodes before a node in reverse document order, excluding its ancestors;
+/// returned by the `preceding` axis.
+///
+pub struct Preceding {
+    inner: Box<dyn Iterator<Item = RefNode>>,
+}
+
+///
+/// An iterator over the namespace mappings in scope for an element, as `(prefix, namespace URI)`
+/// pairs where the default namespace has the prefix `None`; returned by the `namespace` axis.
+///
+pub type NamespaceAxis = std::vec::IntoIter<(Option<String>, String)>;
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Iterates a node and its descendants in reverse document order, the node itself last.
+///
+#[derive(Clone, Debug)]
+struct ReverseDescendantsOrSelf {
+    stack: Vec<(RefNode, usize)>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Axes for RefNode {
+    fn ancestor(&self) -> Ancestors {
+        Ancestors {
+            next: axis_parent(self),
+        }
+    }
+
+    fn ancestor_or_self(&self) -> Ancestors {
+        Ancestors {
+            next: Some(self.clone()),
+        }
+    }
+
+    fn child(&self) -> Siblings {
+        if is_attribute(self) {
+            Siblings::empty()
+        } else {
+            let end = self.borrow().i_child_nodes.len();
+            Siblings {
+                parent: Some(self.clone()),
+                next: 0,
+                end,
+                reverse: false,
+            }
+        }
+    }
+
+    fn descendant(&self) -> Descendants {
+        Descendants {
+            include_self: None,
+            stack: if is_attribute(self) {
+                Vec::new()
+            } else {
+                vec![(self.clone(), 0)]
+            },
+        }
+    }
+
+    fn descendant_or_self(&self) -> Descendants {
+        let mut descendants = self.descendant();
+        descendants.include_self = Some(self.clone());
+        descendants
+    }
+
+    fn following(&self) -> Following {
+        // The following axis of an attribute starts with its owner element's descendants.
+        let (start, leading) = if is_attribute(self) {
+            match axis_parent(self) {
+                None => return Following::empty(),
+                Some(owner) => {
+                    let leading = owner.descendant();
+                    (owner, Some(leading))
+                }
+            }
+        } else {
+            (self.clone(), None)
+        };
+        let rest = start.ancestor_or_self().flat_map(|ancestor| {
+            ancestor
+                .following_sibling()
+                .flat_map(|sibling| sibling.descendant_or_self())
+        });
+        Following {
+            inner: match leading {
+                None => Box::new(rest),
+                Some(leading) => Box::new(leading.chain(rest)),
+            },
+        }
+    }
+
+    fn following_sibling(&self) -> Siblings {
+        match sibling_position(self) {
+            None => Siblings::empty(),
+            Some((parent, index)) => {
+                let end = parent.borrow().i_child_nodes.len();
+                Siblings {
+                    parent: Some(parent),
+                    next: index + 1,
+                    end,
+                    reverse: false,
+                }
+            }
+        }
+    }
+
+    fn preceding(&self) -> Preceding {
+        // An attribute's owner element is an ancestor, so the preceding axis is the same.
+        let start = if is_attribute(self) {
+            match axis_parent(self) {
+                None => return Preceding::empty(),
+                Some(owner) => owner,
+            }
+        } else {
+            self.clone()
+        };
+        Preceding {
+            inner: Box::new(start.ancestor_or_self().flat_map(|ancestor| {
+                ancestor
+                    .preceding_sibling()
+                    .flat_map(ReverseDescendantsOrSelf::new)
+            })),
+        }
+    }
+
+    fn preceding_sibling(&self) -> Siblings {
+        match sibling_position(self) {
+            None => Siblings::empty(),
+            Some((parent, index)) => Siblings {
+                parent: Some(parent),
+                next: 0,
+                end: index,
+                reverse: true,
+            },
+        }
+    }
+
+    fn attribute_axis(&self) -> std::vec::IntoIter<RefNode> {
+        let ref_self = self.borrow();
+        let attributes: Vec<RefNode> = match &ref_self.i_extension {
+            Extension::Element { i_attributes, .. } => i_attributes.values().cloned().collect(),
+            _ => Vec::new(),
+        };
+        attributes.into_iter()
+    }
+
+    fn namespace_axis(&self) -> NamespaceAxis {
+        if self.borrow().i_node_type != NodeType::Element {
+            return Vec::new().into_iter();
+        }
+        let mut in_scope: HashMap<Option<String>, String> = HashMap::new();
+        for ancestor in self.ancestor_or_self() {
+            if let Extension::Element { i_namespaces, .. } = &ancestor.borrow().i_extension {
+                for (prefix, namespace_uri) in i_namespaces {
+                    if !in_scope.contains_key(prefix) {
+                        let _safe_to_ignore =
+                            in_scope.insert(prefix.clone(), namespace_uri.clone());
+                    }
+                }
+            }
+        }
+        let _safe_to_ignore =
+            in_scope.insert(Some(XML_NS_ATTRIBUTE.to_string()), XML_NS_URI.to_string());
+        // an empty default namespace undeclares it.
+        in_scope.retain(|prefix, namespace_uri| prefix.is_some() || !namespace_uri.is_empty());
+        let mut namespaces: Vec<(Option<String>, String)> = in_scope.into_iter().collect();
+        namespaces.sort();
+        namespaces.into_iter()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NodeIterators for RefNode {}
+
+// ------------------------------------------------------------------------------------------------
+
+impl TypedChildren for RefNode {
+    fn children_of_type(&self, node_type: NodeType) -> ChildrenOfType {
+        ChildrenOfType {
+            children: self.child(),
+            node_type,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for Ancestors {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        self.next = axis_parent(&current);
+        Some(current)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for Descendants {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(node) = self.include_self.take() {
+            return Some(node);
+        }
+        loop {
+            let (parent, index) = self.stack.last_mut()?;
+            let child = parent.borrow().i_child_nodes.get(*index).cloned();
+            match child {
+                None => {
+                    let _safe_to_ignore = self.stack.pop();
+                }
+                Some(child) => {
+                    *index += 1;
+                    self.stack.push((child.clone(), 0));
+                    return Some(child);
+                }
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Siblings {
+    fn empty() -> Self {
+        Self {
+            parent: None,
+            next: 0,
+            end: 0,
+            reverse: false,
+        }
+    }
+}
+
+impl Iterator for Siblings {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+        let index = if self.reverse {
+            self.end -= 1;
+            self.end
+        } else {
+            self.next += 1;
+            self.next - 1
+        };
+        let parent = self.parent.as_ref()?;
+        let ref_parent = parent.borrow();
+        ref_parent.i_child_nodes.get(index).cloned()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for ChildrenOfType {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node_type = &self.node_type;
+        self.children
+            .by_ref()
+            .find(|child| &child.borrow().i_node_type == node_type)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for Following {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Following").finish_non_exhaustive()
+    }
+}
+
+impl Following {
+    fn empty() -> Self {
+        Self {
+            inner: Box::new(std::iter::empty()),
+        }
+    }
+}
+
+impl Iterator for Following {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for Preceding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Preceding").finish_non_exhaustive()
+    }
+}
+
+impl Preceding {
+    fn empty() -> Self {
+        Self {
+            inner: Box::new(std::iter::empty()),
+        }
+    }
+}
+
+impl Iterator for Preceding {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ReverseDescendantsOrSelf {
+    fn new(node: RefNode) -> Self {
+        let children = node.borrow().i_child_nodes.len();
+        Self {
+            stack: vec![(node, children)],
+        }
+    }
+}
+
+impl Iterator for ReverseDescendantsOrSelf {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, remaining) = self.stack.last_mut()?;
+            if *remaining == 0 {
+                return self.stack.pop().map(|(node, _)| node);
+            }
+            *remaining -= 1;
+            let child = node.borrow().i_child_nodes.get(*remaining).cloned();
+            if let Some(child) = child {
+                let children = child.borrow().i_child_nodes.len();
+                self.stack.push((child, children));
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The XPath parent of a node; this is the owner element for attributes.
+///
+fn axis_parent(node: &RefNode) -> Option<RefNode> {
+    let ref_node = node.borrow();
+    let parent = match &ref_node.i_extension {
+        Extension::Attribute {
+            i_owner_element, ..
+        } => i_owner_element.as_ref(),
+        _ => ref_node.i_parent_node.as_ref(),
+    };
+    parent.and_then(|parent| parent.clone().upgrade())
+}
+
+///
+/// The parent of a non-attribute node, and the index of the node within its parent's children.
+///
+fn sibling_position(node: &RefNode) -> Option<(RefNode, usize)> {
+    if is_attribute(node) {
+        return None;
+    }
+    let parent = axis_parent(node)?;
+    let index = parent
+        .borrow()
+        .i_child_nodes
+        .iter()
+        .position(|child| child == node)?;
+    Some((parent, index))
+}
@@ -0,0 +1,527 @@
+/*!
+Provides the [`Transaction`](struct.Transaction.html) and [`Violation`](struct.Violation.html)
+types used by the [`Batch`](../trait.Batch.html) trait to apply a set of mutations to a document
+atomically.
+
+A batch is applied in two phases. First, the closure passed to
+[`batch`](../trait.Batch.html#tymethod.batch) records mutations in a `Transaction`, nothing in the
+document is changed. Then the mutations are applied, in the order they were recorded, and the
+combined result is validated; the hierarchy must be one the DOM allows, no two elements may have
+the same ID, and no prefix may be used with a namespace other than the one it is mapped to in
+scope. If any mutation fails, or the result is not valid, every applied mutation is undone and all
+the violations found are returned.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::Batch;
+
+let mut document_node = get_implementation()
+    .create_document(None, Some("rules"), None)
+    .unwrap();
+let document_ref = document_node.clone();
+let document = as_document(&document_ref).unwrap();
+let root_node = document.document_element().unwrap();
+let rule_node = document.create_element("rule").unwrap();
+
+let result = document_node.batch(|tx| {
+    tx.append_child(&root_node, rule_node.clone());
+    tx.set_attribute(&rule_node, "xml:id", "r1");
+    tx.append_child(&document_ref, document.create_text_node("not allowed"));
+});
+let violations = result.unwrap_err();
+assert_eq!(violations.len(), 1);
+assert_eq!(violations[0].operation, Some(2));
+assert_eq!(violations[0].error, Error::HierarchyRequest);
+assert_eq!(root_node.to_string(), "<rules></rules>");
+assert!(document.get_element_by_id("r1").is_none());
+```
+
+*/
+
+use crate::diagnostics::{DUPLICATE_ID, INVALID_EXTENSION};
+use crate::level2::convert::{is_document, is_element};
+use crate::level2::ext::traits::{Axes, Batch, Namespaced};
+use crate::level2::node_impl::{AttributeMap, Extension, RefNode, WeakRefNode};
+use crate::level2::trait_impls::{is_child_allowed, is_document_id_attribute};
+use crate::level2::traits::{Element, Node, NodeType};
+use crate::shared::error::{Error, Result};
+use std::collections::HashMap;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The mutations recorded by a batch, see [`Batch::batch`](../trait.Batch.html#tymethod.batch).
+/// Each method records a single mutation, with the same behavior as the DOM method of the same
+/// name, to be applied once the closure returns.
+///
+#[derive(Debug, Default)]
+pub struct Transaction {
+    operations: Vec<Operation>,
+}
+
+///
+/// A single reason a batch was rejected.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// The index of the mutation that failed, in the order recorded, or `None` if the violation
+    /// was found validating the combined result.
+    pub operation: Option<usize>,
+    /// The node the mutation was applied to, or the node found to be invalid.
+    pub node: RefNode,
+    /// The error returned by the mutation, or describing the violation.
+    pub error: Error,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+enum Operation {
+    InsertBefore {
+        parent: RefNode,
+        new_child: RefNode,
+        ref_child: Option<RefNode>,
+    },
+    RemoveChild {
+        parent: RefNode,
+        old_child: RefNode,
+    },
+    SetAttribute {
+        element: RefNode,
+        name: String,
+        value: String,
+    },
+    SetAttributeNs {
+        element: RefNode,
+        namespace_uri: String,
+        qualified_name: String,
+        value: String,
+    },
+    RemoveAttribute {
+        element: RefNode,
+        name: String,
+    },
+    SetNodeValue {
+        node: RefNode,
+        value: String,
+    },
+}
+
+#[derive(Debug)]
+enum Undo {
+    Position {
+        node: RefNode,
+        parent: Option<RefNode>,
+        next_sibling: Option<RefNode>,
+    },
+    Attributes {
+        element: RefNode,
+        attributes: AttributeMap,
+        namespaces: HashMap<Option<String>, String>,
+    },
+    Value {
+        node: RefNode,
+        value: Option<String>,
+    },
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Transaction {
+    ///
+    /// Record appending `new_child` to the children of `parent`.
+    ///
+    pub fn append_child(&mut self, parent: &RefNode, new_child: RefNode) {
+        self.insert_before(parent, new_child, None)
+    }
+
+    ///
+    /// Record inserting `new_child` into the children of `parent` before `ref_child`, or at the
+    /// end if `ref_child` is `None`.
+    ///
+    pub fn insert_before(
+        &mut self,
+        parent: &RefNode,
+        new_child: RefNode,
+        ref_child: Option<RefNode>,
+    ) {
+        self.operations.push(Operation::InsertBefore {
+            parent: parent.clone(),
+            new_child,
+            ref_child,
+        })
+    }
+
+    ///
+    /// Record removing `old_child` from the children of `parent`.
+    ///
+    pub fn remove_child(&mut self, parent: &RefNode, old_child: RefNode) {
+        self.operations.push(Operation::RemoveChild {
+            parent: parent.clone(),
+            old_child,
+        })
+    }
+
+    ///
+    /// Record setting the attribute `name` of `element` to `value`.
+    ///
+    pub fn set_attribute(&mut self, element: &RefNode, name: &str, value: &str) {
+        self.operations.push(Operation::SetAttribute {
+            element: element.clone(),
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    ///
+    /// Record setting the attribute `qualified_name`, in the namespace `namespace_uri`, of
+    /// `element` to `value`.
+    ///
+    pub fn set_attribute_ns(
+        &mut self,
+        element: &RefNode,
+        namespace_uri: &str,
+        qualified_name: &str,
+        value: &str,
+    ) {
+        self.operations.push(Operation::SetAttributeNs {
+            element: element.clone(),
+            namespace_uri: namespace_uri.to_string(),
+            qualified_name: qualified_name.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    ///
+    /// Record removing the attribute `name` from `element`.
+    ///
+    pub fn remove_attribute(&mut self, element: &RefNode, name: &str) {
+        self.operations.push(Operation::RemoveAttribute {
+            element: element.clone(),
+            name: name.to_string(),
+        })
+    }
+
+    ///
+    /// Record setting the value of `node` to `value`.
+    ///
+    pub fn set_node_value(&mut self, node: &RefNode, value: &str) {
+        self.operations.push(Operation::SetNodeValue {
+            node: node.clone(),
+            value: value.to_string(),
+        })
+    }
+
+    ///
+    /// Returns the number of mutations recorded.
+    ///
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    ///
+    /// Returns `true` if no mutations have been recorded, else `false`.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Operation {
+    fn target(&self) -> &RefNode {
+        match self {
+            Operation::InsertBefore { parent, .. } => parent,
+            Operation::RemoveChild { parent, .. } => parent,
+            Operation::SetAttribute { element, .. } => element,
+            Operation::SetAttributeNs { element, .. } => element,
+            Operation::RemoveAttribute { element, .. } => element,
+            Operation::SetNodeValue { node, .. } => node,
+        }
+    }
+
+    fn undo(&self) -> Undo {
+        match self {
+            Operation::InsertBefore { new_child, .. } => position_of(new_child),
+            Operation::RemoveChild { old_child, .. } => position_of(old_child),
+            Operation::SetAttribute { element, .. }
+            | Operation::SetAttributeNs { element, .. }
+            | Operation::RemoveAttribute { element, .. } => {
+                let (attributes, namespaces) = attributes_of(element);
+                Undo::Attributes {
+                    element: element.clone(),
+                    attributes,
+                    namespaces,
+                }
+            }
+            Operation::SetNodeValue { node, .. } => Undo::Value {
+                node: node.clone(),
+                value: node.node_value(),
+            },
+        }
+    }
+
+    fn apply(&self) -> Result<()> {
+        match self {
+            Operation::InsertBefore {
+                parent,
+                new_child,
+                ref_child,
+            } => parent
+                .clone()
+                .insert_before(new_child.clone(), ref_child.clone())
+                .map(|_| ()),
+            Operation::RemoveChild { parent, old_child } => {
+                parent.clone().remove_child(old_child.clone()).map(|_| ())
+            }
+            Operation::SetAttribute {
+                element,
+                name,
+                value,
+            } => element.clone().set_attribute(name, value),
+            Operation::SetAttributeNs {
+                element,
+                namespace_uri,
+                qualified_name,
+                value,
+            } => element
+                .clone()
+                .set_attribute_ns(namespace_uri, qualified_name, value),
+            Operation::RemoveAttribute { element, name } => element.clone().remove_attribute(name),
+            Operation::SetNodeValue { node, value } => node.clone().set_node_value(value),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Undo {
+    fn revert(self) {
+        match self {
+            Undo::Position {
+                node,
+                parent,
+                next_sibling,
+            } => match parent {
+                Some(mut parent) => {
+                    let _safe_to_ignore = parent.insert_before(node, next_sibling);
+                }
+                None => {
+                    if let Some(mut parent) = node.parent_node() {
+                        let _safe_to_ignore = parent.remove_child(node);
+                    }
+                }
+            },
+            Undo::Attributes {
+                element,
+                attributes,
+                namespaces,
+            } => {
+                //
+                // Restored directly, the attribute nodes still name `element` as their owner.
+                //
+                let mut mut_element = element.borrow_mut();
+                if let Extension::Element {
+                    i_attributes,
+                    i_namespaces,
+                } = &mut mut_element.i_extension
+                {
+                    *i_attributes = attributes;
+                    *i_namespaces = namespaces;
+                }
+            }
+            Undo::Value { mut node, value } => {
+                let _safe_to_ignore = match value {
+                    None => node.unset_node_value(),
+                    Some(value) => node.set_node_value(&value),
+                };
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Batch for RefNode {
+    fn batch<F>(&mut self, mutations: F) -> std::result::Result<(), Vec<Violation>>
+    where
+        F: FnOnce(&mut Transaction),
+    {
+        if !is_document(self) {
+            return Err(vec![Violation {
+                operation: None,
+                node: self.clone(),
+                error: Error::InvalidState,
+            }]);
+        }
+
+        let mut transaction = Transaction::default();
+        mutations(&mut transaction);
+        let id_map = id_map(self);
+
+        let mut violations: Vec<Violation> = Vec::new();
+        let mut undo_log: Vec<Undo> = Vec::with_capacity(transaction.len());
+        for (index, operation) in transaction.operations.iter().enumerate() {
+            let target = operation.target();
+            let result = if target != self && target.owner_document().as_ref() != Some(self) {
+                warn!("batch: the mutation does not apply to this document");
+                Err(Error::WrongDocument)
+            } else {
+                let undo = operation.undo();
+                operation.apply().map(|_| undo_log.push(undo))
+            };
+            if let Err(error) = result {
+                violations.push(Violation {
+                    operation: Some(index),
+                    node: target.clone(),
+                    error,
+                });
+            }
+        }
+
+        if violations.is_empty() {
+            violations = validate(self);
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            //
+            // The ID map is emptied while reverting, so that no intermediate state is rejected,
+            // and then restored.
+            //
+            set_id_map(self, HashMap::new());
+            for undo in undo_log.into_iter().rev() {
+                undo.revert();
+            }
+            set_id_map(self, id_map);
+            Err(violations)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn position_of(node: &RefNode) -> Undo {
+    Undo::Position {
+        node: node.clone(),
+        parent: node.parent_node(),
+        next_sibling: node.next_sibling(),
+    }
+}
+
+fn attributes_of(element: &RefNode) -> (AttributeMap, HashMap<Option<String>, String>) {
+    let ref_element = element.borrow();
+    if let Extension::Element {
+        i_attributes,
+        i_namespaces,
+    } = &ref_element.i_extension
+    {
+        (i_attributes.clone(), i_namespaces.clone())
+    } else {
+        warn!("{}", INVALID_EXTENSION);
+        Default::default()
+    }
+}
+
+fn id_map(document_node: &RefNode) -> HashMap<String, WeakRefNode> {
+    let ref_document = document_node.borrow();
+    if let Extension::Document { i_id_map, .. } = &ref_document.i_extension {
+        i_id_map.clone()
+    } else {
+        warn!("{}", INVALID_EXTENSION);
+        HashMap::new()
+    }
+}
+
+fn set_id_map(document_node: &RefNode, id_map: HashMap<String, WeakRefNode>) {
+    let mut mut_document = document_node.borrow_mut();
+    if let Extension::Document { i_id_map, .. } = &mut mut_document.i_extension {
+        *i_id_map = id_map;
+    } else {
+        warn!("{}", INVALID_EXTENSION);
+    }
+}
+
+fn validate(document_node: &RefNode) -> Vec<Violation> {
+    let mut violations: Vec<Violation> = Vec::new();
+    let mut ids: HashMap<String, RefNode> = HashMap::new();
+    let mut violation = |node: &RefNode, error: Error| {
+        violations.push(Violation {
+            operation: None,
+            node: node.clone(),
+            error,
+        })
+    };
+
+    for node in document_node.descendant_or_self() {
+        //
+        // Hierarchy: every child is allowed by its parent, and a document has one element.
+        //
+        let child_nodes = node.child_nodes();
+        if child_nodes
+            .iter()
+            .any(|child| !is_child_allowed(&node, child))
+            || (is_document(&node) && child_nodes.iter().filter(|c| is_element(c)).count() > 1)
+        {
+            violation(&node, Error::HierarchyRequest);
+        }
+
+        if node.node_type() != NodeType::Element {
+            continue;
+        }
+
+        //
+        // Namespaces: a prefix is only used with the namespace it is mapped to in scope.
+        //
+        let mut names = vec![node.node_name()];
+        for (name, attribute) in node.attributes() {
+            if !name.is_namespace_attribute() {
+                names.push(name.clone());
+            }
+
+            //
+            // IDs: no two elements have the same ID.
+            //
+            if is_document_id_attribute(document_node, &node.node_name(), &name) {
+                if let Some(value) = attribute.node_value() {
+                    match ids.get(&value) {
+                        Some(other) if other != &node => {
+                            warn!("{}", DUPLICATE_ID);
+                            violation(&node, Error::Syntax);
+                        }
+                        _ => {
+                            let _safe_to_ignore = ids.insert(value, node.clone());
+                        }
+                    }
+                }
+            }
+        }
+        for name in names {
+            if let (Some(prefix), Some(namespace_uri)) = (name.prefix(), name.namespace_uri()) {
+                match node.resolve_namespace(Some(prefix)) {
+                    Some(mapped_uri) if &mapped_uri != namespace_uri => {
+                        warn!(
+                            "batch: the prefix {:?} is mapped to {:?}, not {:?}",
+                            prefix, mapped_uri, namespace_uri
+                        );
+                        violation(&node, Error::Namespace);
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+    violations
+}
@@ -0,0 +1,345 @@
+/*!
+Provides [`CanonicalElement`](struct.CanonicalElement.html), a model of an element tree in which
+each element and attribute is identified only by its namespace URI and local name, for pipelines
+that compare, or hash, documents and must ignore the choice of prefixes.
+
+Converting a node to the model, with [`from_node`](struct.CanonicalElement.html#method.from_node),
+drops all prefixes and namespace declarations; in addition adjacent text and CDATA sections are
+merged into a single text node, and entity references are replaced by their content. The
+document type is dropped. Two trees that differ only in these respects are equal, and have the
+same hash.
+
+When the model is written, with [`to_document`](struct.CanonicalElement.html#method.to_document)
+or its `Display` implementation, prefixes are recreated deterministically; each namespace is
+given a prefix `ns1`, `ns2`, ..., in the order it is first used in document order, and all are
+declared on the root element. Names without a namespace have no prefix, and the `xml` prefix is
+used for the XML namespace.
+
+# Example
+
+```rust
+use xml_dom::level2::ext::canonical::CanonicalElement;
+use xml_dom::parser::read_xml;
+
+let first = read_xml(r#"<a:doc xmlns:a="urn:x"><a:item a:n="1"/></a:doc>"#).unwrap();
+let second = read_xml(r#"<doc xmlns="urn:x" xmlns:b="urn:x"><item b:n="1"></item></doc>"#).unwrap();
+
+let canonical = CanonicalElement::from_node(&first).unwrap();
+assert_eq!(canonical, CanonicalElement::from_node(&second).unwrap());
+assert_eq!(
+    canonical.to_string(),
+    r#"<ns1:doc xmlns:ns1="urn:x"><ns1:item ns1:n="1"></ns1:item></ns1:doc>"#
+);
+```
+
+*/
+
+use crate::diagnostics::{INVALID_NODE_TYPE, NAMESPACE};
+use crate::level2::convert::{as_document, is_document};
+use crate::level2::dom_impl::get_implementation;
+use crate::level2::ext::traits::{Namespaced, TextContent};
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Element, Node, NodeType, ProcessingInstruction};
+use crate::shared::error::{Error, Result};
+use crate::shared::name::Name;
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI, XML_NS_ATTRIBUTE, XML_NS_URI};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The name of an element, or attribute, without its prefix.
+///
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExpandedName {
+    /// The namespace of the name, if any.
+    pub namespace_uri: Option<String>,
+    /// The local part of the name.
+    pub local_name: String,
+}
+
+///
+/// An element in the canonical model, see the [module documentation](index.html).
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CanonicalElement {
+    /// The name of the element.
+    pub name: ExpandedName,
+    /// The attributes of the element, other than namespace declarations, in order of name.
+    pub attributes: BTreeMap<ExpandedName, String>,
+    /// The children of the element.
+    pub children: Vec<CanonicalNode>,
+}
+
+///
+/// A child of an element in the canonical model.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CanonicalNode {
+    /// A child element.
+    Element(CanonicalElement),
+    /// The text of adjacent text nodes, CDATA sections, and entity references; never empty.
+    Text(String),
+    /// A comment, with its data.
+    Comment(String),
+    /// A processing instruction, with its target and data.
+    ProcessingInstruction(String, Option<String>),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ExpandedName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.namespace_uri {
+            None => write!(f, "{}", self.local_name),
+            Some(namespace_uri) => write!(f, "{{{}}}{}", namespace_uri, self.local_name),
+        }
+    }
+}
+
+impl ExpandedName {
+    ///
+    /// Construct a new name from its namespace, if any, and local name.
+    ///
+    pub fn new(namespace_uri: Option<&str>, local_name: &str) -> Self {
+        Self {
+            namespace_uri: namespace_uri.map(String::from),
+            local_name: local_name.to_string(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for CanonicalElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let document_node = self.to_document().map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", document_node)
+    }
+}
+
+impl CanonicalElement {
+    ///
+    /// Convert `node`, an element or a document, to the canonical model; a document is
+    /// converted from its document element. It is an error, `Error::InvalidState`, if `node` is
+    /// neither, or is a document without an element, and `Error::Namespace` if a prefix is used
+    /// that is neither declared nor the prefix of a name created with a namespace.
+    ///
+    pub fn from_node(node: &RefNode) -> Result<Self> {
+        let element = if is_document(node) {
+            as_document(node)?.document_element()
+        } else {
+            Some(node.clone())
+        };
+        match element {
+            Some(element) if element.node_type() == NodeType::Element => from_element(&element),
+            _ => {
+                warn!("{}", INVALID_NODE_TYPE);
+                Err(Error::InvalidState)
+            }
+        }
+    }
+    ///
+    /// Returns the prefix that will be written for each namespace used in this tree, in the
+    /// order they are first used; the XML namespace is not included as it always has the `xml`
+    /// prefix.
+    ///
+    pub fn prefixes(&self) -> Vec<(String, String)> {
+        let mut prefixes: Vec<(String, String)> = Vec::new();
+        let mut stack = vec![self];
+        while let Some(element) = stack.pop() {
+            let names = std::iter::once(&element.name).chain(element.attributes.keys());
+            for namespace_uri in names.filter_map(|name| name.namespace_uri.as_ref()) {
+                if namespace_uri != XML_NS_URI
+                    && !prefixes.iter().any(|(uri, _)| uri == namespace_uri)
+                {
+                    let prefix = format!("ns{}", prefixes.len() + 1);
+                    prefixes.push((namespace_uri.clone(), prefix));
+                }
+            }
+            stack.extend(
+                element
+                    .children
+                    .iter()
+                    .rev()
+                    .filter_map(|child| match child {
+                        CanonicalNode::Element(element) => Some(element),
+                        _ => None,
+                    }),
+            );
+        }
+        prefixes
+    }
+    ///
+    /// Create a new document with this element, and its descendants, as the document element,
+    /// using the prefixes returned by [`prefixes`](#method.prefixes).
+    ///
+    pub fn to_document(&self) -> Result<RefNode> {
+        let prefixes = self.prefixes();
+        let document_node = get_implementation().create_document(
+            self.name.namespace_uri.as_deref(),
+            Some(&qualified_name(&self.name, &prefixes)),
+            None,
+        )?;
+        let mut root_node = as_document(&document_node)?.document_element().unwrap();
+        for (namespace_uri, prefix) in &prefixes {
+            root_node.set_attribute_ns(
+                XMLNS_NS_URI,
+                &format!("{}:{}", XMLNS_NS_ATTRIBUTE, prefix),
+                namespace_uri,
+            )?;
+        }
+        fill_element(&document_node, &mut root_node, self, &prefixes)?;
+        Ok(document_node)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn from_element(element: &RefNode) -> Result<CanonicalElement> {
+    let mut attributes: BTreeMap<ExpandedName, String> = BTreeMap::new();
+    for (name, attribute) in element.attributes() {
+        if name.is_namespace_declaration() {
+            continue;
+        }
+        let _safe_to_ignore = attributes.insert(
+            expanded_name(element, &name, false)?,
+            attribute.text_content().unwrap_or_default(),
+        );
+    }
+    let mut children: Vec<CanonicalNode> = Vec::new();
+    add_children(element, &mut children)?;
+    Ok(CanonicalElement {
+        name: expanded_name(element, &element.node_name(), true)?,
+        attributes,
+        children,
+    })
+}
+
+fn add_children(parent: &RefNode, children: &mut Vec<CanonicalNode>) -> Result<()> {
+    for child_node in parent.child_nodes() {
+        match child_node.node_type() {
+            NodeType::Element => children.push(CanonicalNode::Element(from_element(&child_node)?)),
+            NodeType::Text | NodeType::CData => {
+                add_text(children, &child_node.node_value().unwrap_or_default())
+            }
+            NodeType::EntityReference if child_node.has_child_nodes() => {
+                add_children(&child_node, children)?
+            }
+            NodeType::EntityReference => {
+                add_text(children, &child_node.text_content().unwrap_or_default())
+            }
+            NodeType::Comment => children.push(CanonicalNode::Comment(
+                child_node.node_value().unwrap_or_default(),
+            )),
+            NodeType::ProcessingInstruction => children.push(CanonicalNode::ProcessingInstruction(
+                child_node.node_name().to_string(),
+                child_node.node_value(),
+            )),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn add_text(children: &mut Vec<CanonicalNode>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match children.last_mut() {
+        Some(CanonicalNode::Text(existing)) => existing.push_str(text),
+        _ => children.push(CanonicalNode::Text(text.to_string())),
+    }
+}
+
+///
+/// Returns the namespace, and local name, of `name` as used on `element`; a prefix without a
+/// namespace in the name itself is resolved from the declarations in scope, as is the default
+/// namespace if `name` is the element's own name.
+///
+fn expanded_name(element: &RefNode, name: &Name, is_element_name: bool) -> Result<ExpandedName> {
+    let namespace_uri = match (name.namespace_uri(), name.prefix()) {
+        (Some(namespace_uri), _) => Some(namespace_uri.clone()),
+        (None, None) if is_element_name => element.lookup_namespace_uri(None),
+        (None, None) => None,
+        (None, Some(prefix)) if prefix == XML_NS_ATTRIBUTE => Some(XML_NS_URI.to_string()),
+        (None, Some(prefix)) => match element.lookup_namespace_uri(Some(prefix)) {
+            Some(namespace_uri) => Some(namespace_uri),
+            None => {
+                warn!("{}: '{}'", NAMESPACE, prefix);
+                return Err(Error::Namespace);
+            }
+        },
+    };
+    Ok(ExpandedName {
+        namespace_uri,
+        local_name: name.local_name().clone(),
+    })
+}
+
+fn qualified_name(name: &ExpandedName, prefixes: &[(String, String)]) -> String {
+    match &name.namespace_uri {
+        None => name.local_name.clone(),
+        Some(namespace_uri) if namespace_uri == XML_NS_URI => {
+            format!("{}:{}", XML_NS_ATTRIBUTE, name.local_name)
+        }
+        Some(namespace_uri) => {
+            let prefix = prefixes
+                .iter()
+                .find(|(uri, _)| uri == namespace_uri)
+                .map(|(_, prefix)| prefix.as_str())
+                .unwrap_or_default();
+            format!("{}:{}", prefix, name.local_name)
+        }
+    }
+}
+
+fn fill_element(
+    document_node: &RefNode,
+    element: &mut RefNode,
+    canonical: &CanonicalElement,
+    prefixes: &[(String, String)],
+) -> Result<()> {
+    let document = as_document(document_node)?;
+    for (name, value) in &canonical.attributes {
+        match &name.namespace_uri {
+            None => element.set_attribute(&name.local_name, value)?,
+            Some(namespace_uri) => {
+                element.set_attribute_ns(namespace_uri, &qualified_name(name, prefixes), value)?
+            }
+        }
+    }
+    for child in &canonical.children {
+        let child_node = match child {
+            CanonicalNode::Element(child) => {
+                let qualified_name = qualified_name(&child.name, prefixes);
+                let mut child_node = match &child.name.namespace_uri {
+                    None => document.create_element(&qualified_name)?,
+                    Some(namespace_uri) => {
+                        document.create_element_ns(namespace_uri, &qualified_name)?
+                    }
+                };
+                fill_element(document_node, &mut child_node, child, prefixes)?;
+                child_node
+            }
+            CanonicalNode::Text(text) => document.create_text_node(text),
+            CanonicalNode::Comment(data) => document.create_comment(data),
+            CanonicalNode::ProcessingInstruction(target, data) => {
+                let mut pi_node = document.create_processing_instruction(target, None)?;
+                if let Some(data) = data {
+                    pi_node.set_data(data)?;
+                }
+                pi_node
+            }
+        };
+        let _safe_to_ignore = element.append_child(child_node)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+/*!
+Provides the [`CharacterReference`](struct.CharacterReference.html) structure, recording that a
+character in the data of a text node was written in the parsed input as a numeric character
+reference, such as `&#xA0;` or `&#160;`. These are recorded by the parser when
+[`ParseOptions::set_preserve_character_references`](../../../parser/options/struct.ParseOptions.html#method.set_preserve_character_references)
+is set, and written back in their original form until the node's data is changed; see the
+[`CharacterReferences`](../trait.CharacterReferences.html) trait.
+*/
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A numeric character reference, its offset within the data of a text node, the character it
+/// refers to, and the reference exactly as it was written.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CharacterReference {
+    offset: usize,
+    character: char,
+    reference: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl CharacterReference {
+    #[cfg(feature = "quick_parser")]
+    pub(crate) fn new(offset: usize, character: char, reference: &str) -> Self {
+        Self {
+            offset,
+            character,
+            reference: reference.to_string(),
+        }
+    }
+    ///
+    /// Returns the offset, in bytes, of the character within the node's data.
+    ///
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    ///
+    /// Returns the character the reference refers to.
+    ///
+    pub fn character(&self) -> char {
+        self.character
+    }
+    ///
+    /// Returns the reference as it was written, for example `&#xA0;`.
+    ///
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+    ///
+    /// Returns `true` if the reference was written in hexadecimal, else `false`.
+    ///
+    pub fn is_hexadecimal(&self) -> bool {
+        self.reference.starts_with("&#x")
+    }
+
+    #[cfg(feature = "quick_parser")]
+    pub(crate) fn shifted(mut self, by: usize) -> Self {
+        self.offset += by;
+        self
+    }
+}
@@ -0,0 +1,169 @@
+/*!
+Provides adapters so that the children of a node can be assembled with the standard collection
+traits; [`Children`](struct.Children.html) implements `Extend<RefNode>`, appending to a parent
+node, and [`Fragment`](struct.Fragment.html) implements both `FromIterator<RefNode>` and
+`Extend<RefNode>`, collecting nodes into a `DocumentFragment`.
+
+Neither trait allows an error to be returned, so both adapters hold on to the first error and
+return it when they are finished with; once an error has occurred any further nodes are ignored.
+As with [`append_children`](../../trait.Node.html#tymethod.append_children), the nodes added by
+each call to `extend` are validated before any of them is appended.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::collect::{Children, Fragment};
+
+let document_node = get_implementation()
+    .create_document(None, Some("list"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let make_item = |text: &str| {
+    let mut item_node = document.create_element("item").unwrap();
+    let _ = item_node.append_child(document.create_text_node(text)).unwrap();
+    item_node
+};
+
+let fragment = ["a", "b"].iter().map(|text| make_item(text)).collect::<Fragment>();
+let fragment_node = fragment.into_node().unwrap();
+assert_eq!(fragment_node.child_nodes().len(), 2);
+
+let mut children = Children::new(&mut root_node);
+children.extend(vec![fragment_node, make_item("c")]);
+children.finish().unwrap();
+assert_eq!(
+    root_node.to_string(),
+    "<list><item>a</item><item>b</item><item>c</item></list>"
+);
+```
+
+*/
+
+use crate::diagnostics::INVALID_STATE;
+use crate::level2::convert::as_document;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::Node;
+use crate::shared::error::{Error, Result};
+use std::iter::FromIterator;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Appends the nodes it is extended with to the children of a parent node.
+///
+#[derive(Debug)]
+#[must_use = "an error appending children is only returned by finish"]
+pub struct Children<'a> {
+    parent: &'a mut RefNode,
+    result: Result<()>,
+}
+
+///
+/// A set of nodes collected to be the children of a new `DocumentFragment`, which is created
+/// when the fragment is converted into a node.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fragment {
+    nodes: Vec<RefNode>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Children<'a> {
+    ///
+    /// Construct a new adapter appending children to `parent`.
+    ///
+    pub fn new(parent: &'a mut RefNode) -> Self {
+        Self {
+            parent,
+            result: Ok(()),
+        }
+    }
+    ///
+    /// Returns the first error returned appending children, if any.
+    ///
+    pub fn finish(self) -> Result<()> {
+        self.result
+    }
+}
+
+impl Extend<RefNode> for Children<'_> {
+    fn extend<T: IntoIterator<Item = RefNode>>(&mut self, iter: T) {
+        if self.result.is_ok() {
+            self.result = self.parent.append_children(iter.into_iter().collect());
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl FromIterator<RefNode> for Fragment {
+    fn from_iter<T: IntoIterator<Item = RefNode>>(iter: T) -> Self {
+        Self {
+            nodes: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<RefNode> for Fragment {
+    fn extend<T: IntoIterator<Item = RefNode>>(&mut self, iter: T) {
+        self.nodes.extend(iter)
+    }
+}
+
+impl Fragment {
+    ///
+    /// Construct a new, empty, fragment.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Returns the number of nodes collected.
+    ///
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    ///
+    /// Returns `true` if no nodes have been collected, else `false`.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    ///
+    /// Create a `DocumentFragment`, owned by the document of the first node collected, with the
+    /// nodes collected as its children. It is an error, `Error::InvalidState`, if no nodes have
+    /// been collected, as there is no document to own the fragment; otherwise any error is that
+    /// returned by [`append_children`](../../trait.Node.html#tymethod.append_children).
+    ///
+    pub fn into_node(self) -> Result<RefNode> {
+        let document_node = match self.nodes.first().and_then(|node| node.owner_document()) {
+            None => {
+                warn!("{}", INVALID_STATE);
+                return Err(Error::InvalidState);
+            }
+            Some(document_node) => document_node,
+        };
+        let mut fragment_node = as_document(&document_node)?.create_document_fragment()?;
+        fragment_node.append_children(self.nodes)?;
+        Ok(fragment_node)
+    }
+    ///
+    /// Append the nodes collected to the children of `parent`, without creating a
+    /// `DocumentFragment`; this is not an error if no nodes have been collected.
+    ///
+    pub fn append_to(self, parent: &mut RefNode) -> Result<()> {
+        if self.nodes.is_empty() {
+            Ok(())
+        } else {
+            parent.append_children(self.nodes)
+        }
+    }
+}
@@ -0,0 +1,353 @@
+/*!
+Provides the [`DocumentPosition`](struct.DocumentPosition.html) bitmask returned by
+[`NodeComparison::compare_document_position`](../trait.NodeComparison.html#tymethod.compare_document_position).
+
+The `PartialEq` implementation for `RefNode` compares node identity, the
+[`NodeComparison`](../trait.NodeComparison.html) trait makes the choice between identity and
+structural equality explicit, and adds the ability to compare two nodes by document order.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{DocumentPosition, NodeComparison};
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml("<a><b><c/></b><b><c/></b></a>").unwrap();
+let document = as_document(&document_node).unwrap();
+let b = document.get_elements_by_tag_name("b");
+let c = document.get_elements_by_tag_name("c");
+
+assert!(!b[0].is_same_node(&b[1]));
+assert!(b[0].is_equal_node(&b[1]));
+
+assert_eq!(
+    b[0].compare_document_position(&c[0]),
+    DocumentPosition::CONTAINED_BY | DocumentPosition::FOLLOWING
+);
+assert_eq!(
+    c[1].compare_document_position(&c[0]),
+    DocumentPosition::PRECEDING
+);
+```
+
+*/
+
+use crate::level2::ext::traits::NodeComparison;
+use crate::level2::node_impl::{AttributeMap, Extension, RefNode};
+use crate::level2::traits::NodeType;
+use crate::shared::name::Name;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::{BitAnd, BitOr};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A bitmask describing the position of a node relative to a reference node, as defined by the
+/// DOM Level 3 `Node.compareDocumentPosition` operation. A node compared with itself returns the
+/// empty mask.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DocumentPosition(u16);
+
+///
+/// The position of a node within its parent; attributes, and the document type, are not children
+/// of their parent but are ordered before the children.
+///
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum PositionKey {
+    DocumentType,
+    Attribute(String),
+    Child(usize),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for DocumentPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:#04x}", self.0)
+    }
+}
+
+impl BitOr for DocumentPosition {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for DocumentPosition {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl DocumentPosition {
+    /// The two nodes are not in the same tree.
+    pub const DISCONNECTED: Self = Self(0x01);
+    /// The other node precedes the reference node.
+    pub const PRECEDING: Self = Self(0x02);
+    /// The other node follows the reference node.
+    pub const FOLLOWING: Self = Self(0x04);
+    /// The other node contains the reference node, it is always also `PRECEDING`.
+    pub const CONTAINS: Self = Self(0x08);
+    /// The other node is contained by the reference node, it is always also `FOLLOWING`.
+    pub const CONTAINED_BY: Self = Self(0x10);
+    /// The order of the two nodes is determined by the implementation, not the document.
+    pub const IMPLEMENTATION_SPECIFIC: Self = Self(0x20);
+
+    ///
+    /// Returns the value of the mask, as used by the `DOCUMENT_POSITION_*` constants in the DOM
+    /// specification.
+    ///
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+    ///
+    /// Returns `true` if every flag set in `flags` is also set in this mask.
+    ///
+    pub fn contains(&self, flags: Self) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+    ///
+    /// Returns `true` if no flag is set; that is, the two nodes compared are the same node.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NodeComparison for RefNode {
+    fn is_same_node(&self, other: &Self::NodeRef) -> bool {
+        self == other
+    }
+
+    fn is_equal_node(&self, other: &Self::NodeRef) -> bool {
+        equal_nodes(self, other)
+    }
+
+    fn compare_document_position(&self, other: &Self::NodeRef) -> DocumentPosition {
+        if self == other {
+            return DocumentPosition::default();
+        }
+        let reference_path = document_path(self);
+        let other_path = document_path(other);
+        if reference_path[0].0 != other_path[0].0 {
+            // consistent for as long as both trees are alive.
+            let order = if other.as_ptr() < self.as_ptr() {
+                DocumentPosition::PRECEDING
+            } else {
+                DocumentPosition::FOLLOWING
+            };
+            return DocumentPosition::DISCONNECTED
+                | DocumentPosition::IMPLEMENTATION_SPECIFIC
+                | order;
+        }
+        let common = reference_path
+            .iter()
+            .zip(other_path.iter())
+            .take_while(|((lhs, _), (rhs, _))| lhs == rhs)
+            .count();
+        if common == other_path.len() {
+            DocumentPosition::CONTAINS | DocumentPosition::PRECEDING
+        } else if common == reference_path.len() {
+            DocumentPosition::CONTAINED_BY | DocumentPosition::FOLLOWING
+        } else {
+            let reference_key = &reference_path[common].1;
+            let other_key = &other_path[common].1;
+            let order = match other_key.cmp(reference_key) {
+                Ordering::Less => DocumentPosition::PRECEDING,
+                _ => DocumentPosition::FOLLOWING,
+            };
+            match (reference_key, other_key) {
+                (Some(PositionKey::Attribute(_)), Some(PositionKey::Attribute(_))) => {
+                    DocumentPosition::IMPLEMENTATION_SPECIFIC | order
+                }
+                _ => order,
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The nodes from the root of the tree containing `node` down to `node`, each with its position
+/// within the previous node; the root has no position.
+///
+fn document_path(node: &RefNode) -> Vec<(RefNode, Option<PositionKey>)> {
+    let mut path = vec![];
+    let mut current = node.clone();
+    loop {
+        let parent = {
+            let ref_current = current.borrow();
+            match &ref_current.i_extension {
+                Extension::Attribute {
+                    i_owner_element, ..
+                } => i_owner_element.as_ref(),
+                _ => ref_current.i_parent_node.as_ref(),
+            }
+            .and_then(|parent| parent.clone().upgrade())
+        };
+        match parent {
+            None => {
+                path.push((current, None));
+                break;
+            }
+            Some(parent) => {
+                let key = position_key(&parent, &current);
+                path.push((current, Some(key)));
+                current = parent;
+            }
+        }
+    }
+    path.reverse();
+    path
+}
+
+fn position_key(parent: &RefNode, node: &RefNode) -> PositionKey {
+    let ref_node = node.borrow();
+    match ref_node.i_node_type {
+        NodeType::Attribute => PositionKey::Attribute(ref_node.i_name.to_string()),
+        NodeType::DocumentType => PositionKey::DocumentType,
+        _ => PositionKey::Child(
+            parent
+                .borrow()
+                .i_child_nodes
+                .iter()
+                .position(|child| child == node)
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+///
+/// Structural equality as defined by `Node.isEqualNode` in DOM Level 3, the document type of a
+/// document is compared as if it were a child.
+///
+fn equal_nodes(lhs: &RefNode, rhs: &RefNode) -> bool {
+    if lhs == rhs {
+        return true;
+    }
+    let ref_lhs = lhs.borrow();
+    let ref_rhs = rhs.borrow();
+    if ref_lhs.i_node_type != ref_rhs.i_node_type
+        || ref_lhs.i_name != ref_rhs.i_name
+        || ref_lhs.i_value != ref_rhs.i_value
+        || ref_lhs.i_child_nodes.len() != ref_rhs.i_child_nodes.len()
+    {
+        return false;
+    }
+    let equal_extensions = match (&ref_lhs.i_extension, &ref_rhs.i_extension) {
+        (
+            Extension::Document {
+                i_document_type: lhs_document_type,
+                ..
+            },
+            Extension::Document {
+                i_document_type: rhs_document_type,
+                ..
+            },
+        ) => match (lhs_document_type, rhs_document_type) {
+            (None, None) => true,
+            (Some(lhs), Some(rhs)) => equal_nodes(lhs, rhs),
+            _ => false,
+        },
+        (
+            Extension::DocumentType {
+                i_entities: lhs_entities,
+                i_notations: lhs_notations,
+                i_public_id: lhs_public_id,
+                i_system_id: lhs_system_id,
+                i_internal_subset: lhs_internal_subset,
+            },
+            Extension::DocumentType {
+                i_entities: rhs_entities,
+                i_notations: rhs_notations,
+                i_public_id: rhs_public_id,
+                i_system_id: rhs_system_id,
+                i_internal_subset: rhs_internal_subset,
+            },
+        ) => {
+            lhs_public_id == rhs_public_id
+                && lhs_system_id == rhs_system_id
+                && lhs_internal_subset == rhs_internal_subset
+                && equal_maps(lhs_entities, rhs_entities)
+                && equal_maps(lhs_notations, rhs_notations)
+        }
+        (
+            Extension::Element {
+                i_attributes: lhs_attributes,
+                ..
+            },
+            Extension::Element {
+                i_attributes: rhs_attributes,
+                ..
+            },
+        ) => equal_attributes(lhs_attributes, rhs_attributes),
+        (
+            Extension::Entity {
+                i_public_id: lhs_public_id,
+                i_system_id: lhs_system_id,
+                i_notation_name: lhs_notation_name,
+            },
+            Extension::Entity {
+                i_public_id: rhs_public_id,
+                i_system_id: rhs_system_id,
+                i_notation_name: rhs_notation_name,
+            },
+        ) => {
+            lhs_public_id == rhs_public_id
+                && lhs_system_id == rhs_system_id
+                && lhs_notation_name == rhs_notation_name
+        }
+        (
+            Extension::Notation {
+                i_public_id: lhs_public_id,
+                i_system_id: lhs_system_id,
+            },
+            Extension::Notation {
+                i_public_id: rhs_public_id,
+                i_system_id: rhs_system_id,
+            },
+        ) => lhs_public_id == rhs_public_id && lhs_system_id == rhs_system_id,
+        _ => true,
+    };
+    equal_extensions
+        && ref_lhs
+            .i_child_nodes
+            .iter()
+            .zip(ref_rhs.i_child_nodes.iter())
+            .all(|(lhs, rhs)| equal_nodes(lhs, rhs))
+}
+
+fn equal_maps(lhs: &HashMap<Name, RefNode>, rhs: &HashMap<Name, RefNode>) -> bool {
+    lhs.len() == rhs.len()
+        && lhs.iter().all(|(name, lhs)| match rhs.get(name) {
+            Some(rhs) => equal_nodes(lhs, rhs),
+            None => false,
+        })
+}
+
+// attributes are equal regardless of their order.
+fn equal_attributes(lhs: &AttributeMap, rhs: &AttributeMap) -> bool {
+    lhs.len() == rhs.len()
+        && lhs.iter().all(|(name, lhs)| match rhs.get(name) {
+            Some(rhs) => equal_nodes(lhs, rhs),
+            None => false,
+        })
+}
@@ -3,11 +3,12 @@ Provides safe `RefNode` conversion functions, see [`crate::level2::convert`](../
 more details.
 */
 
+use crate::diagnostics::INVALID_NODE_TYPE;
 use crate::level2::ext::namespaced::MutNamespaced;
 use crate::level2::ext::traits::*;
 use crate::level2::node_impl::*;
 use crate::level2::traits::NodeType;
-use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE};
+use crate::shared::error::{Error, Result};
 
 use crate::{make_is_as_functions, make_ref_type};
 
@@ -48,7 +49,7 @@ pub(crate) fn as_element_namespaced_mut(ref_node: &mut RefNode) -> Result<MutRef
     if ref_node.borrow().i_node_type == NodeType::Element {
         Ok(ref_node as MutRefNamespaced<'_>)
     } else {
-        warn!("{}", MSG_INVALID_NODE_TYPE);
+        warn!("{}", INVALID_NODE_TYPE);
         Err(Error::InvalidState)
     }
 }
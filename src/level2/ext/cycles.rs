@@ -0,0 +1,165 @@
+/*!
+Provides a defensive check for strong reference cycles within a DOM tree, this module is only
+available with the `cycle_detection` feature.
+
+Within the tree, parents hold strong references to their children, elements to their attributes,
+and document types to their entities and notations; links back up the tree (`parent_node`,
+`owner_document`, and `owner_element`) are weak. A bug that introduces a strong reference from a
+node back to one of its ancestors creates a cycle that will never be dropped. The
+[`find_reference_cycles`](fn.find_reference_cycles.html) function walks all strong references
+from a node and reports any such cycles, it is intended for use in tests and soak runs rather
+than in production code.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::cycles::find_reference_cycles;
+
+let document_node = get_implementation()
+    .create_document(None, Some("root"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let _ = root_node.append_child(document.create_element("child").unwrap()).unwrap();
+
+assert!(find_reference_cycles(&document_node).is_empty());
+```
+
+*/
+
+use crate::level2::node_impl::{Extension, RefNode};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A strong reference cycle; the node at path `from` holds a strong reference to the node at path
+/// `to`, which is one of its own ancestors (or itself).
+///
+/// Paths are written from the node the search started at, with one segment for each strong
+/// reference followed; children are written as `name[index]`, where `index` is the one-based
+/// position among the siblings with the same name as in an XPath step, attributes as `@name`,
+/// and the document type, entities, and notations by name.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReferenceCycle {
+    /// The path to the node holding the strong reference.
+    pub from: String,
+    /// The path to the ancestor node being referenced.
+    pub to: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Walk all the strong references reachable from `node`, returning any that form a cycle. An
+/// empty result means that the tree below `node` will be dropped once `node` itself is.
+///
+pub fn find_reference_cycles(node: &RefNode) -> Vec<ReferenceCycle> {
+    let mut search = CycleSearch::default();
+    search.visit(node, format!("/{}", node.borrow().i_name));
+    search.cycles
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct CycleSearch {
+    on_path: Vec<(usize, String)>,
+    visited: HashSet<usize>,
+    cycles: Vec<ReferenceCycle>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ReferenceCycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} -> {}", self.from, self.to)
+    }
+}
+
+impl CycleSearch {
+    fn visit(&mut self, node: &RefNode, path: String) {
+        let identity = node_identity(node);
+        if !self.visited.insert(identity) {
+            return;
+        }
+        self.on_path.push((identity, path.clone()));
+        for (segment, reference) in strong_references(node) {
+            let reference_identity = node_identity(&reference);
+            let reference_path = format!("{}/{}", path, segment);
+            match self
+                .on_path
+                .iter()
+                .find(|(on_path, _)| *on_path == reference_identity)
+            {
+                Some((_, ancestor_path)) => self.cycles.push(ReferenceCycle {
+                    from: reference_path,
+                    to: ancestor_path.clone(),
+                }),
+                None => self.visit(&reference, reference_path),
+            }
+        }
+        let _safe_to_ignore = self.on_path.pop();
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn node_identity(node: &RefNode) -> usize {
+    node.as_ptr() as usize
+}
+
+///
+/// All the strong references held by `node`, each with the path segment used to reach it.
+///
+fn strong_references(node: &RefNode) -> Vec<(String, RefNode)> {
+    let ref_node = node.borrow();
+    let mut references: Vec<(String, RefNode)> = Vec::new();
+    match &ref_node.i_extension {
+        Extension::Document {
+            i_document_type: Some(document_type),
+            ..
+        } => references.push((
+            document_type.borrow().i_name.to_string(),
+            document_type.clone(),
+        )),
+        Extension::DocumentType {
+            i_entities,
+            i_notations,
+            ..
+        } => references.extend(
+            i_entities
+                .iter()
+                .chain(i_notations.iter())
+                .map(|(name, node)| (name.to_string(), node.clone())),
+        ),
+        Extension::Element { i_attributes, .. } => references.extend(
+            i_attributes
+                .iter()
+                .map(|(name, node)| (format!("@{}", name), node.clone())),
+        ),
+        _ => {}
+    }
+    let mut same_name_counts: HashMap<String, usize> = HashMap::new();
+    for child in &ref_node.i_child_nodes {
+        let name = child.borrow().i_name.to_string();
+        let index = same_name_counts.entry(name.clone()).or_default();
+        *index += 1;
+        references.push((format!("{}[{}]", name, index), child.clone()));
+    }
+    references
+}
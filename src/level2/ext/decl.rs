@@ -17,7 +17,8 @@ use std::str::FromStr;
 ///
 /// Captures the supported version of the XML specification itself, as used in `XmlDecl`.
 ///
-#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum XmlVersion {
     /// Version 1.0 [`<https://www.w3.org/TR/xml>`]
     V10,
@@ -43,7 +44,8 @@ pub enum XmlVersion {
 /// SDDecl       ::=  S 'standalone' Eq (("'" ('yes' | 'no') "'") | ('"' ('yes' | 'no') '"'))
 /// ```
 ///
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct XmlDecl {
     version: XmlVersion,
     encoding: Option<String>,
@@ -170,7 +172,7 @@ impl XmlDecl {
     /// Return the `version` asserted in this declaration.
     ///
     pub fn version(&self) -> XmlVersion {
-        self.version.clone()
+        self.version
     }
     ///
     /// Return the `encoding` value in this declaration.
@@ -0,0 +1,502 @@
+/*!
+Provides the [`diff`](fn.diff.html) function, computing the edits that turn one tree into another,
+and the [`apply`](fn.apply.html) function, making those edits to a tree; for example to report the
+changes made to a document in a test, to record a document's history as a series of changes, or
+to bring a copy of a document up to date.
+
+Each [`DomEdit`](enum.DomEdit.html) identifies the node it changes by its
+[`NodePath`](type.NodePath.html), the index of each child followed from the root of the tree, as
+the tree is when that edit is applied; so the edits must be applied in order, and to a tree equal
+to the one from which they were computed, although it need not be the same tree. Inserted and
+replacement nodes are held by the edit, and are imported into the document being changed when
+applied.
+
+The children of two nodes are compared by first finding the longest common sequence of equal
+children, which are left unchanged. The remaining children between these are paired in order:
+where a pair has the same type and name the differences within it are found, and where it does
+not the child is replaced; any others are removed or inserted. Attributes are compared by name,
+and the values of text, CDATA sections, comments, and processing instructions are changed in
+place.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::diff::{apply, diff};
+
+fn list(items: &[&str]) -> RefNode {
+    let document_node = get_implementation()
+        .create_document(None, Some("list"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for item in items {
+        let mut item_node = root_node
+            .append_child(document.create_element("item").unwrap())
+            .unwrap();
+        let _ = item_node.append_child(document.create_text_node(item)).unwrap();
+    }
+    document_node
+}
+
+let mut before = list(&["one", "two", "three"]);
+let after = list(&["one", "2", "three", "four"]);
+
+let edits = diff(&before, &after);
+assert_eq!(
+    edits.iter().map(|edit| edit.to_string()).collect::<Vec<String>>(),
+    vec![
+        "change text /0/1/0 to \"2\"",
+        "insert <item>four</item> at /0/3",
+    ]
+);
+
+apply(&edits, &mut before).unwrap();
+assert_eq!(before.to_string(), after.to_string());
+```
+
+*/
+
+use crate::level2::convert::as_attribute_mut;
+use crate::level2::ext::traits::{NodeComparison, TextContent};
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::import_into;
+use crate::level2::traits::{Element, Node, NodeType};
+use crate::shared::error::{Error, Result};
+use crate::shared::name::Name;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The index of each child followed, from the root of a tree, to reach a node; the empty path is
+/// the root itself.
+///
+pub type NodePath = Vec<usize>;
+
+///
+/// A single change to a tree, see [`apply`](fn.apply.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum DomEdit {
+    /// Insert a copy of `node` as the child at `index` of the node at `parent`.
+    InsertNode {
+        /// The path of the parent node.
+        parent: NodePath,
+        /// The index of the new child, it may be equal to the number of existing children.
+        index: usize,
+        /// The node to insert, from the tree the edits were computed against.
+        node: RefNode,
+    },
+    /// Remove the node at `path`, and its descendants.
+    RemoveNode {
+        /// The path of the node to remove.
+        path: NodePath,
+    },
+    /// Replace the node at `path` with a copy of `node`.
+    ReplaceNode {
+        /// The path of the node to replace.
+        path: NodePath,
+        /// The replacement node, from the tree the edits were computed against.
+        node: RefNode,
+    },
+    /// Set the value of the attribute `name` of the element at `path`, adding it if necessary.
+    SetAttribute {
+        /// The path of the element.
+        path: NodePath,
+        /// The name of the attribute.
+        name: Name,
+        /// The new value of the attribute.
+        value: String,
+    },
+    /// Remove the attribute `name` of the element at `path`.
+    RemoveAttribute {
+        /// The path of the element.
+        path: NodePath,
+        /// The name of the attribute.
+        name: Name,
+    },
+    /// Change the value of the text, CDATA section, comment, or processing instruction at `path`.
+    ChangeText {
+        /// The path of the node.
+        path: NodePath,
+        /// The new value, or data of a processing instruction.
+        value: String,
+    },
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The alignment of the children of two nodes.
+///
+#[derive(Debug)]
+enum Step {
+    Keep,
+    Compare(RefNode, RefNode),
+    Replace(RefNode),
+    Remove,
+    Insert(RefNode),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the edits that, applied in order to `from`, or to a tree equal to it, make it equal to
+/// `to`; the result is empty if the trees are already equal.
+///
+pub fn diff(from: &RefNode, to: &RefNode) -> Vec<DomEdit> {
+    let mut edits = Vec::new();
+    if is_same_kind(from, to) {
+        diff_nodes(from, to, &mut Vec::new(), &mut edits);
+    } else {
+        edits.push(DomEdit::ReplaceNode {
+            path: Vec::new(),
+            node: to.clone(),
+        });
+    }
+    edits
+}
+
+///
+/// Make each of `edits`, in order, to the tree rooted at `root`; if `root` itself is replaced it
+/// is updated to refer to its replacement. This returns `Error::NotFound` if the path of an edit
+/// does not identify a node, or the node to remove or replace has no parent, or any error
+/// returned by the change itself; in either case the edits preceding it have already been made.
+///
+pub fn apply(edits: &[DomEdit], root: &mut RefNode) -> Result<()> {
+    for edit in edits {
+        apply_edit(edit, root)?;
+    }
+    Ok(())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for DomEdit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            DomEdit::InsertNode {
+                parent,
+                index,
+                node,
+            } => {
+                let mut path = parent.clone();
+                path.push(*index);
+                write!(f, "insert {} at {}", node, path_string(&path))
+            }
+            DomEdit::RemoveNode { path } => write!(f, "remove {}", path_string(path)),
+            DomEdit::ReplaceNode { path, node } => {
+                write!(f, "replace {} with {}", path_string(path), node)
+            }
+            DomEdit::SetAttribute { path, name, value } => {
+                write!(f, "set {}/@{} to {:?}", path_string(path), name, value)
+            }
+            DomEdit::RemoveAttribute { path, name } => {
+                write!(f, "remove {}/@{}", path_string(path), name)
+            }
+            DomEdit::ChangeText { path, value } => {
+                write!(f, "change text {} to {:?}", path_string(path), value)
+            }
+        }
+    }
+}
+
+impl DomEdit {
+    ///
+    /// Returns the path of the node this edit changes; for an insertion, the path of the parent.
+    ///
+    pub fn path(&self) -> &NodePath {
+        match self {
+            DomEdit::InsertNode { parent, .. } => parent,
+            DomEdit::RemoveNode { path }
+            | DomEdit::ReplaceNode { path, .. }
+            | DomEdit::SetAttribute { path, .. }
+            | DomEdit::RemoveAttribute { path, .. }
+            | DomEdit::ChangeText { path, .. } => path,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn path_string(path: &[usize]) -> String {
+    if path.is_empty() {
+        "/".to_string()
+    } else {
+        path.iter().map(|index| format!("/{}", index)).collect()
+    }
+}
+
+///
+/// Returns `true` if `to` may be reached from `from` by changing its value, attributes, and
+/// children, rather than replacing it.
+///
+fn is_same_kind(from: &RefNode, to: &RefNode) -> bool {
+    from.node_type() == to.node_type()
+        && from.node_name() == to.node_name()
+        && (from.node_type() != NodeType::EntityReference || from.is_equal_node(to))
+        && (from.node_type() != NodeType::DocumentType || from.is_equal_node(to))
+}
+
+fn node_value(node: &RefNode) -> Option<String> {
+    if node.node_type() == NodeType::Attribute {
+        node.text_content()
+    } else {
+        node.node_value()
+    }
+}
+
+fn diff_nodes(from: &RefNode, to: &RefNode, path: &mut NodePath, edits: &mut Vec<DomEdit>) {
+    match from.node_type() {
+        NodeType::Text
+        | NodeType::CData
+        | NodeType::Comment
+        | NodeType::ProcessingInstruction
+        | NodeType::Attribute => {
+            let value = node_value(to);
+            if node_value(from) != value {
+                edits.push(DomEdit::ChangeText {
+                    path: path.clone(),
+                    value: value.unwrap_or_default(),
+                });
+            }
+        }
+        NodeType::Element => {
+            diff_attributes(from, to, path, edits);
+            diff_children(from, to, path, edits);
+        }
+        NodeType::Document | NodeType::DocumentFragment => diff_children(from, to, path, edits),
+        _ => {}
+    }
+}
+
+fn diff_attributes(from: &RefNode, to: &RefNode, path: &NodePath, edits: &mut Vec<DomEdit>) {
+    let from_attributes = from.attributes();
+    let to_attributes = to.attributes();
+    for name in from_attributes.keys() {
+        if to_attributes.get(&name).is_none() {
+            edits.push(DomEdit::RemoveAttribute {
+                path: path.clone(),
+                name,
+            });
+        }
+    }
+    for (name, attribute) in to_attributes.iter() {
+        let value = attribute.text_content().unwrap_or_default();
+        let unchanged = from_attributes
+            .get(&name)
+            .is_some_and(|existing| existing.text_content().unwrap_or_default() == value);
+        if !unchanged {
+            edits.push(DomEdit::SetAttribute {
+                path: path.clone(),
+                name,
+                value,
+            });
+        }
+    }
+}
+
+fn diff_children(from: &RefNode, to: &RefNode, path: &mut NodePath, edits: &mut Vec<DomEdit>) {
+    let from_children = from.child_nodes();
+    let to_children = to.child_nodes();
+    let mut index = 0;
+    for step in align(&from_children, &to_children) {
+        match step {
+            Step::Keep => index += 1,
+            Step::Compare(from_child, to_child) => {
+                path.push(index);
+                diff_nodes(&from_child, &to_child, path, edits);
+                let _safe_to_ignore = path.pop();
+                index += 1;
+            }
+            Step::Replace(node) => {
+                let mut child_path = path.clone();
+                child_path.push(index);
+                edits.push(DomEdit::ReplaceNode {
+                    path: child_path,
+                    node,
+                });
+                index += 1;
+            }
+            Step::Remove => {
+                let mut child_path = path.clone();
+                child_path.push(index);
+                edits.push(DomEdit::RemoveNode { path: child_path });
+            }
+            Step::Insert(node) => {
+                edits.push(DomEdit::InsertNode {
+                    parent: path.clone(),
+                    index,
+                    node,
+                });
+                index += 1;
+            }
+        }
+    }
+}
+
+///
+/// Align the children `from` with `to`, the unchanged children are those in the longest common
+/// subsequence of equal children; those between are paired in order.
+///
+fn align(from: &[RefNode], to: &[RefNode]) -> Vec<Step> {
+    let equal = |from_index: usize, to_index: usize| from[from_index].is_equal_node(&to[to_index]);
+
+    // the common prefix and suffix are found first, as most edits leave most children alone.
+    let mut prefix = 0;
+    while prefix < from.len() && prefix < to.len() && equal(prefix, prefix) {
+        prefix += 1;
+    }
+    let mut suffix = 0;
+    while suffix < from.len() - prefix
+        && suffix < to.len() - prefix
+        && equal(from.len() - suffix - 1, to.len() - suffix - 1)
+    {
+        suffix += 1;
+    }
+    let from_middle = &from[prefix..from.len() - suffix];
+    let to_middle = &to[prefix..to.len() - suffix];
+
+    // the lengths of the longest common subsequences of the remaining children, comparing the
+    // serialized form before the, more expensive, test for equality.
+    let from_text: Vec<String> = from_middle.iter().map(|node| node.to_string()).collect();
+    let to_text: Vec<String> = to_middle.iter().map(|node| node.to_string()).collect();
+    let is_match = |i: usize, j: usize| {
+        from_text[i] == to_text[j] && from_middle[i].is_equal_node(&to_middle[j])
+    };
+    let (rows, columns) = (from_middle.len(), to_middle.len());
+    let mut lengths = vec![vec![0_usize; columns + 1]; rows + 1];
+    for i in (0..rows).rev() {
+        for j in (0..columns).rev() {
+            lengths[i][j] = if is_match(i, j) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut steps: Vec<Step> = (0..prefix).map(|_| Step::Keep).collect();
+    let (mut i, mut j) = (0, 0);
+    let (mut removed, mut inserted): (Vec<RefNode>, Vec<RefNode>) = (Vec::new(), Vec::new());
+    while i < rows || j < columns {
+        if i < rows && j < columns && is_match(i, j) {
+            pair_unmatched(&mut steps, &mut removed, &mut inserted);
+            steps.push(Step::Keep);
+            i += 1;
+            j += 1;
+        } else if j == columns || (i < rows && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            removed.push(from_middle[i].clone());
+            i += 1;
+        } else {
+            inserted.push(to_middle[j].clone());
+            j += 1;
+        }
+    }
+    pair_unmatched(&mut steps, &mut removed, &mut inserted);
+    steps.extend((0..suffix).map(|_| Step::Keep));
+    steps
+}
+
+///
+/// Pair the children removed, and inserted, between two unchanged children in order.
+///
+fn pair_unmatched(steps: &mut Vec<Step>, removed: &mut Vec<RefNode>, inserted: &mut Vec<RefNode>) {
+    let paired = removed.len().min(inserted.len());
+    for (from, to) in removed.drain(..paired).zip(inserted.drain(..paired)) {
+        if is_same_kind(&from, &to) {
+            steps.push(Step::Compare(from, to));
+        } else {
+            steps.push(Step::Replace(to));
+        }
+    }
+    steps.extend(removed.drain(..).map(|_| Step::Remove));
+    steps.extend(inserted.drain(..).map(Step::Insert));
+}
+
+fn find_node(root: &RefNode, path: &[usize]) -> Result<RefNode> {
+    let mut node = root.clone();
+    for index in path {
+        let child_node = node.child_nodes().get(*index).cloned();
+        node = child_node.ok_or(Error::NotFound)?;
+    }
+    Ok(node)
+}
+
+fn apply_edit(edit: &DomEdit, root: &mut RefNode) -> Result<()> {
+    match edit {
+        DomEdit::InsertNode {
+            parent,
+            index,
+            node,
+        } => {
+            let mut parent_node = find_node(root, parent)?;
+            let new_node = import_into(root, node)?;
+            let children = parent_node.child_nodes();
+            if *index > children.len() {
+                return Err(Error::NotFound);
+            }
+            let _safe_to_ignore =
+                parent_node.insert_before(new_node, children.get(*index).cloned())?;
+        }
+        DomEdit::RemoveNode { path } => {
+            let old_node = find_node(root, path)?;
+            let mut parent_node = old_node.parent_node().ok_or(Error::NotFound)?;
+            let _safe_to_ignore = parent_node.remove_child(old_node)?;
+        }
+        DomEdit::ReplaceNode { path, node } => {
+            let old_node = find_node(root, path)?;
+            let new_node = import_into(root, node)?;
+            match old_node.parent_node() {
+                Some(mut parent_node) => {
+                    let _safe_to_ignore = parent_node.replace_child(new_node.clone(), old_node)?;
+                }
+                // only the root of a detached tree, or fragment, has no parent.
+                None if path.is_empty() => {}
+                None => return Err(Error::NotFound),
+            }
+            if path.is_empty() {
+                *root = new_node;
+            }
+        }
+        DomEdit::SetAttribute { path, name, value } => {
+            let mut element = find_node(root, path)?;
+            match name.namespace_uri() {
+                None => element.set_attribute(&name.to_string(), value)?,
+                Some(namespace_uri) => {
+                    element.set_attribute_ns(namespace_uri, &name.to_string(), value)?
+                }
+            }
+        }
+        DomEdit::RemoveAttribute { path, name } => {
+            let mut element = find_node(root, path)?;
+            match name.namespace_uri() {
+                None => element.remove_attribute(&name.to_string())?,
+                Some(namespace_uri) => {
+                    element.remove_attribute_ns(namespace_uri, name.local_name())?
+                }
+            }
+        }
+        DomEdit::ChangeText { path, value } => {
+            let mut node = find_node(root, path)?;
+            if node.node_type() == NodeType::Attribute {
+                as_attribute_mut(&mut node)?.set_value(value)?;
+            } else {
+                node.set_node_value(value)?;
+            }
+        }
+    }
+    Ok(())
+}
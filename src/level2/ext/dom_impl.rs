@@ -2,7 +2,7 @@
 This module implements extended capabilities but not specified by the DOM Core.
 */
 
-use crate::level2::dom_impl::Implementation;
+use crate::level2::dom_impl::this_implementation;
 use crate::level2::ext::traits::DOMImplementation;
 use crate::level2::node_impl::{NodeImpl, RefNode};
 use crate::shared::error::Result;
@@ -13,8 +13,6 @@ use std::str::FromStr;
 // Public Functions
 // ------------------------------------------------------------------------------------------------
 
-const THIS_IMPLEMENTATION: &'static dyn DOMImplementation<NodeRef = RefNode> = &Implementation {};
-
 ///
 /// Return a reference to an instance of this `DOMImplementation` implementation.
 ///
@@ -34,7 +32,7 @@ const THIS_IMPLEMENTATION: &'static dyn DOMImplementation<NodeRef = RefNode> = &
 /// ```
 ///
 pub fn get_implementation_ext() -> &'static dyn DOMImplementation<NodeRef = RefNode> {
-    THIS_IMPLEMENTATION as &'static dyn DOMImplementation<NodeRef = RefNode>
+    this_implementation()
 }
 
 ///
@@ -73,6 +71,24 @@ pub fn create_entity(
     Ok(RefNode::new(node_impl))
 }
 
+///
+/// Create an [`Attribute`](../trait.Attribute.html) whose value was defaulted, usually from a
+/// DTD declaration, rather than explicitly given in the document; the resulting attribute will
+/// return `false` from `specified` until its value is modified.
+///
+/// Rather than add a non-standard member to the [`Document`](../trait.Document.html) trait
+/// this function takes a `Document` as the first parameter.
+///
+pub fn create_defaulted_attribute(
+    owner_document: RefNode,
+    name: &str,
+    value: &str,
+) -> Result<RefNode> {
+    let name = Name::from_str(name)?;
+    let node_impl = NodeImpl::new_defaulted_attribute(owner_document.downgrade(), name, value);
+    Ok(RefNode::new(node_impl))
+}
+
 ///
 /// Required to create instances of the [`Notation`](../trait.Notation.html) Extended interface.
 ///
@@ -0,0 +1,1473 @@
+/*!
+This module provides support types for the [`DtdDeclarations`](../trait.DtdDeclarations.html)
+trait, a structured form of the declarations in the internal subset of a document type.
+
+The internal subset is kept by the document type as the text captured by the parser; the
+[`parse_internal_subset`](fn.parse_internal_subset.html) function parses that text into a list of
+[`DtdDeclaration`](enum.DtdDeclaration.html) values, element declarations with their content
+model, attribute list declarations, entity and notation declarations, as well as any parameter
+entity references, processing instructions, and comments between them. Each declaration
+implements `Display`, writing it in a normalized form, so that a list of declarations may be
+changed and written back as the internal subset.
+
+The [`DtdValidation`](../trait.DtdValidation.html) trait uses these declarations to validate a
+document, reporting each element, or attribute, that does not match them as a
+[`DtdViolation`](struct.DtdViolation.html).
+
+# Example
+
+```rust
+use xml_dom::level2::ext::dtd::{
+    parse_internal_subset, ContentSpec, DefaultDecl, DtdDeclaration, Occurrence,
+};
+
+let declarations = parse_internal_subset(
+    r#"<!ELEMENT note (to, from, body?)>
+<!ATTLIST note   priority (low|high)   "low">"#,
+).unwrap();
+assert_eq!(declarations.len(), 2);
+
+if let DtdDeclaration::Element(element) = &declarations[0] {
+    assert!(matches!(element.content, ContentSpec::Children(_)));
+}
+if let DtdDeclaration::AttributeList(attribute_list) = &declarations[1] {
+    assert_eq!(attribute_list.attributes[0].default, DefaultDecl::Value("low".to_string()));
+}
+assert_eq!(
+    declarations[1].to_string(),
+    r#"<!ATTLIST note priority (low|high) "low">"#
+);
+```
+
+*/
+
+use crate::diagnostics::{HIERARCHY_REQUEST, INVALID_EXTENSION, INVALID_NODE_TYPE, WRONG_DOCUMENT};
+use crate::level2::convert::{as_document, is_document, is_document_type, is_element};
+use crate::level2::ext::traits::{
+    Axes, DocumentTypeExt, DtdDeclarations, DtdValidation, TextContent,
+};
+use crate::level2::node_impl::{Extension, NodeImpl, RefNode};
+use crate::level2::trait_impls::check_not_frozen;
+use crate::level2::traits::{DocumentType, NodeType};
+use crate::shared::error::{Error, Result};
+use crate::shared::name::Name;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A single item in a DTD.
+///
+/// ```ebnf
+/// intSubset  ::= (markupdecl | DeclSep)*
+/// markupdecl ::= elementdecl | AttlistDecl | EntityDecl | NotationDecl | PI | Comment
+/// DeclSep    ::= PEReference | S
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DtdDeclaration {
+    /// An element type declaration.
+    Element(ElementDecl),
+    /// An attribute list declaration.
+    AttributeList(AttributeListDecl),
+    /// A general entity declaration.
+    Entity(EntityDecl),
+    /// A parameter entity declaration, `<!ENTITY % name ...>`.
+    ParameterEntity(EntityDecl),
+    /// A notation declaration.
+    Notation(NotationDecl),
+    /// A reference to a parameter entity, the name without the `%` and `;`.
+    ParameterEntityReference(String),
+    /// A processing instruction, the target followed by any data.
+    ProcessingInstruction(String),
+    /// A comment.
+    Comment(String),
+}
+
+///
+/// An element type declaration.
+///
+/// ```ebnf
+/// elementdecl ::= '<!ELEMENT' S Name S contentspec S? '>'
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElementDecl {
+    /// The name of the element type.
+    pub name: String,
+    /// The content allowed in elements of this type.
+    pub content: ContentSpec,
+}
+
+///
+/// The content allowed by an element type declaration.
+///
+/// ```ebnf
+/// contentspec ::= 'EMPTY' | 'ANY' | Mixed | children
+/// Mixed       ::= '(' S? '#PCDATA' (S? '|' S? Name)* S? ')*' | '(' S? '#PCDATA' S? ')'
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContentSpec {
+    /// The element has no content.
+    Empty,
+    /// The element may contain any content.
+    Any,
+    /// Character data, mixed with any of the named child elements.
+    Mixed(Vec<String>),
+    /// Element content, the child elements described by a content model; the particle is a
+    /// choice or a sequence.
+    Children(ContentParticle),
+}
+
+///
+/// A content particle in the content model of an element type declaration.
+///
+/// ```ebnf
+/// children ::= (choice | seq) ('?' | '*' | '+')?
+/// cp       ::= (Name | choice | seq) ('?' | '*' | '+')?
+/// choice   ::= '(' S? cp ( S? '|' S? cp )+ S? ')'
+/// seq      ::= '(' S? cp ( S? ',' S? cp )* S? ')'
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentParticle {
+    /// The name, choice, or sequence matched.
+    pub particle: Particle,
+    /// The number of times the particle may occur.
+    pub occurrence: Occurrence,
+}
+
+///
+/// The content of a [`ContentParticle`](struct.ContentParticle.html).
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Particle {
+    /// A child element with this name.
+    Name(String),
+    /// Exactly one of the particles.
+    Choice(Vec<ContentParticle>),
+    /// Each of the particles, in order.
+    Sequence(Vec<ContentParticle>),
+}
+
+///
+/// The number of times a content particle, or the names in mixed content, may occur.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Occurrence {
+    /// Exactly once, no suffix.
+    #[default]
+    Once,
+    /// Zero or one times, `?`.
+    Optional,
+    /// Zero or more times, `*`.
+    ZeroOrMore,
+    /// One or more times, `+`.
+    OneOrMore,
+}
+
+///
+/// An attribute list declaration.
+///
+/// ```ebnf
+/// AttlistDecl ::= '<!ATTLIST' S Name AttDef* S? '>'
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeListDecl {
+    /// The name of the element type the attributes are declared for.
+    pub element_name: String,
+    /// The attribute definitions, in the order they were declared.
+    pub attributes: Vec<AttributeDef>,
+}
+
+///
+/// The definition of a single attribute in an attribute list declaration.
+///
+/// ```ebnf
+/// AttDef ::= S Name S AttType S DefaultDecl
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeDef {
+    /// The name of the attribute.
+    pub name: String,
+    /// The type of the attribute.
+    pub attribute_type: AttributeType,
+    /// Whether the attribute is required, and its default value.
+    pub default: DefaultDecl,
+}
+
+///
+/// The type of an attribute.
+///
+/// ```ebnf
+/// AttType        ::= StringType | TokenizedType | EnumeratedType
+/// TokenizedType  ::= 'ID' | 'IDREF' | 'IDREFS' | 'ENTITY' | 'ENTITIES' | 'NMTOKEN' | 'NMTOKENS'
+/// EnumeratedType ::= NotationType | Enumeration
+/// NotationType   ::= 'NOTATION' S '(' S? Name (S? '|' S? Name)* S? ')'
+/// Enumeration    ::= '(' S? Nmtoken (S? '|' S? Nmtoken)* S? ')'
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttributeType {
+    /// `CDATA`, any string.
+    CData,
+    /// `ID`, a name unique within the document.
+    Id,
+    /// `IDREF`, the ID of an element in the document.
+    IdRef,
+    /// `IDREFS`, a space separated list of IDs.
+    IdRefs,
+    /// `ENTITY`, the name of an unparsed entity.
+    Entity,
+    /// `ENTITIES`, a space separated list of unparsed entity names.
+    Entities,
+    /// `NMTOKEN`, a name token.
+    NmToken,
+    /// `NMTOKENS`, a space separated list of name tokens.
+    NmTokens,
+    /// `NOTATION`, one of the named notations.
+    Notation(Vec<String>),
+    /// One of the listed name tokens.
+    Enumeration(Vec<String>),
+}
+
+///
+/// Whether an attribute is required, and its default value.
+///
+/// ```ebnf
+/// DefaultDecl ::= '#REQUIRED' | '#IMPLIED' | (('#FIXED' S)? AttValue)
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DefaultDecl {
+    /// The attribute must always be provided.
+    Required,
+    /// No default value is provided.
+    Implied,
+    /// The attribute must always have this value.
+    Fixed(String),
+    /// The value of the attribute, if it is not provided.
+    Value(String),
+}
+
+///
+/// An entity declaration, either internal (with a `value`) or external (with a `system_id`).
+///
+/// ```ebnf
+/// GEDecl     ::= '<!ENTITY' S Name S EntityDef S? '>'
+/// PEDecl     ::= '<!ENTITY' S '%' S Name S PEDef S? '>'
+/// EntityDef  ::= EntityValue | (ExternalID NDataDecl?)
+/// PEDef      ::= EntityValue | ExternalID
+/// NDataDecl  ::= S 'NDATA' S Name
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityDecl {
+    /// The name of the entity.
+    pub name: String,
+    /// The replacement text of an internal entity.
+    pub value: Option<String>,
+    /// The public identifier of an external entity.
+    pub public_id: Option<String>,
+    /// The system identifier of an external entity.
+    pub system_id: Option<String>,
+    /// The notation name of an unparsed entity.
+    pub notation_name: Option<String>,
+}
+
+///
+/// A notation declaration.
+///
+/// ```ebnf
+/// NotationDecl ::= '<!NOTATION' S Name S (ExternalID | PublicID) S? '>'
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotationDecl {
+    /// The name of the notation.
+    pub name: String,
+    /// The public identifier of the notation.
+    pub public_id: Option<String>,
+    /// The system identifier of the notation.
+    pub system_id: Option<String>,
+}
+
+///
+/// A single validity problem found by
+/// [`validate_against_dtd`](../trait.DtdValidation.html#tymethod.validate_against_dtd).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct DtdViolation {
+    /// The element found to be invalid; for an attribute value, or an undeclared attribute, this
+    /// is the attribute itself.
+    pub node: RefNode,
+    /// The problem found.
+    pub kind: DtdViolationKind,
+}
+
+///
+/// The kinds of problem reported by
+/// [`validate_against_dtd`](../trait.DtdValidation.html#tymethod.validate_against_dtd).
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DtdViolationKind {
+    /// The name of the document element is not the name of the document type, which is given.
+    DocumentElementType(String),
+    /// There is no element type declaration for the element.
+    UndeclaredElement,
+    /// The content of the element does not match its declared content, which is given.
+    InvalidContent(String),
+    /// There is no declaration for the attribute on its element.
+    UndeclaredAttribute(String),
+    /// The element does not have the named attribute, which is declared `#REQUIRED`.
+    MissingAttribute(String),
+    /// The value of the attribute is not one of its enumerated values, or not its `#FIXED` value.
+    InvalidAttributeValue(String),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Parse the declarations in the text of an internal subset, in order; it is an error,
+/// `Error::Syntax`, if the text is not a sequence of declarations, processing instructions,
+/// comments, and parameter entity references, separated by white space. Parameter entity
+/// references are not expanded.
+///
+pub fn parse_internal_subset(text: &str) -> Result<Vec<DtdDeclaration>> {
+    let mut reader = Reader::new(text);
+    let mut declarations = Vec::new();
+    loop {
+        reader.skip_space();
+        if reader.at_end() {
+            return Ok(declarations);
+        }
+        declarations.push(reader.declaration()?);
+    }
+}
+
+///
+/// Write `declarations` as the text of an internal subset, each on its own line.
+///
+pub fn format_internal_subset(declarations: &[DtdDeclaration]) -> String {
+    declarations
+        .iter()
+        .map(|declaration| format!("\n{}", declaration))
+        .chain(std::iter::once("\n".to_string()))
+        .collect()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The element type and attribute list declarations of a document type, by element name; used to
+/// validate elements one at a time, both by `validate_against_dtd` and while parsing.
+///
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DtdRules {
+    doc_type_name: String,
+    elements: HashMap<String, ContentSpec>,
+    attribute_lists: HashMap<String, Vec<AttributeDef>>,
+}
+
+///
+/// A cursor over the text of a DTD.
+///
+#[derive(Debug)]
+struct Reader<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for DtdDeclaration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            DtdDeclaration::Element(decl) => write!(f, "{}", decl),
+            DtdDeclaration::AttributeList(decl) => write!(f, "{}", decl),
+            DtdDeclaration::Entity(decl) => fmt_entity(decl, false, f),
+            DtdDeclaration::ParameterEntity(decl) => fmt_entity(decl, true, f),
+            DtdDeclaration::Notation(decl) => write!(f, "{}", decl),
+            DtdDeclaration::ParameterEntityReference(name) => write!(f, "%{};", name),
+            DtdDeclaration::ProcessingInstruction(content) => write!(f, "<?{}?>", content),
+            DtdDeclaration::Comment(content) => write!(f, "<!--{}-->", content),
+        }
+    }
+}
+
+impl FromStr for DtdDeclaration {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut reader = Reader::new(s.trim());
+        let declaration = reader.declaration()?;
+        if reader.at_end() {
+            Ok(declaration)
+        } else {
+            warn!("Unexpected text after declaration: {:?}", reader.rest());
+            Err(Error::Syntax)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DtdDeclarations for RefNode {
+    fn declarations(&self) -> Result<Vec<DtdDeclaration>> {
+        if !is_document_type(self) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        match self.internal_subset() {
+            None => Ok(Vec::new()),
+            Some(internal_subset) => parse_internal_subset(&internal_subset),
+        }
+    }
+
+    fn set_declarations(&mut self, declarations: &[DtdDeclaration]) -> Result<()> {
+        if !is_document_type(self) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        check_not_frozen(self)?;
+        let internal_subset = if declarations.is_empty() {
+            None
+        } else {
+            Some(format_internal_subset(declarations))
+        };
+        replace_internal_subset(self, internal_subset)
+    }
+}
+
+impl DocumentTypeExt for RefNode {
+    fn add_entity(&mut self, entity: RefNode) -> Result<Option<RefNode>> {
+        add_declared_node(self, entity, NodeType::Entity)
+    }
+
+    fn add_notation(&mut self, notation: RefNode) -> Result<Option<RefNode>> {
+        add_declared_node(self, notation, NodeType::Notation)
+    }
+
+    fn set_internal_subset(&mut self, internal_subset: Option<&str>) -> Result<()> {
+        if !is_document_type(self) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        check_not_frozen(self)?;
+        replace_internal_subset(self, internal_subset.map(String::from))
+    }
+}
+
+impl DtdValidation for RefNode {
+    fn validate_against_dtd(&self) -> Result<Vec<DtdViolation>> {
+        if !is_document(self) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let document = as_document(self)?;
+        let doc_type = match document.doc_type() {
+            None => {
+                warn!("validate_against_dtd: the document has no document type");
+                return Err(Error::InvalidState);
+            }
+            Some(doc_type) => doc_type,
+        };
+        let rules = DtdRules::new(&doc_type)?;
+
+        let mut violations: Vec<DtdViolation> = Vec::new();
+        let root_node = match document.document_element() {
+            None => return Ok(violations),
+            Some(root_node) => root_node,
+        };
+        rules.check_document_element(&root_node, &mut violations);
+        for element in root_node.descendant_or_self().filter(is_element) {
+            rules.check_element(&element, &mut violations);
+            rules.check_attributes(&element, &mut violations);
+        }
+        Ok(violations)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for DtdViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} ({})", self.kind, self.node.node_name())
+    }
+}
+
+impl Display for DtdViolationKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::DocumentElementType(name) => {
+                write!(f, "the document element is not of type {:?}", name)
+            }
+            Self::UndeclaredElement => write!(f, "the element type is not declared"),
+            Self::InvalidContent(content) => {
+                write!(f, "the content of the element does not match {}", content)
+            }
+            Self::UndeclaredAttribute(name) => {
+                write!(f, "the attribute {:?} is not declared", name)
+            }
+            Self::MissingAttribute(name) => {
+                write!(f, "the required attribute {:?} is missing", name)
+            }
+            Self::InvalidAttributeValue(name) => {
+                write!(f, "the value of the attribute {:?} is not allowed", name)
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ElementDecl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "<!ELEMENT {} {}>", self.name, self.content)
+    }
+}
+
+impl Display for ContentSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ContentSpec::Empty => write!(f, "EMPTY"),
+            ContentSpec::Any => write!(f, "ANY"),
+            ContentSpec::Mixed(names) if names.is_empty() => write!(f, "(#PCDATA)"),
+            ContentSpec::Mixed(names) => write!(f, "(#PCDATA|{})*", names.join("|")),
+            ContentSpec::Children(particle) => write!(f, "{}", particle),
+        }
+    }
+}
+
+impl Display for ContentParticle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.particle {
+            Particle::Name(name) => write!(f, "{}", name)?,
+            Particle::Choice(particles) => fmt_group(particles, "|", f)?,
+            Particle::Sequence(particles) => fmt_group(particles, ",", f)?,
+        }
+        write!(f, "{}", self.occurrence)
+    }
+}
+
+impl Display for Occurrence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Occurrence::Once => Ok(()),
+            Occurrence::Optional => write!(f, "?"),
+            Occurrence::ZeroOrMore => write!(f, "*"),
+            Occurrence::OneOrMore => write!(f, "+"),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for AttributeListDecl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "<!ATTLIST {}", self.element_name)?;
+        for attribute in &self.attributes {
+            write!(f, " {}", attribute)?;
+        }
+        write!(f, ">")
+    }
+}
+
+impl Display for AttributeDef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {}", self.name, self.attribute_type, self.default)
+    }
+}
+
+impl Display for AttributeType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            AttributeType::CData => write!(f, "CDATA"),
+            AttributeType::Id => write!(f, "ID"),
+            AttributeType::IdRef => write!(f, "IDREF"),
+            AttributeType::IdRefs => write!(f, "IDREFS"),
+            AttributeType::Entity => write!(f, "ENTITY"),
+            AttributeType::Entities => write!(f, "ENTITIES"),
+            AttributeType::NmToken => write!(f, "NMTOKEN"),
+            AttributeType::NmTokens => write!(f, "NMTOKENS"),
+            AttributeType::Notation(names) => write!(f, "NOTATION ({})", names.join("|")),
+            AttributeType::Enumeration(tokens) => write!(f, "({})", tokens.join("|")),
+        }
+    }
+}
+
+impl Display for DefaultDecl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            DefaultDecl::Required => write!(f, "#REQUIRED"),
+            DefaultDecl::Implied => write!(f, "#IMPLIED"),
+            DefaultDecl::Fixed(value) => write!(f, "#FIXED {}", quote(value)),
+            DefaultDecl::Value(value) => write!(f, "{}", quote(value)),
+        }
+    }
+}
+
+impl DefaultDecl {
+    ///
+    /// Returns the value given to the attribute when it is not provided, if any.
+    ///
+    pub fn value(&self) -> Option<&String> {
+        match self {
+            DefaultDecl::Fixed(value) | DefaultDecl::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for NotationDecl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "<!NOTATION {}", self.name)?;
+        fmt_external_id(self.public_id.as_ref(), self.system_id.as_ref(), f)?;
+        write!(f, ">")
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DtdRules {
+    ///
+    /// Collect the declarations of `doc_type`, the first declaration of an element type, or of
+    /// an attribute, is the one used.
+    ///
+    pub(crate) fn new(doc_type: &RefNode) -> Result<Self> {
+        let mut rules = Self {
+            doc_type_name: doc_type.node_name().to_string(),
+            ..Default::default()
+        };
+        for declaration in doc_type.declarations()? {
+            match declaration {
+                DtdDeclaration::Element(decl) => {
+                    let _safe_to_ignore = rules.elements.entry(decl.name).or_insert(decl.content);
+                }
+                DtdDeclaration::AttributeList(decl) => {
+                    let attributes = rules.attribute_lists.entry(decl.element_name).or_default();
+                    for attribute in decl.attributes {
+                        if !attributes.iter().any(|known| known.name == attribute.name) {
+                            attributes.push(attribute);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(rules)
+    }
+
+    ///
+    /// Check that the name of the document element, `element`, is the name of the document type.
+    ///
+    pub(crate) fn check_document_element(
+        &self,
+        element: &RefNode,
+        violations: &mut Vec<DtdViolation>,
+    ) {
+        if element.node_name().to_string() != self.doc_type_name {
+            violations.push(DtdViolation {
+                node: element.clone(),
+                kind: DtdViolationKind::DocumentElementType(self.doc_type_name.clone()),
+            });
+        }
+    }
+
+    ///
+    /// Check that `element` is declared, and that its content matches the declaration.
+    ///
+    pub(crate) fn check_element(&self, element: &RefNode, violations: &mut Vec<DtdViolation>) {
+        let kind = match self.elements.get(&element.node_name().to_string()) {
+            None => DtdViolationKind::UndeclaredElement,
+            Some(content) if !is_valid_content(element, content) => {
+                DtdViolationKind::InvalidContent(content.to_string())
+            }
+            Some(_) => return,
+        };
+        violations.push(DtdViolation {
+            node: element.clone(),
+            kind,
+        });
+    }
+
+    ///
+    /// Check that the attributes of `element` are declared, with valid values, and that it has
+    /// each of its required attributes.
+    ///
+    pub(crate) fn check_attributes(&self, element: &RefNode, violations: &mut Vec<DtdViolation>) {
+        let mut violation = |node: &RefNode, kind: DtdViolationKind| {
+            violations.push(DtdViolation {
+                node: node.clone(),
+                kind,
+            })
+        };
+        let declared: &[AttributeDef] = self
+            .attribute_lists
+            .get(&element.node_name().to_string())
+            .map(|attributes| attributes.as_slice())
+            .unwrap_or_default();
+        let attributes = element.attributes();
+        for attribute in attributes.values() {
+            let attribute_name = attribute.node_name().to_string();
+            match declared.iter().find(|decl| decl.name == attribute_name) {
+                None => violation(
+                    &attribute,
+                    DtdViolationKind::UndeclaredAttribute(attribute_name),
+                ),
+                Some(decl) => {
+                    let value = attribute.text_content().unwrap_or_default();
+                    if !is_valid_attribute_value(decl, &value) {
+                        violation(
+                            &attribute,
+                            DtdViolationKind::InvalidAttributeValue(attribute_name),
+                        );
+                    }
+                }
+            }
+        }
+        for decl in declared {
+            if decl.default == DefaultDecl::Required
+                && !attributes.keys().any(|name| name.to_string() == decl.name)
+            {
+                violation(
+                    element,
+                    DtdViolationKind::MissingAttribute(decl.name.clone()),
+                );
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Reader<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, offset: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.offset..]
+    }
+
+    fn at_end(&self) -> bool {
+        self.offset == self.text.len()
+    }
+
+    fn skip_space(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start_matches(is_space).len();
+    }
+
+    ///
+    /// Skip white space, returning `true` if there was any.
+    ///
+    fn eat_space(&mut self) -> bool {
+        let offset = self.offset;
+        self.skip_space();
+        self.offset > offset
+    }
+
+    fn require_space(&mut self) -> Result<()> {
+        if self.eat_space() {
+            Ok(())
+        } else {
+            self.syntax_error("white space")
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.offset += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            self.syntax_error(token)
+        }
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let rest = self.rest();
+        let length = rest.len() - rest.trim_start_matches(is_name_char).len();
+        if length == 0 {
+            return self.syntax_error("a name");
+        }
+        self.offset += length;
+        Ok(rest[..length].to_string())
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return self.syntax_error("a quoted string"),
+        };
+        self.offset += 1;
+        match self.rest().find(quote) {
+            None => self.syntax_error("a closing quote"),
+            Some(end) => {
+                let value = self.rest()[..end].to_string();
+                self.offset += end + 1;
+                Ok(value)
+            }
+        }
+    }
+
+    fn until(&mut self, end: &str) -> Result<String> {
+        match self.rest().find(end) {
+            None => self.syntax_error(end),
+            Some(length) => {
+                let content = self.rest()[..length].to_string();
+                self.offset += length + end.len();
+                Ok(content)
+            }
+        }
+    }
+
+    fn syntax_error<T>(&self, expected: &str) -> Result<T> {
+        warn!(
+            "Expected {} in DTD at offset {}, found {:?}",
+            expected,
+            self.offset,
+            self.rest().chars().take(20).collect::<String>()
+        );
+        Err(Error::Syntax)
+    }
+
+    fn declaration(&mut self) -> Result<DtdDeclaration> {
+        if self.eat("<!--") {
+            Ok(DtdDeclaration::Comment(self.until("-->")?))
+        } else if self.eat("<?") {
+            Ok(DtdDeclaration::ProcessingInstruction(self.until("?>")?))
+        } else if self.eat("%") {
+            let name = self.name()?;
+            self.expect(";")?;
+            Ok(DtdDeclaration::ParameterEntityReference(name))
+        } else if self.eat("<!ELEMENT") {
+            self.element_decl().map(DtdDeclaration::Element)
+        } else if self.eat("<!ATTLIST") {
+            self.attribute_list_decl()
+                .map(DtdDeclaration::AttributeList)
+        } else if self.eat("<!ENTITY") {
+            self.entity_decl()
+        } else if self.eat("<!NOTATION") {
+            self.notation_decl().map(DtdDeclaration::Notation)
+        } else {
+            self.syntax_error("a declaration")
+        }
+    }
+
+    fn element_decl(&mut self) -> Result<ElementDecl> {
+        self.require_space()?;
+        let name = self.name()?;
+        self.require_space()?;
+        let content = if self.eat("EMPTY") {
+            ContentSpec::Empty
+        } else if self.eat("ANY") {
+            ContentSpec::Any
+        } else {
+            self.expect("(")?;
+            self.skip_space();
+            if self.eat("#PCDATA") {
+                self.mixed()?
+            } else {
+                ContentSpec::Children(self.group()?)
+            }
+        };
+        self.end_decl()?;
+        Ok(ElementDecl { name, content })
+    }
+
+    fn mixed(&mut self) -> Result<ContentSpec> {
+        let mut names = Vec::new();
+        loop {
+            self.skip_space();
+            if self.eat(")") {
+                break;
+            }
+            self.expect("|")?;
+            self.skip_space();
+            names.push(self.name()?);
+        }
+        if !self.eat("*") && !names.is_empty() {
+            return self.syntax_error("*");
+        }
+        Ok(ContentSpec::Mixed(names))
+    }
+
+    ///
+    /// Parse a choice or sequence, after its opening `(`, and any occurrence that follows it.
+    ///
+    fn group(&mut self) -> Result<ContentParticle> {
+        let mut particles = vec![self.particle()?];
+        let mut separator = None;
+        loop {
+            self.skip_space();
+            if self.eat(")") {
+                break;
+            }
+            let next = match self.rest().chars().next() {
+                Some(next @ ('|' | ',')) => next,
+                _ => return self.syntax_error("| or , or )"),
+            };
+            if *separator.get_or_insert(next) != next {
+                return self.syntax_error("the same separator throughout a group");
+            }
+            self.offset += 1;
+            self.skip_space();
+            particles.push(self.particle()?);
+        }
+        Ok(ContentParticle {
+            particle: if separator == Some('|') {
+                Particle::Choice(particles)
+            } else {
+                Particle::Sequence(particles)
+            },
+            occurrence: self.occurrence(),
+        })
+    }
+
+    fn particle(&mut self) -> Result<ContentParticle> {
+        self.skip_space();
+        if self.eat("(") {
+            self.skip_space();
+            self.group()
+        } else {
+            Ok(ContentParticle {
+                particle: Particle::Name(self.name()?),
+                occurrence: self.occurrence(),
+            })
+        }
+    }
+
+    fn occurrence(&mut self) -> Occurrence {
+        if self.eat("?") {
+            Occurrence::Optional
+        } else if self.eat("*") {
+            Occurrence::ZeroOrMore
+        } else if self.eat("+") {
+            Occurrence::OneOrMore
+        } else {
+            Occurrence::Once
+        }
+    }
+
+    fn attribute_list_decl(&mut self) -> Result<AttributeListDecl> {
+        self.require_space()?;
+        let element_name = self.name()?;
+        let mut attributes = Vec::new();
+        loop {
+            self.skip_space();
+            if self.eat(">") {
+                break;
+            }
+            let name = self.name()?;
+            self.require_space()?;
+            let attribute_type = self.attribute_type()?;
+            self.require_space()?;
+            let default = if self.eat("#REQUIRED") {
+                DefaultDecl::Required
+            } else if self.eat("#IMPLIED") {
+                DefaultDecl::Implied
+            } else if self.eat("#FIXED") {
+                self.require_space()?;
+                DefaultDecl::Fixed(self.quoted()?)
+            } else {
+                DefaultDecl::Value(self.quoted()?)
+            };
+            attributes.push(AttributeDef {
+                name,
+                attribute_type,
+                default,
+            });
+        }
+        Ok(AttributeListDecl {
+            element_name,
+            attributes,
+        })
+    }
+
+    fn attribute_type(&mut self) -> Result<AttributeType> {
+        if self.eat("(") {
+            return Ok(AttributeType::Enumeration(self.name_list()?));
+        }
+        Ok(match self.name()?.as_str() {
+            "CDATA" => AttributeType::CData,
+            "ID" => AttributeType::Id,
+            "IDREF" => AttributeType::IdRef,
+            "IDREFS" => AttributeType::IdRefs,
+            "ENTITY" => AttributeType::Entity,
+            "ENTITIES" => AttributeType::Entities,
+            "NMTOKEN" => AttributeType::NmToken,
+            "NMTOKENS" => AttributeType::NmTokens,
+            "NOTATION" => {
+                self.require_space()?;
+                self.expect("(")?;
+                AttributeType::Notation(self.name_list()?)
+            }
+            _ => return self.syntax_error("an attribute type"),
+        })
+    }
+
+    ///
+    /// Parse a list of names separated by `|`, after its opening `(`.
+    ///
+    fn name_list(&mut self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        loop {
+            self.skip_space();
+            names.push(self.name()?);
+            self.skip_space();
+            if self.eat(")") {
+                return Ok(names);
+            }
+            self.expect("|")?;
+        }
+    }
+
+    fn entity_decl(&mut self) -> Result<DtdDeclaration> {
+        self.require_space()?;
+        let parameter = self.eat("%");
+        if parameter {
+            self.require_space()?;
+        }
+        let name = self.name()?;
+        self.require_space()?;
+        let mut decl = EntityDecl {
+            name,
+            value: None,
+            public_id: None,
+            system_id: None,
+            notation_name: None,
+        };
+        if matches!(self.rest().chars().next(), Some('"' | '\'')) {
+            decl.value = Some(self.quoted()?);
+        } else {
+            (decl.public_id, decl.system_id) = self.external_id(false)?;
+            let had_space = self.eat_space();
+            if !parameter && had_space && self.eat("NDATA") {
+                self.require_space()?;
+                decl.notation_name = Some(self.name()?);
+            }
+        }
+        self.end_decl()?;
+        Ok(if parameter {
+            DtdDeclaration::ParameterEntity(decl)
+        } else {
+            DtdDeclaration::Entity(decl)
+        })
+    }
+
+    fn notation_decl(&mut self) -> Result<NotationDecl> {
+        self.require_space()?;
+        let name = self.name()?;
+        self.require_space()?;
+        let (public_id, system_id) = self.external_id(true)?;
+        self.end_decl()?;
+        Ok(NotationDecl {
+            name,
+            public_id,
+            system_id,
+        })
+    }
+
+    ///
+    /// Parse `SYSTEM` and a system identifier, or `PUBLIC` and a public identifier followed by a
+    /// system identifier that is only optional if `public_only` is `true`.
+    ///
+    fn external_id(&mut self, public_only: bool) -> Result<(Option<String>, Option<String>)> {
+        if self.eat("SYSTEM") {
+            self.require_space()?;
+            Ok((None, Some(self.quoted()?)))
+        } else if self.eat("PUBLIC") {
+            self.require_space()?;
+            let public_id = self.quoted()?;
+            let offset = self.offset;
+            if self.eat_space() && matches!(self.rest().chars().next(), Some('"' | '\'')) {
+                Ok((Some(public_id), Some(self.quoted()?)))
+            } else if public_only {
+                self.offset = offset;
+                Ok((Some(public_id), None))
+            } else {
+                self.syntax_error("a system identifier")
+            }
+        } else {
+            self.syntax_error("SYSTEM or PUBLIC")
+        }
+    }
+
+    fn end_decl(&mut self) -> Result<()> {
+        self.skip_space();
+        self.expect(">")
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Add `node`, an entity or a notation as given by `node_type`, to the corresponding map of
+/// `doc_type`, returning the node it replaces. The declaration of `node` also replaces that of the
+/// same name in the internal subset, or is added to its end, so that the two remain the same.
+///
+fn add_declared_node(
+    doc_type: &mut RefNode,
+    node: RefNode,
+    node_type: NodeType,
+) -> Result<Option<RefNode>> {
+    if !is_document_type(doc_type) {
+        warn!("{}", INVALID_NODE_TYPE);
+        return Err(Error::InvalidState);
+    }
+    if node.node_type() != node_type {
+        warn!("{}", HIERARCHY_REQUEST);
+        return Err(Error::HierarchyRequest);
+    }
+    check_not_frozen(doc_type)?;
+    if let (Some(document), Some(node_document)) =
+        (doc_type.owner_document(), node.owner_document())
+    {
+        if document != node_document {
+            warn!("{}", WRONG_DOCUMENT);
+            return Err(Error::WrongDocument);
+        }
+    }
+    let name = node.node_name();
+    let new_internal_subset = splice_declaration(
+        &doc_type.internal_subset().unwrap_or_default(),
+        &node_type,
+        &name.to_string(),
+        &node.to_string(),
+    )?;
+    let mut mut_doc_type = doc_type.borrow_mut();
+    match &mut mut_doc_type.i_extension {
+        Extension::DocumentType {
+            i_entities,
+            i_notations,
+            i_internal_subset,
+            ..
+        } => {
+            *i_internal_subset = Some(new_internal_subset);
+            if node_type == NodeType::Entity {
+                Ok(i_entities.insert(name, node))
+            } else {
+                Ok(i_notations.insert(name, node))
+            }
+        }
+        _ => {
+            warn!("{}", INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+}
+
+///
+/// Returns `internal_subset` with the entity, or notation, declaration of `name` replaced by
+/// `declaration`, or with `declaration` added to the end if there is none.
+///
+fn splice_declaration(
+    internal_subset: &str,
+    node_type: &NodeType,
+    name: &str,
+    declaration: &str,
+) -> Result<String> {
+    let mut reader = Reader::new(internal_subset);
+    loop {
+        reader.skip_space();
+        if reader.at_end() {
+            return Ok(format!("{}{}", internal_subset, declaration));
+        }
+        let start = reader.offset;
+        let declares_name = match reader.declaration()? {
+            DtdDeclaration::Entity(decl) => *node_type == NodeType::Entity && decl.name == name,
+            DtdDeclaration::Notation(decl) => *node_type == NodeType::Notation && decl.name == name,
+            _ => false,
+        };
+        if declares_name {
+            return Ok(format!(
+                "{}{}{}",
+                &internal_subset[..start],
+                declaration,
+                reader.rest()
+            ));
+        }
+    }
+}
+
+///
+/// Replace the internal subset of `doc_type` with `internal_subset`. The internal subset is the
+/// authoritative form of the declarations it contains, so the entity and notation nodes declared
+/// by the previous internal subset are replaced with those declared by the new one; any declared
+/// only by an external DTD are kept.
+///
+fn replace_internal_subset(doc_type: &mut RefNode, internal_subset: Option<String>) -> Result<()> {
+    let old_declarations = doc_type
+        .internal_subset()
+        .and_then(|text| parse_internal_subset(&text).ok())
+        .unwrap_or_default();
+    let new_declarations = match &internal_subset {
+        None => Vec::new(),
+        Some(text) => parse_internal_subset(text)?,
+    };
+    let owner_document = doc_type.owner_document().map(RefNode::downgrade);
+    let mut new_entities: Vec<(Name, RefNode)> = Vec::new();
+    let mut new_notations: Vec<(Name, RefNode)> = Vec::new();
+    for declaration in &new_declarations {
+        match declaration {
+            DtdDeclaration::Entity(decl) => {
+                let name = Name::from_str(&decl.name)?;
+                let mut node_impl = match &decl.value {
+                    Some(value) => {
+                        NodeImpl::new_internal_entity(owner_document.clone(), name.clone(), value)
+                    }
+                    None => NodeImpl::new_entity(
+                        owner_document.clone(),
+                        name.clone(),
+                        decl.public_id.as_deref(),
+                        decl.system_id.as_deref(),
+                    ),
+                };
+                if let Extension::Entity {
+                    i_notation_name, ..
+                } = &mut node_impl.i_extension
+                {
+                    i_notation_name.clone_from(&decl.notation_name);
+                }
+                new_entities.push((name, RefNode::new(node_impl)));
+            }
+            DtdDeclaration::Notation(decl) => {
+                let name = Name::from_str(&decl.name)?;
+                let node_impl = NodeImpl::new_notation(
+                    owner_document.clone(),
+                    name.clone(),
+                    decl.public_id.as_deref(),
+                    decl.system_id.as_deref(),
+                );
+                new_notations.push((name, RefNode::new(node_impl)));
+            }
+            _ => {}
+        }
+    }
+
+    let mut mut_doc_type = doc_type.borrow_mut();
+    if let Extension::DocumentType {
+        i_entities,
+        i_notations,
+        i_internal_subset,
+        ..
+    } = &mut mut_doc_type.i_extension
+    {
+        for declaration in &old_declarations {
+            match declaration {
+                DtdDeclaration::Entity(decl) => {
+                    i_entities.retain(|name, _| name.to_string() != decl.name)
+                }
+                DtdDeclaration::Notation(decl) => {
+                    i_notations.retain(|name, _| name.to_string() != decl.name)
+                }
+                _ => {}
+            }
+        }
+        // the first declaration of a name is the one that is binding.
+        for (name, node) in new_entities.into_iter().rev() {
+            let _safe_to_ignore = i_entities.insert(name, node);
+        }
+        for (name, node) in new_notations.into_iter().rev() {
+            let _safe_to_ignore = i_notations.insert(name, node);
+        }
+        *i_internal_subset = internal_subset;
+        Ok(())
+    } else {
+        warn!("{}", INVALID_EXTENSION);
+        Err(Error::InvalidState)
+    }
+}
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+fn is_name_char(c: char) -> bool {
+    !is_space(c) && !"()|,?*+>\"'%;[]".contains(c)
+}
+
+///
+/// Returns the children of `node`, with any entity reference replaced by its own children.
+///
+fn content_nodes(node: &RefNode) -> Vec<RefNode> {
+    let mut nodes = Vec::new();
+    for child_node in node.child_nodes() {
+        if child_node.node_type() == NodeType::EntityReference {
+            nodes.extend(content_nodes(&child_node));
+        } else {
+            nodes.push(child_node);
+        }
+    }
+    nodes
+}
+
+fn is_valid_content(element: &RefNode, content: &ContentSpec) -> bool {
+    let nodes = content_nodes(element);
+    let child_names = || {
+        nodes
+            .iter()
+            .filter(|node| is_element(node))
+            .map(|node| node.node_name().to_string())
+    };
+    match content {
+        ContentSpec::Empty => nodes.is_empty(),
+        ContentSpec::Any => true,
+        ContentSpec::Mixed(names) => child_names().all(|name| names.contains(&name)),
+        ContentSpec::Children(particle) => {
+            // only white space may appear between the child elements.
+            let is_element_content = nodes.iter().all(|node| match node.node_type() {
+                NodeType::Text => node.node_value().unwrap_or_default().chars().all(is_space),
+                NodeType::CData => false,
+                _ => true,
+            });
+            let names: Vec<String> = child_names().collect();
+            is_element_content
+                && particle_ends(particle, &names, BTreeSet::from([0])).contains(&names.len())
+        }
+    }
+}
+
+///
+/// Returns each index in `names` at which a match of `particle`, starting at one of `starts`,
+/// may end.
+///
+fn particle_ends(
+    particle: &ContentParticle,
+    names: &[String],
+    starts: BTreeSet<usize>,
+) -> BTreeSet<usize> {
+    let once = |starts: &BTreeSet<usize>| -> BTreeSet<usize> {
+        match &particle.particle {
+            Particle::Name(name) => starts
+                .iter()
+                .filter(|start| names.get(**start) == Some(name))
+                .map(|start| start + 1)
+                .collect(),
+            Particle::Choice(particles) => particles
+                .iter()
+                .flat_map(|particle| particle_ends(particle, names, starts.clone()))
+                .collect(),
+            Particle::Sequence(particles) => {
+                particles.iter().fold(starts.clone(), |ends, particle| {
+                    particle_ends(particle, names, ends)
+                })
+            }
+        }
+    };
+    let repeated = |mut ends: BTreeSet<usize>| {
+        let mut next = ends.clone();
+        while !next.is_empty() {
+            next = once(&next).difference(&ends).cloned().collect();
+            ends.extend(next.iter().cloned());
+        }
+        ends
+    };
+    match particle.occurrence {
+        Occurrence::Once => once(&starts),
+        Occurrence::Optional => starts.union(&once(&starts)).cloned().collect(),
+        Occurrence::ZeroOrMore => repeated(starts),
+        Occurrence::OneOrMore => repeated(once(&starts)),
+    }
+}
+
+fn is_valid_attribute_value(decl: &AttributeDef, value: &str) -> bool {
+    // values other than CDATA are compared after normalizing their white space.
+    let normalized = |value: &str| {
+        if decl.attribute_type == AttributeType::CData {
+            value.to_string()
+        } else {
+            value.split_whitespace().collect::<Vec<&str>>().join(" ")
+        }
+    };
+    let value = normalized(value);
+    let is_listed = match &decl.attribute_type {
+        AttributeType::Enumeration(values) | AttributeType::Notation(values) => {
+            values.contains(&value)
+        }
+        _ => true,
+    };
+    match &decl.default {
+        DefaultDecl::Fixed(fixed) => is_listed && normalized(fixed) == value,
+        _ => is_listed,
+    }
+}
+
+///
+/// Quote `value` with `"`, or with `'` if it contains `"`.
+///
+fn quote(value: &str) -> String {
+    if value.contains('"') {
+        format!("'{}'", value)
+    } else {
+        format!("\"{}\"", value)
+    }
+}
+
+fn fmt_group(particles: &[ContentParticle], separator: &str, f: &mut Formatter<'_>) -> FmtResult {
+    write!(f, "(")?;
+    for (index, particle) in particles.iter().enumerate() {
+        if index > 0 {
+            write!(f, "{}", separator)?;
+        }
+        write!(f, "{}", particle)?;
+    }
+    write!(f, ")")
+}
+
+fn fmt_external_id(
+    public_id: Option<&String>,
+    system_id: Option<&String>,
+    f: &mut Formatter<'_>,
+) -> FmtResult {
+    match (public_id, system_id) {
+        (Some(public_id), Some(system_id)) => {
+            write!(f, " PUBLIC {} {}", quote(public_id), quote(system_id))
+        }
+        (Some(public_id), None) => write!(f, " PUBLIC {}", quote(public_id)),
+        (None, Some(system_id)) => write!(f, " SYSTEM {}", quote(system_id)),
+        (None, None) => Ok(()),
+    }
+}
+
+fn fmt_entity(decl: &EntityDecl, parameter: bool, f: &mut Formatter<'_>) -> FmtResult {
+    write!(f, "<!ENTITY ")?;
+    if parameter {
+        write!(f, "% ")?;
+    }
+    write!(f, "{}", decl.name)?;
+    match &decl.value {
+        Some(value) => write!(f, " {}", quote(value))?,
+        None => fmt_external_id(decl.public_id.as_ref(), decl.system_id.as_ref(), f)?,
+    }
+    if let Some(notation_name) = &decl.notation_name {
+        write!(f, " NDATA {}", notation_name)?;
+    }
+    write!(f, ">")
+}
@@ -0,0 +1,107 @@
+/*!
+Provides the ability to make a subtree read-only at runtime, so that a library may hand out
+references to its internal documents without the risk of callers modifying them.
+
+The [`freeze`](fn.freeze.html) function marks a node, its attributes, and all of its descendants
+as read-only; any operation that would modify one of these nodes fails with
+`Error::NoModificationAllowed`. Freezing returns a [`ThawToken`](struct.ThawToken.html), which is
+the only way to make the subtree writable again with [`thaw`](fn.thaw.html); the token can't be
+cloned, so only the code that froze the subtree can thaw it.
+
+Freezes may be nested, a node remains read-only until every freeze covering it has been thawed.
+Nodes cloned from a frozen node are not frozen.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::freeze::{freeze, is_frozen, thaw};
+
+let document_node = get_implementation()
+    .create_document(None, Some("root"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+
+let token = freeze(&mut root_node);
+assert!(is_frozen(&root_node));
+assert_eq!(root_node.set_attribute("id", "main"), Err(Error::NoModificationAllowed));
+
+thaw(token);
+assert!(root_node.set_attribute("id", "main").is_ok());
+```
+
+*/
+
+use crate::level2::node_impl::{Extension, RefNode, WeakRefNode};
+use crate::level2::trait_impls;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The capability, returned by [`freeze`](fn.freeze.html), required to thaw a frozen subtree. If
+/// this token is dropped the subtree can never be thawed.
+///
+#[derive(Debug)]
+#[must_use = "a frozen subtree can only be thawed with this token"]
+pub struct ThawToken {
+    node: WeakRefNode,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Make `node`, its attributes, and all of its descendants read-only, returning the token
+/// required to thaw them.
+///
+pub fn freeze(node: &mut RefNode) -> ThawToken {
+    adjust_subtree(node, true);
+    ThawToken {
+        node: node.clone().downgrade(),
+    }
+}
+
+///
+/// Release the freeze represented by `token`; nodes that are covered by another freeze remain
+/// read-only.
+///
+pub fn thaw(token: ThawToken) {
+    if let Some(node) = token.node.upgrade() {
+        adjust_subtree(&node, false);
+    }
+}
+
+///
+/// Returns `true` if `node` is currently read-only as the result of a freeze, else `false`.
+///
+pub fn is_frozen(node: &RefNode) -> bool {
+    trait_impls::is_frozen(node)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn adjust_subtree(node: &RefNode, freeze: bool) {
+    let related: Vec<RefNode> = {
+        let mut mut_node = node.borrow_mut();
+        if freeze {
+            mut_node.i_frozen += 1;
+        } else {
+            mut_node.i_frozen -= 1;
+        }
+        let mut related = mut_node.i_child_nodes.clone();
+        if let Extension::Element { i_attributes, .. } = &mut_node.i_extension {
+            related.extend(i_attributes.values().cloned());
+        }
+        related
+    };
+    for related_node in &related {
+        adjust_subtree(related_node, freeze);
+    }
+}
@@ -0,0 +1,157 @@
+/*!
+Provides the export of a DOM tree as a [petgraph](https://crates.io/crates/petgraph) graph, so
+that existing graph tooling can be used for the structural analysis and visualization of large
+documents; this module is only available with the `graph` feature.
+
+The function [`to_graph`](fn.to_graph.html) returns a directed graph with a vertex for each node in
+the tree, labeled with a [`NodeLabel`](struct.NodeLabel.html), and an edge from each node to each
+of its children and attributes, labeled with an [`EdgeKind`](enum.EdgeKind.html). The function
+[`to_dot`](fn.to_dot.html) writes the same graph in the Graphviz DOT format.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::graph::{to_graph, EdgeKind};
+
+let document_node = get_implementation()
+    .create_document(None, Some("root"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+root_node.set_attribute("id", "main").unwrap();
+let _ = root_node.append_child(document.create_text_node("Hello")).unwrap();
+
+let graph = to_graph(&root_node);
+assert_eq!(graph.node_count(), 3);
+assert_eq!(
+    graph.edge_weights().filter(|kind| **kind == EdgeKind::Attribute).count(),
+    1
+);
+```
+
+*/
+
+use crate::level2::convert::as_attribute;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::NodeType;
+use petgraph::dot::Dot;
+use petgraph::graph::{DiGraph, NodeIndex};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The label of each vertex in the graph, describing the DOM node it represents.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeLabel {
+    /// The type of the node.
+    pub node_type: NodeType,
+    /// The node name, as returned by `node_name`, for example `rdf:about` or `#text`.
+    pub name: String,
+    /// The node value, for attributes this is the attribute value.
+    pub value: Option<String>,
+}
+
+///
+/// The label of each edge in the graph, describing the relationship between two nodes.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// The target node is a child of the source node.
+    Child,
+    /// The target node is an attribute of the source element.
+    Attribute,
+}
+
+///
+/// The graph returned by [`to_graph`](fn.to_graph.html).
+///
+pub type DomGraph = DiGraph<NodeLabel, EdgeKind>;
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns a graph of `node` and all of its descendants and attributes; the vertex for `node`
+/// itself is always at index `0`. The attributes of an element are added in the order in which
+/// they were added to the element, then its children in document order, before the descendants
+/// of those children.
+///
+pub fn to_graph(node: &RefNode) -> DomGraph {
+    let mut graph = DomGraph::new();
+    let root_index = graph.add_node(node_label(node));
+    //
+    // An explicit stack is used, rather than recursion, as this is intended for large documents.
+    //
+    let mut stack: Vec<(RefNode, NodeIndex)> = vec![(node.clone(), root_index)];
+    while let Some((node, index)) = stack.pop() {
+        for attribute in node.attributes().values() {
+            let attribute_index = graph.add_node(node_label(&attribute));
+            let _safe_to_ignore = graph.add_edge(index, attribute_index, EdgeKind::Attribute);
+        }
+        let mut children: Vec<(RefNode, NodeIndex)> = Vec::new();
+        for child_node in node.child_nodes() {
+            let child_index = graph.add_node(node_label(&child_node));
+            let _safe_to_ignore = graph.add_edge(index, child_index, EdgeKind::Child);
+            children.push((child_node, child_index));
+        }
+        stack.extend(children.into_iter().rev());
+    }
+    graph
+}
+
+///
+/// Returns the graph of `node`, as described for [`to_graph`](fn.to_graph.html), in the Graphviz
+/// DOT format.
+///
+pub fn to_dot(node: &RefNode) -> String {
+    Dot::new(&to_graph(node)).to_string()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for NodeLabel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.value {
+            None => write!(f, "{}", self.name),
+            Some(value) => write!(f, "{} = {:?}", self.name, value),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for EdgeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            EdgeKind::Child => write!(f, "child"),
+            EdgeKind::Attribute => write!(f, "attribute"),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn node_label(node: &RefNode) -> NodeLabel {
+    let value = match node.node_type() {
+        NodeType::Attribute => as_attribute(node)
+            .ok()
+            .and_then(|attribute| attribute.value()),
+        _ => node.node_value(),
+    };
+    NodeLabel {
+        node_type: node.node_type(),
+        name: node.node_name().to_string(),
+        value,
+    }
+}
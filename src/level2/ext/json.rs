@@ -0,0 +1,214 @@
+/*!
+Provides [`to_json_value`](fn.to_json_value.html), which converts a node, and its descendants,
+directly to a [`serde_json::Value`](https://docs.rs/serde_json/latest/serde_json/enum.Value.html)
+for services that need to include an XML payload in a JSON log entry or API response; this module
+is only available with the `serde_json` feature.
+
+Unlike the [`serialization`](../serialization/index.html) module, which records the complete
+structure of a tree so that it can be rebuilt, the mapping here is lossy and intended to be read
+by people, and by code that knows the shape of the payload:
+
+* a document, or an element, is an object with a single member, named for the element (the
+  document element, for a document), whose value is the content of the element.
+* the content of an element is `null` if the element is empty, the string of its text if it
+  has only text, and otherwise an object with a member for each attribute, named with the
+  [`attribute_prefix`](struct.JsonMappingOptions.html#method.attribute_prefix), a member for each
+  distinct child element name, whose value is an array if the name is repeated, and a member
+  named [`text_key`](struct.JsonMappingOptions.html#method.text_key) for any text.
+* text and CDATA sections are concatenated, entity references are replaced by their text
+  content, and text that is only whitespace is dropped; comments, processing instructions, and
+  the document type are dropped.
+* any other node is the string of its text content, or `null` if it has none.
+
+Members are ordered as `serde_json::Map` orders them, which is by name unless serde_json's
+`preserve_order` feature is enabled.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::json::{to_json_value, JsonMappingOptions};
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml(
+    r#"<order id="17"><item sku="a1">Pen</item><item>Ink</item><note/></order>"#,
+)
+.unwrap();
+
+assert_eq!(
+    to_json_value(&document_node, JsonMappingOptions::new()),
+    serde_json::json!({
+        "order": {
+            "@id": "17",
+            "item": [{ "@sku": "a1", "#text": "Pen" }, "Ink"],
+            "note": null
+        }
+    })
+);
+```
+
+*/
+
+use crate::level2::convert::as_document;
+use crate::level2::ext::traits::TextContent;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::NodeType;
+use serde_json::{Map, Value};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The options that determine how nodes are mapped to JSON by
+/// [`to_json_value`](fn.to_json_value.html). The default names attributes with the prefix `@`,
+/// and text as `#text`, and only uses an array for a repeated element name.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonMappingOptions {
+    attribute_prefix: String,
+    text_key: String,
+    always_array: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Convert `node`, and its descendants, to a JSON value as described in the
+/// [module documentation](index.html).
+///
+pub fn to_json_value(node: &RefNode, options: JsonMappingOptions) -> Value {
+    match node.node_type() {
+        NodeType::Document => match as_document(node).ok().and_then(|d| d.document_element()) {
+            None => Value::Null,
+            Some(root_node) => named_value(&root_node, &options),
+        },
+        NodeType::Element => named_value(node, &options),
+        NodeType::DocumentFragment => content_value(node, &options),
+        _ => node
+            .text_content()
+            .map(Value::String)
+            .unwrap_or(Value::Null),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for JsonMappingOptions {
+    fn default() -> Self {
+        Self {
+            attribute_prefix: "@".to_string(),
+            text_key: "#text".to_string(),
+            always_array: false,
+        }
+    }
+}
+
+impl JsonMappingOptions {
+    ///
+    /// Construct a new `JsonMappingOptions` instance with the default options.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Returns the prefix added to the name of an attribute to name its member.
+    ///
+    pub fn attribute_prefix(&self) -> &str {
+        &self.attribute_prefix
+    }
+    ///
+    /// Set the prefix added to the name of an attribute to name its member; this may be empty.
+    ///
+    pub fn set_attribute_prefix(&mut self, attribute_prefix: &str) {
+        self.attribute_prefix = attribute_prefix.to_string()
+    }
+    ///
+    /// Returns the name of the member holding the text of an element that also has attributes,
+    /// or child elements.
+    ///
+    pub fn text_key(&self) -> &str {
+        &self.text_key
+    }
+    ///
+    /// Set the name of the member holding the text of an element that also has attributes, or
+    /// child elements.
+    ///
+    pub fn set_text_key(&mut self, text_key: &str) {
+        self.text_key = text_key.to_string()
+    }
+    ///
+    /// Returns `true` if the member for a child element name is always an array, else `false`.
+    ///
+    pub fn has_always_array(&self) -> bool {
+        self.always_array
+    }
+    ///
+    /// Make the member for each child element name an array, even if there is only one element
+    /// with that name, so that the shape of the output does not depend on the number of
+    /// children.
+    ///
+    pub fn set_always_array(&mut self) {
+        self.always_array = true
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn named_value(element: &RefNode, options: &JsonMappingOptions) -> Value {
+    let mut object = Map::new();
+    let _safe_to_ignore = object.insert(
+        element.node_name().to_string(),
+        content_value(element, options),
+    );
+    Value::Object(object)
+}
+
+fn content_value(node: &RefNode, options: &JsonMappingOptions) -> Value {
+    let mut object = Map::new();
+    for attribute in node.attributes().values() {
+        let _safe_to_ignore = object.insert(
+            format!("{}{}", options.attribute_prefix, attribute.node_name()),
+            Value::String(attribute.text_content().unwrap_or_default()),
+        );
+    }
+    let mut text = String::new();
+    for child_node in node.child_nodes() {
+        match child_node.node_type() {
+            NodeType::Element => {
+                let name = child_node.node_name().to_string();
+                let value = content_value(&child_node, options);
+                match object.get_mut(&name) {
+                    Some(Value::Array(values)) => values.push(value),
+                    Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+                    None if options.always_array => {
+                        let _safe_to_ignore = object.insert(name, Value::Array(vec![value]));
+                    }
+                    None => {
+                        let _safe_to_ignore = object.insert(name, value);
+                    }
+                }
+            }
+            NodeType::Text | NodeType::CData | NodeType::EntityReference => {
+                text.push_str(&child_node.text_content().unwrap_or_default())
+            }
+            _ => {}
+        }
+    }
+    let has_text = !text.trim().is_empty();
+    match (object.is_empty(), has_text) {
+        (true, false) => Value::Null,
+        (true, true) => Value::String(text),
+        (_, true) => {
+            let _safe_to_ignore = object.insert(options.text_key.clone(), Value::String(text));
+            Value::Object(object)
+        }
+        (_, false) => Value::Object(object),
+    }
+}
@@ -0,0 +1,311 @@
+/*!
+Provides a mapping between Rust types and elements, for clients that want a typed model of some
+documents while keeping the DOM as the representation exchanged with other components.
+
+The [`ToXmlDom`](trait.ToXmlDom.html) trait writes a value as a new element, and the
+[`FromXmlDom`](trait.FromXmlDom.html) trait reads a value from an existing element; the
+[`XmlValue`](trait.XmlValue.html) trait converts simple values, such as strings and numbers, to
+and from the text used in attributes and text nodes. Every `XmlValue` type is also mapped as an
+element containing only its text. The `bool` and floating point types, and the additional types in
+the [`xsd`](../xsd/index.html) module, use the lexical forms of the XML Schema datatypes.
+
+With the `derive` feature enabled, the derive macros `ToXmlDom` and `FromXmlDom` are re-exported
+from this module; they map each field of a struct to an attribute, the text content, or child
+elements as described in the [xml_dom_derive](https://docs.rs/xml_dom_derive/) crate. The public
+functions in this module are those used by the derived implementations, they may also be used
+when implementing the traits by hand, as below.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::mapping::*;
+
+#[derive(Debug, PartialEq)]
+struct Title {
+    lang: Option<String>,
+    text: String,
+}
+
+impl ToXmlDom for Title {
+    fn to_element(&self, document: &RefNode, name: &str) -> Result<RefNode> {
+        let mut element = create_element(document, name)?;
+        if let Some(lang) = &self.lang {
+            set_attribute(&mut element, "xml:lang", lang)?;
+        }
+        append_text(document, &mut element, &self.text)?;
+        Ok(element)
+    }
+}
+
+impl FromXmlDom for Title {
+    fn from_element(element: &RefNode) -> Result<Self> {
+        Ok(Self {
+            lang: attribute(element, "xml:lang")?,
+            text: text(element)?,
+        })
+    }
+}
+
+let title = Title { lang: None, text: "A Guide to Growing Roses".to_string() };
+let document_node = title.to_document("title").unwrap();
+assert_eq!(document_node.to_string(), "<title>A Guide to Growing Roses</title>");
+assert_eq!(Title::from_document(&document_node).unwrap(), title);
+```
+
+*/
+
+use crate::level2::convert::{as_document, is_element};
+use crate::level2::dom_impl::get_implementation;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Node, NodeType};
+use crate::shared::error::{Error, Result};
+
+#[cfg(feature = "derive")]
+pub use xml_dom_derive::{FromXmlDom, ToXmlDom};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Implemented by types that are written as the text of an attribute or text node.
+///
+pub trait XmlValue: Sized {
+    ///
+    /// Returns the text representing this value.
+    ///
+    fn to_xml_value(&self) -> String;
+    ///
+    /// Parse a value from `value`, returning `Error::Syntax` if it is not valid for this type.
+    ///
+    fn from_xml_value(value: &str) -> Result<Self>;
+}
+
+///
+/// Implemented by types that can be written as an element.
+///
+pub trait ToXmlDom {
+    ///
+    /// Create a new element named `name`, owned by `document`, representing this value; the
+    /// element is not added to the tree.
+    ///
+    fn to_element(&self, document: &RefNode, name: &str) -> Result<RefNode>;
+    ///
+    /// Create a new document whose document element, named `name`, represents this value.
+    ///
+    fn to_document(&self, name: &str) -> Result<RefNode> {
+        let mut document_node = get_implementation().create_document(None, None, None)?;
+        let element = self.to_element(&document_node, name)?;
+        let _safe_to_ignore = document_node.append_child(element)?;
+        Ok(document_node)
+    }
+}
+
+///
+/// Implemented by types that can be read from an element.
+///
+pub trait FromXmlDom: Sized {
+    ///
+    /// Read a value from `element`; the name of the element is not checked.
+    ///
+    fn from_element(element: &RefNode) -> Result<Self>;
+    ///
+    /// Read a value from the document element of `document`.
+    ///
+    fn from_document(document: &RefNode) -> Result<Self> {
+        match as_document(document)?.document_element() {
+            None => {
+                warn!("The document has no document element.");
+                Err(Error::NotFound)
+            }
+            Some(element) => Self::from_element(&element),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Create a new element named `name`, owned by `document`.
+///
+pub fn create_element(document: &RefNode, name: &str) -> Result<RefNode> {
+    as_document(document)?.create_element(name)
+}
+
+///
+/// Set the attribute `name` of `element` to `value`.
+///
+pub fn set_attribute<T: XmlValue>(element: &mut RefNode, name: &str, value: &T) -> Result<()> {
+    element.set_attribute(name, &value.to_xml_value())
+}
+
+///
+/// Append a text node containing `value` to `element`; nothing is appended if the text is empty.
+///
+pub fn append_text<T: XmlValue>(
+    document: &RefNode,
+    element: &mut RefNode,
+    value: &T,
+) -> Result<()> {
+    let value = value.to_xml_value();
+    if !value.is_empty() {
+        let text_node = as_document(document)?.create_text_node(&value);
+        let _safe_to_ignore = element.append_child(text_node)?;
+    }
+    Ok(())
+}
+
+///
+/// Append a new child element named `name`, representing `value`, to `element`.
+///
+pub fn append_element<T: ToXmlDom>(
+    document: &RefNode,
+    element: &mut RefNode,
+    name: &str,
+    value: &T,
+) -> Result<()> {
+    let child_node = value.to_element(document, name)?;
+    let _safe_to_ignore = element.append_child(child_node)?;
+    Ok(())
+}
+
+///
+/// Returns the value of the attribute `name` of `element`, or `None` if it is not present.
+///
+pub fn attribute<T: XmlValue>(element: &RefNode, name: &str) -> Result<Option<T>> {
+    element
+        .get_attribute(name)
+        .map(|value| T::from_xml_value(&value))
+        .transpose()
+}
+
+///
+/// Returns the value of the text content of `element`, the concatenation of its text and CDATA
+/// children.
+///
+pub fn text<T: XmlValue>(element: &RefNode) -> Result<T> {
+    T::from_xml_value(&text_content(element))
+}
+
+///
+/// Returns the value of the text content of `element`, as for [`text`](fn.text.html), or `None`
+/// if it has no text content.
+///
+pub fn optional_text<T: XmlValue>(element: &RefNode) -> Result<Option<T>> {
+    let text = text_content(element);
+    if text.is_empty() {
+        Ok(None)
+    } else {
+        T::from_xml_value(&text).map(Some)
+    }
+}
+
+///
+/// Returns the value read from the first child element of `element` named `name`, or `None` if
+/// there is no such child.
+///
+pub fn child<T: FromXmlDom>(element: &RefNode, name: &str) -> Result<Option<T>> {
+    element
+        .child_nodes()
+        .iter()
+        .find(|child_node| is_named_element(child_node, name))
+        .map(T::from_element)
+        .transpose()
+}
+
+///
+/// Returns the values read from every child element of `element` named `name`, in document order.
+///
+pub fn children<T: FromXmlDom>(element: &RefNode, name: &str) -> Result<Vec<T>> {
+    element
+        .child_nodes()
+        .iter()
+        .filter(|child_node| is_named_element(child_node, name))
+        .map(T::from_element)
+        .collect()
+}
+
+///
+/// Returns the value of a required attribute or child element, or `Error::NotFound` if it is
+/// missing; `name` is only used in the log message.
+///
+pub fn required<T>(value: Option<T>, name: &str) -> Result<T> {
+    match value {
+        None => {
+            warn!("The required attribute or element `{}` is missing.", name);
+            Err(Error::NotFound)
+        }
+        Some(value) => Ok(value),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl XmlValue for String {
+    fn to_xml_value(&self) -> String {
+        self.clone()
+    }
+
+    fn from_xml_value(value: &str) -> Result<Self> {
+        Ok(value.to_string())
+    }
+}
+
+macro_rules! impl_xml_value {
+    ($($value_type:ty),*) => {
+        $(
+        impl XmlValue for $value_type {
+            fn to_xml_value(&self) -> String {
+                self.to_string()
+            }
+
+            fn from_xml_value(value: &str) -> Result<Self> {
+                value.trim().parse().map_err(|_| {
+                    warn!("The value {:?} is not a valid `{}`.", value, stringify!($value_type));
+                    Error::Syntax
+                })
+            }
+        }
+        )*
+    };
+}
+
+impl_xml_value!(char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+// ------------------------------------------------------------------------------------------------
+
+impl<T: XmlValue> ToXmlDom for T {
+    fn to_element(&self, document: &RefNode, name: &str) -> Result<RefNode> {
+        let mut element = create_element(document, name)?;
+        append_text(document, &mut element, self)?;
+        Ok(element)
+    }
+}
+
+impl<T: XmlValue> FromXmlDom for T {
+    fn from_element(element: &RefNode) -> Result<Self> {
+        text(element)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn text_content(element: &RefNode) -> String {
+    element
+        .child_nodes()
+        .iter()
+        .filter(|child_node| matches!(child_node.node_type(), NodeType::Text | NodeType::CData))
+        .filter_map(|child_node| child_node.node_value())
+        .collect()
+}
+
+fn is_named_element(node: &RefNode, name: &str) -> bool {
+    is_element(node) && node.node_name().to_string() == name
+}
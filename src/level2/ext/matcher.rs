@@ -0,0 +1,374 @@
+/*!
+Provides a declarative way to check the shape of a tree of elements, intended for tests and the
+validation of message shapes where comparing serialized strings is too brittle.
+
+A pattern is usually written with the [`tree!`](../../../macro.tree.html) macro and checked with
+the [`matches`](fn.matches.html) function, the resulting [`MatchReport`](struct.MatchReport.html)
+lists every difference found rather than just the first.
+
+# Pattern Syntax
+
+Each element pattern is a name, an optional list of attribute tests in parenthesis, and an
+optional list of child element patterns following `=>`.
+
+```text
+"name" ( key = value, ... ) => [ child, ... ]
+```
+
+* The name is compared with the element's qualified name, `node_name`.
+* An attribute key may be an identifier or a string literal. It is compared with the attribute's
+  qualified name and, if no attribute has that name, with the local name of each attribute; so
+  `about` will match an `rdf:about` attribute.
+* The key `text` instead tests the text content of the element, the concatenation of its text and
+  CDATA children with leading and trailing whitespace removed.
+* A value is either a literal, which must match exactly, or `_` which only requires that the
+  attribute be present.
+* If a child list is present the element's child elements must match it, in order and in number;
+  other child nodes such as text and comments are ignored. If the child list is absent the
+  element's children are not checked.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::matcher::matches;
+use xml_dom::tree;
+
+let document_node = get_implementation()
+    .create_document(None, Some("message"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+root_node.set_attribute("id", "m-1").unwrap();
+let mut body_node = root_node
+    .append_child(document.create_element("body").unwrap())
+    .unwrap();
+let _ = body_node.append_child(document.create_text_node(" Hello ")).unwrap();
+
+let report = matches(&document_node, &tree! { "message"(id = _) => [ "body"(text = "Hello") ] });
+assert!(report.is_match(), "{}", report);
+
+let report = matches(&document_node, &tree! { "message" => [ "body"(text = "Goodbye") ] });
+assert_eq!(
+    report.to_string(),
+    "/message/body[1]: expected text \"Goodbye\", found \"Hello\""
+);
+```
+
+*/
+
+use crate::level2::convert::{as_attribute, as_document, is_document, is_element};
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::NodeType;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A test applied to an attribute value, or to the text content of an element.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValuePattern {
+    /// Matches any value, only requiring that it be present.
+    Any,
+    /// Matches only this exact value.
+    Exact(String),
+}
+
+///
+/// A pattern describing an element, its attributes, text content, and child elements.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElementPattern {
+    name: String,
+    attributes: Vec<(String, ValuePattern)>,
+    text: Option<ValuePattern>,
+    children: Option<Vec<ElementPattern>>,
+}
+
+///
+/// A single difference between a node and a pattern.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The path to the element that did not match, each segment is written as `name[index]`
+    /// (one-based) with the index omitted for the root.
+    pub path: String,
+    /// A description of the difference.
+    pub message: String,
+}
+
+///
+/// The result of [`matches`](fn.matches.html), listing every difference found.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatchReport {
+    mismatches: Vec<Mismatch>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Check `node` against `pattern`. If `node` is a document its document element is checked.
+///
+pub fn matches(node: &RefNode, pattern: &ElementPattern) -> MatchReport {
+    let mut report = MatchReport::default();
+    let node = if is_document(node) {
+        as_document(node)
+            .ok()
+            .and_then(|document| document.document_element())
+    } else {
+        Some(node.clone())
+    };
+    match node {
+        None => report.push(
+            "/".to_string(),
+            format!("expected element `{}`, found no element", pattern.name),
+        ),
+        Some(node) => {
+            let path = format!("/{}", node.node_name());
+            match_element(&node, pattern, path, &mut report)
+        }
+    }
+    report
+}
+
+// ------------------------------------------------------------------------------------------------
+// Macros
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Construct an [`ElementPattern`](level2/ext/matcher/struct.ElementPattern.html) using the syntax
+/// described in the [`matcher`](level2/ext/matcher/index.html) module.
+///
+/// ```rust
+/// use xml_dom::tree;
+///
+/// let pattern = tree! {
+///     "rdf:RDF" => [
+///         "rdf:Description"(about = _) => [
+///             "dc:title"(text = "A Guide to Growing Roses")
+///         ]
+///     ]
+/// };
+/// ```
+///
+#[macro_export]
+macro_rules! tree {
+    (@children [$($done:expr),*]) => {
+        vec![$($done),*]
+    };
+    (@children [$($done:expr),*] $name:literal $(($($attributes:tt)*))? => [$($children:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::tree!(
+            @children
+            [$($done,)* $crate::tree!($name $(($($attributes)*))? => [$($children)*])]
+            $($($rest)*)?
+        )
+    };
+    (@children [$($done:expr),*] $name:literal $(($($attributes:tt)*))? $(, $($rest:tt)*)?) => {
+        $crate::tree!(
+            @children
+            [$($done,)* $crate::tree!($name $(($($attributes)*))?)]
+            $($($rest)*)?
+        )
+    };
+    (@value _) => {
+        $crate::level2::ext::matcher::ValuePattern::Any
+    };
+    (@value $value:literal) => {
+        $crate::level2::ext::matcher::ValuePattern::Exact($value.to_string())
+    };
+    (@attribute $pattern:ident text = $value:tt) => {
+        $pattern.text($crate::tree!(@value $value))
+    };
+    (@attribute $pattern:ident $key:ident = $value:tt) => {
+        $pattern.attribute(stringify!($key), $crate::tree!(@value $value))
+    };
+    (@attribute $pattern:ident $key:literal = $value:tt) => {
+        $pattern.attribute($key, $crate::tree!(@value $value))
+    };
+    ($name:literal $(($($key:tt = $value:tt),* $(,)?))? $(=> [$($children:tt)*])?) => {{
+        let pattern = $crate::level2::ext::matcher::ElementPattern::new($name);
+        $($(let pattern = $crate::tree!(@attribute pattern $key = $value);)*)?
+        $(let pattern = pattern.children($crate::tree!(@children [] $($children)*));)?
+        pattern
+    }};
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ValuePattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ValuePattern::Any => write!(f, "_"),
+            ValuePattern::Exact(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+impl ValuePattern {
+    ///
+    /// Returns `true` if `value` satisfies this pattern, else `false`.
+    ///
+    pub fn is_match(&self, value: &str) -> bool {
+        match self {
+            ValuePattern::Any => true,
+            ValuePattern::Exact(expected) => expected == value,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ElementPattern {
+    ///
+    /// Construct a pattern that matches any element with the qualified name `name`.
+    ///
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            attributes: Default::default(),
+            text: None,
+            children: None,
+        }
+    }
+    ///
+    /// Add a test for the attribute named `key`.
+    ///
+    pub fn attribute(mut self, key: &str, value: ValuePattern) -> Self {
+        self.attributes.push((key.to_string(), value));
+        self
+    }
+    ///
+    /// Add a test for the text content of the element.
+    ///
+    pub fn text(mut self, value: ValuePattern) -> Self {
+        self.text = Some(value);
+        self
+    }
+    ///
+    /// Require that the child elements match `children`, in order and in number.
+    ///
+    pub fn children(mut self, children: Vec<ElementPattern>) -> Self {
+        self.children = Some(children);
+        self
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for MatchReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let lines: Vec<String> = self.mismatches.iter().map(Mismatch::to_string).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl MatchReport {
+    ///
+    /// Returns `true` if no differences were found, else `false`.
+    ///
+    pub fn is_match(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+    ///
+    /// Returns the differences found, in document order.
+    ///
+    pub fn mismatches(&self) -> &Vec<Mismatch> {
+        &self.mismatches
+    }
+
+    fn push(&mut self, path: String, message: String) {
+        self.mismatches.push(Mismatch { path, message })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn match_element(node: &RefNode, pattern: &ElementPattern, path: String, report: &mut MatchReport) {
+    let name = node.node_name().to_string();
+    if name != pattern.name {
+        report.push(
+            path,
+            format!("expected element `{}`, found `{}`", pattern.name, name),
+        );
+        return;
+    }
+
+    let attributes = node.attributes();
+    for (key, value_pattern) in &pattern.attributes {
+        let attribute = attributes
+            .iter()
+            .find(|(name, _)| name.to_string() == *key)
+            .or_else(|| attributes.iter().find(|(name, _)| name.local_name() == key));
+        match attribute {
+            None => report.push(path.clone(), format!("missing attribute `{}`", key)),
+            Some((_, attribute)) => {
+                let value = as_attribute(&attribute)
+                    .ok()
+                    .and_then(|attribute| attribute.value())
+                    .unwrap_or_default();
+                if !value_pattern.is_match(&value) {
+                    report.push(
+                        path.clone(),
+                        format!(
+                            "expected attribute `{}` to be {}, found {:?}",
+                            key, value_pattern, value
+                        ),
+                    )
+                }
+            }
+        }
+    }
+
+    if let Some(text_pattern) = &pattern.text {
+        let text: String = node
+            .child_nodes()
+            .iter()
+            .filter(|child| matches!(child.node_type(), NodeType::Text | NodeType::CData))
+            .filter_map(|child| child.node_value())
+            .collect();
+        let text = text.trim();
+        if !text_pattern.is_match(text) {
+            report.push(
+                path.clone(),
+                format!("expected text {}, found {:?}", text_pattern, text),
+            );
+        }
+    }
+
+    if let Some(child_patterns) = &pattern.children {
+        let children: Vec<RefNode> = node.child_nodes().into_iter().filter(is_element).collect();
+        if children.len() != child_patterns.len() {
+            report.push(
+                path.clone(),
+                format!(
+                    "expected {} child elements, found {}",
+                    child_patterns.len(),
+                    children.len()
+                ),
+            );
+        }
+        for (index, (child, child_pattern)) in children.iter().zip(child_patterns).enumerate() {
+            let child_path = format!("{}/{}[{}]", path, child.node_name(), index + 1);
+            match_element(child, child_pattern, child_path, report);
+        }
+    }
+}
@@ -0,0 +1,310 @@
+/*!
+Provides the [`merge`](fn.merge.html) function, merging an overlay document, or element, into a
+base; the common case being a configuration file with local overrides applied to its defaults.
+
+Elements are merged recursively. The attributes of an overlay element are added to those of the
+base element, and the content of the overlay element is merged into the base element's content:
+
+* each child element of the overlay is matched with a child element of the base with the same
+  qualified name and, if it has one, the same value of the first of the policy's key attributes
+  it has; a matched pair is merged, an overlay element without a match is copied to the end of
+  the base element's children. Elements are matched in order, so that without key attributes the
+  second `item` of the overlay is merged with the second `item` of the base.
+* the text of the overlay element, its text and CDATA section children, replaces the text of the
+  base element; text that is only whitespace is ignored.
+* comments and processing instructions in the overlay are ignored.
+
+Children of the base element that match nothing in the overlay are kept. How conflicting
+attributes, and text, are resolved is set by the [`MergePolicy`](struct.MergePolicy.html).
+
+# Example
+
+```rust
+use xml_dom::level2::ext::merge::{merge, MergePolicy, TextMerge};
+use xml_dom::parser::read_xml;
+
+let mut base = read_xml(
+    r#"<config><server name="a" port="80"/><server name="b" port="81"/><motd>Hello</motd></config>"#,
+).unwrap();
+let overlay = read_xml(
+    r#"<config><server name="b" port="8081" tls="true"/><server name="c"/><motd>, world</motd></config>"#,
+).unwrap();
+
+let mut policy = MergePolicy::new();
+policy.add_key_attribute("name");
+policy.set_text(TextMerge::Concatenate);
+merge(&mut base, &overlay, &policy).unwrap();
+assert_eq!(
+    base.to_string(),
+    r#"<config><server name="a" port="80"></server><server name="b" port="8081" tls="true"></server><motd>Hello, world</motd><server name="c"></server></config>"#
+);
+```
+
+*/
+
+use crate::diagnostics::{INVALID_NODE_TYPE, INVALID_STATE};
+use crate::level2::convert::{as_document, is_document, is_element};
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::import_into;
+use crate::level2::traits::{CharacterData, Element, Node, NodeType};
+use crate::shared::error::{Error, Result};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Determines how conflicts are resolved by [`merge`](fn.merge.html). The default is that the
+/// attributes and text of the overlay win, and elements are matched by name alone.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergePolicy {
+    attributes: AttributeMerge,
+    text: TextMerge,
+    key_attributes: Vec<String>,
+}
+
+///
+/// Determines how the attributes of an overlay element are merged with those of the base
+/// element, see [`set_attributes`](struct.MergePolicy.html#method.set_attributes).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttributeMerge {
+    /// Add each attribute of the overlay element, replacing any attribute of the base element
+    /// with the same name.
+    #[default]
+    Overlay,
+    /// Add each attribute of the overlay element that the base element does not have, keeping
+    /// the value of those it does.
+    Base,
+    /// Replace all of the attributes of the base element with those of the overlay element.
+    Replace,
+}
+
+///
+/// Determines how the text of an overlay element is merged with that of the base element, see
+/// [`set_text`](struct.MergePolicy.html#method.set_text).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextMerge {
+    /// Replace the text of the base element with that of the overlay element.
+    #[default]
+    Replace,
+    /// Append the text of the overlay element to that of the base element.
+    Concatenate,
+    /// Keep the text of the base element, using the text of the overlay element only if the base
+    /// element has none.
+    Base,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Merge `overlay` into `base`, as set by `policy`; see the module documentation for how elements
+/// are matched. Both nodes must be documents, in which case their document elements are merged,
+/// or both must be elements; nodes copied from the overlay are imported into the document of
+/// `base`.
+///
+/// This returns `Error::InvalidModification` if the elements to merge do not have the same
+/// qualified name, and `Error::NoModificationAllowed` if a node to change is frozen, in which case
+/// the changes preceding it in document order have already been made.
+///
+pub fn merge(base: &mut RefNode, overlay: &RefNode, policy: &MergePolicy) -> Result<()> {
+    if is_document(base) && is_document(overlay) {
+        let overlay_root = match as_document(overlay)?.document_element() {
+            None => return Ok(()),
+            Some(overlay_root) => overlay_root,
+        };
+        match as_document(base)?.document_element() {
+            None => {
+                let new_root = import_into(base, &overlay_root)?;
+                let _safe_to_ignore = base.append_child(new_root)?;
+                Ok(())
+            }
+            Some(mut base_root) => merge_elements(&mut base_root, &overlay_root, policy),
+        }
+    } else if is_element(base) && is_element(overlay) {
+        merge_elements(base, overlay, policy)
+    } else {
+        warn!("{}", INVALID_NODE_TYPE);
+        Err(Error::InvalidState)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl MergePolicy {
+    ///
+    /// Construct a new `MergePolicy` instance where the overlay wins.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Returns how attributes are merged.
+    ///
+    pub fn attributes(&self) -> AttributeMerge {
+        self.attributes
+    }
+    ///
+    /// Merge the attributes of matched elements as set by `attributes`.
+    ///
+    pub fn set_attributes(&mut self, attributes: AttributeMerge) {
+        self.attributes = attributes
+    }
+    ///
+    /// Returns how text is merged.
+    ///
+    pub fn text(&self) -> TextMerge {
+        self.text
+    }
+    ///
+    /// Merge the text of matched elements as set by `text`.
+    ///
+    pub fn set_text(&mut self, text: TextMerge) {
+        self.text = text
+    }
+    ///
+    /// Returns the names of the attributes that identify child elements, in the order they are
+    /// tried.
+    ///
+    pub fn key_attributes(&self) -> &[String] {
+        &self.key_attributes
+    }
+    ///
+    /// Match child elements by the value of the attribute with the qualified name `name`, as well
+    /// as by their name; where an element has more than one key attribute the first added is
+    /// used.
+    ///
+    pub fn add_key_attribute(&mut self, name: &str) {
+        if !self.key_attributes.iter().any(|existing| existing == name) {
+            self.key_attributes.push(name.to_string())
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn merge_elements(base: &mut RefNode, overlay: &RefNode, policy: &MergePolicy) -> Result<()> {
+    if base.node_name() != overlay.node_name() {
+        warn!(
+            "merge: cannot merge element {} with element {}",
+            overlay.node_name(),
+            base.node_name()
+        );
+        return Err(Error::InvalidModification);
+    }
+    merge_attributes(base, overlay, policy.attributes)?;
+    merge_text(base, overlay, policy.text)?;
+
+    let mut candidates: Vec<Option<RefNode>> = base
+        .child_nodes()
+        .into_iter()
+        .filter(is_element)
+        .map(Some)
+        .collect();
+    for overlay_child in overlay.child_nodes().into_iter().filter(is_element) {
+        let key = merge_key(&overlay_child, policy);
+        let matched = candidates.iter_mut().find(|candidate| {
+            candidate
+                .as_ref()
+                .is_some_and(|candidate| merge_key(candidate, policy) == key)
+        });
+        match matched.and_then(Option::take) {
+            Some(mut base_child) => merge_elements(&mut base_child, &overlay_child, policy)?,
+            None => {
+                let new_child = import_into(base, &overlay_child)?;
+                let _safe_to_ignore = base.append_child(new_child)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn merge_attributes(base: &mut RefNode, overlay: &RefNode, policy: AttributeMerge) -> Result<()> {
+    if policy == AttributeMerge::Replace {
+        for attribute_node in base.attributes().values() {
+            let _safe_to_ignore = base.remove_attribute_node(attribute_node)?;
+        }
+    }
+    for (name, attribute_node) in overlay.attributes() {
+        if policy == AttributeMerge::Base && base.attributes().get(&name).is_some() {
+            continue;
+        }
+        let new_attribute = import_into(base, &attribute_node)?;
+        let _safe_to_ignore = base.set_attribute_node(new_attribute)?;
+    }
+    Ok(())
+}
+
+fn merge_text(base: &mut RefNode, overlay: &RefNode, policy: TextMerge) -> Result<()> {
+    let text: String = overlay
+        .child_nodes()
+        .iter()
+        .filter(|child_node| is_text(child_node))
+        .filter_map(|child_node| child_node.node_value())
+        .collect();
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+    let base_text: Vec<RefNode> = base
+        .child_nodes()
+        .into_iter()
+        .filter(is_text)
+        .filter(|child_node| {
+            !child_node
+                .node_value()
+                .unwrap_or_default()
+                .trim()
+                .is_empty()
+        })
+        .collect();
+    match (policy, base_text.last()) {
+        (TextMerge::Base, Some(_)) => Ok(()),
+        (TextMerge::Concatenate, Some(last_text)) => last_text.clone().append_data(&text),
+        (TextMerge::Replace, Some(_)) => {
+            // the new text takes the place of the first text replaced.
+            let new_text = create_text(base, &text)?;
+            let _safe_to_ignore = base.insert_before(new_text, Some(base_text[0].clone()))?;
+            for text_node in base_text {
+                let _safe_to_ignore = base.remove_child(text_node)?;
+            }
+            Ok(())
+        }
+        (_, None) => {
+            let new_text = create_text(base, &text)?;
+            let _safe_to_ignore = base.append_child(new_text)?;
+            Ok(())
+        }
+    }
+}
+
+///
+/// Returns the qualified name of `element`, and the name and value of its first key attribute.
+///
+fn merge_key(element: &RefNode, policy: &MergePolicy) -> (String, Option<(String, String)>) {
+    let key = policy.key_attributes.iter().find_map(|name| {
+        element
+            .get_attribute(name)
+            .map(|value| (name.to_string(), value))
+    });
+    (element.node_name().to_string(), key)
+}
+
+fn is_text(node: &RefNode) -> bool {
+    matches!(node.node_type(), NodeType::Text | NodeType::CData)
+}
+
+fn create_text(element: &RefNode, text: &str) -> Result<RefNode> {
+    let document_node = element.owner_document().ok_or_else(|| {
+        warn!("{}", INVALID_STATE);
+        Error::InvalidState
+    })?;
+    Ok(as_document(&document_node)?.create_text_node(text))
+}
@@ -6,19 +6,110 @@ This module contains extensions above and beyond the Level 2 specification.
 // Public Modules
 // ------------------------------------------------------------------------------------------------
 
+pub mod axes;
+
+pub mod batch;
+pub use batch::{Transaction, Violation};
+
+pub mod canonical;
+
+pub mod character_reference;
+pub use character_reference::CharacterReference;
+
+pub mod collect;
+
+pub mod comparison;
+pub use comparison::DocumentPosition;
+
 pub mod convert;
 
+#[cfg(feature = "cycle_detection")]
+pub mod cycles;
+
 pub mod decl;
 pub use decl::{XmlDecl, XmlVersion};
 
+pub mod diff;
+pub use diff::{DomEdit, NodePath};
+
 pub mod dom_impl;
 
+pub mod dtd;
+pub use dtd::DtdDeclaration;
+
+pub mod freeze;
+
+#[cfg(feature = "serde_json")]
+pub mod json;
+
+#[cfg(feature = "graph")]
+pub mod graph;
+
 pub mod options;
 pub use options::ProcessingOptions;
 
+pub mod mapping;
+
+pub mod matcher;
+
+pub mod merge;
+pub use merge::MergePolicy;
+
+pub mod normalize;
+pub use normalize::NormalizationOptions;
+
+pub mod node_list;
+pub use node_list::LiveNodeList;
+
+pub mod node_map;
+pub use node_map::NodeMap;
+
 pub mod namespaced;
 pub use namespaced::NamespacePrefix;
 
+pub mod position;
+pub use position::{Position, Span};
+
+#[cfg(feature = "quick_parser")]
+pub mod preserve;
+
+pub mod provenance;
+pub use provenance::{Operation, Provenance};
+
+#[cfg(feature = "internals")]
+pub mod raw;
+
+pub mod redact;
+
+pub mod schema;
+
+pub mod search;
+pub use search::SearchMatch;
+
+#[cfg(feature = "selectors")]
+pub mod selectors;
+
+#[cfg(feature = "serde")]
+pub mod serialization;
+
+pub mod snapshot;
+pub use snapshot::Snapshot;
+
+pub mod sync;
+
+pub mod teardown;
+
+pub mod tree_sizes;
+
+pub mod validate;
+
+pub mod writer;
+pub use writer::{IncrementalWriter, WriterOptions};
+
+pub mod xsd;
+
+pub(crate) mod tag_index;
+
 pub(crate) mod traits;
 pub use traits::*;
 
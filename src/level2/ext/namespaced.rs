@@ -2,11 +2,14 @@
 This module provides support types for the [`Namespaced`](trait.Namespaced.html) trait.
 */
 
+use crate::diagnostics::{INVALID_EXTENSION, INVALID_NODE_TYPE, WEAK_REF};
+use crate::level2::convert::{as_attribute, as_document, as_element, is_element};
 use crate::level2::ext::traits::Namespaced;
 use crate::level2::node_impl::{Extension, RefNode};
-use crate::level2::traits::{Node, NodeType};
-use crate::shared::error::{
-    Error, Result, MSG_INVALID_EXTENSION, MSG_INVALID_NODE_TYPE, MSG_WEAK_REF,
+use crate::level2::traits::NodeType;
+use crate::shared::error::{Error, Result};
+use crate::shared::syntax::{
+    XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI, XML_NS_ATTRIBUTE, XML_NS_SEPARATOR, XML_NS_URI,
 };
 
 // ------------------------------------------------------------------------------------------------
@@ -34,7 +37,9 @@ pub(crate) trait MutNamespaced: Namespaced {
         prefix: Option<&str>,
         namespace_uri: &str,
     ) -> Result<Option<String>>;
+    #[allow(dead_code)]
     fn remove_mapping(&mut self, prefix: Option<&str>) -> Result<Option<String>>;
+    #[allow(dead_code)]
     fn normalize_mappings(&mut self) -> Result<()>;
 }
 
@@ -91,7 +96,7 @@ fn add_namespaces(element_node: &RefNode) -> bool {
         if let Extension::Document { i_options, .. } = &ref_document.i_extension {
             return i_options.has_add_namespaces();
         } else {
-            warn!("{}", MSG_INVALID_EXTENSION);
+            warn!("{}", INVALID_EXTENSION);
         }
     }
     false
@@ -107,11 +112,11 @@ impl Namespaced for RefNode {
             if let Extension::Element { i_namespaces, .. } = &ref_self.i_extension {
                 i_namespaces.contains_key(&prefix.map(String::from))
             } else {
-                warn!("{}", MSG_INVALID_EXTENSION);
+                warn!("{}", INVALID_EXTENSION);
                 false
             }
         } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn!("{}", INVALID_NODE_TYPE);
             false
         }
     }
@@ -126,11 +131,11 @@ impl Namespaced for RefNode {
                 let value = i_namespaces.get(&prefix.map(String::from));
                 value.map(String::to_string)
             } else {
-                warn!("{}", MSG_INVALID_EXTENSION);
+                warn!("{}", INVALID_EXTENSION);
                 None
             }
         } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn!("{}", INVALID_NODE_TYPE);
             None
         }
     }
@@ -146,7 +151,8 @@ impl Namespaced for RefNode {
                     None => None,
                     Some(parent) => {
                         let parent = parent.clone();
-                        let parent_node = parent.upgrade().expect(MSG_WEAK_REF);
+                        let parent_node =
+                            parent.upgrade().unwrap_or_else(|| panic!("{}", WEAK_REF));
                         parent_node.resolve_namespace(prefix)
                     }
                 }
@@ -174,11 +180,11 @@ impl Namespaced for RefNode {
                     Some((None, _)) => NamespacePrefix::Default,
                 }
             } else {
-                warn!("{}", MSG_INVALID_EXTENSION);
+                warn!("{}", INVALID_EXTENSION);
                 NamespacePrefix::None
             }
         } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn!("{}", INVALID_NODE_TYPE);
             NamespacePrefix::None
         }
     }
@@ -194,7 +200,8 @@ impl Namespaced for RefNode {
                     None => NamespacePrefix::None,
                     Some(parent) => {
                         let parent = parent.clone();
-                        let parent_node = parent.upgrade().expect(MSG_WEAK_REF);
+                        let parent_node =
+                            parent.upgrade().unwrap_or_else(|| panic!("{}", WEAK_REF));
                         parent_node.resolve_prefix(namespace_uri)
                     }
                 }
@@ -202,6 +209,133 @@ impl Namespaced for RefNode {
             found => found,
         }
     }
+
+    fn lookup_namespace_uri(&self, prefix: Option<&str>) -> Option<String> {
+        match prefix {
+            Some(XML_NS_ATTRIBUTE) => return Some(XML_NS_URI.to_string()),
+            Some(XMLNS_NS_ATTRIBUTE) => return Some(XMLNS_NS_URI.to_string()),
+            _ => {}
+        }
+        let mut current = lookup_element(self);
+        while let Some(element) = current {
+            let name = element.node_name();
+            if name.namespace_uri().is_some() && name.prefix().as_deref() == prefix {
+                return name.namespace_uri().clone();
+            }
+            if let Some(declared) = declared_namespace(&element, prefix) {
+                return declared;
+            }
+            current = parent_element(&element);
+        }
+        None
+    }
+
+    fn lookup_prefix(&self, namespace_uri: &str) -> Option<String> {
+        let original = lookup_element(self)?;
+        let is_in_scope = |prefix: &str| {
+            original.lookup_namespace_uri(Some(prefix)).as_deref() == Some(namespace_uri)
+        };
+        let mut current = Some(original.clone());
+        while let Some(element) = current {
+            let name = element.node_name();
+            if let (Some(uri), Some(prefix)) = (name.namespace_uri(), name.prefix()) {
+                if uri == namespace_uri && is_in_scope(prefix) {
+                    return Some(prefix.clone());
+                }
+            }
+            let mut declared: Vec<(String, Option<String>)> = element
+                .attributes()
+                .values()
+                .filter(|attribute| {
+                    attribute.node_name().prefix().as_deref() == Some(XMLNS_NS_ATTRIBUTE)
+                })
+                .map(|attribute| {
+                    (
+                        attribute.node_name().local_name().clone(),
+                        as_attribute(&attribute).ok().and_then(|a| a.value()),
+                    )
+                })
+                .collect();
+            declared.sort();
+            if let Some((prefix, _)) = declared
+                .into_iter()
+                .find(|(prefix, uri)| uri.as_deref() == Some(namespace_uri) && is_in_scope(prefix))
+            {
+                return Some(prefix);
+            }
+            current = parent_element(&element);
+        }
+        match namespace_uri {
+            XML_NS_URI => Some(XML_NS_ATTRIBUTE.to_string()),
+            XMLNS_NS_URI => Some(XMLNS_NS_ATTRIBUTE.to_string()),
+            _ => None,
+        }
+    }
+
+    fn is_default_namespace(&self, namespace_uri: Option<&str>) -> bool {
+        let mut current = lookup_element(self);
+        while let Some(element) = current {
+            let name = element.node_name();
+            if name.prefix().is_none() {
+                return name.namespace_uri().as_deref() == namespace_uri;
+            }
+            if let Some(declared) = declared_namespace(&element, None) {
+                return declared.as_deref() == namespace_uri;
+            }
+            current = parent_element(&element);
+        }
+        false
+    }
+}
+
+///
+/// The element at which a namespace lookup starts for `node`.
+///
+fn lookup_element(node: &RefNode) -> Option<RefNode> {
+    match node.node_type() {
+        NodeType::Element => Some(node.clone()),
+        NodeType::Document => as_document(node).ok()?.document_element(),
+        NodeType::Attribute => as_attribute(node).ok()?.owner_element(),
+        NodeType::DocumentType
+        | NodeType::DocumentFragment
+        | NodeType::Entity
+        | NodeType::Notation => None,
+        _ => parent_element(node),
+    }
+}
+
+fn parent_element(node: &RefNode) -> Option<RefNode> {
+    let mut current = node.parent_node();
+    while let Some(parent_node) = current {
+        if is_element(&parent_node) {
+            return Some(parent_node);
+        }
+        current = parent_node.parent_node();
+    }
+    None
+}
+
+///
+/// Returns `None` if `element` has no `xmlns` attribute for `prefix`, else the namespace it
+/// declares, which is `None` where the default namespace is undeclared with an empty value.
+///
+pub(crate) fn declared_namespace(
+    element: &RefNode,
+    prefix: Option<&str>,
+) -> Option<Option<String>> {
+    let qualified_name = match prefix {
+        None => XMLNS_NS_ATTRIBUTE.to_string(),
+        Some(prefix) => format!("{}{}{}", XMLNS_NS_ATTRIBUTE, XML_NS_SEPARATOR, prefix),
+    };
+    let attribute_node = as_element(element)
+        .ok()?
+        .get_attribute_node(&qualified_name)?;
+    Some(
+        as_attribute(&attribute_node)
+            .ok()?
+            .value()
+            .filter(|uri| !uri.is_empty()),
+    )
 }
 
 impl MutNamespaced for RefNode {
@@ -218,11 +352,11 @@ impl MutNamespaced for RefNode {
             if let Extension::Element { i_namespaces, .. } = &mut mut_self.i_extension {
                 Ok(i_namespaces.insert(prefix.map(String::from), namespace_uri.to_string()))
             } else {
-                warn!("{}", MSG_INVALID_EXTENSION);
+                warn!("{}", INVALID_EXTENSION);
                 Err(Error::InvalidState)
             }
         } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn!("{}", INVALID_NODE_TYPE);
             Err(Error::InvalidState)
         }
     }
@@ -236,11 +370,11 @@ impl MutNamespaced for RefNode {
             if let Extension::Element { i_namespaces, .. } = &mut mut_self.i_extension {
                 Ok(i_namespaces.remove(&prefix.map(String::from)))
             } else {
-                warn!("{}", MSG_INVALID_EXTENSION);
+                warn!("{}", INVALID_EXTENSION);
                 Err(Error::InvalidState)
             }
         } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn!("{}", INVALID_NODE_TYPE);
             Err(Error::InvalidState)
         }
     }
@@ -440,4 +574,71 @@ mod tests {
             NamespacePrefix::new_some("xslt")
         );
     }
+
+    #[test]
+    fn test_lookup() {
+        use crate::level2::convert::as_document;
+        use crate::level2::ext::Namespaced;
+        use crate::level2::{get_implementation, Element, Node};
+
+        const XMLNS: &str = "http://www.w3.org/2000/xmlns/";
+
+        // the mapping tables are not used, so add_namespaces is not set.
+        let document_node = get_implementation()
+            .create_document(Some(EX), Some("ex:root"), None)
+            .unwrap();
+        let document = as_document(&document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        root_node.set_attribute_ns(XMLNS, "xmlns", HTML).unwrap();
+        root_node.set_attribute_ns(XMLNS, "xmlns:xsd", XSD).unwrap();
+        let mut child_node = root_node
+            .append_child(document.create_element_ns(XSLT, "xsd:child").unwrap())
+            .unwrap();
+        child_node.set_attribute("xmlns", "").unwrap();
+        let text_node = child_node
+            .append_child(document.create_text_node("text"))
+            .unwrap();
+
+        // prefix to namespace
+        assert_eq!(
+            document_node.lookup_namespace_uri(Some("ex")),
+            Some(EX.to_string())
+        );
+        assert_eq!(
+            text_node.lookup_namespace_uri(Some("ex")),
+            Some(EX.to_string())
+        );
+        assert_eq!(root_node.lookup_namespace_uri(None), Some(HTML.to_string()));
+        assert_eq!(child_node.lookup_namespace_uri(None), None);
+        assert_eq!(
+            root_node.lookup_namespace_uri(Some("xsd")),
+            Some(XSD.to_string())
+        );
+        assert_eq!(
+            text_node.lookup_namespace_uri(Some("xsd")),
+            Some(XSLT.to_string())
+        );
+        assert_eq!(text_node.lookup_namespace_uri(Some("nope")), None);
+        assert_eq!(
+            text_node.lookup_namespace_uri(Some("xml")),
+            Some("http://www.w3.org/XML/1998/namespace".to_string())
+        );
+
+        // namespace to prefix
+        assert_eq!(root_node.lookup_prefix(XSD), Some("xsd".to_string()));
+        assert_eq!(text_node.lookup_prefix(XSD), None);
+        assert_eq!(text_node.lookup_prefix(XSLT), Some("xsd".to_string()));
+        assert_eq!(text_node.lookup_prefix(EX), Some("ex".to_string()));
+        assert_eq!(root_node.lookup_prefix(HTML), None);
+        assert_eq!(root_node.lookup_prefix(XMLNS), Some("xmlns".to_string()));
+
+        // default namespace
+        assert!(!root_node.is_default_namespace(Some(EX)));
+        assert!(root_node.is_default_namespace(Some(HTML)));
+        assert!(text_node.is_default_namespace(None));
+        assert!(!text_node.is_default_namespace(Some(HTML)));
+        let detached_node = document.create_element_ns(XSD, "xsd:detached").unwrap();
+        assert!(!detached_node.is_default_namespace(None));
+        assert_eq!(detached_node.lookup_prefix(XSD), Some("xsd".to_string()));
+    }
 }
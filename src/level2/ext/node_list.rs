@@ -0,0 +1,166 @@
+/*!
+Provides the [`LiveNodeList`](struct.LiveNodeList.html) type, a `NodeList` that reflects the
+current state of the tree rather than a copy taken when it was created; see the
+[`LiveCollections`](../trait.LiveCollections.html) trait.
+
+Methods such as `child_nodes` and `get_elements_by_tag_name` return a `Vec<RefNode>`, a snapshot
+that does not change as nodes are added or removed. A `LiveNodeList` holds only a weak reference
+to the node it was created from, and the query that selects its nodes; `length` and `item` run
+that query each time they are called, so they always agree with the tree. Once the node has been
+dropped the list is empty.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::LiveCollections;
+
+let document_node = get_implementation()
+    .create_document(None, Some("root"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+
+let items = document_node.elements_by_tag_name("item");
+let children = root_node.child_node_list();
+assert_eq!(items.length(), 0);
+
+let _ = root_node.append_child(document.create_element("item").unwrap()).unwrap();
+let _ = root_node.append_child(document.create_text_node("text")).unwrap();
+assert_eq!(items.length(), 1);
+assert_eq!(children.length(), 2);
+assert_eq!(children.item(1).unwrap().node_type(), NodeType::Text);
+```
+
+*/
+
+use crate::level2::convert::{is_document, is_element};
+use crate::level2::ext::traits::LiveCollections;
+use crate::level2::node_impl::{RefNode, WeakRefNode};
+use crate::level2::trait_impls::{namespaced_name_match, tag_name_match};
+use crate::level2::traits::NodeList;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A `NodeList` whose content is computed from the tree each time it is read.
+///
+#[derive(Clone, Debug)]
+pub struct LiveNodeList {
+    root: WeakRefNode,
+    query: Query,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+enum Query {
+    Children,
+    TagName(String),
+    TagNameNs(String, String),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl NodeList for LiveNodeList {
+    type NodeRef = RefNode;
+
+    fn length(&self) -> usize {
+        match (self.root.clone().upgrade(), &self.query) {
+            (None, _) => 0,
+            (Some(root), Query::Children) => root.borrow().i_child_nodes.len(),
+            (Some(root), _) => {
+                let mut seen = 0;
+                let _safe_to_ignore = self.find(&root, usize::MAX, &mut seen);
+                seen
+            }
+        }
+    }
+
+    fn item(&self, index: usize) -> Option<Self::NodeRef> {
+        match (self.root.clone().upgrade(), &self.query) {
+            (None, _) => None,
+            (Some(root), Query::Children) => root.borrow().i_child_nodes.get(index).cloned(),
+            (Some(root), _) => self.find(&root, index, &mut 0),
+        }
+    }
+}
+
+impl LiveNodeList {
+    ///
+    /// Returns the nodes currently in the list; the result is not live.
+    ///
+    pub fn snapshot(&self) -> Vec<RefNode> {
+        (0..).map_while(|index| self.item(index)).collect()
+    }
+
+    fn new(root: &RefNode, query: Query) -> Self {
+        Self {
+            root: root.clone().downgrade(),
+            query,
+        }
+    }
+
+    ///
+    /// Returns the `index`th matching element, in document order, at or below `root`; `seen` is
+    /// the number of matching elements before `root`, and is updated with those visited.
+    ///
+    fn find(&self, root: &RefNode, index: usize, seen: &mut usize) -> Option<RefNode> {
+        if is_document(root) {
+            // the document element may be replaced, so it is found each time.
+            return root
+                .document_element()
+                .and_then(|element| self.find(&element, index, seen));
+        }
+        if !is_element(root) {
+            return None;
+        }
+        let ref_root = root.borrow();
+        let matched = match &self.query {
+            Query::Children => false,
+            Query::TagName(tag_name) => tag_name_match(&ref_root.i_name.to_string(), tag_name),
+            Query::TagNameNs(namespace_uri, local_name) => namespaced_name_match(
+                ref_root.i_name.namespace_uri().as_deref(),
+                ref_root.i_name.local_name(),
+                namespace_uri,
+                local_name,
+            ),
+        };
+        if matched {
+            if *seen == index {
+                return Some(root.clone());
+            }
+            *seen += 1;
+        }
+        ref_root
+            .i_child_nodes
+            .iter()
+            .find_map(|child_node| self.find(child_node, index, seen))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl LiveCollections for RefNode {
+    fn child_node_list(&self) -> LiveNodeList {
+        LiveNodeList::new(self, Query::Children)
+    }
+
+    fn elements_by_tag_name(&self, tag_name: &str) -> LiveNodeList {
+        LiveNodeList::new(self, Query::TagName(tag_name.to_string()))
+    }
+
+    fn elements_by_tag_name_ns(&self, namespace_uri: &str, local_name: &str) -> LiveNodeList {
+        LiveNodeList::new(
+            self,
+            Query::TagNameNs(namespace_uri.to_string(), local_name.to_string()),
+        )
+    }
+}
@@ -0,0 +1,161 @@
+/*!
+Provides the [`NodeMap`](struct.NodeMap.html) type, a map keyed by node identity that allows
+clients to associate their own data, such as annotations or computed styles, with nodes in a
+tree.
+
+`RefNode` does not implement `Hash`, and its `PartialEq` implementation compares identity, so it
+cannot be used directly as the key of a `HashMap`. A `NodeMap` holds only a weak reference to each
+key, so it does not keep nodes alive; once a node has been dropped its entry is no longer
+returned by any method and is removed by [`purge`](struct.NodeMap.html#method.purge) or the next
+`insert`.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::node_map::NodeMap;
+
+let document_node = get_implementation()
+    .create_document(None, Some("root"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let root_node = document.document_element().unwrap();
+
+let mut styles: NodeMap<&str> = NodeMap::new();
+let _ = styles.insert(&root_node, "display: block");
+assert_eq!(styles.get(&root_node), Some(&"display: block"));
+assert_eq!(styles.get(&document_node), None);
+```
+
+*/
+
+use crate::level2::node_impl::{RefNode, WeakRefNode};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A map from nodes, compared by identity, to values of type `V`.
+///
+pub struct NodeMap<V> {
+    entries: HashMap<usize, (WeakRefNode, V)>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<V> Default for NodeMap<V> {
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+        }
+    }
+}
+
+impl<V: Debug> Debug for NodeMap<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_map()
+            .entries(
+                self.iter()
+                    .map(|(node, value)| (node.borrow().i_name.to_string(), value)),
+            )
+            .finish()
+    }
+}
+
+impl<V> NodeMap<V> {
+    ///
+    /// Construct a new, empty, map.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Associate `value` with `node`, returning any value previously associated with it. Entries
+    /// for nodes that have been dropped are removed first.
+    ///
+    pub fn insert(&mut self, node: &RefNode, value: V) -> Option<V> {
+        let _safe_to_ignore = self.purge();
+        self.entries
+            .insert(key(node), (node.clone().downgrade(), value))
+            .map(|(_, value)| value)
+    }
+    ///
+    /// Returns the value associated with `node`, if any.
+    ///
+    pub fn get(&self, node: &RefNode) -> Option<&V> {
+        self.entries.get(&key(node)).map(|(_, value)| value)
+    }
+    ///
+    /// Returns a mutable reference to the value associated with `node`, if any.
+    ///
+    pub fn get_mut(&mut self, node: &RefNode) -> Option<&mut V> {
+        self.entries.get_mut(&key(node)).map(|(_, value)| value)
+    }
+    ///
+    /// Remove, and return, the value associated with `node`, if any.
+    ///
+    pub fn remove(&mut self, node: &RefNode) -> Option<V> {
+        self.entries.remove(&key(node)).map(|(_, value)| value)
+    }
+    ///
+    /// Returns `true` if there is a value associated with `node`, else `false`.
+    ///
+    pub fn contains_key(&self, node: &RefNode) -> bool {
+        self.entries.contains_key(&key(node))
+    }
+    ///
+    /// The number of entries whose node is still alive.
+    ///
+    pub fn len(&self) -> usize {
+        self.entries
+            .values()
+            .filter(|(node, _)| !node.is_dropped())
+            .count()
+    }
+    ///
+    /// Returns `true` if there are no entries whose node is still alive, else `false`.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    ///
+    /// Remove all entries.
+    ///
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+    ///
+    /// Remove the entries for any nodes that have been dropped, returning the number removed.
+    ///
+    pub fn purge(&mut self) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, (node, _)| !node.is_dropped());
+        before - self.entries.len()
+    }
+    ///
+    /// Returns an iterator over the nodes that are still alive, and their values.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = (RefNode, &V)> {
+        self.entries
+            .values()
+            .filter_map(|(node, value)| node.clone().upgrade().map(|node| (node, value)))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// While the map holds a weak reference to a node its allocation, and so its address, cannot be
+/// reused; a live node therefore never matches a dropped node's entry.
+///
+fn key(node: &RefNode) -> usize {
+    node.as_ptr() as usize
+}
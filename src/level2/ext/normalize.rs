@@ -0,0 +1,396 @@
+/*!
+Provides the [`NormalizationOptions`](struct.NormalizationOptions.html) used by the
+[`NormalizeDocument`](../trait.NormalizeDocument.html) trait, which provides a form of the DOM
+Level 3 `Document.normalizeDocument` operation.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{NormalizationOptions, NormalizeDocument};
+
+let mut document_node = get_implementation()
+    .create_document(None, Some("root"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let _ = root_node.append_child(document.create_text_node("Hello, ")).unwrap();
+let _ = root_node.append_child(document.create_comment("to be removed")).unwrap();
+let _ = root_node.append_child(document.create_text_node("World")).unwrap();
+
+let mut options = NormalizationOptions::new();
+options.set_discard_comments();
+document_node.normalize_document(&options).unwrap();
+
+assert_eq!(root_node.to_string(), "<root>Hello, World</root>");
+```
+
+*/
+
+use crate::diagnostics::INVALID_NODE_TYPE;
+use crate::level2::convert::{
+    as_attribute, as_attribute_mut, as_document, is_document, is_element, is_text,
+};
+use crate::level2::ext::dtd::AttributeType;
+use crate::level2::ext::namespaced::MutNamespaced;
+use crate::level2::ext::schema::{document_type_provider, is_nilled, TypeProvider};
+use crate::level2::ext::trait_impls::{is_element_content, space_handling};
+use crate::level2::ext::traits::{ElementContentWhitespace, NormalizeDocument};
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{CharacterData, Element, Node, NodeType};
+use crate::shared::error::{Error, Result};
+use crate::shared::name::Name;
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI, XML_NS_ATTRIBUTE, XML_NS_URI};
+use crate::shared::text::{self, SpaceHandling};
+use std::collections::HashMap;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This type encapsulates the set of options that govern
+/// [`normalize_document`](../trait.NormalizeDocument.html#tymethod.normalize_document), it plays
+/// the part of the DOM Level 3 `DOMConfiguration` interface. The default for
+/// `NormalizationOptions` is that none of the options are set, in which case normalization only
+/// merges adjacent, and removes empty, text nodes throughout the document.
+///
+/// Each option corresponds to a `DOMConfiguration` parameter as follows.
+///
+/// | Option                               | Parameter                                   |
+/// |--------------------------------------|---------------------------------------------|
+/// | `discard_comments`                   | `comments` set to `false`                   |
+/// | `convert_cdata_to_text`              | `cdata-sections` set to `false`             |
+/// | `normalize_namespaces`               | `namespaces` set to `true`                  |
+/// | `discard_element_content_whitespace` | `element-content-whitespace` set to `false` |
+/// | `datatype_normalization`             | `datatype-normalization` set to `true`      |
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NormalizationOptions {
+    discard_comments: bool,
+    convert_cdata_to_text: bool,
+    normalize_namespaces: bool,
+    discard_element_content_whitespace: bool,
+    datatype_normalization: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+type Namespaces = HashMap<Option<String>, String>;
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl NormalizationOptions {
+    ///
+    /// Construct a new `NormalizationOptions` instance with all options off.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Returns `true` if comment nodes will be removed from the document, else `false`.
+    ///
+    pub fn has_discard_comments(&self) -> bool {
+        self.discard_comments
+    }
+    ///
+    /// Remove all comment nodes from the document.
+    ///
+    pub fn set_discard_comments(&mut self) {
+        self.discard_comments = true
+    }
+    ///
+    /// Returns `true` if CDATA sections will be replaced by text nodes, else `false`.
+    ///
+    pub fn has_convert_cdata_to_text(&self) -> bool {
+        self.convert_cdata_to_text
+    }
+    ///
+    /// Replace CDATA sections with text nodes, which are then merged with any adjacent text nodes.
+    /// Sections whose content could not be written as text, as it contains markup characters,
+    /// are left in place.
+    ///
+    pub fn set_convert_cdata_to_text(&mut self) {
+        self.convert_cdata_to_text = true
+    }
+    ///
+    /// Returns `true` if namespace declarations will be normalized, else `false`.
+    ///
+    pub fn has_normalize_namespaces(&self) -> bool {
+        self.normalize_namespaces
+    }
+    ///
+    /// Normalize the `xmlns` attributes in the document; declarations are added for any element,
+    /// or attribute, namespace that is not in scope, and declarations that repeat a mapping
+    /// already in scope are removed. Namespaced attributes without a usable prefix are given one,
+    /// either an existing prefix for the namespace or a generated `NS`_n_ prefix.
+    ///
+    pub fn set_normalize_namespaces(&mut self) {
+        self.normalize_namespaces = true
+    }
+    ///
+    /// Returns `true` if text nodes of element content whitespace will be removed from the
+    /// document, else `false`.
+    ///
+    pub fn has_discard_element_content_whitespace(&self) -> bool {
+        self.discard_element_content_whitespace
+    }
+    ///
+    /// Remove all the text nodes for which
+    /// [`is_element_content_whitespace`](../trait.ElementContentWhitespace.html#tymethod.is_element_content_whitespace)
+    /// is `true`, such as the indentation between elements in a document parsed with white space
+    /// preserved.
+    ///
+    pub fn set_discard_element_content_whitespace(&mut self) {
+        self.discard_element_content_whitespace = true
+    }
+    ///
+    /// Returns `true` if values will be normalized using the types of the registered
+    /// [`TypeProvider`](../schema/trait.TypeProvider.html), else `false`.
+    ///
+    pub fn has_datatype_normalization(&self) -> bool {
+        self.datatype_normalization
+    }
+    ///
+    /// Normalize values using the types of the document's registered
+    /// [`TypeProvider`](../schema/trait.TypeProvider.html); the white space in the value of an
+    /// attribute of a type other than `CDATA` is collapsed to single spaces, and white space text
+    /// is removed from nillable elements with `xsi:nil="true"`, unless within the scope of an
+    /// `xml:space="preserve"` attribute. This has no effect if no type provider is registered.
+    ///
+    pub fn set_datatype_normalization(&mut self) {
+        self.datatype_normalization = true
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NormalizeDocument for RefNode {
+    fn normalize_document(&mut self, options: &NormalizationOptions) -> Result<()> {
+        if !is_document(self) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let document_node = self.clone();
+        normalize_children(&document_node, self, options, false)?;
+        if options.has_normalize_namespaces() {
+            if let Some(mut document_element) = as_document(self)?.document_element() {
+                let mut in_scope: Namespaces = HashMap::new();
+                let _safe_to_ignore = in_scope.insert(None, String::new());
+                let _safe_to_ignore =
+                    in_scope.insert(Some(XML_NS_ATTRIBUTE.to_string()), XML_NS_URI.to_string());
+                fixup_namespaces(&mut document_element, &in_scope)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Normalize the children of `parent`, and their descendants; `space_preserved` is `true` if
+/// `parent` is within the scope of an `xml:space="preserve"` attribute, in which case no white
+/// space is removed.
+///
+fn normalize_children(
+    document_node: &RefNode,
+    parent: &mut RefNode,
+    options: &NormalizationOptions,
+    space_preserved: bool,
+) -> Result<()> {
+    let document = as_document(document_node)?;
+    let type_provider = document_type_provider(document_node)
+        .filter(|_| options.has_datatype_normalization() && is_element(parent));
+    if let Some(type_provider) = &type_provider {
+        normalize_attribute_values(parent, type_provider.as_ref())?;
+    }
+    let is_nilled = type_provider.is_some_and(|type_provider| {
+        type_provider.is_nillable(&parent.node_name()) && is_nilled(parent)
+    });
+    let discard_whitespace = !space_preserved
+        && (is_nilled
+            || (options.has_discard_element_content_whitespace() && is_element_content(parent)));
+    for child_node in parent.child_nodes() {
+        let child_node = match child_node.node_type() {
+            NodeType::Comment if options.has_discard_comments() => {
+                let _safe_to_ignore = parent.remove_child(child_node)?;
+                continue;
+            }
+            NodeType::Text if discard_whitespace && child_node.is_whitespace() => {
+                let _safe_to_ignore = parent.remove_child(child_node)?;
+                continue;
+            }
+            NodeType::CData
+                if options.has_convert_cdata_to_text()
+                    && !text::requires_cdata(child_node.node_value().unwrap_or_default()) =>
+            {
+                let new_node =
+                    document.create_text_node(&child_node.node_value().unwrap_or_default());
+                let _safe_to_ignore = parent.replace_child(new_node.clone(), child_node)?;
+                new_node
+            }
+            NodeType::Element => {
+                let space_preserved = space_handling(&child_node)
+                    .map(|handling| handling == SpaceHandling::Preserve)
+                    .unwrap_or(space_preserved);
+                normalize_children(
+                    document_node,
+                    &mut child_node.clone(),
+                    options,
+                    space_preserved,
+                )?;
+                child_node
+            }
+            _ => child_node,
+        };
+        if is_text(&child_node) {
+            if CharacterData::length(&child_node) == 0 {
+                let _safe_to_ignore = parent.remove_child(child_node)?;
+            } else if let Some(mut previous_node) = child_node.previous_sibling() {
+                if is_text(&previous_node) {
+                    previous_node.append_data(&child_node.node_value().unwrap_or_default())?;
+                    let _safe_to_ignore = parent.remove_child(child_node)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+///
+/// Implements a simplified form of the DOM Level 3 namespace normalization algorithm,
+/// [Appendix B.1](https://www.w3.org/TR/DOM-Level-3-Core/namespaces-algorithms.html#normalizeDocumentAlgo),
+/// `in_scope` holds the mappings declared by the ancestors of `element`.
+///
+fn fixup_namespaces(element: &mut RefNode, in_scope: &Namespaces) -> Result<()> {
+    let mut scope = in_scope.clone();
+
+    let mut attributes: Vec<(Name, RefNode)> = element.attributes().into_iter().collect();
+    attributes.sort_by_key(|(name, _)| name.to_string());
+
+    //
+    // Existing declarations, removing those that repeat an inherited mapping.
+    //
+    for (name, attribute) in attributes
+        .iter()
+        .filter(|(name, _)| name.is_namespace_attribute())
+    {
+        let prefix = declared_prefix(name);
+        let namespace_uri = as_attribute(attribute)?.value().unwrap_or_default();
+        if in_scope.get(&prefix) == Some(&namespace_uri) {
+            let _safe_to_ignore = element.remove_attribute_node(attribute.clone())?;
+            let _safe_to_ignore = element.remove_mapping(prefix.as_deref())?;
+        } else {
+            let _safe_to_ignore = scope.insert(prefix, namespace_uri);
+        }
+    }
+
+    //
+    // The element's own namespace.
+    //
+    let prefix = element.prefix();
+    let namespace_uri = element.namespace_uri().unwrap_or_default();
+    if (prefix.is_none() || !namespace_uri.is_empty()) && scope.get(&prefix) != Some(&namespace_uri)
+    {
+        declare_namespace(element, prefix, namespace_uri, &mut scope)?;
+    }
+
+    //
+    // The namespaces of attributes.
+    //
+    for (name, attribute) in attributes
+        .iter()
+        .filter(|(name, _)| !name.is_namespace_attribute())
+    {
+        let namespace_uri = match name.namespace_uri() {
+            None => continue,
+            Some(namespace_uri) => namespace_uri.clone(),
+        };
+        match name.prefix() {
+            Some(prefix) if scope.get(&Some(prefix.clone())) == Some(&namespace_uri) => {}
+            Some(prefix) if !scope.contains_key(&Some(prefix.clone())) => {
+                declare_namespace(element, Some(prefix.clone()), namespace_uri, &mut scope)?;
+            }
+            _ => {
+                let existing = scope
+                    .iter()
+                    .find(|(prefix, mapped)| prefix.is_some() && **mapped == namespace_uri)
+                    .and_then(|(prefix, _)| prefix.clone());
+                let new_prefix = match existing {
+                    Some(prefix) => prefix,
+                    None => {
+                        let prefix = (1..)
+                            .map(|index| format!("NS{}", index))
+                            .find(|prefix| !scope.contains_key(&Some(prefix.clone())))
+                            .unwrap();
+                        declare_namespace(
+                            element,
+                            Some(prefix.clone()),
+                            namespace_uri.clone(),
+                            &mut scope,
+                        )?;
+                        prefix
+                    }
+                };
+                let value = as_attribute(attribute)?.value().unwrap_or_default();
+                let _safe_to_ignore = element.remove_attribute_node(attribute.clone())?;
+                element.set_attribute_ns(
+                    &namespace_uri,
+                    &format!("{}:{}", new_prefix, name.local_name()),
+                    &value,
+                )?;
+            }
+        }
+    }
+
+    for mut child_node in element.child_nodes().into_iter().filter(is_element) {
+        fixup_namespaces(&mut child_node, &scope)?;
+    }
+    Ok(())
+}
+
+fn normalize_attribute_values(element: &RefNode, type_provider: &dyn TypeProvider) -> Result<()> {
+    let element_name = element.node_name();
+    for (name, mut attribute) in element.attributes() {
+        match type_provider.attribute_type(&element_name, &name) {
+            None | Some(AttributeType::CData) => {}
+            // values with entity references are left as they are, as they would be replaced.
+            Some(_) if !attribute.child_nodes().iter().all(is_text) => {}
+            Some(_) => {
+                let value = as_attribute(&attribute)?.value().unwrap_or_default();
+                let collapsed = value.split_whitespace().collect::<Vec<&str>>().join(" ");
+                if collapsed != value {
+                    as_attribute_mut(&mut attribute)?.set_value(&collapsed)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn declared_prefix(name: &Name) -> Option<String> {
+    name.prefix().as_ref().map(|_| name.local_name().clone())
+}
+
+fn declare_namespace(
+    element: &mut RefNode,
+    prefix: Option<String>,
+    namespace_uri: String,
+    scope: &mut Namespaces,
+) -> Result<()> {
+    let qualified_name = match &prefix {
+        None => XMLNS_NS_ATTRIBUTE.to_string(),
+        Some(prefix) => format!("{}:{}", XMLNS_NS_ATTRIBUTE, prefix),
+    };
+    element.set_attribute_ns(XMLNS_NS_URI, &qualified_name, &namespace_uri)?;
+    let _safe_to_ignore = scope.insert(prefix, namespace_uri);
+    Ok(())
+}
@@ -45,8 +45,9 @@ use std::ops::{BitAnd, BitOr};
 ///     .unwrap();
 /// ```
 ///
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub struct ProcessingOptions(u8);
+pub struct ProcessingOptions(u16);
 
 // ------------------------------------------------------------------------------------------------
 // Private Types
@@ -54,11 +55,21 @@ pub struct ProcessingOptions(u8);
 
 #[doc(hidden)]
 #[derive(Clone, Debug)]
-#[repr(u8)]
+#[repr(u16)]
 enum ProcessingOptionFlags {
     AssumeIDs = 0b0000_0001,
     ParseEntities = 0b0000_0010,
     AddNamespaces = 0b0000_0100,
+    MergeCDataSections = 0b0000_1000,
+    ConvertCDataToText = 0b0001_0000,
+    StrictNames = 0b0010_0000,
+    ValidateNamespaceURIs = 0b0100_0000,
+    FailOnInvalidCharacter = 0b1000_0000,
+    FailOnDuplicateAttribute = 0b0001_0000_0000,
+    TrackTreeSizes = 0b0010_0000_0000,
+    QuietAccessors = 0b0100_0000_0000,
+    UseElementIndex = 0b1000_0000_0000,
+    TrackProvenance = 0b0001_0000_0000_0000,
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -79,6 +90,36 @@ impl Display for ProcessingOptions {
         if self.has_add_namespaces() {
             option_strings.push("AddNamespaces");
         }
+        if self.has_merge_cdata_sections() {
+            option_strings.push("MergeCDataSections");
+        }
+        if self.has_convert_cdata_to_text() {
+            option_strings.push("ConvertCDataToText");
+        }
+        if self.has_strict_names() {
+            option_strings.push("StrictNames");
+        }
+        if self.has_validate_namespace_uris() {
+            option_strings.push("ValidateNamespaceURIs");
+        }
+        if self.has_fail_on_invalid_character() {
+            option_strings.push("FailOnInvalidCharacter");
+        }
+        if self.has_fail_on_duplicate_attribute() {
+            option_strings.push("FailOnDuplicateAttribute");
+        }
+        if self.has_track_tree_sizes() {
+            option_strings.push("TrackTreeSizes");
+        }
+        if self.has_quiet_accessors() {
+            option_strings.push("QuietAccessors");
+        }
+        if self.has_use_element_index() {
+            option_strings.push("UseElementIndex");
+        }
+        if self.has_track_provenance() {
+            option_strings.push("TrackProvenance");
+        }
         write!(f, "{}", option_strings.join(", "))?;
 
         write!(f, "}}")
@@ -90,9 +131,9 @@ impl Display for ProcessingOptions {
 impl Binary for ProcessingOptions {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         if f.alternate() {
-            write!(f, "{:#010b}", self.0)
+            write!(f, "{:#018b}", self.0)
         } else {
-            write!(f, "{:08b}", self.0)
+            write!(f, "{:016b}", self.0)
         }
     }
 }
@@ -123,8 +164,8 @@ impl ProcessingOptions {
     ///
     /// Construct a new `ProcessingOptions` instance with all options off.
     ///
-    pub fn new() -> Self {
-        Default::default()
+    pub const fn new() -> Self {
+        Self(0)
     }
     ///
     /// Returns true if all options are `false`.
@@ -137,21 +178,88 @@ impl ProcessingOptions {
     /// as XML `id` values, else `false`.
     ///
     pub fn has_assume_ids(&self) -> bool {
-        self.0 & (ProcessingOptionFlags::AssumeIDs as u8) != 0
+        self.0 & (ProcessingOptionFlags::AssumeIDs as u16) != 0
     }
     ///
     /// Returns `true` if the document will parse entities inside text nodes and create
     /// `EntityReference` nodes, else `false`.
     ///
     pub fn has_parse_entities(&self) -> bool {
-        self.0 & (ProcessingOptionFlags::ParseEntities as u8) != 0
+        self.0 & (ProcessingOptionFlags::ParseEntities as u16) != 0
     }
     ///
     /// Returns `true` if the document will automatically add namespace attributes to elements if
     /// qualified names are added that do not have current mappings., else `false`.
     ///
     pub fn has_add_namespaces(&self) -> bool {
-        self.0 & (ProcessingOptionFlags::AddNamespaces as u8) != 0
+        self.0 & (ProcessingOptionFlags::AddNamespaces as u16) != 0
+    }
+    ///
+    /// Returns `true` if `Node::normalize` will merge adjacent CDATA sections, else `false`.
+    ///
+    pub fn has_merge_cdata_sections(&self) -> bool {
+        self.0 & (ProcessingOptionFlags::MergeCDataSections as u16) != 0
+    }
+    ///
+    /// Returns `true` if `Node::normalize` will replace CDATA sections with text nodes where the
+    /// content does not require a CDATA section, else `false`.
+    ///
+    pub fn has_convert_cdata_to_text(&self) -> bool {
+        self.0 & (ProcessingOptionFlags::ConvertCDataToText as u16) != 0
+    }
+    ///
+    /// Returns `true` if the names of new elements and attributes must also conform to the
+    /// Namespaces in XML recommendation, else `false`.
+    ///
+    pub fn has_strict_names(&self) -> bool {
+        self.0 & (ProcessingOptionFlags::StrictNames as u16) != 0
+    }
+    ///
+    /// Returns `true` if the namespace URIs used by the document must be valid IRIs, else `false`.
+    ///
+    pub fn has_validate_namespace_uris(&self) -> bool {
+        self.0 & (ProcessingOptionFlags::ValidateNamespaceURIs as u16) != 0
+    }
+    ///
+    /// Returns `true` if content containing characters not allowed by the document's XML version
+    /// is rejected, else `false`.
+    ///
+    pub fn has_fail_on_invalid_character(&self) -> bool {
+        self.0 & (ProcessingOptionFlags::FailOnInvalidCharacter as u16) != 0
+    }
+    ///
+    /// Returns `true` if an attribute whose name would duplicate that of another attribute of the
+    /// same element is rejected, else `false`.
+    ///
+    pub fn has_fail_on_duplicate_attribute(&self) -> bool {
+        self.0 & (ProcessingOptionFlags::FailOnDuplicateAttribute as u16) != 0
+    }
+    ///
+    /// Returns `true` if the depth and subtree size of each node are maintained as the document
+    /// is changed, else `false`.
+    ///
+    pub fn has_track_tree_sizes(&self) -> bool {
+        self.0 & (ProcessingOptionFlags::TrackTreeSizes as u16) != 0
+    }
+    ///
+    /// Returns `true` if the warnings logged by accessors are silenced, else `false`.
+    ///
+    pub fn has_quiet_accessors(&self) -> bool {
+        self.0 & (ProcessingOptionFlags::QuietAccessors as u16) != 0
+    }
+    ///
+    /// Returns `true` if the document's elements are indexed by name for the `Document` lookups
+    /// by tag name, else `false`.
+    ///
+    pub fn has_use_element_index(&self) -> bool {
+        self.0 & (ProcessingOptionFlags::UseElementIndex as u16) != 0
+    }
+    ///
+    /// Returns `true` if the elements created by cloning, or importing, other elements record
+    /// the element they were derived from, else `false`.
+    ///
+    pub fn has_track_provenance(&self) -> bool {
+        self.0 & (ProcessingOptionFlags::TrackProvenance as u16) != 0
     }
     ///
     /// TBD.
@@ -162,20 +270,117 @@ impl ProcessingOptions {
     /// See xml:id Version 1.0, §4 [Processing xml:id Attributes](https://www.w3.org/TR/xml-id/#processing)
     /// for more details.
     ///
-    pub fn set_assume_ids(&mut self) {
-        self.0 |= ProcessingOptionFlags::AssumeIDs as u8
+    pub const fn set_assume_ids(&mut self) {
+        self.0 |= ProcessingOptionFlags::AssumeIDs as u16
     }
     ///
     /// TBD
     ///
-    pub fn set_parse_entities(&mut self) {
-        self.0 |= ProcessingOptionFlags::ParseEntities as u8
+    pub const fn set_parse_entities(&mut self) {
+        self.0 |= ProcessingOptionFlags::ParseEntities as u16
     }
     ///
     /// TBD
     ///
-    pub fn set_add_namespaces(&mut self) {
-        self.0 |= ProcessingOptionFlags::AddNamespaces as u8
+    pub const fn set_add_namespaces(&mut self) {
+        self.0 |= ProcessingOptionFlags::AddNamespaces as u16
+    }
+    ///
+    /// When `Node::normalize` is called, merge adjacent CDATA sections into one, in the same way
+    /// as adjacent text nodes are merged. This reduces the node count after many small appends.
+    ///
+    pub const fn set_merge_cdata_sections(&mut self) {
+        self.0 |= ProcessingOptionFlags::MergeCDataSections as u16
+    }
+    ///
+    /// When `Node::normalize` is called, replace any CDATA section whose content contains no
+    /// markup characters (`<`, `&`, or the sequence `]]>`) with a text node; the new text node is
+    /// then merged with any adjacent text.
+    ///
+    pub const fn set_convert_cdata_to_text(&mut self) {
+        self.0 |= ProcessingOptionFlags::ConvertCDataToText as u16
+    }
+    ///
+    /// In addition to the XML `Name` production, which is always checked, require that the names
+    /// of elements and attributes created by the document conform to the
+    /// [Namespaces in XML](https://www.w3.org/TR/xml-names/) recommendation. Names may not begin
+    /// with the reserved string `xml` (in any case), except for the `xml` prefix and, for
+    /// attributes, the `xmlns` namespace declarations; and elements may not use the `xmlns`
+    /// prefix.
+    ///
+    pub const fn set_strict_names(&mut self) {
+        self.0 |= ProcessingOptionFlags::StrictNames as u16
+    }
+    ///
+    /// Require that the namespace URIs of elements and attributes created by the document, and
+    /// the values of namespace declarations, are valid IRI references as defined by
+    /// [RFC 3987](https://www.rfc-editor.org/rfc/rfc3987); an invalid URI is an `Error::Namespace`.
+    /// As the use of relative URI references as namespace names is deprecated, a warning is logged
+    /// for any namespace URI that is not absolute.
+    ///
+    pub const fn set_validate_namespace_uris(&mut self) {
+        self.0 |= ProcessingOptionFlags::ValidateNamespaceURIs as u16
+    }
+    ///
+    /// Require that the content of nodes, text, CDATA sections, comments, processing instruction
+    /// data, and attribute values, only contains the characters allowed by the `Char` production
+    /// of the document's XML version, 1.0 unless the XML declaration says otherwise; any other
+    /// character is an `Error::InvalidCharacter`. Content is checked by `set_attribute` and
+    /// the other ways of setting attributes, by those `create_*` methods that may fail, by
+    /// `set_node_value`, and when a node is added to the tree by `append_child` or
+    /// `insert_before`; so that, for example, a text node created with an invalid character is
+    /// rejected when it is added to an element.
+    ///
+    pub const fn set_fail_on_invalid_character(&mut self) {
+        self.0 |= ProcessingOptionFlags::FailOnInvalidCharacter as u16
+    }
+    ///
+    /// Reject, with `Error::InUseAttribute`, an attribute that would be written with the same
+    /// qualified name as another attribute of the element, or that has the same namespace and
+    /// local name as another; either is not well-formed. Setting an attribute with exactly the
+    /// same name replaces the existing attribute as usual.
+    ///
+    pub const fn set_fail_on_duplicate_attribute(&mut self) {
+        self.0 |= ProcessingOptionFlags::FailOnDuplicateAttribute as u16
+    }
+    ///
+    /// Maintain the depth, and subtree size, of each node as children are added and removed, so
+    /// that the [`TreeSizes`](../trait.TreeSizes.html) operations are constant time; without
+    /// this they walk the tree each time. Moving a subtree then takes time proportional to its
+    /// size, and adding or removing a node to its depth.
+    ///
+    pub const fn set_track_tree_sizes(&mut self) {
+        self.0 |= ProcessingOptionFlags::TrackTreeSizes as u16
+    }
+    ///
+    /// Do not log the warnings from accessors, such as `get_attribute` called on a node that is
+    /// not an element, which return an empty result rather than an error; by default each is
+    /// logged once for the document. Warnings from methods that change the document, which
+    /// accompany an error, are always logged.
+    ///
+    pub const fn set_quiet_accessors(&mut self) {
+        self.0 |= ProcessingOptionFlags::QuietAccessors as u16
+    }
+    ///
+    /// Index the document's elements by name, and by local name, the first time that
+    /// `Document::get_elements_by_tag_name` or `Document::get_elements_by_tag_name_ns` is
+    /// called, so that later calls only visit the matching elements rather than walking the
+    /// whole tree. The index is discarded whenever a child is added or removed anywhere in the
+    /// document, and rebuilt by the next lookup; it suits documents that are queried repeatedly
+    /// between changes.
+    ///
+    pub const fn set_use_element_index(&mut self) {
+        self.0 |= ProcessingOptionFlags::UseElementIndex as u16
+    }
+    ///
+    /// Record, for each element created by `clone_node` or `import_node`, the element it was
+    /// derived from and the operation used; the record is returned by
+    /// [`NodeProvenance::provenance`](../trait.NodeProvenance.html#tymethod.provenance). The
+    /// option is read from the document that owns the new element, for `import_node` this is
+    /// the importing document.
+    ///
+    pub const fn set_track_provenance(&mut self) {
+        self.0 |= ProcessingOptionFlags::TrackProvenance as u16
     }
 }
 
@@ -195,10 +400,20 @@ mod tests {
         assert!(!options.has_assume_ids());
         assert!(!options.has_parse_entities());
         assert!(!options.has_add_namespaces());
+        assert!(!options.has_merge_cdata_sections());
+        assert!(!options.has_convert_cdata_to_text());
+        assert!(!options.has_strict_names());
+        assert!(!options.has_validate_namespace_uris());
+        assert!(!options.has_fail_on_invalid_character());
+        assert!(!options.has_fail_on_duplicate_attribute());
+        assert!(!options.has_track_tree_sizes());
+        assert!(!options.has_quiet_accessors());
+        assert!(!options.has_use_element_index());
+        assert!(!options.has_track_provenance());
 
         assert_eq!(format!("{}", options), r"ProcessingOptions {}".to_string());
-        assert_eq!(format!("{:b}", options), r"00000000".to_string());
-        assert_eq!(format!("{:#b}", options), r"0b00000000".to_string());
+        assert_eq!(format!("{:b}", options), r"0000000000000000".to_string());
+        assert_eq!(format!("{:#b}", options), r"0b0000000000000000".to_string());
 
         let new_options = ProcessingOptions::new();
         assert_eq!(options, new_options);
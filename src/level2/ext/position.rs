@@ -0,0 +1,128 @@
+/*!
+Provides the [`Position`](struct.Position.html) structure, identifying a location in the text
+from which a document was parsed. Positions are attached to the errors returned by the parser,
+and to the nodes it creates when
+[`ParseOptions::set_track_positions`](../../../parser/options/struct.ParseOptions.html#method.set_track_positions)
+is set; see the [`SourcePosition`](../trait.SourcePosition.html) trait.
+
+The [`Span`](struct.Span.html) structure, a pair of positions, identifies the text of a tag, a
+name, or an attribute value, recorded by the parser when
+[`ParseOptions::set_track_spans`](../../../parser/options/struct.ParseOptions.html#method.set_track_spans)
+is set; see the [`SourceSpans`](../trait.SourceSpans.html) trait.
+*/
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Range;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A position in the input, as a byte offset and as a line and column. Lines and columns are
+/// numbered from 1, and columns are counted in bytes from the start of the line.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Position {
+    byte: u64,
+    line: u64,
+    column: u64,
+}
+
+///
+/// A range of the input, from the position of its first byte up to, but not including, the
+/// position of the byte following it.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+    start: Position,
+    end: Position,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+impl Position {
+    ///
+    /// Construct a new position; this is only needed by an
+    /// [`XmlTokenSource`](../../../parser/source/trait.XmlTokenSource.html) other than the one
+    /// provided, which must number lines and columns from 1.
+    ///
+    pub fn new(byte: u64, line: u64, column: u64) -> Self {
+        Self { byte, line, column }
+    }
+    ///
+    /// Returns the offset, in bytes, from the start of the input.
+    ///
+    pub fn byte(&self) -> u64 {
+        self.byte
+    }
+    ///
+    /// Returns the line number, starting at 1.
+    ///
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+    ///
+    /// Returns the column number, in bytes, starting at 1.
+    ///
+    pub fn column(&self) -> u64 {
+        self.column
+    }
+
+    ///
+    /// Returns the line of `input` containing this position, followed by a line with a `^`
+    /// marking the column; or `None` if `input` does not contain this position.
+    ///
+    #[cfg(feature = "quick_parser")]
+    pub(crate) fn snippet(&self, input: &str) -> Option<String> {
+        let line = input.split('\n').nth(self.line as usize - 1)?;
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let column = (self.column as usize - 1).min(line.len());
+        let prefix = line.get(..column).unwrap_or(line);
+        Some(format!("{}\n{}^", line, " ".repeat(prefix.chars().count())))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} to {}", self.start, self.end)
+    }
+}
+
+impl Span {
+    ///
+    /// Construct a new span from `start` up to, but not including, `end`.
+    ///
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+    ///
+    /// Returns the position of the first byte in the span.
+    ///
+    pub fn start(&self) -> Position {
+        self.start
+    }
+    ///
+    /// Returns the position of the byte following the span.
+    ///
+    pub fn end(&self) -> Position {
+        self.end
+    }
+    ///
+    /// Returns the span as a range of byte offsets, for example to slice, or replace, the text of
+    /// the input.
+    ///
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start.byte as usize..self.end.byte as usize
+    }
+}
@@ -0,0 +1,613 @@
+/*!
+Provides a writer that keeps the formatting of a parsed document, re-emitting the nodes that have
+not been modified since parsing exactly as they were written in the input, and serializing only
+those that have; so that a tool editing a human-maintained file produces a minimal diff.
+
+The document must have been parsed with
+[`ParseOptions::set_track_spans`](../../../parser/options/struct.ParseOptions.html#method.set_track_spans),
+and the same input passed to [`write_preserving`](fn.write_preserving.html). A node is
+unmodified if its own content, and the list of its children, are as they were when parsing
+finished, and the same is true of all of its descendants. For a modified element:
+
+* the start tag is kept if its name and attributes are unchanged; otherwise, if only the
+  attributes changed, the values of changed attributes are replaced, removed attributes are cut
+  out, and added attributes are written after the last one kept.
+* the whitespace between children, that may not be held in text nodes if whitespace was trimmed,
+  is kept where the children on either side are still adjacent.
+* the end tag is kept, unless the element was written as an empty element tag and now has
+  children.
+
+Nodes created after parsing, and parsed nodes that have been modified, other than elements, are
+serialized as by the `Display` implementation. The [`PreserveOptions`](struct.PreserveOptions.html)
+determine the line ending used for serialized nodes, and whether nodes that are inserted, or lose
+the whitespace before them, are placed on a new line with the indentation of their neighbours.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::preserve::{preserving_to_string, PreserveOptions};
+use xml_dom::parser::{read_xml_with, ParseOptions};
+
+let xml = "<config>\n    <server  port = '80'/>\n    <!-- keep me -->\n</config>\n";
+let mut options = ParseOptions::new();
+options.set_track_spans();
+let (document_node, _) = read_xml_with(xml, options).unwrap();
+let document = as_document(&document_node).unwrap();
+let mut server_node = document.get_elements_by_tag_name("server").remove(0);
+server_node.set_attribute("port", "8080").unwrap();
+server_node.set_attribute("host", "example.org").unwrap();
+
+assert_eq!(
+    preserving_to_string(&document_node, xml, &PreserveOptions::new()),
+    "<config>\n    <server  port = '8080' host=\"example.org\"/>\n    <!-- keep me -->\n</config>\n"
+);
+```
+
+*/
+
+use crate::level2::convert::{as_attribute, as_document, as_element};
+use crate::level2::ext::convert::as_document_decl;
+use crate::level2::ext::position::Span;
+use crate::level2::ext::writer::{node_id, WriterOptions};
+use crate::level2::node_impl::{NodeSpans, RefNode};
+use crate::level2::traits::NodeType;
+use crate::parser::source::attribute_ranges;
+use crate::shared::display::{self, NamespaceScope};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fmt::{Result, Write};
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This type encapsulates the options that affect the parts of the output that are serialized
+/// rather than copied from the input. The default is that line endings follow the input, and
+/// inserted nodes are written without any added whitespace.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PreserveOptions {
+    line_ending: LineEnding,
+    indent_inserted: bool,
+}
+
+///
+/// The line ending written in serialized nodes, see
+/// [`set_line_ending`](struct.PreserveOptions.html#method.set_line_ending).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\r\n` if the input contains it, else `\n`.
+    #[default]
+    AsInput,
+    /// Always `\n`.
+    Lf,
+    /// Always `\r\n`.
+    CrLf,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+struct Preserver<'a> {
+    source: &'a str,
+    options: &'a PreserveOptions,
+    line_ending: &'static str,
+    unmodified: HashSet<usize>,
+    output: String,
+}
+
+const LF: &str = "\n";
+const CR_LF: &str = "\r\n";
+const BYTE_ORDER_MARK: &str = "\u{FEFF}";
+const XML_DECL_PREFIX: &str = "<?xml";
+const XML_DECL_SUFFIX: &str = "?>";
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Write `node`, and all its descendants, to `writer` copying the unmodified parts from `source`,
+/// the input from which the node was parsed. A node without recorded spans, or whose spans do
+/// not fall within `source`, is serialized.
+///
+pub fn write_preserving(
+    node: &RefNode,
+    source: &str,
+    options: &PreserveOptions,
+    writer: &mut impl Write,
+) -> Result {
+    let mut preserver = Preserver::new(source, options);
+    let _safe_to_ignore = preserver.mark(node);
+    preserver.write_node(node)?;
+    writer.write_str(&preserver.output)
+}
+
+///
+/// Return `node`, and all its descendants, written as by
+/// [`write_preserving`](fn.write_preserving.html).
+///
+pub fn preserving_to_string(node: &RefNode, source: &str, options: &PreserveOptions) -> String {
+    let mut buffer = String::new();
+    let _safe_to_ignore = write_preserving(node, source, options, &mut buffer);
+    buffer
+}
+
+///
+/// Record the digests of each node, and attribute, at or below `node` that has spans.
+///
+pub(crate) fn record_digests(node: &RefNode) {
+    let mut nodes = vec![node.clone()];
+    while let Some(node) = nodes.pop() {
+        if node.borrow().i_spans.is_some() {
+            let node_digests = digests(&node);
+            if let Some(spans) = &mut node.borrow_mut().i_spans {
+                spans.digests = Some(node_digests);
+            }
+        }
+        nodes.extend(node.attributes().values());
+        nodes.extend(content(&node));
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl PreserveOptions {
+    ///
+    /// Construct a new `PreserveOptions` instance with the default options.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Returns the line ending written in serialized nodes.
+    ///
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+    ///
+    /// Set the line ending written before nodes placed on a new line, and to which the line
+    /// endings within serialized nodes are converted; so that an edited file does not mix line
+    /// endings.
+    ///
+    pub fn set_line_ending(&mut self, line_ending: LineEnding) {
+        self.line_ending = line_ending
+    }
+    ///
+    /// Returns `true` if inserted nodes will be placed on a new line, else `false`.
+    ///
+    pub fn has_indent_inserted(&self) -> bool {
+        self.indent_inserted
+    }
+    ///
+    /// Place each node inserted after parsing on a new line, with the indentation of the nearest
+    /// sibling that starts a line in the input; and place each parsed node, or end tag, that
+    /// started a line in the input, but whose preceding whitespace could not be kept, on a new
+    /// line with its original indentation.
+    ///
+    pub fn set_indent_inserted(&mut self) {
+        self.indent_inserted = true
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Preserver<'a> {
+    fn new(source: &'a str, options: &'a PreserveOptions) -> Self {
+        let line_ending = match options.line_ending() {
+            LineEnding::AsInput if source.contains(CR_LF) => CR_LF,
+            LineEnding::AsInput | LineEnding::Lf => LF,
+            LineEnding::CrLf => CR_LF,
+        };
+        Self {
+            source,
+            options,
+            line_ending,
+            unmodified: Default::default(),
+            output: String::new(),
+        }
+    }
+
+    ///
+    /// Record each node at or below `node` that is unmodified, returning `true` if `node` is.
+    ///
+    fn mark(&mut self, node: &RefNode) -> bool {
+        let mut unmodified = true;
+        for child_node in content(node) {
+            unmodified &= self.mark(&child_node);
+        }
+        let spans = node_spans(node);
+        if unmodified
+            && self.outer_range(node).is_some()
+            && spans.and_then(|spans| spans.digests) == Some(digests(node))
+        {
+            let _safe_to_ignore = self.unmodified.insert(node_id(node));
+            true
+        } else {
+            false
+        }
+    }
+
+    fn write_node(&mut self, node: &RefNode) -> Result {
+        if self.unmodified.contains(&node_id(node)) {
+            if let Some(range) = self.outer_range(node) {
+                self.output.push_str(&self.source[range]);
+                return Ok(());
+            }
+        }
+        match node.node_type() {
+            NodeType::Element => self.write_element(node),
+            NodeType::Document | NodeType::DocumentFragment => self.write_document(node),
+            _ => {
+                let mut buffer = String::new();
+                display::fmt_node(node, &WriterOptions::default(), &mut buffer)?;
+                self.push_serialized(&buffer);
+                Ok(())
+            }
+        }
+    }
+
+    fn write_element(&mut self, node: &RefNode) -> Result {
+        let spans = node_spans(node).unwrap_or_default();
+        let name = node.node_name().to_string();
+        let markup = match self.range(spans.markup) {
+            Some(markup) if self.source[markup.start + 1..].starts_with(&name) => markup,
+            _ => {
+                // an inserted, or renamed, element has its tags serialized.
+                let mut scope = NamespaceScope::default();
+                let mut buffer = String::new();
+                let name = display::fmt_element_start(
+                    as_element(node).unwrap(),
+                    &WriterOptions::default(),
+                    &mut scope,
+                    &mut buffer,
+                )?;
+                self.push_serialized(&buffer);
+                self.write_children(node, None, None)?;
+                buffer.clear();
+                display::fmt_element_end(&name, &mut scope, &mut buffer)?;
+                self.output.push_str(&buffer);
+                return Ok(());
+            }
+        };
+
+        let start_tag = if spans.digests.map(|(content, _)| content) == Some(content_digest(node)) {
+            self.source[markup.clone()].to_string()
+        } else {
+            self.rebuild_start_tag(node, &name, &markup)
+        };
+        match self.range(spans.end_tag) {
+            Some(end_tag) => {
+                self.output.push_str(&start_tag);
+                self.write_children(node, Some(markup.end), Some(end_tag.start))?;
+                self.output.push_str(&self.source[end_tag]);
+            }
+            None if !node.has_child_nodes() => self.output.push_str(&start_tag),
+            None => {
+                // an empty element tag is opened, and closed, around the new children.
+                let open = start_tag.trim_end_matches("/>").trim_end();
+                self.output.push_str(open);
+                self.output.push('>');
+                self.write_children(node, None, None)?;
+                write!(self.output, "</{}>", name)?;
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns the start tag of `node`, with the name `name`, edited from its `markup` in the
+    /// input to reflect the current attributes.
+    ///
+    fn rebuild_start_tag(&self, node: &RefNode, name: &str, markup: &Range<usize>) -> String {
+        let source = self.source;
+        let offset = markup.start + 1;
+        let mut attributes: Vec<(String, RefNode)> = node
+            .attributes()
+            .into_iter()
+            .filter(|(_, attribute)| {
+                as_attribute(attribute)
+                    .map(|attribute| attribute.specified())
+                    .unwrap_or(true)
+            })
+            .map(|(name, attribute)| (name.to_string(), attribute))
+            .collect();
+
+        let mut tag = String::new();
+        let mut position = offset + name.len();
+        tag.push_str(&source[markup.start..position]);
+        for (name_range, value_range) in attribute_ranges(&source.as_bytes()[offset..markup.end]) {
+            let name_range = offset + name_range.start..offset + name_range.end;
+            let value_range = offset + value_range.start..offset + value_range.end;
+            let index = attributes
+                .iter()
+                .position(|(name, _)| *name == source[name_range.clone()]);
+            match index.map(|index| attributes.remove(index)) {
+                Some((_, attribute)) => {
+                    tag.push_str(&source[position..value_range.start]);
+                    let spans = node_spans(&attribute).unwrap_or_default();
+                    if self.range(spans.value).as_ref() == Some(&value_range)
+                        && spans.digests.map(|(content, _)| content)
+                            == Some(content_digest(&attribute))
+                    {
+                        tag.push_str(&source[value_range.clone()]);
+                    } else {
+                        // the value is held escaped, as it is written by `Display`.
+                        let value = as_attribute(&attribute)
+                            .ok()
+                            .and_then(|attribute| attribute.value())
+                            .unwrap_or_default();
+                        tag.push_str(&value);
+                    }
+                    // the closing quote.
+                    position = value_range.end + 1;
+                    tag.push_str(&source[value_range.end..position]);
+                }
+                None => {
+                    // remove the attribute, and the whitespace before it.
+                    let removed_start = source[position..name_range.start].trim_end().len();
+                    tag.push_str(&source[position..position + removed_start]);
+                    position = value_range.end + 1;
+                }
+            }
+        }
+        for (_, attribute) in attributes {
+            tag.push(' ');
+            let _safe_to_ignore =
+                display::fmt_node(&attribute, &WriterOptions::default(), &mut tag);
+        }
+        tag.push_str(&source[position..markup.end]);
+        tag
+    }
+
+    fn write_document(&mut self, node: &RefNode) -> Result {
+        let spans = node_spans(node).unwrap_or_default();
+        let (content_start, content_end) = match self.range(spans.markup) {
+            Some(markup) => {
+                let prolog_end = self.prolog_end(markup.start);
+                if spans.digests.map(|(content, _)| content) == Some(content_digest(node)) {
+                    self.output.push_str(&self.source[markup.start..prolog_end]);
+                } else {
+                    self.write_declaration(node)?;
+                }
+                (Some(prolog_end), Some(markup.end))
+            }
+            None => {
+                self.write_declaration(node)?;
+                (None, None)
+            }
+        };
+        self.write_children(node, content_start, content_end)
+    }
+
+    fn write_declaration(&mut self, node: &RefNode) -> Result {
+        if let Some(xml_declaration) = as_document_decl(node)
+            .ok()
+            .and_then(|document| document.xml_declaration())
+        {
+            write!(self.output, "{}", xml_declaration)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Write the children of `node`, with the whitespace between those from the input; `start`
+    /// and `end` are the offsets in the input at which its content starts and ends, if they are
+    /// being kept.
+    ///
+    fn write_children(
+        &mut self,
+        node: &RefNode,
+        start: Option<usize>,
+        end: Option<usize>,
+    ) -> Result {
+        let children = content(node);
+        let mut cursor = start;
+        for (index, child_node) in children.iter().enumerate() {
+            let range = self.outer_range(child_node);
+            match &range {
+                Some(range) => self.separate(cursor, range.start),
+                None if self.options.has_indent_inserted() => {
+                    let indentation = children[..index]
+                        .iter()
+                        .rev()
+                        .chain(children[index + 1..].iter())
+                        .find_map(|sibling| {
+                            self.outer_range(sibling)
+                                .and_then(|range| self.indentation(range.start))
+                        });
+                    if let Some(indentation) = indentation {
+                        self.new_line(indentation);
+                    }
+                }
+                None => {}
+            }
+            self.write_node(child_node)?;
+            cursor = range.map(|range| range.end);
+        }
+        if let Some(end) = end {
+            self.separate(cursor, end);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Write the input between `cursor`, where the last node written ended in the input, and
+    /// `start` if it is only whitespace; otherwise, if `start` began a line, start a new line if
+    /// inserted nodes are indented.
+    ///
+    fn separate(&mut self, cursor: Option<usize>, start: usize) {
+        if let Some(between) = cursor.and_then(|cursor| self.source.get(cursor..start)) {
+            if between.chars().all(|c| c.is_ascii_whitespace()) {
+                self.output.push_str(between);
+                return;
+            }
+        }
+        if self.options.has_indent_inserted() {
+            if let Some(indentation) = self.indentation(start) {
+                self.new_line(indentation);
+            }
+        }
+    }
+
+    ///
+    /// Ensure that the output is at the start of a line, replacing any spaces or tabs already
+    /// written on the current line, and write `indentation`.
+    ///
+    fn new_line(&mut self, indentation: &str) {
+        let line_start = self.output.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        if self.output[line_start..]
+            .chars()
+            .all(|c| matches!(c, ' ' | '\t'))
+        {
+            self.output.truncate(line_start);
+        } else {
+            self.output.push_str(self.line_ending);
+        }
+        self.output.push_str(indentation);
+    }
+
+    fn push_serialized(&mut self, text: &str) {
+        let text = text.replace(CR_LF, LF);
+        if self.line_ending == LF {
+            self.output.push_str(&text);
+        } else {
+            self.output.push_str(&text.replace(LF, self.line_ending));
+        }
+    }
+
+    ///
+    /// Returns the spaces, and tabs, before `start` on its line in the input, if it is the
+    /// first markup on the line.
+    ///
+    fn indentation(&self, start: usize) -> Option<&'a str> {
+        let source = self.source;
+        let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let indentation = &source[line_start..start];
+        indentation
+            .chars()
+            .all(|c| matches!(c, ' ' | '\t'))
+            .then_some(indentation)
+    }
+
+    ///
+    /// Returns the offset following any byte order mark, and XML or text declaration, at
+    /// `start` in the input.
+    ///
+    fn prolog_end(&self, start: usize) -> usize {
+        let mut end = start;
+        if self.source[end..].starts_with(BYTE_ORDER_MARK) {
+            end += BYTE_ORDER_MARK.len();
+        }
+        let rest = &self.source[end..];
+        if rest.starts_with(XML_DECL_PREFIX)
+            && rest[XML_DECL_PREFIX.len()..].starts_with(|c: char| c.is_ascii_whitespace())
+        {
+            if let Some(length) = rest.find(XML_DECL_SUFFIX) {
+                end += length + XML_DECL_SUFFIX.len();
+            }
+        }
+        end
+    }
+
+    ///
+    /// Returns the range of the input from which all of `node` was parsed.
+    ///
+    fn outer_range(&self, node: &RefNode) -> Option<Range<usize>> {
+        let spans = node_spans(node)?;
+        let markup = self.range(spans.markup)?;
+        match self.range(spans.end_tag) {
+            Some(end_tag) if markup.end <= end_tag.start => Some(markup.start..end_tag.end),
+            Some(_) => None,
+            None => Some(markup),
+        }
+    }
+
+    ///
+    /// Returns the byte range of `span`, if it falls within the input.
+    ///
+    fn range(&self, span: Option<Span>) -> Option<Range<usize>> {
+        let range = span?.byte_range();
+        self.source.get(range.clone()).map(|_| range)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn node_spans(node: &RefNode) -> Option<NodeSpans> {
+    node.borrow().i_spans.as_deref().cloned()
+}
+
+///
+/// Returns the children of `node`, and for a document its document type, in the order in which
+/// they appear in the input.
+///
+fn content(node: &RefNode) -> Vec<RefNode> {
+    let mut children = node.child_nodes();
+    if let Some(doc_type) = as_document(node)
+        .ok()
+        .and_then(|document| document.doc_type())
+    {
+        let start = node_spans(&doc_type).and_then(|spans| spans.markup);
+        let index = children
+            .iter()
+            .position(|child_node| {
+                match (start, node_spans(child_node).and_then(|spans| spans.markup)) {
+                    (Some(start), Some(child_start)) => {
+                        child_start.start().byte() > start.start().byte()
+                    }
+                    _ => true,
+                }
+            })
+            .unwrap_or(children.len());
+        children.insert(index, doc_type);
+    }
+    children
+}
+
+fn digests(node: &RefNode) -> (u64, u64) {
+    let mut hasher = DefaultHasher::new();
+    for child_node in content(node) {
+        node_id(&child_node).hash(&mut hasher);
+    }
+    (content_digest(node), hasher.finish())
+}
+
+///
+/// Returns a digest of the content of `node` itself: the name and attributes of an element, the
+/// declaration of a document, or the serialized form of any other node.
+///
+fn content_digest(node: &RefNode) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    match node.node_type() {
+        NodeType::Element => {
+            node.node_name().to_string().hash(&mut hasher);
+            for attribute in node.attributes().values() {
+                attribute.to_string().hash(&mut hasher);
+                as_attribute(&attribute)
+                    .map(|attribute| attribute.specified())
+                    .unwrap_or(true)
+                    .hash(&mut hasher);
+            }
+        }
+        NodeType::Document => as_document_decl(node)
+            .ok()
+            .and_then(|document| document.xml_declaration())
+            .map(|xml_declaration| xml_declaration.to_string())
+            .hash(&mut hasher),
+        NodeType::DocumentFragment => {}
+        _ => node.to_string().hash(&mut hasher),
+    }
+    hasher.finish()
+}
@@ -0,0 +1,253 @@
+/*!
+Provides the implementation of the [`NodeProvenance`](../trait.NodeProvenance.html) trait, records
+of the element each element was derived from, so that a pipeline can explain how each element of
+its output was produced from its inputs.
+
+When a document is created with the
+[`set_track_provenance`](../options/struct.ProcessingOptions.html#method.set_track_provenance)
+processing option, each element created by `clone_node` or `import_node` in that document records
+its source element and the [`Operation`](enum.Operation.html) used. Other transformations may
+record their own provenance with
+[`record_provenance`](../trait.NodeProvenance.html#tymethod.record_provenance), in any document.
+
+A [`Provenance`](struct.Provenance.html) record holds the identifier and path of the source
+element, taken when the element was derived, as well as a weak reference to the source itself;
+the record still describes the source after it has been changed, moved, or dropped.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::dom_impl::get_implementation_ext;
+use xml_dom::level2::ext::{NodeProvenance, Operation, ProcessingOptions};
+
+let mut options = ProcessingOptions::new();
+options.set_track_provenance();
+let document_node = get_implementation_ext()
+    .create_document_with_options(None, Some("root"), None, options)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let item_node = root_node
+    .append_child(document.create_element("item").unwrap())
+    .unwrap();
+
+let copy_node = item_node.clone_node(true).unwrap();
+let provenance = copy_node.provenance().unwrap();
+assert_eq!(provenance.operation(), &Operation::Clone);
+assert_eq!(provenance.source_path(), "/root/item[1]");
+assert_eq!(provenance.source(), Some(item_node.clone()));
+assert!(item_node.provenance().is_none());
+```
+
+*/
+
+use crate::diagnostics::{INVALID_NODE_TYPE, INVALID_STATE};
+use crate::level2::convert::{is_document, is_element};
+use crate::level2::ext::traits::NodeProvenance;
+use crate::level2::ext::writer::{node_id, node_path};
+use crate::level2::node_impl::{Extension, RefNode, WeakRefNode};
+use crate::level2::trait_impls::document_options;
+use crate::shared::error::{Error, Result};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The operation by which an element was derived from its source.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// The element was created by `Node::clone_node`.
+    Clone,
+    /// The element was created by `Document::import_node`.
+    Import,
+    /// The element was created by a client transformation, described by the string.
+    Transform(String),
+}
+
+///
+/// A record of the element from which an element was derived.
+///
+#[derive(Clone, Debug)]
+pub struct Provenance {
+    source: WeakRefNode,
+    source_id: usize,
+    source_path: String,
+    operation: Operation,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Crate Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The provenance of the elements of a document, keyed by node identity; the entries for
+/// dropped elements are removed once the map has doubled in size since they were last removed.
+///
+#[doc(hidden)]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProvenanceMap {
+    entries: HashMap<usize, (WeakRefNode, Provenance)>,
+    purge_at: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Crate Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Called once `new_node` has been created from `source` by `operation`; records its provenance
+/// if `new_node` is an element owned by a document that tracks provenance.
+///
+pub(crate) fn node_derived(new_node: &RefNode, source: &RefNode, operation: Operation) {
+    if is_element(new_node) && document_options(new_node).has_track_provenance() {
+        if let Some(document_node) = new_node.owner_document() {
+            record(&document_node, new_node, source, operation);
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Operation::Clone => write!(f, "clone"),
+            Operation::Import => write!(f, "import"),
+            Operation::Transform(description) => write!(f, "transform ({})", description),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} of {} (id {})",
+            self.operation, self.source_path, self.source_id
+        )
+    }
+}
+
+impl Provenance {
+    ///
+    /// Returns the source element, if it has not been dropped.
+    ///
+    pub fn source(&self) -> Option<RefNode> {
+        self.source.clone().upgrade()
+    }
+    ///
+    /// Returns the identifier of the source element, as returned by
+    /// [`node_id`](../writer/fn.node_id.html), when the element was derived.
+    ///
+    pub fn source_id(&self) -> usize {
+        self.source_id
+    }
+    ///
+    /// Returns the path of the source element, as returned by
+    /// [`node_path`](../writer/fn.node_path.html), when the element was derived.
+    ///
+    pub fn source_path(&self) -> &str {
+        &self.source_path
+    }
+    ///
+    /// Returns the operation by which the element was derived.
+    ///
+    pub fn operation(&self) -> &Operation {
+        &self.operation
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ProvenanceMap {
+    fn insert(&mut self, node: &RefNode, provenance: Provenance) {
+        if self.entries.len() >= self.purge_at {
+            self.entries.retain(|_, (node, _)| !node.is_dropped());
+            self.purge_at = (self.entries.len() * 2).max(PURGE_MINIMUM);
+        }
+        let _safe_to_ignore = self
+            .entries
+            .insert(node_id(node), (node.clone().downgrade(), provenance));
+    }
+
+    fn get(&self, node: &RefNode) -> Option<&Provenance> {
+        // while the map holds a weak reference to an element its address can't be reused, so
+        // a live element never matches a dropped element's entry.
+        self.entries
+            .get(&node_id(node))
+            .map(|(_, provenance)| provenance)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NodeProvenance for RefNode {
+    fn provenance(&self) -> Option<Provenance> {
+        let document_node = self.owner_document()?;
+        let ref_document = document_node.borrow();
+        if let Extension::Document { i_provenance, .. } = &ref_document.i_extension {
+            i_provenance.get(self).cloned()
+        } else {
+            None
+        }
+    }
+
+    fn provenance_chain(&self) -> Vec<Provenance> {
+        let mut chain: Vec<Provenance> = Vec::new();
+        let mut current = self.provenance();
+        while let Some(provenance) = current {
+            current = provenance
+                .source()
+                .filter(|source| !chain.iter().any(|p| p.source_id == node_id(source)))
+                .and_then(|source| source.provenance());
+            chain.push(provenance);
+        }
+        chain
+    }
+
+    fn record_provenance(&mut self, source: &RefNode, operation: Operation) -> Result<()> {
+        if !is_element(self) || !is_element(source) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        match self.owner_document() {
+            Some(document_node) if !is_document(self) => {
+                record(&document_node, self, source, operation);
+                Ok(())
+            }
+            _ => {
+                warn!("{}", INVALID_STATE);
+                Err(Error::InvalidState)
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const PURGE_MINIMUM: usize = 64;
+
+fn record(document_node: &RefNode, node: &RefNode, source: &RefNode, operation: Operation) {
+    let provenance = Provenance {
+        source: source.clone().downgrade(),
+        source_id: node_id(source),
+        source_path: node_path(source),
+        operation,
+    };
+    let mut mut_document = document_node.borrow_mut();
+    if let Extension::Document { i_provenance, .. } = &mut mut_document.i_extension {
+        i_provenance.insert(node, provenance);
+    }
+}
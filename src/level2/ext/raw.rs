@@ -0,0 +1,350 @@
+/*!
+Provides a read-only view of the storage behind each node, for profilers, debuggers, and
+migration tools; this module is only available with the `internals` feature.
+
+The internal representation of a node may change between releases, the structures here are a
+stable summary of it. [`inspect`](fn.inspect.html) returns a [`RawNode`](struct.RawNode.html)
+for a single node, with the state of its weak references, reference counts, and the variant of
+its type-specific [`RawExtension`](enum.RawExtension.html); [`inspect_tree`](fn.inspect_tree.html)
+returns the same for every node held, by strong reference, below a node. New fields and variants
+may be added, so these types are `non_exhaustive`.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::raw::{inspect, inspect_tree, RawExtension, WeakRefState};
+
+let document_node = get_implementation()
+    .create_document(None, Some("root"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+root_node.set_attribute("id", "main").unwrap();
+
+let raw = inspect(&root_node);
+assert_eq!(raw.name, "root");
+assert!(matches!(raw.parent_node, WeakRefState::Live(_)));
+assert!(matches!(raw.extension, RawExtension::Element { attribute_count: 1, .. }));
+
+// the document, its root element, the attribute, and the attribute's text.
+assert_eq!(inspect_tree(&document_node).len(), 4);
+```
+
+*/
+
+use crate::level2::ext::character_reference::CharacterReference;
+use crate::level2::ext::position::Position;
+use crate::level2::ext::writer::node_id;
+use crate::level2::ext::{ProcessingOptions, XmlDecl};
+use crate::level2::node_impl::{Extension, NodeImpl, RefNode, WeakRefNode};
+use crate::level2::traits::NodeType;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A summary of the storage of a single node.
+///
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RawNode {
+    /// The identifier of the node, as returned by
+    /// [`writer::node_id`](../writer/fn.node_id.html).
+    pub id: usize,
+    /// The type of the node.
+    pub node_type: NodeType,
+    /// The qualified name of the node.
+    pub name: String,
+    /// The value stored directly in the node; for attributes the value is held in child text
+    /// nodes.
+    pub value: Option<String>,
+    /// The number of strong references to the node, including those held by the tree.
+    pub strong_count: usize,
+    /// The number of weak references to the node, including those held by the tree.
+    pub weak_count: usize,
+    /// The reference to the parent of the node.
+    pub parent_node: WeakRefState,
+    /// The reference to the document that owns the node.
+    pub owner_document: WeakRefState,
+    /// The number of child nodes.
+    pub child_count: usize,
+    /// The number of child nodes space is allocated for.
+    pub child_capacity: usize,
+    /// The number of freezes covering the node, it is read-only if this is non-zero.
+    pub frozen: usize,
+    /// The position in the parsed input at which the node started, if tracked by the parser.
+    pub position: Option<Position>,
+    /// The storage specific to the type of node.
+    pub extension: RawExtension,
+}
+
+///
+/// The state of a weak reference held by a node.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeakRefState {
+    /// No reference is held.
+    Unset,
+    /// The referenced node is alive, with this identifier.
+    Live(usize),
+    /// The referenced node has been dropped.
+    Dropped,
+}
+
+///
+/// The storage specific to the type of a node.
+///
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum RawExtension {
+    /// The node has no type-specific storage.
+    None,
+    /// An `Attribute` node.
+    #[non_exhaustive]
+    Attribute {
+        /// The reference to the element the attribute is set on.
+        owner_element: WeakRefState,
+        /// `false` if the attribute holds a default value that has not been set.
+        specified: bool,
+    },
+    /// A `Document` node.
+    #[non_exhaustive]
+    Document {
+        /// The XML declaration, if any.
+        xml_declaration: Option<XmlDecl>,
+        /// `true` if the document has a document type.
+        has_document_type: bool,
+        /// The number of entries in the ID map.
+        id_map_entries: usize,
+        /// The number of entries in the ID map whose element has been dropped.
+        dropped_id_map_entries: usize,
+        /// The attributes registered as ID attributes, as namespace and local name, sorted.
+        id_attributes: Vec<(Option<String>, String)>,
+        /// The attributes indexed by the parser, sorted.
+        indexed_attributes: Vec<String>,
+        /// The processing options of the document.
+        options: ProcessingOptions,
+    },
+    /// A `DocumentType` node.
+    #[non_exhaustive]
+    DocumentType {
+        /// The number of entity nodes.
+        entity_count: usize,
+        /// The number of notation nodes.
+        notation_count: usize,
+        /// The public identifier, if any.
+        public_id: Option<String>,
+        /// The system identifier, if any.
+        system_id: Option<String>,
+        /// The internal subset captured by the parser, if any.
+        internal_subset: Option<String>,
+    },
+    /// An `Element` node.
+    #[non_exhaustive]
+    Element {
+        /// The number of attribute nodes.
+        attribute_count: usize,
+        /// The namespace mappings declared on the element, as prefix and namespace, sorted.
+        namespaces: Vec<(Option<String>, String)>,
+    },
+    /// An `Entity` node.
+    #[non_exhaustive]
+    Entity {
+        /// The public identifier, if any.
+        public_id: Option<String>,
+        /// The system identifier, if any.
+        system_id: Option<String>,
+        /// The notation name of an unparsed entity.
+        notation_name: Option<String>,
+    },
+    /// A `Notation` node.
+    #[non_exhaustive]
+    Notation {
+        /// The public identifier, if any.
+        public_id: Option<String>,
+        /// The system identifier, if any.
+        system_id: Option<String>,
+    },
+    /// A `Text` node created by the parser.
+    #[non_exhaustive]
+    Text {
+        /// The numeric character references recorded by the parser.
+        character_references: Vec<CharacterReference>,
+    },
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns a summary of the storage of `node`.
+///
+pub fn inspect(node: &RefNode) -> RawNode {
+    let ref_node = node.borrow();
+    RawNode {
+        id: node_id(node),
+        node_type: ref_node.i_node_type.clone(),
+        name: ref_node.i_name.to_string(),
+        value: ref_node.i_value.clone(),
+        strong_count: node.strong_count(),
+        weak_count: node.weak_count(),
+        parent_node: weak_ref_state(&ref_node.i_parent_node),
+        owner_document: weak_ref_state(&ref_node.i_owner_document),
+        child_count: ref_node.i_child_nodes.len(),
+        child_capacity: ref_node.i_child_nodes.capacity(),
+        frozen: ref_node.i_frozen,
+        position: ref_node.i_position,
+        extension: raw_extension(&ref_node),
+    }
+}
+
+///
+/// Returns a summary of the storage of `node`, and of every node it holds by strong reference,
+/// depth first. The document type follows its document; entities and notations follow their
+/// document type, and attributes their element, each in order of name; child nodes follow in
+/// order.
+///
+pub fn inspect_tree(node: &RefNode) -> Vec<RawNode> {
+    let mut nodes = Vec::new();
+    visit(node, &mut nodes);
+    nodes
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn visit(node: &RefNode, nodes: &mut Vec<RawNode>) {
+    nodes.push(inspect(node));
+    let ref_node = node.borrow();
+    let mut held: Vec<(String, RefNode)> = match &ref_node.i_extension {
+        Extension::Document {
+            i_document_type: Some(document_type),
+            ..
+        } => vec![(String::new(), document_type.clone())],
+        Extension::DocumentType {
+            i_entities,
+            i_notations,
+            ..
+        } => i_entities
+            .iter()
+            .chain(i_notations.iter())
+            .map(|(name, node)| (name.to_string(), node.clone()))
+            .collect(),
+        Extension::Element { i_attributes, .. } => i_attributes
+            .iter()
+            .map(|(name, node)| (name.to_string(), node.clone()))
+            .collect(),
+        _ => Vec::new(),
+    };
+    held.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+    for child_node in held
+        .into_iter()
+        .map(|(_, node)| node)
+        .chain(ref_node.i_child_nodes.iter().cloned())
+    {
+        visit(&child_node, nodes);
+    }
+}
+
+fn weak_ref_state(reference: &Option<WeakRefNode>) -> WeakRefState {
+    match reference {
+        None => WeakRefState::Unset,
+        Some(reference) => match reference.clone().upgrade() {
+            None => WeakRefState::Dropped,
+            Some(node) => WeakRefState::Live(node_id(&node)),
+        },
+    }
+}
+
+fn raw_extension(node: &NodeImpl) -> RawExtension {
+    match &node.i_extension {
+        Extension::None => RawExtension::None,
+        Extension::Attribute {
+            i_owner_element,
+            i_specified,
+        } => RawExtension::Attribute {
+            owner_element: weak_ref_state(i_owner_element),
+            specified: *i_specified,
+        },
+        Extension::Document {
+            i_xml_declaration,
+            i_document_type,
+            i_id_map,
+            i_id_attributes,
+            i_element_index,
+            i_options,
+            ..
+        } => {
+            let mut id_attributes: Vec<(Option<String>, String)> =
+                i_id_attributes.iter().cloned().collect();
+            id_attributes.sort();
+            let mut indexed_attributes: Vec<String> = i_element_index.keys().cloned().collect();
+            indexed_attributes.sort();
+            RawExtension::Document {
+                xml_declaration: i_xml_declaration.clone(),
+                has_document_type: i_document_type.is_some(),
+                id_map_entries: i_id_map.len(),
+                dropped_id_map_entries: i_id_map
+                    .values()
+                    .filter(|element| element.is_dropped())
+                    .count(),
+                id_attributes,
+                indexed_attributes,
+                options: i_options.clone(),
+            }
+        }
+        Extension::DocumentType {
+            i_entities,
+            i_notations,
+            i_public_id,
+            i_system_id,
+            i_internal_subset,
+        } => RawExtension::DocumentType {
+            entity_count: i_entities.len(),
+            notation_count: i_notations.len(),
+            public_id: i_public_id.clone(),
+            system_id: i_system_id.clone(),
+            internal_subset: i_internal_subset.clone(),
+        },
+        Extension::Element {
+            i_attributes,
+            i_namespaces,
+        } => {
+            let mut namespaces: Vec<(Option<String>, String)> = i_namespaces
+                .iter()
+                .map(|(prefix, namespace_uri)| (prefix.clone(), namespace_uri.clone()))
+                .collect();
+            namespaces.sort();
+            RawExtension::Element {
+                attribute_count: i_attributes.len(),
+                namespaces,
+            }
+        }
+        Extension::Entity {
+            i_public_id,
+            i_system_id,
+            i_notation_name,
+        } => RawExtension::Entity {
+            public_id: i_public_id.clone(),
+            system_id: i_system_id.clone(),
+            notation_name: i_notation_name.clone(),
+        },
+        Extension::Notation {
+            i_public_id,
+            i_system_id,
+        } => RawExtension::Notation {
+            public_id: i_public_id.clone(),
+            system_id: i_system_id.clone(),
+        },
+        Extension::Text {
+            i_character_references,
+        } => RawExtension::Text {
+            character_references: i_character_references.clone(),
+        },
+    }
+}
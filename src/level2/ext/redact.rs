@@ -0,0 +1,322 @@
+/*!
+Provides the [`redact`](fn.redact.html) function, replacing the values of sensitive attributes and
+elements with a placeholder, so that a document such as a configuration file may be shared
+without the credentials it contains.
+
+Each [`RedactionRule`](struct.RedactionRule.html) selects elements, by name or, with the
+`selectors` feature, by a [`Selector`](../selectors/struct.Selector.html); and redacts either
+their content or one of their attributes. A name is compared with the qualified name of an element
+or attribute and, if it doesn't match, with its local name; so `password` matches a
+`cfg:password` attribute. The content of a redacted element is replaced by a single text node
+holding the placeholder, which is `[REDACTED]` unless the rule sets another.
+
+The [`RedactionReport`](struct.RedactionReport.html) returned lists the path of each attribute or
+element redacted, and the rule that redacted it; it never includes the values replaced.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::redact::{redact, RedactionRule};
+
+let mut document_node = get_implementation()
+    .create_document(None, Some("config"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let mut database_node = root_node
+    .append_child(document.create_element("database").unwrap())
+    .unwrap();
+database_node.set_attribute("user", "admin").unwrap();
+database_node.set_attribute("password", "hunter2").unwrap();
+let mut token_node = root_node
+    .append_child(document.create_element("token").unwrap())
+    .unwrap();
+let _ = token_node
+    .append_child(document.create_text_node("0123456789abcdef"))
+    .unwrap();
+
+let report = redact(
+    &mut document_node,
+    &[
+        RedactionRule::attribute("password"),
+        RedactionRule::element("token").with_placeholder("***"),
+    ],
+)
+.unwrap();
+assert_eq!(
+    root_node.to_string(),
+    r#"<config><database user="admin" password="[REDACTED]"></database><token>***</token></config>"#
+);
+assert_eq!(
+    report.to_string(),
+    "/config/database[1]/@password: redacted by rule 0\n/config/token[1]: redacted by rule 1"
+);
+```
+
+*/
+
+use crate::level2::convert::{as_document, is_element};
+use crate::level2::ext::writer::node_path;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Element, Node};
+use crate::shared::error::{Error, Result};
+use crate::shared::name::Name;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[cfg(feature = "selectors")]
+use crate::level2::ext::selectors::Selector;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Selects the elements, and optionally the attribute of those elements, to redact.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RedactionRule {
+    elements: ElementTest,
+    attribute: Option<String>,
+    placeholder: String,
+}
+
+///
+/// A single attribute, or element, redacted.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Redaction {
+    /// The path of the element redacted, or of the element holding the attribute redacted, as
+    /// returned by [`node_path`](../writer/fn.node_path.html).
+    pub path: String,
+    /// The qualified name of the attribute redacted, or `None` if the element's content was
+    /// redacted.
+    pub attribute: Option<String>,
+    /// The index, in the rules given to `redact`, of the rule that matched.
+    pub rule: usize,
+}
+
+///
+/// The redactions made by [`redact`](fn.redact.html), in document order.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RedactionReport {
+    redactions: Vec<Redaction>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ElementTest {
+    Any,
+    Name(String),
+    #[cfg(feature = "selectors")]
+    Selector(Selector),
+}
+
+const DEFAULT_PLACEHOLDER: &str = "[REDACTED]";
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Redact the attributes and elements, in the tree below and including `node`, matched by any of
+/// `rules`; where more than one rule matches the first is used, and nothing within an element whose
+/// content is redacted is redacted separately. The nodes to redact are found
+/// before any are changed, so that a rule never matches the placeholder written by another.
+///
+/// This returns `Error::NoModificationAllowed` if a node to redact is frozen, in which case the
+/// redactions preceding it in document order have already been made.
+///
+pub fn redact(node: &mut RefNode, rules: &[RedactionRule]) -> Result<RedactionReport> {
+    let mut pending: Vec<(RefNode, Option<Name>, usize)> = Vec::new();
+    let mut elements = vec![node.clone()];
+    while let Some(element) = elements.pop() {
+        let mut children = element.child_nodes();
+        if !is_element(&element) {
+            elements.extend(children.into_iter().rev());
+            continue;
+        }
+        let matching: Vec<(usize, &RedactionRule)> = rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.elements.matches(&element))
+            .collect();
+        for (name, _) in element.attributes() {
+            if let Some((index, _)) = matching.iter().find(|(_, rule)| {
+                rule.attribute
+                    .as_ref()
+                    .is_some_and(|attribute| name_matches(&name, attribute))
+            }) {
+                pending.push((element.clone(), Some(name), *index));
+            }
+        }
+        if !children.is_empty() {
+            // the descendants of an element whose content is redacted are not visited.
+            if let Some((index, _)) = matching.iter().find(|(_, rule)| rule.attribute.is_none()) {
+                pending.push((element.clone(), None, *index));
+                children.clear();
+            }
+        }
+        elements.extend(children.into_iter().rev());
+    }
+
+    let mut report = RedactionReport::default();
+    for (mut element, name, index) in pending {
+        let placeholder = &rules[index].placeholder;
+        let path = node_path(&element);
+        match &name {
+            None => redact_content(&mut element, placeholder)?,
+            Some(name) => match name.namespace_uri() {
+                None => element.set_attribute(&name.to_string(), placeholder)?,
+                Some(namespace_uri) => {
+                    element.set_attribute_ns(namespace_uri, &name.to_string(), placeholder)?
+                }
+            },
+        }
+        report.redactions.push(Redaction {
+            path,
+            attribute: name.map(|name| name.to_string()),
+            rule: index,
+        });
+    }
+    Ok(report)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl RedactionRule {
+    ///
+    /// A rule redacting the attribute `name` of any element.
+    ///
+    pub fn attribute(name: &str) -> Self {
+        Self {
+            elements: ElementTest::Any,
+            attribute: Some(name.to_string()),
+            placeholder: DEFAULT_PLACEHOLDER.to_string(),
+        }
+    }
+    ///
+    /// A rule redacting the content of elements named `name`.
+    ///
+    pub fn element(name: &str) -> Self {
+        Self {
+            elements: ElementTest::Name(name.to_string()),
+            attribute: None,
+            placeholder: DEFAULT_PLACEHOLDER.to_string(),
+        }
+    }
+    ///
+    /// A rule redacting the content of elements matching `selector`.
+    ///
+    #[cfg(feature = "selectors")]
+    pub fn selector(selector: Selector) -> Self {
+        Self {
+            elements: ElementTest::Selector(selector),
+            attribute: None,
+            placeholder: DEFAULT_PLACEHOLDER.to_string(),
+        }
+    }
+    ///
+    /// Redact the attribute `name` of the elements this rule matches, rather than their content.
+    ///
+    pub fn with_attribute(self, name: &str) -> Self {
+        Self {
+            attribute: Some(name.to_string()),
+            ..self
+        }
+    }
+    ///
+    /// Replace the values redacted by this rule with `placeholder`, rather than `[REDACTED]`.
+    ///
+    pub fn with_placeholder(self, placeholder: &str) -> Self {
+        Self {
+            placeholder: placeholder.to_string(),
+            ..self
+        }
+    }
+    ///
+    /// Returns the placeholder written in place of the values redacted by this rule.
+    ///
+    pub fn placeholder(&self) -> &str {
+        &self.placeholder
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Redaction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.attribute {
+            None => write!(f, "{}: redacted by rule {}", self.path, self.rule),
+            Some(name) => write!(f, "{}/@{}: redacted by rule {}", self.path, name, self.rule),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for RedactionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let lines: Vec<String> = self.redactions.iter().map(|r| r.to_string()).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl RedactionReport {
+    ///
+    /// Returns the redactions made, in document order.
+    ///
+    pub fn redactions(&self) -> &[Redaction] {
+        &self.redactions
+    }
+    ///
+    /// Returns the number of redactions made.
+    ///
+    pub fn len(&self) -> usize {
+        self.redactions.len()
+    }
+    ///
+    /// Returns `true` if nothing was redacted.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.redactions.is_empty()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ElementTest {
+    fn matches(&self, element: &RefNode) -> bool {
+        match self {
+            ElementTest::Any => true,
+            ElementTest::Name(name) => name_matches(&element.borrow().i_name, name),
+            #[cfg(feature = "selectors")]
+            ElementTest::Selector(selector) => selector.matches(element),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn name_matches(name: &Name, pattern: &str) -> bool {
+    name.to_string() == pattern || name.local_name() == pattern
+}
+
+fn redact_content(element: &mut RefNode, placeholder: &str) -> Result<()> {
+    let document_node = element.owner_document().ok_or(Error::InvalidState)?;
+    let document = as_document(&document_node)?;
+    for child_node in element.child_nodes() {
+        let _safe_to_ignore = element.remove_child(child_node)?;
+    }
+    let _safe_to_ignore = element.append_child(document.create_text_node(placeholder))?;
+    Ok(())
+}
@@ -0,0 +1,221 @@
+/*!
+Provides the [`TypeProvider`](trait.TypeProvider.html) trait, the extension point through which an
+external schema processor makes a document schema-aware, and its registration on a document with
+the [`SchemaTypes`](../trait.SchemaTypes.html) trait.
+
+This crate does not read schemas itself; by default the only attributes of type ID are `xml:id`,
+and those registered with the [`IdAttributes`](../trait.IdAttributes.html) trait. Once a type
+provider is registered on a document the types it gives to attributes, and the nillability it
+gives to elements, are used as follows.
+
+* an attribute the provider types as `ID` is an ID attribute; it is added to the map used by
+  [`get_element_by_id`](../../trait.Document.html#tymethod.get_element_by_id), and must be unique.
+* [`normalize_document`](../trait.NormalizeDocument.html#tymethod.normalize_document), with the
+  [`datatype_normalization`](../normalize/struct.NormalizationOptions.html#method.set_datatype_normalization)
+  option, collapses the white space in the values of attributes of a type other than `CDATA`, and
+  removes white space text from nillable elements with `xsi:nil="true"`.
+* [`check_document`](../validate/fn.check_document.html) reports `IDREF` and `IDREFS` values that
+  do not refer to an ID in the document, `xsi:nil` on elements that are not nillable, and nilled
+  elements that have content.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::dtd::AttributeType;
+use xml_dom::level2::ext::schema::TypeProvider;
+use xml_dom::level2::ext::sync::Shared;
+use xml_dom::level2::ext::validate::{check_document, ViolationKind};
+use xml_dom::level2::ext::SchemaTypes;
+
+#[derive(Debug)]
+struct Catalog;
+
+impl TypeProvider for Catalog {
+    fn attribute_type(&self, element_name: &Name, attribute_name: &Name) -> Option<AttributeType> {
+        match (element_name.local_name().as_str(), attribute_name.local_name().as_str()) {
+            ("item", "key") => Some(AttributeType::Id),
+            ("item", "see") => Some(AttributeType::IdRef),
+            _ => None,
+        }
+    }
+}
+
+let mut document_node = get_implementation()
+    .create_document(None, Some("catalog"), None)
+    .unwrap();
+let document_ref = document_node.clone();
+let document = as_document(&document_ref).unwrap();
+let mut root_node = document.document_element().unwrap();
+let mut item_node = root_node
+    .append_child(document.create_element("item").unwrap())
+    .unwrap();
+item_node.set_attribute("key", "i1").unwrap();
+item_node.set_attribute("see", "i2").unwrap();
+assert!(document.get_element_by_id("i1").is_none());
+
+document_node.set_type_provider(Some(Shared::new(Catalog))).unwrap();
+assert_eq!(document.get_element_by_id("i1"), Some(item_node));
+
+let violations = check_document(&document_node);
+assert_eq!(violations[0].kind, ViolationKind::DanglingIdRef("i2".to_string()));
+```
+
+*/
+
+use crate::diagnostics::{DUPLICATE_ID, INVALID_NODE_TYPE};
+use crate::level2::convert::{as_attribute, is_document, is_element};
+use crate::level2::ext::dtd::AttributeType;
+use crate::level2::ext::sync::{Shareable, Shared};
+use crate::level2::ext::traits::{Axes, Namespaced, SchemaTypes};
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::level2::trait_impls::{check_not_frozen, is_document_id_attribute};
+use crate::shared::error::{Error, Result};
+use crate::shared::name::Name;
+use crate::shared::syntax::{XSI_NS_ATTR_NIL, XSI_NS_URI};
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Implemented by a schema processor to provide the types of the attributes, and the
+/// nillability of the elements, of the documents it is registered on. Types are given using the
+/// DTD [`AttributeType`](../dtd/enum.AttributeType.html) they correspond to, so that the XML
+/// Schema types `xs:ID`, `xs:IDREF`, and `xs:IDREFS` are `Id`, `IdRef`, and `IdRefs`. Both methods
+/// are called often, and should be cheap. With the `threadsafe` feature a provider must be `Send`
+/// and `Sync`, see [`Shareable`](../sync/trait.Shareable.html).
+///
+pub trait TypeProvider: Debug + Shareable {
+    ///
+    /// Returns the type of the attribute `attribute_name` of an element `element_name`, or
+    /// `None` if the schema does not declare one; the default declares no attribute types.
+    ///
+    fn attribute_type(
+        &self,
+        _element_name: &Name,
+        _attribute_name: &Name,
+    ) -> Option<AttributeType> {
+        None
+    }
+    ///
+    /// Returns `true` if an element `element_name` may be nilled with `xsi:nil="true"`, else
+    /// `false`; the default is that no element is nillable.
+    ///
+    fn is_nillable(&self, _element_name: &Name) -> bool {
+        false
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl SchemaTypes for RefNode {
+    fn set_type_provider(&mut self, type_provider: Option<Shared<dyn TypeProvider>>) -> Result<()> {
+        check_not_frozen(self)?;
+        if !is_document(self) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let old_type_provider = replace_type_provider(self, type_provider);
+        //
+        // Rebuild the ID map with the new types, keeping the old provider if any ID is not
+        // unique.
+        //
+        let mut id_map: HashMap<String, RefNode> = HashMap::new();
+        for element in self.descendant_or_self().filter(is_element) {
+            for (name, attribute) in element.attributes() {
+                if !is_document_id_attribute(self, &element.node_name(), &name) {
+                    continue;
+                }
+                let id_value = as_attribute(&attribute)?.value().unwrap_or_default();
+                match id_map.get(&id_value) {
+                    Some(existing) if existing != &element => {
+                        warn!("{}", DUPLICATE_ID);
+                        let _safe_to_ignore = replace_type_provider(self, old_type_provider);
+                        return Err(Error::Syntax);
+                    }
+                    _ => {
+                        let _safe_to_ignore = id_map.insert(id_value, element.clone());
+                    }
+                }
+            }
+        }
+        if let Extension::Document { i_id_map, .. } = &mut self.borrow_mut().i_extension {
+            *i_id_map = id_map
+                .into_iter()
+                .map(|(id_value, element)| (id_value, element.downgrade()))
+                .collect();
+        }
+        Ok(())
+    }
+
+    fn type_provider(&self) -> Option<Shared<dyn TypeProvider>> {
+        document_type_provider(self)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Crate Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the type provider registered on `node`, if it is a document, or on its owner document.
+///
+pub(crate) fn document_type_provider(node: &RefNode) -> Option<Shared<dyn TypeProvider>> {
+    let document_node = if is_document(node) {
+        node.clone()
+    } else {
+        node.owner_document()?
+    };
+    let ref_document = document_node.borrow();
+    if let Extension::Document {
+        i_type_provider, ..
+    } = &ref_document.i_extension
+    {
+        i_type_provider.clone()
+    } else {
+        None
+    }
+}
+
+///
+/// Returns `true` if `element` has an `xsi:nil` attribute with the value `true`, or `1`.
+///
+pub(crate) fn is_nilled(element: &RefNode) -> bool {
+    element.attributes().into_iter().any(|(name, attribute)| {
+        let namespace_uri = match name.prefix() {
+            Some(prefix) => element.lookup_namespace_uri(Some(prefix)),
+            None => None,
+        };
+        name.local_name() == XSI_NS_ATTR_NIL
+            && (name.namespace_uri().as_deref() == Some(XSI_NS_URI)
+                || namespace_uri.as_deref() == Some(XSI_NS_URI))
+            && as_attribute(&attribute)
+                .ok()
+                .and_then(|attribute| attribute.value())
+                .is_some_and(|value| matches!(value.trim(), "true" | "1"))
+    })
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn replace_type_provider(
+    document_node: &RefNode,
+    type_provider: Option<Shared<dyn TypeProvider>>,
+) -> Option<Shared<dyn TypeProvider>> {
+    if let Extension::Document {
+        i_type_provider, ..
+    } = &mut document_node.borrow_mut().i_extension
+    {
+        std::mem::replace(i_type_provider, type_provider)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,106 @@
+/*!
+Provides the [`SearchMatch`](struct.SearchMatch.html) type returned by the
+[`Search`](../trait.Search.html) trait.
+
+The search visits nodes with the lazy [`descendant_or_self`](../trait.Axes.html#tymethod.descendant_or_self)
+axis, so no copy of the tree, or its serialized form, is made; only the data of each node is
+cloned, one node at a time, while it is searched.
+
+# Example
+
+```rust
+use regex::Regex;
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::Search;
+
+let document_node = get_implementation()
+    .create_document(None, Some("note"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let _ = root_node
+    .append_child(document.create_text_node("My SSN is 078-05-1120."))
+    .unwrap();
+
+// Redact each match in place, the last first so that earlier offsets remain valid.
+let ssn = Regex::new(r"\d{3}-\d{2}-\d{4}").unwrap();
+for found in document_node.find_text(&ssn).into_iter().rev() {
+    let mut text_node = found.node.clone();
+    text_node
+        .replace_data(found.start, found.end - found.start, "[REDACTED]")
+        .unwrap();
+}
+assert_eq!(root_node.to_string(), "<note>My SSN is [REDACTED].</note>");
+```
+
+*/
+
+use crate::level2::convert::{as_attribute, is_element};
+use crate::level2::ext::traits::{Axes, Search};
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::NodeType;
+use regex::Regex;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A single match found by the [`Search`](../trait.Search.html) trait.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchMatch {
+    /// The `Text`, `CDataSection`, or `Attribute` node containing the match.
+    pub node: RefNode,
+    /// The byte offset of the start of the match within the node's data, or attribute value.
+    pub start: usize,
+    /// The byte offset of the end of the match, exclusive.
+    pub end: usize,
+    /// The matched text.
+    pub text: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Search for RefNode {
+    fn find_text(&self, regex: &Regex) -> Vec<SearchMatch> {
+        let mut matches = Vec::new();
+        for node in self
+            .descendant_or_self()
+            .filter(|node| matches!(node.node_type(), NodeType::Text | NodeType::CData))
+        {
+            if let Some(data) = node.node_value() {
+                push_matches(&mut matches, &node, &data, regex);
+            }
+        }
+        matches
+    }
+
+    fn find_attribute_values(&self, regex: &Regex) -> Vec<SearchMatch> {
+        let mut matches = Vec::new();
+        for element in self.descendant_or_self().filter(is_element) {
+            for attribute in element.attributes().values() {
+                if let Some(value) = as_attribute(&attribute).ok().and_then(|a| a.value()) {
+                    push_matches(&mut matches, &attribute, &value, regex);
+                }
+            }
+        }
+        matches
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn push_matches(matches: &mut Vec<SearchMatch>, node: &RefNode, data: &str, regex: &Regex) {
+    matches.extend(regex.find_iter(data).map(|found| SearchMatch {
+        node: node.clone(),
+        start: found.start(),
+        end: found.end(),
+        text: found.as_str().to_string(),
+    }));
+}
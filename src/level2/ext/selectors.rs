@@ -0,0 +1,552 @@
+/*!
+Provides the [`Selector`](struct.Selector.html) type, a subset of CSS selectors used to find
+elements, and returned by the [`QuerySelector`](../trait.QuerySelector.html) trait; this module is
+only available with the `selectors` feature. For HTML-like documents these are often simpler than
+walking the tree, or composing the axes, by hand.
+
+The following selectors are supported, and may be combined into compound selectors such as
+`item.new[lang|=en]`, into complex selectors with the descendant (whitespace) and child (`>`)
+combinators, and into a comma-separated list.
+
+| Selector        | Matches elements                                                     |
+|-----------------|----------------------------------------------------------------------|
+| `*`             | any element.                                                         |
+| `name`          | with the qualified name `name`.                                      |
+| `#value`        | with an `id` attribute of `value`.                                   |
+| `.value`        | with `value` in the whitespace-separated list of the class attribute. |
+| `[name]`        | with an attribute `name`.                                            |
+| `[name=value]`  | with an attribute `name` of exactly `value`.                         |
+| `[name~=value]` | with `value` in the whitespace-separated list of attribute `name`.   |
+| `[name\|=value]` | with an attribute `name` of `value`, or starting with `value-`.     |
+| `[name^=value]` | with an attribute `name` starting with `value`.                      |
+| `[name$=value]` | with an attribute `name` ending with `value`.                        |
+| `[name*=value]` | with an attribute `name` containing `value`.                         |
+
+Names are matched case-sensitively, as written in the document. The class attribute is `class`
+by default, and may be changed with
+[`set_class_attribute`](struct.Selector.html#method.set_class_attribute). Within names a
+backslash escapes the following character, so that the element `x:item` is selected by
+`x\:item`; attribute values may be quoted with `"` or `'`.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::QuerySelector;
+use xml_dom::level2::ext::selectors::Selector;
+
+let document_node = get_implementation()
+    .create_document(None, Some("list"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+for (id, role) in [("a", "item new"), ("b", "item")] {
+    let mut item_node = document.create_element("entry").unwrap();
+    item_node.set_attribute("id", id).unwrap();
+    item_node.set_attribute("role", role).unwrap();
+    let _ = root_node.append_child(item_node).unwrap();
+}
+
+let found = document_node.query_selector_all("list > entry[role~=item]").unwrap();
+assert_eq!(found.len(), 2);
+assert!(document_node.query_selector("entry.new").unwrap().is_none());
+
+let mut selector = Selector::new("entry.new").unwrap();
+selector.set_class_attribute("role");
+let found = selector.select(&document_node).unwrap();
+assert_eq!(found.get_attribute("id"), Some("a".to_string()));
+```
+
+*/
+
+use crate::level2::convert::is_element;
+use crate::level2::ext::traits::{Axes, QuerySelector};
+use crate::level2::node_impl::RefNode;
+use crate::shared::error::{Error, Result};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A parsed list of selectors; an element matches if it matches any selector in the list.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selector {
+    alternatives: Vec<Vec<(Combinator, Compound)>>,
+    class_attribute: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The combinator preceding a compound selector, the first in a complex selector is ignored.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Compound {
+    element_name: Option<String>,
+    conditions: Vec<Condition>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Condition {
+    Id(String),
+    Class(String),
+    Attribute {
+        name: String,
+        operator: Option<(AttributeOperator, String)>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AttributeOperator {
+    Equals,
+    Includes,
+    DashMatch,
+    Prefix,
+    Suffix,
+    Substring,
+}
+
+#[derive(Debug)]
+struct SelectorParser<'a> {
+    text: &'a str,
+    chars: Vec<char>,
+    index: usize,
+}
+
+const DEFAULT_CLASS_ATTRIBUTE: &str = "class";
+const ID_ATTRIBUTE: &str = "id";
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl QuerySelector for RefNode {
+    fn query_selector(&self, selectors: &str) -> Result<Option<RefNode>> {
+        Ok(Selector::new(selectors)?.select(self))
+    }
+
+    fn query_selector_all(&self, selectors: &str) -> Result<Vec<RefNode>> {
+        Ok(Selector::new(selectors)?.select_all(self))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Selector {
+    ///
+    /// Parse `selectors`, a comma-separated list of selectors; this returns `Error::Syntax` if
+    /// `selectors` is empty, or is not in the subset of the CSS syntax supported.
+    ///
+    pub fn new(selectors: &str) -> Result<Self> {
+        Ok(Self {
+            alternatives: SelectorParser::new(selectors).selector_list()?,
+            class_attribute: DEFAULT_CLASS_ATTRIBUTE.to_string(),
+        })
+    }
+    ///
+    /// Returns the name of the attribute holding the classes matched by `.value` selectors.
+    ///
+    pub fn class_attribute(&self) -> &str {
+        &self.class_attribute
+    }
+    ///
+    /// Match `.value` selectors against the attribute with the qualified name `name`, rather
+    /// than `class`.
+    ///
+    pub fn set_class_attribute(&mut self, name: &str) {
+        self.class_attribute = name.to_string()
+    }
+    ///
+    /// Returns `true` if `node` is an element matching this selector, else `false`. As in the
+    /// DOM, ancestors are matched throughout the tree containing `node`.
+    ///
+    pub fn matches(&self, node: &RefNode) -> bool {
+        is_element(node)
+            && self
+                .alternatives
+                .iter()
+                .any(|steps| self.matches_step(steps, steps.len() - 1, node))
+    }
+    ///
+    /// Returns the first descendant of `node`, in document order, matching this selector.
+    ///
+    pub fn select(&self, node: &RefNode) -> Option<RefNode> {
+        node.descendant().find(|element| self.matches(element))
+    }
+    ///
+    /// Returns all the descendants of `node`, in document order, matching this selector.
+    ///
+    pub fn select_all(&self, node: &RefNode) -> Vec<RefNode> {
+        node.descendant()
+            .filter(|element| self.matches(element))
+            .collect()
+    }
+
+    fn matches_step(
+        &self,
+        steps: &[(Combinator, Compound)],
+        index: usize,
+        element: &RefNode,
+    ) -> bool {
+        let (combinator, compound) = &steps[index];
+        if !self.matches_compound(compound, element) {
+            return false;
+        }
+        if index == 0 {
+            return true;
+        }
+        let mut ancestor = parent_element(element);
+        while let Some(ancestor_element) = ancestor {
+            if self.matches_step(steps, index - 1, &ancestor_element) {
+                return true;
+            }
+            if *combinator == Combinator::Child {
+                break;
+            }
+            ancestor = parent_element(&ancestor_element);
+        }
+        false
+    }
+
+    fn matches_compound(&self, compound: &Compound, element: &RefNode) -> bool {
+        if let Some(element_name) = &compound.element_name {
+            if &element.node_name().to_string() != element_name {
+                return false;
+            }
+        }
+        compound
+            .conditions
+            .iter()
+            .all(|condition| self.matches_condition(condition, element))
+    }
+
+    fn matches_condition(&self, condition: &Condition, element: &RefNode) -> bool {
+        match condition {
+            Condition::Id(id) => element.get_attribute(ID_ATTRIBUTE).as_ref() == Some(id),
+            Condition::Class(class) => element
+                .get_attribute(&self.class_attribute)
+                .is_some_and(|classes| classes.split_whitespace().any(|value| value == class)),
+            Condition::Attribute { name, operator } => {
+                match (element.get_attribute(name), operator) {
+                    (None, _) => false,
+                    (Some(_), None) => true,
+                    (Some(actual), Some((operator, value))) => match operator {
+                        AttributeOperator::Equals => &actual == value,
+                        AttributeOperator::Includes => {
+                            actual.split_whitespace().any(|item| item == value)
+                        }
+                        AttributeOperator::DashMatch => {
+                            &actual == value
+                                || actual
+                                    .strip_prefix(value.as_str())
+                                    .is_some_and(|rest| rest.starts_with('-'))
+                        }
+                        AttributeOperator::Prefix => {
+                            !value.is_empty() && actual.starts_with(value.as_str())
+                        }
+                        AttributeOperator::Suffix => {
+                            !value.is_empty() && actual.ends_with(value.as_str())
+                        }
+                        AttributeOperator::Substring => {
+                            !value.is_empty() && actual.contains(value.as_str())
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> SelectorParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            chars: text.chars().collect(),
+            index: 0,
+        }
+    }
+
+    fn selector_list(&mut self) -> Result<Vec<Vec<(Combinator, Compound)>>> {
+        let mut alternatives = Vec::new();
+        loop {
+            let _safe_to_ignore = self.whitespace();
+            alternatives.push(self.complex()?);
+            let _safe_to_ignore = self.whitespace();
+            match self.next() {
+                None => return Ok(alternatives),
+                Some(',') => {}
+                Some(_) => return self.error(),
+            }
+        }
+    }
+
+    fn complex(&mut self) -> Result<Vec<(Combinator, Compound)>> {
+        let mut steps = vec![(Combinator::Descendant, self.compound()?)];
+        loop {
+            let had_whitespace = self.whitespace();
+            match self.peek() {
+                None | Some(',') => return Ok(steps),
+                Some('>') => {
+                    self.index += 1;
+                    let _safe_to_ignore = self.whitespace();
+                    steps.push((Combinator::Child, self.compound()?));
+                }
+                Some(_) if had_whitespace => {
+                    steps.push((Combinator::Descendant, self.compound()?));
+                }
+                Some(_) => return self.error(),
+            }
+        }
+    }
+
+    fn compound(&mut self) -> Result<Compound> {
+        let start = self.index;
+        let mut compound = Compound::default();
+        if self.peek() == Some('*') {
+            self.index += 1;
+        } else if self.peek().is_some_and(is_name_char) {
+            compound.element_name = Some(self.name()?);
+        }
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.index += 1;
+                    compound.conditions.push(Condition::Id(self.name()?));
+                }
+                Some('.') => {
+                    self.index += 1;
+                    compound.conditions.push(Condition::Class(self.name()?));
+                }
+                Some('[') => {
+                    self.index += 1;
+                    compound.conditions.push(self.attribute()?);
+                }
+                _ => break,
+            }
+        }
+        if self.index == start {
+            self.error()
+        } else {
+            Ok(compound)
+        }
+    }
+
+    fn attribute(&mut self) -> Result<Condition> {
+        let _safe_to_ignore = self.whitespace();
+        let name = self.name()?;
+        let _safe_to_ignore = self.whitespace();
+        let operator = match self.next() {
+            Some(']') => {
+                return Ok(Condition::Attribute {
+                    name,
+                    operator: None,
+                })
+            }
+            Some('=') => AttributeOperator::Equals,
+            Some(c) => {
+                let operator = match c {
+                    '~' => AttributeOperator::Includes,
+                    '|' => AttributeOperator::DashMatch,
+                    '^' => AttributeOperator::Prefix,
+                    '$' => AttributeOperator::Suffix,
+                    '*' => AttributeOperator::Substring,
+                    _ => return self.error(),
+                };
+                if self.next() != Some('=') {
+                    return self.error();
+                }
+                operator
+            }
+            None => return self.error(),
+        };
+        let _safe_to_ignore = self.whitespace();
+        let value = match self.peek() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                self.index += 1;
+                self.quoted(quote)?
+            }
+            _ => self.name()?,
+        };
+        let _safe_to_ignore = self.whitespace();
+        if self.next() != Some(']') {
+            return self.error();
+        }
+        Ok(Condition::Attribute {
+            name,
+            operator: Some((operator, value)),
+        })
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                self.index += 1;
+                match self.next() {
+                    Some(escaped) => name.push(escaped),
+                    None => return self.error(),
+                }
+            } else if is_name_char(c) {
+                self.index += 1;
+                name.push(c);
+            } else {
+                break;
+            }
+        }
+        if name.is_empty() {
+            self.error()
+        } else {
+            Ok(name)
+        }
+    }
+
+    fn quoted(&mut self, quote: char) -> Result<String> {
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('\\') => match self.next() {
+                    Some(escaped) => value.push(escaped),
+                    None => return self.error(),
+                },
+                Some(c) if c == quote => return Ok(value),
+                Some(c) => value.push(c),
+                None => return self.error(),
+            }
+        }
+    }
+
+    ///
+    /// Skip any whitespace, returning `true` if there was some.
+    ///
+    fn whitespace(&mut self) -> bool {
+        let start = self.index;
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.index += 1;
+        }
+        self.index > start
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let next = self.peek();
+        if next.is_some() {
+            self.index += 1;
+        }
+        next
+    }
+
+    fn error<T>(&self) -> Result<T> {
+        warn!(
+            "invalid selector '{}' at character {}",
+            self.text, self.index
+        );
+        Err(Error::Syntax)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+fn parent_element(node: &RefNode) -> Option<RefNode> {
+    node.parent_node().filter(is_element)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_selectors() {
+        for selectors in [
+            "*",
+            "item",
+            "x\\:item",
+            "#a",
+            ".new",
+            "item#a.new.old",
+            "[lang]",
+            "[ lang |= 'en' ]",
+            "[title=\"a \\\" b\"]",
+            "list > item",
+            "list>item",
+            "list item, list > *",
+            "  list  ",
+        ] {
+            assert!(Selector::new(selectors).is_ok(), "{}", selectors);
+        }
+        for selectors in [
+            "",
+            " ",
+            ",item",
+            "item,",
+            "list >",
+            "> item",
+            "#",
+            ".",
+            "[lang",
+            "[lang=]",
+            "[lang!=en]",
+            "[lang='en]",
+            "item!",
+            "x\\",
+        ] {
+            assert_eq!(
+                Selector::new(selectors),
+                Err(Error::Syntax),
+                "{}",
+                selectors
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_structure() {
+        let selector = Selector::new("a > b c[d^=\"e\"]").unwrap();
+        assert_eq!(selector.alternatives.len(), 1);
+        let steps = &selector.alternatives[0];
+        let combinators: Vec<Combinator> = steps.iter().map(|(c, _)| *c).collect();
+        assert_eq!(
+            combinators,
+            vec![
+                Combinator::Descendant,
+                Combinator::Child,
+                Combinator::Descendant
+            ]
+        );
+        assert_eq!(
+            steps[2].1,
+            Compound {
+                element_name: Some("c".to_string()),
+                conditions: vec![Condition::Attribute {
+                    name: "d".to_string(),
+                    operator: Some((AttributeOperator::Prefix, "e".to_string())),
+                }],
+            }
+        );
+    }
+}
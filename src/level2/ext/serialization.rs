@@ -0,0 +1,538 @@
+/*!
+Provides [serde](https://serde.rs) support for DOM trees, so that a parsed document may be cached,
+or exchanged with other tooling, in any serde format without writing it as XML text and parsing it
+again; this module is only available with the `serde` feature.
+
+`RefNode` implements `Serialize`, for any node, and `Deserialize`, for documents. The serialized
+form is the structure of the tree, each node with its type, name, value, children, and the data
+specific to its type (such as the attributes of an element, or the XML declaration and processing
+options of a document), it is *not* the XML text of the node. Attributes are written in the order
+in which they were added, and namespace mappings, entities, and notations in name order, so the
+same tree always produces the same output.
+
+A deserialized document has the default [`Implementation`](../../dom_impl/struct.Implementation.html),
+its ID map is rebuilt from the ID attributes in the tree, and no node in it is frozen. Names are
+checked as they are when nodes are created, and the hierarchy as it is when children are added.
+To deserialize any other node, owned by an existing document, use
+[`NodeSeed`](struct.NodeSeed.html).
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+
+let document_node = get_implementation()
+    .create_document(None, Some("cache"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+root_node.set_attribute("xml:id", "main").unwrap();
+
+let bytes = bincode::serialize(&document_node).unwrap();
+let copy_node: RefNode = bincode::deserialize(&bytes).unwrap();
+let copy = as_document(&copy_node).unwrap();
+assert_eq!(copy_node.to_string(), document_node.to_string());
+assert!(copy.get_element_by_id("main").is_some());
+```
+
+*/
+
+use crate::diagnostics::DUPLICATE_ID;
+use crate::level2::convert::is_document;
+use crate::level2::dom_impl::Implementation;
+use crate::level2::ext::character_reference::CharacterReference;
+use crate::level2::ext::decl::XmlDecl;
+use crate::level2::ext::options::ProcessingOptions;
+use crate::level2::ext::sync::Shared;
+use crate::level2::ext::tree_sizes::child_attached;
+use crate::level2::node_impl::{Extension, NodeImpl, RefNode};
+use crate::level2::trait_impls::{is_child_allowed, is_document_id_attribute};
+use crate::level2::traits::NodeType;
+use crate::shared::error::{Error, Result};
+use crate::shared::name::Name;
+use serde::de::{DeserializeSeed, Error as DeError};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Deserializes a node, of any type other than `Document`, owned by an existing document. The
+/// node is not added to the document's tree, and so any ID attributes in it are not added to the
+/// document's ID map until it is.
+///
+/// # Example
+///
+/// ```rust
+/// use serde::de::DeserializeSeed;
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::serialization::NodeSeed;
+///
+/// let document_node = get_implementation()
+///     .create_document(None, Some("root"), None)
+///     .unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let element_node = document.create_element("item").unwrap();
+/// let json = serde_json::to_string(&element_node).unwrap();
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(&json);
+/// let copy_node = NodeSeed::new(&document_node)
+///     .deserialize(&mut deserializer)
+///     .unwrap();
+/// assert_eq!(copy_node.owner_document(), Some(document_node.clone()));
+/// assert_eq!(copy_node.to_string(), "<item></item>");
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct NodeSeed<'a> {
+    document: &'a RefNode,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NodeData {
+    node_type: NodeType,
+    namespace_uri: Option<String>,
+    name: String,
+    value: Option<String>,
+    extension: ExtensionData,
+    // attributes, the document type, entities, and notations.
+    related: Vec<NodeData>,
+    children: Vec<NodeData>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum ExtensionData {
+    None,
+    Attribute {
+        specified: bool,
+    },
+    Document {
+        xml_declaration: Option<XmlDecl>,
+        id_attributes: Vec<(Option<String>, String)>,
+        options: ProcessingOptions,
+    },
+    DocumentType {
+        public_id: Option<String>,
+        system_id: Option<String>,
+        internal_subset: Option<String>,
+    },
+    Element {
+        namespaces: Vec<(Option<String>, String)>,
+    },
+    Entity {
+        public_id: Option<String>,
+        system_id: Option<String>,
+        notation_name: Option<String>,
+    },
+    Notation {
+        public_id: Option<String>,
+        system_id: Option<String>,
+    },
+    Text {
+        character_references: Vec<CharacterReference>,
+    },
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Serialize for RefNode {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        NodeData::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RefNode {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let data = NodeData::deserialize(deserializer)?;
+        if data.node_type != NodeType::Document {
+            return Err(D::Error::custom(
+                "only a document may be deserialized as a `RefNode`, use `NodeSeed` for other nodes",
+            ));
+        }
+        data.into_node(None, true).map_err(D::Error::custom)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> NodeSeed<'a> {
+    ///
+    /// Construct a new seed that deserializes nodes owned by `document`.
+    ///
+    pub fn new(document: &'a RefNode) -> Self {
+        Self { document }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for NodeSeed<'_> {
+    type Value = RefNode;
+
+    fn deserialize<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if !is_document(self.document) {
+            return Err(D::Error::custom(Error::InvalidState));
+        }
+        let data = NodeData::deserialize(deserializer)?;
+        data.into_node(Some(self.document), false)
+            .map_err(D::Error::custom)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl From<&RefNode> for NodeData {
+    fn from(node: &RefNode) -> Self {
+        let ref_node = node.borrow();
+        let (extension, related): (ExtensionData, Vec<&RefNode>) = match &ref_node.i_extension {
+            Extension::None => (ExtensionData::None, Vec::new()),
+            Extension::Attribute { i_specified, .. } => (
+                ExtensionData::Attribute {
+                    specified: *i_specified,
+                },
+                Vec::new(),
+            ),
+            Extension::Document {
+                i_xml_declaration,
+                i_document_type,
+                i_id_attributes,
+                i_options,
+                ..
+            } => {
+                let mut id_attributes: Vec<(Option<String>, String)> =
+                    i_id_attributes.iter().cloned().collect();
+                id_attributes.sort();
+                (
+                    ExtensionData::Document {
+                        xml_declaration: i_xml_declaration.clone(),
+                        id_attributes,
+                        options: i_options.clone(),
+                    },
+                    i_document_type.iter().collect(),
+                )
+            }
+            Extension::DocumentType {
+                i_entities,
+                i_notations,
+                i_public_id,
+                i_system_id,
+                i_internal_subset,
+            } => (
+                ExtensionData::DocumentType {
+                    public_id: i_public_id.clone(),
+                    system_id: i_system_id.clone(),
+                    internal_subset: i_internal_subset.clone(),
+                },
+                in_name_order(i_entities)
+                    .into_iter()
+                    .chain(in_name_order(i_notations))
+                    .collect(),
+            ),
+            Extension::Element {
+                i_attributes,
+                i_namespaces,
+            } => {
+                let mut namespaces: Vec<(Option<String>, String)> = i_namespaces
+                    .iter()
+                    .map(|(prefix, namespace_uri)| (prefix.clone(), namespace_uri.clone()))
+                    .collect();
+                namespaces.sort();
+                (
+                    ExtensionData::Element { namespaces },
+                    i_attributes.values().collect(),
+                )
+            }
+            Extension::Entity {
+                i_public_id,
+                i_system_id,
+                i_notation_name,
+            } => (
+                ExtensionData::Entity {
+                    public_id: i_public_id.clone(),
+                    system_id: i_system_id.clone(),
+                    notation_name: i_notation_name.clone(),
+                },
+                Vec::new(),
+            ),
+            Extension::Notation {
+                i_public_id,
+                i_system_id,
+            } => (
+                ExtensionData::Notation {
+                    public_id: i_public_id.clone(),
+                    system_id: i_system_id.clone(),
+                },
+                Vec::new(),
+            ),
+            Extension::Text {
+                i_character_references,
+            } => (
+                ExtensionData::Text {
+                    character_references: i_character_references.clone(),
+                },
+                Vec::new(),
+            ),
+        };
+        Self {
+            node_type: ref_node.i_node_type.clone(),
+            namespace_uri: ref_node.i_name.namespace_uri().clone(),
+            name: ref_node.i_name.to_string(),
+            value: ref_node.i_value.clone(),
+            extension,
+            related: related.into_iter().map(NodeData::from).collect(),
+            children: ref_node.i_child_nodes.iter().map(NodeData::from).collect(),
+        }
+    }
+}
+
+impl NodeData {
+    ///
+    /// Construct the node, and its subtree; `document` is `None` only for a document. The IDs of
+    /// elements are only added to the document's ID map if `register_ids` is `true`.
+    ///
+    fn into_node(self, document: Option<&RefNode>, register_ids: bool) -> Result<RefNode> {
+        let name = self.node_name()?;
+        let extension = self.node_extension()?;
+        if (self.node_type == NodeType::Document) != document.is_none() {
+            warn!("a document may only be the root of a deserialized tree");
+            return Err(Error::HierarchyRequest);
+        }
+        let node = RefNode::new(NodeImpl {
+            i_node_type: self.node_type,
+            i_name: name,
+            i_value: self.value,
+            i_parent_node: None,
+            i_owner_document: document.map(|document| document.clone().downgrade()),
+            i_child_nodes: Vec::new(),
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
+            i_extension: extension,
+        });
+        let document = document.unwrap_or(&node);
+
+        let is_element = node.borrow().i_node_type == NodeType::Element;
+        for related in self.related {
+            let related_node = related.into_node(Some(document), false)?;
+            attach_related(document, &node, related_node, register_ids && is_element)?;
+        }
+
+        for child in self.children {
+            let child_node = child.into_node(Some(document), register_ids)?;
+            if !is_child_allowed(&node, &child_node) {
+                warn!("The child you tried to add is not valid for this parent.");
+                return Err(Error::HierarchyRequest);
+            }
+            child_node.borrow_mut().i_parent_node = Some(node.clone().downgrade());
+            node.borrow_mut().i_child_nodes.push(child_node.clone());
+            child_attached(&node, &child_node);
+        }
+        Ok(node)
+    }
+
+    fn node_name(&self) -> Result<Name> {
+        match self.node_type {
+            NodeType::Text => Ok(Name::for_text()),
+            NodeType::CData => Ok(Name::for_cdata()),
+            NodeType::Comment => Ok(Name::for_comment()),
+            NodeType::Document => Ok(Name::for_document()),
+            NodeType::DocumentFragment => Ok(Name::for_document_fragment()),
+            _ => match &self.namespace_uri {
+                None => Name::from_str(&self.name),
+                Some(namespace_uri) => Name::new_ns(namespace_uri, &self.name),
+            },
+        }
+    }
+
+    fn node_extension(&self) -> Result<Extension> {
+        let extension = match (&self.node_type, &self.extension) {
+            (
+                NodeType::Text
+                | NodeType::CData
+                | NodeType::Comment
+                | NodeType::ProcessingInstruction
+                | NodeType::EntityReference
+                | NodeType::DocumentFragment,
+                ExtensionData::None,
+            ) => Extension::None,
+            (NodeType::Attribute, ExtensionData::Attribute { specified }) => Extension::Attribute {
+                i_owner_element: None,
+                i_specified: *specified,
+            },
+            (
+                NodeType::Document,
+                ExtensionData::Document {
+                    xml_declaration,
+                    id_attributes,
+                    options,
+                },
+            ) => Extension::Document {
+                i_implementation: Shared::new(Implementation::new()),
+                i_xml_declaration: xml_declaration.clone(),
+                i_document_type: None,
+                i_id_map: Default::default(),
+                i_id_attributes: id_attributes.iter().cloned().collect(),
+                i_element_index: Default::default(),
+                i_options: options.clone(),
+                i_type_provider: None,
+                i_reported_diagnostics: Default::default(),
+                i_tag_index: Default::default(),
+                i_provenance: Default::default(),
+            },
+            (
+                NodeType::DocumentType,
+                ExtensionData::DocumentType {
+                    public_id,
+                    system_id,
+                    internal_subset,
+                },
+            ) => Extension::DocumentType {
+                i_entities: Default::default(),
+                i_notations: Default::default(),
+                i_public_id: public_id.clone(),
+                i_system_id: system_id.clone(),
+                i_internal_subset: internal_subset.clone(),
+            },
+            (NodeType::Element, ExtensionData::Element { namespaces }) => Extension::Element {
+                i_attributes: Default::default(),
+                i_namespaces: namespaces.iter().cloned().collect(),
+            },
+            (
+                NodeType::Entity,
+                ExtensionData::Entity {
+                    public_id,
+                    system_id,
+                    notation_name,
+                },
+            ) => Extension::Entity {
+                i_public_id: public_id.clone(),
+                i_system_id: system_id.clone(),
+                i_notation_name: notation_name.clone(),
+            },
+            (
+                NodeType::Notation,
+                ExtensionData::Notation {
+                    public_id,
+                    system_id,
+                },
+            ) => Extension::Notation {
+                i_public_id: public_id.clone(),
+                i_system_id: system_id.clone(),
+            },
+            (
+                NodeType::Text,
+                ExtensionData::Text {
+                    character_references,
+                },
+            ) => Extension::Text {
+                i_character_references: character_references.clone(),
+            },
+            (node_type, _) => {
+                warn!(
+                    "The extension data does not match the node type {:?}.",
+                    node_type
+                );
+                return Err(Error::InvalidState);
+            }
+        };
+        Ok(extension)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn in_name_order(nodes: &HashMap<Name, RefNode>) -> Vec<&RefNode> {
+    let mut nodes: Vec<(&Name, &RefNode)> = nodes.iter().collect();
+    nodes.sort_by_key(|(name, _)| name.to_string());
+    nodes.into_iter().map(|(_, node)| node).collect()
+}
+
+///
+/// Add `related_node` to the attributes, document type, entities, or notations of `node`; if
+/// `register_id` is `true`, and the node is an ID attribute, its value is added to the document's
+/// ID map.
+///
+fn attach_related(
+    document: &RefNode,
+    node: &RefNode,
+    related_node: RefNode,
+    register_id: bool,
+) -> Result<()> {
+    let name = related_node.borrow().i_name.clone();
+    let related_type = related_node.borrow().i_node_type.clone();
+    let id_value = if register_id
+        && related_type == NodeType::Attribute
+        && is_document_id_attribute(document, &node.borrow().i_name, &name)
+    {
+        related_node.value()
+    } else {
+        None
+    };
+
+    let mut mut_node = node.borrow_mut();
+    match (&mut mut_node.i_extension, related_type) {
+        (Extension::Element { i_attributes, .. }, NodeType::Attribute) => {
+            if let Extension::Attribute {
+                i_owner_element, ..
+            } = &mut related_node.borrow_mut().i_extension
+            {
+                *i_owner_element = Some(node.clone().downgrade());
+            }
+            let _safe_to_ignore = i_attributes.insert(name, related_node);
+        }
+        (Extension::DocumentType { i_entities, .. }, NodeType::Entity) => {
+            let _safe_to_ignore = i_entities.insert(name, related_node);
+        }
+        (Extension::DocumentType { i_notations, .. }, NodeType::Notation) => {
+            let _safe_to_ignore = i_notations.insert(name, related_node);
+        }
+        (
+            Extension::Document {
+                i_document_type, ..
+            },
+            NodeType::DocumentType,
+        ) if i_document_type.is_none() => {
+            *i_document_type = Some(related_node);
+        }
+        _ => {
+            warn!("The node {:?} can not be related to this node.", name);
+            return Err(Error::HierarchyRequest);
+        }
+    }
+    drop(mut_node);
+
+    if let Some(id_value) = id_value {
+        let mut mut_document = document.borrow_mut();
+        if let Extension::Document { i_id_map, .. } = &mut mut_document.i_extension {
+            if i_id_map.contains_key(&id_value) {
+                warn!("{}", DUPLICATE_ID);
+                return Err(Error::Syntax);
+            }
+            let _safe_to_ignore = i_id_map.insert(id_value, node.clone().downgrade());
+        }
+    }
+    Ok(())
+}
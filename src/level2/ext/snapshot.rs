@@ -0,0 +1,643 @@
+/*!
+Provides the [`Snapshot`](struct.Snapshot.html) type, an immutable copy of a document taken by
+the [`DocumentSnapshot`](../trait.DocumentSnapshot.html) trait, which may be published to other
+threads, or kept as the history of a document that continues to be edited.
+
+A snapshot is built on `Arc`, and is `Send` and `Sync`; as it can't be changed it needs no
+locks, unlike the [`ArcNode`](../sync/type.ArcNode.html) of the `threadsafe` feature. The
+nodes of the live tree can't be shared with a snapshot, as they refer to their parent and owner
+document and are changed in place; instead each node of the live tree keeps the snapshot last
+taken of it, and is marked as changed, with its ancestors, whenever it is mutably borrowed. Taking
+a snapshot only visits the changed nodes, and their children, reusing the kept snapshot of every
+unchanged subtree without visiting it, so both the cost of taking a snapshot and that of keeping
+many snapshots are in proportion to the changes made between them.
+
+The snapshot holds elements and their attributes, text, CDATA sections, comments, processing
+instructions, entity references and their replacement, and documents with their XML declaration
+and document type; the entity and notation declarations of a document type are not copied, nor
+is any extension state. A snapshot may be copied back into a new `RefNode` tree
+with [`to_ref_node`](struct.Snapshot.html#method.to_ref_node), to continue editing from that
+point in its history.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::DocumentSnapshot;
+
+let document_node = get_implementation()
+    .create_document(None, Some("doc"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let mut first_node = root_node
+    .append_child(document.create_element("para").unwrap())
+    .unwrap();
+let _ = root_node
+    .append_child(document.create_element("para").unwrap())
+    .unwrap();
+
+let before = document_node.snapshot().unwrap();
+first_node.set_attribute("style", "bold").unwrap();
+let after = document_node.snapshot().unwrap();
+
+let (before_root, after_root) = (
+    before.document_element().unwrap(),
+    after.document_element().unwrap(),
+);
+// the changed element, and its ancestors, are copied; its unchanged sibling is shared.
+assert_ne!(before_root.child_nodes()[0], after_root.child_nodes()[0]);
+assert_eq!(before_root.child_nodes()[1], after_root.child_nodes()[1]);
+assert_eq!(before_root.child_nodes()[0].get_attribute("style"), None);
+assert_eq!(after_root.child_nodes()[0].get_attribute("style"), Some("bold"));
+assert_eq!(before.to_string(), "<doc><para></para><para></para></doc>");
+```
+
+*/
+
+use crate::diagnostics::{INVALID_EXTENSION, INVALID_NODE_TYPE};
+use crate::level2::convert::{
+    as_attribute_mut, as_document, as_document_type, as_processing_instruction,
+};
+use crate::level2::dom_impl::get_implementation;
+use crate::level2::ext::convert::{as_document_decl, as_document_decl_mut};
+use crate::level2::ext::decl::XmlDecl;
+use crate::level2::ext::traits::{DocumentSnapshot, TextContent};
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::level2::traits::{Element, Node, NodeType};
+use crate::shared::error::{Error, Result};
+use crate::shared::name::Name;
+use crate::shared::rc_cell::InnerCell;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::sync::Arc;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An immutable node of a snapshot; cloning a `Snapshot` clones the reference, not the node, and
+/// two `Snapshot`s are equal if they refer to the same node. The unchanged subtrees of successive
+/// snapshots of a document are the same nodes.
+///
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    inner: Arc<SnapshotNode>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Crate Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Whether a node of the live tree, or one of its descendants, has changed since the last
+/// snapshot that included it, and the snapshot then taken of it. A node is marked as changed
+/// when it is created, and whenever it is mutably borrowed, see `NodeImpl::will_change`.
+///
+#[doc(hidden)]
+#[derive(Debug)]
+pub(crate) struct SnapshotState {
+    changed: InnerCell<bool>,
+    snapshot: InnerCell<Option<Snapshot>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+struct SnapshotNode {
+    node_type: NodeType,
+    name: Name,
+    value: Option<String>,
+    attributes: Vec<(Name, String)>,
+    child_nodes: Vec<Snapshot>,
+    extension: SnapshotExtension,
+}
+
+///
+/// A node whose snapshot is being rebuilt, the children still to be visited, and the snapshots
+/// of those that have been; for a document the first of these is its document type, if any.
+///
+struct SnapshotFrame {
+    node: RefNode,
+    pending: std::vec::IntoIter<RefNode>,
+    has_document_type: bool,
+    done: Vec<Snapshot>,
+}
+
+#[derive(Debug, PartialEq)]
+enum SnapshotExtension {
+    None,
+    Document {
+        xml_declaration: Option<XmlDecl>,
+        document_type: Option<Snapshot>,
+    },
+    DocumentType {
+        public_id: Option<String>,
+        system_id: Option<String>,
+        internal_subset: Option<String>,
+    },
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentSnapshot for RefNode {
+    fn snapshot(&self) -> Result<Snapshot> {
+        if !matches!(self.borrow().i_extension, Extension::Document { .. }) {
+            warn!("{}", INVALID_EXTENSION);
+            return Err(Error::InvalidState);
+        }
+        Ok(snapshot_tree(self))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Default for SnapshotState {
+    fn default() -> Self {
+        Self {
+            changed: InnerCell::new(true),
+            snapshot: Default::default(),
+        }
+    }
+}
+
+impl Clone for SnapshotState {
+    fn clone(&self) -> Self {
+        // a copy of a node is a new node, not part of any snapshot.
+        Self::default()
+    }
+}
+
+impl SnapshotState {
+    ///
+    /// Mark the node as changed, returning `false` if it already was.
+    ///
+    pub(crate) fn mark_changed(&self) -> bool {
+        !std::mem::replace(&mut *self.changed.borrow_mut(), true)
+    }
+
+    fn unchanged(&self) -> Option<Snapshot> {
+        if *self.changed.borrow() {
+            None
+        } else {
+            self.snapshot.borrow().clone()
+        }
+    }
+
+    fn record(&self, snapshot: Snapshot) {
+        *self.snapshot.borrow_mut() = Some(snapshot);
+        *self.changed.borrow_mut() = false;
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl PartialEq for Snapshot {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for Snapshot {}
+
+impl Display for Snapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.to_ref_node() {
+            Ok(node) => write!(f, "{}", node),
+            Err(_) => Err(std::fmt::Error),
+        }
+    }
+}
+
+impl Snapshot {
+    ///
+    /// The type of this node.
+    ///
+    pub fn node_type(&self) -> NodeType {
+        self.inner.node_type.clone()
+    }
+    ///
+    /// The name of this node, as for [`Node::node_name`](../../trait.Node.html#tymethod.node_name).
+    ///
+    pub fn node_name(&self) -> &Name {
+        &self.inner.name
+    }
+    ///
+    /// The value of this node, as for
+    /// [`Node::node_value`](../../trait.Node.html#tymethod.node_value).
+    ///
+    pub fn node_value(&self) -> Option<&str> {
+        self.inner.value.as_deref()
+    }
+    ///
+    /// The children of this node, in order.
+    ///
+    pub fn child_nodes(&self) -> &[Snapshot] {
+        &self.inner.child_nodes
+    }
+    ///
+    /// Returns `true` if this node has any children, else `false`.
+    ///
+    pub fn has_child_nodes(&self) -> bool {
+        !self.inner.child_nodes.is_empty()
+    }
+    ///
+    /// The first child of this node, if any.
+    ///
+    pub fn first_child(&self) -> Option<&Snapshot> {
+        self.inner.child_nodes.first()
+    }
+    ///
+    /// The last child of this node, if any.
+    ///
+    pub fn last_child(&self) -> Option<&Snapshot> {
+        self.inner.child_nodes.last()
+    }
+    ///
+    /// The names, and values, of the attributes of this element, in order.
+    ///
+    pub fn attributes(&self) -> &[(Name, String)] {
+        &self.inner.attributes
+    }
+    ///
+    /// The value of the attribute of this element with the qualified name `name`, if any.
+    ///
+    pub fn get_attribute(&self, name: &str) -> Option<&str> {
+        self.inner
+            .attributes
+            .iter()
+            .find(|(attribute_name, _)| attribute_name.to_string() == name)
+            .map(|(_, value)| value.as_str())
+    }
+    ///
+    /// The text content of this node, as for
+    /// [`TextContent::text_content`](../trait.TextContent.html#tymethod.text_content).
+    ///
+    pub fn text_content(&self) -> String {
+        match self.inner.node_type {
+            NodeType::Text
+            | NodeType::CData
+            | NodeType::Comment
+            | NodeType::ProcessingInstruction => self.inner.value.clone().unwrap_or_default(),
+            _ => self
+                .inner
+                .child_nodes
+                .iter()
+                .filter(|child| {
+                    !matches!(
+                        child.inner.node_type,
+                        NodeType::Comment | NodeType::ProcessingInstruction
+                    )
+                })
+                .map(Snapshot::text_content)
+                .collect(),
+        }
+    }
+    ///
+    /// The first element child of this document, if any.
+    ///
+    pub fn document_element(&self) -> Option<&Snapshot> {
+        self.inner
+            .child_nodes
+            .iter()
+            .find(|child| child.inner.node_type == NodeType::Element)
+    }
+    ///
+    /// The XML declaration of this document, if any.
+    ///
+    pub fn xml_declaration(&self) -> Option<&XmlDecl> {
+        match &self.inner.extension {
+            SnapshotExtension::Document {
+                xml_declaration, ..
+            } => xml_declaration.as_ref(),
+            _ => None,
+        }
+    }
+    ///
+    /// The document type of this document, if any.
+    ///
+    pub fn doc_type(&self) -> Option<&Snapshot> {
+        match &self.inner.extension {
+            SnapshotExtension::Document { document_type, .. } => document_type.as_ref(),
+            _ => None,
+        }
+    }
+    ///
+    /// Copy this node, and its descendants, into a new `RefNode` tree. A document is copied
+    /// to a new document; any other node is copied to a node owned by a new document without
+    /// a document element, and is returned without a parent.
+    ///
+    pub fn to_ref_node(&self) -> Result<RefNode> {
+        if self.inner.node_type == NodeType::Document {
+            self.to_ref_document()
+        } else {
+            let document_node = get_implementation().create_document(None, None, None)?;
+            self.to_ref_node_in(&document_node)
+        }
+    }
+
+    fn to_ref_document(&self) -> Result<RefNode> {
+        let implementation = get_implementation();
+        let document_type = match self.doc_type() {
+            None => None,
+            Some(document_type) => {
+                let document_type_node = implementation.create_document_type(
+                    &document_type.inner.name.to_string(),
+                    None,
+                    None,
+                )?;
+                if let (
+                    Extension::DocumentType {
+                        i_public_id,
+                        i_system_id,
+                        i_internal_subset,
+                        ..
+                    },
+                    SnapshotExtension::DocumentType {
+                        public_id,
+                        system_id,
+                        internal_subset,
+                    },
+                ) = (
+                    &mut document_type_node.borrow_mut().i_extension,
+                    &document_type.inner.extension,
+                ) {
+                    i_public_id.clone_from(public_id);
+                    i_system_id.clone_from(system_id);
+                    i_internal_subset.clone_from(internal_subset);
+                }
+                Some(document_type_node)
+            }
+        };
+        let mut document_node = implementation.create_document(None, None, document_type)?;
+        if let Some(xml_declaration) = self.xml_declaration() {
+            as_document_decl_mut(&mut document_node)?
+                .set_xml_declaration(xml_declaration.clone())?;
+        }
+        for child in self.child_nodes() {
+            if child.inner.node_type != NodeType::DocumentType {
+                let child_node = child.to_ref_node_in(&document_node)?;
+                let _safe_to_ignore = document_node.append_child(child_node)?;
+            }
+        }
+        Ok(document_node)
+    }
+
+    fn to_ref_node_in(&self, document_node: &RefNode) -> Result<RefNode> {
+        let document = as_document(document_node)?;
+        let node = &self.inner;
+        let name = node.name.to_string();
+        let value = node.value.clone().unwrap_or_default();
+        let mut ref_node = match node.node_type {
+            NodeType::Element => match node.name.namespace_uri() {
+                None => document.create_element(&name)?,
+                Some(namespace_uri) => document.create_element_ns(namespace_uri, &name)?,
+            },
+            NodeType::Text => document.create_text_node(&value),
+            NodeType::CData => document.create_cdata_section(&value)?,
+            NodeType::Comment => document.create_comment(&value),
+            NodeType::ProcessingInstruction => {
+                document.create_processing_instruction(&name, node.value.as_deref())?
+            }
+            NodeType::EntityReference => document.create_entity_reference(&name)?,
+            _ => {
+                warn!("{}", INVALID_NODE_TYPE);
+                return Err(Error::NotSupported);
+            }
+        };
+        for (attribute_name, value) in &node.attributes {
+            let mut attribute_node = match attribute_name.namespace_uri() {
+                None => document.create_attribute(&attribute_name.to_string())?,
+                Some(namespace_uri) => {
+                    document.create_attribute_ns(namespace_uri, &attribute_name.to_string())?
+                }
+            };
+            as_attribute_mut(&mut attribute_node)?.set_value(value)?;
+            let _safe_to_ignore = ref_node.set_attribute_node(attribute_node)?;
+        }
+        for child in &node.child_nodes {
+            let child_node = child.to_ref_node_in(document_node)?;
+            let _safe_to_ignore = ref_node.append_child(child_node)?;
+        }
+        Ok(ref_node)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the snapshot of `node`, reusing the snapshot kept by each unchanged node rather than
+/// visiting it; the tree is walked with an explicit stack as documents may be deeply nested.
+///
+fn snapshot_tree(node: &RefNode) -> Snapshot {
+    if let Some(snapshot) = node.borrow().i_snapshot.unchanged() {
+        return snapshot;
+    }
+    let mut stack = vec![SnapshotFrame::new(node)];
+    loop {
+        let frame = stack.last_mut().unwrap();
+        match frame.pending.next() {
+            Some(child_node) => {
+                let unchanged = child_node.borrow().i_snapshot.unchanged();
+                match unchanged {
+                    Some(snapshot) => frame.done.push(snapshot),
+                    None => stack.push(SnapshotFrame::new(&child_node)),
+                }
+            }
+            None => {
+                let frame = stack.pop().unwrap();
+                let snapshot = frame.rebuild();
+                match stack.last_mut() {
+                    Some(parent) => parent.done.push(snapshot),
+                    None => return snapshot,
+                }
+            }
+        }
+    }
+}
+
+impl SnapshotFrame {
+    fn new(node: &RefNode) -> Self {
+        let document_type = as_document(node)
+            .ok()
+            .and_then(|document| document.doc_type());
+        let has_document_type = document_type.is_some();
+        let pending: Vec<RefNode> = document_type
+            .into_iter()
+            .chain(node.child_nodes().into_iter().filter(|child_node| {
+                !matches!(
+                    child_node.node_type(),
+                    NodeType::Entity | NodeType::Notation
+                )
+            }))
+            .collect();
+        Self {
+            node: node.clone(),
+            done: Vec::with_capacity(pending.len()),
+            pending: pending.into_iter(),
+            has_document_type,
+        }
+    }
+
+    ///
+    /// Returns the snapshot of the node from those of its children, which is the one it kept if
+    /// nothing has in fact changed, and keeps it.
+    ///
+    fn rebuild(self) -> Snapshot {
+        let node = &self.node;
+        let mut child_nodes = self.done;
+        let extension = match node.node_type() {
+            NodeType::Document => SnapshotExtension::Document {
+                xml_declaration: as_document_decl(node)
+                    .ok()
+                    .and_then(|document| document.xml_declaration()),
+                document_type: if self.has_document_type {
+                    Some(child_nodes.remove(0))
+                } else {
+                    None
+                },
+            },
+            NodeType::DocumentType => match as_document_type(node) {
+                Ok(document_type) => SnapshotExtension::DocumentType {
+                    public_id: document_type.public_id(),
+                    system_id: document_type.system_id(),
+                    internal_subset: document_type.internal_subset(),
+                },
+                Err(_) => SnapshotExtension::None,
+            },
+            _ => SnapshotExtension::None,
+        };
+        let attribute_nodes = node.attributes();
+        let fresh = SnapshotNode {
+            node_type: node.node_type(),
+            name: node.node_name(),
+            value: match node.node_type() {
+                NodeType::ProcessingInstruction => as_processing_instruction(node)
+                    .ok()
+                    .and_then(|pi| pi.data()),
+                _ => node.node_value(),
+            },
+            attributes: attribute_nodes
+                .iter()
+                .map(|(name, attribute)| (name, attribute.text_content().unwrap_or_default()))
+                .collect(),
+            child_nodes,
+            extension,
+        };
+        // attributes, and their text, are part of the snapshot of their element, so are marked
+        // as unchanged with it; otherwise a later change would not mark the element again.
+        let mut pending: Vec<RefNode> = attribute_nodes.values().collect();
+        while let Some(attribute_node) = pending.pop() {
+            let ref_node = attribute_node.borrow();
+            *ref_node.i_snapshot.changed.borrow_mut() = false;
+            pending.extend(ref_node.i_child_nodes.iter().cloned());
+        }
+
+        let ref_node = node.borrow();
+        let kept = ref_node.i_snapshot.snapshot.borrow().clone();
+        let snapshot = match kept {
+            // child snapshots compare by identity, so this only matches if the subtree is unchanged.
+            Some(kept) if *kept.inner == fresh => kept,
+            _ => Snapshot {
+                inner: Arc::new(fresh),
+            },
+        };
+        ref_node.i_snapshot.record(snapshot.clone());
+        snapshot
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_changed(node: &RefNode) -> bool {
+        let changed = *node.borrow().i_snapshot.changed.borrow();
+        changed
+    }
+
+    #[test]
+    fn test_unchanged_subtree_is_not_rebuilt() {
+        let document_node = get_implementation()
+            .create_document(None, Some("doc"), None)
+            .unwrap();
+        let document = as_document(&document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        let mut first_node = root_node
+            .append_child(document.create_element("para").unwrap())
+            .unwrap();
+        let mut second_node = root_node
+            .append_child(document.create_element("para").unwrap())
+            .unwrap();
+        let text_node = second_node
+            .append_child(document.create_text_node("unchanged"))
+            .unwrap();
+
+        let before = document_node.snapshot().unwrap();
+        assert!(!is_changed(&document_node));
+        assert!(!is_changed(&text_node));
+
+        first_node.set_attribute("style", "bold").unwrap();
+        assert!(is_changed(&first_node));
+        assert!(is_changed(&root_node));
+        assert!(is_changed(&document_node));
+        assert!(!is_changed(&second_node));
+        assert!(!is_changed(&text_node));
+
+        // change the unchanged subtree without marking it, the kept snapshot is only reused if
+        // the subtree is not visited.
+        text_node.borrow_mut_unmarked().i_value = Some("not visited".to_string());
+        let after = document_node.snapshot().unwrap();
+
+        let (before_root, after_root) = (
+            before.document_element().unwrap(),
+            after.document_element().unwrap(),
+        );
+        assert_ne!(before_root.child_nodes()[0], after_root.child_nodes()[0]);
+        assert_eq!(before_root.child_nodes()[1], after_root.child_nodes()[1]);
+        assert_eq!(after_root.child_nodes()[1].text_content(), "unchanged");
+        assert!(!is_changed(&document_node));
+        assert!(!is_changed(&first_node));
+    }
+
+    #[test]
+    fn test_attribute_change_marks_element() {
+        let document_node = get_implementation()
+            .create_document(None, Some("doc"), None)
+            .unwrap();
+        let mut root_node = as_document(&document_node)
+            .unwrap()
+            .document_element()
+            .unwrap();
+        root_node.set_attribute("style", "bold").unwrap();
+        let before = document_node.snapshot().unwrap();
+
+        let mut attribute_node = root_node.get_attribute_node("style").unwrap();
+        as_attribute_mut(&mut attribute_node)
+            .unwrap()
+            .set_value("italic")
+            .unwrap();
+        assert!(is_changed(&root_node));
+        let after = document_node.snapshot().unwrap();
+        assert_eq!(
+            before.document_element().unwrap().get_attribute("style"),
+            Some("bold")
+        );
+        assert_eq!(
+            after.document_element().unwrap().get_attribute("style"),
+            Some("italic")
+        );
+        assert!(!is_changed(&attribute_node));
+    }
+}
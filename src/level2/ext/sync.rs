@@ -0,0 +1,114 @@
+/*!
+Provides the types that change with the `threadsafe` feature, which makes the DOM tree safe to
+share between threads.
+
+A [`RefNode`](../../type.RefNode.html) is built on `Rc<RefCell<…>>` and so is neither `Send` nor
+`Sync`. With the `threadsafe` feature it is instead built on `Arc<RwLock<…>>`, and is available
+by the name [`ArcNode`](type.ArcNode.html); it is the same type, so it implements all of the DOM
+traits, and their extensions, and keeps all of the state of the tree, such as the entity and
+notation declarations of a document type, registered ID attributes, and processing options. A
+tree may be parsed, or built, on one thread and then read on any number of others.
+
+The values a tree shares with its nodes, such as a
+[`TypeProvider`](../schema/trait.TypeProvider.html), are held by [`Shared`](type.Shared.html),
+an `Rc` or, with the feature, an `Arc`; and must be [`Shareable`](trait.Shareable.html), which
+with the feature requires them to be `Send` and `Sync`. As Cargo features are additive, a crate
+that shares such values should use these names, rather than `Rc`, to build with either.
+
+A method locks each node it reads, or changes, while it does so, and often locks more than one
+node at a time; a change, such as `append_child`, is not atomic with respect to other threads,
+and changes made by more than one thread to the same document must be serialized by the caller,
+for example with a `Mutex` around the document, or they may deadlock.
+
+# Example
+
+```rust
+# #[cfg(feature = "threadsafe")]
+# {
+use std::thread;
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::sync::ArcNode;
+
+let document_node: ArcNode = get_implementation()
+    .create_document(None, Some("list"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+for text in ["a", "b"] {
+    let mut item_node = root_node
+        .append_child(document.create_element("item").unwrap())
+        .unwrap();
+    let _safe_to_ignore = item_node
+        .append_child(document.create_text_node(text))
+        .unwrap();
+}
+
+let handles: Vec<_> = (0..2)
+    .map(|index| {
+        let document_node = document_node.clone();
+        thread::spawn(move || {
+            let document = as_document(&document_node).unwrap();
+            let items = document.get_elements_by_tag_name("item");
+            items[index].first_child().unwrap().node_value().unwrap()
+        })
+    })
+    .collect();
+let texts: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+assert_eq!(texts, vec!["a".to_string(), "b".to_string()]);
+# }
+```
+
+*/
+
+#[cfg(feature = "threadsafe")]
+use crate::level2::node_impl::RefNode;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A thread-safe node, built on `Arc<RwLock<…>>`; this is the same type as `RefNode` which,
+/// with the `threadsafe` feature, is `Send` and `Sync`.
+///
+#[cfg(feature = "threadsafe")]
+pub type ArcNode = RefNode;
+
+///
+/// The shared reference to a value held by the nodes of a tree; an `Arc` with the `threadsafe`
+/// feature, else an `Rc`.
+///
+#[cfg(feature = "threadsafe")]
+pub type Shared<T> = std::sync::Arc<T>;
+
+///
+/// The shared reference to a value held by the nodes of a tree; an `Arc` with the `threadsafe`
+/// feature, else an `Rc`.
+///
+#[cfg(not(feature = "threadsafe"))]
+pub type Shared<T> = std::rc::Rc<T>;
+
+///
+/// Implemented by every type that may be shared by the nodes of a tree; with the `threadsafe`
+/// feature those that are `Send` and `Sync`, else all types.
+///
+#[cfg(feature = "threadsafe")]
+pub trait Shareable: Send + Sync {}
+
+///
+/// Implemented by every type that may be shared by the nodes of a tree; with the `threadsafe`
+/// feature those that are `Send` and `Sync`, else all types.
+///
+#[cfg(not(feature = "threadsafe"))]
+pub trait Shareable {}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(feature = "threadsafe")]
+impl<T: Send + Sync + ?Sized> Shareable for T {}
+
+#[cfg(not(feature = "threadsafe"))]
+impl<T: ?Sized> Shareable for T {}
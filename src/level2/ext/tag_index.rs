@@ -0,0 +1,180 @@
+/*!
+The index of a document's elements by name, used by the `Document` lookups by tag name when the
+document was created with the
+[`set_use_element_index`](../options/struct.ProcessingOptions.html#method.set_use_element_index)
+processing option.
+
+The index is built by the first lookup, and discarded whenever a child is added to, or removed
+from, any node of the document; names can't be changed once a node is created, so this is the
+only change that affects it.
+*/
+
+use crate::level2::node_impl::{Extension, RefNode, WeakRefNode};
+use crate::level2::trait_impls::{namespaced_name_match, WILD_CARD};
+use crate::level2::traits::NodeType;
+use std::collections::HashMap;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The elements below the document element, in document order, with the positions of those
+/// with each qualified name, and with each local name.
+///
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TagIndex {
+    elements: Vec<WeakRefNode>,
+    names: HashMap<String, Vec<usize>>,
+    local_names: HashMap<String, Vec<usize>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Crate Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The result of `Document::get_elements_by_tag_name` from the index, or `None` if the document
+/// does not use one.
+///
+pub(crate) fn elements_by_tag_name(
+    document_node: &RefNode,
+    tag_name: &str,
+) -> Option<Vec<RefNode>> {
+    with_index(document_node, |index| {
+        if tag_name == WILD_CARD {
+            index.all()
+        } else {
+            index.at(index.names.get(tag_name))
+        }
+    })
+}
+
+///
+/// The result of `Document::get_elements_by_tag_name_ns` from the index, or `None` if the
+/// document does not use one.
+///
+pub(crate) fn elements_by_tag_name_ns(
+    document_node: &RefNode,
+    namespace_uri: &str,
+    local_name: &str,
+) -> Option<Vec<RefNode>> {
+    with_index(document_node, |index| {
+        let candidates = if local_name == WILD_CARD {
+            index.all()
+        } else {
+            index.at(index.local_names.get(local_name))
+        };
+        candidates
+            .into_iter()
+            .filter(|element| {
+                let ref_element = element.borrow();
+                namespaced_name_match(
+                    ref_element.i_name.namespace_uri().as_deref(),
+                    ref_element.i_name.local_name(),
+                    namespace_uri,
+                    local_name,
+                )
+            })
+            .collect()
+    })
+}
+
+///
+/// Called once a child has been added to, or removed from, `parent`; discards the index of the
+/// document that owns `parent`, if any.
+///
+pub(crate) fn tree_changed(parent: &RefNode) {
+    let document_node = {
+        let ref_parent = parent.borrow();
+        if let Extension::Document { i_tag_index, .. } = &ref_parent.i_extension {
+            let _safe_to_ignore = i_tag_index.borrow_mut().take();
+            return;
+        }
+        ref_parent
+            .i_owner_document
+            .as_ref()
+            .and_then(|owner_document| owner_document.clone().upgrade())
+    };
+    if let Some(document_node) = document_node {
+        if let Extension::Document { i_tag_index, .. } = &document_node.borrow().i_extension {
+            let _safe_to_ignore = i_tag_index.borrow_mut().take();
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl TagIndex {
+    fn new(document_node: &RefNode) -> Self {
+        let mut index = Self::default();
+        let mut pending: Vec<RefNode> = document_node.borrow().i_child_nodes.clone();
+        pending.reverse();
+        while let Some(node) = pending.pop() {
+            let ref_node = node.borrow();
+            if ref_node.i_node_type != NodeType::Element {
+                continue;
+            }
+            let position = index.elements.len();
+            index
+                .names
+                .entry(ref_node.i_name.to_string())
+                .or_default()
+                .push(position);
+            index
+                .local_names
+                .entry(ref_node.i_name.local_name().clone())
+                .or_default()
+                .push(position);
+            index.elements.push(node.clone().downgrade());
+            pending.extend(ref_node.i_child_nodes.iter().rev().cloned());
+        }
+        index
+    }
+
+    fn all(&self) -> Vec<RefNode> {
+        self.elements
+            .iter()
+            .filter_map(|element| element.clone().upgrade())
+            .collect()
+    }
+
+    fn at(&self, positions: Option<&Vec<usize>>) -> Vec<RefNode> {
+        positions
+            .map(|positions| {
+                positions
+                    .iter()
+                    .filter_map(|position| self.elements[*position].clone().upgrade())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn with_index<F>(document_node: &RefNode, lookup: F) -> Option<Vec<RefNode>>
+where
+    F: FnOnce(&TagIndex) -> Vec<RefNode>,
+{
+    let ref_document = document_node.borrow();
+    if let Extension::Document {
+        i_options,
+        i_tag_index,
+        ..
+    } = &ref_document.i_extension
+    {
+        if !i_options.has_use_element_index() {
+            return None;
+        }
+        let mut tag_index = i_tag_index.borrow_mut();
+        let index = tag_index.get_or_insert_with(|| Box::new(TagIndex::new(document_node)));
+        Some(lookup(index))
+    } else {
+        None
+    }
+}
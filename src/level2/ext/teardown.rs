@@ -0,0 +1,107 @@
+/*!
+Provides the implementation of the [`DocumentTeardown`](../trait.DocumentTeardown.html) and
+[`NodeLifetime`](../trait.NodeLifetime.html) traits, taking apart a document's tree and detecting
+nodes that have outlived their document.
+
+Within the tree, parents hold strong references to their children, and the links back up the
+tree (`parent_node` and `owner_document`) are weak, so a tree is dropped along with the last
+reference to its root. The children of a node are dropped without recursion, so that dropping a
+deeply nested tree does not overflow the stack.
+
+A node held by a client once its document has been dropped is orphaned, it remains usable but
+its `owner_document` is `None` and any operation requiring the document, such as creating nodes,
+fails; [`is_orphaned`](../trait.NodeLifetime.html#tymethod.is_orphaned) detects this case, rather
+than relying on the warning logged when the document can't be found.
+[`detach_all`](../trait.DocumentTeardown.html#tymethod.detach_all) detaches every node in a tree
+from its parent, so that subtrees held by clients do not keep the rest of the tree alive.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{DocumentTeardown, NodeLifetime};
+
+let mut document_node = get_implementation()
+    .create_document(None, Some("root"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let child_node = root_node
+    .append_child(document.create_element("child").unwrap())
+    .unwrap();
+
+document_node.detach_all().unwrap();
+assert!(root_node.parent_node().is_none());
+assert!(child_node.parent_node().is_none());
+assert!(!child_node.is_orphaned());
+
+drop(document_node);
+assert!(child_node.is_orphaned());
+```
+
+*/
+
+use crate::diagnostics::INVALID_EXTENSION;
+use crate::level2::ext::traits::{DocumentTeardown, NodeLifetime};
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::level2::trait_impls::check_not_frozen;
+use crate::shared::error::{Error, Result};
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl NodeLifetime for RefNode {
+    fn is_orphaned(&self) -> bool {
+        match &self.borrow().i_owner_document {
+            None => false,
+            Some(owner_document) => owner_document.is_dropped(),
+        }
+    }
+}
+
+impl DocumentTeardown for RefNode {
+    fn detach_all(&mut self) -> Result<()> {
+        if !matches!(self.borrow().i_extension, Extension::Document { .. }) {
+            warn!("{}", INVALID_EXTENSION);
+            return Err(Error::InvalidState);
+        }
+
+        // check the whole tree first, so that nothing is detached if any of it is frozen.
+        let mut pending = vec![self.clone()];
+        while let Some(node) = pending.pop() {
+            check_not_frozen(&node)?;
+            pending.extend(node.borrow().i_child_nodes.iter().cloned());
+        }
+
+        let mut pending = vec![self.clone()];
+        while let Some(node) = pending.pop() {
+            let children = {
+                let mut mut_node = node.borrow_mut();
+                mut_node.i_subtree_size = 1;
+                std::mem::take(&mut mut_node.i_child_nodes)
+            };
+            for child_node in &children {
+                let mut mut_child = child_node.borrow_mut();
+                mut_child.i_parent_node = None;
+                mut_child.i_depth = 0;
+            }
+            pending.extend(children);
+        }
+
+        let mut mut_self = self.borrow_mut();
+        if let Extension::Document {
+            i_id_map,
+            i_element_index,
+            i_tag_index,
+            ..
+        } = &mut mut_self.i_extension
+        {
+            i_id_map.clear();
+            i_element_index.clear();
+            let _safe_to_ignore = i_tag_index.get_mut().take();
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,27 @@
+use crate::diagnostics::{DUPLICATE_ID, INVALID_EXTENSION, INVALID_NODE_TYPE, WEAK_REF};
+use crate::level2::convert::{
+    as_attribute, as_attribute_mut, as_document, as_element, as_element_mut, is_document,
+    is_element,
+};
 use crate::level2::dom_impl::Implementation;
+use crate::level2::ext::character_reference::CharacterReference;
 use crate::level2::ext::decl::*;
 use crate::level2::ext::options::ProcessingOptions;
+use crate::level2::ext::position::{Position, Span};
+use crate::level2::ext::sync::Shared;
 use crate::level2::ext::traits::*;
 use crate::level2::node_impl::*;
-use crate::level2::trait_impls::create_document_with_options;
+use crate::level2::trait_impls::{
+    check_not_frozen, create_document_with_options, is_child_allowed,
+};
+use crate::level2::traits::{Node, NodeType};
 use crate::shared::error::*;
+use crate::shared::syntax::{
+    XML_CDATA_END, XML_NS_ATTRIBUTE, XML_NS_ATTR_LANG, XML_NS_ATTR_SPACE, XML_NS_URI,
+};
+use crate::shared::text::{is_language_tag, is_xml_space, SpaceHandling};
+use std::collections::HashMap;
+use std::str::FromStr;
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
@@ -19,12 +36,13 @@ impl DocumentDecl for RefNode {
         {
             i_xml_declaration.clone()
         } else {
-            warn!("{}", MSG_INVALID_EXTENSION);
+            warn!("{}", INVALID_EXTENSION);
             None
         }
     }
 
     fn set_xml_declaration(&mut self, xml_decl: XmlDecl) -> Result<()> {
+        check_not_frozen(self)?;
         let mut mut_self = self.borrow_mut();
         if let Extension::Document {
             i_xml_declaration, ..
@@ -33,7 +51,7 @@ impl DocumentDecl for RefNode {
             *i_xml_declaration = Some(xml_decl);
             Ok(())
         } else {
-            warn!("{}", MSG_INVALID_EXTENSION);
+            warn!("{}", INVALID_EXTENSION);
             Err(Error::InvalidState)
         }
     }
@@ -41,6 +59,457 @@ impl DocumentDecl for RefNode {
 
 // ------------------------------------------------------------------------------------------------
 
+impl IdAttributes for RefNode {
+    fn register_id_attribute(
+        &mut self,
+        namespace_uri: Option<&str>,
+        local_name: &str,
+    ) -> Result<()> {
+        check_not_frozen(self)?;
+        if !is_document(self) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let key = (namespace_uri.map(String::from), local_name.to_string());
+        //
+        // Find the attributes already in the document, checking for duplicates before anything
+        // is changed.
+        //
+        let mut new_ids: HashMap<String, RefNode> = Default::default();
+        for element in self.descendant_or_self().filter(is_element) {
+            for (name, attribute) in element.attributes() {
+                if name.namespace_uri() != &key.0 || name.local_name() != &key.1 {
+                    continue;
+                }
+                let id_value = as_attribute(&attribute)?.value().unwrap_or_default();
+                let existing = match new_ids.get(&id_value) {
+                    Some(existing) => Some(existing.clone()),
+                    None => self.get_element_by_id(&id_value),
+                };
+                match existing {
+                    Some(existing) if existing != element => {
+                        warn!("{}", DUPLICATE_ID);
+                        return Err(Error::Syntax);
+                    }
+                    _ => {
+                        let _safe_to_ignore = new_ids.insert(id_value, element.clone());
+                    }
+                }
+            }
+        }
+        let mut mut_self = self.borrow_mut();
+        if let Extension::Document {
+            i_id_map,
+            i_id_attributes,
+            ..
+        } = &mut mut_self.i_extension
+        {
+            let _safe_to_ignore = i_id_attributes.insert(key);
+            i_id_map.extend(
+                new_ids
+                    .into_iter()
+                    .map(|(id_value, element)| (id_value, element.downgrade())),
+            );
+            Ok(())
+        } else {
+            warn!("{}", INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+
+    fn is_registered_id_attribute(&self, namespace_uri: Option<&str>, local_name: &str) -> bool {
+        let ref_self = self.borrow();
+        if let Extension::Document {
+            i_id_attributes, ..
+        } = &ref_self.i_extension
+        {
+            i_id_attributes.contains(&(namespace_uri.map(String::from), local_name.to_string()))
+        } else {
+            warn!("{}", INVALID_EXTENSION);
+            false
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl IndexedElements for RefNode {
+    fn indexed_elements(&self, name: &str) -> Option<HashMap<String, Vec<RefNode>>> {
+        let ref_self = self.borrow();
+        if let Extension::Document {
+            i_element_index, ..
+        } = &ref_self.i_extension
+        {
+            let index = i_element_index.get(name)?;
+            Some(
+                index
+                    .iter()
+                    .filter_map(|(value, elements)| {
+                        let elements: Vec<RefNode> = elements
+                            .iter()
+                            .filter_map(|element| element.clone().upgrade())
+                            .filter(|element| {
+                                element.get_attribute(name).as_ref() == Some(value)
+                                    && is_in_document(element, self)
+                            })
+                            .collect();
+                        (!elements.is_empty()).then(|| (value.clone(), elements))
+                    })
+                    .collect(),
+            )
+        } else {
+            warn!("{}", INVALID_EXTENSION);
+            None
+        }
+    }
+
+    fn indexed_attributes(&self) -> Vec<String> {
+        let ref_self = self.borrow();
+        if let Extension::Document {
+            i_element_index, ..
+        } = &ref_self.i_extension
+        {
+            i_element_index.keys().cloned().collect()
+        } else {
+            warn!("{}", INVALID_EXTENSION);
+            Vec::default()
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl TextContent for RefNode {
+    fn text_content(&self) -> Option<String> {
+        match self.node_type() {
+            NodeType::Element
+            | NodeType::Attribute
+            | NodeType::Entity
+            | NodeType::EntityReference
+            | NodeType::DocumentFragment => Some(
+                self.child_nodes()
+                    .iter()
+                    .filter(|child| {
+                        !matches!(
+                            child.node_type(),
+                            NodeType::Comment | NodeType::ProcessingInstruction
+                        )
+                    })
+                    .filter_map(|child| child.text_content())
+                    .collect(),
+            ),
+            NodeType::Text
+            | NodeType::CData
+            | NodeType::Comment
+            | NodeType::ProcessingInstruction => self.node_value(),
+            NodeType::Document | NodeType::DocumentType | NodeType::Notation => None,
+        }
+    }
+
+    fn set_text_content(&mut self, text: &str) -> Result<()> {
+        match self.node_type() {
+            NodeType::Element | NodeType::DocumentFragment => {
+                let document_node = self.owner_document().ok_or_else(|| {
+                    warn!("{}", WEAK_REF);
+                    Error::InvalidState
+                })?;
+                for child in self.child_nodes() {
+                    let _safe_to_ignore = self.remove_child(child)?;
+                }
+                if !text.is_empty() {
+                    let text_node = as_document(&document_node)?.create_text_node(text);
+                    let _safe_to_ignore = self.append_child(text_node)?;
+                }
+                Ok(())
+            }
+            NodeType::Attribute => as_attribute_mut(self)?.set_value(text),
+            NodeType::Text
+            | NodeType::CData
+            | NodeType::Comment
+            | NodeType::ProcessingInstruction => self.set_node_value(text),
+            NodeType::Entity | NodeType::EntityReference => {
+                warn!("Entity and entity reference nodes are read-only.");
+                Err(Error::NoModificationAllowed)
+            }
+            NodeType::Document | NodeType::DocumentType | NodeType::Notation => Ok(()),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl SourcePosition for RefNode {
+    fn source_position(&self) -> Option<Position> {
+        self.borrow().i_position
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl SourceSpans for RefNode {
+    fn markup_span(&self) -> Option<Span> {
+        self.borrow().i_spans.as_ref()?.markup
+    }
+
+    fn name_span(&self) -> Option<Span> {
+        self.borrow().i_spans.as_ref()?.name
+    }
+
+    fn value_span(&self) -> Option<Span> {
+        self.borrow().i_spans.as_ref()?.value
+    }
+
+    fn end_tag_span(&self) -> Option<Span> {
+        self.borrow().i_spans.as_ref()?.end_tag
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl CharacterReferences for RefNode {
+    fn character_references(&self) -> Vec<CharacterReference> {
+        match &self.borrow().i_extension {
+            Extension::Text {
+                i_character_references,
+            } => i_character_references.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ElementContentWhitespace for RefNode {
+    fn is_whitespace(&self) -> bool {
+        self.borrow()
+            .i_value
+            .as_deref()
+            .unwrap_or_default()
+            .chars()
+            .all(is_xml_space)
+    }
+
+    fn is_element_content_whitespace(&self) -> bool {
+        self.node_type() == NodeType::Text
+            && self.is_whitespace()
+            && self
+                .parent_node()
+                .is_some_and(|parent| is_element_content(&parent) && !is_space_preserved(&parent))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Restructure for RefNode {
+    fn wrap_with(&mut self, wrapper: RefNode) -> Result<RefNode> {
+        let mut parent_node = self.parent_node().ok_or_else(|| {
+            warn!("wrap_with: this node has no parent");
+            Error::HierarchyRequest
+        })?;
+        if !is_element(&wrapper) || wrapper.parent_node().is_some() {
+            warn!("wrap_with: the wrapper must be an element without a parent");
+            return Err(Error::HierarchyRequest);
+        }
+        if !is_child_allowed(&parent_node, &wrapper) || !is_child_allowed(&wrapper, self) {
+            warn!("The child you tried to add is not valid for this parent.");
+            return Err(Error::HierarchyRequest);
+        }
+        if wrapper.owner_document() != self.owner_document() {
+            warn!("wrap_with: the wrapper was created by a different document");
+            return Err(Error::WrongDocument);
+        }
+        check_not_frozen(&parent_node)?;
+        check_not_frozen(&wrapper)?;
+        let _safe_to_ignore = parent_node.replace_child(wrapper.clone(), self.clone())?;
+        let mut wrapper = wrapper;
+        let _safe_to_ignore = wrapper.append_child(self.clone())?;
+        Ok(wrapper)
+    }
+
+    fn unwrap_element(&mut self) -> Result<Vec<RefNode>> {
+        if !is_element(self) {
+            warn!("unwrap_element: only an element may be unwrapped");
+            return Err(Error::HierarchyRequest);
+        }
+        let mut parent_node = match self.parent_node() {
+            Some(parent_node) if !is_document(&parent_node) => parent_node,
+            _ => {
+                warn!("unwrap_element: the element must have a parent other than the document");
+                return Err(Error::HierarchyRequest);
+            }
+        };
+        let children = self.child_nodes();
+        if !children
+            .iter()
+            .all(|child| is_child_allowed(&parent_node, child))
+        {
+            warn!("The child you tried to add is not valid for this parent.");
+            return Err(Error::HierarchyRequest);
+        }
+        check_not_frozen(&parent_node)?;
+        check_not_frozen(self)?;
+        for child in &children {
+            let _safe_to_ignore = parent_node.insert_before(child.clone(), Some(self.clone()))?;
+        }
+        let _safe_to_ignore = parent_node.remove_child(self.clone())?;
+        Ok(children)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Language for RefNode {
+    fn language(&self) -> Option<String> {
+        let mut current = match self.node_type() {
+            NodeType::Attribute => as_attribute(self).ok()?.owner_element(),
+            _ => Some(self.clone()),
+        };
+        while let Some(node) = current {
+            if let Some(attribute_node) = language_attribute(&node) {
+                return language_value(&attribute_node);
+            }
+            current = node.parent_node();
+        }
+        None
+    }
+
+    fn set_language(&mut self, tag: &str) -> Result<()> {
+        if !tag.is_empty() && !is_language_tag(tag) {
+            warn!("Invalid BCP 47 language tag '{}'.", tag);
+            return Err(Error::Syntax);
+        }
+        match language_attribute(self) {
+            Some(mut attribute_node) => as_attribute_mut(&mut attribute_node)?.set_value(tag),
+            None => as_element_mut(self)?.set_attribute_ns(
+                XML_NS_URI,
+                &format!("{}:{}", XML_NS_ATTRIBUTE, XML_NS_ATTR_LANG),
+                tag,
+            ),
+        }
+    }
+
+    fn text_in_language(&self, range: &str) -> String {
+        let mut text = String::new();
+        collect_text_in_language(self, self.language(), range, &mut text);
+        text
+    }
+}
+
+fn language_attribute(node: &RefNode) -> Option<RefNode> {
+    xml_attribute(node, XML_NS_ATTR_LANG)
+}
+
+///
+/// Returns the attribute `local_name` in the `xml` namespace of `node`, if it is an element.
+///
+fn xml_attribute(node: &RefNode, local_name: &str) -> Option<RefNode> {
+    as_element(node)
+        .ok()?
+        .attributes()
+        .into_iter()
+        .find(|(name, _)| {
+            name.local_name() == local_name
+                && (name.namespace_uri().as_deref() == Some(XML_NS_URI)
+                    || name.prefix().as_deref() == Some(XML_NS_ATTRIBUTE))
+        })
+        .map(|(_, attribute_node)| attribute_node)
+}
+
+///
+/// Returns `true` if `element` has at least one child element, and all of its text children are
+/// white space, else `false`.
+///
+pub(crate) fn is_element_content(element: &RefNode) -> bool {
+    if !is_element(element) {
+        return false;
+    }
+    let ref_element = element.borrow();
+    let mut has_element = false;
+    for child_node in &ref_element.i_child_nodes {
+        match child_node.node_type() {
+            NodeType::Element => has_element = true,
+            NodeType::Text | NodeType::CData if !child_node.is_whitespace() => return false,
+            _ => {}
+        }
+    }
+    has_element
+}
+
+///
+/// Returns the white space handling set by the `xml:space` attribute of `node` itself, if it has
+/// one; a value other than `preserve` is treated as `default`.
+///
+pub(crate) fn space_handling(node: &RefNode) -> Option<SpaceHandling> {
+    let attribute_node = xml_attribute(node, XML_NS_ATTR_SPACE)?;
+    match as_attribute(&attribute_node)
+        .ok()
+        .and_then(|attribute| attribute.value())
+        .and_then(|value| SpaceHandling::from_str(&value).ok())
+    {
+        Some(SpaceHandling::Preserve) => Some(SpaceHandling::Preserve),
+        _ => Some(SpaceHandling::Default),
+    }
+}
+
+///
+/// Returns `true` if the nearest `xml:space` attribute on `node`, or its ancestors, is
+/// `preserve`, else `false`.
+///
+pub(crate) fn is_space_preserved(node: &RefNode) -> bool {
+    let mut current = Some(node.clone());
+    while let Some(node) = current {
+        if let Some(handling) = space_handling(&node) {
+            return handling == SpaceHandling::Preserve;
+        }
+        current = node.parent_node();
+    }
+    false
+}
+
+fn language_value(attribute_node: &RefNode) -> Option<String> {
+    as_attribute(attribute_node)
+        .ok()?
+        .value()
+        .filter(|value| !value.is_empty())
+}
+
+fn collect_text_in_language(
+    node: &RefNode,
+    language: Option<String>,
+    range: &str,
+    text: &mut String,
+) {
+    match node.node_type() {
+        NodeType::Text | NodeType::CData
+            if language
+                .as_deref()
+                .is_some_and(|language| language_matches(language, range)) =>
+        {
+            text.push_str(&node.node_value().unwrap_or_default());
+        }
+        NodeType::Element | NodeType::Document | NodeType::DocumentFragment => {
+            for child_node in node.child_nodes() {
+                let child_language = match language_attribute(&child_node) {
+                    Some(attribute_node) => language_value(&attribute_node),
+                    None => language.clone(),
+                };
+                collect_text_in_language(&child_node, child_language, range, text);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn language_matches(language: &str, range: &str) -> bool {
+    range == "*"
+        || language.eq_ignore_ascii_case(range)
+        || (language.len() > range.len()
+            && language[..range.len()].eq_ignore_ascii_case(range)
+            && language[range.len()..].starts_with('-'))
+}
+
+// ------------------------------------------------------------------------------------------------
+
 impl DOMImplementation for Implementation {
     fn create_document_with_options(
         &self,
@@ -49,6 +518,128 @@ impl DOMImplementation for Implementation {
         doc_type: Option<Self::NodeRef>,
         options: ProcessingOptions,
     ) -> Result<Self::NodeRef> {
-        create_document_with_options(namespace_uri, qualified_name, doc_type, options)
+        create_document_with_options(
+            Shared::new(self.clone()),
+            namespace_uri,
+            qualified_name,
+            doc_type,
+            options,
+        )
     }
 }
+
+// ------------------------------------------------------------------------------------------------
+
+impl CDataContent for RefNode {
+    fn set_cdata(&mut self, data: &str) -> Result<()> {
+        if !is_element(self) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let document_node = self.owner_document().ok_or_else(|| {
+            warn!("{}", WEAK_REF);
+            Error::InvalidState
+        })?;
+        let document = as_document(&document_node)?;
+        for child in self.child_nodes() {
+            let _safe_to_ignore = self.remove_child(child)?;
+        }
+        for section in split_cdata(data) {
+            let new_section = document.create_cdata_section(&section)?;
+            let _safe_to_ignore = self.append_child(new_section)?;
+        }
+        Ok(())
+    }
+
+    fn cdata_sections(&self) -> Vec<RefNode> {
+        self.child_nodes()
+            .into_iter()
+            .filter(|child| child.node_type() == NodeType::CData)
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl InnerXml for RefNode {
+    fn inner_xml(&self) -> String {
+        self.child_nodes()
+            .iter()
+            .map(|child| child.to_string())
+            .collect()
+    }
+
+    fn outer_xml(&self) -> String {
+        self.to_string()
+    }
+
+    #[cfg(feature = "quick_parser")]
+    fn set_inner_xml(&mut self, xml: &str) -> Result<()> {
+        use crate::parser::{read_fragment, Error as ParserError};
+
+        if !is_element(self) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let fragment_node = read_fragment(xml, self).map_err(|error| {
+            warn!(
+                "the new content of the element could not be parsed: {}",
+                error
+            );
+            match error.into_kind() {
+                ParserError::DOMError(error) => error,
+                _ => Error::Syntax,
+            }
+        })?;
+        for child in self.child_nodes() {
+            let _safe_to_ignore = self.remove_child(child)?;
+        }
+        for child in fragment_node.child_nodes() {
+            let _safe_to_ignore = self.append_child(child)?;
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns `true` if `node` is a descendant of `document`, else `false`.
+///
+fn is_in_document(node: &RefNode, document: &RefNode) -> bool {
+    let mut current = node.parent_node();
+    while let Some(parent) = current {
+        if &parent == document {
+            return true;
+        }
+        current = parent.parent_node();
+    }
+    false
+}
+
+///
+/// Split `data` so that no section contains `]]>`, each occurrence ends one section with `]]`
+/// and starts the next with `>`.
+///
+fn split_cdata(data: &str) -> Vec<String> {
+    let (cdata_end_brackets, cdata_end_gt) = XML_CDATA_END.split_at(2);
+    let parts: Vec<&str> = data.split(XML_CDATA_END).collect();
+    let last = parts.len() - 1;
+    parts
+        .iter()
+        .enumerate()
+        .map(|(index, part)| {
+            let mut section = String::new();
+            if index > 0 {
+                section.push_str(cdata_end_gt);
+            }
+            section.push_str(part);
+            if index < last {
+                section.push_str(cdata_end_brackets);
+            }
+            section
+        })
+        .collect()
+}
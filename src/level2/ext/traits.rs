@@ -1,13 +1,836 @@
+use crate::level2::ext::axes::{
+    Ancestors, ChildrenOfType, Descendants, Following, NamespaceAxis, Preceding, Siblings,
+};
+use crate::level2::ext::batch::{Transaction, Violation};
+use crate::level2::ext::character_reference::CharacterReference;
+use crate::level2::ext::comparison::DocumentPosition;
 use crate::level2::ext::decl::XmlDecl;
+use crate::level2::ext::dtd::{AttributeDef, DtdDeclaration, DtdViolation, ElementDecl};
+use crate::level2::ext::mapping::XmlValue;
 use crate::level2::ext::namespaced::NamespacePrefix;
+use crate::level2::ext::node_list::LiveNodeList;
+use crate::level2::ext::normalize::NormalizationOptions;
 use crate::level2::ext::options::ProcessingOptions;
+use crate::level2::ext::position::{Position, Span};
+use crate::level2::ext::provenance::{Operation, Provenance};
+use crate::level2::ext::schema::TypeProvider;
+use crate::level2::ext::search::SearchMatch;
+use crate::level2::ext::snapshot::Snapshot;
+use crate::level2::ext::sync::Shared;
 use crate::level2::traits as base;
 use crate::shared::error::Result;
+use regex::Regex;
+use std::collections::HashMap;
 
 // ------------------------------------------------------------------------------------------------
 // Public Traits
 // ------------------------------------------------------------------------------------------------
 
+///
+/// This interface extends the DOM standard `Node` with the axes defined by XPath, each returned as
+/// an iterator over nodes. These allow clients to implement custom query logic without walking
+/// `parent_node`/`child_nodes` links by hand.
+///
+/// # Specification
+///
+/// From XPath 1.0 [§2.2 Axes](https://www.w3.org/TR/xpath-10/#axes):
+///
+/// * the `ancestor`, `descendant`, `following`, `preceding` and `self` axes partition a document
+///   (ignoring attribute and namespace nodes): they do not overlap and together they contain all
+///   the nodes in the document.
+/// * An axis that only ever contains the context node or nodes that are after the context node in
+///   document order is a forward axis. An axis that only ever contains the context node or nodes
+///   that are before the context node in document order is a reverse axis.
+///
+/// Forward axes return nodes in document order, reverse axes (`ancestor`, `ancestor-or-self`,
+/// `preceding`, and `preceding-sibling`) return nodes in reverse document order, that is nearest
+/// to the context node first. The parent of an attribute, for the purpose of these axes, is its
+/// owner element.
+///
+pub trait Axes: base::Node {
+    ///
+    /// The `ancestor` axis contains the ancestors of the context node; the ancestors of the context
+    /// node consist of the parent of context node and the parent's parent and so on.
+    ///
+    fn ancestor(&self) -> Ancestors;
+    ///
+    /// The `ancestor-or-self` axis contains the context node and the ancestors of the context node.
+    ///
+    fn ancestor_or_self(&self) -> Ancestors;
+    ///
+    /// The `child` axis contains the children of the context node.
+    ///
+    fn child(&self) -> Siblings;
+    ///
+    /// The `descendant` axis contains the descendants of the context node; a descendant is a child
+    /// or a child of a child and so on; thus the descendant axis never contains attribute or
+    /// namespace nodes.
+    ///
+    fn descendant(&self) -> Descendants;
+    ///
+    /// The `descendant-or-self` axis contains the context node and the descendants of the context
+    /// node.
+    ///
+    fn descendant_or_self(&self) -> Descendants;
+    ///
+    /// The `following` axis contains all nodes in the same document as the context node that are
+    /// after the context node in document order, excluding any descendants and excluding
+    /// attribute nodes and namespace nodes.
+    ///
+    fn following(&self) -> Following;
+    ///
+    /// The `following-sibling` axis contains all the following siblings of the context node; if
+    /// the context node is an attribute node, the axis is empty.
+    ///
+    fn following_sibling(&self) -> Siblings;
+    ///
+    /// The `preceding` axis contains all nodes in the same document as the context node that are
+    /// before the context node in document order, excluding any ancestors and excluding attribute
+    /// nodes and namespace nodes.
+    ///
+    fn preceding(&self) -> Preceding;
+    ///
+    /// The `preceding-sibling` axis contains all the preceding siblings of the context node; if
+    /// the context node is an attribute node, the axis is empty.
+    ///
+    fn preceding_sibling(&self) -> Siblings;
+    ///
+    /// The `attribute` axis contains the attributes of the context node; the axis will be empty
+    /// unless the context node is an element.
+    ///
+    fn attribute_axis(&self) -> std::vec::IntoIter<Self::NodeRef>;
+    ///
+    /// The `namespace` axis contains the namespace nodes of the context node, as `(prefix, URI)`
+    /// pairs; the axis will be empty unless the context node is an element. The `xml` prefix is
+    /// always in scope.
+    ///
+    fn namespace_axis(&self) -> NamespaceAxis;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with iterators over the children of a node that
+/// are of a particular type, replacing the common pattern of filtering `child_nodes` by
+/// `node_type`.
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::TypedChildren;
+///
+/// let document_node = get_implementation()
+///     .create_document(None, Some("root"), None)
+///     .unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let mut root_node = document.document_element().unwrap();
+/// let _ = root_node.append_child(document.create_text_node("text")).unwrap();
+/// let _ = root_node.append_child(document.create_element("child").unwrap()).unwrap();
+///
+/// assert_eq!(root_node.children_elements().count(), 1);
+/// assert_eq!(root_node.children_text().count(), 1);
+/// ```
+///
+pub trait TypedChildren: base::Node {
+    ///
+    /// Returns an iterator over the children of this node with the given `node_type`.
+    ///
+    fn children_of_type(&self, node_type: base::NodeType) -> ChildrenOfType;
+    ///
+    /// Returns an iterator over the children of this node that are elements.
+    ///
+    fn children_elements(&self) -> ChildrenOfType {
+        self.children_of_type(base::NodeType::Element)
+    }
+    ///
+    /// Returns an iterator over the children of this node that are text nodes; note that this
+    /// does not include CDATA sections.
+    ///
+    fn children_text(&self) -> ChildrenOfType {
+        self.children_of_type(base::NodeType::Text)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with iterators over the family of a node, named
+/// as they would be in Rust rather than by XPath; each method is the same as the corresponding
+/// [`Axes`](trait.Axes.html) method. None of these iterators copy the child lists of the nodes
+/// they visit, unlike [`child_nodes`](../trait.Node.html#tymethod.child_nodes).
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::NodeIterators;
+///
+/// let document_node = get_implementation()
+///     .create_document(None, Some("root"), None)
+///     .unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let mut root_node = document.document_element().unwrap();
+/// let mut child_node = root_node
+///     .append_child(document.create_element("child").unwrap())
+///     .unwrap();
+/// let _ = child_node.append_child(document.create_text_node("text")).unwrap();
+///
+/// assert_eq!(document_node.descendants().count(), 3);
+/// assert_eq!(child_node.ancestors().count(), 2);
+/// ```
+///
+pub trait NodeIterators: Axes {
+    ///
+    /// Returns an iterator over the ancestors of this node, nearest first, ending with the
+    /// document node; the owner element of an attribute is treated as its parent.
+    ///
+    fn ancestors(&self) -> Ancestors {
+        self.ancestor()
+    }
+    ///
+    /// Returns an iterator over the children of this node.
+    ///
+    fn children(&self) -> Siblings {
+        self.child()
+    }
+    ///
+    /// Returns an iterator over the descendants of this node, in document order.
+    ///
+    fn descendants(&self) -> Descendants {
+        self.descendant()
+    }
+    ///
+    /// Returns an iterator over the siblings after this node, nearest first.
+    ///
+    fn following_siblings(&self) -> Siblings {
+        self.following_sibling()
+    }
+    ///
+    /// Returns an iterator over the siblings before this node, nearest first.
+    ///
+    fn preceding_siblings(&self) -> Siblings {
+        self.preceding_sibling()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with the node comparison operations added in
+/// DOM Level 3. The `PartialEq` implementation for node references is identity, the same as
+/// `is_same_node`.
+///
+/// # Specification
+///
+/// From DOM Level 3 Core [`isEqualNode`](https://www.w3.org/TR/DOM-Level-3-Core/core.html#Node3-isEqualNode):
+///
+/// > Two nodes are equal if and only if the following conditions are satisfied:
+/// >
+/// > * The two nodes are of the same type.
+/// > * The following string attributes are equal: `nodeName`, `localName`, `namespaceURI`,
+/// >   `prefix`, `nodeValue`.
+/// > * The `attributes` `NamedNodeMaps` are equal.
+/// > * The `childNodes` `NodeLists` are equal.
+/// >
+/// > For two `DocumentType` nodes to be equal, the following conditions must also be satisfied:
+/// >
+/// > * The following string attributes are equal: `publicId`, `systemId`, `internalSubset`.
+/// > * The `entities` `NamedNodeMaps` are equal.
+/// > * The `notations` `NamedNodeMaps` are equal.
+///
+pub trait NodeComparison: base::Node {
+    ///
+    /// Returns whether this node is the same node as `other`.
+    ///
+    fn is_same_node(&self, other: &Self::NodeRef) -> bool;
+    ///
+    /// Returns whether this node is equal to `other`, as described above; the document type of a
+    /// document is compared as if it were one of its children.
+    ///
+    fn is_equal_node(&self, other: &Self::NodeRef) -> bool;
+    ///
+    /// Compares the position of `other` in document order with this, the reference, node. The
+    /// owner element of an attribute is treated as its parent, attributes are ordered after their
+    /// element and before its children, and by name on the same element; the document type is
+    /// ordered before the children of its document. Nodes in different trees are `DISCONNECTED`.
+    ///
+    fn compare_document_position(&self, other: &Self::NodeRef) -> DocumentPosition;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with live forms of the collections returned by
+/// `child_nodes`, `get_elements_by_tag_name`, and `get_elements_by_tag_name_ns`. Each returns a
+/// [`LiveNodeList`](node_list/struct.LiveNodeList.html) that reflects later changes to the tree,
+/// as the DOM specification requires of a `NodeList`, rather than a snapshot.
+///
+/// The element lists select the same elements, in the same order, as the corresponding methods
+/// on [`Element`](../trait.Element.html) and [`Document`](../trait.Document.html); each call to
+/// `length` or `item` walks the tree, so a snapshot remains cheaper when the tree is not changing.
+///
+pub trait LiveCollections: base::Node {
+    ///
+    /// Returns a live list of the children of this node.
+    ///
+    fn child_node_list(&self) -> LiveNodeList;
+    ///
+    /// Returns a live list of the elements with the name `tag_name`, or all elements if it is
+    /// `"*"`, at or below this element, or in this document.
+    ///
+    fn elements_by_tag_name(&self, tag_name: &str) -> LiveNodeList;
+    ///
+    /// Returns a live list of the elements with the name `local_name` in `namespace_uri`, either of
+    /// which may be `"*"`, at or below this element, or in this document.
+    ///
+    fn elements_by_tag_name_ns(&self, namespace_uri: &str, local_name: &str) -> LiveNodeList;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with the ability to search the text, and
+/// attribute values, of a node and its descendants with a regular expression, without first
+/// serializing the tree to a string.
+///
+/// Each match is returned as a [`SearchMatch`](search/struct.SearchMatch.html) holding the node
+/// that contains it, and the byte offsets of the match within that node's data; these offsets may
+/// be used directly with the `CharacterData` methods such as `replace_data`.
+///
+/// ```rust
+/// use regex::Regex;
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::Search;
+///
+/// let document_node = get_implementation()
+///     .create_document(None, Some("contact"), None)
+///     .unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let mut root_node = document.document_element().unwrap();
+/// root_node.set_attribute("email", "jane@example.com").unwrap();
+/// let _ = root_node
+///     .append_child(document.create_text_node("Call 555-0100 or 555-0199."))
+///     .unwrap();
+///
+/// let phone = Regex::new(r"\d{3}-\d{4}").unwrap();
+/// let matches = document_node.find_text(&phone);
+/// assert_eq!(matches.len(), 2);
+/// assert_eq!((matches[1].start, matches[1].end), (17, 25));
+///
+/// let email = Regex::new(r"@example\.com$").unwrap();
+/// assert_eq!(document_node.find_attribute_values(&email)[0].text, "@example.com");
+/// ```
+///
+pub trait Search: base::Node {
+    ///
+    /// Returns every match of `regex` in the data of this node and its descendant `Text` and
+    /// `CDataSection` nodes, in document order. Matches do not span nodes, so adjacent text nodes
+    /// should be normalized first if required.
+    ///
+    fn find_text(&self, regex: &Regex) -> Vec<SearchMatch>;
+    ///
+    /// Returns every match of `regex` in the values of the attributes of this node and its
+    /// descendant elements, in document order and then in the order of their attributes; the node
+    /// of each match is the `Attribute` node.
+    ///
+    fn find_attribute_values(&self, regex: &Regex) -> Vec<SearchMatch>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with the `querySelector` and
+/// `querySelectorAll` operations of the DOM Living Standard, for a subset of CSS selectors; this
+/// trait is only available with the `selectors` feature. See the
+/// [`selectors`](selectors/index.html) module for the syntax supported, and an example.
+///
+#[cfg(feature = "selectors")]
+pub trait QuerySelector: base::Node {
+    ///
+    /// Returns the first descendant element, in document order, matching `selectors`; it is an
+    /// error, `Error::Syntax`, if `selectors` cannot be parsed.
+    ///
+    fn query_selector(&self, selectors: &str) -> Result<Option<Self::NodeRef>>;
+    ///
+    /// Returns all the descendant elements, in document order, matching `selectors`; it is an
+    /// error, `Error::Syntax`, if `selectors` cannot be parsed.
+    ///
+    fn query_selector_all(&self, selectors: &str) -> Result<Vec<Self::NodeRef>>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with the DOM Level 3 `textContent` attribute,
+/// the text of a node and its descendants.
+///
+/// # Specification
+///
+/// From DOM Level 3 Core [`Node.textContent`](https://www.w3.org/TR/DOM-Level-3-Core/core.html#Node3-textContent)
+/// -- This attribute returns the text content of this node and its descendants. When it is
+/// defined to be `null`, setting it has no effect. On setting, any possible children this node
+/// may have are removed and, if the new string is not empty or `null`, replaced by a single
+/// `Text` node containing the string this attribute is set to.
+///
+/// The text content is:
+///
+/// * for `Element`, `Attribute`, `Entity`, `EntityReference`, and `DocumentFragment` nodes, the
+///   concatenation of the text content of every child node, excluding comments and processing
+///   instructions;
+/// * for `Text`, `CDataSection`, `Comment`, and `ProcessingInstruction` nodes, the node value;
+/// * for `Document`, `DocumentType`, and `Notation` nodes, `None`.
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::TextContent;
+///
+/// let document_node = get_implementation()
+///     .create_document(None, Some("p"), None)
+///     .unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let mut root_node = document.document_element().unwrap();
+/// let mut bold_node = document.create_element("b").unwrap();
+/// let _ = bold_node.append_child(document.create_text_node("bold")).unwrap();
+/// let _ = root_node.append_child(document.create_text_node("Some ")).unwrap();
+/// let _ = root_node.append_child(bold_node).unwrap();
+/// let _ = root_node.append_child(document.create_comment("note")).unwrap();
+/// assert_eq!(root_node.text_content(), Some("Some bold".to_string()));
+///
+/// root_node.set_text_content("Plain").unwrap();
+/// assert_eq!(root_node.to_string(), "<p>Plain</p>");
+/// ```
+///
+pub trait TextContent: base::Node {
+    ///
+    /// Returns the text content of this node and its descendants, as described above.
+    ///
+    fn text_content(&self) -> Option<String>;
+    ///
+    /// Replace the children of this node with a single text node containing `text`, or no
+    /// children if `text` is empty; for character data and processing instructions set the node
+    /// value. This has no effect for the node types whose content is `None`.
+    ///
+    fn set_text_content(&mut self, text: &str) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with two common restructuring operations,
+/// inserting a new parent between a node and its current parent, and replacing an element by its
+/// children; each is a single step rather than a sequence of changes to child lists.
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::Restructure;
+///
+/// let document_node = get_implementation()
+///     .create_document(None, Some("p"), None)
+///     .unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let mut root_node = document.document_element().unwrap();
+/// let mut text_node = root_node
+///     .append_child(document.create_text_node("bold"))
+///     .unwrap();
+///
+/// let mut bold_node = text_node
+///     .wrap_with(document.create_element("b").unwrap())
+///     .unwrap();
+/// assert_eq!(root_node.to_string(), "<p><b>bold</b></p>");
+///
+/// let children = bold_node.unwrap_element().unwrap();
+/// assert_eq!(children, vec![text_node]);
+/// assert_eq!(root_node.to_string(), "<p>bold</p>");
+/// ```
+///
+pub trait Restructure: base::Node {
+    ///
+    /// Insert the element `wrapper` in place of this node, and make this node the last child of
+    /// `wrapper`; returns `wrapper`. The wrapper must be an element, from the same document, that
+    /// is not already in the tree.
+    ///
+    /// # Errors
+    ///
+    /// * `HierarchyRequest`: if this node has no parent, or `wrapper` is not an element, has a
+    ///   parent, or may not contain this node.
+    /// * `WrongDocument`: if `wrapper` was created by a different document.
+    /// * `NoModificationAllowed`: if the parent of this node, or `wrapper`, is frozen.
+    ///
+    fn wrap_with(&mut self, wrapper: Self::NodeRef) -> Result<Self::NodeRef>;
+    ///
+    /// Replace this element by its children, in order, in its parent; returns the children. The
+    /// element is left without a parent or children. This is not named `unwrap` as that would be
+    /// hidden by the method of the same name on `RefNode` itself.
+    ///
+    /// # Errors
+    ///
+    /// * `HierarchyRequest`: if this node is not an element, has no parent, or its parent is the
+    ///   document, or any of its children are not allowed in the parent.
+    /// * `NoModificationAllowed`: if this element, or its parent, is frozen.
+    ///
+    fn unwrap_element(&mut self) -> Result<Vec<Self::NodeRef>>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with the resolution of the language of a node
+/// from the `xml:lang` attribute.
+///
+/// # Specification
+///
+/// From XML 1.0 [§2.12 Language Identification](https://www.w3.org/TR/xml/#sec-lang-tag) -- The
+/// language specified by `xml:lang` applies to the element where it is specified (including the
+/// values of its attributes), and to all elements in its content unless overridden with another
+/// instance of `xml:lang`. In particular, the empty value of `xml:lang` is used on an element B to
+/// override a specification of `xml:lang` on an enclosing element A, without specifying another
+/// language.
+///
+/// The values of `xml:lang` are language tags as defined by
+/// [BCP 47](https://www.rfc-editor.org/info/bcp47), and a language range, used to filter text, is
+/// matched using the "basic filtering" scheme of
+/// [RFC 4647 §3.3.1](https://www.rfc-editor.org/rfc/rfc4647#section-3.3.1).
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::Language;
+///
+/// let document_node = get_implementation()
+///     .create_document(None, Some("greetings"), None)
+///     .unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let mut root_node = document.document_element().unwrap();
+/// root_node.set_language("en").unwrap();
+/// for (language, text) in [("en-GB", "Hello"), ("fr", "Bonjour")] {
+///     let mut greeting_node = document.create_element("greeting").unwrap();
+///     greeting_node.set_language(language).unwrap();
+///     let _ = greeting_node.append_child(document.create_text_node(text)).unwrap();
+///     let _ = root_node.append_child(greeting_node).unwrap();
+/// }
+///
+/// let text_node = root_node.first_child().unwrap().first_child().unwrap();
+/// assert_eq!(text_node.language(), Some("en-GB".to_string()));
+/// assert_eq!(root_node.text_in_language("en"), "Hello");
+/// assert!(root_node.set_language("not a tag").is_err());
+/// ```
+///
+pub trait Language: base::Node {
+    ///
+    /// Returns the language of this node, the value of the `xml:lang` attribute on the nearest
+    /// element that has one, starting with this node if it is an element, or its owner element if
+    /// it is an attribute. Returns `None` if there is no such attribute, or if the nearest one
+    /// has an empty value.
+    ///
+    fn language(&self) -> Option<String>;
+    ///
+    /// Set the `xml:lang` attribute of this element to `tag`, which must be a well-formed BCP 47
+    /// language tag, or empty to override the language of the enclosing element. Returns a
+    /// `Syntax` error if `tag` is not well-formed, and an error if this node is not an element.
+    ///
+    fn set_language(&mut self, tag: &str) -> Result<()>;
+    ///
+    /// Returns the concatenated text of this node, and its descendants, whose language matches
+    /// the language `range`. A range matches a language that is equal to it, or that begins with
+    /// it followed by `-`, ignoring case; the range `*` matches any language, but not text with no
+    /// language.
+    ///
+    fn text_in_language(&self, range: &str) -> String;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with the position in the parsed input at which
+/// the node started. Positions are only recorded by the parser when
+/// [`ParseOptions::set_track_positions`](../../parser/options/struct.ParseOptions.html#method.set_track_positions)
+/// is set; this allows tools that validate or lint documents to report where in the original
+/// text a node came from.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "quick_parser")] {
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::SourcePosition;
+/// use xml_dom::parser::{read_xml_with, ParseOptions};
+///
+/// let mut options = ParseOptions::new();
+/// options.set_track_positions();
+/// let (document_node, _) = read_xml_with("<root>\n  <child/>\n</root>", options).unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let child_node = document.document_element().unwrap().first_child().unwrap();
+///
+/// let position = child_node.source_position().unwrap();
+/// assert_eq!((position.line(), position.column()), (2, 3));
+/// # }
+/// ```
+///
+pub trait SourcePosition: base::Node {
+    ///
+    /// Returns the position in the parsed input at which this node started, or `None` if the
+    /// node was not created by the parser, or positions were not tracked. Attributes have the
+    /// position of their element, and text nodes that merge several parts of the input have the
+    /// position of the first.
+    ///
+    fn source_position(&self) -> Option<Position>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with the spans of the parsed input from which a
+/// node was created. Spans are only recorded by the parser when
+/// [`ParseOptions::set_track_spans`](../../parser/options/struct.ParseOptions.html#method.set_track_spans)
+/// is set; this allows linters, and refactoring tools, to make precise edits to the original text
+/// rather than writing the whole document again, which loses its formatting. The
+/// [`preserve`](preserve/index.html) module uses them to write a modified document, copying the
+/// unmodified parts from the input.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "quick_parser")] {
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::SourceSpans;
+/// use xml_dom::parser::{read_xml_with, ParseOptions};
+///
+/// let xml = r#"<root><item  lang='en'>text</item></root>"#;
+/// let mut options = ParseOptions::new();
+/// options.set_track_spans();
+/// let (document_node, _) = read_xml_with(xml, options).unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let item_node = document.document_element().unwrap().first_child().unwrap();
+///
+/// assert_eq!(&xml[item_node.markup_span().unwrap().byte_range()], "<item  lang='en'>");
+/// assert_eq!(&xml[item_node.end_tag_span().unwrap().byte_range()], "</item>");
+///
+/// // replace the value of the attribute, leaving the rest of the text as it was.
+/// let value_span = item_node.get_attribute_node("lang").unwrap().value_span().unwrap();
+/// let mut edited = xml.to_string();
+/// edited.replace_range(value_span.byte_range(), "fr");
+/// assert_eq!(edited, r#"<root><item  lang='fr'>text</item></root>"#);
+/// # }
+/// ```
+///
+pub trait SourceSpans: base::Node {
+    ///
+    /// Returns the span of the markup this node was created from: the start tag of an element,
+    /// the name, `=`, and quoted value of an attribute, all of the input for a document, or the
+    /// whole of any other node. Returns `None` if spans were not tracked, or for nodes that do not
+    /// correspond to a single span, such as text merged from several parts of the input, or split
+    /// around entity references.
+    ///
+    fn markup_span(&self) -> Option<Span>;
+    ///
+    /// Returns the span of the qualified name of an element, in its start tag, or of an
+    /// attribute.
+    ///
+    fn name_span(&self) -> Option<Span>;
+    ///
+    /// Returns the span of the value of an attribute, between the quotes; this is the text as
+    /// written, before any references in it were replaced.
+    ///
+    fn value_span(&self) -> Option<Span>;
+    ///
+    /// Returns the span of the end tag of an element; this is `None` for an element written as
+    /// an empty element tag.
+    ///
+    fn end_tag_span(&self) -> Option<Span>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Text` with the numeric character references from
+/// which characters in its data were parsed. The data of a text node holds only the characters,
+/// so that `&#xA0;` and a literal no-break space are indistinguishable; when
+/// [`ParseOptions::set_preserve_character_references`](../../parser/options/struct.ParseOptions.html#method.set_preserve_character_references)
+/// is set the parser records each reference, and the node is written with the references as
+/// they appeared in the input. Changing the data of the node discards the references.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "quick_parser")] {
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::CharacterReferences;
+/// use xml_dom::parser::{read_xml_with, ParseOptions};
+///
+/// let mut options = ParseOptions::new();
+/// options.set_preserve_character_references();
+/// let (document_node, _) = read_xml_with("<p>1&#xA0;km&#160;</p>", options).unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let text_node = document.document_element().unwrap().first_child().unwrap();
+///
+/// assert_eq!(text_node.node_value(), Some("1\u{A0}km\u{A0}".to_string()));
+/// let references = text_node.character_references();
+/// assert_eq!(references.len(), 2);
+/// assert!(references[0].is_hexadecimal());
+/// assert_eq!(document_node.to_string(), "<p>1&#xA0;km&#160;</p>");
+/// # }
+/// ```
+///
+pub trait CharacterReferences: base::Node {
+    ///
+    /// Returns the character references recorded for this node, in order of offset; this is
+    /// empty if the node is not a text node, was not created by the parser, the option was not
+    /// set, or the data has been changed.
+    ///
+    fn character_references(&self) -> Vec<CharacterReference>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with its depth in the tree, and the size of
+/// the subtree below it; see the [`tree_sizes`](tree_sizes/index.html) module. These are constant
+/// time if the document was created with the
+/// [`set_track_tree_sizes`](options/struct.ProcessingOptions.html#method.set_track_tree_sizes)
+/// processing option, otherwise they walk the tree.
+///
+pub trait TreeSizes: base::Node {
+    ///
+    /// Returns the number of ancestors of this node; `0` for a document, or for a node that has
+    /// no parent, and `1` for the document element.
+    ///
+    fn depth(&self) -> usize;
+    ///
+    /// Returns the number of nodes in the subtree rooted at this node, including the node itself;
+    /// attributes are not counted, other than in the subtree of an attribute itself.
+    ///
+    fn subtree_size(&self) -> usize;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with the ability to detect that the document
+/// which owns a node has been dropped; see the [`teardown`](teardown/index.html) module. A node
+/// only holds a weak reference to its owner document, so once the document has been dropped
+/// `owner_document` returns `None` and operations requiring the document fail.
+///
+pub trait NodeLifetime: base::Node {
+    ///
+    /// Returns `true` if this node was created by a document which has since been dropped, else
+    /// `false`; a document, or a node not created by a document, is never orphaned.
+    ///
+    fn is_orphaned(&self) -> bool;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Node` with a record of the element from which an
+/// element was derived; see the [`provenance`](provenance/index.html) module.
+///
+pub trait NodeProvenance: base::Node {
+    ///
+    /// Returns the record of the element this element was derived from, if one was recorded.
+    ///
+    fn provenance(&self) -> Option<Provenance>;
+    ///
+    /// Returns the record of the element this element was derived from, followed by that of the
+    /// element it was derived from, and so on for as long as the sources are alive and have
+    /// records.
+    ///
+    fn provenance_chain(&self) -> Vec<Provenance>;
+    ///
+    /// Record that this element was derived from the element `source` by `operation`, replacing
+    /// any existing record; this is recorded whether or not the document tracks provenance. It
+    /// is an error, `Error::InvalidState`, if either node is not an element, or this element is
+    /// not owned by a document.
+    ///
+    fn record_provenance(&mut self, source: &Self::NodeRef, operation: Operation) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Document` with immutable snapshots of its tree, which
+/// share the subtrees unchanged since the previous snapshot; see the
+/// [`snapshot`](snapshot/index.html) module.
+///
+pub trait DocumentSnapshot: base::Document {
+    ///
+    /// Returns an immutable copy of this document's tree, sharing every subtree that is unchanged
+    /// since the last snapshot of this document was taken. It is an error,
+    /// `Error::InvalidState`, if this node is not a document.
+    ///
+    fn snapshot(&self) -> Result<Snapshot>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Text` with the DOM Level 3 `isElementContentWhitespace`
+/// attribute, and the detection of text that is only white space. These identify the white space
+/// that is purely structural, such as the indentation between elements, when a document is
+/// parsed with [`ParseOptions::set_preserve_whitespace`](../../parser/options/struct.ParseOptions.html#method.set_preserve_whitespace);
+/// such nodes may be removed with the `discard_element_content_whitespace` option of
+/// [`NormalizationOptions`](normalize/struct.NormalizationOptions.html).
+///
+/// # Specification
+///
+/// From DOM Level 3 Core [`Text.isElementContentWhitespace`](https://www.w3.org/TR/DOM-Level-3-Core/core.html#Text3-isElementContentWhitespace)
+/// -- Returns whether this text node contains element content whitespace, often abusively called
+/// "ignorable whitespace". The text node is determined to contain whitespace in element content
+/// during the load of the document or if validation occurs while using `Document.normalizeDocument()`.
+///
+/// As element declarations in a DTD are not processed, element content is identified from the
+/// document itself; see [`is_element_content_whitespace`](#tymethod.is_element_content_whitespace).
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "quick_parser")] {
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::ElementContentWhitespace;
+/// use xml_dom::parser::{read_xml_with, ParseOptions};
+///
+/// let mut options = ParseOptions::new();
+/// options.set_preserve_whitespace();
+/// let (document_node, _) = read_xml_with("<list>\n  <item> </item>\n</list>", options).unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let root_node = document.document_element().unwrap();
+///
+/// let indent_node = root_node.first_child().unwrap();
+/// assert!(indent_node.is_element_content_whitespace());
+/// let space_node = indent_node.next_sibling().unwrap().first_child().unwrap();
+/// assert!(space_node.is_whitespace());
+/// assert!(!space_node.is_element_content_whitespace());
+/// # }
+/// ```
+///
+pub trait ElementContentWhitespace: base::Text {
+    ///
+    /// Returns `true` if the data of this node is empty, or consists only of the white space
+    /// characters space, tab, carriage return, and line feed, else `false`.
+    ///
+    fn is_whitespace(&self) -> bool;
+    ///
+    /// Returns `true` if this is a `Text` node of white space within element content, else
+    /// `false`. The content of an element is element content if it has at least one child
+    /// element and all of its text is white space; white space within the scope of an
+    /// `xml:space="preserve"` attribute is never element content whitespace.
+    ///
+    fn is_element_content_whitespace(&self) -> bool;
+}
+
+// ------------------------------------------------------------------------------------------------
+
 ///
 /// This interface extends the DOM standard `Document` and allows the setting, and retrieval,
 /// of the XML declaration from the document prolog.
@@ -36,6 +859,449 @@ pub trait DocumentDecl: base::Document {
 
 // ------------------------------------------------------------------------------------------------
 
+///
+/// This interface extends the DOM standard `Document` with the ability to declare which attributes
+/// are of type ID, so that [`get_element_by_id`](../trait.Document.html#tymethod.get_element_by_id)
+/// finds elements identified by application-specific attributes, such as those declared by a
+/// schema.
+///
+/// # Specification
+///
+/// From DOM Level 2 Core [`Document.getElementById`](https://www.w3.org/TR/DOM-Level-2-Core/core.html#ID-getElBId)
+/// -- The DOM implementation must have information that says which attributes are of type ID.
+/// Attributes with the name "ID" are not of type ID unless so defined.
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::IdAttributes;
+///
+/// let mut document_node = get_implementation()
+///     .create_document(None, Some("catalog"), None)
+///     .unwrap();
+/// let document_ref = document_node.clone();
+/// let document = as_document(&document_ref).unwrap();
+/// let mut root_node = document.document_element().unwrap();
+/// root_node.set_attribute("key", "c1").unwrap();
+/// assert!(document.get_element_by_id("c1").is_none());
+///
+/// document_node.register_id_attribute(None, "key").unwrap();
+/// assert_eq!(document.get_element_by_id("c1"), Some(root_node));
+/// ```
+///
+pub trait IdAttributes: base::Document {
+    ///
+    /// Declare that attributes with the namespace `namespace_uri` and local name `local_name` are
+    /// of type ID. Any such attributes already in the document are added to the ID map, as are
+    /// those set later; it is an error, `Error::Syntax`, if this would give two elements the same
+    /// ID, in which case nothing is registered.
+    ///
+    /// Note that an attribute without a prefix has no namespace, whatever the namespace of its
+    /// element.
+    ///
+    fn register_id_attribute(
+        &mut self,
+        namespace_uri: Option<&str>,
+        local_name: &str,
+    ) -> Result<()>;
+    ///
+    /// Returns `true` if the attribute has been registered as an ID attribute, else `false`; this
+    /// does not include the `xml:id` attribute, which is always of type ID.
+    ///
+    fn is_registered_id_attribute(&self, namespace_uri: Option<&str>, local_name: &str) -> bool;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Document` with a
+/// [`TypeProvider`](schema/trait.TypeProvider.html), through which an external schema processor
+/// gives types to attributes and nillability to elements; see the
+/// [`schema`](schema/index.html) module for how these are used.
+///
+pub trait SchemaTypes: base::Document {
+    ///
+    /// Register `type_provider` on this document, replacing any registered before, or remove the
+    /// registered provider if it is `None`. The ID map is rebuilt with the types of the new
+    /// provider; it is an error, `Error::Syntax`, if this would give two elements the same ID, in
+    /// which case the previous provider is kept.
+    ///
+    fn set_type_provider(&mut self, type_provider: Option<Shared<dyn TypeProvider>>) -> Result<()>;
+    ///
+    /// Returns the type provider registered on this document, if any.
+    ///
+    fn type_provider(&self) -> Option<Shared<dyn TypeProvider>>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Document` with an index of its elements by the
+/// value of an attribute, built by the parser when
+/// [`ParseOptions::add_index_attribute`](../../parser/options/struct.ParseOptions.html#method.add_index_attribute)
+/// is set; this avoids a second pass over a large document to build lookup tables.
+///
+/// The index is not maintained as the document is changed; elements that have been removed from
+/// the document, or whose attribute value has changed, are left out of the index returned, but
+/// elements added to the document are not included.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "quick_parser")] {
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::ext::IndexedElements;
+/// use xml_dom::parser::{read_xml_with, ParseOptions};
+///
+/// let mut options = ParseOptions::new();
+/// options.add_index_attribute("code");
+/// let (document_node, _) = read_xml_with(
+///     r#"<units><unit code="km"/><unit code="m"/><alias code="km"/></units>"#,
+///     options,
+/// )
+/// .unwrap();
+///
+/// let index = document_node.indexed_elements("code").unwrap();
+/// assert_eq!(index.len(), 2);
+/// assert_eq!(index["km"].len(), 2);
+/// assert_eq!(index["m"][0].node_name().to_string(), "unit");
+/// assert!(document_node.indexed_elements("name").is_none());
+/// # }
+/// ```
+///
+pub trait IndexedElements: base::Document {
+    ///
+    /// Returns the elements with the attribute `name`, keyed by its value and in document order,
+    /// or `None` if the parser did not index the attribute `name`.
+    ///
+    fn indexed_elements(&self, name: &str) -> Option<HashMap<String, Vec<Self::NodeRef>>>;
+    ///
+    /// Returns the names of the attributes indexed by the parser.
+    ///
+    fn indexed_attributes(&self) -> Vec<String>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Document` with the ability to apply a set of
+/// mutations atomically, either all of them are applied or, if any fails or the combined result
+/// is not valid, none are. See the [`batch`](batch/index.html) module for the validation
+/// performed and an example.
+///
+pub trait Batch: base::Document {
+    ///
+    /// Call `mutations` to record the mutations to make in a [`Transaction`](batch/struct.Transaction.html),
+    /// then apply them in order and validate the result. If a mutation fails, or the result is
+    /// not valid, the document is returned to its state before the batch and every violation
+    /// found is returned.
+    ///
+    /// Note that a mutation is applied to the document as changed by those before it, and that
+    /// the combined result is only validated if every mutation succeeds.
+    ///
+    fn batch<F>(&mut self, mutations: F) -> std::result::Result<(), Vec<Violation>>
+    where
+        F: FnOnce(&mut Transaction);
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Document` with the ability to take apart its tree;
+/// see the [`teardown`](teardown/index.html) module.
+///
+pub trait DocumentTeardown: base::Document {
+    ///
+    /// Detach every node in this document's tree from its parent, without recursion, leaving
+    /// each node without a parent or children. Attributes remain on their elements, and the
+    /// document type remains with the document. It is an error, `Error::NoModificationAllowed`,
+    /// if any node in the tree is frozen, in which case the tree is unchanged.
+    ///
+    fn detach_all(&mut self) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Element` with the ability to set, and get, the value
+/// of attributes and text content from typed values, rather than strings. The values are written
+/// using the [`XmlValue`](mapping/trait.XmlValue.html) trait, which uses the lexical forms of the
+/// XML Schema datatypes for numbers, booleans, and, with the `chrono` feature, dates and times;
+/// see the [`xsd`](xsd/index.html) module.
+///
+pub trait TypedContent: base::Element {
+    ///
+    /// Set the attribute `name` to `value`.
+    ///
+    fn set_attribute_value<T: XmlValue>(&mut self, name: &str, value: &T) -> Result<()>;
+    ///
+    /// Returns the value of the attribute `name`, or `None` if it is not present; returns
+    /// `Error::Syntax` if the attribute is not a valid value of type `T`.
+    ///
+    fn attribute_value<T: XmlValue>(&self, name: &str) -> Result<Option<T>>;
+    ///
+    /// Replace all the children of this element with a single text node containing `value`.
+    ///
+    fn set_text_value<T: XmlValue>(&mut self, value: &T) -> Result<()>;
+    ///
+    /// Returns the value of the text content of this element, the concatenation of its text and
+    /// CDATA children, or `None` if it has no text content; returns `Error::Syntax` if the text is
+    /// not a valid value of type `T`.
+    ///
+    fn text_value<T: XmlValue>(&self) -> Result<Option<T>>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Document` with a form of the DOM Level 3
+/// `normalizeDocument` operation, governed by a set of
+/// [`NormalizationOptions`](normalize/struct.NormalizationOptions.html).
+///
+/// # Specification
+///
+/// From DOM Level 3 Core [`Document.normalizeDocument`](https://www.w3.org/TR/DOM-Level-3-Core/core.html#Document3-normalizeDocument)
+/// -- This method acts as if the document was going through a save and load cycle, putting the
+/// document in a "normal" form. As a consequence, this method updates the replacement tree of
+/// `EntityReference` nodes and normalizes `Text` nodes, as defined in the method
+/// `Node.normalize()`.
+///
+/// Unlike `Node::normalize` this applies to the full depth of the document; entity reference
+/// replacement trees are not updated.
+///
+pub trait NormalizeDocument: base::Document {
+    ///
+    /// Normalize the whole document according to `options`.
+    ///
+    fn normalize_document(&mut self, options: &NormalizationOptions) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `DocumentType` with the declarations of its internal
+/// subset in a structured form, as a list of [`DtdDeclaration`](dtd/enum.DtdDeclaration.html)
+/// values; see the [`dtd`](dtd/index.html) module.
+///
+/// The DOM only provides the internal subset as a string, and the entities and notations it
+/// declares as nodes. The element and attribute list declarations allow a client to validate the
+/// content of elements, and to add the default values of attributes; changing the declarations,
+/// and setting them, replaces the internal subset written with the document type.
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::DtdDeclarations;
+/// use xml_dom::parser::read_xml;
+///
+/// let document_node = read_xml(
+///     r#"<!DOCTYPE note [
+///   <!ELEMENT note (#PCDATA)>
+///   <!ATTLIST note lang CDATA "en" id ID #REQUIRED>
+/// ]><note id="n1">Hello</note>"#,
+/// ).unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let doc_type = document.doc_type().unwrap();
+///
+/// assert_eq!(doc_type.declarations().unwrap().len(), 2);
+/// assert!(doc_type.element_declaration("note").is_some());
+/// assert_eq!(
+///     doc_type.default_attributes("note"),
+///     vec![("lang".to_string(), "en".to_string())]
+/// );
+/// ```
+///
+pub trait DtdDeclarations: base::DocumentType {
+    ///
+    /// Returns the declarations in the internal subset, in order; or an empty list if there is
+    /// no internal subset. It is an error, `Error::Syntax`, if the internal subset cannot be
+    /// parsed.
+    ///
+    fn declarations(&self) -> Result<Vec<DtdDeclaration>>;
+    ///
+    /// Replace the internal subset with `declarations`, each written in its normalized form. The
+    /// entity and notation nodes declared by the previous internal subset are replaced with those
+    /// of `declarations`.
+    ///
+    fn set_declarations(&mut self, declarations: &[DtdDeclaration]) -> Result<()>;
+    ///
+    /// Returns the declaration of the element type `name`, if the internal subset declares it.
+    ///
+    fn element_declaration(&self, name: &str) -> Option<ElementDecl> {
+        self.declarations()
+            .ok()?
+            .into_iter()
+            .find_map(|declaration| match declaration {
+                DtdDeclaration::Element(decl) if decl.name == name => Some(decl),
+                _ => None,
+            })
+    }
+    ///
+    /// Returns the attributes declared for the element type `element_name`, across all of its
+    /// attribute list declarations. As in XML the first declaration of an attribute is binding,
+    /// later declarations of the same name are ignored.
+    ///
+    fn attribute_declarations(&self, element_name: &str) -> Vec<AttributeDef> {
+        let mut attributes: Vec<AttributeDef> = Vec::new();
+        for declaration in self.declarations().unwrap_or_default() {
+            if let DtdDeclaration::AttributeList(decl) = declaration {
+                if decl.element_name == element_name {
+                    for attribute in decl.attributes {
+                        if !attributes.iter().any(|known| known.name == attribute.name) {
+                            attributes.push(attribute);
+                        }
+                    }
+                }
+            }
+        }
+        attributes
+    }
+    ///
+    /// Returns the name and default value of each attribute declared for the element type
+    /// `element_name` that has one, either a default or a `#FIXED` value.
+    ///
+    fn default_attributes(&self, element_name: &str) -> Vec<(String, String)> {
+        self.attribute_declarations(element_name)
+            .into_iter()
+            .filter_map(|attribute| {
+                let value = attribute.default.value()?.clone();
+                Some((attribute.name, value))
+            })
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `DocumentType`, which may not be changed once created,
+/// so that a document type built by a program can declare entities and notations, and carry an
+/// internal subset; these are written with the document type, in the document's prolog.
+///
+/// Entities and notations are created with [`create_entity`](dom_impl/fn.create_entity.html),
+/// [`create_internal_entity`](dom_impl/fn.create_internal_entity.html), and
+/// [`create_notation`](dom_impl/fn.create_notation.html), which need the document, so the
+/// document type is first attached to a new document by `create_document`.
+///
+/// The internal subset is the authoritative form of the declarations it contains, and is written
+/// in place of the entity and notation nodes; these methods keep the two the same, and the entity
+/// and notation nodes themselves are read-only.
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::dom_impl::{create_internal_entity, create_notation};
+/// use xml_dom::level2::ext::DocumentTypeExt;
+///
+/// let implementation = get_implementation();
+/// let doc_type = implementation
+///     .create_document_type("note", None, None)
+///     .unwrap();
+/// let document_node = implementation
+///     .create_document(None, Some("note"), Some(doc_type))
+///     .unwrap();
+///
+/// let mut doc_type = as_document(&document_node).unwrap().doc_type().unwrap();
+/// assert_eq!(doc_type.owner_document(), Some(document_node.clone()));
+/// doc_type.set_internal_subset(Some("<!ELEMENT note (#PCDATA)>")).unwrap();
+/// let entity = create_internal_entity(document_node.clone(), "writer", "Donald Duck.").unwrap();
+/// let _ = doc_type.add_entity(entity).unwrap();
+/// let notation = create_notation(document_node.clone(), "gif", None, Some("image/gif")).unwrap();
+/// let _ = doc_type.add_notation(notation).unwrap();
+///
+/// assert_eq!(
+///     document_node.to_string(),
+///     r#"<!DOCTYPE note [<!ELEMENT note (#PCDATA)><!ENTITY writer "Donald Duck."><!NOTATION gif SYSTEM "image/gif">]><note></note>"#
+/// );
+/// ```
+///
+pub trait DocumentTypeExt: base::DocumentType {
+    ///
+    /// Add the `Entity` node `entity` to the entities of this document type, replacing, and
+    /// returning, any entity with the same name; its declaration replaces that of the same name
+    /// in the internal subset, or is added to the end. It is an error, `Error::HierarchyRequest`, if
+    /// `entity` is not an entity, and `Error::WrongDocument` if it was created for a document
+    /// other than the one this document type is attached to.
+    ///
+    fn add_entity(&mut self, entity: Self::NodeRef) -> Result<Option<Self::NodeRef>>;
+    ///
+    /// Add the `Notation` node `notation` to the notations of this document type, replacing, and
+    /// returning, any notation with the same name, and its declaration in the internal subset; the
+    /// errors are as for `add_entity`.
+    ///
+    fn add_notation(&mut self, notation: Self::NodeRef) -> Result<Option<Self::NodeRef>>;
+    ///
+    /// Replace the internal subset with `internal_subset`, or remove it if `None`. It is an
+    /// error, `Error::Syntax`, if the text is not a sequence of declarations, see
+    /// [`parse_internal_subset`](dtd/fn.parse_internal_subset.html). The entity and notation
+    /// nodes declared by the previous internal subset are replaced with those declared by the
+    /// new one.
+    ///
+    fn set_internal_subset(&mut self, internal_subset: Option<&str>) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Document` with validation against the element type
+/// and attribute list declarations in the internal subset of its document type; see the
+/// [`dtd`](dtd/index.html) module.
+///
+/// # Specification
+///
+/// From XML 1.0 [§2.8 Prolog and Document Type Declaration](https://www.w3.org/TR/xml/#dt-valid)
+/// -- An XML document is valid if it has an associated document type declaration and if the
+/// document complies with the constraints expressed in it.
+///
+/// The constraints checked are the name of the document element, the content model of each
+/// element, and for its attributes that each is declared, that those declared `#REQUIRED` are
+/// present, and that enumerated, and `#FIXED`, values match. Entity references are replaced by
+/// their content when matching content models; ID and IDREF constraints are not checked, see
+/// [`check_document`](validate/fn.check_document.html) for duplicate IDs.
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::ext::dtd::DtdViolationKind;
+/// use xml_dom::level2::ext::DtdValidation;
+/// use xml_dom::parser::read_xml;
+///
+/// let document_node = read_xml(
+///     r#"<!DOCTYPE note [
+///   <!ELEMENT note (to, body)>
+///   <!ELEMENT to (#PCDATA)>
+///   <!ELEMENT body (#PCDATA)>
+///   <!ATTLIST note priority (low|high) #REQUIRED>
+/// ]><note priority="urgent"><body>Hello</body></note>"#,
+/// ).unwrap();
+///
+/// let violations = document_node.validate_against_dtd().unwrap();
+/// assert_eq!(violations.len(), 2);
+/// assert_eq!(violations[0].kind, DtdViolationKind::InvalidContent("(to,body)".to_string()));
+/// assert_eq!(
+///     violations[1].kind,
+///     DtdViolationKind::InvalidAttributeValue("priority".to_string())
+/// );
+/// ```
+///
+pub trait DtdValidation: base::Document {
+    ///
+    /// Returns the problems found validating this document, in document order; the result is
+    /// empty if the document is valid. It is an error, `Error::InvalidState`, if the document has
+    /// no document type, and `Error::Syntax` if its internal subset cannot be parsed.
+    ///
+    fn validate_against_dtd(&self) -> Result<Vec<DtdViolation>>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
 ///
 /// This corresponds to the DOM `DOMImplementation` interface.
 ///
@@ -133,4 +1399,136 @@ pub trait Namespaced: base::Element {
     /// with a prefix for this, or any parent, element.
     ///
     fn resolve_prefix(&self, namespace_uri: &str) -> NamespacePrefix;
+
+    ///
+    /// Returns the namespace URI bound to `prefix`, or the default namespace if `prefix` is
+    /// `None`, in scope at this node. Unlike `resolve_namespace` this does not depend on the
+    /// mapping tables, it considers the namespace and prefix of each element, and its `xmlns`
+    /// attributes, from this node up through its ancestors; the prefixes `xml` and `xmlns` are
+    /// always bound.
+    ///
+    /// # Specification
+    ///
+    /// From DOM Level 3 Core [`Node.lookupNamespaceURI`](https://www.w3.org/TR/DOM-Level-3-Core/core.html#Node3-lookupNamespaceURI)
+    /// -- Look up the namespace URI associated to the given prefix, starting from this node. See
+    /// [Namespace URI Lookup](https://www.w3.org/TR/DOM-Level-3-Core/namespaces-algorithms.html#lookupNamespaceURIAlgo)
+    /// for details on the algorithm used by this method.
+    ///
+    /// The lookup starts at the document element for a document, the owner element for an
+    /// attribute, and the nearest ancestor element for other nodes.
+    ///
+    fn lookup_namespace_uri(&self, prefix: Option<&str>) -> Option<String>;
+    ///
+    /// Returns a prefix, other than the default, bound to `namespace_uri` in scope at this node;
+    /// a prefix is only returned if it has not been bound to another namespace nearer to this
+    /// node.
+    ///
+    /// # Specification
+    ///
+    /// From DOM Level 3 Core [`Node.lookupPrefix`](https://www.w3.org/TR/DOM-Level-3-Core/core.html#Node3-lookupNamespacePrefix)
+    /// -- Look up the prefix associated to the given namespace URI, starting from this node. The
+    /// default namespace declarations are ignored by this method. See
+    /// [Namespace Prefix Lookup](https://www.w3.org/TR/DOM-Level-3-Core/namespaces-algorithms.html#lookupNamespacePrefixAlgo)
+    /// for details on the algorithm used by this method.
+    ///
+    fn lookup_prefix(&self, namespace_uri: &str) -> Option<String>;
+    ///
+    /// Returns `true` if `namespace_uri` is the default namespace in scope at this node, else
+    /// `false`.
+    ///
+    /// # Specification
+    ///
+    /// From DOM Level 3 Core [`Node.isDefaultNamespace`](https://www.w3.org/TR/DOM-Level-3-Core/core.html#Node3-isDefaultNamespace)
+    /// -- This method checks if the specified `namespaceURI` is the default namespace or not.
+    ///
+    fn is_default_namespace(&self, namespace_uri: Option<&str>) -> bool;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that manages element content held in `CDATASection` nodes, for example
+/// when embedding scripts or markup fragments in a generated document.
+///
+/// # Specification
+///
+/// From XML 1.1 [§2.7 CDATA Sections](https://www.w3.org/TR/xml11/#sec-cdata-sect) -- Within
+/// a CDATA section, only the `CDEnd` string is recognized as markup, so that left angle brackets
+/// and ampersands may occur in their literal form; they need not (and cannot) be escaped using
+/// "`&lt;`" and "`&amp;`". CDATA sections cannot nest.
+///
+/// ```ebnf
+/// CDSect  ::= CDStart CData CDEnd
+/// CDStart ::= '<![CDATA['
+/// CData   ::= (Char* - (Char* ']]>' Char*))
+/// CDEnd   ::= ']]>'
+/// ```
+///
+pub trait CDataContent: base::Element {
+    ///
+    /// Replace all of the children of this element with CDATA content. As a CDATA section cannot
+    /// contain the `CDEnd` string "`]]>`", any occurrence causes the data to be split across
+    /// adjacent sections between the "`]]`" and the "`>`".
+    ///
+    fn set_cdata(&mut self, data: &str) -> Result<()>;
+    ///
+    /// Returns the children of this element that are CDATA sections, in document order.
+    ///
+    fn cdata_sections(&self) -> Vec<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `Element` with access to the serialized form of the
+/// element, or of its content, as found in most DOM-like libraries. The content may be replaced
+/// by parsing new markup, this requires the `quick_parser` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+/// use xml_dom::level2::ext::InnerXml;
+///
+/// let document_node = get_implementation()
+///     .create_document(None, Some("p"), None)
+///     .unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let mut root_node = document.document_element().unwrap();
+/// let _ = root_node.append_child(document.create_text_node("plain")).unwrap();
+/// assert_eq!(root_node.inner_xml(), "plain");
+/// assert_eq!(root_node.outer_xml(), "<p>plain</p>");
+///
+/// # #[cfg(feature = "quick_parser")] {
+/// root_node.set_inner_xml("Some <b>bold</b> text").unwrap();
+/// assert_eq!(root_node.outer_xml(), "<p>Some<b>bold</b>text</p>");
+/// assert!(root_node.set_inner_xml("<b>unclosed").is_err());
+/// assert_eq!(root_node.child_nodes().len(), 3);
+/// # }
+/// ```
+///
+pub trait InnerXml: base::Element {
+    ///
+    /// Returns the serialized form of the children of this element, in order.
+    ///
+    fn inner_xml(&self) -> String;
+    ///
+    /// Returns the serialized form of this element, including its start and end tags.
+    ///
+    fn outer_xml(&self) -> String;
+    ///
+    /// Replace the children of this element with the content parsed from `xml`, which may
+    /// contain any content allowed in an element, and may use the namespace prefixes in scope at
+    /// this element; see [`parser::read_fragment`](../../parser/fn.read_fragment.html). The
+    /// children are unchanged if `xml` cannot be parsed.
+    ///
+    /// # Errors
+    ///
+    /// * `Syntax`: if `xml` is not well-formed.
+    /// * `InvalidState`: if this node is not an element.
+    /// * Any error raised by the DOM while constructing the new content.
+    ///
+    #[cfg(feature = "quick_parser")]
+    fn set_inner_xml(&mut self, xml: &str) -> Result<()>;
 }
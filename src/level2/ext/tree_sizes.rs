@@ -0,0 +1,156 @@
+/*!
+Provides the implementation of the [`TreeSizes`](../trait.TreeSizes.html) trait, the depth of a
+node and the size of its subtree.
+
+When the document was created with the
+[`set_track_tree_sizes`](../options/struct.ProcessingOptions.html#method.set_track_tree_sizes)
+processing option, both are recorded on each node and updated as children are added and removed,
+so that pruning, budgeting, and progress reporting can read them cheaply; otherwise they are
+computed by walking the tree each time they are requested.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::dom_impl::get_implementation_ext;
+use xml_dom::level2::ext::{ProcessingOptions, TreeSizes};
+
+let mut options = ProcessingOptions::new();
+options.set_track_tree_sizes();
+let document_node = get_implementation_ext()
+    .create_document_with_options(None, Some("root"), None, options)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let mut child_node = root_node
+    .append_child(document.create_element("child").unwrap())
+    .unwrap();
+let text_node = child_node
+    .append_child(document.create_text_node("text"))
+    .unwrap();
+
+assert_eq!(document_node.subtree_size(), 4);
+assert_eq!(root_node.subtree_size(), 3);
+assert_eq!(text_node.depth(), 3);
+```
+
+*/
+
+use crate::level2::ext::tag_index::tree_changed;
+use crate::level2::ext::traits::TreeSizes;
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::document_options;
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl TreeSizes for RefNode {
+    fn depth(&self) -> usize {
+        if is_tracked(self) {
+            self.borrow().i_depth
+        } else {
+            let mut depth = 0;
+            let mut current = parent(self);
+            while let Some(node) = current {
+                depth += 1;
+                current = parent(&node);
+            }
+            depth
+        }
+    }
+
+    fn subtree_size(&self) -> usize {
+        if is_tracked(self) {
+            self.borrow().i_subtree_size
+        } else {
+            let mut size = 0;
+            let mut stack = vec![self.clone()];
+            while let Some(node) = stack.pop() {
+                size += 1;
+                stack.extend(node.borrow().i_child_nodes.iter().cloned());
+            }
+            size
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Crate Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Called once `child` has been added to the children of `parent`, and its parent set; adds the
+/// size of its subtree to `parent` and its ancestors, and sets the depth of each node in it. Any
+/// element index of the document is also discarded.
+///
+pub(crate) fn child_attached(parent: &RefNode, child: &RefNode) {
+    tree_changed(parent);
+    if is_tracked(parent) {
+        let depth = parent.borrow().i_depth + 1;
+        set_depths(child, depth);
+        add_to_ancestors(parent, child.borrow().i_subtree_size, true);
+    }
+}
+
+///
+/// Called once `child` has been removed from the children of `parent`, and its parent cleared;
+/// removes the size of its subtree from `parent` and its ancestors, and makes `child` the root
+/// of its subtree. Any element index of the document is also discarded.
+///
+pub(crate) fn child_detached(parent: &RefNode, child: &RefNode) {
+    tree_changed(parent);
+    if is_tracked(parent) {
+        set_depths(child, 0);
+        add_to_ancestors(parent, child.borrow().i_subtree_size, false);
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn is_tracked(node: &RefNode) -> bool {
+    document_options(node).has_track_tree_sizes()
+}
+
+fn parent(node: &RefNode) -> Option<RefNode> {
+    node.borrow()
+        .i_parent_node
+        .as_ref()
+        .and_then(|parent_node| parent_node.clone().upgrade())
+}
+
+fn add_to_ancestors(node: &RefNode, size: usize, add: bool) {
+    let mut current = Some(node.clone());
+    while let Some(node) = current {
+        {
+            let mut mut_node = node.borrow_mut();
+            if add {
+                mut_node.i_subtree_size += size;
+            } else {
+                mut_node.i_subtree_size = mut_node.i_subtree_size.saturating_sub(size);
+            }
+        }
+        current = parent(&node);
+    }
+}
+
+fn set_depths(node: &RefNode, depth: usize) {
+    // the depths within a subtree are always consistent with that of its root.
+    if node.borrow().i_depth == depth {
+        return;
+    }
+    let mut stack = vec![(node.clone(), depth)];
+    while let Some((node, depth)) = stack.pop() {
+        let mut mut_node = node.borrow_mut();
+        mut_node.i_depth = depth;
+        stack.extend(
+            mut_node
+                .i_child_nodes
+                .iter()
+                .map(|child_node| (child_node.clone(), depth + 1)),
+        );
+    }
+}
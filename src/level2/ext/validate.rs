@@ -0,0 +1,371 @@
+/*!
+Provides [`check_document`](fn.check_document.html), which audits a tree built by a program for
+structural problems that the DOM methods themselves do not prevent, but that would produce
+output that is not well-formed, or not namespace-well-formed, when serialized.
+
+The problems reported, as a [`Violation`](struct.Violation.html) for each, are:
+
+* a document with more than one document element.
+* a text node that is a child of the document itself.
+* an attribute whose name is not a qualified name.
+* two `xml:id`, or other ID, attributes with the same value.
+* an element or attribute name using a prefix that is not declared by an `xmlns` attribute on the
+  element, or one of its ancestors.
+* a CDATA section whose content contains the CDATA-section-close delimiter `]]>`.
+
+When a [`TypeProvider`](../schema/trait.TypeProvider.html) is registered on the document the
+following are also reported.
+
+* an `IDREF`, or `IDREFS`, attribute value that is not the ID of an element in the document.
+* an element with `xsi:nil="true"` that is not nillable.
+* a nillable element with `xsi:nil="true"` that has text, or element, content.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::validate::{check_document, ViolationKind};
+
+let document_node = get_implementation()
+    .create_document(None, Some("doc"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let _ = root_node
+    .append_child(document.create_cdata_section("a ]]> b").unwrap())
+    .unwrap();
+let _ = root_node
+    .append_child(document.create_element_ns("urn:x", "x:item").unwrap())
+    .unwrap();
+
+let violations = check_document(&document_node);
+assert_eq!(violations.len(), 2);
+assert_eq!(violations[0].kind, ViolationKind::CDataEnd);
+assert_eq!(
+    violations[1].kind,
+    ViolationKind::UnboundPrefix("x".to_string())
+);
+```
+
+*/
+
+use crate::level2::convert::{as_attribute, as_document, is_document, is_element};
+use crate::level2::ext::dtd::AttributeType;
+use crate::level2::ext::namespaced::declared_namespace;
+use crate::level2::ext::schema::{document_type_provider, is_nilled};
+use crate::level2::ext::traits::Axes;
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::is_document_id_attribute;
+use crate::level2::traits::NodeType;
+use crate::shared::name::Name;
+use crate::shared::syntax::{
+    XMLNS_NS_ATTRIBUTE, XML_CDATA_END, XML_NS_ATTRIBUTE, XML_NS_SEPARATOR,
+};
+use crate::shared::text::is_xml_name;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A single structural problem found by [`check_document`](fn.check_document.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// The node found to be invalid; for an attribute problem this is the attribute itself.
+    pub node: RefNode,
+    /// The problem found.
+    pub kind: ViolationKind,
+}
+
+///
+/// The kinds of problem reported by [`check_document`](fn.check_document.html).
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The document has more than one element child; reported for each element after the first.
+    MultipleDocumentElements,
+    /// A text node is a child of the document.
+    TextInDocument,
+    /// The name of an attribute is not a qualified name.
+    InvalidAttributeName(String),
+    /// An ID attribute has the same value as one that precedes it in document order.
+    DuplicateId(String),
+    /// The prefix of an element, or attribute, name is not declared in scope.
+    UnboundPrefix(String),
+    /// The content of a CDATA section contains `]]>`.
+    CDataEnd,
+    /// An `IDREF`, or `IDREFS`, attribute refers to an ID that no element has; reported after
+    /// the problems found in document order.
+    DanglingIdRef(String),
+    /// An element has `xsi:nil="true"` but is not nillable.
+    NotNillable,
+    /// An element has `xsi:nil="true"` but also has content.
+    NilWithContent,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Walk `node`, and all of its descendants, returning the problems found in document order; the
+/// result is empty if none were found. Checks that concern the document itself are only made if
+/// `node` is a `Document`.
+///
+pub fn check_document(node: &RefNode) -> Vec<Violation> {
+    let mut violations: Vec<Violation> = Vec::new();
+    let mut violation = |node: &RefNode, kind: ViolationKind| {
+        violations.push(Violation {
+            node: node.clone(),
+            kind,
+        })
+    };
+    let document_node = if is_document(node) {
+        Some(node.clone())
+    } else {
+        node.owner_document()
+    };
+    let type_provider = document_node.as_ref().and_then(document_type_provider);
+    let mut ids: HashMap<String, RefNode> = HashMap::new();
+    let mut id_refs: Vec<(RefNode, String)> = Vec::new();
+
+    for node in node.descendant_or_self() {
+        match node.node_type() {
+            NodeType::Document => {
+                for child_node in node.child_nodes().iter().filter(|c| is_element(c)).skip(1) {
+                    violation(child_node, ViolationKind::MultipleDocumentElements);
+                }
+            }
+            NodeType::Text if node.parent_node().is_some_and(|p| is_document(&p)) => {
+                violation(&node, ViolationKind::TextInDocument);
+            }
+            NodeType::CData
+                if node
+                    .node_value()
+                    .is_some_and(|data| data.contains(XML_CDATA_END)) =>
+            {
+                violation(&node, ViolationKind::CDataEnd);
+            }
+            NodeType::Element => {
+                if let Some(prefix) = unbound_prefix(&node, &node.node_name()) {
+                    violation(&node, ViolationKind::UnboundPrefix(prefix));
+                }
+                if let (Some(type_provider), true) = (&type_provider, is_nilled(&node)) {
+                    if !type_provider.is_nillable(&node.node_name()) {
+                        violation(&node, ViolationKind::NotNillable);
+                    } else if node.child_nodes().iter().any(is_content) {
+                        violation(&node, ViolationKind::NilWithContent);
+                    }
+                }
+                for (name, attribute) in node.attributes() {
+                    let qualified_name = name.to_string();
+                    if !is_qualified_name(&qualified_name) {
+                        violation(
+                            &attribute,
+                            ViolationKind::InvalidAttributeName(qualified_name),
+                        );
+                        continue;
+                    }
+                    if let Some(prefix) = unbound_prefix(&node, &name) {
+                        violation(&attribute, ViolationKind::UnboundPrefix(prefix));
+                    }
+                    let is_id = document_node.as_ref().is_some_and(|document_node| {
+                        is_document_id_attribute(document_node, &node.node_name(), &name)
+                    });
+                    let value = as_attribute(&attribute).ok().and_then(|a| a.value());
+                    if let (Some(type_provider), Some(value)) = (&type_provider, &value) {
+                        if matches!(
+                            type_provider.attribute_type(&node.node_name(), &name),
+                            Some(AttributeType::IdRef | AttributeType::IdRefs)
+                        ) {
+                            id_refs.extend(
+                                value
+                                    .split_whitespace()
+                                    .map(|id_ref| (attribute.clone(), id_ref.to_string())),
+                            );
+                        }
+                    }
+                    if let (true, Some(value)) = (is_id, value) {
+                        match ids.get(&value) {
+                            Some(other) if other != &node => {
+                                violation(&attribute, ViolationKind::DuplicateId(value))
+                            }
+                            _ => {
+                                let _safe_to_ignore = ids.insert(value, node.clone());
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    for (attribute, id_ref) in id_refs {
+        let is_found = ids.contains_key(&id_ref)
+            || document_node
+                .as_ref()
+                .and_then(|document_node| as_document(document_node).ok())
+                .is_some_and(|document| document.get_element_by_id(&id_ref).is_some());
+        if !is_found {
+            violation(&attribute, ViolationKind::DanglingIdRef(id_ref));
+        }
+    }
+    violations
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} ({})", self.kind, self.node.node_name())
+    }
+}
+
+impl Display for ViolationKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::MultipleDocumentElements => write!(f, "the document has more than one element"),
+            Self::TextInDocument => write!(f, "the document has a text child"),
+            Self::InvalidAttributeName(name) => {
+                write!(f, "the attribute name {:?} is not a qualified name", name)
+            }
+            Self::DuplicateId(value) => write!(f, "the ID {:?} is used more than once", value),
+            Self::UnboundPrefix(prefix) => write!(f, "the prefix {:?} is not declared", prefix),
+            Self::CDataEnd => write!(f, "the CDATA section contains {:?}", XML_CDATA_END),
+            Self::DanglingIdRef(value) => write!(f, "no element has the ID {:?}", value),
+            Self::NotNillable => write!(f, "the element is nilled but not nillable"),
+            Self::NilWithContent => write!(f, "the element is nilled but has content"),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// ```ebnf
+/// QName          ::= PrefixedName | UnprefixedName
+/// PrefixedName   ::= Prefix ':' LocalPart
+/// ```
+///
+fn is_qualified_name(name: &str) -> bool {
+    match name.split_once(XML_NS_SEPARATOR) {
+        None => is_xml_name(name),
+        Some((prefix, local_part)) => {
+            is_xml_name(prefix) && is_xml_name(local_part) && !local_part.contains(XML_NS_SEPARATOR)
+        }
+    }
+}
+
+///
+/// Returns `true` if `node` is content that a nilled element may not have; comments and
+/// processing instructions are allowed.
+///
+fn is_content(node: &RefNode) -> bool {
+    matches!(
+        node.node_type(),
+        NodeType::Element | NodeType::Text | NodeType::CData | NodeType::EntityReference
+    )
+}
+
+///
+/// Returns the prefix of `name` if it is not declared on `element`, or any of its ancestors; the
+/// `xml` and `xmlns` prefixes are always bound.
+///
+fn unbound_prefix(element: &RefNode, name: &Name) -> Option<String> {
+    let prefix = name.prefix().as_deref()?;
+    if prefix == XML_NS_ATTRIBUTE || prefix == XMLNS_NS_ATTRIBUTE {
+        return None;
+    }
+    let mut current = Some(element.clone());
+    while let Some(node) = current {
+        if is_element(&node) {
+            match declared_namespace(&node, Some(prefix)) {
+                Some(Some(_)) => return None,
+                // an empty value undeclares the prefix.
+                Some(None) => break,
+                None => {}
+            }
+        }
+        current = node.parent_node();
+    }
+    Some(prefix.to_string())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level2::dom_impl::get_implementation;
+    use crate::level2::node_impl::Extension;
+
+    // the DOM methods do not allow these trees, so nodes are added directly.
+    fn push_child(parent_node: &RefNode, child_node: RefNode) {
+        child_node.borrow_mut().i_parent_node = Some(parent_node.clone().downgrade());
+        parent_node.borrow_mut().i_child_nodes.push(child_node);
+    }
+
+    fn push_attribute(element: &RefNode, attribute: RefNode) {
+        let name = attribute.node_name();
+        if let Extension::Element { i_attributes, .. } = &mut element.borrow_mut().i_extension {
+            let _safe_to_ignore = i_attributes.insert(name, attribute);
+        }
+    }
+
+    #[test]
+    fn test_check_prevented_by_dom() {
+        let document_node = get_implementation()
+            .create_document(None, Some("doc"), None)
+            .unwrap();
+        let document = as_document(&document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        let text_node = document.create_text_node("loose");
+        let other_node = document.create_element("other").unwrap();
+        push_child(&document_node, text_node.clone());
+        push_child(&document_node, other_node.clone());
+
+        root_node.set_attribute("xml:id", "one").unwrap();
+        let item_node = document.create_element("item").unwrap();
+        let id_node = document.create_attribute_with("xml:id", "one").unwrap();
+        push_attribute(&item_node, id_node.clone());
+        push_child(&root_node, item_node);
+
+        assert_eq!(
+            check_document(&document_node),
+            vec![
+                Violation {
+                    node: other_node,
+                    kind: ViolationKind::MultipleDocumentElements,
+                },
+                Violation {
+                    node: id_node,
+                    kind: ViolationKind::DuplicateId("one".to_string()),
+                },
+                Violation {
+                    node: text_node,
+                    kind: ViolationKind::TextInDocument,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_qualified_names() {
+        for name in ["a", "a:b", "xmlns:a", "a.b-c"] {
+            assert!(is_qualified_name(name), "{:?}", name);
+        }
+        for name in ["", ":a", "a:", "a:b:c", "1a", "a:1b"] {
+            assert!(!is_qualified_name(name), "{:?}", name);
+        }
+    }
+}
@@ -0,0 +1,937 @@
+/*!
+Provides control over the serialization of nodes beyond the default behavior of the `Display`
+implementation for [`RefNode`](../../type.RefNode.html).
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::writer::{node_to_string, WriterOptions};
+
+let implementation = get_implementation();
+let document_node = implementation
+    .create_document(None, Some("root"), None)
+    .unwrap();
+
+let mut options = WriterOptions::new();
+options.set_omit_unspecified_attributes();
+
+assert_eq!(node_to_string(&document_node, &options), document_node.to_string());
+```
+
+When debugging a pipeline that transforms documents it can be hard to tell which in-memory node
+produced a particular piece of output. Setting the `annotate_elements` option writes a comment
+before each element with its [`node_id`](fn.node_id.html) and [`node_path`](fn.node_path.html).
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::writer::{node_id, node_to_string, WriterOptions};
+
+let document_node = get_implementation()
+    .create_document(None, Some("root"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let root_node = document.document_element().unwrap();
+
+let mut options = WriterOptions::new();
+options.set_annotate_elements();
+
+assert_eq!(
+    node_to_string(&root_node, &options),
+    format!("<!-- id={} path=/root --><root></root>", node_id(&root_node))
+);
+```
+
+Elements and attributes created with a namespace, for example by
+[`create_element_ns`](../../trait.Document.html#tymethod.create_element_ns), are written with
+their prefix but without declaring it unless an `xmlns` attribute has been added. Setting the
+`normalize_namespaces` option adds the missing declarations to the output.
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::writer::{node_to_string, WriterOptions};
+
+let document_node = get_implementation()
+    .create_document(Some("urn:example"), Some("ex:root"), None)
+    .unwrap();
+
+let mut options = WriterOptions::new();
+options.set_normalize_namespaces();
+
+assert_eq!(document_node.to_string(), "<ex:root></ex:root>");
+assert_eq!(
+    node_to_string(&document_node, &options),
+    r#"<ex:root xmlns:ex="urn:example"></ex:root>"#
+);
+```
+
+By default each declaration is added to the first element that uses the namespace; with the
+[`NamespacePlacement::DocumentElement`](enum.NamespacePlacement.html) placement they are all
+written on the document element instead, as preferred by some XSLT processors and signature
+tools. The function [`namespace_conflicts`](fn.namespace_conflicts.html) reports the prefixes
+that cannot be hoisted as they are bound to more than one namespace.
+
+Documents that are served as HTML to older renderers need to follow a few extra rules, setting
+the `xhtml_compatible` option writes them accordingly.
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::writer::{node_to_string, WriterOptions};
+
+let document_node = get_implementation()
+    .create_document(None, Some("html"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+for name in ["br", "p"] {
+    let _safe_to_ignore = root_node
+        .append_child(document.create_element(name).unwrap())
+        .unwrap();
+}
+
+let mut options = WriterOptions::new();
+options.set_xhtml_compatible();
+
+assert_eq!(
+    node_to_string(&document_node, &options),
+    r#"<html xmlns="http://www.w3.org/1999/xhtml"><br /><p></p></html>"#
+);
+```
+
+Setting the `indent` option writes the children of elements with element content on separate,
+indented, lines; the content of elements within the scope of an `xml:space="preserve"`
+attribute is written unchanged.
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::writer::{node_to_string, WriterOptions};
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml(
+    r#"<doc><list><item>a</item><item>b</item></list><pre xml:space="preserve"><b>x</b><i>y</i></pre></doc>"#,
+)
+.unwrap();
+
+let mut options = WriterOptions::new();
+options.set_indent("  ");
+
+assert_eq!(
+    node_to_string(&document_node, &options),
+    r#"<doc>
+  <list>
+    <item>a</item>
+    <item>b</item>
+  </list>
+  <pre xml:space="preserve"><b>x</b><i>y</i></pre>
+</doc>"#
+);
+```
+
+The `Display` implementation always produces a Rust, UTF-8, string regardless of the encoding
+named in the document's XML declaration. The function
+[`write_document_with_encoding`](fn.write_document_with_encoding.html) instead writes bytes in
+the declared encoding, replacing characters that the encoding cannot represent with numeric
+character references. The function [`serialize_to_bytes`](fn.serialize_to_bytes.html) writes in
+an encoding chosen by the caller, and the XML declaration advertises that encoding.
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{DocumentDecl, XmlDecl, XmlVersion};
+use xml_dom::level2::ext::convert::as_document_decl_mut;
+use xml_dom::level2::ext::writer::{write_document_with_encoding, WriterOptions};
+
+let mut document_node = get_implementation()
+    .create_document(None, Some("price"), None)
+    .unwrap();
+let document = as_document_decl_mut(&mut document_node).unwrap();
+document
+    .set_xml_declaration(XmlDecl::new(XmlVersion::V10, Some("US-ASCII".to_string()), None))
+    .unwrap();
+let mut root_node = document.document_element().unwrap();
+let _safe_to_ignore = root_node
+    .append_child(document.create_text_node("€10"))
+    .unwrap();
+
+let mut bytes: Vec<u8> = Vec::new();
+write_document_with_encoding(&document_node, &WriterOptions::new(), &mut bytes).unwrap();
+assert_eq!(
+    bytes,
+    br#"<?xml version="1.0" encoding="US-ASCII"?><price>&#x20AC;10</price>"#
+);
+```
+
+Where output must be ASCII whatever encoding it is later stored in, the `escape_non_ascii` option
+writes every other character as a character reference. Conversely the `minimal_escaping` option
+escapes only the characters the XML specification requires, rather than also escaping every
+`'`, `"`, and `>`.
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::writer::{node_to_string, WriterOptions};
+
+let document_node = get_implementation()
+    .create_document(None, Some("q"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let _safe_to_ignore = root_node
+    .append_child(document.create_text_node("\"café\" > tea"))
+    .unwrap();
+
+let mut options = WriterOptions::new();
+options.set_minimal_escaping();
+assert_eq!(node_to_string(&root_node, &options), "<q>\"café\" > tea</q>");
+
+options.set_escape_non_ascii();
+assert_eq!(node_to_string(&root_node, &options), "<q>\"caf&#xE9;\" > tea</q>");
+```
+
+Large generated documents need not be held in memory before they are written, an
+[`IncrementalWriter`](struct.IncrementalWriter.html) writes the document head and the start tag
+of the root element, then each child subtree as soon as it is produced, and finally closes the
+root.
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{XmlDecl, XmlVersion};
+use xml_dom::level2::ext::writer::{IncrementalWriter, WriterOptions};
+
+let document_node = get_implementation()
+    .create_document(None, Some("log"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let root_node = document.document_element().unwrap();
+
+let mut writer = IncrementalWriter::new(Vec::new(), WriterOptions::new());
+writer.write_declaration(&XmlDecl::new(XmlVersion::V10, None, None)).unwrap();
+writer.start_element(&root_node).unwrap();
+for n in 1..=2 {
+    // each entry may be dropped as soon as it has been written.
+    let mut entry_node = document.create_element("entry").unwrap();
+    entry_node.set_attribute("n", &n.to_string()).unwrap();
+    writer.write_node(&entry_node).unwrap();
+}
+let bytes = writer.finish().unwrap();
+
+assert_eq!(
+    String::from_utf8(bytes).unwrap(),
+    r#"<?xml version="1.0"?><log><entry n="1"></entry><entry n="2"></entry></log>"#
+);
+```
+
+*/
+
+use crate::level2::convert::{as_element, is_document, is_document_fragment, is_element};
+use crate::level2::ext::convert::as_document_decl;
+use crate::level2::ext::decl::{XmlDecl, XmlVersion};
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::NodeType;
+use crate::shared::display::{self, NamespaceScope};
+use crate::shared::syntax::{XML_DECL_ENCODING, XML_DECL_END, XML_DECL_START, XML_DECL_VERSION};
+use std::fmt::{Display, Formatter, Result, Write};
+use std::io::{Error as IoError, ErrorKind};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This type encapsulates a set of options that a client can set that affect the serialization of
+/// nodes. The default for `WriterOptions` is that none of the options are set, which results in
+/// the same output as the `Display` implementation for `RefNode`.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WriterOptions {
+    omit_unspecified_attributes: bool,
+    annotate_elements: bool,
+    normalize_namespaces: bool,
+    xhtml_compatible: bool,
+    escape_non_ascii: bool,
+    minimal_escaping: bool,
+    internal_subset: InternalSubset,
+    namespace_placement: NamespacePlacement,
+    indent: Option<String>,
+    encoding: Option<OutputEncoding>,
+}
+
+///
+/// Determines how the internal subset of a `<!DOCTYPE>` declaration is written, see
+/// [`set_internal_subset`](struct.WriterOptions.html#method.set_internal_subset).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InternalSubset {
+    /// Write the internal subset exactly as it was captured by the parser, or set, if there is
+    /// one; otherwise write the declarations regenerated from the entity and notation nodes, as
+    /// `Regenerated` does. This is the behavior of the `Display` implementation.
+    #[default]
+    Preferred,
+    /// Write only the internal subset exactly as it was captured by the parser; if the document
+    /// type was not parsed no internal subset is written.
+    Verbatim,
+    /// Write only the declarations regenerated from the entity and notation nodes, each in order
+    /// of name, ignoring any internal subset captured by the parser.
+    Regenerated,
+}
+
+///
+/// Determines where the namespace declarations added by the `normalize_namespaces` option are
+/// written, see
+/// [`set_namespace_placement`](struct.WriterOptions.html#method.set_namespace_placement).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NamespacePlacement {
+    /// Declare each namespace on the first element that uses it, and again wherever it has been
+    /// bound to a different namespace in between.
+    #[default]
+    FirstUse,
+    /// Declare every namespace used in the tree on the outermost element written, except for
+    /// prefixes bound to more than one namespace, which are declared where they are used.
+    DocumentElement,
+}
+
+///
+/// A prefix, or `None` for the default namespace, bound to more than one namespace within a tree,
+/// as returned by [`namespace_conflicts`](fn.namespace_conflicts.html). A namespace of `None` is
+/// the absence of a namespace, as for an unprefixed element without one.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NamespaceConflict {
+    /// The prefix, `None` for the default namespace.
+    pub prefix: Option<String>,
+    /// The namespaces the prefix is bound to, in the order they are first used.
+    pub namespace_uris: Vec<Option<String>>,
+}
+
+///
+/// The character encodings supported by
+/// [`write_document_with_encoding`](fn.write_document_with_encoding.html) and
+/// [`serialize_to_bytes`](fn.serialize_to_bytes.html).
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputEncoding {
+    /// UTF-8, every character can be represented.
+    Utf8,
+    /// UTF-16, written big-endian and preceded by a byte order mark; every character can be
+    /// represented.
+    Utf16,
+    /// ISO-8859-1 (Latin-1), only the characters `U+0000` to `U+00FF` can be represented.
+    Iso8859_1,
+    /// US-ASCII, only the characters `U+0000` to `U+007F` can be represented.
+    UsAscii,
+}
+
+///
+/// Writes a document in pieces, flushing each to the underlying writer as it is written, so that
+/// a generated document need not be held in memory. The document head, an XML declaration and
+/// any document type or other nodes before the root element, is written first, then the start
+/// tag of the root element, with its attributes and namespace declarations, then each child
+/// subtree, and finally the end tag. Elements may be nested by starting further elements before
+/// the root is ended.
+///
+/// The writer does not check that the output is well-formed, for example that only one root
+/// element is written.
+///
+#[derive(Debug)]
+pub struct IncrementalWriter<W: std::io::Write> {
+    writer: W,
+    options: WriterOptions,
+    open_elements: Vec<String>,
+    scope: NamespaceScope,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Replaces characters that cannot be written literally, because of the target encoding or the
+/// `escape_non_ascii` option, with numeric character references as they are written.
+///
+struct CharacterReferenceWriter<'a> {
+    inner: &'a mut dyn Write,
+    options: &'a WriterOptions,
+}
+
+const UTF_8: &str = "UTF-8";
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Write the serialized form of `node`, and all its descendants, to `writer` according to the
+/// provided `options`.
+///
+pub fn write_node(node: &RefNode, options: &WriterOptions, writer: &mut impl Write) -> Result {
+    with_references(options, writer, |writer| {
+        display::fmt_node(node, options, writer)
+    })
+}
+
+///
+/// Return the serialized form of `node`, and all its descendants, according to the provided
+/// `options`.
+///
+pub fn node_to_string(node: &RefNode, options: &WriterOptions) -> String {
+    let mut buffer = String::new();
+    let _safe_to_ignore = write_node(node, options, &mut buffer);
+    buffer
+}
+
+///
+/// Write `node` as an external parsed entity, such as a chapter included by a master document:
+/// a text declaration followed by the content, without any document type. The content of a
+/// `Document` or `DocumentFragment` node is its children, otherwise it is the node itself. The
+/// text declaration has the version of the owner document's XML declaration, if it has one, and
+/// declares the encoding as UTF-8. The result may be read back into a `DocumentFragment` with
+/// [`parser::read_external_entity`](../../../parser/fn.read_external_entity.html).
+///
+pub fn write_external_entity(
+    node: &RefNode,
+    options: &WriterOptions,
+    writer: &mut impl Write,
+) -> Result {
+    let document_node = if is_document(node) {
+        Some(node.clone())
+    } else {
+        node.owner_document()
+    };
+    let version = document_node
+        .and_then(|document_node| as_document_decl(&document_node).ok()?.xml_declaration())
+        .map(|xml_declaration| xml_declaration.version())
+        .unwrap_or(XmlVersion::V10);
+    write!(
+        writer,
+        "{} {}=\"{}\" {}=\"{}\"{}",
+        XML_DECL_START, XML_DECL_VERSION, version, XML_DECL_ENCODING, UTF_8, XML_DECL_END
+    )?;
+    if is_document(node) || is_document_fragment(node) {
+        for child_node in node.child_nodes() {
+            if child_node.node_type() != NodeType::DocumentType {
+                write_node(&child_node, options, writer)?;
+            }
+        }
+        Ok(())
+    } else {
+        write_node(node, options, writer)
+    }
+}
+
+///
+/// Return `node` written as an external parsed entity, see
+/// [`write_external_entity`](fn.write_external_entity.html).
+///
+pub fn external_entity_to_string(node: &RefNode, options: &WriterOptions) -> String {
+    let mut buffer = String::new();
+    let _safe_to_ignore = write_external_entity(node, options, &mut buffer);
+    buffer
+}
+
+///
+/// Write the serialized form of `node`, and all its descendants, to `writer` as bytes in the
+/// encoding named by the XML declaration of the node's owner document, or of `node` itself if it
+/// is a `Document`; without a declared encoding UTF-8 is used. Characters the encoding cannot
+/// represent are written as numeric character references, and CDATA sections are split around
+/// them, as described for [`CDataSection`](../../trait.CDataSection.html). It is an error,
+/// of kind `InvalidData`, if such a character appears in a name, comment, or processing
+/// instruction where references are not recognized, and an error, of kind `InvalidInput`, if
+/// the declared encoding is not one of the [`OutputEncoding`](enum.OutputEncoding.html) values.
+///
+pub fn write_document_with_encoding(
+    node: &RefNode,
+    options: &WriterOptions,
+    writer: &mut impl std::io::Write,
+) -> std::io::Result<()> {
+    let document_node = if is_document(node) {
+        Some(node.clone())
+    } else {
+        node.owner_document()
+    };
+    let encoding = match document_node
+        .and_then(|document_node| as_document_decl(&document_node).ok()?.xml_declaration())
+        .and_then(|xml_declaration| xml_declaration.encoding())
+    {
+        None => OutputEncoding::Utf8,
+        Some(name) => OutputEncoding::from_name(&name).ok_or_else(|| {
+            IoError::new(
+                ErrorKind::InvalidInput,
+                format!("unsupported output encoding '{}'", name),
+            )
+        })?,
+    };
+    writer.write_all(&encode_node(node, encoding, options)?)
+}
+
+///
+/// Return the serialized form of `node`, and all its descendants, as bytes in `encoding`.
+/// Characters the encoding cannot represent are handled as for
+/// [`write_document_with_encoding`](fn.write_document_with_encoding.html), with the same errors.
+/// If `node` is a `Document` its XML declaration is written with the name of `encoding`, in
+/// place of any other encoding it declares; a document without a declaration is given one
+/// unless `encoding` is UTF-8.
+///
+pub fn serialize_to_bytes(
+    node: &RefNode,
+    encoding: OutputEncoding,
+    options: &WriterOptions,
+) -> std::io::Result<Vec<u8>> {
+    encode_node(node, encoding, options)
+}
+
+///
+/// Returns the prefixes that are bound to more than one namespace within the element `node`, or
+/// the document element if `node` is a document; these cannot be declared only once, on the
+/// document element, with the `DocumentElement`
+/// [`NamespacePlacement`](enum.NamespacePlacement.html). The result is empty for any other type
+/// of node.
+///
+pub fn namespace_conflicts(node: &RefNode) -> Vec<NamespaceConflict> {
+    let element_node = if is_document(node) {
+        as_document_decl(node)
+            .ok()
+            .and_then(|document| document.document_element())
+    } else {
+        Some(node.clone())
+    };
+    match element_node.as_ref().map(as_element) {
+        Some(Ok(element)) => display::namespace_bindings(element)
+            .into_iter()
+            .filter(|(_, namespace_uris)| namespace_uris.len() > 1)
+            .map(|(prefix, namespace_uris)| NamespaceConflict {
+                prefix,
+                namespace_uris,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+///
+/// Returns an identifier for `node` that is unique among all live nodes, and stable for the
+/// lifetime of the node; this is the `id` written by the `annotate_elements` option.
+///
+pub fn node_id(node: &RefNode) -> usize {
+    node.as_ptr() as usize
+}
+
+///
+/// Returns the path to the element `node` from the top-most element above it, each segment is
+/// written as `name[index]` where the one-based index counts only preceding siblings with the same
+/// name; the index is omitted for the top-most element. For example, `/a/b[2]` is the second `b`
+/// element within `a`. This is the `path` written by the `annotate_elements` option.
+///
+pub fn node_path(node: &RefNode) -> String {
+    let mut segments: Vec<String> = Vec::new();
+    let mut current = Some(node.clone());
+    while let Some(node) = current.filter(is_element) {
+        let name = node.node_name();
+        current = node.parent_node();
+        if current.as_ref().map(is_element).unwrap_or_default() {
+            let mut index = 1;
+            let mut sibling = node.previous_sibling();
+            while let Some(sibling_node) = sibling {
+                if is_element(&sibling_node) && sibling_node.node_name() == name {
+                    index += 1;
+                }
+                sibling = sibling_node.previous_sibling();
+            }
+            segments.push(format!("{}[{}]", name, index));
+        } else {
+            segments.push(name.to_string());
+        }
+    }
+    segments.reverse();
+    format!("/{}", segments.join("/"))
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl WriterOptions {
+    ///
+    /// Construct a new `WriterOptions` instance with all options off.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Returns `true` if attributes that were not specified in the document, i.e. those that
+    /// were defaulted, will be omitted from the output, else `false`.
+    ///
+    pub fn has_omit_unspecified_attributes(&self) -> bool {
+        self.omit_unspecified_attributes
+    }
+    ///
+    /// Omit attributes whose [`specified`](../../trait.Attribute.html#method.specified) flag is
+    /// `false` from the output.
+    ///
+    pub fn set_omit_unspecified_attributes(&mut self) {
+        self.omit_unspecified_attributes = true
+    }
+    ///
+    /// Returns `true` if each element will be preceded by a comment identifying it, else `false`.
+    ///
+    pub fn has_annotate_elements(&self) -> bool {
+        self.annotate_elements
+    }
+    ///
+    /// Precede each element with a comment of the form `<!-- id=42 path=/a/b[2] -->`, giving the
+    /// [`node_id`](fn.node_id.html) and [`node_path`](fn.node_path.html) of the element. This is
+    /// intended for debugging, the output is not equivalent to the original document.
+    ///
+    pub fn set_annotate_elements(&mut self) {
+        self.annotate_elements = true
+    }
+    ///
+    /// Returns `true` if missing namespace declarations will be added to the output, else
+    /// `false`.
+    ///
+    pub fn has_normalize_namespaces(&self) -> bool {
+        self.normalize_namespaces
+    }
+    ///
+    /// Make the output namespace well-formed, in the manner of DOM Level 3 namespace
+    /// normalization, without changing the nodes themselves. Elements and attributes created with
+    /// a namespace, for example by `create_element_ns`, need not have a corresponding `xmlns`
+    /// attribute; a declaration is added to the start tag of the first element that needs it.
+    /// Where the prefix of a name is already declared, on the same element, for a different
+    /// namespace, another prefix bound to the namespace is used or, if there is none, a new
+    /// prefix of the form `ns1`, `ns2`, ... is declared. An element without a namespace within
+    /// a default namespace declares `xmlns=""`.
+    ///
+    pub fn set_normalize_namespaces(&mut self) {
+        self.normalize_namespaces = true
+    }
+    ///
+    /// Returns where the declarations added by the `normalize_namespaces` option are written.
+    ///
+    pub fn namespace_placement(&self) -> NamespacePlacement {
+        self.namespace_placement
+    }
+    ///
+    /// Set where the declarations added by the `normalize_namespaces` option are written; this
+    /// has no effect unless that option is also set. With `DocumentElement` the declarations on
+    /// the outermost element written cover every namespace used below it, and `xmlns` attributes
+    /// of descendants that repeat a declaration in scope are not written; prefixes that are
+    /// bound to more than one namespace, see [`namespace_conflicts`](fn.namespace_conflicts.html),
+    /// are declared at first use, and a warning is logged.
+    ///
+    pub fn set_namespace_placement(&mut self, namespace_placement: NamespacePlacement) {
+        self.namespace_placement = namespace_placement
+    }
+    ///
+    /// Returns `true` if the output will follow the XHTML compatibility guidelines, else `false`.
+    ///
+    pub fn has_xhtml_compatible(&self) -> bool {
+        self.xhtml_compatible
+    }
+    ///
+    /// Write XHTML that legacy HTML renderers accept, following the compatibility guidelines of
+    /// appendix C of the XHTML 1.0 specification. Elements that are always empty in HTML, such
+    /// as `br` and `img`, are written as `<br />` when they have no children; all other elements
+    /// have an explicit end tag, as is always the case. An `html` element that has no parent
+    /// element declares the XHTML namespace if it does not already. The text content of `script`
+    /// and `style` elements is written unescaped, and where it contains `<` or `&` it is wrapped
+    /// in a CDATA section that is hidden from HTML renderers within script or style comments.
+    ///
+    pub fn set_xhtml_compatible(&mut self) {
+        self.xhtml_compatible = true
+    }
+    ///
+    /// Returns `true` if all non-ASCII characters will be written as character references, else
+    /// `false`.
+    ///
+    pub fn has_escape_non_ascii(&self) -> bool {
+        self.escape_non_ascii
+    }
+    ///
+    /// Write every character outside the ASCII range as a hexadecimal character reference, so
+    /// that the output may be carried over a channel that only accepts ASCII, whatever the
+    /// encoding it is later written in. As with an output encoding, CDATA sections are split
+    /// around such characters, and it is an error for one to appear in a name, comment, or
+    /// processing instruction.
+    ///
+    pub fn set_escape_non_ascii(&mut self) {
+        self.escape_non_ascii = true
+    }
+    ///
+    /// Returns `true` if only the characters that must be escaped will be, else `false`.
+    ///
+    pub fn has_minimal_escaping(&self) -> bool {
+        self.minimal_escaping
+    }
+    ///
+    /// Escape only the characters that the XML specification requires; in text these are `&`,
+    /// `<`, and the `>` of `]]>`, and in attribute values `&`, `<`, and `"`. By default the
+    /// characters `'`, `"`, and `>` are also escaped wherever they appear.
+    ///
+    pub fn set_minimal_escaping(&mut self) {
+        self.minimal_escaping = true
+    }
+    ///
+    /// Returns how the internal subset of a document type will be written.
+    ///
+    pub fn internal_subset(&self) -> InternalSubset {
+        self.internal_subset
+    }
+    ///
+    /// Set how the internal subset of a document type is written. Use `Verbatim` where the
+    /// output must reproduce the declarations of the parsed document byte for byte, for example
+    /// when it is covered by a signature, and `Regenerated` where the entity and notation nodes
+    /// have been changed, or include those added from an external DTD.
+    ///
+    pub fn set_internal_subset(&mut self, internal_subset: InternalSubset) {
+        self.internal_subset = internal_subset
+    }
+    ///
+    /// Returns the string written for each level of indentation, if the output is indented.
+    ///
+    pub fn indent(&self) -> Option<&str> {
+        self.indent.as_deref()
+    }
+    ///
+    /// Indent the output for people to read; each child of an element with element content, and
+    /// of the document, is written on a new line, preceded by `indent` once for each element it
+    /// is within, and the white space text already between them is not written. Elements with
+    /// text content are written unchanged, as is everything within the scope of an
+    /// `xml:space="preserve"` attribute, inherited from its ancestors.
+    ///
+    pub fn set_indent(&mut self, indent: &str) {
+        self.indent = Some(indent.to_string())
+    }
+    ///
+    /// Returns the encoding that output is being written in, this is only set by
+    /// `write_document_with_encoding` and `serialize_to_bytes`.
+    ///
+    pub(crate) fn encoding(&self) -> Option<OutputEncoding> {
+        self.encoding
+    }
+    ///
+    /// Returns `true` if `c` may be written literally, rather than as a character reference,
+    /// given the output encoding and the `escape_non_ascii` option.
+    ///
+    pub(crate) fn can_write(&self, c: char) -> bool {
+        self.encoding
+            .map(|encoding| encoding.can_encode(c))
+            .unwrap_or(true)
+            && (c.is_ascii() || !self.escape_non_ascii)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<W: std::io::Write> IncrementalWriter<W> {
+    ///
+    /// Construct a new writer that writes to `writer` using the provided `options`.
+    ///
+    pub fn new(writer: W, options: WriterOptions) -> Self {
+        Self {
+            writer,
+            options,
+            open_elements: Vec::new(),
+            scope: NamespaceScope::default(),
+        }
+    }
+    ///
+    /// Write an XML declaration, this must be the first thing written.
+    ///
+    pub fn write_declaration(&mut self, declaration: &XmlDecl) -> std::io::Result<()> {
+        self.write_str(&declaration.to_string())
+    }
+    ///
+    /// Write the start tag of the element `node`, including its attributes, but none of its
+    /// children; the element remains open until [`end_element`](#method.end_element) is called.
+    /// It is an error, of kind `InvalidInput`, if `node` is not an element.
+    ///
+    pub fn start_element(&mut self, node: &RefNode) -> std::io::Result<()> {
+        let element = as_element(node).map_err(|_| not_an_element())?;
+        let mut buffer = String::new();
+        let name = with_references(&self.options, &mut buffer, |buffer| {
+            display::fmt_element_start(element, &self.options, &mut self.scope, buffer)
+        })
+        .map_err(format_error)?;
+        self.write_str(&buffer)?;
+        self.open_elements.push(name);
+        Ok(())
+    }
+    ///
+    /// Write the serialized form of `node`, and all its descendants, and flush the output; once
+    /// written the node may be dropped, or reused, by the caller.
+    ///
+    pub fn write_node(&mut self, node: &RefNode) -> std::io::Result<()> {
+        let mut buffer = String::new();
+        with_references(&self.options, &mut buffer, |buffer| {
+            display::fmt_node_in(node, &self.options, &mut self.scope, buffer)
+        })
+        .map_err(format_error)?;
+        self.write_str(&buffer)
+    }
+    ///
+    /// Write the end tag of the most recently started element. It is an error, of kind
+    /// `InvalidInput`, if there is no open element.
+    ///
+    pub fn end_element(&mut self) -> std::io::Result<()> {
+        let name = self.open_elements.pop().ok_or_else(not_an_element)?;
+        let mut buffer = String::new();
+        display::fmt_element_end(&name, &mut self.scope, &mut buffer).map_err(format_error)?;
+        self.write_str(&buffer)
+    }
+    ///
+    /// Returns the number of elements that have been started but not yet ended.
+    ///
+    pub fn depth(&self) -> usize {
+        self.open_elements.len()
+    }
+    ///
+    /// Returns a reference to the underlying writer.
+    ///
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+    ///
+    /// End any open elements, flush the output, and return the underlying writer.
+    ///
+    pub fn finish(mut self) -> std::io::Result<W> {
+        while !self.open_elements.is_empty() {
+            self.end_element()?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_str(&mut self, s: &str) -> std::io::Result<()> {
+        self.writer.write_all(s.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for OutputEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Utf8 => UTF_8,
+                Self::Utf16 => "UTF-16",
+                Self::Iso8859_1 => "ISO-8859-1",
+                Self::UsAscii => "US-ASCII",
+            }
+        )
+    }
+}
+
+impl OutputEncoding {
+    ///
+    /// Returns the encoding for the name used in an XML declaration, ignoring case and
+    /// accepting the common aliases, or `None` if the encoding is not supported.
+    ///
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "UTF-8" | "UTF8" => Some(Self::Utf8),
+            "UTF-16" | "UTF16" => Some(Self::Utf16),
+            "ISO-8859-1" | "ISO_8859-1" | "ISO8859-1" | "LATIN1" | "L1" => Some(Self::Iso8859_1),
+            "US-ASCII" | "ASCII" => Some(Self::UsAscii),
+            _ => None,
+        }
+    }
+    ///
+    /// Returns `true` if the character `c` can be represented in this encoding, else `false`.
+    ///
+    pub fn can_encode(&self, c: char) -> bool {
+        match self {
+            Self::Utf8 | Self::Utf16 => true,
+            Self::Iso8859_1 => (c as u32) <= 0xFF,
+            Self::UsAscii => c.is_ascii(),
+        }
+    }
+
+    fn encode(&self, s: &str) -> Vec<u8> {
+        match self {
+            Self::Utf8 => s.as_bytes().to_vec(),
+            Self::Utf16 => [0xFE, 0xFF]
+                .into_iter()
+                .chain(s.encode_utf16().flat_map(u16::to_be_bytes))
+                .collect(),
+            Self::Iso8859_1 | Self::UsAscii => s.chars().map(|c| c as u8).collect(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Write for CharacterReferenceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> Result {
+        for c in s.chars() {
+            if self.options.can_write(c) {
+                self.inner.write_char(c)?;
+            } else {
+                write!(self.inner, "&#x{:X};", c as u32)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn encode_node(
+    node: &RefNode,
+    encoding: OutputEncoding,
+    options: &WriterOptions,
+) -> std::io::Result<Vec<u8>> {
+    let mut options = options.clone();
+    options.encoding = Some(encoding);
+
+    let mut buffer = String::new();
+    let mut reference_writer = CharacterReferenceWriter {
+        inner: &mut buffer,
+        options: &options,
+    };
+    display::fmt_node(node, &options, &mut reference_writer).map_err(|_| {
+        IoError::new(
+            ErrorKind::InvalidData,
+            format!(
+                "a name, comment, or processing instruction contains a character that cannot be represented in {}",
+                encoding
+            ),
+        )
+    })?;
+    Ok(encoding.encode(&buffer))
+}
+
+///
+/// Call `write` with `writer`, wrapped so that characters are replaced by references if the
+/// `escape_non_ascii` option is set.
+///
+fn with_references<T>(
+    options: &WriterOptions,
+    writer: &mut dyn Write,
+    write: impl FnOnce(&mut dyn Write) -> std::result::Result<T, std::fmt::Error>,
+) -> std::result::Result<T, std::fmt::Error> {
+    if options.has_escape_non_ascii() {
+        write(&mut CharacterReferenceWriter {
+            inner: writer,
+            options,
+        })
+    } else {
+        write(writer)
+    }
+}
+
+fn not_an_element() -> IoError {
+    IoError::new(ErrorKind::InvalidInput, "no element to start or end")
+}
+
+fn format_error(_: std::fmt::Error) -> IoError {
+    IoError::other("unable to serialize node")
+}
@@ -0,0 +1,262 @@
+/*!
+Provides values written, and read, using the lexical forms of the XML Schema built-in datatypes,
+so that generated documents are schema-valid regardless of the locale of the process.
+
+The [`XmlValue`](../mapping/trait.XmlValue.html) implementations for the Rust numeric types use
+the forms of `xs:integer` and `xs:double`; floating point values are written without an exponent,
+and infinity and not-a-number as `INF`, `-INF`, and `NaN`. The [`Decimal`](struct.Decimal.html)
+type writes a floating point value as `xs:decimal`, which has no exponent or special values. With
+the `chrono` feature enabled the date and time types of [chrono](https://crates.io/crates/chrono)
+implement `XmlValue` as follows.
+
+| Rust type               | XML Schema type                   | Example                     |
+|-------------------------|-----------------------------------|-----------------------------|
+| `DateTime<Utc>`         | `xs:dateTime`, with a timezone    | `2024-01-02T03:04:05Z`      |
+| `DateTime<FixedOffset>` | `xs:dateTime`, with a timezone    | `2024-01-02T03:04:05+01:00` |
+| `NaiveDateTime`         | `xs:dateTime`, without a timezone | `2024-01-02T03:04:05.5`     |
+| `NaiveDate`             | `xs:date`                         | `2024-01-02`                |
+| `NaiveTime`             | `xs:time`                         | `03:04:05`                  |
+
+The [`TypedContent`](../trait.TypedContent.html) trait uses these to set, and get, the attributes
+and text content of an element.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::TypedContent;
+use xml_dom::level2::ext::xsd::Decimal;
+
+let document_node = get_implementation()
+    .create_document(None, Some("price"), None)
+    .unwrap();
+let document = as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+root_node.set_attribute_value("rate", &f64::INFINITY).unwrap();
+root_node.set_text_value(&Decimal::new(1234.5).unwrap()).unwrap();
+assert_eq!(root_node.to_string(), r#"<price rate="INF">1234.5</price>"#);
+
+assert_eq!(root_node.attribute_value::<f64>("rate"), Ok(Some(f64::INFINITY)));
+assert_eq!(root_node.text_value::<Decimal>().unwrap().unwrap().value(), 1234.5);
+```
+
+*/
+
+use crate::level2::ext::mapping::{self, XmlValue};
+use crate::level2::ext::traits::TypedContent;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::Node;
+use crate::shared::error::{Error, Result};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Utc};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A finite floating point value written as an `xs:decimal`, which has no exponent.
+///
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Decimal(f64);
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Decimal {
+    ///
+    /// Construct a new decimal value, returning `Error::Syntax` if `value` is infinite or
+    /// not-a-number, which `xs:decimal` can not represent.
+    ///
+    pub fn new(value: f64) -> Result<Self> {
+        if value.is_finite() {
+            Ok(Self(value))
+        } else {
+            Err(invalid(&value.to_string(), "xs:decimal"))
+        }
+    }
+    ///
+    /// Returns the floating point value.
+    ///
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl XmlValue for Decimal {
+    fn to_xml_value(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_xml_value(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if (whole.is_empty() && fraction.is_empty())
+            || !whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid(value, "xs:decimal"));
+        }
+        value
+            .parse()
+            .map(Self)
+            .map_err(|_| invalid(value, "xs:decimal"))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl XmlValue for bool {
+    fn to_xml_value(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_xml_value(value: &str) -> Result<Self> {
+        match value.trim() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(invalid(value, "xs:boolean")),
+        }
+    }
+}
+
+macro_rules! impl_xml_double {
+    ($($value_type:ty),*) => {
+        $(
+        impl XmlValue for $value_type {
+            fn to_xml_value(&self) -> String {
+                if self.is_nan() {
+                    "NaN".to_string()
+                } else if self.is_infinite() {
+                    if *self > 0.0 { "INF" } else { "-INF" }.to_string()
+                } else {
+                    self.to_string()
+                }
+            }
+
+            fn from_xml_value(value: &str) -> Result<Self> {
+                //
+                // Rust's own forms, such as `inf` and `NaN` in any case, are not accepted.
+                //
+                match value.trim() {
+                    "INF" | "+INF" => Ok(<$value_type>::INFINITY),
+                    "-INF" => Ok(<$value_type>::NEG_INFINITY),
+                    "NaN" => Ok(<$value_type>::NAN),
+                    trimmed if trimmed.chars().all(|c| {
+                        c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-')
+                    }) => trimmed.parse().map_err(|_| invalid(value, "xs:double")),
+                    _ => Err(invalid(value, "xs:double")),
+                }
+            }
+        }
+        )*
+    };
+}
+
+impl_xml_double!(f32, f64);
+
+// ------------------------------------------------------------------------------------------------
+
+impl TypedContent for RefNode {
+    fn set_attribute_value<T: XmlValue>(&mut self, name: &str, value: &T) -> Result<()> {
+        mapping::set_attribute(self, name, value)
+    }
+
+    fn attribute_value<T: XmlValue>(&self, name: &str) -> Result<Option<T>> {
+        mapping::attribute(self, name)
+    }
+
+    fn set_text_value<T: XmlValue>(&mut self, value: &T) -> Result<()> {
+        let document_node = self.owner_document().ok_or(Error::InvalidState)?;
+        for child_node in self.child_nodes() {
+            let _safe_to_ignore = self.remove_child(child_node)?;
+        }
+        mapping::append_text(&document_node, self, value)
+    }
+
+    fn text_value<T: XmlValue>(&self) -> Result<Option<T>> {
+        mapping::optional_text(self)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(feature = "chrono")]
+impl XmlValue for DateTime<Utc> {
+    fn to_xml_value(&self) -> String {
+        self.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+
+    fn from_xml_value(value: &str) -> Result<Self> {
+        DateTime::<FixedOffset>::from_xml_value(value)
+            .map(|date_time| date_time.with_timezone(&Utc))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl XmlValue for DateTime<FixedOffset> {
+    fn to_xml_value(&self) -> String {
+        self.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+
+    fn from_xml_value(value: &str) -> Result<Self> {
+        DateTime::parse_from_rfc3339(value.trim()).map_err(|_| invalid(value, "xs:dateTime"))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl XmlValue for NaiveDateTime {
+    fn to_xml_value(&self) -> String {
+        self.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+    }
+
+    fn from_xml_value(value: &str) -> Result<Self> {
+        NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%dT%H:%M:%S%.f")
+            .map_err(|_| invalid(value, "xs:dateTime"))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl XmlValue for NaiveDate {
+    fn to_xml_value(&self) -> String {
+        self.format("%Y-%m-%d").to_string()
+    }
+
+    fn from_xml_value(value: &str) -> Result<Self> {
+        NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| invalid(value, "xs:date"))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl XmlValue for NaiveTime {
+    fn to_xml_value(&self) -> String {
+        self.format("%H:%M:%S%.f").to_string()
+    }
+
+    fn from_xml_value(value: &str) -> Result<Self> {
+        NaiveTime::parse_from_str(value.trim(), "%H:%M:%S%.f")
+            .map_err(|_| invalid(value, "xs:time"))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn invalid(value: &str, type_name: &str) -> Error {
+    warn!("The value {:?} is not a valid `{}`.", value, type_name);
+    Error::Syntax
+}
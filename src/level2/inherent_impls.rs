@@ -0,0 +1,235 @@
+/*!
+Inherent methods on [`RefNode`](../type.RefNode.html) that mirror the most frequently called
+methods of the DOM traits.
+
+Each method calls the trait implementation for `RefNode` directly, and is marked `#[inline]`, so
+that calls are resolved statically and may be inlined; none go through the `&dyn` references
+returned by the functions in the [`convert`](../convert/index.html) module. They also do not
+require the traits to be in scope. The traits remain the definition of each method's behavior.
+*/
+
+use crate::level2::convert::is_document;
+use crate::level2::named_node_map::NamedAttributeMap;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Attribute, Document, Element, Node, NodeType};
+use crate::shared::error::Result;
+use crate::shared::name::Name;
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl RefNode {
+    ///
+    /// See [`Node::node_name`](trait.Node.html#tymethod.node_name).
+    ///
+    #[inline]
+    pub fn node_name(&self) -> Name {
+        Node::node_name(self)
+    }
+    ///
+    /// See [`Node::node_value`](trait.Node.html#tymethod.node_value).
+    ///
+    #[inline]
+    pub fn node_value(&self) -> Option<String> {
+        Node::node_value(self)
+    }
+    ///
+    /// See [`Node::node_type`](trait.Node.html#tymethod.node_type).
+    ///
+    #[inline]
+    pub fn node_type(&self) -> NodeType {
+        Node::node_type(self)
+    }
+    ///
+    /// See [`Node::parent_node`](trait.Node.html#tymethod.parent_node).
+    ///
+    #[inline]
+    pub fn parent_node(&self) -> Option<RefNode> {
+        Node::parent_node(self)
+    }
+    ///
+    /// See [`Node::child_nodes`](trait.Node.html#tymethod.child_nodes).
+    ///
+    #[inline]
+    pub fn child_nodes(&self) -> Vec<RefNode> {
+        Node::child_nodes(self)
+    }
+    ///
+    /// See [`Node::first_child`](trait.Node.html#tymethod.first_child).
+    ///
+    #[inline]
+    pub fn first_child(&self) -> Option<RefNode> {
+        Node::first_child(self)
+    }
+    ///
+    /// See [`Node::last_child`](trait.Node.html#tymethod.last_child).
+    ///
+    #[inline]
+    pub fn last_child(&self) -> Option<RefNode> {
+        Node::last_child(self)
+    }
+    ///
+    /// See [`Node::previous_sibling`](trait.Node.html#tymethod.previous_sibling).
+    ///
+    #[inline]
+    pub fn previous_sibling(&self) -> Option<RefNode> {
+        Node::previous_sibling(self)
+    }
+    ///
+    /// See [`Node::next_sibling`](trait.Node.html#tymethod.next_sibling).
+    ///
+    #[inline]
+    pub fn next_sibling(&self) -> Option<RefNode> {
+        Node::next_sibling(self)
+    }
+    ///
+    /// See [`Node::attributes`](trait.Node.html#tymethod.attributes).
+    ///
+    #[inline]
+    pub fn attributes(&self) -> NamedAttributeMap {
+        Node::attributes(self)
+    }
+    ///
+    /// See [`Node::owner_document`](trait.Node.html#tymethod.owner_document).
+    ///
+    #[inline]
+    pub fn owner_document(&self) -> Option<RefNode> {
+        Node::owner_document(self)
+    }
+    ///
+    /// See [`Node::has_child_nodes`](trait.Node.html#tymethod.has_child_nodes).
+    ///
+    #[inline]
+    pub fn has_child_nodes(&self) -> bool {
+        Node::has_child_nodes(self)
+    }
+    ///
+    /// See [`Node::has_attributes`](trait.Node.html#tymethod.has_attributes).
+    ///
+    #[inline]
+    pub fn has_attributes(&self) -> bool {
+        Node::has_attributes(self)
+    }
+    ///
+    /// See [`Node::local_name`](trait.Node.html#method.local_name).
+    ///
+    #[inline]
+    pub fn local_name(&self) -> String {
+        Node::local_name(self)
+    }
+    ///
+    /// See [`Node::namespace_uri`](trait.Node.html#method.namespace_uri).
+    ///
+    #[inline]
+    pub fn namespace_uri(&self) -> Option<String> {
+        Node::namespace_uri(self)
+    }
+    ///
+    /// See [`Node::prefix`](trait.Node.html#method.prefix).
+    ///
+    #[inline]
+    pub fn prefix(&self) -> Option<String> {
+        Node::prefix(self)
+    }
+
+    // --------------------------------------------------------------------------------------------
+
+    ///
+    /// See [`Element::tag_name`](trait.Element.html#method.tag_name).
+    ///
+    #[inline]
+    pub fn tag_name(&self) -> String {
+        Element::tag_name(self)
+    }
+    ///
+    /// See [`Element::get_attribute`](trait.Element.html#tymethod.get_attribute).
+    ///
+    #[inline]
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        Element::get_attribute(self, name)
+    }
+    ///
+    /// See [`Element::get_attribute_ns`](trait.Element.html#tymethod.get_attribute_ns).
+    ///
+    #[inline]
+    pub fn get_attribute_ns(&self, namespace_uri: &str, local_name: &str) -> Option<String> {
+        Element::get_attribute_ns(self, namespace_uri, local_name)
+    }
+    ///
+    /// See [`Element::get_attribute_node`](trait.Element.html#tymethod.get_attribute_node).
+    ///
+    #[inline]
+    pub fn get_attribute_node(&self, name: &str) -> Option<RefNode> {
+        Element::get_attribute_node(self, name)
+    }
+    ///
+    /// See [`Element::has_attribute`](trait.Element.html#tymethod.has_attribute).
+    ///
+    #[inline]
+    pub fn has_attribute(&self, name: &str) -> bool {
+        Element::has_attribute(self, name)
+    }
+    ///
+    /// See [`Element::has_attribute_ns`](trait.Element.html#tymethod.has_attribute_ns).
+    ///
+    #[inline]
+    pub fn has_attribute_ns(&self, namespace_uri: &str, local_name: &str) -> bool {
+        Element::has_attribute_ns(self, namespace_uri, local_name)
+    }
+    ///
+    /// See [`Element::set_attribute`](trait.Element.html#tymethod.set_attribute).
+    ///
+    #[inline]
+    pub fn set_attribute(&mut self, name: &str, value: &str) -> Result<()> {
+        Element::set_attribute(self, name, value)
+    }
+    ///
+    /// See [`Element::get_elements_by_tag_name`](trait.Element.html#tymethod.get_elements_by_tag_name)
+    /// or, if this node is a document,
+    /// [`Document::get_elements_by_tag_name`](trait.Document.html#tymethod.get_elements_by_tag_name);
+    /// calling the trait method is ambiguous where both traits are in scope.
+    ///
+    #[inline]
+    pub fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<RefNode> {
+        if is_document(self) {
+            Document::get_elements_by_tag_name(self, tag_name)
+        } else {
+            Element::get_elements_by_tag_name(self, tag_name)
+        }
+    }
+
+    // --------------------------------------------------------------------------------------------
+
+    ///
+    /// See [`Attribute::value`](trait.Attribute.html#tymethod.value).
+    ///
+    #[inline]
+    pub fn value(&self) -> Option<String> {
+        Attribute::value(self)
+    }
+    ///
+    /// See [`Attribute::owner_element`](trait.Attribute.html#tymethod.owner_element).
+    ///
+    #[inline]
+    pub fn owner_element(&self) -> Option<RefNode> {
+        Attribute::owner_element(self)
+    }
+
+    // --------------------------------------------------------------------------------------------
+
+    ///
+    /// See [`Document::document_element`](trait.Document.html#tymethod.document_element).
+    ///
+    #[inline]
+    pub fn document_element(&self) -> Option<RefNode> {
+        Document::document_element(self)
+    }
+    ///
+    /// See [`Document::get_element_by_id`](trait.Document.html#tymethod.get_element_by_id).
+    ///
+    #[inline]
+    pub fn get_element_by_id(&self, id: &str) -> Option<RefNode> {
+        Document::get_element_by_id(self, id)
+    }
+}
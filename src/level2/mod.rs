@@ -16,9 +16,9 @@ Implementation for DOM Core Level 2.
 | `Element`               | [`Element`](level2/trait.Element.html)                             |
 | `Entity`                | [`Entity`](level2/trait.Entity.html)                               |
 | `EntityReference`       | [`EntityReference`](level2/trait.EntityReference.html)             |
-| `NamedNodeMap`          | `HashMap<Name, RefNode>`                                    |
+| `NamedNodeMap`          | [`NamedNodeMap`](level2/trait.NamedNodeMap.html) for `NamedAttributeMap` and `HashMap<Name, RefNode>` |
 | `Node`                  | [`Node`](level2/trait.Node.html)                                   |
-| `NodeList`              | `Vec<Rc<RefNode>>`                                          |
+| `NodeList`              | [`NodeList`](level2/trait.NodeList.html) for `Vec<RefNode>` and `LiveNodeList` |
 | `Notation`              | [`Notation`](level2/trait.Notation.html)                           |
 | `ProcessingInstruction` | [`ProcessingInstruction`](level2/trait.ProcessingInstruction.html) |
 | `Text`                  | [`Text`](level2/trait.Text.html)                                   |
@@ -49,6 +49,12 @@ The following extensions are provided beyond the DOM Level 2 specification, all
    requirement from the specification: _"The DOM Level 2 API does not define a standard way to
    create DOMImplementation objects; DOM implementations must provide some proprietary way of
    bootstrapping these DOM interfaces, and then all other objects can be built from there."_.
+1. The [`Implementation`](dom_impl/struct.Implementation.html) type may be constructed directly to
+   provide independent implementations with their own default `ProcessingOptions`; documents
+   remember the implementation that created them.
+1. [`RefNode`](type.RefNode.html) has inherent methods mirroring the most frequently called methods
+   of the `Node`, `Element`, `Attribute`, and `Document` traits, which are resolved statically and
+   may be inlined, rather than called through the `&dyn` references of the `convert` module.
 1. The [`get_implementation_version`](dom_impl/fn.get_implementation_version.html) function in the
    [`dom_impl`](dom_impl/index.html) module returns a vendor-specific version identifier for the
    `DOMImplementation`.
@@ -58,14 +64,117 @@ The following extensions are provided beyond the DOM Level 2 specification, all
    optional behavior for a given `Document` instance.
 1. The trait [`DocumentDecl`](trait.DocumentDecl.html) extends `Document` with the ability to set
    and retrieve the XML declaration from the document's prolog.
+1. The trait [`IdAttributes`](trait.IdAttributes.html) extends `Document` with the ability to
+   declare application-specific ID attributes, which `get_element_by_id` then honours.
+1. The trait [`SchemaTypes`](trait.SchemaTypes.html) extends `Document` with a registered
+   [`TypeProvider`](ext/schema/trait.TypeProvider.html), through which a schema processor types ID
+   attributes, and makes elements nillable, for lookup, normalization, and validation.
+1. The trait [`IndexedElements`](trait.IndexedElements.html) extends `Document` with the index of
+   its elements by attribute value built by the parser.
+1. The trait [`Batch`](trait.Batch.html) extends `Document` with the ability to apply a set of
+   mutations atomically, validating the combined result and reporting every violation found; see
+   the [`batch`](batch/index.html) module.
 1. The trait [`Namespaced`](trait.Namespaced.html) extends `Element` with the ability to look-up
    namespace mappings (using the standard `xmlns` attribute).
+1. The trait [`CDataContent`](trait.CDataContent.html) extends `Element` with the ability to
+   replace its content with CDATA sections.
+1. The trait [`InnerXml`](trait.InnerXml.html) extends `Element` with the serialized form of the
+   element, and of its content, and the ability to replace its content by parsing markup.
+1. The trait [`Axes`](trait.Axes.html) extends `Node` with iterators over the XPath axes, such as
+   `ancestor_or_self`, `descendant_or_self`, `following`, and `preceding`.
+1. The trait [`NodeIterators`](trait.NodeIterators.html) extends `Node` with the same iterators
+   named as Rust iterators, `ancestors`, `children`, `descendants`, `following_siblings`, and
+   `preceding_siblings`.
+1. The trait [`NodeComparison`](trait.NodeComparison.html) extends `Node` with the DOM Level 3
+   operations `is_same_node`, `is_equal_node`, and `compare_document_position`.
+1. The trait [`LiveCollections`](trait.LiveCollections.html) extends `Node` with live
+   [`LiveNodeList`](ext/node_list/struct.LiveNodeList.html) forms of `child_nodes` and
+   `get_elements_by_tag_name`, which reflect later changes to the tree.
+1. The trait [`Search`](trait.Search.html) extends `Node` with the ability to find the matches of a
+   regular expression in the text, and attribute values, of a node and its descendants.
+1. The trait [`QuerySelector`](trait.QuerySelector.html) extends `Node` with the
+   `query_selector` and `query_selector_all` operations, using a subset of CSS selectors; this
+   requires the `selectors` feature.
+1. The trait [`TypedContent`](trait.TypedContent.html) extends `Element` with the ability to set,
+   and get, attribute and text values from typed values, using the lexical forms of the XML Schema
+   datatypes provided by the [`xsd`](xsd/index.html) module.
+1. The trait [`TextContent`](trait.TextContent.html) extends `Node` with the DOM Level 3
+   `text_content` and `set_text_content` operations.
+1. The trait [`Restructure`](trait.Restructure.html) extends `Node` with the `wrap_with` and
+   `unwrap_element` operations, which insert a new parent above a node, and replace an element by its
+   children.
+1. The trait [`Language`](trait.Language.html) extends `Node` with the resolution of its language
+   from `xml:lang` attributes, and the extraction of the text in a given language.
+1. The trait [`SourcePosition`](trait.SourcePosition.html) extends `Node` with the position in the
+   parsed input at which the node started, when tracked by the parser.
+1. The trait [`SourceSpans`](trait.SourceSpans.html) extends `Node` with the spans of the tags,
+   names, and attribute values of elements and attributes, and of the markup of other nodes, in
+   the parsed input, when tracked by the parser.
+1. The trait [`CharacterReferences`](trait.CharacterReferences.html) extends `Node` with the
+   numeric character references from which the data of a text node was parsed, when preserved by
+   the parser.
+1. The trait [`TreeSizes`](trait.TreeSizes.html) extends `Node` with its depth, and the size of
+   its subtree, maintained as the document changes when tracked by its processing options.
+1. The trait [`ElementContentWhitespace`](trait.ElementContentWhitespace.html) extends `Text` with
+   the DOM Level 3 `is_element_content_whitespace` operation, and the detection of text that is
+   only white space.
+1. The trait [`TypedChildren`](trait.TypedChildren.html) extends `Node` with iterators over the
+   children of a node filtered by `NodeType`.
+1. The trait [`NormalizeDocument`](trait.NormalizeDocument.html) extends `Document` with a form of
+   the DOM Level 3 `normalizeDocument` operation, governed by
+   [`NormalizationOptions`](normalize/struct.NormalizationOptions.html).
+1. The trait [`DtdDeclarations`](trait.DtdDeclarations.html) extends `DocumentType` with the
+   declarations of its internal subset, element, attribute list, entity, and notation
+   declarations, in the structured form provided by the [`dtd`](ext/dtd/index.html) module.
+1. The trait [`DocumentTypeExt`](trait.DocumentTypeExt.html) extends `DocumentType` so that a
+   document type created by a program can have entities, notations, and an internal subset added.
+1. The trait [`DtdValidation`](trait.DtdValidation.html) extends `Document` with the validation of
+   its elements, and their attributes, against the declarations of its document type.
+1. The trait [`DocumentTeardown`](trait.DocumentTeardown.html) extends `Document` with the
+   ability to detach every node in its tree, and the trait [`NodeLifetime`](trait.NodeLifetime.html)
+   extends `Node` to detect nodes whose owner document has been dropped; see the
+   [`teardown`](ext/teardown/index.html) module.
+1. The trait [`NodeProvenance`](trait.NodeProvenance.html) extends `Node` with a record of the
+   element from which an element was cloned, imported, or transformed; see the
+   [`provenance`](ext/provenance/index.html) module.
+1. The trait [`DocumentSnapshot`](trait.DocumentSnapshot.html) extends `Document` with immutable,
+   thread-safe, snapshots of its tree that share the subtrees unchanged since the previous
+   snapshot; see the [`snapshot`](ext/snapshot/index.html) module.
 1. The functions [`create_entity`](dom_impl/fn.create_entity.html),
+   [`create_defaulted_attribute`](dom_impl/fn.create_defaulted_attribute.html),
    [`create_internal_entity`](dom_impl/fn.create_internal_entity.html), and
    [`create_notation`](dom_impl/fn.create_notation.html) in the
    [`dom_impl`](dom_impl/index.html) module provide the ability to create instances of these
    Level 2 extended interfaces. In general most clients using the DOM do not need to create these
    however parsers constructing the DOM may.
+1. The [`NodeMap`](ext/node_map/struct.NodeMap.html) type associates client data with nodes,
+   keyed by node identity, without keeping those nodes alive.
+1. The [`canonical`](ext/canonical/index.html) module provides a model of an element tree that
+   identifies names only by namespace and local name, ignoring the choice of prefixes.
+1. The [`collect`](ext/collect/index.html) module provides adapters implementing `Extend` and
+   `FromIterator` so that children, and document fragments, can be assembled from iterators.
+1. The [`diff`](ext/diff/index.html) module computes the edits that turn one tree into another,
+   and applies them to a tree.
+1. The [`freeze`](ext/freeze/index.html) module provides the ability to make a subtree read-only
+   at runtime, and to thaw it again with the token returned when it was frozen.
+1. The [`mapping`](ext/mapping/index.html) module provides traits mapping Rust types to, and
+   from, elements; with the `derive` feature these may be derived for structs.
+1. The [`matcher`](ext/matcher/index.html) module, and the `tree!` macro, provide declarative
+   matching of element trees with detailed mismatch reports.
+1. The [`merge`](ext/merge/index.html) module merges an overlay document into a base document,
+   matching elements by name and key attributes, with a policy for conflicting attributes and
+   text.
+1. The [`raw`](ext/raw/index.html) module, with the `internals` feature, provides a read-only
+   summary of the storage behind each node for profilers, debuggers, and migration tools.
+1. The [`redact`](ext/redact/index.html) module replaces the values of sensitive attributes, and
+   the content of sensitive elements, with a placeholder and reports what was redacted.
+1. The [`sync`](ext/sync/index.html) module, with the `threadsafe` feature, provides a
+   thread-safe copy of a tree, built on `Arc<RwLock<…>>`, that may be read on other threads.
+1. The [`validate`](ext/validate/index.html) module audits a tree built by a program for
+   structural problems, such as unbound prefixes, before it is serialized.
+1. The [`writer`](ext/writer/index.html) module provides serialization with a set of
+   [`WriterOptions`](ext/writer/struct.WriterOptions.html), beyond the default `Display`
+   implementation.
 
 */
 
@@ -76,11 +185,14 @@ The following extensions are provided beyond the DOM Level 2 specification, all
 pub mod convert;
 
 pub mod dom_impl;
-pub use dom_impl::get_implementation;
+pub use dom_impl::{get_implementation, Implementation};
 
 pub(crate) mod node_impl;
 pub use node_impl::RefNode;
 
+mod named_node_map;
+pub use named_node_map::NamedAttributeMap;
+
 pub mod ext;
 
 // ------------------------------------------------------------------------------------------------
@@ -100,4 +212,6 @@ pub use traits::*;
 
 mod entity_resolver;
 
+mod inherent_impls;
+
 mod trait_impls;
@@ -0,0 +1,201 @@
+use crate::level2::convert::{is_attribute, is_element};
+use crate::level2::node_impl::{AttributeMap, Extension, RefNode};
+use crate::level2::traits::{Element, NamedNodeMap};
+use crate::shared::error::{Error, Result};
+use crate::shared::name::Name;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The attributes of an element, as returned by [`attributes`](trait.Node.html#tymethod.attributes),
+/// in the order in which they were added to the element; for a parsed document this is the order
+/// in which they were written.
+///
+/// The map is live, it reads the attributes of the element each time it is used, and
+/// `set_named_item` and `remove_named_item` change the attributes of the element itself. The
+/// map returned for a node that is not an element is empty, and readonly.
+///
+/// # Example
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::as_document;
+///
+/// let document_node = get_implementation()
+///     .create_document(None, Some("root"), None)
+///     .unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let mut root_node = document.document_element().unwrap();
+/// root_node.set_attribute("b", "1").unwrap();
+/// root_node.set_attribute("a", "2").unwrap();
+///
+/// let mut attributes = root_node.attributes();
+/// let _ = attributes
+///     .set_named_item(document.create_attribute_with("c", "3").unwrap())
+///     .unwrap();
+/// assert_eq!(attributes.item(0).unwrap().node_name().to_string(), "b");
+/// assert_eq!(root_node.get_attribute("c"), Some("3".to_string()));
+/// assert_eq!(document_node.to_string(), r#"<root b="1" a="2" c="3"></root>"#);
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct NamedAttributeMap {
+    element: Option<RefNode>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl NamedNodeMap for NamedAttributeMap {
+    type NodeRef = RefNode;
+
+    fn length(&self) -> usize {
+        self.with_attributes(|attributes| attributes.len())
+    }
+
+    fn item(&self, index: usize) -> Option<Self::NodeRef> {
+        self.with_attributes(|attributes| attributes.values().nth(index).cloned())
+    }
+
+    fn get_named_item(&self, name: &str) -> Option<Self::NodeRef> {
+        self.with_attributes(|attributes| attributes.get_qualified(name).cloned())
+    }
+
+    fn get_named_item_ns(&self, namespace_uri: &str, local_name: &str) -> Option<Self::NodeRef> {
+        self.with_attributes(|attributes| {
+            attributes.get_ns(Some(namespace_uri), local_name).cloned()
+        })
+    }
+
+    fn set_named_item(&mut self, arg: Self::NodeRef) -> Result<Option<Self::NodeRef>> {
+        let mut element = self.writable(&arg)?;
+        let old_attribute = self.get_named_item(&arg.node_name().to_string());
+        let _safe_to_ignore = element.set_attribute_node(arg)?;
+        Ok(old_attribute)
+    }
+
+    fn remove_named_item(&mut self, name: &str) -> Result<Self::NodeRef> {
+        let old_attribute = self.get_named_item(name).ok_or(Error::NotFound)?;
+        self.remove(old_attribute)
+    }
+
+    fn set_named_item_ns(&mut self, arg: Self::NodeRef) -> Result<Option<Self::NodeRef>> {
+        let mut element = self.writable(&arg)?;
+        let name = arg.node_name();
+        let old_attribute = self.with_attributes(|attributes| {
+            attributes
+                .get_ns(name.namespace_uri().as_deref(), name.local_name())
+                .cloned()
+        });
+        let _safe_to_ignore = element.set_attribute_node_ns(arg)?;
+        Ok(old_attribute)
+    }
+
+    fn remove_named_item_ns(
+        &mut self,
+        namespace_uri: &str,
+        local_name: &str,
+    ) -> Result<Self::NodeRef> {
+        let old_attribute = self
+            .get_named_item_ns(namespace_uri, local_name)
+            .ok_or(Error::NotFound)?;
+        self.remove(old_attribute)
+    }
+}
+
+impl IntoIterator for NamedAttributeMap {
+    type Item = (Name, RefNode);
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl NamedAttributeMap {
+    pub(crate) fn new(node: &RefNode) -> Self {
+        Self {
+            element: if is_element(node) {
+                Some(node.clone())
+            } else {
+                None
+            },
+        }
+    }
+
+    ///
+    /// Returns the number of attributes, as `length`.
+    ///
+    pub fn len(&self) -> usize {
+        self.length()
+    }
+
+    ///
+    /// Returns `true` if there are no attributes.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.length() == 0
+    }
+
+    ///
+    /// Returns the attribute with the name `name`, if present.
+    ///
+    pub fn get(&self, name: &Name) -> Option<RefNode> {
+        self.with_attributes(|attributes| attributes.get(name).cloned())
+    }
+
+    ///
+    /// Returns the names, and attributes, currently in the map in order; the result is not live.
+    ///
+    pub fn iter(&self) -> std::vec::IntoIter<(Name, RefNode)> {
+        self.with_attributes(|attributes| {
+            attributes
+                .iter()
+                .map(|(name, attribute)| (name.clone(), attribute.clone()))
+                .collect::<Vec<(Name, RefNode)>>()
+        })
+        .into_iter()
+    }
+
+    ///
+    /// Returns the names of the attributes currently in the map in order.
+    ///
+    pub fn keys(&self) -> impl Iterator<Item = Name> {
+        self.iter().map(|(name, _)| name)
+    }
+
+    ///
+    /// Returns the attributes currently in the map in order.
+    ///
+    pub fn values(&self) -> impl Iterator<Item = RefNode> {
+        self.iter().map(|(_, attribute)| attribute)
+    }
+
+    fn with_attributes<T: Default>(&self, f: impl FnOnce(&AttributeMap) -> T) -> T {
+        match &self.element {
+            None => T::default(),
+            Some(element) => match &element.borrow().i_extension {
+                Extension::Element { i_attributes, .. } => f(i_attributes),
+                _ => T::default(),
+            },
+        }
+    }
+
+    fn writable(&self, arg: &RefNode) -> Result<RefNode> {
+        match &self.element {
+            None => Err(Error::NoModificationAllowed),
+            Some(_) if !is_attribute(arg) => Err(Error::HierarchyRequest),
+            Some(element) => Ok(element.clone()),
+        }
+    }
+
+    fn remove(&mut self, old_attribute: RefNode) -> Result<RefNode> {
+        match &self.element {
+            None => Err(Error::NoModificationAllowed),
+            Some(element) => element.clone().remove_attribute_node(old_attribute),
+        }
+    }
+}
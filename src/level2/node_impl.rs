@@ -1,11 +1,18 @@
+use crate::level2::dom_impl::Implementation;
+use crate::level2::ext::character_reference::CharacterReference;
+use crate::level2::ext::position::{Position, Span};
+use crate::level2::ext::provenance::ProvenanceMap;
+use crate::level2::ext::schema::TypeProvider;
+use crate::level2::ext::snapshot::SnapshotState;
+use crate::level2::ext::sync::Shared;
+use crate::level2::ext::tag_index::TagIndex;
 use crate::level2::ext::ProcessingOptions;
 use crate::level2::ext::XmlDecl;
-use crate::level2::traits::{Node, NodeType};
-use crate::level2::{get_implementation, DOMImplementation};
+use crate::level2::traits::NodeType;
 use crate::shared::name::Name;
-use crate::shared::rc_cell::{RcRefCell, WeakRefCell};
-use std::collections::HashMap;
-use std::fmt::{Debug, Formatter};
+use crate::shared::rc_cell::{InnerCell, Mutable, RcRefCell, WeakRefCell};
+use crate::shared::syntax::XML_NS_SEPARATOR;
+use std::collections::{HashMap, HashSet};
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
@@ -41,13 +48,25 @@ pub(crate) enum Extension {
     None,
     Attribute {
         i_owner_element: Option<WeakRefNode>,
+        i_specified: bool,
     },
     Document {
-        i_implementation: &'static dyn DOMImplementation<NodeRef = RefNode>,
+        i_implementation: Shared<Implementation>,
         i_xml_declaration: Option<XmlDecl>,
         i_document_type: Option<RefNode>,
         i_id_map: HashMap<String, WeakRefNode>,
+        i_id_attributes: HashSet<(Option<String>, String)>,
+        // only populated by the parser, see `ParseOptions::add_index_attribute`.
+        i_element_index: HashMap<String, HashMap<String, Vec<WeakRefNode>>>,
         i_options: ProcessingOptions,
+        i_type_provider: Option<Shared<dyn TypeProvider>>,
+        // the codes of the diagnostics already logged by accessors, each is only logged once.
+        i_reported_diagnostics: InnerCell<HashSet<&'static str>>,
+        // only populated if the document uses an element index, see `tag_index`; boxed as it
+        // is only built on request.
+        i_tag_index: InnerCell<Option<Box<TagIndex>>>,
+        // only populated if the document tracks provenance, see `provenance`.
+        i_provenance: ProvenanceMap,
     },
     DocumentType {
         i_entities: HashMap<Name, RefNode>,
@@ -57,7 +76,7 @@ pub(crate) enum Extension {
         i_internal_subset: Option<String>,
     },
     Element {
-        i_attributes: HashMap<Name, RefNode>,
+        i_attributes: AttributeMap,
         i_namespaces: HashMap<Option<String>, String>,
     },
     Entity {
@@ -69,6 +88,29 @@ pub(crate) enum Extension {
         i_public_id: Option<String>,
         i_system_id: Option<String>,
     },
+    // only created by the parser, or by deserialization.
+    #[cfg_attr(
+        not(any(feature = "quick_parser", feature = "serde")),
+        allow(dead_code)
+    )]
+    Text {
+        i_character_references: Vec<CharacterReference>,
+    },
+}
+
+///
+/// The attributes of an element, in the order in which they were added, with an index of their
+/// positions by name, and of their names by local name. This allows attributes to be found by
+/// name, expanded name, or qualified name, without comparing against every attribute; elements in
+/// SVG or OOXML documents may have hundreds. The map must only be modified using `insert` and
+/// `remove`, to maintain the indexes.
+///
+#[doc(hidden)]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AttributeMap {
+    attributes: Vec<(Name, RefNode)>,
+    positions: HashMap<Name, usize>,
+    local_names: HashMap<String, Vec<Name>>,
 }
 
 ///
@@ -85,16 +127,143 @@ pub struct NodeImpl {
     pub(crate) i_parent_node: Option<WeakRefNode>,
     pub(crate) i_owner_document: Option<WeakRefNode>,
     pub(crate) i_child_nodes: Vec<RefNode>,
+    // the number of freezes covering this node, it is read-only while this is non-zero.
+    pub(crate) i_frozen: usize,
+    // whether this node, or a descendant, has changed since the last snapshot that included it,
+    // and the snapshot then taken of it; see `snapshot`.
+    pub(crate) i_snapshot: SnapshotState,
+    // the position in the parsed input at which this node started, if tracked by the parser.
+    pub(crate) i_position: Option<Position>,
+    // the spans of the markup in the parsed input the node was created from, if tracked by the
+    // parser; boxed as they are only recorded on request.
+    pub(crate) i_spans: Option<Box<NodeSpans>>,
+    // the number of ancestors of this node, and the number of nodes in its subtree, including
+    // itself; only kept up to date if the document's processing options track tree sizes.
+    pub(crate) i_depth: usize,
+    pub(crate) i_subtree_size: usize,
     pub(crate) i_extension: Extension,
 }
 
+///
+/// The spans of the markup in the parsed input from which a node was created, and digests of its
+/// content when parsing finished.
+///
+#[doc(hidden)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct NodeSpans {
+    // the start tag of an element, the name, `=`, and quoted value of an attribute, all the input
+    // of a document or fragment, or the whole of any other node.
+    pub(crate) markup: Option<Span>,
+    pub(crate) name: Option<Span>,
+    // the value of an attribute, between the quotes.
+    pub(crate) value: Option<Span>,
+    // the end tag of an element, if it was not an empty element tag.
+    pub(crate) end_tag: Option<Span>,
+    // the digests of the node's own content, and of the identity of its children, used to find
+    // the nodes modified since they were parsed.
+    pub(crate) digests: Option<(u64, u64)>,
+}
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
-impl Debug for &'static dyn DOMImplementation<NodeRef = RefNode> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "DOMImplementation")
+impl AttributeMap {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            attributes: Vec::with_capacity(capacity),
+            positions: HashMap::with_capacity(capacity),
+            local_names: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.attributes.len()
+    }
+
+    pub(crate) fn get(&self, name: &Name) -> Option<&RefNode> {
+        self.positions
+            .get(name)
+            .map(|position| &self.attributes[*position].1)
+    }
+
+    ///
+    /// Returns the attributes, and their names, in the order in which they were added.
+    ///
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Name, &RefNode)> {
+        self.attributes
+            .iter()
+            .map(|(name, attribute)| (name, attribute))
+    }
+
+    pub(crate) fn values(&self) -> impl Iterator<Item = &RefNode> {
+        self.attributes.iter().map(|(_, attribute)| attribute)
+    }
+
+    ///
+    /// Adds `attribute` with the name `name`; an attribute replacing one with the same name takes
+    /// its place in the order.
+    ///
+    pub(crate) fn insert(&mut self, name: Name, attribute: RefNode) -> Option<RefNode> {
+        match self.positions.get(&name) {
+            Some(position) => Some(std::mem::replace(
+                &mut self.attributes[*position].1,
+                attribute,
+            )),
+            None => {
+                let _safe_to_ignore = self.positions.insert(name.clone(), self.attributes.len());
+                self.local_names
+                    .entry(name.local_name.clone())
+                    .or_default()
+                    .push(name.clone());
+                self.attributes.push((name, attribute));
+                None
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, name: &Name) -> Option<RefNode> {
+        let position = self.positions.remove(name)?;
+        let (_, old_attribute) = self.attributes.remove(position);
+        for (following, _) in &self.attributes[position..] {
+            if let Some(following) = self.positions.get_mut(following) {
+                *following -= 1;
+            }
+        }
+        if let Some(names) = self.local_names.get_mut(&name.local_name) {
+            names.retain(|indexed| indexed != name);
+            if names.is_empty() {
+                let _safe_to_ignore = self.local_names.remove(&name.local_name);
+            }
+        }
+        Some(old_attribute)
+    }
+
+    ///
+    /// Returns the attribute with the expanded name `namespace_uri` and `local_name`.
+    ///
+    pub(crate) fn get_ns(&self, namespace_uri: Option<&str>, local_name: &str) -> Option<&RefNode> {
+        self.local_names
+            .get(local_name)?
+            .iter()
+            .find(|name| name.namespace_uri.as_deref() == namespace_uri)
+            .and_then(|name| self.get(name))
+    }
+
+    ///
+    /// Returns the attribute with the qualified name `qualified_name`, `prefix:local_name` or
+    /// `local_name`, regardless of its namespace.
+    ///
+    pub(crate) fn get_qualified(&self, qualified_name: &str) -> Option<&RefNode> {
+        let (prefix, local_name) = match qualified_name.split_once(XML_NS_SEPARATOR) {
+            None => (None, qualified_name),
+            Some((prefix, local_name)) => (Some(prefix), local_name),
+        };
+        self.local_names
+            .get(local_name)?
+            .iter()
+            .find(|name| name.prefix.as_deref() == prefix)
+            .and_then(|name| self.get(name))
     }
 }
 
@@ -102,15 +271,29 @@ impl Debug for &'static dyn DOMImplementation<NodeRef = RefNode> {
 
 impl NodeImpl {
     pub(crate) fn new_element(owner_document: WeakRefNode, name: Name) -> Self {
+        Self::new_element_with_capacity(owner_document, name, 0, 0)
+    }
+    pub(crate) fn new_element_with_capacity(
+        owner_document: WeakRefNode,
+        name: Name,
+        children_hint: usize,
+        attributes_hint: usize,
+    ) -> Self {
         Self {
             i_node_type: NodeType::Element,
             i_name: name,
             i_value: None,
             i_parent_node: None,
             i_owner_document: Some(owner_document),
-            i_child_nodes: vec![],
+            i_child_nodes: Vec::with_capacity(children_hint),
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::Element {
-                i_attributes: Default::default(),
+                i_attributes: AttributeMap::with_capacity(attributes_hint),
                 i_namespaces: Default::default(),
             },
         }
@@ -131,12 +314,30 @@ impl NodeImpl {
             i_value: None,
             i_parent_node: None,
             i_owner_document: Some(owner_document),
+            i_subtree_size: 1 + children.len(),
             i_child_nodes: children,
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
             i_extension: Extension::Attribute {
                 i_owner_element: None,
+                i_specified: true,
             },
         }
     }
+    pub(crate) fn new_defaulted_attribute(
+        owner_document: WeakRefNode,
+        name: Name,
+        value: &str,
+    ) -> Self {
+        let mut new_attribute = Self::new_attribute(owner_document, name, Some(value));
+        if let Extension::Attribute { i_specified, .. } = &mut new_attribute.i_extension {
+            *i_specified = false;
+        }
+        new_attribute
+    }
     pub(crate) fn new_text(owner_document: WeakRefNode, data: impl Into<String>) -> Self {
         Self {
             i_node_type: NodeType::Text,
@@ -145,6 +346,12 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::None,
         }
     }
@@ -156,6 +363,12 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::None,
         }
     }
@@ -171,6 +384,12 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::None,
         }
     }
@@ -182,10 +401,20 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::None,
         }
     }
-    pub(crate) fn new_document(doc_type: Option<RefNode>, options: ProcessingOptions) -> Self {
+    pub(crate) fn new_document(
+        implementation: Shared<Implementation>,
+        doc_type: Option<RefNode>,
+        options: ProcessingOptions,
+    ) -> Self {
         Self {
             i_node_type: NodeType::Document,
             i_name: Name::for_document(),
@@ -193,12 +422,24 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: None,
             i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::Document {
-                i_implementation: get_implementation(),
+                i_implementation: implementation,
                 i_xml_declaration: None,
                 i_document_type: doc_type,
                 i_id_map: Default::default(),
+                i_id_attributes: Default::default(),
+                i_element_index: Default::default(),
                 i_options: options,
+                i_type_provider: None,
+                i_reported_diagnostics: Default::default(),
+                i_tag_index: Default::default(),
+                i_provenance: Default::default(),
             },
         }
     }
@@ -210,6 +451,12 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::None,
         }
     }
@@ -226,6 +473,12 @@ impl NodeImpl {
             i_parent_node: owner_document.clone(),
             i_owner_document: owner_document,
             i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::DocumentType {
                 i_entities: Default::default(),
                 i_notations: Default::default(),
@@ -243,6 +496,12 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::None,
         }
     }
@@ -259,6 +518,12 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: owner_document,
             i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::Entity {
                 i_public_id: public_id.map(String::from),
                 i_system_id: system_id.map(String::from),
@@ -278,6 +543,12 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: owner_document,
             i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::Entity {
                 i_public_id: None,
                 i_system_id: None,
@@ -298,54 +569,70 @@ impl NodeImpl {
             i_parent_node: None,
             i_owner_document: owner_document,
             i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: Extension::Notation {
                 i_public_id: public_id.map(String::from),
                 i_system_id: system_id.map(String::from),
             },
         }
     }
-    #[allow(suspicious_double_ref_op)]
-    pub(crate) fn clone_node(&self, deep: bool) -> Self {
+    ///
+    /// A detached copy of this node alone; the copy has no parent or children, and any nodes
+    /// held by the extension (attributes, the document type, entities and notations) are not
+    /// copied. These are filled in by `Node::clone_node`.
+    ///
+    pub(crate) fn clone_node(&self) -> Self {
         let extension = match &self.i_extension {
             Extension::None => Extension::None,
-            Extension::Attribute { i_owner_element } => Extension::Attribute {
-                i_owner_element: i_owner_element.clone(),
+            Extension::Attribute { .. } => Extension::Attribute {
+                i_owner_element: None,
+                // clones of unspecified attributes are specified.
+                i_specified: true,
             },
             Extension::Document {
                 i_implementation,
                 i_xml_declaration,
-                i_document_type,
-                i_id_map,
+                i_id_attributes,
                 i_options,
+                i_type_provider,
+                ..
             } => Extension::Document {
                 i_implementation: i_implementation.clone(),
                 i_xml_declaration: i_xml_declaration.clone(),
-                i_document_type: i_document_type.clone(),
-                i_id_map: i_id_map.clone(),
+                i_document_type: None,
+                i_id_map: Default::default(),
+                i_id_attributes: i_id_attributes.clone(),
+                i_element_index: Default::default(),
                 i_options: i_options.clone(),
+                i_type_provider: i_type_provider.clone(),
+                i_reported_diagnostics: Default::default(),
+                i_tag_index: Default::default(),
+                i_provenance: Default::default(),
             },
             Extension::DocumentType {
-                i_entities,
-                i_notations,
                 i_public_id,
                 i_system_id,
                 i_internal_subset,
+                ..
             } => Extension::DocumentType {
-                i_entities: i_entities.clone(),
-                i_notations: i_notations.clone(),
+                i_entities: Default::default(),
+                i_notations: Default::default(),
                 i_public_id: i_public_id.clone(),
                 i_system_id: i_system_id.clone(),
                 i_internal_subset: i_internal_subset.clone(),
             },
-            Extension::Element {
-                i_attributes,
-                i_namespaces,
-            } => Extension::Element {
-                i_attributes: i_attributes.clone(),
+            Extension::Element { i_namespaces, .. } => Extension::Element {
+                i_attributes: Default::default(),
                 i_namespaces: i_namespaces.clone(),
             },
             entity @ Extension::Entity { .. } => entity.clone(),
             notation @ Extension::Notation { .. } => notation.clone(),
+            text @ Extension::Text { .. } => text.clone(),
         };
         Self {
             i_node_type: self.i_node_type.clone(),
@@ -353,15 +640,86 @@ impl NodeImpl {
             i_value: self.i_value.clone(),
             i_parent_node: None,
             i_owner_document: self.i_owner_document.clone(),
-            i_child_nodes: if deep {
-                self.i_child_nodes
-                    .iter()
-                    .map(|node| node.clone_node(deep).unwrap())
-                    .collect()
-            } else {
-                vec![]
-            },
+            i_child_nodes: vec![],
+            i_frozen: 0,
+            i_snapshot: Default::default(),
+            i_position: None,
+            i_spans: None,
+            i_depth: 0,
+            i_subtree_size: 1,
             i_extension: extension,
         }
     }
+    ///
+    /// Record the character references in the data of a text node; an empty list discards any
+    /// recorded.
+    ///
+    #[cfg(feature = "quick_parser")]
+    pub(crate) fn set_character_references(&mut self, references: Vec<CharacterReference>) {
+        if references.is_empty() {
+            self.discard_character_references();
+        } else if self.i_node_type == NodeType::Text {
+            self.i_extension = Extension::Text {
+                i_character_references: references,
+            };
+        }
+    }
+    ///
+    /// Character references are only written in their original form until the data is changed.
+    ///
+    pub(crate) fn discard_character_references(&mut self) {
+        if matches!(self.i_extension, Extension::Text { .. }) {
+            self.i_extension = Extension::None;
+        }
+    }
+    ///
+    /// The parent of this node or, as an attribute has no parent, the element that owns it.
+    ///
+    fn parent_or_owner_element(&self) -> Option<RefNode> {
+        match &self.i_extension {
+            Extension::Attribute {
+                i_owner_element, ..
+            } => i_owner_element.clone(),
+            _ => self.i_parent_node.clone(),
+        }
+        .and_then(WeakRefNode::upgrade)
+    }
+}
+
+impl Mutable for NodeImpl {
+    fn will_change(&self) {
+        // a changed node is marked, with each of its ancestors, so that a snapshot only visits
+        // the changed parts of the tree. As the ancestors of a marked node are always marked,
+        // marking stops at the first that is already; one mutably borrowed was marked then.
+        if !self.i_snapshot.mark_changed() {
+            return;
+        }
+        let mut next_node = self.parent_or_owner_element();
+        while let Some(node) = next_node {
+            let ref_node = match node.try_borrow() {
+                Some(ref_node) => ref_node,
+                None => break,
+            };
+            if !ref_node.i_snapshot.mark_changed() {
+                break;
+            }
+            next_node = ref_node.parent_or_owner_element();
+        }
+    }
+}
+
+impl Drop for NodeImpl {
+    fn drop(&mut self) {
+        // dropping a node drops each child it holds the only reference to, and so on down the
+        // tree; left to the compiler this recursion can overflow the stack for deeply nested
+        // trees, so the children of such nodes are taken, and dropped, in this loop instead.
+        let mut pending = std::mem::take(&mut self.i_child_nodes);
+        while let Some(node) = pending.pop() {
+            if node.strong_count() == 1 {
+                if let Some(mut mut_node) = node.try_borrow_mut() {
+                    pending.append(&mut mut_node.i_child_nodes);
+                }
+            }
+        }
+    }
 }
@@ -1,7 +1,20 @@
+use crate::diagnostics::{
+    Diagnostic, DUPLICATE_ID, FROZEN, INDEX_OUT_OF_RANGE, INVALID_EXTENSION, INVALID_NAME,
+    INVALID_NODE_TYPE, INVALID_STATE, NO_MODIFICATION_ALLOWED, NO_PARENT_NODE, WEAK_REF,
+    WRONG_DOCUMENT,
+};
 use crate::level2::convert::*;
 use crate::level2::dom_impl::{get_implementation, Implementation};
 use crate::level2::ext::convert::as_element_namespaced_mut;
+use crate::level2::ext::decl::{XmlDecl, XmlVersion};
+use crate::level2::ext::dtd::AttributeType;
 use crate::level2::ext::options::ProcessingOptions;
+use crate::level2::ext::provenance::{node_derived, Operation};
+use crate::level2::ext::sync::Shared;
+use crate::level2::ext::tag_index::{elements_by_tag_name, elements_by_tag_name_ns};
+use crate::level2::ext::tree_sizes::{child_attached, child_detached};
+use crate::level2::ext::TextContent;
+use crate::level2::named_node_map::NamedAttributeMap;
 use crate::level2::node_impl::*;
 use crate::level2::traits::*;
 use crate::shared::error::*;
@@ -9,7 +22,7 @@ use crate::shared::name::Name;
 use crate::shared::syntax::*;
 use crate::shared::{display, text};
 use std::collections::hash_map::RandomState;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::str::FromStr;
 
@@ -23,7 +36,7 @@ macro_rules! unwrap_extension_field {
         if let Extension::$variant { $field, .. } = &ref_self.i_extension {
             $field.clone()
         } else {
-            warn!("{}", MSG_INVALID_EXTENSION);
+            warn!("{}", INVALID_EXTENSION);
             Default::default()
         }
     }};
@@ -32,7 +45,7 @@ macro_rules! unwrap_extension_field {
         if let Extension::$variant { $field, .. } = &ref_self.i_extension {
             $closure_fn($field)
         } else {
-            warn!("{}", MSG_INVALID_EXTENSION);
+            warn!("{}", INVALID_EXTENSION);
             Default::default()
         }
     }};
@@ -44,7 +57,7 @@ macro_rules! unwrap_extension_field {
                 Some(value) => $some_closure(value),
             }
         } else {
-            warn!("{}", MSG_INVALID_EXTENSION);
+            warn!("{}", INVALID_EXTENSION);
             Default::default()
         }
     }};
@@ -56,7 +69,7 @@ macro_rules! unwrap_extension_field {
                 Some(value) => $some_closure(value),
             }
         } else {
-            warn!("{}", MSG_INVALID_EXTENSION);
+            warn!("{}", INVALID_EXTENSION);
             Default::default()
         }
     }};
@@ -82,8 +95,14 @@ impl Attribute for RefNode {
             let mut result = String::new();
             for child_node in self.child_nodes() {
                 if child_node.node_type() == NodeType::EntityReference {
-                    if let Some(value) = child_node.node_value() {
-                        result.push_str(&value);
+                    //
+                    // Use the replacement text of the entity reference if it has any, otherwise
+                    // the reference is resolved, from the document type, by normalization.
+                    //
+                    if child_node.has_child_nodes() {
+                        result.push_str(&child_node.text_content().unwrap_or_default());
+                    } else {
+                        result.push_str(&format!("&{};", child_node.node_name()));
                     }
                 } else if child_node.node_type() == NodeType::Text {
                     //
@@ -102,17 +121,44 @@ impl Attribute for RefNode {
         }
     }
     fn set_value(&mut self, value: &str) -> Result<()> {
+        let id_owner = id_attribute_owner(self);
+        if let Some((document_node, element)) = &id_owner {
+            check_ids_available(document_node, &[(value.to_string(), element.clone())])?;
+        }
         self.unset_value()?;
         let document_node = self.owner_document().unwrap();
         let document = as_document(&document_node).unwrap();
         let _safe_to_ignore = self.append_child(document.create_text_node(value))?;
+        if let Some((document_node, element)) = id_owner {
+            register_ids(&document_node, vec![(value.to_string(), element)]);
+        }
         Ok(())
     }
     fn unset_value(&mut self) -> Result<()> {
-        let mut mut_self = self.borrow_mut();
-        mut_self.i_child_nodes.clear();
+        check_not_frozen(self)?;
+        if let Some((document_node, element)) = id_attribute_owner(self) {
+            let old_value = self.value().unwrap_or_default();
+            unregister_ids(&document_node, &[(old_value, element)]);
+        }
+        let removed = {
+            let mut mut_self = self.borrow_mut();
+            //
+            // Any user modification of the value makes this a specified attribute.
+            //
+            if let Extension::Attribute { i_specified, .. } = &mut mut_self.i_extension {
+                *i_specified = true;
+            }
+            std::mem::take(&mut mut_self.i_child_nodes)
+        };
+        for child_node in &removed {
+            child_node.borrow_mut().i_parent_node = None;
+            child_detached(self, child_node);
+        }
         Ok(())
     }
+    fn specified(&self) -> bool {
+        unwrap_extension_field!(self, Attribute, i_specified)
+    }
     fn owner_element(&self) -> Option<Self::NodeRef> {
         unwrap_extension_field!(
             self,
@@ -123,7 +169,7 @@ impl Attribute for RefNode {
                     None => None,
                     Some(weak_ref) => match weak_ref.clone().upgrade() {
                         None => {
-                            warn!("{}", MSG_WEAK_REF);
+                            warn!("{}", WEAK_REF);
                             None
                         }
                         Some(ref_element) => Some(ref_element),
@@ -148,12 +194,12 @@ impl CharacterData for RefNode {
         let ref_self = self.borrow();
         match &ref_self.i_value {
             None => {
-                warn!("{}", MSG_INDEX_ERROR);
+                warn!("{}", INDEX_OUT_OF_RANGE);
                 Err(Error::IndexSize)
             }
             Some(data) => {
                 if offset >= data.len() {
-                    warn!("{}", MSG_INDEX_ERROR);
+                    warn!("{}", INDEX_OUT_OF_RANGE);
                     Err(Error::IndexSize)
                 } else if offset + count >= data.len() {
                     Ok(data[offset..].to_string())
@@ -168,11 +214,13 @@ impl CharacterData for RefNode {
         if new_data.is_empty() {
             return Ok(());
         }
+        check_not_frozen(self)?;
         let mut mut_self = self.borrow_mut();
         match &mut_self.i_value {
             None => mut_self.i_value = Some(new_data.to_string()),
             Some(old_data) => mut_self.i_value = Some(format!("{}{}", old_data, new_data)),
         }
+        mut_self.discard_character_references();
         Ok(())
     }
 
@@ -192,20 +240,22 @@ impl CharacterData for RefNode {
     }
 
     fn replace_data(&mut self, offset: usize, count: usize, replace_data: &str) -> Result<()> {
+        check_not_frozen(self)?;
         let mut mut_self = self.borrow_mut();
         match &mut_self.i_value {
             None => {
                 if offset + count != 0 {
-                    warn!("{}", MSG_INDEX_ERROR);
+                    warn!("{}", INDEX_OUT_OF_RANGE);
                     Err(Error::IndexSize)
                 } else {
                     mut_self.i_value = Some(replace_data.to_string());
+                    mut_self.discard_character_references();
                     Ok(())
                 }
             }
             Some(old_data) => {
                 if offset >= old_data.len() {
-                    warn!("{}", MSG_INDEX_ERROR);
+                    warn!("{}", INDEX_OUT_OF_RANGE);
                     Err(Error::IndexSize)
                 } else {
                     let mut new_data = old_data.clone();
@@ -215,6 +265,7 @@ impl CharacterData for RefNode {
                         new_data.replace_range(offset..offset + count, replace_data);
                     }
                     mut_self.i_value = Some(new_data);
+                    mut_self.discard_character_references();
                     Ok(())
                 }
             }
@@ -237,37 +288,42 @@ impl Document for RefNode {
         self.child_nodes().first().cloned()
     }
 
-    fn implementation(&self) -> &dyn DOMImplementation<NodeRef = RefNode> {
+    fn implementation(&self) -> Shared<dyn DOMImplementation<NodeRef = RefNode>> {
         let ref_self = self.borrow();
         if let Extension::Document {
             i_implementation, ..
         } = &ref_self.i_extension
         {
-            *i_implementation
+            i_implementation.clone()
         } else {
-            panic!("{}", MSG_INVALID_EXTENSION);
+            panic!("{}", INVALID_EXTENSION);
         }
     }
 
     fn create_attribute(&self, name: &str) -> Result<RefNode> {
         let name = Name::from_str(name)?;
+        check_name(self, &name, true)?;
         let node_impl = NodeImpl::new_attribute(self.clone().downgrade(), name, None);
         Ok(RefNode::new(node_impl))
     }
 
     fn create_attribute_with(&self, name: &str, value: &str) -> Result<RefNode> {
         let name = Name::from_str(name)?;
+        check_name(self, &name, true)?;
+        check_characters(self, value)?;
         let node_impl = NodeImpl::new_attribute(self.clone().downgrade(), name, Some(value));
         Ok(RefNode::new(node_impl))
     }
 
     fn create_attribute_ns(&self, namespace_uri: &str, qualified_name: &str) -> Result<RefNode> {
         let name = Name::new_ns(namespace_uri, qualified_name)?;
+        check_name(self, &name, true)?;
         let node_impl = NodeImpl::new_attribute(self.clone().downgrade(), name, None);
         Ok(RefNode::new(node_impl))
     }
 
     fn create_cdata_section(&self, data: &str) -> Result<RefNode> {
+        check_characters(self, data)?;
         let node_impl = NodeImpl::new_cdata(self.clone().downgrade(), data);
         Ok(RefNode::new(node_impl))
     }
@@ -290,12 +346,31 @@ impl Document for RefNode {
 
     fn create_element(&self, tag_name: &str) -> Result<RefNode> {
         let name = Name::from_str(tag_name)?;
+        check_name(self, &name, false)?;
         let node_impl = NodeImpl::new_element(self.clone().downgrade(), name);
         Ok(RefNode::new(node_impl))
     }
 
+    fn create_element_with_capacity(
+        &self,
+        tag_name: &str,
+        children_hint: usize,
+        attributes_hint: usize,
+    ) -> Result<RefNode> {
+        let name = Name::from_str(tag_name)?;
+        check_name(self, &name, false)?;
+        let node_impl = NodeImpl::new_element_with_capacity(
+            self.clone().downgrade(),
+            name,
+            children_hint,
+            attributes_hint,
+        );
+        Ok(RefNode::new(node_impl))
+    }
+
     fn create_element_ns(&self, namespace_uri: &str, qualified_name: &str) -> Result<RefNode> {
         let name = Name::new_ns(namespace_uri, qualified_name)?;
+        check_name(self, &name, false)?;
         let node_impl = NodeImpl::new_element(self.clone().downgrade(), name);
         Ok(RefNode::new(node_impl))
     }
@@ -310,6 +385,7 @@ impl Document for RefNode {
             return Err(Error::Syntax);
         }
         let target = Name::from_str(target)?;
+        check_characters(self, data.unwrap_or_default())?;
         let node_impl =
             NodeImpl::new_processing_instruction(self.clone().downgrade(), target, data);
         Ok(RefNode::new(node_impl))
@@ -327,19 +403,22 @@ impl Document for RefNode {
                 None => None,
                 Some(weak_ref) => match weak_ref.clone().upgrade() {
                     None => {
-                        warn!("{}", MSG_WEAK_REF);
+                        warn!("{}", WEAK_REF);
                         None
                     }
                     Some(ref_element) => Some(ref_element),
                 },
             }
         } else {
-            warn!("{}", MSG_INVALID_EXTENSION);
+            warn!("{}", INVALID_EXTENSION);
             None
         }
     }
 
     fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<RefNode> {
+        if let Some(elements) = elements_by_tag_name(self, tag_name) {
+            return elements;
+        }
         //
         // Delegate this call to the document element
         //
@@ -351,6 +430,9 @@ impl Document for RefNode {
     }
 
     fn get_elements_by_tag_name_ns(&self, namespace_uri: &str, local_name: &str) -> Vec<RefNode> {
+        if let Some(elements) = elements_by_tag_name_ns(self, namespace_uri, local_name) {
+            return elements;
+        }
         //
         // Delegate this call to the document element
         //
@@ -360,6 +442,17 @@ impl Document for RefNode {
             Vec::default()
         }
     }
+
+    fn import_node(&mut self, imported_node: RefNode, deep: bool) -> Result<RefNode> {
+        if !is_document(self) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        match imported_node.node_type() {
+            NodeType::Document | NodeType::DocumentType => Err(Error::NotSupported),
+            _ => Ok(clone_subtree(&imported_node, deep, Some(self), true)),
+        }
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -401,9 +494,13 @@ impl DOMImplementation for Implementation {
         qualified_name: Option<&str>,
         doc_type: Option<RefNode>,
     ) -> Result<RefNode> {
-        let mut options = ProcessingOptions::new();
-        options.set_add_namespaces();
-        create_document_with_options(namespace_uri, qualified_name, doc_type, options)
+        create_document_with_options(
+            Shared::new(self.clone()),
+            namespace_uri,
+            qualified_name,
+            doc_type,
+            self.default_options(),
+        )
     }
 
     fn create_document_type(
@@ -432,7 +529,7 @@ impl Element for RefNode {
             Some(attribute_node) => match as_attribute(&attribute_node) {
                 Ok(attribute) => attribute.value(),
                 Err(_) => {
-                    warn!("{}", MSG_INVALID_NODE_TYPE);
+                    warn_accessor(self, &INVALID_NODE_TYPE, None);
                     None
                 }
             },
@@ -462,30 +559,60 @@ impl Element for RefNode {
                 Ok(name) => {
                     let ref_self = self.borrow();
                     if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
-                        let node_name = name.to_string();
-                        i_attributes
-                            .iter()
-                            .find(|(name, _)| name.to_string() == node_name)
-                            .map(|(_, node)| node.clone())
+                        i_attributes.get_qualified(&name.to_string()).cloned()
                     } else {
-                        warn!("{}", MSG_INVALID_EXTENSION);
+                        warn_accessor(self, &INVALID_EXTENSION, None);
                         None
                     }
                 }
                 Err(_) => {
-                    warn!("{}: '{}'", MSG_INVALID_NAME, name);
+                    warn_accessor(self, &INVALID_NAME, Some(name));
                     None
                 }
             }
         } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn_accessor(self, &INVALID_NODE_TYPE, None);
             None
         }
     }
 
     fn set_attribute_node(&mut self, new_attribute: RefNode) -> Result<RefNode> {
         if is_element(self) && is_attribute(&new_attribute) {
+            check_not_frozen(self)?;
             check_same_document(self, &new_attribute)?;
+            check_duplicate_attribute(self, &new_attribute.node_name())?;
+            check_content_characters(&new_attribute)?;
+
+            //
+            // Check that any ID is not already in use, before anything is changed.
+            //
+            let name: Name = new_attribute.node_name();
+            let id_document = self
+                .owner_document()
+                .filter(|document| is_document_id_attribute(document, &self.node_name(), &name));
+            let id_value = as_attribute(&new_attribute)
+                .ok()
+                .and_then(|attribute| attribute.value())
+                .unwrap_or_default();
+            if let Some(document) = &id_document {
+                check_ids_available(document, &[(id_value.clone(), self.clone())])?;
+            }
+
+            //
+            // Check the namespace of the attribute, and the namespace it declares, if any.
+            //
+            if let Some(namespace_uri) = name.namespace_uri() {
+                check_namespace_uri(self, namespace_uri)?;
+            }
+            if name.is_namespace_declaration() {
+                if let Some(namespace_uri) = as_attribute(&new_attribute)
+                    .ok()
+                    .and_then(|attribute| attribute.value())
+                    .filter(|namespace_uri| !namespace_uri.is_empty())
+                {
+                    check_namespace_uri(self, &namespace_uri)?;
+                }
+            }
 
             //
             // Set the attribute's owner. This is *not* the same as parent which remains `None`.
@@ -498,11 +625,10 @@ impl Element for RefNode {
                 {
                     *i_owner_element = Some(self.clone().downgrade())
                 } else {
-                    panic!("{}", MSG_INVALID_EXTENSION);
+                    panic!("{}", INVALID_EXTENSION);
                 }
             }
 
-            let name: Name = new_attribute.node_name();
             if name.is_namespace_attribute() {
                 //
                 // Add to the element's namespace mapping hash
@@ -513,59 +639,47 @@ impl Element for RefNode {
                 let as_namespaced = as_element_namespaced_mut(self).unwrap();
                 let _ignore = match &name.prefix() {
                     None => as_namespaced.insert_mapping(None, &namespace_uri),
-                    Some(prefix) => as_namespaced.insert_mapping(Some(prefix), &namespace_uri),
+                    // the mapped prefix is the local part of `xmlns:prefix`.
+                    Some(_) => {
+                        as_namespaced.insert_mapping(Some(name.local_name()), &namespace_uri)
+                    }
                 }?;
             }
 
             let mut mut_self = self.borrow_mut();
             if let Extension::Element { i_attributes, .. } = &mut mut_self.i_extension {
-                let _safe_to_ignore =
+                let old_attribute =
                     i_attributes.insert(new_attribute.node_name(), new_attribute.clone());
-                {
+                if let Some(document) = id_document {
                     //
-                    // Add to the owning document's id_map hash
+                    // Update the owning document's id_map hash, replacing the ID of any
+                    // attribute with the same name.
                     //
-                    let attribute = as_attribute(&new_attribute).unwrap();
-                    let document = attribute.owner_document().unwrap();
-                    let mut mut_document = document.borrow_mut();
-                    let lax =
-                        if let Extension::Document { i_options, .. } = &mut_document.i_extension {
-                            i_options.has_assume_ids()
-                        } else {
-                            warn!("{}", MSG_INVALID_EXTENSION);
-                            false
-                        };
-                    if name.is_id_attribute(lax) {
-                        //
-                        // Update the document ID mapping
-                        //
-                        if let Extension::Document { i_id_map, .. } = &mut mut_document.i_extension
-                        {
-                            let id_value = attribute.value().unwrap();
-                            if i_id_map.contains_key(&id_value) {
-                                warn!("{}", MSG_DUPLICATE_ID);
-                                return Err(Error::Syntax);
-                            }
-                            let _safe_to_ignore =
-                                i_id_map.insert(id_value, self.clone().downgrade());
-                        } else {
-                            warn!("{}", MSG_INVALID_EXTENSION);
-                        }
+                    if let Some(old_value) = old_attribute
+                        .and_then(|old_attribute| as_attribute(&old_attribute).ok()?.value())
+                    {
+                        unregister_ids(&document, &[(old_value, self.clone())]);
                     }
+                    register_ids(&document, vec![(id_value, self.clone())]);
                 }
                 Ok(new_attribute)
             } else {
-                warn!("{}", MSG_INVALID_EXTENSION);
+                warn!("{}", INVALID_EXTENSION);
                 Err(Error::Syntax)
             }
         } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn!("{}", INVALID_NODE_TYPE);
             Err(Error::InvalidState)
         }
     }
 
     fn remove_attribute_node(&mut self, old_attribute: RefNode) -> Result<RefNode> {
         if is_element(self) {
+            check_not_frozen(self)?;
+            if let Some((document, element)) = id_attribute_owner(&old_attribute) {
+                let old_value = as_attribute(&old_attribute)?.value().unwrap_or_default();
+                unregister_ids(&document, &[(old_value, element)]);
+            }
             let mut mut_self = self.borrow_mut();
             if let Extension::Element { i_attributes, .. } = &mut mut_self.i_extension {
                 let _safe_to_ignore = i_attributes.remove(&old_attribute.node_name());
@@ -573,14 +687,13 @@ impl Element for RefNode {
                 let mut mut_old = mut_old.borrow_mut();
                 mut_old.i_parent_node = None;
                 // TODO: remove from Element::namespaces
-                // TODO: remove from Document::id_map
                 Ok(old_attribute)
             } else {
-                warn!("{}", MSG_INVALID_EXTENSION);
+                warn!("{}", INVALID_EXTENSION);
                 Err(Error::Syntax)
             }
         } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn!("{}", INVALID_NODE_TYPE);
             Err(Error::InvalidState)
         }
     }
@@ -597,7 +710,7 @@ impl Element for RefNode {
                 match as_element(child_node) {
                     Ok(ref_child) => results.extend(ref_child.get_elements_by_tag_name(&tag_name)),
                     Err(_) => {
-                        warn!("{}", MSG_INVALID_NODE_TYPE);
+                        warn_accessor(child_node, &INVALID_NODE_TYPE, None);
                     }
                 }
             }
@@ -611,7 +724,7 @@ impl Element for RefNode {
             Some(attribute_node) => match as_attribute(&attribute_node) {
                 Ok(attribute) => attribute.value(),
                 Err(_) => {
-                    warn!("{}", MSG_INVALID_NODE_TYPE);
+                    warn_accessor(self, &INVALID_NODE_TYPE, None);
                     None
                 }
             },
@@ -646,27 +759,21 @@ impl Element for RefNode {
                 Ok(_) => {
                     let ref_self = self.borrow();
                     if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
-                        let namespace_uri = &Some(namespace_uri.to_string());
-                        let local_name = &local_name.to_string();
                         i_attributes
-                            .iter()
-                            .find(|(name, _)| {
-                                name.namespace_uri() == namespace_uri
-                                    && name.local_name() == local_name
-                            })
-                            .map(|(_, node)| node.clone())
+                            .get_ns(Some(namespace_uri), local_name)
+                            .cloned()
                     } else {
-                        warn!("{}", MSG_INVALID_EXTENSION);
+                        warn_accessor(self, &INVALID_EXTENSION, None);
                         None
                     }
                 }
                 Err(_) => {
-                    warn!("{}: '{}'", MSG_INVALID_NAME, local_name);
+                    warn_accessor(self, &INVALID_NAME, Some(local_name));
                     None
                 }
             }
         } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn_accessor(self, &INVALID_NODE_TYPE, None);
             None
         }
     }
@@ -697,7 +804,7 @@ impl Element for RefNode {
                     Ok(ref_child) => results
                         .extend(ref_child.get_elements_by_tag_name_ns(&namespace_uri, &local_name)),
                     Err(_) => {
-                        warn!("{}", MSG_INVALID_NODE_TYPE);
+                        warn_accessor(child_node, &INVALID_NODE_TYPE, None);
                     }
                 }
             }
@@ -711,21 +818,19 @@ impl Element for RefNode {
                 Ok(name) => {
                     let ref_self = self.borrow();
                     if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
-                        i_attributes
-                            .keys()
-                            .any(|n| n.to_string() == name.to_string())
+                        i_attributes.get_qualified(&name.to_string()).is_some()
                     } else {
-                        warn!("{}", MSG_INVALID_EXTENSION);
+                        warn_accessor(self, &INVALID_EXTENSION, None);
                         false
                     }
                 }
                 Err(_) => {
-                    warn!("{}: '{}'", MSG_INVALID_NAME, name);
+                    warn_accessor(self, &INVALID_NAME, Some(name));
                     false
                 }
             }
         } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn_accessor(self, &INVALID_NODE_TYPE, None);
             false
         }
     }
@@ -736,25 +841,34 @@ impl Element for RefNode {
                 Ok(name) => {
                     let ref_self = self.borrow();
                     if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
-                        i_attributes.keys().any(|n| {
-                            n.namespace_uri() == name.namespace_uri()
-                                && n.local_name() == name.local_name()
-                        })
+                        i_attributes
+                            .get_ns(name.namespace_uri().as_deref(), name.local_name())
+                            .is_some()
                     } else {
-                        warn!("{}", MSG_INVALID_EXTENSION);
+                        warn_accessor(self, &INVALID_EXTENSION, None);
                         false
                     }
                 }
                 Err(_) => {
-                    warn!("{}: '{}'", MSG_INVALID_NAME, local_name);
+                    warn_accessor(self, &INVALID_NAME, Some(local_name));
                     false
                 }
             }
         } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
+            warn_accessor(self, &INVALID_NODE_TYPE, None);
             false
         }
     }
+
+    fn attributes_in_ns(&self, namespace_uri: &str) -> Vec<RefNode> {
+        let mut attributes: Vec<(Name, RefNode)> = self
+            .attributes()
+            .into_iter()
+            .filter(|(name, _)| name.namespace_uri().as_deref() == Some(namespace_uri))
+            .collect();
+        attributes.sort_by(|(lhs, _), (rhs, _)| lhs.local_name().cmp(rhs.local_name()));
+        attributes.into_iter().map(|(_, node)| node).collect()
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -779,6 +893,39 @@ impl EntityReference for RefNode {}
 
 // ------------------------------------------------------------------------------------------------
 
+impl NamedNodeMap for HashMap<Name, RefNode> {
+    type NodeRef = RefNode;
+
+    fn length(&self) -> usize {
+        self.len()
+    }
+
+    fn item(&self, index: usize) -> Option<Self::NodeRef> {
+        self.values().nth(index).cloned()
+    }
+
+    fn get_named_item(&self, name: &str) -> Option<Self::NodeRef> {
+        match Name::from_str(name) {
+            Ok(name) => self.get(&name).cloned(),
+            Err(_) => {
+                warn!("{}", INVALID_NAME);
+                None
+            }
+        }
+    }
+
+    fn get_named_item_ns(&self, namespace_uri: &str, local_name: &str) -> Option<Self::NodeRef> {
+        self.iter()
+            .find(|(name, _)| {
+                name.namespace_uri().as_deref() == Some(namespace_uri)
+                    && name.local_name() == local_name
+            })
+            .map(|(_, node)| node.clone())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
 impl Node for RefNode {
     type NodeRef = RefNode;
 
@@ -793,14 +940,21 @@ impl Node for RefNode {
     }
 
     fn set_node_value(&mut self, value: &str) -> Result<()> {
+        check_not_frozen(self)?;
+        check_not_declaration(self)?;
+        check_characters(self, value)?;
         let mut mut_self = self.borrow_mut();
         mut_self.i_value = Some(value.to_string());
+        mut_self.discard_character_references();
         Ok(())
     }
 
     fn unset_node_value(&mut self) -> Result<()> {
+        check_not_frozen(self)?;
+        check_not_declaration(self)?;
         let mut mut_self = self.borrow_mut();
         mut_self.i_value = None;
+        mut_self.discard_character_references();
         Ok(())
     }
 
@@ -842,7 +996,7 @@ impl Node for RefNode {
         let ref_self = self.borrow();
         match &ref_self.i_parent_node {
             None => {
-                warn!("{}", MSG_NO_PARENT_NODE);
+                warn!("{}", NO_PARENT_NODE);
                 None
             }
             Some(parent_node) => {
@@ -875,7 +1029,7 @@ impl Node for RefNode {
         let ref_self = self.borrow();
         match &ref_self.i_parent_node {
             None => {
-                warn!("{}", MSG_NO_PARENT_NODE);
+                warn!("{}", NO_PARENT_NODE);
                 None
             }
             Some(parent_node) => {
@@ -897,13 +1051,8 @@ impl Node for RefNode {
         }
     }
 
-    fn attributes(&self) -> HashMap<Name, RefNode, RandomState> {
-        if is_element(self) {
-            unwrap_extension_field!(self, Element, i_attributes)
-        } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
-            HashMap::default()
-        }
+    fn attributes(&self) -> NamedAttributeMap {
+        NamedAttributeMap::new(self)
     }
 
     fn owner_document(&self) -> Option<RefNode> {
@@ -920,18 +1069,25 @@ impl Node for RefNode {
             new_child: &RefNode,
             insert_position: Option<usize>,
         ) {
-            let mut mut_parent = parent_node.borrow_mut();
-            let new_child = new_child.clone();
-            match insert_position {
-                None => mut_parent.i_child_nodes.push(new_child),
-                Some(position) => mut_parent.i_child_nodes.insert(position, new_child),
+            {
+                let mut mut_parent = parent_node.borrow_mut();
+                let new_child = new_child.clone();
+                match insert_position {
+                    None => mut_parent.i_child_nodes.push(new_child),
+                    Some(position) => mut_parent.i_child_nodes.insert(position, new_child),
+                }
             }
+            child_attached(parent_node, new_child);
         }
 
+        check_not_frozen(self)?;
+
         if !is_child_allowed(self, &new_child) {
             warn!("The child you tried to add is not valid for this parent.");
             return Err(Error::HierarchyRequest);
         }
+        check_content_characters(&new_child)?;
+        let old_attribute_value = attribute_value(self);
 
         //
         // Special case for Document only.
@@ -968,6 +1124,24 @@ impl Node for RefNode {
 
         check_same_document(self, &new_child)?;
 
+        //
+        // Check that the IDs of the new elements are not in use by any other element, before
+        // anything is changed.
+        //
+        let document_node = if is_document(self) {
+            Some(self.clone())
+        } else {
+            self.owner_document()
+        };
+        let new_ids = match &document_node {
+            None => Vec::new(),
+            Some(document_node) => {
+                let new_ids = subtree_ids(document_node, &new_child);
+                check_ids_available(document_node, &new_ids)?;
+                new_ids
+            }
+        };
+
         //
         // Remove from it's current parent
         //
@@ -1008,10 +1182,18 @@ impl Node for RefNode {
             insert_or_append(self, &new_child, insert_position)
         }
 
+        if let Some(document_node) = document_node {
+            register_ids(&document_node, new_ids);
+        }
+        if let Some(old_value) = old_attribute_value {
+            attribute_value_changed(self, old_value);
+        }
+
         Ok(new_child)
     }
 
     fn replace_child(&mut self, new_child: RefNode, old_child: RefNode) -> Result<RefNode> {
+        check_not_frozen(self)?;
         if !is_child_allowed(self, &new_child) {
             return Err(Error::HierarchyRequest);
         }
@@ -1031,6 +1213,7 @@ impl Node for RefNode {
     }
 
     fn remove_child(&mut self, old_child: Self::NodeRef) -> Result<Self::NodeRef> {
+        check_not_frozen(self)?;
         let position = {
             let ref_self = self.borrow();
             ref_self
@@ -1044,13 +1227,21 @@ impl Node for RefNode {
                 Err(Error::NotFound)
             }
             Some(position) => {
+                let old_attribute_value = attribute_value(self);
                 let removed = {
                     let mut mut_self = self.borrow_mut();
                     mut_self.i_child_nodes.remove(position)
                 };
-                let mut mut_removed = removed.borrow_mut();
-                mut_removed.i_parent_node = None;
-                Ok(removed.clone())
+                removed.borrow_mut().i_parent_node = None;
+                child_detached(self, &removed);
+                if let Some(document_node) = removed.owner_document() {
+                    let old_ids = subtree_ids(&document_node, &removed);
+                    unregister_ids(&document_node, &old_ids);
+                }
+                if let Some(old_value) = old_attribute_value {
+                    attribute_value_changed(self, old_value);
+                }
+                Ok(removed)
             }
         }
     }
@@ -1059,18 +1250,137 @@ impl Node for RefNode {
         self.insert_before(new_child, None)
     }
 
+    fn append_children(&mut self, new_children: Vec<RefNode>) -> Result<()> {
+        check_not_frozen(self)?;
+
+        //
+        // The contents of any document fragment are appended in place of the fragment.
+        //
+        let mut fragments: Vec<RefNode> = Vec::new();
+        let mut appended: Vec<RefNode> = Vec::with_capacity(new_children.len());
+        for new_child in new_children {
+            if is_document_fragment(&new_child) {
+                check_not_frozen(&new_child)?;
+                appended.extend(new_child.child_nodes());
+                fragments.push(new_child);
+            } else {
+                appended.push(new_child);
+            }
+        }
+
+        //
+        // Validate every node before modifying anything.
+        //
+        let mut ancestors: Vec<RefNode> = Vec::new();
+        let mut ancestor = Some(self.clone());
+        while let Some(ancestor_node) = ancestor {
+            ancestor = ancestor_node.parent_node();
+            ancestors.push(ancestor_node);
+        }
+        let mut has_element = is_document(self)
+            && self
+                .child_nodes()
+                .iter()
+                .any(|n| n.node_type() == NodeType::Element);
+        let mut seen: HashSet<usize> = HashSet::with_capacity(appended.len());
+        for new_child in &appended {
+            if !is_child_allowed(self, new_child) {
+                warn!("The child you tried to add is not valid for this parent.");
+                return Err(Error::HierarchyRequest);
+            }
+            if is_document(self) && is_element(new_child) {
+                if has_element {
+                    warn!("cannot add more than one element to a document");
+                    return Error::HierarchyRequest.into();
+                }
+                has_element = true;
+            }
+            if ancestors.contains(new_child) || !seen.insert(new_child.as_ptr() as usize) {
+                warn!("cannot append a node to itself, its descendants, or more than once");
+                return Error::HierarchyRequest.into();
+            }
+            check_same_document(self, new_child)?;
+            if let Some(parent_node) = new_child.parent_node() {
+                if !is_document_fragment(&parent_node) {
+                    check_not_frozen(&parent_node)?;
+                }
+            }
+        }
+
+        let old_attribute_value = attribute_value(self);
+
+        //
+        // Remove from current parents, then update with references from self
+        //
+        for fragment in &mut fragments {
+            let children = std::mem::take(&mut fragment.borrow_mut().i_child_nodes);
+            for child_node in &children {
+                child_detached(fragment, child_node);
+            }
+        }
+        let owner_document = if is_document(self) {
+            Some(self.clone().downgrade())
+        } else {
+            self.borrow().i_owner_document.clone()
+        };
+        for new_child in &appended {
+            if let Some(mut parent_node) = new_child.parent_node() {
+                if !is_document_fragment(&parent_node) {
+                    let _safe_to_ignore = parent_node.remove_child(new_child.clone())?;
+                }
+            }
+            let mut mut_child = new_child.borrow_mut();
+            mut_child.i_parent_node = Some(self.clone().downgrade());
+            mut_child.i_owner_document.clone_from(&owner_document);
+        }
+
+        {
+            let mut mut_self = self.borrow_mut();
+            mut_self.i_child_nodes.reserve(appended.len());
+            mut_self.i_child_nodes.extend(appended.iter().cloned());
+        }
+        for new_child in &appended {
+            child_attached(self, new_child);
+        }
+        if let Some(old_value) = old_attribute_value {
+            attribute_value_changed(self, old_value);
+        }
+        Ok(())
+    }
+
     fn has_child_nodes(&self) -> bool {
         !self.child_nodes().is_empty()
     }
 
     fn clone_node(&self, deep: bool) -> Option<RefNode> {
-        let ref_self = self.borrow();
-        let new_node = ref_self.clone_node(deep);
-        Some(RefNode::new(new_node))
+        Some(clone_subtree(self, deep, None, false))
     }
 
     fn normalize(&mut self) {
+        if is_frozen(self) {
+            warn!("{}", FROZEN);
+            return;
+        }
+        let options = document_options(self);
         for child_node in self.child_nodes() {
+            if is_frozen(&child_node) {
+                continue;
+            }
+            let child_node = if is_cdata_section(&child_node)
+                && options.has_convert_cdata_to_text()
+                && !text::requires_cdata(child_node.node_value().unwrap_or_default())
+            {
+                let document_node = self.owner_document().unwrap_or_else(|| self.clone());
+                let document = as_document(&document_node).unwrap();
+                let new_node =
+                    document.create_text_node(&child_node.node_value().unwrap_or_default());
+                if self.replace_child(new_node.clone(), child_node).is_err() {
+                    panic!("Could not replace CDATA section with text node");
+                }
+                new_node
+            } else {
+                child_node
+            };
             if is_text(&child_node) {
                 if CharacterData::length(&child_node) == 0 {
                     if self.remove_child(child_node).is_err() {
@@ -1078,7 +1388,7 @@ impl Node for RefNode {
                     }
                 } else if let Some(last_child_node) = child_node.previous_sibling() {
                     let last_child_node = &mut last_child_node.clone();
-                    if is_text(last_child_node) {
+                    if is_text(last_child_node) && !is_frozen(last_child_node) {
                         if last_child_node
                             .append_data(&child_node.node_value().unwrap())
                             .is_err()
@@ -1090,6 +1400,21 @@ impl Node for RefNode {
                         }
                     }
                 }
+            } else if is_cdata_section(&child_node) && options.has_merge_cdata_sections() {
+                if let Some(last_child_node) = child_node.previous_sibling() {
+                    let last_child_node = &mut last_child_node.clone();
+                    if is_cdata_section(last_child_node) && !is_frozen(last_child_node) {
+                        if last_child_node
+                            .append_data(&child_node.node_value().unwrap_or_default())
+                            .is_err()
+                        {
+                            panic!("Could not merge CDATA sections");
+                        }
+                        if self.remove_child(child_node).is_err() {
+                            panic!("Could not remove unnecessary CDATA section");
+                        }
+                    }
+                }
             }
         }
     }
@@ -1105,6 +1430,20 @@ impl Node for RefNode {
 
 // ------------------------------------------------------------------------------------------------
 
+impl NodeList for Vec<RefNode> {
+    type NodeRef = RefNode;
+
+    fn length(&self) -> usize {
+        self.len()
+    }
+
+    fn item(&self, index: usize) -> Option<Self::NodeRef> {
+        self.get(index).cloned()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
 impl Notation for RefNode {
     fn public_id(&self) -> Option<String> {
         unwrap_extension_field!(self, Notation, i_public_id)
@@ -1151,7 +1490,7 @@ impl Text for RefNode {
                     Ok(NodeImpl::new_cdata(document.clone(), &new_data))
                 }
                 _ => {
-                    warn!("{}", MSG_INVALID_NODE_TYPE);
+                    warn!("{}", INVALID_NODE_TYPE);
                     Err(Error::Syntax)
                 }
             }?
@@ -1169,7 +1508,7 @@ impl Text for RefNode {
 
 impl Display for RefNode {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        display::fmt_node(self, f)
+        display::fmt_node(self, &Default::default(), f)
     }
 }
 
@@ -1177,13 +1516,530 @@ impl Display for RefNode {
 // Private Functions
 // ------------------------------------------------------------------------------------------------
 
-const WILD_CARD: &str = "*";
+pub(crate) const WILD_CARD: &str = "*";
+
+pub(crate) fn document_options(node: &RefNode) -> ProcessingOptions {
+    let document_node = if is_document(node) {
+        node.clone()
+    } else {
+        match node.owner_document() {
+            None => return Default::default(),
+            Some(document_node) => document_node,
+        }
+    };
+    unwrap_extension_field!(document_node, Document, i_options)
+}
+
+///
+/// Log `diagnostic`, followed by `detail` if any, for an accessor called on `node` that returns
+/// an empty result. As accessors are called often, each diagnostic is only logged once for a
+/// document, and not at all if the document has the
+/// [`quiet_accessors`](ext/options/struct.ProcessingOptions.html#method.set_quiet_accessors)
+/// processing option.
+///
+pub(crate) fn warn_accessor(node: &RefNode, diagnostic: &Diagnostic, detail: Option<&str>) {
+    let document_node = if is_document(node) {
+        Some(node.clone())
+    } else {
+        node.owner_document()
+    };
+    if let Some(document_node) = document_node {
+        // if the document is being changed the diagnostic is always logged.
+        if let Some(ref_document) = document_node.try_borrow() {
+            if let Extension::Document {
+                i_options,
+                i_reported_diagnostics,
+                ..
+            } = &ref_document.i_extension
+            {
+                if i_options.has_quiet_accessors()
+                    || !i_reported_diagnostics
+                        .borrow_mut()
+                        .insert(diagnostic.code())
+                {
+                    return;
+                }
+            }
+        }
+    }
+    match detail {
+        None => warn!("{}", diagnostic),
+        Some(detail) => warn!("{}: '{}'", diagnostic, detail),
+    }
+}
+
+///
+/// Returns `true` if an attribute named `name` is an ID attribute in `document`; either by name,
+/// as `xml:id` (or any `id` if the document assumes IDs), or because the name has been registered
+/// using [`register_id_attribute`](ext/trait.IdAttributes.html#tymethod.register_id_attribute).
+///
+pub(crate) fn is_document_id_attribute(
+    document: &RefNode,
+    element_name: &Name,
+    name: &Name,
+) -> bool {
+    let ref_document = document.borrow();
+    if let Extension::Document {
+        i_id_attributes,
+        i_options,
+        i_type_provider,
+        ..
+    } = &ref_document.i_extension
+    {
+        name.is_id_attribute(i_options.has_assume_ids())
+            || i_id_attributes.contains(&(name.namespace_uri().clone(), name.local_name().clone()))
+            || i_type_provider.as_ref().is_some_and(|type_provider| {
+                type_provider.attribute_type(element_name, name) == Some(AttributeType::Id)
+            })
+    } else {
+        warn!("{}", INVALID_EXTENSION);
+        false
+    }
+}
+
+///
+/// If `attribute` is an ID attribute of an element, returns the owning document and the element.
+///
+fn id_attribute_owner(attribute: &RefNode) -> Option<(RefNode, RefNode)> {
+    let element = as_attribute(attribute).ok()?.owner_element()?;
+    let document = attribute.owner_document()?;
+    if is_document_id_attribute(&document, &element.node_name(), &attribute.node_name()) {
+        Some((document, element))
+    } else {
+        None
+    }
+}
+
+///
+/// If `node` is an attribute returns its value, which may be empty, else `None`.
+///
+fn attribute_value(node: &RefNode) -> Option<String> {
+    as_attribute(node)
+        .ok()
+        .map(|attribute| attribute.value().unwrap_or_default())
+}
+
+///
+/// Called when the children of `attribute` have changed; the attribute becomes specified, as for
+/// `set_value`, and if it is an ID attribute the document's ID map is updated.
+///
+fn attribute_value_changed(attribute: &RefNode, old_value: String) {
+    if let Extension::Attribute { i_specified, .. } = &mut attribute.borrow_mut().i_extension {
+        *i_specified = true;
+    }
+    if let Some((document_node, element)) = id_attribute_owner(attribute) {
+        unregister_ids(&document_node, &[(old_value, element.clone())]);
+        let new_ids = vec![(attribute_value(attribute).unwrap_or_default(), element)];
+        if check_ids_available(&document_node, &new_ids).is_ok() {
+            register_ids(&document_node, new_ids);
+        }
+    }
+}
+
+///
+/// The value of each ID attribute of `node`, and of its descendant elements, with the element.
+///
+fn subtree_ids(document: &RefNode, node: &RefNode) -> Vec<(String, RefNode)> {
+    let mut ids = Vec::new();
+    let mut nodes = vec![node.clone()];
+    while let Some(node) = nodes.pop() {
+        if is_element(&node) {
+            for (name, attribute) in node.attributes() {
+                if is_document_id_attribute(document, &node.node_name(), &name) {
+                    let id_value = as_attribute(&attribute)
+                        .ok()
+                        .and_then(|attribute| attribute.value())
+                        .unwrap_or_default();
+                    ids.push((id_value, node.clone()));
+                }
+            }
+        }
+        nodes.extend(node.child_nodes());
+    }
+    ids
+}
+
+///
+/// Returns `Error::Syntax` if any of the IDs is mapped to an element other than the one given, or
+/// is given twice for different elements.
+///
+fn check_ids_available(document: &RefNode, ids: &[(String, RefNode)]) -> Result<()> {
+    let ref_document = document.borrow();
+    if let Extension::Document { i_id_map, .. } = &ref_document.i_extension {
+        let mut seen: HashMap<&String, &RefNode> = HashMap::new();
+        for (id_value, element) in ids {
+            let existing = seen
+                .get(id_value)
+                .map(|existing| (*existing).clone())
+                .or_else(|| {
+                    i_id_map
+                        .get(id_value)
+                        .and_then(|weak_ref| weak_ref.clone().upgrade())
+                });
+            match existing {
+                Some(existing) if &existing != element => {
+                    warn!("{}", DUPLICATE_ID);
+                    return Err(Error::Syntax);
+                }
+                _ => {
+                    let _safe_to_ignore = seen.insert(id_value, element);
+                }
+            }
+        }
+    } else {
+        warn!("{}", INVALID_EXTENSION);
+    }
+    Ok(())
+}
 
-fn tag_name_match(test: &str, against: &str) -> bool {
+fn register_ids(document: &RefNode, ids: Vec<(String, RefNode)>) {
+    let mut mut_document = document.borrow_mut();
+    if let Extension::Document { i_id_map, .. } = &mut mut_document.i_extension {
+        i_id_map.extend(
+            ids.into_iter()
+                .map(|(id_value, element)| (id_value, element.downgrade())),
+        );
+    } else {
+        warn!("{}", INVALID_EXTENSION);
+    }
+}
+
+///
+/// Removes each ID from the document's map, if it is still mapped to the element given; any
+/// entry whose element has been dropped is also removed.
+///
+fn unregister_ids(document: &RefNode, ids: &[(String, RefNode)]) {
+    let mut mut_document = document.borrow_mut();
+    if let Extension::Document { i_id_map, .. } = &mut mut_document.i_extension {
+        for (id_value, element) in ids {
+            let is_mapped = i_id_map
+                .get(id_value)
+                .map(|weak_ref| match weak_ref.clone().upgrade() {
+                    None => true,
+                    Some(existing) => &existing == element,
+                })
+                .unwrap_or_default();
+            if is_mapped {
+                let _safe_to_ignore = i_id_map.remove(id_value);
+            }
+        }
+    } else {
+        warn!("{}", INVALID_EXTENSION);
+    }
+}
+
+fn check_name(document: &RefNode, name: &Name, is_attribute: bool) -> Result<()> {
+    if document_options(document).has_strict_names() {
+        name.check_strict(is_attribute)?;
+    }
+    match name.namespace_uri() {
+        Some(namespace_uri) => check_namespace_uri(document, namespace_uri),
+        None => Ok(()),
+    }
+}
+
+///
+/// For documents with the `validate_namespace_uris` processing option, returns `Error::Namespace`
+/// if `namespace_uri` is not an IRI reference, and logs a warning if it is not absolute.
+///
+fn check_namespace_uri(document: &RefNode, namespace_uri: &str) -> Result<()> {
+    if document_options(document).has_validate_namespace_uris() {
+        if !text::is_iri_reference(namespace_uri) {
+            warn!("the namespace URI `{}` is not a valid IRI", namespace_uri);
+            return Err(Error::Namespace);
+        } else if !text::is_absolute_iri(namespace_uri) {
+            warn!(
+                "the namespace URI `{}` is relative, relative namespace URIs are deprecated",
+                namespace_uri
+            );
+        }
+    }
+    Ok(())
+}
+
+///
+/// For documents with the `fail_on_invalid_character` processing option, returns
+/// `Error::InvalidCharacter` if `data` contains a character that is not allowed by the version
+/// of XML declared by the document of `node`.
+///
+fn check_characters(node: &RefNode, data: &str) -> Result<()> {
+    match character_check(node) {
+        None => Ok(()),
+        Some(is_char) => check_characters_with(is_char, data),
+    }
+}
+
+///
+/// As `check_characters` for the content of `node`, its attributes, and all its descendants.
+///
+fn check_content_characters(node: &RefNode) -> Result<()> {
+    fn check_subtree(is_char: fn(char) -> bool, node: &RefNode) -> Result<()> {
+        match node.node_type() {
+            NodeType::Text
+            | NodeType::CData
+            | NodeType::Comment
+            | NodeType::ProcessingInstruction => {
+                check_characters_with(is_char, &node.node_value().unwrap_or_default())?
+            }
+            NodeType::Element => {
+                for attribute in node.attributes().values() {
+                    check_subtree(is_char, &attribute)?;
+                }
+            }
+            _ => {}
+        }
+        for child_node in node.child_nodes() {
+            check_subtree(is_char, &child_node)?;
+        }
+        Ok(())
+    }
+    match character_check(node) {
+        None => Ok(()),
+        Some(is_char) => check_subtree(is_char, node),
+    }
+}
+
+fn character_check(node: &RefNode) -> Option<fn(char) -> bool> {
+    if !document_options(node).has_fail_on_invalid_character() {
+        return None;
+    }
+    let document_node = if is_document(node) {
+        node.clone()
+    } else {
+        node.owner_document()?
+    };
+    let version = unwrap_extension_field!(
+        document_node,
+        Document,
+        i_xml_declaration,
+        |xml_declaration: &Option<XmlDecl>| xml_declaration.as_ref().map(|decl| decl.version())
+    );
+    match version {
+        Some(XmlVersion::V11) => Some(text::is_xml_11_char),
+        _ => Some(text::is_xml_10_char),
+    }
+}
+
+fn check_characters_with(is_char: fn(char) -> bool, data: &str) -> Result<()> {
+    match data.chars().find(|c| !is_char(*c)) {
+        None => Ok(()),
+        Some(c) => {
+            warn!(
+                "the character U+{:04X} is not allowed in XML content",
+                c as u32
+            );
+            Err(Error::InvalidCharacter)
+        }
+    }
+}
+
+///
+/// For documents with the `fail_on_duplicate_attribute` processing option, returns
+/// `Error::InUseAttribute` if `element` has an attribute, other than one named `name`, with the
+/// same qualified name, or the same namespace and local name.
+///
+fn check_duplicate_attribute(element: &RefNode, name: &Name) -> Result<()> {
+    if !document_options(element).has_fail_on_duplicate_attribute() {
+        return Ok(());
+    }
+    let ref_element = element.borrow();
+    if let Extension::Element { i_attributes, .. } = &ref_element.i_extension {
+        let same_qualified_name = i_attributes.get_qualified(&name.to_string());
+        let same_expanded_name = match name.namespace_uri() {
+            None => None,
+            Some(namespace_uri) => i_attributes.get_ns(Some(namespace_uri), name.local_name()),
+        };
+        if same_qualified_name
+            .into_iter()
+            .chain(same_expanded_name)
+            .any(|existing| &existing.node_name() != name)
+        {
+            warn!("the element already has an attribute named like `{}`", name);
+            return Err(Error::InUseAttribute);
+        }
+    }
+    Ok(())
+}
+
+//
+// CHECK: Raise `Error::NoModificationAllowed` if `node` is an entity or a notation; these are
+// read-only, as their declarations in the internal subset are the authoritative form.
+//
+fn check_not_declaration(node: &RefNode) -> Result<()> {
+    if matches!(node.node_type(), NodeType::Entity | NodeType::Notation) {
+        warn!("{}", NO_MODIFICATION_ALLOWED);
+        Err(Error::NoModificationAllowed)
+    } else {
+        Ok(())
+    }
+}
+
+///
+/// Returns `true` if `node` is covered by at least one freeze, else `false`.
+///
+pub(crate) fn is_frozen(node: &RefNode) -> bool {
+    node.borrow().i_frozen > 0
+}
+
+//
+// CHECK: Raise `Error::NoModificationAllowed` if `node` is frozen.
+//
+pub(crate) fn check_not_frozen(node: &RefNode) -> Result<()> {
+    if is_frozen(node) {
+        warn!("{}", FROZEN);
+        Err(Error::NoModificationAllowed)
+    } else {
+        Ok(())
+    }
+}
+
+///
+/// Returns a deep copy of `node`, from another tree, owned by `target` if it is a document, else
+/// by the document that owns `target`.
+///
+pub(crate) fn import_into(target: &RefNode, node: &RefNode) -> Result<RefNode> {
+    let mut document_node = if is_document(target) {
+        target.clone()
+    } else {
+        target.owner_document().ok_or_else(|| {
+            warn!("{}", INVALID_STATE);
+            Error::InvalidState
+        })?
+    };
+    as_document_mut(&mut document_node)?.import_node(node.clone(), true)
+}
+
+///
+/// Clone `node`, and if `deep` its descendants. When cloning within a document clone, or
+/// importing, `new_document` is the document that becomes the owner of every node cloned.
+///
+/// The attributes of an element, and the value of an attribute, are always copied. The clone of
+/// a document also copies its document type, with its entities and notations, if `deep`, and
+/// adds its elements to the new ID map. When `importing`, unspecified attributes and the children
+/// of entity references are not copied, as described for `Document::import_node`.
+///
+fn clone_subtree(
+    node: &RefNode,
+    deep: bool,
+    new_document: Option<&RefNode>,
+    importing: bool,
+) -> RefNode {
+    let mut new_impl = node.borrow().clone_node();
+    if let Some(new_document) = new_document {
+        new_impl.i_owner_document = Some(new_document.clone().downgrade());
+    }
+    let new_node = RefNode::new(new_impl);
+    node_derived(
+        &new_node,
+        node,
+        if importing {
+            Operation::Import
+        } else {
+            Operation::Clone
+        },
+    );
+    let new_document = if is_document(&new_node) {
+        Some(&new_node)
+    } else {
+        new_document
+    };
+
+    let (related, children): (Vec<(Name, RefNode)>, Vec<RefNode>) = {
+        let ref_node = node.borrow();
+        let related = match &ref_node.i_extension {
+            Extension::Element { i_attributes, .. } => i_attributes
+                .iter()
+                .filter(|(_, node)| {
+                    !importing || as_attribute(node).map(|a| a.specified()).unwrap_or(true)
+                })
+                .map(|(name, node)| (name.clone(), node.clone()))
+                .collect(),
+            Extension::DocumentType {
+                i_entities,
+                i_notations,
+                ..
+            } if deep => i_entities
+                .iter()
+                .chain(i_notations.iter())
+                .map(|(name, node)| (name.clone(), node.clone()))
+                .collect(),
+            Extension::Document {
+                i_document_type: Some(document_type),
+                ..
+            } if deep => vec![(document_type.node_name(), document_type.clone())],
+            _ => Vec::new(),
+        };
+        let children = if (deep && !(importing && is_entity_reference(node))) || is_attribute(node)
+        {
+            ref_node.i_child_nodes.clone()
+        } else {
+            Vec::new()
+        };
+        (related, children)
+    };
+
+    for (name, related_node) in related {
+        let new_related = clone_subtree(&related_node, true, new_document, importing);
+        let is_id = !importing
+            && is_attribute(&new_related)
+            && new_document.is_some_and(|document| {
+                is_document_id_attribute(document, &node.node_name(), &name)
+            });
+        let mut mut_node = new_node.borrow_mut();
+        match &mut mut_node.i_extension {
+            Extension::Element { i_attributes, .. } => {
+                if let Extension::Attribute {
+                    i_owner_element, ..
+                } = &mut new_related.borrow_mut().i_extension
+                {
+                    *i_owner_element = Some(new_node.clone().downgrade());
+                }
+                if let (true, Some(new_document)) = (is_id, new_document) {
+                    let id_value = as_attribute(&new_related)
+                        .ok()
+                        .and_then(|attribute| attribute.value())
+                        .unwrap_or_default();
+                    if let Extension::Document { i_id_map, .. } =
+                        &mut new_document.borrow_mut().i_extension
+                    {
+                        let _safe_to_ignore =
+                            i_id_map.insert(id_value, new_node.clone().downgrade());
+                    }
+                }
+                let _safe_to_ignore = i_attributes.insert(name, new_related);
+            }
+            Extension::DocumentType {
+                i_entities,
+                i_notations,
+                ..
+            } => {
+                let _safe_to_ignore = if is_notation(&new_related) {
+                    i_notations.insert(name, new_related)
+                } else {
+                    i_entities.insert(name, new_related)
+                };
+            }
+            Extension::Document {
+                i_document_type, ..
+            } => *i_document_type = Some(new_related),
+            _ => {}
+        }
+    }
+
+    for child_node in children {
+        let new_child = clone_subtree(&child_node, true, new_document, importing);
+        new_child.borrow_mut().i_parent_node = Some(new_node.clone().downgrade());
+        new_node.borrow_mut().i_child_nodes.push(new_child.clone());
+        child_attached(&new_node, &new_child);
+    }
+    new_node
+}
+
+pub(crate) fn tag_name_match(test: &str, against: &str) -> bool {
     (test == against) || test == WILD_CARD || against == WILD_CARD
 }
 
-fn namespaced_name_match(
+pub(crate) fn namespaced_name_match(
     test_ns: Option<&str>,
     test_local: &str,
     against_ns: &str,
@@ -1264,7 +2120,7 @@ fn check_same_document(self_node: &RefNode, new_child: &RefNode) -> Result<()> {
 // * Entity -- Element, ProcessingInstruction, Comment, Text, CDATASection, EntityReference
 // * Notation -- no children
 //
-fn is_child_allowed(parent: &RefNode, child: &RefNode) -> bool {
+pub(crate) fn is_child_allowed(parent: &RefNode, child: &RefNode) -> bool {
     let self_node_type = { &parent.borrow().i_node_type };
     let child_node_type = { &child.borrow().i_node_type };
     match self_node_type {
@@ -1321,14 +2177,38 @@ fn is_child_allowed(parent: &RefNode, child: &RefNode) -> bool {
 }
 
 pub(crate) fn create_document_with_options(
+    implementation: Shared<Implementation>,
     namespace_uri: Option<&str>,
     qualified_name: Option<&str>,
     doc_type: Option<RefNode>,
     options: ProcessingOptions,
 ) -> Result<RefNode> {
-    let node_impl = NodeImpl::new_document(doc_type, options);
+    //
+    // A document type may only be used by one document.
+    //
+    if let Some(doc_type) = &doc_type {
+        if !is_document_type(doc_type) {
+            warn!("{}", INVALID_NODE_TYPE);
+            return Err(Error::HierarchyRequest);
+        }
+        if doc_type.owner_document().is_some() {
+            warn!("{}", WRONG_DOCUMENT);
+            return Err(Error::WrongDocument);
+        }
+    }
+    let node_impl = NodeImpl::new_document(implementation, doc_type.clone(), options);
     let mut document_node = RefNode::new(node_impl);
 
+    //
+    // The document type is not one of the document's children, but is below it.
+    //
+    if let Some(doc_type) = doc_type {
+        let mut mut_doc_type = doc_type.borrow_mut();
+        mut_doc_type.i_owner_document = Some(document_node.clone().downgrade());
+        mut_doc_type.i_parent_node = Some(document_node.clone().downgrade());
+        mut_doc_type.i_depth = 1;
+    }
+
     //
     // If specified, create a new root element
     //
@@ -1354,3 +2234,56 @@ pub(crate) fn create_document_with_options(
 
     Ok(document_node)
 }
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level2::ext::dom_impl::get_implementation_ext;
+
+    fn reported_diagnostics(document_node: &RefNode) -> Vec<&'static str> {
+        if let Extension::Document {
+            i_reported_diagnostics,
+            ..
+        } = &document_node.borrow().i_extension
+        {
+            let mut codes: Vec<&'static str> =
+                i_reported_diagnostics.borrow().iter().cloned().collect();
+            codes.sort();
+            codes
+        } else {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_accessor_warnings_once() {
+        for quiet in [false, true] {
+            let mut options = ProcessingOptions::new();
+            if quiet {
+                options.set_quiet_accessors();
+            }
+            let document_node = get_implementation_ext()
+                .create_document_with_options(None, Some("root"), None, options)
+                .unwrap();
+            let document = as_document(&document_node).unwrap();
+            let text_node = document.create_text_node("text");
+            let root_node = document.document_element().unwrap();
+            for _ in 0..3 {
+                assert!(text_node.get_attribute("a").is_none());
+                assert!(!root_node.has_attribute("1a"));
+            }
+            if quiet {
+                assert!(reported_diagnostics(&document_node).is_empty());
+            } else {
+                assert_eq!(
+                    reported_diagnostics(&document_node),
+                    vec![INVALID_NODE_TYPE.code(), INVALID_NAME.code()]
+                );
+            }
+        }
+    }
+}
@@ -1,5 +1,8 @@
-use crate::shared::error::Result;
+use crate::level2::ext::sync::Shared;
+use crate::level2::named_node_map::NamedAttributeMap;
+use crate::shared::error::{Error, Result};
 use crate::shared::name::Name;
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI, XML_NS_ATTRIBUTE, XML_NS_SEPARATOR};
 use crate::shared::text;
 use std::collections::HashMap;
 
@@ -14,6 +17,11 @@ pub trait Attribute: Node {
     ///
     /// On retrieval, the value of the attribute is returned as a string.
     ///
+    /// The value is computed from the attribute's children, `Text` and `EntityReference` nodes
+    /// which may be added with `append_child` and so on; an entity reference contributes the text
+    /// of its children, if any, or the value of the entity declared by the document type. A
+    /// reference to an undeclared entity is kept in the value.
+    ///
     /// # Specification
     ///
     /// Character and general entity references are replaced with their values. See also the method
@@ -263,6 +271,7 @@ pub trait CharacterData: Node {
     ///   replaced; (i.e., the effect is the same as a remove method call with the same range,
     ///   followed by an append method invocation).
     /// * `arg` of type `DOMString`: The `DOMString` with which the range must be replaced.
+    ///
     /// Exceptions
     ///
     /// INDEX_SIZE_ERR: Raised if the specified `offset` is negative or greater than the number
@@ -314,7 +323,8 @@ pub trait Document: Node {
     ///
     fn document_element(&self) -> Option<Self::NodeRef>;
     ///
-    /// The DOMImplementation object that handles this document.
+    /// The DOMImplementation object that handles this document; this is the implementation that
+    /// created the document, and so shares its defaults.
     ///
     /// Note: this function will panic if for some reason an implementation is not associated
     /// with the document instance.
@@ -323,7 +333,7 @@ pub trait Document: Node {
     ///
     /// A DOM application may use objects from multiple implementations.
     ///
-    fn implementation(&self) -> &dyn DOMImplementation<NodeRef = Self::NodeRef>;
+    fn implementation(&self) -> Shared<dyn DOMImplementation<NodeRef = Self::NodeRef>>;
     ///
     /// Creates an [`Attribute`](trait.Attribute.html) of the given name. Note that the `Attr`
     /// instance can then be set on an [`Element`](trait.Element.html) using the `setAttributeNode`
@@ -493,6 +503,18 @@ pub trait Document: Node {
     ///
     fn create_element(&self, tag_name: &str) -> Result<Self::NodeRef>;
     ///
+    /// Implementation defined extension: this is the same as `create_element` except that the
+    /// new element has space allocated for `children_hint` child nodes and `attributes_hint`
+    /// attributes, avoiding repeated reallocation while building very wide documents. The hints
+    /// do not limit the number of children or attributes the element may have.
+    ///
+    fn create_element_with_capacity(
+        &self,
+        tag_name: &str,
+        children_hint: usize,
+        attributes_hint: usize,
+    ) -> Result<Self::NodeRef>;
+    ///
     /// Creates an element of the given qualified name and namespace URI.
     ///
     /// # Specification
@@ -530,6 +552,39 @@ pub trait Document: Node {
     fn create_element_ns(&self, namespace_uri: &str, qualified_name: &str)
         -> Result<Self::NodeRef>;
     ///
+    /// Implementation defined extension: this is the same as `create_element_ns` except that
+    /// the qualified name is made from `prefix`, if any, and `local_name`, and the new element
+    /// also has the `xmlns:prefix`, or for no prefix the `xmlns`, attribute that declares the
+    /// binding to `namespace_uri`; so that the element is written with its namespace declared
+    /// wherever it is added. The `xml` prefix is never declared. The declaration is an ordinary
+    /// attribute, and may be removed if an ancestor declares the same binding.
+    ///
+    /// The default implementation uses `create_element_ns` and then `set_attribute_ns` to add
+    /// the declaration.
+    ///
+    fn create_element_ns_with_prefix(
+        &self,
+        namespace_uri: &str,
+        local_name: &str,
+        prefix: Option<&str>,
+    ) -> Result<Self::NodeRef>
+    where
+        Self::NodeRef: Element,
+    {
+        let (qualified_name, declaration) = match prefix {
+            None => (local_name.to_string(), XMLNS_NS_ATTRIBUTE.to_string()),
+            Some(prefix) => (
+                format!("{}{}{}", prefix, XML_NS_SEPARATOR, local_name),
+                format!("{}{}{}", XMLNS_NS_ATTRIBUTE, XML_NS_SEPARATOR, prefix),
+            ),
+        };
+        let mut element = self.create_element_ns(namespace_uri, &qualified_name)?;
+        if prefix != Some(XML_NS_ATTRIBUTE) {
+            element.set_attribute_ns(XMLNS_NS_URI, &declaration, namespace_uri)?;
+        }
+        Ok(element)
+    }
+    ///
     /// Creates a [`ProcessingInstruction`](trait.ProcessingInstruction.html) node given the
     /// specified name and data strings.
     ///
@@ -575,7 +630,11 @@ pub trait Document: Node {
     /// XML namespace will be treated as identifiers. If the
     /// [`ProcessingOptions::set_assume_ids`](struct.ProcessingOptions.html#method.set_assume_ids)
     /// method is used when constructing a document any attribute with the local name `id` will
-    /// be treated as identifiers.
+    /// be treated as identifiers. Other attributes may be declared as identifiers using the
+    /// [`IdAttributes::register_id_attribute`](ext/trait.IdAttributes.html#tymethod.register_id_attribute)
+    /// method. IDs are kept up to date as attributes are set, changed, or removed, and an element
+    /// removed from its parent, with its descendants, is no longer found by its ID until it is
+    /// inserted again.
     ///
     /// # Specification
     ///
@@ -638,6 +697,80 @@ pub trait Document: Node {
         namespace_uri: &str,
         local_name: &str,
     ) -> Vec<Self::NodeRef>;
+    ///
+    /// Imports a node from another document to this document. The returned node has no parent;
+    /// (`parentNode` is `null`). The source node is not altered or removed from the original
+    /// document; this method creates a new copy of the source node.
+    ///
+    /// **Note:** This implementation has no DTD information with which to create default
+    /// attributes, so attributes that were not specified on a source element are simply not
+    /// imported.
+    ///
+    /// # Specification
+    ///
+    /// For all nodes, importing a node creates a node object owned by the importing document, with
+    /// attribute values identical to the source node's `nodeName` and `nodeType`, plus the
+    /// attributes related to namespaces (`prefix`, `localName`, and `namespaceURI`). As in the
+    /// `cloneNode` operation on a `Node`, the source node is not altered.
+    ///
+    /// Additional information is copied as appropriate to the `nodeType`, attempting to mirror
+    /// the behavior expected if a fragment of XML or HTML source was copied from one document to
+    /// another, recognizing that the two documents may have different DTDs in the XML case. The
+    /// following list describes the specifics for each type of node.
+    ///
+    /// * `ATTRIBUTE_NODE`: The `ownerElement` attribute is set to `null` and the `specified` flag
+    ///   is set to `true` on the generated `Attr`. The descendants of the source `Attr` are
+    ///   recursively imported and the resulting nodes reassembled to form the corresponding
+    ///   subtree. Note that the `deep` parameter has no effect on `Attr` nodes; they always carry
+    ///   their children with them when imported.
+    /// * `DOCUMENT_FRAGMENT_NODE`: If the `deep` option was set to `true`, the descendants of the
+    ///   source element are recursively imported and the resulting nodes reassembled to form the
+    ///   corresponding subtree. Otherwise, this simply generates an empty `DocumentFragment`.
+    /// * `DOCUMENT_NODE`: `Document` nodes cannot be imported.
+    /// * `DOCUMENT_TYPE_NODE`: `DocumentType` nodes cannot be imported.
+    /// * `ELEMENT_NODE`: Specified attribute nodes of the source element are imported, and the
+    ///   generated `Attr` nodes are attached to the generated `Element`. Default attributes are
+    ///   not copied, though if the document being imported into defines default attributes for
+    ///   this element name, those are assigned. If the `importNode` `deep` parameter was set to
+    ///   `true`, the descendants of the source element are recursively imported and the resulting
+    ///   nodes reassembled to form the corresponding subtree.
+    /// * `ENTITY_NODE`: `Entity` nodes can be imported, however in the current release of the DOM
+    ///   the `DocumentType` is readonly. Ability to add these imported nodes to a `DocumentType`
+    ///   will be considered for addition to a future release of the DOM. On import, the
+    ///   `publicId`, `systemId`, and `notationName` attributes are copied. If a `deep` import is
+    ///   requested, the descendants of the the source `Entity` are recursively imported and the
+    ///   resulting nodes reassembled to form the corresponding subtree.
+    /// * `ENTITY_REFERENCE_NODE`: Only the `EntityReference` itself is copied, even if a `deep`
+    ///   import is requested, since the source and destination documents might have defined the
+    ///   entity differently. If the document being imported into provides a definition for this
+    ///   entity name, its value is assigned.
+    /// * `NOTATION_NODE`: `Notation` nodes can be imported, however in the current release of the
+    ///   DOM the `DocumentType` is readonly. Ability to add these imported nodes to a
+    ///   `DocumentType` will be considered for addition to a future release of the DOM. On import,
+    ///   the `publicId` and `systemId` attributes are copied. Note that the `deep` parameter has
+    ///   no effect on `Notation` nodes since they never have any children.
+    /// * `PROCESSING_INSTRUCTION_NODE`: The imported node copies its `target` and `data` values
+    ///   from those of the source node.
+    /// * `TEXT_NODE`, `CDATA_SECTION_NODE`, `COMMENT_NODE`: These three types of nodes inheriting
+    ///   from `CharacterData` copy their `data` and `length` attributes from those of the source
+    ///   node.
+    ///
+    /// **Parameters**
+    ///
+    /// * `importedNode` of type `Node`: The node to import.
+    /// * `deep` of type `boolean`: If `true`, recursively import the subtree under the specified
+    ///   node; if `false`, import only the node itself, as explained above. This has no effect on
+    ///   `Attr`, `EntityReference`, and `Notation` nodes.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: The imported node that belongs to this `Document`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised if the type of node being imported is not supported.
+    ///
+    fn import_node(&mut self, imported_node: Self::NodeRef, deep: bool) -> Result<Self::NodeRef>;
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -910,7 +1043,7 @@ pub trait Element: Node {
     /// **Return Value**
     ///
     /// * `DOMString`: The `Attr` value as a string, or the empty string if that attribute does not
-    /// have a specified or default value.
+    ///   have a specified or default value.
     ///
     fn get_attribute(&self, name: &str) -> Option<String>;
     ///
@@ -931,7 +1064,7 @@ pub trait Element: Node {
     /// **Parameters**
     ///
     /// * `name` of type `DOMString`: The name of the attribute to create or alter.
-    /// `value` of type `DOMString`: Value to set in string form.
+    /// * `value` of type `DOMString`: Value to set in string form.
     ///
     /// **Exceptions**
     ///
@@ -972,7 +1105,7 @@ pub trait Element: Node {
     /// **Return Value**
     ///
     /// * `Attr`: The `Attr` node with the specified name (`nodeName`) or null if there is no such
-    /// attribute.
+    ///   attribute.
     ///
     fn get_attribute_node(&self, name: &str) -> Option<Self::NodeRef>;
     ///
@@ -1092,7 +1225,7 @@ pub trait Element: Node {
     ///   prefix and the `namespaceURI` is null, if the `qualifiedName` has a prefix that is "xml"
     ///   and the `namespaceURI` is different from '<http://www.w3.org/XML/1998/namespace>', or if
     ///   the `qualifiedName` is "xmlns" and the `namespaceURI` is different from
-    /// `<http://www.w3.org/2000/xmlns/>`.
+    ///   `<http://www.w3.org/2000/xmlns/>`.
     ///
     fn set_attribute_ns(
         &mut self,
@@ -1221,7 +1354,7 @@ pub trait Element: Node {
     /// **Parameters**
     ///
     /// * `namespaceURI` of type `DOMString`: The namespace URI of the attribute to look for.
-    /// `localName` of type `DOMString`: The local name of the attribute to look for.
+    /// * `localName` of type `DOMString`: The local name of the attribute to look for.
     ///
     /// **Return Value**
     ///
@@ -1229,6 +1362,13 @@ pub trait Element: Node {
     ///   specified or has a default value on this element, `false` otherwise.
     ///
     fn has_attribute_ns(&self, namespace_uri: &str, local_name: &str) -> bool;
+    ///
+    /// Returns the attributes of this element in the namespace `namespace_uri`, ordered by local
+    /// name; for example all the `xlink:*` attributes.
+    ///
+    /// **Note:** this is an extension and not part of the DOM specification.
+    ///
+    fn attributes_in_ns(&self, namespace_uri: &str) -> Vec<Self::NodeRef>;
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -1320,6 +1460,211 @@ pub trait EntityReference: Node {}
 
 // ------------------------------------------------------------------------------------------------
 
+///
+/// This corresponds to the DOM `NamedNodeMap` interface.
+///
+/// # Specification
+///
+/// Objects implementing the `NamedNodeMap` interface are used to represent collections of nodes
+/// that can be accessed by name. Note that `NamedNodeMap` does not inherit from `NodeList`;
+/// `NamedNodeMaps` are not maintained in any particular order. Objects contained in an object
+/// implementing `NamedNodeMap` may also be accessed by an ordinal index, but this is simply to
+/// allow convenient enumeration of the contents of a `NamedNodeMap`, and does not imply that the
+/// DOM specifies an order to these Nodes.
+///
+/// **Note:** this is implemented by the [`NamedAttributeMap`](struct.NamedAttributeMap.html)
+/// returned by [`attributes`](trait.Node.html#tymethod.attributes), which keeps attributes in the
+/// order in which they were added, and for the readonly `HashMap<Name, RefNode>` returned by
+/// [`entities`](trait.DocumentType.html#tymethod.entities) and
+/// [`notations`](trait.DocumentType.html#tymethod.notations), so that code ported from other DOM
+/// implementations can use the familiar accessors.
+///
+pub trait NamedNodeMap {
+    ///
+    /// The opaque reference type that wraps the implementation of a node within the DOM.
+    ///
+    type NodeRef;
+    ///
+    /// The number of nodes in this map. The range of valid child node indices is `0` to
+    /// `length-1` inclusive.
+    ///
+    fn length(&self) -> usize;
+    ///
+    /// Returns the `index`th item in the map. If `index` is greater than or equal to the number
+    /// of nodes in this map, this returns `None`.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `index` of type `unsigned long`: Index into this map.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: The node at the `index`th position in the map, or `null` if that is not a valid
+    ///   index.
+    ///
+    fn item(&self, index: usize) -> Option<Self::NodeRef>;
+    ///
+    /// Retrieves a node specified by name.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `name` of type `DOMString`: The `nodeName` of a node to retrieve.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: A `Node` (of any type) with the specified `nodeName`, or `null` if it does not
+    ///   identify any node in this map.
+    ///
+    fn get_named_item(&self, name: &str) -> Option<Self::NodeRef>;
+    ///
+    /// Retrieves a node specified by local name and namespace URI.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `namespaceURI` of type `DOMString`: The namespace URI of the node to retrieve.
+    /// * `localName` of type `DOMString`: The local name of the node to retrieve.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: A `Node` (of any type) with the specified local name and namespace URI, or `null`
+    ///   if they do not identify any node in this map.
+    ///
+    fn get_named_item_ns(&self, namespace_uri: &str, local_name: &str) -> Option<Self::NodeRef>;
+    ///
+    /// Adds a node using its `nodeName` attribute. If a node with that name is already present in
+    /// this map, it is replaced by the new one.
+    ///
+    /// The default implementation is for a map that is readonly, and always returns
+    /// `NoModificationAllowed`.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `arg` of type `Node`: A node to store in this map. The node will later be accessible
+    ///   using the value of its `nodeName` attribute.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: If the new `Node` replaces an existing node the replaced `Node` is returned,
+    ///   otherwise `null` is returned.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `arg` was created from a different document than the one
+    ///   that created this map.
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this map is readonly.
+    /// * `INUSE_ATTRIBUTE_ERR`: Raised if `arg` is an `Attr` that is already an attribute of
+    ///   another `Element` object. The DOM user must explicitly clone `Attr` nodes to re-use them
+    ///   in other elements.
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if an attempt is made to add a node doesn't belong in
+    ///   this `NamedNodeMap`.
+    ///
+    fn set_named_item(&mut self, _arg: Self::NodeRef) -> Result<Option<Self::NodeRef>> {
+        Err(Error::NoModificationAllowed)
+    }
+    ///
+    /// Removes a node specified by name.
+    ///
+    /// The default implementation is for a map that is readonly, and always returns
+    /// `NoModificationAllowed`.
+    ///
+    /// # Specification
+    ///
+    /// When this map contains the attributes attached to an element, if the removed attribute is
+    /// known to have a default value, an attribute immediately appears containing the default
+    /// value as well as the corresponding namespace URI, local name, and prefix when applicable.
+    ///
+    /// **Parameters**
+    ///
+    /// * `name` of type `DOMString`: The `nodeName` of the node to remove.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: The node removed from this map if a node with such a name exists.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_FOUND_ERR`: Raised if there is no node named `name` in this map.
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this map is readonly.
+    ///
+    fn remove_named_item(&mut self, _name: &str) -> Result<Self::NodeRef> {
+        Err(Error::NoModificationAllowed)
+    }
+    ///
+    /// Adds a node using its `namespaceURI` and `localName`. If a node with that namespace URI
+    /// and that local name is already present in this map, it is replaced by the new one.
+    ///
+    /// The default implementation is for a map that is readonly, and always returns
+    /// `NoModificationAllowed`.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `arg` of type `Node`: A node to store in this map. The node will later be accessible
+    ///   using the value of its `namespaceURI` and `localName` attributes.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: If the new `Node` replaces an existing node the replaced `Node` is returned,
+    ///   otherwise `null` is returned.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `arg` was created from a different document than the one
+    ///   that created this map.
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this map is readonly.
+    /// * `INUSE_ATTRIBUTE_ERR`: Raised if `arg` is an `Attr` that is already an attribute of
+    ///   another `Element` object. The DOM user must explicitly clone `Attr` nodes to re-use them
+    ///   in other elements.
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if an attempt is made to add a node doesn't belong in
+    ///   this `NamedNodeMap`.
+    ///
+    fn set_named_item_ns(&mut self, _arg: Self::NodeRef) -> Result<Option<Self::NodeRef>> {
+        Err(Error::NoModificationAllowed)
+    }
+    ///
+    /// Removes a node specified by local name and namespace URI.
+    ///
+    /// The default implementation is for a map that is readonly, and always returns
+    /// `NoModificationAllowed`.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `namespaceURI` of type `DOMString`: The namespace URI of the node to remove.
+    /// * `localName` of type `DOMString`: The local name of the node to remove.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: The node removed from this map if a node with such a local name and namespace
+    ///   URI exists.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_FOUND_ERR`: Raised if there is no node with the specified `namespaceURI` and
+    ///   `localName` in this map.
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this map is readonly.
+    ///
+    fn remove_named_item_ns(
+        &mut self,
+        _namespace_uri: &str,
+        _local_name: &str,
+    ) -> Result<Self::NodeRef> {
+        Err(Error::NoModificationAllowed)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
 ///
 /// This corresponds to the DOM `Node` interface.
 ///
@@ -1348,7 +1693,7 @@ pub trait EntityReference: Node {}
 /// | `Document`              | `"#document"`             | `None`                              | `None`       |
 /// | `DocumentFragment`      | `"#document-fragment"`    | `None`                              | `None`       |
 /// | `DocumentType`          | document type name        | `None`                              | `None`       |
-/// | `Element`               | tag name                  | `None`                              | `HashMap`    |
+/// | `Element`               | tag name                  | `None`                              | `NamedNodeMap` |
 /// | `Entity`                | entity name               | `None`                              | `None`       |
 /// | `EntityReference`       | name of entity referenced | `None`                              | `None`       |
 /// | `Notation`              | notation name             | `None`                              | `None`       |
@@ -1377,7 +1722,7 @@ pub trait Node {
     /// **Exceptions on retrieval**
     ///
     /// * `DOMSTRING_SIZE_ERR`: Raised when it would return more characters than fit in a DOMString
-    /// variable on the implementation platform.
+    ///   variable on the implementation platform.
     ///
     fn node_value(&self) -> Option<String>;
     ///
@@ -1420,10 +1765,11 @@ pub trait Node {
     ///
     fn next_sibling(&self) -> Option<Self::NodeRef>;
     ///
-    /// A `HashMap` containing the attributes of this node (if it is an `Element`) or
-    /// `None` otherwise.
+    /// A [`NamedAttributeMap`](struct.NamedAttributeMap.html) containing the attributes of this
+    /// node, in the order in which they were added, if it is an `Element`; or an empty map
+    /// otherwise.
     ///
-    fn attributes(&self) -> HashMap<Name, Self::NodeRef>;
+    fn attributes(&self) -> NamedAttributeMap;
     ///
     /// The `Document` object associated with this node. This is also the `Document`
     /// object used to create new nodes. When this node is a `Document` or a `DocumentType` which is
@@ -1544,6 +1890,24 @@ pub trait Node {
     ///
     fn append_child(&mut self, new_child: Self::NodeRef) -> Result<Self::NodeRef>;
     ///
+    /// Adds all of the nodes in `new_children`, in order, to the end of the list of children of
+    /// this node. This is not part of the DOM specification; it has the same effect as calling
+    /// [`append_child`](#tymethod.append_child) for each node, except that every node is
+    /// validated before any are appended, so that either all nodes are appended or, on error,
+    /// none are.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if this node is of a type that does not allow children
+    ///   of the type of any node, if any node is this node or one of its ancestors, or if the same
+    ///   node appears more than once.
+    /// * `WRONG_DOCUMENT_ERR`: Raised if any node was created from a different document than
+    ///   the one that created this node.
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this node, or the current parent of any node,
+    ///   is readonly.
+    ///
+    fn append_children(&mut self, new_children: Vec<Self::NodeRef>) -> Result<()>;
+    ///
     /// Returns whether this node has any children.
     ///
     /// # Specification
@@ -1699,6 +2063,53 @@ pub trait Node {
 
 // ------------------------------------------------------------------------------------------------
 
+///
+/// This corresponds to the DOM `NodeList` interface.
+///
+/// # Specification
+///
+/// The `NodeList` interface provides the abstraction of an ordered collection of nodes, without
+/// defining or constraining how this collection is implemented. `NodeList` objects in the DOM are
+/// live.
+///
+/// The items in the `NodeList` are accessible via an integral index, starting from 0.
+///
+/// **Note:** this is implemented for the `Vec<RefNode>` returned by methods such as
+/// [`child_nodes`](trait.Node.html#tymethod.child_nodes) so that code ported from other DOM
+/// implementations can use the familiar accessors; these are snapshots, and are not live. The
+/// [`LiveNodeList`](ext/node_list/struct.LiveNodeList.html) returned by the
+/// [`LiveCollections`](ext/trait.LiveCollections.html) methods is live.
+///
+pub trait NodeList {
+    ///
+    /// The opaque reference type that wraps the implementation of a node within the DOM.
+    ///
+    type NodeRef;
+    ///
+    /// The number of nodes in the list. The range of valid child node indices is `0` to
+    /// `length-1` inclusive.
+    ///
+    fn length(&self) -> usize;
+    ///
+    /// Returns the `index`th item in the collection. If `index` is greater than or equal to the
+    /// number of nodes in the list, this returns `None`.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `index` of type `unsigned long`: Index into the collection.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: The node at the `index`th position in the `NodeList`, or `null` if that is not a
+    ///   valid index.
+    ///
+    fn item(&self, index: usize) -> Option<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
 ///
 /// This corresponds to the DOM `Notation` interface.
 ///
@@ -1848,6 +2259,7 @@ pub trait Text: CharacterData {
 ///
 /// This corresponds to the DOM `NodeType` set of constants.
 ///
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[repr(u16)]
 pub enum NodeType {
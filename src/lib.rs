@@ -16,8 +16,8 @@ module.
 
 ## Features
 
-Currently only one feature, `quick_parser`, is provided which provides a new module `parser` with the
-single public function. This feature is enabled by default.
+The feature `quick_parser` provides a new module `parser` with the
+following public function. This feature is enabled by default.
 
 ``` rust,ignore
 pub fn read_xml(xml: AsRef<str>) -> Result<RefNode>;
@@ -25,6 +25,95 @@ pub fn read_xml(xml: AsRef<str>) -> Result<RefNode>;
 
 This will parse the document and return a new `RefNode` that corresponds to the `Document` trait.
 
+The `http` feature, which implies `quick_parser`, adds a function to retrieve and parse a document
+from a URL using the blocking client from [reqwest](https://crates.io/crates/reqwest).
+
+``` rust,ignore
+pub fn read_xml_url(url: AsRef<str>) -> Result<RefNode>;
+```
+
+The `cycle_detection` feature adds a module `level2::ext::cycles` that reports any strong reference
+cycles in a DOM tree, intended for use in tests and soak runs.
+
+``` rust,ignore
+pub fn find_reference_cycles(node: &RefNode) -> Vec<ReferenceCycle>;
+```
+
+The `graph` feature adds a module `level2::ext::graph` that exports a DOM tree as a
+[petgraph](https://crates.io/crates/petgraph) graph, or as Graphviz DOT text, for analysis and
+visualization with existing graph tooling.
+
+``` rust,ignore
+pub fn to_graph(node: &RefNode) -> DomGraph;
+pub fn to_dot(node: &RefNode) -> String;
+```
+
+The `derive` feature adds the derive macros `ToXmlDom` and `FromXmlDom` to the module
+`level2::ext::mapping`, provided by the companion crate `xml_dom_derive`, that map the fields of a
+struct to the attributes, text, and child elements of an element.
+
+``` rust,ignore
+#[derive(ToXmlDom, FromXmlDom)]
+struct Note {
+    #[xml(attribute)]
+    id: u32,
+    body: String,
+}
+```
+
+The `chrono` feature adds implementations of `XmlValue`, in the module `level2::ext::xsd`, for the
+date and time types of [chrono](https://crates.io/crates/chrono), using the lexical forms of
+`xs:dateTime`, `xs:date`, and `xs:time`.
+
+``` rust,ignore
+element.set_attribute_value("created", &Utc::now())?;
+```
+
+The `serde` feature implements `Serialize` and `Deserialize`, in the module
+`level2::ext::serialization`, for `RefNode` trees, so that documents may be cached or sent between
+processes in any serde format; deserialization checks names and the node hierarchy.
+
+``` rust,ignore
+let bytes = bincode::serialize(&document_node)?;
+let copy: RefNode = bincode::deserialize(&bytes)?;
+```
+
+The `serde_json` feature adds a module `level2::ext::json`, with a function that converts a node
+directly to a `serde_json::Value`, attributes and child elements as members, for including an XML
+payload in a JSON log entry or API response.
+
+``` rust,ignore
+let value = to_json_value(&element, JsonMappingOptions::new());
+```
+
+The `selectors` feature adds a module `level2::ext::selectors`, and the trait `QuerySelector`,
+that find elements with a subset of CSS selectors; type, `#id`, `.class`, and attribute
+selectors, with the descendant and child combinators.
+
+``` rust,ignore
+let items = document_node.query_selector_all("list > item.new[lang|=en]")?;
+```
+
+The `internals` feature adds a module `level2::ext::raw` with a read-only view of the storage of
+each node, its reference counts, weak references, and type-specific data, for profilers,
+debuggers, and migration tools.
+
+``` rust,ignore
+pub fn inspect(node: &RefNode) -> RawNode;
+pub fn inspect_tree(node: &RefNode) -> Vec<RawNode>;
+```
+
+The `threadsafe` feature builds `RefNode` on `Arc<RwLock<…>>` rather than `Rc<RefCell<…>>`, so
+that a document parsed on one thread may be read on others; the module `level2::ext::sync` names
+it `ArcNode`, and all of the DOM traits, and extensions, are implemented for it. Values shared
+by the nodes of a tree, such as a `TypeProvider`, are held by `sync::Shared`, an `Arc` with the
+feature and an `Rc` without it.
+
+``` rust,ignore
+let shared: ArcNode = document_node.clone();
+thread::spawn(move || as_document(&shared).unwrap().get_elements_by_tag_name("item").len());
+```
+
 # Example
 
 ```rust
@@ -101,7 +190,8 @@ which in turn are references an opaque `NodeImpl struct. `RefNode` implements al
 specified, and extension, interfaces.
 
 * The exception type `DOMException` and associated constants are represented by the enumeration
-  `Error`.
+  `Error`. Both this and the parser's error type convert into the categorized
+  [`XmlError`](error/enum.XmlError.html) for clients that want a single error type.
 * IDL Interface attributes are represented by functions;
   * readonly attributes simply have an `attribute_name` getter,
   * writeable attributes also have a `set_attribute_name` setter,
@@ -125,7 +215,8 @@ specified, and extension, interfaces.
 
 The DOM implementation makes use of the  [`log`](https://crates.io/crates/log) crate, although only
 the `warn!` and `error!` macros are used to provide more information than the set of error
-conditions defined by the DOM.
+conditions defined by the DOM. Each error, and the common logged messages, has a stable code
+listed in the [`diagnostics`](diagnostics/index.html) catalog.
 
 */
 
@@ -153,6 +244,10 @@ pub mod parser;
 
 pub mod level2;
 
+pub mod error;
+
+pub mod diagnostics;
+
 // ------------------------------------------------------------------------------------------------
 // Private Modules
 // ------------------------------------------------------------------------------------------------
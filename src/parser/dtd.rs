@@ -0,0 +1,407 @@
+/*!
+Provides the [`DtdCache`](struct.DtdCache.html), shared across parse calls, which caches external
+DTDs keyed by their system identifier.
+
+When a document's `<!DOCTYPE>` declaration has a system identifier, and a cache has been set
+in the [`ParseOptions`](../options/struct.ParseOptions.html), the external DTD is retrieved from
+the cache, or loaded using the cache's [`DocumentLoader`](../loader/trait.DocumentLoader.html)
+and then cached. The entity and notation declarations in the external DTD are added to the
+document's `DocumentType` node. Ingesting many documents that reference the same DTD therefore
+only fetches and parses it once.
+
+Only the `ENTITY` and `NOTATION` declarations in a DTD are processed; element, attribute list,
+and parameter entity declarations are ignored. All of the declarations in the internal subset of
+a parsed document are available from the
+[`DtdDeclarations`](../../level2/ext/trait.DtdDeclarations.html) trait.
+
+# Example
+
+```rust
+use std::rc::Rc;
+use xml_dom::level2::*;
+use xml_dom::level2::convert::{as_document, as_document_type};
+use xml_dom::parser::dtd::{Dtd, DtdCache};
+use xml_dom::parser::loader::DocumentLoader;
+use xml_dom::parser::{read_xml_with, ParseOptions, Result};
+
+#[derive(Debug)]
+struct NoLoader;
+
+impl DocumentLoader for NoLoader {
+    fn load(&self, url: &str) -> Result<String> {
+        panic!("unexpected load of {}", url)
+    }
+}
+
+// Pre-populate the cache, acting as a catalog of local copies.
+let cache = Rc::new(DtdCache::new(NoLoader));
+cache.insert("note.dtd", Dtd::parse(r#"<!ENTITY writer "Donald Duck.">"#));
+
+let mut options = ParseOptions::new();
+options.set_dtd_cache(cache);
+
+let (document_node, _) = read_xml_with(
+    r#"<!DOCTYPE note SYSTEM "note.dtd"><note/>"#,
+    options,
+).unwrap();
+let document = as_document(&document_node).unwrap();
+let doc_type_node = document.doc_type().unwrap();
+let doc_type = as_document_type(&doc_type_node).unwrap();
+assert_eq!(doc_type.entities().len(), 1);
+```
+
+*/
+
+pub use crate::level2::ext::dtd::{EntityDecl, NotationDecl};
+use crate::level2::node_impl::{Extension, NodeImpl, RefNode};
+use crate::parser::loader::DocumentLoader;
+use crate::parser::{Error, Result};
+use crate::shared::name::Name;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The declarations parsed from a DTD.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dtd {
+    entities: Vec<EntityDecl>,
+    notations: Vec<NotationDecl>,
+}
+
+///
+/// A cache of parsed external DTDs keyed by system identifier. The cache is intended to be
+/// wrapped in an `Rc` and shared by the `ParseOptions` for many parse calls.
+///
+#[derive(Debug)]
+pub struct DtdCache {
+    loader: Box<dyn DocumentLoader>,
+    entries: RefCell<HashMap<String, Rc<Dtd>>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The parts of a `<!DOCTYPE>` declaration.
+///
+/// ```ebnf
+/// doctypedecl ::= '<!DOCTYPE' S Name (S ExternalID)? S? ('[' intSubset ']' S?)? '>'
+/// ```
+///
+#[derive(Debug)]
+pub(crate) struct DocTypeDecl {
+    pub(crate) name: String,
+    pub(crate) public_id: Option<String>,
+    pub(crate) system_id: Option<String>,
+    pub(crate) internal_subset: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Dtd {
+    ///
+    /// Parse the entity and notation declarations from the text of a DTD; any other
+    /// declarations, and any text that is not a recognized declaration, are ignored.
+    ///
+    pub fn parse(text: &str) -> Self {
+        static COMMENT: OnceLock<regex::Regex> = OnceLock::new();
+        static ENTITY: OnceLock<regex::Regex> = OnceLock::new();
+        static NOTATION: OnceLock<regex::Regex> = OnceLock::new();
+        let comment = COMMENT.get_or_init(|| regex::Regex::new(r"(?s)<!--.*?-->").unwrap());
+        let entity = ENTITY.get_or_init(|| {
+            regex::Regex::new(
+                r#"<!ENTITY\s+([^\s%]+)\s+(?:"([^"]*)"|'([^']*)'|SYSTEM\s+("[^"]*"|'[^']*')|PUBLIC\s+("[^"]*"|'[^']*')\s+("[^"]*"|'[^']*'))(?:\s+NDATA\s+([^\s>]+))?\s*>"#,
+            )
+            .unwrap()
+        });
+        let notation = NOTATION.get_or_init(|| {
+            regex::Regex::new(
+                r#"<!NOTATION\s+([^\s>]+)\s+(?:SYSTEM\s+("[^"]*"|'[^']*')|PUBLIC\s+("[^"]*"|'[^']*')(?:\s+("[^"]*"|'[^']*'))?)\s*>"#,
+            )
+            .unwrap()
+        });
+
+        let text = comment.replace_all(text, "");
+        let group = |captures: &regex::Captures<'_>, index: usize| {
+            captures.get(index).map(|m| unquote(m.as_str()))
+        };
+        Self {
+            entities: entity
+                .captures_iter(&text)
+                .map(|captures| EntityDecl {
+                    name: captures[1].to_string(),
+                    value: captures
+                        .get(2)
+                        .or_else(|| captures.get(3))
+                        .map(|m| m.as_str().to_string()),
+                    public_id: group(&captures, 5),
+                    system_id: group(&captures, 4).or_else(|| group(&captures, 6)),
+                    notation_name: captures.get(7).map(|m| m.as_str().to_string()),
+                })
+                .collect(),
+            notations: notation
+                .captures_iter(&text)
+                .map(|captures| NotationDecl {
+                    name: captures[1].to_string(),
+                    public_id: group(&captures, 3),
+                    system_id: group(&captures, 2).or_else(|| group(&captures, 4)),
+                })
+                .collect(),
+        }
+    }
+    ///
+    /// The general entity declarations, in the order they were declared.
+    ///
+    pub fn entities(&self) -> &[EntityDecl] {
+        &self.entities
+    }
+    ///
+    /// The notation declarations, in the order they were declared.
+    ///
+    pub fn notations(&self) -> &[NotationDecl] {
+        &self.notations
+    }
+
+    ///
+    /// Add `Entity` and `Notation` nodes for these declarations to the document type; as in XML
+    /// the first declaration of a name is binding, existing nodes are not replaced. External
+    /// entities are not added if `discard_external_entities` is `true`.
+    ///
+    pub(crate) fn declare(
+        &self,
+        document: &RefNode,
+        doc_type: &RefNode,
+        discard_external_entities: bool,
+    ) -> Result<()> {
+        let owner_document = Some(document.clone().downgrade());
+        let mut new_entities: Vec<(Name, RefNode)> = Vec::new();
+        for decl in self
+            .entities
+            .iter()
+            .filter(|decl| !discard_external_entities || decl.value.is_some())
+        {
+            let name = Name::from_str(&decl.name)?;
+            let mut node_impl = match &decl.value {
+                Some(value) => {
+                    NodeImpl::new_internal_entity(owner_document.clone(), name.clone(), value)
+                }
+                None => NodeImpl::new_entity(
+                    owner_document.clone(),
+                    name.clone(),
+                    decl.public_id.as_deref(),
+                    decl.system_id.as_deref(),
+                ),
+            };
+            if let Extension::Entity {
+                i_notation_name, ..
+            } = &mut node_impl.i_extension
+            {
+                i_notation_name.clone_from(&decl.notation_name);
+            }
+            new_entities.push((name, RefNode::new(node_impl)));
+        }
+        let mut new_notations: Vec<(Name, RefNode)> = Vec::new();
+        for decl in &self.notations {
+            let name = Name::from_str(&decl.name)?;
+            let node_impl = NodeImpl::new_notation(
+                owner_document.clone(),
+                name.clone(),
+                decl.public_id.as_deref(),
+                decl.system_id.as_deref(),
+            );
+            new_notations.push((name, RefNode::new(node_impl)));
+        }
+
+        let mut mut_doc_type = doc_type.borrow_mut();
+        if let Extension::DocumentType {
+            i_entities,
+            i_notations,
+            ..
+        } = &mut mut_doc_type.i_extension
+        {
+            for (name, node) in new_entities {
+                let _safe_to_ignore = i_entities.entry(name).or_insert(node);
+            }
+            for (name, node) in new_notations {
+                let _safe_to_ignore = i_notations.entry(name).or_insert(node);
+            }
+            Ok(())
+        } else {
+            Err(Error::Malformed)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DtdCache {
+    ///
+    /// Construct a new, empty, cache that will use `loader` to retrieve DTDs that are not
+    /// already cached.
+    ///
+    pub fn new(loader: impl DocumentLoader + 'static) -> Self {
+        Self {
+            loader: Box::new(loader),
+            entries: Default::default(),
+        }
+    }
+    ///
+    /// Return the DTD for `system_id`, loading and parsing it only if it is not already cached.
+    ///
+    pub fn get(&self, system_id: &str) -> Result<Rc<Dtd>> {
+        if let Some(dtd) = self.entries.borrow().get(system_id) {
+            return Ok(dtd.clone());
+        }
+        let dtd = Rc::new(Dtd::parse(&self.loader.load(system_id)?));
+        let _safe_to_ignore = self
+            .entries
+            .borrow_mut()
+            .insert(system_id.to_string(), dtd.clone());
+        Ok(dtd)
+    }
+    ///
+    /// Add a DTD to the cache for `system_id`, replacing any existing entry. This allows a cache
+    /// to act as a catalog of local copies of well-known DTDs.
+    ///
+    pub fn insert(&self, system_id: &str, dtd: Dtd) {
+        let _safe_to_ignore = self
+            .entries
+            .borrow_mut()
+            .insert(system_id.to_string(), Rc::new(dtd));
+    }
+    ///
+    /// Returns `true` if a DTD is cached for `system_id`, else `false`.
+    ///
+    pub fn contains(&self, system_id: &str) -> bool {
+        self.entries.borrow().contains_key(system_id)
+    }
+    ///
+    /// The number of DTDs in the cache.
+    ///
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+    ///
+    /// Returns `true` if the cache is empty, else `false`.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+    ///
+    /// Remove all DTDs from the cache.
+    ///
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl FromStr for DocTypeDecl {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        static DOCTYPE: OnceLock<regex::Regex> = OnceLock::new();
+        let doctype = DOCTYPE.get_or_init(|| {
+            regex::Regex::new(
+                r#"(?s)^\s*([^\s\[>]+)(?:\s+(?:SYSTEM\s+("[^"]*"|'[^']*')|PUBLIC\s+("[^"]*"|'[^']*')\s+("[^"]*"|'[^']*')))?\s*(?:\[(.*)\])?\s*$"#,
+            )
+            .unwrap()
+        });
+        match doctype.captures(s) {
+            None => {
+                error!("Invalid document type declaration: {:?}", s);
+                Err(Error::Malformed)
+            }
+            Some(captures) => Ok(Self {
+                name: captures[1].to_string(),
+                public_id: captures.get(3).map(|m| unquote(m.as_str())),
+                system_id: captures
+                    .get(2)
+                    .or_else(|| captures.get(4))
+                    .map(|m| unquote(m.as_str())),
+                internal_subset: captures.get(5).map(|m| m.as_str().to_string()),
+            }),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn unquote(quoted: &str) -> String {
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dtd() {
+        let dtd = Dtd::parse(
+            r#"<!-- <!ENTITY hidden "no"> -->
+<!ELEMENT note (#PCDATA)>
+<!ENTITY % param "ignored">
+<!ENTITY writer "Donald Duck.">
+<!ENTITY copy 'Copyright'>
+<!ENTITY logo SYSTEM "logo.gif" NDATA gif>
+<!ENTITY chapter PUBLIC "-//Example//Chapter" "chapter.xml">
+<!NOTATION gif SYSTEM "image/gif">
+<!NOTATION jpeg PUBLIC "JPG 1.0">"#,
+        );
+        let names: Vec<&str> = dtd.entities().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["writer", "copy", "logo", "chapter"]);
+        assert_eq!(dtd.entities()[1].value, Some("Copyright".to_string()));
+        assert_eq!(dtd.entities()[2].system_id, Some("logo.gif".to_string()));
+        assert_eq!(dtd.entities()[2].notation_name, Some("gif".to_string()));
+        assert_eq!(
+            dtd.entities()[3].public_id,
+            Some("-//Example//Chapter".to_string())
+        );
+        assert_eq!(dtd.notations().len(), 2);
+        assert_eq!(dtd.notations()[1].public_id, Some("JPG 1.0".to_string()));
+        assert_eq!(dtd.notations()[1].system_id, None);
+    }
+
+    #[test]
+    fn test_parse_doctype_decl() {
+        let decl = DocTypeDecl::from_str(" html").unwrap();
+        assert_eq!(decl.name, "html");
+        assert!(decl.system_id.is_none());
+
+        let decl = DocTypeDecl::from_str(
+            r#"html PUBLIC "-//W3C//DTD XHTML 1.0 Strict//EN" 'xhtml1-strict.dtd'"#,
+        )
+        .unwrap();
+        assert_eq!(
+            decl.public_id,
+            Some("-//W3C//DTD XHTML 1.0 Strict//EN".to_string())
+        );
+        assert_eq!(decl.system_id, Some("xhtml1-strict.dtd".to_string()));
+
+        let decl = DocTypeDecl::from_str(r#"note SYSTEM "note.dtd" [ <!ENTITY a "b"> ]"#).unwrap();
+        assert_eq!(
+            decl.internal_subset,
+            Some(r#" <!ENTITY a "b"> "#.to_string())
+        );
+
+        assert!(DocTypeDecl::from_str("").is_err());
+    }
+}
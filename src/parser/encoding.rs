@@ -0,0 +1,242 @@
+/*!
+Detects the character encoding of a document from its byte order mark and XML declaration, as
+described in appendix F of the XML specification, and decodes it into a `String` for parsing.
+
+The encodings UTF-8, UTF-16 (big- and little-endian), ISO-8859-1, and US-ASCII are supported.
+*/
+
+use crate::level2::ext::writer::OutputEncoding;
+use crate::parser::{Error, Result};
+use std::borrow::Cow;
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Detected {
+    Utf8Bom,
+    Utf16Be { bom: bool },
+    Utf16Le { bom: bool },
+    AsciiCompatible,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Decode `bytes` according to the byte order mark, if any, and the encoding declared in the
+/// XML declaration, if any; without either the content must be UTF-8. The byte order mark is not
+/// included in the result.
+///
+pub(crate) fn decode(bytes: &[u8]) -> Result<Cow<'_, str>> {
+    match detect(bytes) {
+        Detected::Utf8Bom => {
+            let text = decode_utf8(&bytes[3..], UTF_8, 3)?;
+            match declared_encoding(&text) {
+                Some(declared)
+                    if OutputEncoding::from_name(declared) != Some(OutputEncoding::Utf8) =>
+                {
+                    encoding_mismatch(declared, 0)
+                }
+                _ => Ok(text),
+            }
+        }
+        Detected::Utf16Be { bom } => decode_utf16(bytes, bom, u16::from_be_bytes, UTF_16BE),
+        Detected::Utf16Le { bom } => decode_utf16(bytes, bom, u16::from_le_bytes, UTF_16LE),
+        Detected::AsciiCompatible => {
+            // the declaration itself only contains ASCII characters.
+            let declaration = match bytes.windows(2).position(|pair| pair == b"?>") {
+                Some(end) if bytes.starts_with(XML_DECL_START) => {
+                    String::from_utf8_lossy(&bytes[..end + 2]).to_string()
+                }
+                _ => String::new(),
+            };
+            match declared_encoding(&declaration) {
+                None => decode_utf8(bytes, UTF_8, 0),
+                Some(declared) => match OutputEncoding::from_name(declared) {
+                    Some(OutputEncoding::Utf8) => decode_utf8(bytes, declared, 0),
+                    Some(OutputEncoding::Iso8859_1) => {
+                        Ok(Cow::Owned(bytes.iter().map(|b| *b as char).collect()))
+                    }
+                    Some(OutputEncoding::UsAscii) => match bytes.iter().position(|b| !b.is_ascii())
+                    {
+                        Some(position) => encoding_mismatch(declared, position),
+                        None => decode_utf8(bytes, declared, 0),
+                    },
+                    Some(OutputEncoding::Utf16) => encoding_mismatch(declared, 0),
+                    None if is_utf16_name(declared) => encoding_mismatch(declared, 0),
+                    None => Error::UnsupportedEncoding(declared.to_string()).into(),
+                },
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const UTF_8: &str = "UTF-8";
+const UTF_16BE: &str = "UTF-16BE";
+const UTF_16LE: &str = "UTF-16LE";
+
+const XML_DECL_START: &[u8] = b"<?xml";
+
+fn detect(bytes: &[u8]) -> Detected {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => Detected::Utf8Bom,
+        [0xFE, 0xFF, ..] => Detected::Utf16Be { bom: true },
+        [0xFF, 0xFE, ..] => Detected::Utf16Le { bom: true },
+        [0x00, 0x3C, 0x00, 0x3F, ..] => Detected::Utf16Be { bom: false },
+        [0x3C, 0x00, 0x3F, 0x00, ..] => Detected::Utf16Le { bom: false },
+        _ => Detected::AsciiCompatible,
+    }
+}
+
+fn decode_utf8<'a>(bytes: &'a [u8], encoding: &str, offset: usize) -> Result<Cow<'a, str>> {
+    std::str::from_utf8(bytes)
+        .map(Cow::Borrowed)
+        .or_else(|e| encoding_mismatch(encoding, offset + e.valid_up_to()))
+}
+
+fn decode_utf16(
+    bytes: &[u8],
+    bom: bool,
+    from_bytes: fn([u8; 2]) -> u16,
+    encoding: &str,
+) -> Result<Cow<'static, str>> {
+    let offset = if bom { 2 } else { 0 };
+    let content = &bytes[offset..];
+    if !content.len().is_multiple_of(2) {
+        return encoding_mismatch(encoding, bytes.len() - 1);
+    }
+    let units = content.chunks(2).map(|pair| from_bytes([pair[0], pair[1]]));
+    let mut text = String::with_capacity(content.len() / 2);
+    for (index, c) in char::decode_utf16(units).enumerate() {
+        match c {
+            Ok(c) => text.push(c),
+            Err(_) => return encoding_mismatch(encoding, offset + index * 2),
+        }
+    }
+    match declared_encoding(&text) {
+        Some(declared) if !is_utf16_name(declared) => encoding_mismatch(declared, 0),
+        Some(declared)
+            if !declared.eq_ignore_ascii_case("UTF-16")
+                && !declared.eq_ignore_ascii_case(encoding) =>
+        {
+            encoding_mismatch(declared, 0)
+        }
+        // without a byte order mark the declaration is required.
+        None if !bom => encoding_mismatch(encoding, 0),
+        _ => Ok(Cow::Owned(text)),
+    }
+}
+
+///
+/// Returns the value of the `encoding` pseudo-attribute if `text` begins with an XML
+/// declaration that has one.
+///
+fn declared_encoding(text: &str) -> Option<&str> {
+    let declaration = text.strip_prefix("<?xml")?;
+    let declaration = &declaration[..declaration.find("?>")?];
+    let rest = &declaration[declaration.find("encoding")? + "encoding".len()..];
+    let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+    let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let rest = &rest[1..];
+    Some(&rest[..rest.find(quote)?])
+}
+
+fn is_utf16_name(name: &str) -> bool {
+    ["UTF-16", "UTF16", UTF_16BE, UTF_16LE]
+        .iter()
+        .any(|utf16| name.eq_ignore_ascii_case(utf16))
+}
+
+fn encoding_mismatch<T>(encoding: &str, position: usize) -> Result<T> {
+    Error::EncodingMismatch {
+        encoding: encoding.to_string(),
+        position: position as u64,
+    }
+    .into()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, to_bytes: fn(u16) -> [u8; 2], bom: &[u8]) -> Vec<u8> {
+        bom.iter()
+            .copied()
+            .chain(text.encode_utf16().flat_map(to_bytes))
+            .collect()
+    }
+
+    #[test]
+    fn test_declared_encoding() {
+        assert_eq!(
+            declared_encoding(r#"<?xml version="1.0" encoding = 'latin1'?><a/>"#),
+            Some("latin1")
+        );
+        assert_eq!(declared_encoding(r#"<?xml version="1.0"?><a/>"#), None);
+        assert_eq!(declared_encoding(r#"<a encoding="UTF-8"/>"#), None);
+    }
+
+    #[test]
+    fn test_decode_utf16() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-16"?><a>é€</a>"#;
+        let bytes = utf16(xml, u16::to_be_bytes, &[0xFE, 0xFF]);
+        assert_eq!(decode(&bytes).unwrap(), xml);
+        let bytes = utf16(xml, u16::to_le_bytes, &[0xFF, 0xFE]);
+        assert_eq!(decode(&bytes).unwrap(), xml);
+        let bytes = utf16(xml, u16::to_le_bytes, &[]);
+        assert_eq!(decode(&bytes).unwrap(), xml);
+
+        let bytes = utf16("<a>é€</a>", u16::to_be_bytes, &[0xFE, 0xFF]);
+        assert_eq!(decode(&bytes).unwrap(), "<a>é€</a>");
+
+        let xml = r#"<?xml version="1.0" encoding="ISO-8859-1"?><a/>"#;
+        let bytes = utf16(xml, u16::to_be_bytes, &[0xFE, 0xFF]);
+        assert!(matches!(
+            decode(&bytes),
+            Err(Error::EncodingMismatch { position: 0, .. })
+        ));
+        let xml = r#"<?xml version="1.0" encoding="UTF-16BE"?><a/>"#;
+        let bytes = utf16(xml, u16::to_le_bytes, &[0xFF, 0xFE]);
+        assert!(decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_decode_ascii_compatible() {
+        let bytes = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><a>\xE9</a>";
+        assert_eq!(
+            decode(bytes).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><a>é</a>"
+        );
+        let bytes = b"\xEF\xBB\xBF<a>\xC3\xA9</a>";
+        assert_eq!(decode(bytes).unwrap(), "<a>é</a>");
+
+        let bytes = b"<a>\xE9</a>";
+        assert!(matches!(
+            decode(bytes),
+            Err(Error::EncodingMismatch { position: 3, .. })
+        ));
+        let bytes = b"<?xml version=\"1.0\" encoding=\"US-ASCII\"?><a>\xC3\xA9</a>";
+        assert!(matches!(
+            decode(bytes),
+            Err(Error::EncodingMismatch { position: 44, .. })
+        ));
+        let bytes = b"<?xml version=\"1.0\" encoding=\"UTF-16\"?><a/>";
+        assert!(decode(bytes).is_err());
+        let bytes = b"<?xml version=\"1.0\" encoding=\"EBCDIC\"?><a/>";
+        assert!(matches!(
+            decode(bytes),
+            Err(Error::UnsupportedEncoding(name)) if name == "EBCDIC"
+        ));
+    }
+}
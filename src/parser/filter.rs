@@ -0,0 +1,168 @@
+/*!
+Provides the [`ParseFilter`](trait.ParseFilter.html) trait, used to drop, or rewrite, content as
+it is parsed and before any node is added to the document; this saves both the time and memory
+spent constructing nodes that the application will never use. Filters are added to the
+[`ParseOptions`](../options/struct.ParseOptions.html) with
+[`add_filter`](../options/struct.ParseOptions.html#method.add_filter), content is kept only if
+every filter keeps it.
+
+The filters [`DropComments`](struct.DropComments.html),
+[`DropProcessingInstructions`](struct.DropProcessingInstructions.html),
+[`DropElements`](struct.DropElements.html), and
+[`RewriteNamespaces`](struct.RewriteNamespaces.html) cover the common cases.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::parser::filter::{DropComments, DropElements};
+use xml_dom::parser::{read_xml_with, ParseOptions};
+
+let mut options = ParseOptions::new();
+options.add_filter(DropComments);
+options.add_filter(DropElements::new(&["script"]));
+
+let (document_node, _) = read_xml_with(
+    "<page><!-- note --><script>run()</script><p>text</p></page>",
+    options,
+)
+.unwrap();
+let document = as_document(&document_node).unwrap();
+assert_eq!(
+    document.document_element().unwrap().to_string(),
+    "<page><p>text</p></page>"
+);
+```
+
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Implemented by a filter applied to content as it is parsed. Each method is called before the
+/// corresponding node is created, and by default keeps the content unchanged.
+///
+pub trait ParseFilter: Debug {
+    ///
+    /// Return `false` to drop the element with the qualified name `name`, as written in the
+    /// input, together with its attributes and all of its content.
+    ///
+    fn keep_element(&self, _name: &str) -> bool {
+        true
+    }
+    ///
+    /// Return `false` to drop the comment with the text `data`.
+    ///
+    fn keep_comment(&self, _data: &str) -> bool {
+        true
+    }
+    ///
+    /// Return `false` to drop the processing instruction with `target` and `data`.
+    ///
+    fn keep_processing_instruction(&self, _target: &str, _data: Option<&str>) -> bool {
+        true
+    }
+    ///
+    /// Return a replacement for the namespace `namespace_uri`, declared by an `xmlns` attribute,
+    /// or `None` to keep it.
+    ///
+    fn rewrite_namespace(&self, _namespace_uri: &str) -> Option<String> {
+        None
+    }
+}
+
+///
+/// A filter that drops all comments.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DropComments;
+
+///
+/// A filter that drops all processing instructions.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DropProcessingInstructions;
+
+///
+/// A filter that drops all elements with one of a set of qualified names, and their content.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DropElements {
+    names: HashSet<String>,
+}
+
+///
+/// A filter that replaces the namespaces declared by `xmlns` attributes, for example to move
+/// documents from an old version of a vocabulary to a new one.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RewriteNamespaces {
+    rewrites: HashMap<String, String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl ParseFilter for DropComments {
+    fn keep_comment(&self, _data: &str) -> bool {
+        false
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ParseFilter for DropProcessingInstructions {
+    fn keep_processing_instruction(&self, _target: &str, _data: Option<&str>) -> bool {
+        false
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ParseFilter for DropElements {
+    fn keep_element(&self, name: &str) -> bool {
+        !self.names.contains(name)
+    }
+}
+
+impl DropElements {
+    ///
+    /// Construct a new filter dropping the elements with any of the qualified `names`.
+    ///
+    pub fn new(names: &[&str]) -> Self {
+        Self {
+            names: names.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ParseFilter for RewriteNamespaces {
+    fn rewrite_namespace(&self, namespace_uri: &str) -> Option<String> {
+        self.rewrites.get(namespace_uri).cloned()
+    }
+}
+
+impl RewriteNamespaces {
+    ///
+    /// Construct a new filter with no rewrites.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Replace the namespace `from` with `to` wherever it is declared.
+    ///
+    pub fn rewrite(mut self, from: &str, to: &str) -> Self {
+        let _safe_to_ignore = self.rewrites.insert(from.to_string(), to.to_string());
+        self
+    }
+}
@@ -0,0 +1,159 @@
+/*!
+Provides the [`DocumentLoader`](trait.DocumentLoader.html) trait used to fetch the text of a
+document, or external resource, identified by a URL.
+
+The loader is deliberately independent of the parser itself so that the same implementation may
+be shared by any feature that has to resolve a system identifier, such as external entities or
+XInclude, as well as by [`read_xml_url_with`](../fn.read_xml_url_with.html).
+
+When the `http` feature is enabled the [`HttpDocumentLoader`](struct.HttpDocumentLoader.html)
+provides a default implementation using the blocking client from
+[reqwest](https://crates.io/crates/reqwest); it follows redirects and decodes the response body
+using the charset specified in the response's `Content-Type` header.
+
+# Example
+
+```rust
+use xml_dom::parser::loader::DocumentLoader;
+use xml_dom::parser::{read_xml_url_with, Result};
+
+#[derive(Debug)]
+struct StaticLoader;
+
+impl DocumentLoader for StaticLoader {
+    fn load(&self, _url: &str) -> Result<String> {
+        Ok(String::from("<xml/>"))
+    }
+}
+
+let dom = read_xml_url_with("urn:example:document", &StaticLoader);
+assert!(dom.is_ok());
+```
+
+*/
+
+use crate::parser::Result;
+use std::fmt::Debug;
+
+#[cfg(feature = "http")]
+use crate::parser::Error;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Implemented by anything that is able to retrieve the text content of a resource, given its URL.
+///
+pub trait DocumentLoader: Debug {
+    ///
+    /// Load the resource identified by `url` and return its content, already decoded into a
+    /// `String`.
+    ///
+    fn load(&self, url: &str) -> Result<String>;
+}
+
+///
+/// The default loader, this uses the blocking HTTP client from reqwest to retrieve documents.
+///
+#[cfg(feature = "http")]
+#[derive(Debug)]
+pub struct HttpDocumentLoader {
+    client: reqwest::blocking::Client,
+}
+
+///
+/// The maximum number of redirects the default [`HttpDocumentLoader`](struct.HttpDocumentLoader.html)
+/// will follow before failing.
+///
+#[cfg(feature = "http")]
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(feature = "http")]
+impl DocumentLoader for HttpDocumentLoader {
+    fn load(&self, url: &str) -> Result<String> {
+        let response = self
+            .client
+            .get(url)
+            .header(reqwest::header::ACCEPT, ACCEPT_XML)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| load_error(url, e))?;
+        response.text().map_err(|e| load_error(url, e))
+    }
+}
+
+#[cfg(feature = "http")]
+impl HttpDocumentLoader {
+    ///
+    /// Construct a new loader that will follow at most
+    /// [`DEFAULT_MAX_REDIRECTS`](constant.DEFAULT_MAX_REDIRECTS.html) redirects. This returns
+    /// `Error::Loader` if the HTTP client cannot be constructed, for example if the TLS backend
+    /// cannot be initialized.
+    ///
+    pub fn try_new() -> Result<Self> {
+        Self::try_with_max_redirects(DEFAULT_MAX_REDIRECTS)
+    }
+
+    ///
+    /// Construct a new loader that will follow at most `max_redirects` redirects; see
+    /// [`try_new`](#method.try_new).
+    ///
+    pub fn try_with_max_redirects(max_redirects: usize) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder()
+            .user_agent(USER_AGENT)
+            .redirect(reqwest::redirect::Policy::limited(max_redirects))
+            .build()
+            .map_err(|e| {
+                error!("Could not construct HTTP client: {}", e);
+                Error::Loader(Box::new(e))
+            })?;
+        Ok(Self { client })
+    }
+
+    ///
+    /// Construct a new loader from an existing, configured, reqwest client.
+    ///
+    pub fn with_client(client: reqwest::blocking::Client) -> Self {
+        Self { client }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(feature = "http")]
+const ACCEPT_XML: &str = "application/xml, text/xml;q=0.9, */*;q=0.1";
+
+#[cfg(feature = "http")]
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+#[cfg(feature = "http")]
+fn load_error(url: &str, source: reqwest::Error) -> Error {
+    error!("Could not load document from '{}': {}", url, source);
+    Error::Load {
+        url: url.to_string(),
+        source: Box::new(source),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unreachable_url() {
+        let loader = HttpDocumentLoader::try_new().unwrap();
+        let result = loader.load("http://127.0.0.1:1/document.xml");
+        assert!(matches!(result, Err(Error::Load { .. })));
+    }
+}
@@ -0,0 +1,172 @@
+/*!
+Provides the [`ParserMetrics`](struct.ParserMetrics.html) structure returned by
+[`read_xml_with`](../fn.read_xml_with.html) when the `collect_metrics` option is set.
+
+The metrics are intended to allow clients to track the cost of parsing, for example against a
+regression budget, without having to wrap the parser externally.
+*/
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::{Duration, Instant};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The phases of parsing a document, as defined by the `document` production.
+///
+/// ```ebnf
+/// document          ::= prolog element Misc*
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParsePhase {
+    /// The XML declaration, and any comments or processing instructions before the document
+    /// element.
+    Prolog,
+    /// The document element and all of its content.
+    Body,
+    /// Any comments or processing instructions after the document element.
+    Epilog,
+}
+
+///
+/// Counts and timings collected while parsing a single document.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParserMetrics {
+    events_processed: u64,
+    nodes_created: u64,
+    bytes_consumed: u64,
+    elapsed: HashMap<ParsePhase, Duration>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Tracks the phase currently being timed, if metrics collection is enabled.
+///
+#[derive(Debug)]
+pub(crate) struct MetricsCollector {
+    enabled: bool,
+    current: Option<(ParsePhase, Instant)>,
+    metrics: ParserMetrics,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ParsePhase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                ParsePhase::Prolog => "prolog",
+                ParsePhase::Body => "body",
+                ParsePhase::Epilog => "epilog",
+            }
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ParserMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "ParserMetrics {{events: {}, nodes: {}, bytes: {}, elapsed: {:?}}}",
+            self.events_processed,
+            self.nodes_created,
+            self.bytes_consumed,
+            self.total_elapsed()
+        )
+    }
+}
+
+impl ParserMetrics {
+    ///
+    /// The number of events read from the underlying tokenizer.
+    ///
+    pub fn events_processed(&self) -> u64 {
+        self.events_processed
+    }
+    ///
+    /// The number of DOM nodes, including attributes, created by the parser.
+    ///
+    pub fn nodes_created(&self) -> u64 {
+        self.nodes_created
+    }
+    ///
+    /// The number of bytes of input consumed by the parser.
+    ///
+    pub fn bytes_consumed(&self) -> u64 {
+        self.bytes_consumed
+    }
+    ///
+    /// The time spent in the given phase, if that phase was reached.
+    ///
+    pub fn elapsed(&self, phase: ParsePhase) -> Option<Duration> {
+        self.elapsed.get(&phase).cloned()
+    }
+    ///
+    /// The total time spent across all phases.
+    ///
+    pub fn total_elapsed(&self) -> Duration {
+        self.elapsed.values().sum()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl MetricsCollector {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            current: None,
+            metrics: Default::default(),
+        }
+    }
+
+    pub(crate) fn event(&mut self) {
+        if self.enabled {
+            self.metrics.events_processed += 1;
+        }
+    }
+
+    pub(crate) fn node_created(&mut self) {
+        if self.enabled {
+            self.metrics.nodes_created += 1;
+        }
+    }
+
+    pub(crate) fn bytes_consumed(&mut self, position: u64) {
+        if self.enabled {
+            self.metrics.bytes_consumed = position;
+        }
+    }
+
+    pub(crate) fn begin_phase(&mut self, phase: ParsePhase) {
+        if self.enabled {
+            self.end_phase();
+            self.current = Some((phase, Instant::now()));
+        }
+    }
+
+    pub(crate) fn end_phase(&mut self) {
+        if let Some((phase, started)) = self.current.take() {
+            *self.metrics.elapsed.entry(phase).or_default() += started.elapsed();
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> ParserMetrics {
+        self.end_phase();
+        self.metrics
+    }
+}
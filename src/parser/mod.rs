@@ -3,9 +3,13 @@ Provides a basic parser from text to DOM using the [quick-xml](https://crates.io
 crate.
 
 The parsing capability of quick-xml is limited in some ways, it does not support DTD handling other
-than returning the entire DTD content as a string; therefore entities, notations, or entity
-references are not constructed in the DOM. It does parse `Text`, `CDataSection`, and `Comment` nodes
-but does limited entity processing or escaping.
+than returning the entire DTD content as a string; this parser creates the `DocumentType` node,
+with `Entity` and `Notation` nodes for any declarations in the internal subset, but entity
+references are not constructed in the DOM. External DTDs are only retrieved when a shared
+[`DtdCache`](dtd/struct.DtdCache.html) is provided. It does parse `Text`, `CDataSection`, and
+`Comment` nodes but does limited entity processing or escaping. A reference to an entity other than
+the predefined entities is an error, unless another behavior is chosen with
+[`ParseOptions::set_unknown_entities`](options/struct.ParseOptions.html#method.set_unknown_entities).
 
 # Example
 
@@ -16,21 +20,122 @@ let dom = read_xml(r#"<?xml version="1.0"?><xml/>"#);
 assert!(dom.is_ok());
 ```
 
+Documents may also be read from a URL using a [`DocumentLoader`](loader/trait.DocumentLoader.html)
+with [`read_xml_url_with`](fn.read_xml_url_with.html). When the `http` feature is enabled
+[`read_xml_url`](fn.read_xml_url.html) uses the default HTTP loader.
+
+The functions [`read_xml_with`](fn.read_xml_with.html) and [`read_reader_with`](fn.read_reader_with.html)
+take a set of [`ParseOptions`](options/struct.ParseOptions.html) and also return the
+[`ParserMetrics`](metrics/struct.ParserMetrics.html) collected during the parse. The function
+[`read_xml_recovering`](fn.read_xml_recovering.html) behaves in the same way, except that on
+failure it returns a [`ParseFailure`](struct.ParseFailure.html) containing the partially
+constructed document.
+
+The functions above assume their input is UTF-8 text; the function
+[`read_bytes`](fn.read_bytes.html) instead detects the encoding from the byte order mark and XML
+declaration, and decodes UTF-16, ISO-8859-1, and US-ASCII input before parsing.
+
+When a document has an XML declaration, characters that are not allowed in the version of XML it
+declares are rejected with [`Error::InvalidCharacter`](enum.Error.html#variant.InvalidCharacter);
+in XML 1.1 references to the control characters `#x1` to `#x1F` are allowed, but the restricted
+characters may not appear literally. Documents without a declaration are not checked.
+
+Errors detected while parsing are returned as
+[`Error::Positioned`](enum.Error.html#variant.Positioned), carrying the
+[`Position`](position/struct.Position.html), byte offset, line, and column, at which the error was
+detected; use [`Error::kind`](enum.Error.html#method.kind) to match on the underlying error, and
+[`Error::source_snippet`](enum.Error.html#method.source_snippet) to show the offending line.
+With [`ParseOptions::set_track_positions`](options/struct.ParseOptions.html#method.set_track_positions)
+the position at which each node started is also recorded, and returned by the
+[`SourcePosition`](../level2/ext/trait.SourcePosition.html) trait; with
+[`ParseOptions::set_track_spans`](options/struct.ParseOptions.html#method.set_track_spans) the
+spans of the tags, names, and attribute values of elements and attributes, and of the markup of
+other nodes, are recorded, and returned by the
+[`SourceSpans`](../level2/ext/trait.SourceSpans.html) trait; the
+[`preserve`](../level2/ext/preserve/index.html) writer uses these to write an edited document
+keeping the formatting of its unmodified parts.
+
+The function [`read_external_entity`](fn.read_external_entity.html) parses an external parsed
+entity, such as a chapter included by a master document, into a `DocumentFragment` owned by an
+existing document; these may be written with
+[`write_external_entity`](../level2/ext/writer/fn.write_external_entity.html). The function
+[`read_fragment`](fn.read_fragment.html) parses the same content in the context of an existing
+node, inheriting the namespace bindings in scope there.
+
+Content the application will never use, such as comments or particular elements, may be dropped
+as it is parsed by adding a [`ParseFilter`](filter/trait.ParseFilter.html) to the options; see the
+[`filter`](filter/index.html) module.
+
+Documents from untrusted sources should be parsed with a
+[`SecurityPolicy`](security/struct.SecurityPolicy.html) set in the options, and the function
+[`audit_xml`](security/fn.audit_xml.html) reports the risky constructs found while parsing.
+
+The DOM is built from the tokens returned by an
+[`XmlTokenSource`](source/trait.XmlTokenSource.html); the functions above all use the quick-xml
+tokenizer, [`read_source_with`](fn.read_source_with.html) accepts any other implementation of the
+trait, see the [`source`](source/index.html) module.
+A parser that pushes events rather than tokens, such as a SAX parser, may build the DOM with a
+[`DomSink`](sink/struct.DomSink.html), see the [`sink`](sink/index.html) module.
+
+A document may be validated as it is parsed, with each violation reported at its position in the
+input, by adding a [`ParseValidator`](validator/trait.ParseValidator.html) to the options; see
+the [`validator`](validator/index.html) module.
+
 */
 
-use crate::level2::convert::as_document_mut;
-use crate::level2::ext::{XmlDecl, XmlVersion};
-use crate::level2::node_impl::Extension;
+use crate::level2::convert::{as_document, as_document_mut, is_document, is_element};
+use crate::level2::ext::dom_impl::get_implementation_ext;
+use crate::level2::ext::preserve::record_digests;
+use crate::level2::ext::{
+    CharacterReference, CharacterReferences, Namespaced, XmlDecl, XmlVersion,
+};
+use crate::level2::node_impl::{Extension, NodeSpans};
 use crate::level2::*;
 use crate::shared::error::Error as DOMError;
-use quick_xml::events::{BytesCData, BytesDecl, BytesEnd, BytesStart, BytesText, Event};
-use quick_xml::reader::Reader;
-use std::borrow::Borrow;
-use std::io::BufRead;
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XML_NS_ATTRIBUTE};
+use crate::shared::text::{is_xml_char, is_xml_literal_char, is_xml_name};
+use quick_xml::escape::{unescape, EscapeError};
+use std::collections::HashMap;
+use std::io::{BufRead, Read};
 use std::str::FromStr;
 
 use thiserror::Error as E;
 
+// ------------------------------------------------------------------------------------------------
+// Public Modules
+// ------------------------------------------------------------------------------------------------
+
+pub mod dtd;
+use dtd::{DocTypeDecl, Dtd};
+
+mod encoding;
+
+pub mod filter;
+
+pub mod loader;
+use loader::DocumentLoader;
+
+pub mod metrics;
+use metrics::MetricsCollector;
+pub use metrics::{ParsePhase, ParserMetrics};
+
+pub mod options;
+pub use options::{BufferMode, BufferOptions, ParseOptions, UnknownEntities};
+
+pub mod position;
+pub use position::{Position, Span};
+
+pub mod security;
+use security::{RiskyConstruct, SecurityReport};
+
+pub mod sink;
+
+pub mod source;
+use source::{QuickXmlSource, XmlToken, XmlTokenSource};
+
+pub mod validator;
+use validator::{ParseValidator, ValidationReport};
+
 // ------------------------------------------------------------------------------------------------
 // Public Types
 // ------------------------------------------------------------------------------------------------
@@ -40,7 +145,8 @@ use thiserror::Error as E;
 ///
 #[derive(Debug, E)]
 pub enum Error {
-    /// Usually a missing quote.
+    /// Usually a missing quote, or a character that is not allowed in the version of XML
+    /// declared by the document.
     #[error("invalid character")]
     InvalidCharacter,
     /// Everything else.
@@ -52,6 +158,81 @@ pub enum Error {
     /// Errors passed through from quick-xml
     #[error("quick-xml error: {0}")]
     QuickXMLError(#[from] quick_xml::Error),
+    /// Errors passed through from an `XmlTokenSource` other than quick-xml.
+    #[error("token source error: {0}")]
+    Source(Box<dyn std::error::Error + Send + Sync>),
+    /// An element or attribute name is not allowed by the document's `ProcessingOptions`, or is
+    /// not an XML name at all.
+    #[error("invalid name '{name}' at byte {position}: {source}")]
+    InvalidName {
+        /// The name as it appears in the input.
+        name: String,
+        /// The byte offset in the input at which the name was rejected, this is the end of the
+        /// tag containing the name.
+        position: u64,
+        /// The reason the DOM rejected the name.
+        source: DOMError,
+    },
+    /// Elements were nested deeper than the limit set in `ParseOptions`, or its
+    /// `SecurityPolicy`.
+    #[error("elements nested deeper than the maximum depth of {0}")]
+    DepthExceeded(usize),
+    /// More nodes were created than the limit set in the `SecurityPolicy`.
+    #[error("more nodes created than the maximum of {0}")]
+    NodeLimitExceeded(usize),
+    /// The encoding named in the document's XML declaration is not supported.
+    #[error("unsupported encoding '{0}'")]
+    UnsupportedEncoding(String),
+    /// The content does not match the encoding declared in the XML declaration, or indicated by
+    /// the byte order mark.
+    #[error("content at byte {position} does not match the encoding '{encoding}'")]
+    EncodingMismatch {
+        /// The name of the declared, or detected, encoding.
+        encoding: String,
+        /// The byte offset in the input of the first content that does not match.
+        position: u64,
+    },
+    /// A `DocumentLoader` was unable to retrieve the content for a URL.
+    #[error("unable to load document from '{url}': {source}")]
+    Load {
+        /// The URL that was requested.
+        url: String,
+        /// The underlying error reported by the loader.
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A `DocumentLoader` could not be constructed.
+    #[error("unable to construct document loader: {0}")]
+    Loader(Box<dyn std::error::Error + Send + Sync>),
+    /// A validator added to the `ParseOptions` reported a violation, with this message, and the
+    /// options are set to fail on the first violation.
+    #[error("invalid document: {0}")]
+    Invalid(String),
+    /// Wraps any of the other errors raised while parsing with the position in the input at
+    /// which it was detected; see [`kind`](#method.kind) and [`position`](#method.position).
+    #[error("{source} at {position}")]
+    Positioned {
+        /// The error detected.
+        source: Box<Error>,
+        /// The position in the input at which the error was detected.
+        position: Position,
+    },
+}
+
+///
+/// Returned by [`read_xml_recovering`](fn.read_xml_recovering.html) when parsing fails, this
+/// retains the partially constructed document so that tools can show what was successfully
+/// parsed and where the failure occurred.
+///
+#[derive(Debug, E)]
+#[error("{error} (at byte {position})")]
+pub struct ParseFailure {
+    /// The error that caused parsing to stop.
+    #[source]
+    pub error: Error,
+    /// The `Document` node containing everything successfully parsed before the error.
+    pub partial_document: RefNode,
+    /// The byte offset in the input at which the error was detected.
+    pub position: u64,
 }
 
 ///
@@ -68,7 +249,20 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// can be safely assumed to be a `Document` node.
 ///
 pub fn read_xml(xml: impl AsRef<str>) -> Result<RefNode> {
-    inner_read(&mut Reader::from_str(xml.as_ref()))
+    read_xml_with(xml, Default::default()).map(|(document, _)| document)
+}
+
+///
+/// Parse the provided string into a DOM structure using the provided options; if the result is
+/// OK, the first element of the returned tuple can be safely assumed to be a `Document` node.
+/// The second element contains the metrics collected during the parse, these are all zero unless
+/// the `collect_metrics` option was set.
+///
+pub fn read_xml_with(
+    xml: impl AsRef<str>,
+    options: ParseOptions,
+) -> Result<(RefNode, ParserMetrics)> {
+    inner_read(&mut QuickXmlSource::new(xml.as_ref().as_bytes()), options)
 }
 
 ///
@@ -76,7 +270,261 @@ pub fn read_xml(xml: impl AsRef<str>) -> Result<RefNode> {
 /// can be safely assumed to be a `Document` node.
 ///
 pub fn read_reader<B: BufRead>(reader: B) -> Result<RefNode> {
-    inner_read(&mut Reader::from_reader(reader))
+    read_reader_with(reader, Default::default()).map(|(document, _)| document)
+}
+
+///
+/// Parse the provided reader into a DOM structure using the provided options; see
+/// [`read_xml_with`](fn.read_xml_with.html).
+///
+pub fn read_reader_with<B: BufRead>(
+    reader: B,
+    options: ParseOptions,
+) -> Result<(RefNode, ParserMetrics)> {
+    inner_read(&mut QuickXmlSource::new(reader), options)
+}
+
+///
+/// Parse the provided bytes into a DOM structure; the encoding is detected from the byte order
+/// mark, and the encoding declared in the XML declaration, before parsing. The encodings UTF-8,
+/// UTF-16, ISO-8859-1, and US-ASCII are supported. If the result is OK, the result returned can be
+/// safely assumed to be a `Document` node.
+///
+pub fn read_bytes(bytes: impl AsRef<[u8]>) -> Result<RefNode> {
+    read_bytes_with(bytes, Default::default()).map(|(document, _)| document)
+}
+
+///
+/// Parse the provided bytes into a DOM structure using the provided options; see
+/// [`read_bytes`](fn.read_bytes.html) and [`read_xml_with`](fn.read_xml_with.html).
+///
+pub fn read_bytes_with(
+    bytes: impl AsRef<[u8]>,
+    options: ParseOptions,
+) -> Result<(RefNode, ParserMetrics)> {
+    let text = encoding::decode(bytes.as_ref())?;
+    inner_read(&mut QuickXmlSource::new(text.as_bytes()), options)
+}
+
+///
+/// Read all the bytes from the provided reader and parse them into a DOM structure, detecting
+/// the encoding; see [`read_bytes`](fn.read_bytes.html).
+///
+pub fn read_reader_bytes<R: Read>(reader: R) -> Result<RefNode> {
+    read_reader_bytes_with(reader, Default::default()).map(|(document, _)| document)
+}
+
+///
+/// Read all the bytes from the provided reader and parse them into a DOM structure using the
+/// provided options; see [`read_bytes_with`](fn.read_bytes_with.html).
+///
+pub fn read_reader_bytes_with<R: Read>(
+    mut reader: R,
+    options: ParseOptions,
+) -> Result<(RefNode, ParserMetrics)> {
+    let mut bytes: Vec<u8> = Vec::new();
+    let _safe_to_ignore = reader
+        .read_to_end(&mut bytes)
+        .map_err(quick_xml::Error::from)?;
+    read_bytes_with(bytes, options)
+}
+
+///
+/// Parse the provided string into a DOM structure using the provided options; unlike
+/// [`read_xml_with`](fn.read_xml_with.html) a failure returns the partially constructed
+/// document, and the position of the error, as a [`ParseFailure`](struct.ParseFailure.html).
+///
+pub fn read_xml_recovering(
+    xml: impl AsRef<str>,
+    options: ParseOptions,
+) -> std::result::Result<(RefNode, ParserMetrics), ParseFailure> {
+    inner_read_recovering(&mut QuickXmlSource::new(xml.as_ref().as_bytes()), options)
+}
+
+///
+/// Parse the provided reader into a DOM structure using the provided options; see
+/// [`read_xml_recovering`](fn.read_xml_recovering.html).
+///
+pub fn read_reader_recovering<B: BufRead>(
+    reader: B,
+    options: ParseOptions,
+) -> std::result::Result<(RefNode, ParserMetrics), ParseFailure> {
+    inner_read_recovering(&mut QuickXmlSource::new(reader), options)
+}
+
+///
+/// Parse the provided string as an external parsed entity, such as a chapter stored separately
+/// from a master document, into a new `DocumentFragment` node owned by `document`. The entity may
+/// begin with a text declaration and contains any content allowed in an element, including more
+/// than one element or text outside any element; see
+/// [`write_external_entity`](../level2/ext/writer/fn.write_external_entity.html).
+///
+pub fn read_external_entity(xml: impl AsRef<str>, document: &RefNode) -> Result<RefNode> {
+    read_external_entity_with(xml, document, Default::default()).map(|(fragment, _)| fragment)
+}
+
+///
+/// Parse the provided string as an external parsed entity using the provided options; see
+/// [`read_external_entity`](fn.read_external_entity.html). The processing options of `document`
+/// are used rather than those in `options`.
+///
+pub fn read_external_entity_with(
+    xml: impl AsRef<str>,
+    document: &RefNode,
+    options: ParseOptions,
+) -> Result<(RefNode, ParserMetrics)> {
+    inner_read_entity(
+        &mut QuickXmlSource::new(xml.as_ref().as_bytes()),
+        document,
+        ParserState::new(options),
+    )
+}
+
+///
+/// Parse the provided reader as an external parsed entity using the provided options; see
+/// [`read_external_entity_with`](fn.read_external_entity_with.html).
+///
+pub fn read_external_entity_reader<B: BufRead>(
+    reader: B,
+    document: &RefNode,
+    options: ParseOptions,
+) -> Result<(RefNode, ParserMetrics)> {
+    inner_read_entity(
+        &mut QuickXmlSource::new(reader),
+        document,
+        ParserState::new(options),
+    )
+}
+
+///
+/// Parse the provided string as a fragment of content, such as a template or a partial update, in
+/// the context of the node `context`, into a new `DocumentFragment` node owned by the document of
+/// `context`. The fragment is an external parsed entity, see
+/// [`read_external_entity`](fn.read_external_entity.html), and inherits the namespace bindings in
+/// scope at `context`; these are those of the nearest element at or above `context`, as returned by
+/// [`Namespaced::lookup_namespace_uri`](../level2/ext/trait.Namespaced.html#tymethod.lookup_namespace_uri).
+/// So, with [`ParseOptions::set_strict_namespaces`](options/struct.ParseOptions.html#method.set_strict_namespaces),
+/// a prefix declared by the context need not be declared again in the fragment. The fragment is
+/// not inserted into the document, that is left to the caller.
+///
+pub fn read_fragment(xml: impl AsRef<str>, context: &RefNode) -> Result<RefNode> {
+    read_fragment_with(xml, context, Default::default()).map(|(fragment, _)| fragment)
+}
+
+///
+/// Parse the provided string as a fragment of content in the context of the node `context` using
+/// the provided options; see [`read_fragment`](fn.read_fragment.html). The processing options of
+/// the context's document are used rather than those in `options`.
+///
+pub fn read_fragment_with(
+    xml: impl AsRef<str>,
+    context: &RefNode,
+    options: ParseOptions,
+) -> Result<(RefNode, ParserMetrics)> {
+    let document_node = if is_document(context) {
+        context.clone()
+    } else {
+        context.owner_document().ok_or_else(|| {
+            error!("the context node has no owner document");
+            Error::DOMError(DOMError::WrongDocument)
+        })?
+    };
+    let mut source = QuickXmlSource::new(xml.as_ref().as_bytes());
+    let mut state = ParserState::new(options);
+    state.namespace_context = Some(context.clone());
+    inner_read_entity(&mut source, &document_node, state)
+}
+
+///
+/// Parse the tokens read from `source` into a DOM structure; if the result is OK, the result
+/// returned can be safely assumed to be a `Document` node. This allows a tokenizer other than
+/// quick-xml to be used, see the [`source`](source/index.html) module.
+///
+pub fn read_source<S: XmlTokenSource>(source: &mut S) -> Result<RefNode> {
+    read_source_with(source, Default::default()).map(|(document, _)| document)
+}
+
+///
+/// Parse the tokens read from `source` into a DOM structure using the provided options; see
+/// [`read_source`](fn.read_source.html) and [`read_xml_with`](fn.read_xml_with.html).
+///
+pub fn read_source_with<S: XmlTokenSource>(
+    source: &mut S,
+    options: ParseOptions,
+) -> Result<(RefNode, ParserMetrics)> {
+    inner_read(source, options)
+}
+
+///
+/// Retrieve the content at `url` using the default HTTP loader and parse it into a DOM
+/// structure; if the result is OK, the result returned can be safely assumed to be a `Document`
+/// node.
+///
+#[cfg(feature = "http")]
+pub fn read_xml_url(url: impl AsRef<str>) -> Result<RefNode> {
+    read_xml_url_with(url, &loader::HttpDocumentLoader::try_new()?)
+}
+
+///
+/// Retrieve the content at `url` using the provided `loader` and parse it into a DOM
+/// structure; if the result is OK, the result returned can be safely assumed to be a `Document`
+/// node.
+///
+pub fn read_xml_url_with(url: impl AsRef<str>, loader: &dyn DocumentLoader) -> Result<RefNode> {
+    let content = loader.load(url.as_ref())?;
+    read_xml(content)
+}
+
+impl Error {
+    ///
+    /// Returns the position in the input at which this error was detected, if known. All errors
+    /// raised while parsing a document, or external entity, have a position; those raised before
+    /// parsing starts, such as loading or decoding the input, do not.
+    ///
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Self::Positioned { position, .. } => Some(*position),
+            _ => None,
+        }
+    }
+    ///
+    /// Returns the error without its position, this is the error to match on to determine the
+    /// kind of failure.
+    ///
+    pub fn kind(&self) -> &Error {
+        match self {
+            Self::Positioned { source, .. } => source.kind(),
+            _ => self,
+        }
+    }
+    ///
+    /// Consumes this error, returning the error without its position; see
+    /// [`kind`](#method.kind).
+    ///
+    pub fn into_kind(self) -> Error {
+        match self {
+            Self::Positioned { source, .. } => source.into_kind(),
+            _ => self,
+        }
+    }
+    ///
+    /// Returns the line of `input` at which this error was detected, followed by a line with a `^`
+    /// marking the column, where `input` is the text that was parsed. Returns `None` if this error
+    /// has no position, or if `input` does not contain it.
+    ///
+    pub fn source_snippet(&self, input: &str) -> Option<String> {
+        self.position()?.snippet(input)
+    }
+
+    fn at(self, position: Position) -> Self {
+        match self {
+            Self::Positioned { .. } => self,
+            _ => Self::Positioned {
+                source: Box::new(self),
+                position,
+            },
+        }
+    }
 }
 
 impl<T> From<Error> for Result<T> {
@@ -89,16 +537,253 @@ impl<T> From<Error> for Result<T> {
 // Private Functions
 // ------------------------------------------------------------------------------------------------
 
-fn inner_read<T: BufRead>(reader: &mut Reader<T>) -> Result<RefNode> {
-    let _safe_to_ignore = reader.trim_text(true);
+///
+/// The largest number of children allocated for a new element from the sizes of those already
+/// parsed; an unusually wide element should not cause every later one to be over-allocated.
+///
+const MAX_CHILDREN_HINT: usize = 1024;
+
+///
+/// A part of some text content, after references to unknown entities have been handled. Text
+/// carries the numeric character references within it, if these are being preserved.
+///
+#[derive(Debug)]
+enum TextPart {
+    Text(String, Vec<CharacterReference>),
+    EntityReference(String),
+}
+
+///
+/// State shared by all the parsing functions for a single document.
+///
+#[derive(Debug)]
+struct ParserState {
+    metrics: MetricsCollector,
+    options: ParseOptions,
+    depth: usize,
+    nodes: usize,
+    report: SecurityReport,
+    validation: ValidationReport,
+    // the start of the last event read, when tracking positions.
+    position: Option<Position>,
+    // the span of the last event read, when tracking spans.
+    span: Option<Span>,
+    // the version of XML declared, characters not allowed in that version are rejected.
+    version: Option<XmlVersion>,
+    // the node whose in-scope namespace bindings are inherited, when reading a fragment.
+    namespace_context: Option<RefNode>,
+    // the number of elements started, and of their children, by name; used to size new elements.
+    element_sizes: HashMap<String, (usize, usize)>,
+}
+
+impl ParserState {
+    fn new(options: ParseOptions) -> Self {
+        Self {
+            metrics: MetricsCollector::new(options.has_collect_metrics()),
+            options,
+            depth: 0,
+            nodes: 0,
+            report: Default::default(),
+            validation: ValidationReport::new(Position::new(0, 1, 1)),
+            position: None,
+            span: None,
+            version: None,
+            namespace_context: None,
+            element_sizes: Default::default(),
+        }
+    }
+
+    ///
+    /// Returns the number of children to allocate for a new element `name`, the mean number of
+    /// children of the elements with that name so far.
+    ///
+    fn children_hint(&self, name: &str) -> usize {
+        match self.element_sizes.get(name) {
+            Some((elements, children)) if *elements > 0 => {
+                children.div_ceil(*elements).min(MAX_CHILDREN_HINT)
+            }
+            _ => 0,
+        }
+    }
+
+    fn element_started(&mut self, name: &str) {
+        match self.element_sizes.get_mut(name) {
+            Some((elements, _)) => *elements += 1,
+            None => {
+                let _safe_to_ignore = self.element_sizes.insert(name.to_string(), (1, 0));
+            }
+        }
+    }
+
+    fn element_ended(&mut self, name: &str, children: usize) {
+        if let Some((_, total)) = self.element_sizes.get_mut(name) {
+            *total += children;
+        }
+    }
+
+    ///
+    /// Returns the spans to record for a node created from the whole of the last event read, if
+    /// spans are tracked.
+    ///
+    fn markup_spans(&self) -> Option<Box<NodeSpans>> {
+        self.span.map(|span| {
+            Box::new(NodeSpans {
+                markup: Some(span),
+                ..Default::default()
+            })
+        })
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        match (
+            self.options.max_depth(),
+            self.options.security_policy().max_depth(),
+        ) {
+            (Some(lhs), Some(rhs)) => Some(lhs.min(rhs)),
+            (lhs, rhs) => lhs.or(rhs),
+        }
+    }
+
+    fn node_created(&mut self, node: &RefNode) -> Result<()> {
+        if self.position.is_some() {
+            node.borrow_mut().i_position = self.position;
+        }
+        self.metrics.node_created();
+        self.nodes += 1;
+        match self.options.security_policy().max_nodes() {
+            Some(max_nodes) if self.nodes > max_nodes => {
+                error!(
+                    "parsing created more than the maximum of {} nodes",
+                    max_nodes
+                );
+                Error::NodeLimitExceeded(max_nodes).into()
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+///
+/// Read the next token, recording the position at which it started if positions are tracked.
+///
+fn next_token<S: XmlTokenSource>(source: &mut S, state: &mut ParserState) -> Result<XmlToken> {
+    match source.next_token() {
+        Ok(token) => {
+            state.metrics.event();
+            if state.options.has_track_positions() {
+                state.position = Some(source.token_position());
+            }
+            if state.options.has_track_spans() {
+                state.span = Some(Span::new(source.token_position(), source.position()));
+            }
+            if let Some(version) = state.version {
+                check_characters(version, &token)?;
+            }
+            Ok(token)
+        }
+        Err(err) => {
+            error!("Unexpected parser error: {:?}", err);
+            Err(err)
+        }
+    }
+}
+
+fn inner_read<S: XmlTokenSource>(
+    source: &mut S,
+    options: ParseOptions,
+) -> Result<(RefNode, ParserMetrics)> {
+    inner_read_recovering(source, options).map_err(|failure| failure.error)
+}
+
+fn inner_read_recovering<S: XmlTokenSource>(
+    source: &mut S,
+    options: ParseOptions,
+) -> std::result::Result<(RefNode, ParserMetrics), ParseFailure> {
+    inner_read_audited(source, options).map(|(document, metrics, _, _)| (document, metrics))
+}
+
+fn inner_read_audited<S: XmlTokenSource>(
+    source: &mut S,
+    options: ParseOptions,
+) -> std::result::Result<(RefNode, ParserMetrics, SecurityReport, ValidationReport), ParseFailure> {
+    source.set_trim_text(!options.has_preserve_whitespace());
+    source.set_buffer_options(*options.buffer_options());
+
+    let mut state = ParserState::new(options);
+    let mut document_node = get_implementation_ext()
+        .create_document_with_options(None, None, None, state.options.processing_options().clone())
+        .unwrap();
+
+    let start = source.position();
+    if state.options.has_track_positions() {
+        document_node.borrow_mut().i_position = Some(start);
+    }
+    if let Extension::Document {
+        i_element_index, ..
+    } = &mut document_node.borrow_mut().i_extension
+    {
+        i_element_index.extend(
+            state
+                .options
+                .index_attributes()
+                .iter()
+                .map(|name| (name.clone(), Default::default())),
+        );
+    }
+    state.metrics.begin_phase(ParsePhase::Prolog);
+    match document(source, &mut state, &mut document_node).and_then(|_| {
+        validate(&mut state, source.position(), |validator, report| {
+            validator.document_ended(&document_node, report)
+        })
+    }) {
+        Ok(_) => {
+            if state.options.has_track_spans() {
+                record_source(&document_node, Span::new(start, source.position()));
+            }
+            state.metrics.bytes_consumed(source.position().byte());
+            Ok((
+                document_node,
+                state.metrics.finish(),
+                state.report,
+                state.validation,
+            ))
+        }
+        Err(error) => {
+            let position = source.position();
+            Err(ParseFailure {
+                error: error.at(position),
+                partial_document: document_node,
+                position: position.byte(),
+            })
+        }
+    }
+}
+
+fn inner_read_entity<S: XmlTokenSource>(
+    source: &mut S,
+    document: &RefNode,
+    mut state: ParserState,
+) -> Result<(RefNode, ParserMetrics)> {
+    source.set_trim_text(!state.options.has_preserve_whitespace());
+    source.set_buffer_options(*state.options.buffer_options());
 
-    let mut event_buffer: Vec<u8> = Vec::new();
+    let mut document_node = document.clone();
+    let mut fragment_node = as_document(document)?.create_document_fragment()?;
 
-    document(reader, &mut event_buffer)
+    let start = source.position();
+    state.metrics.begin_phase(ParsePhase::Body);
+    let _safe_to_ignore =
+        external_entity(source, &mut state, &mut document_node, &mut fragment_node)
+            .map_err(|error| error.at(source.position()))?;
+    if state.options.has_track_spans() {
+        record_source(&fragment_node, Span::new(start, source.position()));
+    }
+    state.metrics.bytes_consumed(source.position().byte());
+    Ok((fragment_node, state.metrics.finish()))
 }
 
 ///
-/// This only needs to deal with the events that could start a document.
+/// This only needs to deal with the tokens that could start a document.
 ///
 /// ```ebnf
 /// document          ::= prolog element Misc* - Char* RestrictedChar Char*
@@ -118,13 +803,18 @@ fn inner_read<T: BufRead>(reader: &mut Reader<T>) -> Result<RefNode> {
 /// S                 ::= (#x20 | #x9 | #xD | #xA)+
 /// ```
 ///
-fn document<T: BufRead>(reader: &mut Reader<T>, event_buffer: &mut Vec<u8>) -> Result<RefNode> {
-    let mut document = get_implementation()
-        .create_document(None, None, None)
-        .unwrap();
+fn document<S: XmlTokenSource>(
+    source: &mut S,
+    state: &mut ParserState,
+    document: &mut RefNode,
+) -> Result<RefNode> {
     loop {
-        match reader.read_event_into(event_buffer) {
-            Ok(Event::Decl(ev)) => {
+        match next_token(source, state)? {
+            XmlToken::Declaration {
+                version,
+                encoding,
+                standalone,
+            } => {
                 let mut mut_document = document.borrow_mut();
                 if let Extension::Document {
                     i_xml_declaration, ..
@@ -133,52 +823,49 @@ fn document<T: BufRead>(reader: &mut Reader<T>, event_buffer: &mut Vec<u8>) -> R
                     if i_xml_declaration.is_some() {
                         error!("XML declaration must be first");
                         return Error::Malformed.into();
-                    } else {
-                        let (version, encoding, standalone) = make_decl(reader, ev)?;
-                        *i_xml_declaration = Some(XmlDecl::new(
-                            XmlVersion::from_str(&version).unwrap(),
-                            encoding,
-                            standalone,
-                        ));
                     }
+                    let version = version.ok_or_else(|| {
+                        error!("XML declaration must have a version");
+                        Error::Malformed
+                    })?;
+                    let version = XmlVersion::from_str(&version).map_err(|_| {
+                        error!("XML declaration has an unsupported version {:?}", version);
+                        Error::Malformed
+                    })?;
+                    state.version = Some(version);
+                    *i_xml_declaration = Some(XmlDecl::new(version, encoding, standalone));
                 }
             }
-            Ok(Event::Start(ev)) => {
-                let mut new_element = handle_start(reader, &mut document, None, ev)?;
-                let _safe_to_ignore =
-                    element(reader, event_buffer, &mut document, &mut new_element);
-            }
-            Ok(Event::Empty(ev)) => {
-                let _safe_to_ignore = handle_start(reader, &mut document, None, ev)?;
-            }
-            Ok(Event::End(ev)) => {
-                let _safe_to_ignore = handle_end(reader, &mut document, None, ev)?;
-            }
-            Ok(Event::Comment(ev)) => {
-                let _safe_to_ignore = handle_comment(&mut document, None, ev)?;
-            }
-            Ok(Event::PI(ev)) => {
-                let _safe_to_ignore = handle_pi(reader, &mut document, None, ev)?;
-            }
-            // Ok(Event::DocType(ev)) => {
-            //     if prolog_pre_nodes
-            //         .iter()
-            //         .find(|n| n.kind == PreNodeKind::DocType)
-            //         .is_some()
-            //     {
-            //         error!("only one document type allowed");
-            //         return Error::Malformed.into();
-            //     }
-            //     prolog_pre_nodes.push(make_doc_type(reader, ev)?);
-            // }
-            Ok(Event::Eof) => return Ok(document),
-            Ok(ev) => {
-                error!("Unexpected parser event: {:?}", ev);
-                return Error::Malformed.into();
+            XmlToken::StartElement {
+                name,
+                attributes,
+                empty,
+            } => {
+                state.metrics.begin_phase(ParsePhase::Body);
+                start_element(source, state, document, None, &name, attributes, empty)?;
+                state.metrics.begin_phase(ParsePhase::Epilog);
+            }
+            XmlToken::EndElement { name } => {
+                // every element's end tag is read with its content, so this closes nothing.
+                check_end_tag(source, None, &name)?;
             }
-            Err(err) => {
-                error!("Unexpected parser error: {:?}", err);
-                return Error::from(err).into();
+            XmlToken::Comment(text) => {
+                let _safe_to_ignore = handle_comment(state, document, None, text)?;
+            }
+            XmlToken::ProcessingInstruction(text) => {
+                let _safe_to_ignore = handle_pi(state, document, None, &text)?;
+            }
+            XmlToken::DocumentType(text) => {
+                let _safe_to_ignore = handle_doctype(state, document, &text)?;
+            }
+            XmlToken::Text(text)
+                if text
+                    .bytes()
+                    .all(|b| matches!(b, b' ' | b'\t' | b'\r' | b'\n')) => {}
+            XmlToken::Eof => return Ok(document.clone()),
+            token => {
+                error!("Unexpected parser event: {:?}", token);
+                return Error::Malformed.into();
             }
         }
     }
@@ -195,146 +882,680 @@ fn document<T: BufRead>(reader: &mut Reader<T>, event_buffer: &mut Vec<u8>) -> R
 /// EmptyElemTag      ::= '<' Name (S Attribute)* S? '/>'
 /// ```
 ///
-fn element<T: BufRead>(
-    reader: &mut Reader<T>,
-    event_buffer: &mut Vec<u8>,
+fn element<S: XmlTokenSource>(
+    source: &mut S,
+    state: &mut ParserState,
     document: &mut RefNode,
     parent_element: &mut RefNode,
+    start_name: &str,
 ) -> Result<RefNode> {
     loop {
-        match reader.read_event_into(event_buffer) {
-            Ok(Event::Start(ev)) => {
-                let mut new_element = handle_start(reader, document, Some(parent_element), ev)?;
-                let _safe_to_ignore = element(reader, event_buffer, document, &mut new_element)?;
+        match next_token(source, state)? {
+            XmlToken::StartElement {
+                name,
+                attributes,
+                empty,
+            } => {
+                start_element(
+                    source,
+                    state,
+                    document,
+                    Some(parent_element),
+                    &name,
+                    attributes,
+                    empty,
+                )?;
             }
-            Ok(Event::Empty(ev)) => {
-                let _safe_to_ignore = handle_start(reader, document, Some(parent_element), ev)?;
-            }
-            Ok(Event::End(ev)) => {
-                let _safe_to_ignore = handle_end(reader, document, Some(parent_element), ev)?;
+            XmlToken::EndElement { name } => {
+                check_end_tag(source, Some(start_name), &name)?;
+                if state.options.has_track_spans() {
+                    if let Some(spans) = &mut parent_element.borrow_mut().i_spans {
+                        spans.end_tag = Some(Span::new(source.token_position(), source.position()));
+                    }
+                }
+                handle_end(state, parent_element, &name);
                 return Ok(parent_element.clone());
             }
-            Ok(Event::Comment(ev)) => {
-                let _safe_to_ignore = handle_comment(document, Some(parent_element), ev)?;
+            XmlToken::Comment(text) => {
+                let _safe_to_ignore = handle_comment(state, document, Some(parent_element), text)?;
             }
-            Ok(Event::PI(ev)) => {
-                let _safe_to_ignore = handle_pi(reader, document, Some(parent_element), ev)?;
+            XmlToken::ProcessingInstruction(text) => {
+                let _safe_to_ignore = handle_pi(state, document, Some(parent_element), &text)?;
             }
-            Ok(Event::Text(ev)) => {
-                let _safe_to_ignore = handle_text(document, Some(parent_element), ev)?;
+            XmlToken::Text(text) => {
+                let _safe_to_ignore = handle_text(state, document, Some(parent_element), &text)?;
             }
-            Ok(Event::CData(ev)) => {
-                let _safe_to_ignore = handle_cdata(reader, document, Some(parent_element), ev)?;
+            XmlToken::CData(text) => {
+                let _safe_to_ignore = handle_cdata(state, document, Some(parent_element), text)?;
             }
-            Ok(ev) => {
-                error!("Unexpected parser event: {:?}", ev);
+            token => {
+                error!("Unexpected parser event: {:?}", token);
                 return Error::Malformed.into();
             }
-            Err(err) => {
-                error!("Unexpected parser error: {:?}", err);
-                return Error::from(err).into();
+        }
+    }
+}
+
+///
+/// Add the content of an external parsed entity to `fragment`.
+///
+/// ```ebnf
+/// extParsedEnt      ::= TextDecl? content
+/// TextDecl          ::= '<?xml' VersionInfo? EncodingDecl S? '?>'
+/// ```
+///
+fn external_entity<S: XmlTokenSource>(
+    source: &mut S,
+    state: &mut ParserState,
+    document: &mut RefNode,
+    fragment: &mut RefNode,
+) -> Result<RefNode> {
+    let mut first_token = true;
+    loop {
+        match next_token(source, state)? {
+            XmlToken::Declaration {
+                version,
+                encoding,
+                standalone,
+            } if first_token => {
+                if encoding.is_none() || standalone.is_some() {
+                    error!("A text declaration must have an encoding and no standalone");
+                    return Error::Malformed.into();
+                }
+                if let Some(version) = version {
+                    state.version = XmlVersion::from_str(&version).ok();
+                }
+            }
+            XmlToken::StartElement {
+                name,
+                attributes,
+                empty,
+            } => {
+                start_element(
+                    source,
+                    state,
+                    document,
+                    Some(fragment),
+                    &name,
+                    attributes,
+                    empty,
+                )?;
+            }
+            XmlToken::Comment(text) => {
+                let _safe_to_ignore = handle_comment(state, document, Some(fragment), text)?;
+            }
+            XmlToken::ProcessingInstruction(text) => {
+                let _safe_to_ignore = handle_pi(state, document, Some(fragment), &text)?;
+            }
+            XmlToken::Text(text) => {
+                let _safe_to_ignore = handle_text(state, document, Some(fragment), &text)?;
+            }
+            XmlToken::CData(text) => {
+                let _safe_to_ignore = handle_cdata(state, document, Some(fragment), text)?;
+            }
+            XmlToken::Eof => return Ok(fragment.clone()),
+            token => {
+                error!("Unexpected parser event: {:?}", token);
+                return Error::Malformed.into();
             }
         }
+        first_token = false;
     }
 }
 
 // ------------------------------------------------------------------------------------------------
 
-fn handle_start<T: BufRead>(
-    reader: &mut Reader<T>,
+///
+/// Add the element started by a `StartElement` token to `parent_node`, followed by its content
+/// unless it is an empty element; or skip the element if it is dropped by a filter.
+///
+fn start_element<S: XmlTokenSource>(
+    source: &mut S,
+    state: &mut ParserState,
+    document: &mut RefNode,
+    parent_node: Option<&mut RefNode>,
+    name: &str,
+    attributes: Vec<(String, String)>,
+    empty: bool,
+) -> Result<()> {
+    if !keep_element(state, name) {
+        if !empty {
+            skip_element(source, state, name)?;
+        }
+        return Ok(());
+    }
+    let mut new_element = handle_start(source, state, document, parent_node, name, attributes)?;
+    validate(state, source.token_position(), |validator, report| {
+        validator.element_started(&new_element, report)
+    })?;
+    if !empty {
+        state.depth += 1;
+        let _safe_to_ignore = element(source, state, document, &mut new_element, name)?;
+        state.depth -= 1;
+    }
+    // the last token read is the end tag, or the start tag of an empty element.
+    validate(state, source.token_position(), |validator, report| {
+        validator.element_ended(&new_element, report)
+    })
+}
+
+///
+/// Call `check` for each of the validators in the parse options, recording any violations at
+/// `position`; if the options are set to fail on the first violation, it is returned as an error.
+///
+fn validate<F>(state: &mut ParserState, position: Position, check: F) -> Result<()>
+where
+    F: Fn(&dyn ParseValidator, &mut ValidationReport),
+{
+    if state.options.validators().is_empty() {
+        return Ok(());
+    }
+    let reported = state.validation.len();
+    state.validation.set_position(position);
+    for validator in state.options.validators() {
+        check(validator.as_ref(), &mut state.validation);
+    }
+    match state.validation.violations().get(reported) {
+        Some(violation) if state.options.has_fail_on_invalid() => {
+            error!("{}", violation);
+            Err(Error::Invalid(violation.message.clone()).at(position))
+        }
+        _ => Ok(()),
+    }
+}
+
+///
+/// Returns `false` if the element `name` is dropped by one of the filters in the parse options.
+///
+fn keep_element(state: &ParserState, name: &str) -> bool {
+    state
+        .options
+        .filters()
+        .iter()
+        .all(|filter| filter.keep_element(name))
+}
+
+///
+/// Read, and discard, the content of an element dropped by a filter, up to and including its
+/// end tag.
+///
+fn skip_element<S: XmlTokenSource>(
+    source: &mut S,
+    state: &mut ParserState,
+    start_name: &str,
+) -> Result<()> {
+    let mut open_elements = vec![start_name.to_string()];
+    loop {
+        match next_token(source, state)? {
+            XmlToken::StartElement {
+                name, empty: false, ..
+            } => open_elements.push(name),
+            XmlToken::EndElement { name } => {
+                check_end_tag(source, open_elements.pop().as_deref(), &name)?;
+                if open_elements.is_empty() {
+                    return Ok(());
+                }
+            }
+            XmlToken::Eof => {
+                error!("Unexpected end of input within a dropped element");
+                return Error::Malformed.into();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn handle_start<S: XmlTokenSource>(
+    source: &S,
+    state: &mut ParserState,
     document: &mut RefNode,
     parent_node: Option<&mut RefNode>,
-    ev: BytesStart<'_>,
+    name: &str,
+    attributes: Vec<(String, String)>,
 ) -> Result<RefNode> {
+    if let Some(max_depth) = state.max_depth() {
+        if state.depth >= max_depth {
+            error!("element nesting exceeds maximum depth of {}", max_depth);
+            return Error::DepthExceeded(max_depth).into();
+        }
+    }
     let mut element = {
         let mut_document = as_document_mut(document).unwrap();
-        let name = reader.decoder().decode(ev.name().into_inner())?;
-        let new_node = mut_document.create_element(&name).unwrap();
+        let new_node = mut_document
+            .create_element_with_capacity(name, state.children_hint(name), attributes.len())
+            .map_err(|source_error| invalid_name(source, name, source_error))?;
+        state.element_started(name);
         let mut actual_parent = match parent_node {
             None => document.clone(),
             Some(actual) => actual.clone(),
         };
         actual_parent.append_child(new_node)?
     };
+    state.node_created(&element)?;
+
+    let mut attribute_spans = Vec::new();
+    if state.options.has_track_spans() {
+        let start = source.token_position();
+        element.borrow_mut().i_spans = Some(Box::new(NodeSpans {
+            markup: Some(Span::new(start, source.position())),
+            name: Some(same_line_span(next_byte(start, 1), name.len())),
+            ..Default::default()
+        }));
+        attribute_spans = source.attribute_spans();
+        if attribute_spans.len() != attributes.len() {
+            attribute_spans.clear();
+        }
+    }
+    let mut attribute_spans = attribute_spans.into_iter();
 
-    for attribute in ev.attributes() {
-        let attribute = attribute.unwrap();
-        let value = attribute.decode_and_unescape_value(reader)?;
-        let name = reader.decoder().decode(attribute.key.into_inner())?;
-        let attribute_node = document.create_attribute_with(&name, &value)?;
+    for (name, raw_value) in attributes {
+        let mut value = unescape_attribute_value(state, &raw_value)?;
+        if Name::from_str(&name).is_ok_and(|name| name.is_namespace_declaration()) {
+            for filter in state.options.filters() {
+                if let Some(rewritten) = filter.rewrite_namespace(&value) {
+                    value = rewritten;
+                }
+            }
+        }
+        let attribute_node = document
+            .create_attribute_with(&name, &value)
+            .map_err(|source_error| invalid_name(source, &name, source_error))?;
+        state.node_created(&attribute_node)?;
+        if let Some((name_span, value_span)) = attribute_spans.next() {
+            attribute_node.borrow_mut().i_spans = Some(Box::new(NodeSpans {
+                // the closing quote follows the value.
+                markup: Some(Span::new(name_span.start(), next_byte(value_span.end(), 1))),
+                name: Some(name_span),
+                value: Some(value_span),
+                ..Default::default()
+            }));
+        }
         let _safe_to_ignore = element.set_attribute_node(attribute_node)?;
     }
 
+    if state.options.has_strict_namespaces() {
+        check_prefixes_bound(state, &element)?;
+    }
+
+    if !state.options.index_attributes().is_empty() {
+        index_element(state, document, &element);
+    }
+
     Ok(element)
 }
 
-fn handle_end<T: BufRead>(
-    _reader: &mut Reader<T>,
-    document: &mut RefNode,
-    parent_node: Option<&mut RefNode>,
-    _ev: BytesEnd<'_>,
-) -> Result<RefNode> {
-    Ok(match parent_node {
-        None => document,
-        Some(actual) => actual,
+///
+/// Add `element` to the document's index for each of the indexed attributes it has.
+///
+fn index_element(state: &ParserState, document: &RefNode, element: &RefNode) {
+    let mut mut_document = document.borrow_mut();
+    if let Extension::Document {
+        i_element_index, ..
+    } = &mut mut_document.i_extension
+    {
+        for name in state.options.index_attributes() {
+            if let Some(value) = element.get_attribute(name) {
+                i_element_index
+                    .entry(name.clone())
+                    .or_default()
+                    .entry(value)
+                    .or_default()
+                    .push(element.clone().downgrade());
+            }
+        }
+    }
+}
+
+///
+/// Returns the position `count` bytes after `position`, which must be on the same line.
+///
+fn next_byte(position: Position, count: u64) -> Position {
+    Position::new(
+        position.byte() + count,
+        position.line(),
+        position.column() + count,
+    )
+}
+
+///
+/// Returns the position following `text`, which starts at `start`.
+///
+fn advance(start: Position, text: &str) -> Position {
+    match text.rfind('\n') {
+        None => next_byte(start, text.len() as u64),
+        Some(last_newline) => Position::new(
+            start.byte() + text.len() as u64,
+            start.line() + text.matches('\n').count() as u64,
+            (text.len() - last_newline) as u64,
+        ),
+    }
+}
+
+///
+/// Record the span of all the input on `node`, the document or fragment read, and the digests
+/// of the content of each node with spans, used to find the nodes modified after parsing.
+///
+fn record_source(node: &RefNode, span: Span) {
+    node.borrow_mut().i_spans = Some(Box::new(NodeSpans {
+        markup: Some(span),
+        ..Default::default()
+    }));
+    record_digests(node);
+}
+
+///
+/// Returns the span of `length` bytes from `start`, which must be on the same line.
+///
+fn same_line_span(start: Position, length: usize) -> Span {
+    Span::new(start, next_byte(start, length as u64))
+}
+
+fn invalid_name<S: XmlTokenSource>(token_source: &S, name: &str, source: DOMError) -> Error {
+    error!("invalid name '{}': {}", name, source);
+    Error::InvalidName {
+        name: name.to_string(),
+        position: token_source.position().byte(),
+        source,
+    }
+}
+
+///
+/// Ensure that the prefixes used in the names of `element`, and its attributes, are declared on
+/// `element` or one of its ancestors, or are in scope at the context of a fragment.
+///
+fn check_prefixes_bound(state: &ParserState, element: &RefNode) -> Result<()> {
+    let mut prefixes: Vec<String> = element.node_name().prefix().iter().cloned().collect();
+    for name in element.attributes().keys() {
+        if let Some(prefix) = name.prefix() {
+            if prefix != XMLNS_NS_ATTRIBUTE {
+                prefixes.push(prefix.clone());
+            }
+        }
+    }
+    for prefix in prefixes
+        .iter()
+        .filter(|prefix| prefix.as_str() != XML_NS_ATTRIBUTE)
+    {
+        let declaration = format!("{}:{}", XMLNS_NS_ATTRIBUTE, prefix);
+        let mut scope = Some(element.clone());
+        let mut bound = false;
+        while let Some(scope_element) = scope {
+            if !is_element(&scope_element) {
+                break;
+            }
+            if scope_element.has_attribute(&declaration) {
+                bound = true;
+                break;
+            }
+            scope = scope_element.parent_node();
+        }
+        if !bound {
+            bound = state
+                .namespace_context
+                .as_ref()
+                .and_then(|context| context.lookup_namespace_uri(Some(prefix)))
+                .is_some();
+        }
+        if !bound {
+            error!("namespace prefix '{}' is not declared", prefix);
+            return Error::DOMError(DOMError::Namespace).into();
+        }
+    }
+    Ok(())
+}
+
+fn handle_doctype(state: &mut ParserState, document: &mut RefNode, text: &str) -> Result<RefNode> {
+    if as_document(document)?.doc_type().is_some() {
+        error!("only one document type allowed");
+        return Error::Malformed.into();
+    }
+    let decl = DocTypeDecl::from_str(text)?;
+    let doc_type = get_implementation().create_document_type(
+        &decl.name,
+        decl.public_id.as_deref(),
+        decl.system_id.as_deref(),
+    )?;
+    state.node_created(&doc_type)?;
+
+    state.report.push(RiskyConstruct::DocumentType, false);
+
+    //
+    // Declarations in the internal subset take precedence over those in the external DTD.
+    //
+    if let Some(internal_subset) = &decl.internal_subset {
+        declare_dtd(state, document, &doc_type, &Dtd::parse(internal_subset))?;
+    }
+    if let Some(system_id) = &decl.system_id {
+        let ignore_external_dtd = state.options.security_policy().has_ignore_external_dtd();
+        match state.options.dtd_cache().cloned() {
+            Some(dtd_cache) if !ignore_external_dtd => {
+                state
+                    .report
+                    .push(RiskyConstruct::ExternalDtd(system_id.clone()), false);
+                declare_dtd(state, document, &doc_type, &*dtd_cache.get(system_id)?)?;
+            }
+            _ => state
+                .report
+                .push(RiskyConstruct::ExternalDtd(system_id.clone()), true),
+        }
+    }
+
+    {
+        let mut mut_doc_type = doc_type.borrow_mut();
+        mut_doc_type.i_spans = state.markup_spans();
+        mut_doc_type.i_owner_document = Some(document.clone().downgrade());
+        mut_doc_type.i_parent_node = Some(document.clone().downgrade());
+        // the document type is not one of the document's children, but is below it.
+        mut_doc_type.i_depth = 1;
+        // the internal subset is the authoritative form of its declarations; the entity and
+        // notation nodes created from it are read-only, and it is written in their place.
+        if let Extension::DocumentType {
+            i_internal_subset, ..
+        } = &mut mut_doc_type.i_extension
+        {
+            *i_internal_subset = decl.internal_subset;
+        }
+    }
+    let mut mut_document = document.borrow_mut();
+    if let Extension::Document {
+        i_document_type, ..
+    } = &mut mut_document.i_extension
+    {
+        *i_document_type = Some(doc_type.clone());
+    }
+    Ok(doc_type)
+}
+
+fn declare_dtd(
+    state: &mut ParserState,
+    document: &RefNode,
+    doc_type: &RefNode,
+    dtd: &Dtd,
+) -> Result<()> {
+    let discard_external_entities = state
+        .options
+        .security_policy()
+        .has_discard_external_entities();
+    for entity in dtd
+        .entities()
+        .iter()
+        .filter(|entity| entity.value.is_none())
+    {
+        state.report.push(
+            RiskyConstruct::ExternalEntity(entity.name.clone()),
+            discard_external_entities,
+        );
+    }
+    dtd.declare(document, doc_type, discard_external_entities)
+}
+
+fn handle_end(state: &mut ParserState, element: &RefNode, name: &str) {
+    let children = RefNode::borrow(element).i_child_nodes.len();
+    state.element_ended(name, children);
+}
+
+///
+/// Check that the end tag `name` closes the element started by the start tag `start_name`, if
+/// there is one; the token source is not relied on to have done so.
+///
+/// ```ebnf
+/// ETag              ::= '</' Name S? '>'
+/// ```
+///
+fn check_end_tag<S: XmlTokenSource>(
+    source: &S,
+    start_name: Option<&str>,
+    name: &str,
+) -> Result<()> {
+    if start_name == Some(name) {
+        Ok(())
+    } else {
+        error!(
+            "End tag </{}> does not match the start tag {:?}",
+            name, start_name
+        );
+        Err(Error::Malformed.at(source.token_position()))
     }
-    .clone())
 }
 
 fn handle_comment(
+    state: &mut ParserState,
     document: &mut RefNode,
     parent_node: Option<&mut RefNode>,
-    ev: BytesText<'_>,
-) -> Result<RefNode> {
+    text: String,
+) -> Result<Option<RefNode>> {
+    if state.options.has_discard_comments() {
+        return Ok(None);
+    }
+    if !state
+        .options
+        .filters()
+        .iter()
+        .all(|filter| filter.keep_comment(&text))
+    {
+        return Ok(None);
+    }
     let mut_document = as_document_mut(document).unwrap();
-    let text = make_text(ev)?;
     let new_node = mut_document.create_comment(&text);
+    new_node.borrow_mut().i_spans = state.markup_spans();
     let actual_parent = match parent_node {
         None => document,
         Some(actual) => actual,
     };
-    actual_parent.append_child(new_node).map_err(|e| e.into())
+    state.node_created(&new_node)?;
+    actual_parent
+        .append_child(new_node)
+        .map(Some)
+        .map_err(|e| e.into())
 }
 
 fn handle_text(
+    state: &mut ParserState,
     document: &mut RefNode,
     parent_node: Option<&mut RefNode>,
-    ev: BytesText<'_>,
+    raw_text: &str,
 ) -> Result<RefNode> {
-    let mut_document = as_document_mut(document).unwrap();
-    let text = make_text(ev)?;
-    let new_node = mut_document.create_text_node(&text);
-    let actual_parent = match parent_node {
-        None => document,
-        Some(actual) => actual,
+    let mut actual_parent = match parent_node {
+        None => document.clone(),
+        Some(actual) => actual.clone(),
     };
-    actual_parent.append_child(new_node).map_err(|e| e.into())
+    let mut last_node = actual_parent.clone();
+    let parts = unescape_text(state, raw_text)?;
+    // the text is only recorded as written if it is not split around entity references; the
+    // span of a trimmed token includes the whitespace that was trimmed from its end.
+    let spans = state.span.filter(|_| parts.len() == 1).map(|span| {
+        Box::new(NodeSpans {
+            markup: Some(Span::new(span.start(), advance(span.start(), raw_text))),
+            ..Default::default()
+        })
+    });
+    for part in parts {
+        last_node = match part {
+            TextPart::Text(text, references) => append_text(
+                state,
+                document,
+                Some(&mut actual_parent),
+                &text,
+                references,
+                spans.clone(),
+            )?,
+            TextPart::EntityReference(name) => {
+                let new_node = as_document(document)?.create_entity_reference(&name)?;
+                state.node_created(&new_node)?;
+                actual_parent.append_child(new_node)?
+            }
+        };
+    }
+    Ok(last_node)
 }
 
-fn handle_cdata<T: BufRead>(
-    reader: &mut Reader<T>,
+fn handle_cdata(
+    state: &mut ParserState,
     document: &mut RefNode,
     parent_node: Option<&mut RefNode>,
-    ev: BytesCData<'_>,
+    text: String,
 ) -> Result<RefNode> {
+    if state.options.has_coalesce_cdata() {
+        let spans = state.markup_spans();
+        return append_text(state, document, parent_node, &text, Vec::new(), spans);
+    }
     let mut_document = as_document_mut(document).unwrap();
-    let text = make_cdata(reader, ev)?;
-    let new_node = mut_document.create_cdata_section(text.as_ref()).unwrap();
+    let new_node = mut_document.create_cdata_section(text.as_ref())?;
+    new_node.borrow_mut().i_spans = state.markup_spans();
     let actual_parent = match parent_node {
         None => document,
         Some(actual) => actual,
     };
+    state.node_created(&new_node)?;
     actual_parent.append_child(new_node).map_err(|e| e.into())
 }
 
-fn handle_pi<T: BufRead>(
-    _reader: &mut Reader<T>,
+///
+/// Add `text` to the parent, merging it with the parent's last child if that is a text node;
+/// which may be the case if a comment was discarded, or a CDATA section coalesced. The offsets
+/// of `references` are relative to the start of `text`. A merged node no longer corresponds to
+/// a single span of the input, and so has no `spans`.
+///
+fn append_text(
+    state: &mut ParserState,
     document: &mut RefNode,
     parent_node: Option<&mut RefNode>,
-    ev: BytesText<'_>,
+    text: &str,
+    references: Vec<CharacterReference>,
+    spans: Option<Box<NodeSpans>>,
 ) -> Result<RefNode> {
+    let actual_parent = match parent_node {
+        None => document.clone(),
+        Some(actual) => actual.clone(),
+    };
+    if let Some(mut last_child) = actual_parent.last_child() {
+        if last_child.node_type() == NodeType::Text {
+            let mut merged = last_child.character_references();
+            let offset = last_child.node_value().unwrap_or_default().len();
+            last_child.append_data(text)?;
+            merged.extend(references.into_iter().map(|r| r.shifted(offset)));
+            let mut mut_last_child = last_child.borrow_mut();
+            mut_last_child.set_character_references(merged);
+            mut_last_child.i_spans = None;
+            drop(mut_last_child);
+            return Ok(last_child);
+        }
+    }
     let mut_document = as_document_mut(document).unwrap();
-    let text = ev.unescape()?;
+    let new_node = mut_document.create_text_node(text);
+    new_node.borrow_mut().set_character_references(references);
+    new_node.borrow_mut().i_spans = spans;
+    state.node_created(&new_node)?;
+    let mut actual_parent = actual_parent;
+    actual_parent.append_child(new_node).map_err(|e| e.into())
+}
+
+fn handle_pi(
+    state: &mut ParserState,
+    document: &mut RefNode,
+    parent_node: Option<&mut RefNode>,
+    text: &str,
+) -> Result<Option<RefNode>> {
     let parts = text.splitn(2, ' ').collect::<Vec<&str>>();
     let (target, data) = match parts.len() {
         1 => (parts[0].to_string(), None),
@@ -348,58 +1569,158 @@ fn handle_pi<T: BufRead>(
         }
         _ => return Error::Malformed.into(),
     };
-    let new_node = match data {
-        None => mut_document
-            .create_processing_instruction(&target, None)
-            .unwrap(),
-        Some(s) => mut_document
-            .create_processing_instruction(&target, Some(s.as_str()))
-            .unwrap(),
-    };
+    if !state
+        .options
+        .filters()
+        .iter()
+        .all(|filter| filter.keep_processing_instruction(&target, data.as_deref()))
+    {
+        return Ok(None);
+    }
+    let mut_document = as_document_mut(document).unwrap();
+    let new_node = mut_document.create_processing_instruction(&target, data.as_deref())?;
+    new_node.borrow_mut().i_spans = state.markup_spans();
     let actual_parent = match parent_node {
         None => document,
         Some(actual) => actual,
     };
-    actual_parent.append_child(new_node).map_err(|e| e.into())
+    state.node_created(&new_node)?;
+    actual_parent
+        .append_child(new_node)
+        .map(Some)
+        .map_err(|e| e.into())
 }
 
 // ------------------------------------------------------------------------------------------------
 
-fn make_text(ev: BytesText<'_>) -> Result<String> {
-    Ok(ev.unescape()?.to_string())
-}
-
-fn make_cdata<T: BufRead>(reader: &mut Reader<T>, ev: BytesCData<'_>) -> Result<String> {
-    let cdata_bytes = ev.into_inner();
-    let decoded_string = reader.decoder().decode(cdata_bytes.as_ref())?;
-    Ok(decoded_string.to_string())
-}
-
-fn make_decl<T: BufRead>(
-    reader: &mut Reader<T>,
-    ev: BytesDecl<'_>,
-) -> Result<(String, Option<String>, Option<bool>)> {
-    let version = ev.version().unwrap();
-    let version = version.borrow();
-    let version = reader.decoder().decode(version).unwrap();
-    let version = unquote(version.to_string())?;
-    let encoding = if let Some(ev_value) = ev.encoding() {
-        let encoding = ev_value.unwrap();
-        let encoding = encoding.borrow();
-        let encoding = reader.decoder().decode(encoding).unwrap();
-        Some(encoding.to_string())
-    } else {
-        None
-    };
-    let standalone = if let Some(ev_value) = ev.standalone() {
-        let standalone = ev_value.unwrap();
-        let standalone = standalone.borrow();
-        let standalone = reader.decoder().decode(standalone).unwrap();
-        Some(standalone == "yes")
-    } else {
-        None
+///
+/// Unescape `raw_text`, handling references to unknown entities as set in the options. The text is
+/// split around references to be created as `EntityReference` nodes, otherwise the result has at
+/// most one text part.
+///
+fn unescape_text(state: &ParserState, raw_text: &str) -> Result<Vec<TextPart>> {
+    let handling = state.options.unknown_entities();
+    let preserve_references = state.options.has_preserve_character_references();
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut references = Vec::new();
+    let mut rest = raw_text;
+    while let Some(start) = rest.find('&') {
+        text.push_str(&rest[..start]);
+        let reference = &rest[start..];
+        let end = reference.find(';').unwrap_or(reference.len() - 1);
+        match unescape(&reference[..=end]) {
+            Ok(unescaped) => {
+                if let (Some(version), true) = (state.version, reference.starts_with("&#")) {
+                    check_referenced_characters(version, &unescaped)?;
+                }
+                if preserve_references && reference.starts_with("&#") {
+                    if let Some(character) = unescaped.chars().next() {
+                        references.push(CharacterReference::new(
+                            text.len(),
+                            character,
+                            &reference[..=end],
+                        ));
+                    }
+                }
+                text.push_str(&unescaped)
+            }
+            Err(EscapeError::UnrecognizedSymbol(range, name)) if is_xml_name(&name) => {
+                match handling {
+                    UnknownEntities::Keep => text.push_str(&reference[..=end]),
+                    UnknownEntities::Replace => {
+                        warn!("replacing reference to unknown entity '{}'", name);
+                        text.push(char::REPLACEMENT_CHARACTER);
+                    }
+                    UnknownEntities::Reference => {
+                        if !text.is_empty() {
+                            parts.push(TextPart::Text(
+                                std::mem::take(&mut text),
+                                std::mem::take(&mut references),
+                            ));
+                        }
+                        parts.push(TextPart::EntityReference(name));
+                    }
+                    UnknownEntities::Error => {
+                        error!("reference to unknown entity '{}'", name);
+                        return Error::from(quick_xml::Error::from(
+                            EscapeError::UnrecognizedSymbol(range, name),
+                        ))
+                        .into();
+                    }
+                }
+            }
+            Err(e) => return Error::from(quick_xml::Error::from(e)).into(),
+        }
+        rest = &reference[end + 1..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        parts.push(TextPart::Text(text, references));
+    }
+    Ok(parts)
+}
+
+///
+/// Returns `Error::InvalidCharacter` if the content of `token`, other than names that are checked
+/// when nodes are created, has a character that may not appear literally in the declared
+/// `version` of XML.
+///
+fn check_characters(version: XmlVersion, token: &XmlToken) -> Result<()> {
+    let first_illegal = |text: &str| text.chars().find(|c| !is_xml_literal_char(version, *c));
+    let illegal = match token {
+        XmlToken::DocumentType(text)
+        | XmlToken::Text(text)
+        | XmlToken::CData(text)
+        | XmlToken::Comment(text)
+        | XmlToken::ProcessingInstruction(text) => first_illegal(text),
+        XmlToken::StartElement { attributes, .. } => attributes
+            .iter()
+            .find_map(|(_, value)| first_illegal(value)),
+        _ => None,
     };
-    Ok((version, encoding, standalone))
+    match illegal {
+        None => Ok(()),
+        Some(c) => {
+            error!(
+                "the character U+{:04X} is not allowed in XML {}",
+                c as u32, version
+            );
+            Error::InvalidCharacter.into()
+        }
+    }
+}
+
+///
+/// Returns `Error::InvalidCharacter` if a character reference resolved to a character that is not
+/// a `Char` in the declared `version` of XML.
+///
+fn check_referenced_characters(version: XmlVersion, text: &str) -> Result<()> {
+    match text.chars().find(|c| !is_xml_char(version, *c)) {
+        None => Ok(()),
+        Some(c) => {
+            error!(
+                "a reference to the character U+{:04X} is not allowed in XML {}",
+                c as u32, version
+            );
+            Error::InvalidCharacter.into()
+        }
+    }
+}
+
+///
+/// Unescape an attribute value, where references to unknown entities are kept rather than
+/// creating nodes.
+///
+fn unescape_attribute_value(state: &ParserState, raw_value: &str) -> Result<String> {
+    let mut value = String::new();
+    for part in unescape_text(state, raw_value)? {
+        match part {
+            TextPart::Text(text, _) => value.push_str(&text),
+            TextPart::EntityReference(name) => value.push_str(&format!("&{};", name)),
+        }
+    }
+    Ok(value)
 }
 
 #[allow(clippy::if_same_then_else)]
@@ -422,6 +1743,14 @@ fn unquote(s: String) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::level2::convert::as_document_type;
+    use crate::level2::ext::dom_impl::create_internal_entity;
+    use crate::level2::ext::{DocumentTypeExt, DtdValidation, ProcessingOptions, TextContent};
+    use crate::parser::dtd::DtdCache;
+    use crate::parser::security::{audit_xml, SecurityPolicy};
+    use crate::parser::validator::{validate_xml, DtdValidator};
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
 
     fn test_good_xml(xml: &str) {
         let dom = read_xml(xml);
@@ -469,6 +1798,1199 @@ mod tests {
         test_good_xml("<xml id=\"11\"></xml>");
     }
 
+    #[test]
+    fn test_metrics() {
+        let mut options = ParseOptions::new();
+        options.set_collect_metrics();
+        let xml = "<?xml version=\"1.0\"?><!-- before --><xml id=\"1\"><a>text</a><b/></xml><!-- after -->";
+        let (_, metrics) = read_xml_with(xml, options).unwrap();
+        println!("{}", metrics);
+        // comment, xml, @id, a, text, b, comment
+        assert_eq!(metrics.nodes_created(), 7);
+        // decl, comment, start, start, text, end, empty, end, comment, eof
+        assert_eq!(metrics.events_processed(), 10);
+        assert_eq!(metrics.bytes_consumed(), xml.len() as u64);
+        assert!(metrics.elapsed(ParsePhase::Prolog).is_some());
+        assert!(metrics.elapsed(ParsePhase::Body).is_some());
+        assert!(metrics.elapsed(ParsePhase::Epilog).is_some());
+    }
+
+    #[test]
+    fn test_no_metrics() {
+        let (_, metrics) = read_xml_with("<xml><a/></xml>", ParseOptions::new()).unwrap();
+        assert_eq!(metrics, ParserMetrics::default());
+    }
+
+    #[test]
+    fn test_recovering_failure() {
+        let xml = "<root><a>text</a><b></c></root>";
+        let failure = read_xml_recovering(xml, ParseOptions::new()).unwrap_err();
+        println!("{}", failure);
+        assert_eq!(failure.position, 24);
+        assert!(matches!(failure.error.kind(), Error::QuickXMLError(_)));
+
+        let document = as_document(&failure.partial_document).unwrap();
+        let root = document.document_element().unwrap();
+        let children: Vec<String> = root
+            .child_nodes()
+            .iter()
+            .map(|n| n.node_name().to_string())
+            .collect();
+        assert_eq!(children, vec!["a".to_string(), "b".to_string()]);
+
+        assert!(read_xml(xml).is_err());
+    }
+
+    #[test]
+    fn test_recovering_success() {
+        let result = read_xml_recovering("<root><a/></root>", ParseOptions::new());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_doctype() {
+        let document_node = read_xml(
+            r#"<?xml version="1.0"?>
+<!DOCTYPE note PUBLIC "-//Example//Note" "note.dtd" [
+  <!ENTITY writer "Donald Duck.">
+]>
+<note/>"#,
+        )
+        .unwrap();
+        let document = as_document(&document_node).unwrap();
+        let doc_type_node = document.doc_type().unwrap();
+        let doc_type = as_document_type(&doc_type_node).unwrap();
+        assert_eq!(doc_type.node_name().to_string(), "note");
+        assert_eq!(doc_type.public_id(), Some("-//Example//Note".to_string()));
+        assert_eq!(doc_type.system_id(), Some("note.dtd".to_string()));
+        assert!(doc_type.internal_subset().is_some());
+        assert_eq!(doc_type.entities().len(), 1);
+        assert_eq!(doc_type.owner_document(), Some(document_node.clone()));
+
+        assert!(read_xml(r#"<!DOCTYPE a><!DOCTYPE b><a/>"#).is_err());
+    }
+
+    #[test]
+    fn test_doctype_round_trip() {
+        let xml = r#"<!DOCTYPE note [<!ENTITY writer "Donald Duck."><!ELEMENT note (#PCDATA)>]><note>Hello</note>"#;
+        let document_node = read_xml(xml).unwrap();
+        assert_eq!(document_node.to_string(), xml);
+
+        let mut doc_type_node = as_document(&document_node).unwrap().doc_type().unwrap();
+        let doc_type = as_document_type(&doc_type_node).unwrap();
+        let mut writer = doc_type
+            .entities()
+            .get(&Name::from_str("writer").unwrap())
+            .unwrap()
+            .clone();
+        assert_eq!(
+            writer.set_node_value("Mickey Mouse."),
+            Err(crate::level2::Error::NoModificationAllowed)
+        );
+
+        let replacement =
+            create_internal_entity(document_node.clone(), "writer", "Mickey Mouse.").unwrap();
+        assert_eq!(doc_type_node.add_entity(replacement), Ok(Some(writer)));
+        assert_eq!(
+            document_node.to_string(),
+            r#"<!DOCTYPE note [<!ENTITY writer "Mickey Mouse."><!ELEMENT note (#PCDATA)>]><note>Hello</note>"#
+        );
+
+        doc_type_node
+            .set_internal_subset(Some(r#"<!ENTITY copy "(c)">"#))
+            .unwrap();
+        let entities = as_document_type(&doc_type_node).unwrap().entities();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(
+            entities
+                .get(&Name::from_str("copy").unwrap())
+                .unwrap()
+                .node_value(),
+            Some("(c)".to_string())
+        );
+    }
+
+    #[derive(Debug, Default)]
+    struct CountingLoader(Rc<Cell<usize>>);
+
+    impl DocumentLoader for CountingLoader {
+        fn load(&self, url: &str) -> Result<String> {
+            self.0.set(self.0.get() + 1);
+            assert_eq!(url, "http://example.org/note.dtd");
+            Ok(r#"<!ENTITY writer "Mickey Mouse."><!ENTITY copy "(c)">"#.to_string())
+        }
+    }
+
+    #[test]
+    fn test_dtd_cache() {
+        let loads = Rc::new(Cell::new(0));
+        let cache = Rc::new(DtdCache::new(CountingLoader(loads.clone())));
+        let mut options = ParseOptions::new();
+        options.set_dtd_cache(cache.clone());
+
+        let xml = r#"<!DOCTYPE note SYSTEM "http://example.org/note.dtd" [
+  <!ENTITY writer "Donald Duck.">
+]><note/>"#;
+        for _ in 0..3 {
+            let (document_node, _) = read_xml_with(xml, options.clone()).unwrap();
+            let document = as_document(&document_node).unwrap();
+            let doc_type_node = document.doc_type().unwrap();
+            let doc_type = as_document_type(&doc_type_node).unwrap();
+            let entities = doc_type.entities();
+            assert_eq!(entities.len(), 2);
+            let writer = entities.get(&Name::from_str("writer").unwrap()).unwrap();
+            assert_eq!(writer.node_value(), Some("Donald Duck.".to_string()));
+        }
+        assert_eq!(loads.get(), 1);
+        assert!(cache.contains("http://example.org/note.dtd"));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_preserve_whitespace() {
+        let xml = "<?xml version=\"1.0\"?>\n<p>Some <b>bold</b> text</p>\n";
+        let document_node = read_xml(xml).unwrap();
+        assert_eq!(
+            document_node.to_string(),
+            "<?xml version=\"1.0\"?><p>Some<b>bold</b>text</p>"
+        );
+
+        let mut options = ParseOptions::new();
+        options.set_preserve_whitespace();
+        let (document_node, _) = read_xml_with(xml, options).unwrap();
+        assert_eq!(
+            document_node.to_string(),
+            "<?xml version=\"1.0\"?><p>Some <b>bold</b> text</p>"
+        );
+    }
+
+    #[test]
+    fn test_comments_and_cdata() {
+        let xml = "<p>one <!-- two --> three <![CDATA[<four>]]></p>";
+        let mut options = ParseOptions::new();
+        options.set_preserve_whitespace();
+        options.set_discard_comments();
+        options.set_coalesce_cdata();
+        let (document_node, _) = read_xml_with(xml, options).unwrap();
+        let document = as_document(&document_node).unwrap();
+        let root_node = document.document_element().unwrap();
+        let children = root_node.child_nodes();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].node_type(), NodeType::Text);
+        assert_eq!(
+            children[0].node_value(),
+            Some("one  three <four>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_strict_namespaces() {
+        let mut options = ParseOptions::new();
+        options.set_strict_namespaces();
+
+        let xml = r#"<a:root xmlns:a="urn:a"><a:child xml:lang="en" b:attr="1"/></a:root>"#;
+        assert!(read_xml(xml).is_ok());
+        assert!(matches!(
+            read_xml_with(xml, options.clone()).map_err(Error::into_kind),
+            Err(Error::DOMError(DOMError::Namespace))
+        ));
+
+        let xml = r#"<a:root xmlns:a="urn:a"><a:child xmlns:b="urn:b" b:attr="1"/></a:root>"#;
+        assert!(read_xml_with(xml, options).is_ok());
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut options = ParseOptions::new();
+        options.set_max_depth(2);
+
+        assert!(read_xml_with("<a><b/><b><c/></b></a>", options.clone()).is_err());
+        assert!(matches!(
+            read_xml_with("<a><b><c></c></b></a>", options.clone()).map_err(Error::into_kind),
+            Err(Error::DepthExceeded(2))
+        ));
+        assert!(read_xml_with("<a><b/><b>text</b></a>", options).is_ok());
+    }
+
+    #[test]
+    fn test_security_policy_limits() {
+        let mut policy = SecurityPolicy::new();
+        policy.set_max_depth(3);
+        policy.set_max_nodes(4);
+        let mut options = ParseOptions::new();
+        options.set_max_depth(2);
+        options.set_security_policy(policy);
+
+        assert!(matches!(
+            read_xml_with("<a><b><c/></b></a>", options.clone()).map_err(Error::into_kind),
+            Err(Error::DepthExceeded(2))
+        ));
+        assert!(read_xml_with(r#"<a x="1"><b/><b/></a>"#, options.clone()).is_ok());
+        assert!(matches!(
+            read_xml_with(r#"<a x="1"><b/><b/>text</a>"#, options).map_err(Error::into_kind),
+            Err(Error::NodeLimitExceeded(4))
+        ));
+    }
+
+    #[test]
+    fn test_security_audit() {
+        let xml = r#"<!DOCTYPE note SYSTEM "http://example.org/note.dtd" [
+  <!ENTITY logo SYSTEM "logo.gif" NDATA gif>
+  <!ENTITY writer "Donald Duck.">
+]><note/>"#;
+        let loads = Rc::new(Cell::new(0));
+        let mut options = ParseOptions::new();
+        options.set_dtd_cache(Rc::new(DtdCache::new(CountingLoader(loads.clone()))));
+
+        let (document_node, report) = audit_xml(xml, options.clone()).unwrap();
+        assert_eq!(
+            report.to_string(),
+            "document type declaration\nexternal entity `logo`\nexternal DTD \"http://example.org/note.dtd\""
+        );
+        let document = as_document(&document_node).unwrap();
+        let doc_type_node = document.doc_type().unwrap();
+        assert_eq!(
+            as_document_type(&doc_type_node).unwrap().entities().len(),
+            3
+        );
+        assert_eq!(loads.get(), 1);
+
+        options.set_security_policy(SecurityPolicy::untrusted());
+        let (document_node, report) = audit_xml(xml, options).unwrap();
+        assert_eq!(report.findings().len(), 3);
+        assert_eq!(report.neutralized().count(), 2);
+        let document = as_document(&document_node).unwrap();
+        let doc_type_node = document.doc_type().unwrap();
+        let entities = as_document_type(&doc_type_node).unwrap().entities();
+        assert_eq!(entities.len(), 1);
+        assert!(entities.contains_key(&Name::from_str("writer").unwrap()));
+        assert_eq!(loads.get(), 1);
+
+        let (_, report) = audit_xml("<note/>", ParseOptions::new()).unwrap();
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        match read_xml("<root><1child/></root>").map_err(Error::into_kind) {
+            Err(Error::InvalidName {
+                name,
+                position,
+                source,
+            }) => {
+                assert_eq!(name, "1child");
+                assert_eq!(position, 15);
+                assert_eq!(source, DOMError::InvalidCharacter);
+            }
+            result => panic!("expected an invalid name, not {:?}", result),
+        }
+        assert!(matches!(
+            read_xml(r#"<root a:b:c="1"/>"#).map_err(Error::into_kind),
+            Err(Error::InvalidName { .. })
+        ));
+        let failure = read_xml_recovering("<root><ok/><2bad/></root>", ParseOptions::new())
+            .err()
+            .unwrap();
+        assert!(matches!(failure.error.kind(), Error::InvalidName { .. }));
+        assert_eq!(
+            failure.partial_document.to_string(),
+            "<root><ok></ok></root>"
+        );
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let xml = "<root>\n  <a>text</b>\n</root>";
+        let error = read_xml(xml).unwrap_err();
+        assert!(matches!(error.kind(), Error::QuickXMLError(_)));
+        let position = error.position().unwrap();
+        assert_eq!(position.line(), 2);
+        assert!(error.to_string().ends_with(&format!("at {}", position)));
+        assert!(error
+            .source_snippet(xml)
+            .unwrap()
+            .starts_with("  <a>text</b>\n"));
+
+        let xml = "<root>\n<ok/>\n<2bad/></root>";
+        let error = read_xml(xml).unwrap_err();
+        assert!(matches!(error.kind(), Error::InvalidName { .. }));
+        assert_eq!(error.position().unwrap().line(), 3);
+
+        let error = read_reader("<a>\r\n<b></a>".as_bytes()).unwrap_err();
+        assert_eq!(error.position().unwrap().line(), 2);
+    }
+
+    #[test]
+    fn test_track_positions() {
+        use crate::level2::ext::SourcePosition;
+
+        let xml = "<?xml version=\"1.0\"?>\n<root a=\"1\">\n  <!--c-->text<b/>\r\n  <![CDATA[x]]>\n</root>";
+        let (document_node, _) = read_xml_with(xml, ParseOptions::new()).unwrap();
+        assert!(document_node.source_position().is_none());
+
+        let mut options = ParseOptions::new();
+        options.set_track_positions();
+        let (document_node, _) = read_xml_with(xml, options.clone()).unwrap();
+        let line_column = |node: &RefNode| {
+            let position = node.source_position().unwrap();
+            (position.line(), position.column())
+        };
+        assert_eq!(line_column(&document_node), (1, 1));
+        let root_node = as_document(&document_node)
+            .unwrap()
+            .document_element()
+            .unwrap();
+        assert_eq!(line_column(&root_node), (2, 1));
+        assert_eq!(
+            line_column(&root_node.get_attribute_node("a").unwrap()),
+            (2, 1)
+        );
+        let children = root_node.child_nodes();
+        assert_eq!(children.len(), 4);
+        assert_eq!(line_column(&children[0]), (3, 3));
+        assert_eq!(line_column(&children[1]), (3, 11));
+        assert_eq!(line_column(&children[2]), (3, 15));
+        assert_eq!(
+            children[1].source_position().unwrap().byte(),
+            xml.find("text").unwrap() as u64
+        );
+        assert_eq!(line_column(&children[3]), (4, 3));
+
+        options.set_preserve_whitespace();
+        let (document_node, _) = read_xml_with(xml, options).unwrap();
+        let root_node = as_document(&document_node)
+            .unwrap()
+            .document_element()
+            .unwrap();
+        let first_child = root_node.first_child().unwrap();
+        assert_eq!(first_child.node_type(), NodeType::Text);
+        assert_eq!(line_column(&first_child), (2, 13));
+    }
+
+    #[test]
+    fn test_track_spans() {
+        use crate::level2::ext::SourceSpans;
+
+        let xml = "<root>\n  <a  x = \"1 &amp; 2\"\n     y='' />text<b\tz='3'>B</b >\n</root>";
+        let (document_node, _) = read_xml_with(xml, ParseOptions::new()).unwrap();
+        let root_node = as_document(&document_node)
+            .unwrap()
+            .document_element()
+            .unwrap();
+        assert!(root_node.markup_span().is_none());
+
+        let mut options = ParseOptions::new();
+        options.set_track_spans();
+        let (document_node, _) = read_xml_with(xml, options).unwrap();
+        let root_node = as_document(&document_node)
+            .unwrap()
+            .document_element()
+            .unwrap();
+        let text = |span: Option<Span>| &xml[span.unwrap().byte_range()];
+        assert_eq!(text(root_node.markup_span()), "<root>");
+        assert_eq!(text(root_node.name_span()), "root");
+        assert_eq!(text(root_node.end_tag_span()), "</root>");
+        assert!(root_node.value_span().is_none());
+
+        let children = root_node.child_nodes();
+        let a_node = &children[0];
+        assert_eq!(
+            text(a_node.markup_span()),
+            "<a  x = \"1 &amp; 2\"\n     y='' />"
+        );
+        assert!(a_node.end_tag_span().is_none());
+        let x_node = a_node.get_attribute_node("x").unwrap();
+        assert_eq!(text(x_node.markup_span()), "x = \"1 &amp; 2\"");
+        assert_eq!(text(x_node.name_span()), "x");
+        assert_eq!(text(x_node.value_span()), "1 &amp; 2");
+        let y_node = a_node.get_attribute_node("y").unwrap();
+        assert_eq!(text(y_node.value_span()), "");
+        let position = y_node.name_span().unwrap().start();
+        assert_eq!((position.line(), position.column()), (3, 6));
+        assert_eq!(text(children[1].markup_span()), "text");
+        assert!(children[1].name_span().is_none());
+
+        let b_node = &children[2];
+        assert_eq!(text(b_node.markup_span()), "<b\tz='3'>");
+        assert_eq!(text(b_node.end_tag_span()), "</b >");
+        let z_node = b_node.get_attribute_node("z").unwrap();
+        assert_eq!(text(z_node.value_span()), "3");
+        let end = root_node.end_tag_span().unwrap().start();
+        assert_eq!((end.line(), end.column()), (4, 1));
+    }
+
+    #[test]
+    fn test_unknown_entities() {
+        let xml = r#"<p title="&copy; &amp; co">A&nbsp;B &lt; &copy;</p>"#;
+        assert!(matches!(
+            read_xml(xml).map_err(Error::into_kind),
+            Err(Error::QuickXMLError(_))
+        ));
+        let parse = |unknown_entities: UnknownEntities| {
+            let mut options = ParseOptions::new();
+            options.set_unknown_entities(unknown_entities);
+            let (document_node, _) = read_xml_with(xml, options).unwrap();
+            as_document(&document_node)
+                .unwrap()
+                .document_element()
+                .unwrap()
+        };
+
+        let element = parse(UnknownEntities::Keep);
+        assert_eq!(
+            element.get_attribute("title").unwrap(),
+            "&#38;copy; &#38; co"
+        );
+        assert_eq!(element.child_nodes().len(), 1);
+        assert_eq!(
+            element.first_child().unwrap().node_value().unwrap(),
+            "A&nbsp;B < &copy;"
+        );
+
+        let element = parse(UnknownEntities::Replace);
+        assert_eq!(element.get_attribute("title").unwrap(), "\u{FFFD} &#38; co");
+        assert_eq!(
+            element.first_child().unwrap().node_value().unwrap(),
+            "A\u{FFFD}B < \u{FFFD}"
+        );
+
+        let element = parse(UnknownEntities::Reference);
+        assert_eq!(
+            element.get_attribute("title").unwrap(),
+            "&#38;copy; &#38; co"
+        );
+        let children = element.child_nodes();
+        assert_eq!(
+            children.iter().map(|n| n.node_type()).collect::<Vec<_>>(),
+            vec![
+                NodeType::Text,
+                NodeType::EntityReference,
+                NodeType::Text,
+                NodeType::EntityReference
+            ]
+        );
+        assert_eq!(children[1].node_name().to_string(), "nbsp");
+        assert_eq!(children[2].node_value().unwrap(), "B < ");
+        assert_eq!(
+            element.to_string(),
+            r#"<p title="&#38;copy; &#38; co">A&nbsp;B &#60; &copy;</p>"#
+        );
+
+        let mut options = ParseOptions::new();
+        options.set_unknown_entities(UnknownEntities::Keep);
+        assert!(read_xml_with("<p>&#xZZ; &bad</p>", options.clone()).is_err());
+        assert!(read_xml_with("<p>& x;</p>", options).is_err());
+    }
+
+    #[test]
+    fn test_preserve_character_references() {
+        let xml = "<p>a&#xA0;<![CDATA[x]]>&#169; &lt; &#x000a9;</p>";
+        let mut options = ParseOptions::new();
+        options.set_coalesce_cdata();
+        let (document_node, _) = read_xml_with(xml, options.clone()).unwrap();
+        assert_eq!(
+            document_node.to_string(),
+            "<p>a\u{A0}x\u{A9} &#60; \u{A9}</p>"
+        );
+
+        options.set_preserve_character_references();
+        let (document_node, _) = read_xml_with(xml, options).unwrap();
+        let mut text_node = as_document(&document_node)
+            .unwrap()
+            .document_element()
+            .unwrap()
+            .first_child()
+            .unwrap();
+        assert_eq!(text_node.node_value().unwrap(), "a\u{A0}x\u{A9} < \u{A9}");
+        let references = text_node.character_references();
+        assert_eq!(
+            references
+                .iter()
+                .map(|r| (r.offset(), r.character(), r.is_hexadecimal()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, '\u{A0}', true),
+                (4, '\u{A9}', false),
+                (9, '\u{A9}', true)
+            ]
+        );
+        assert_eq!(
+            document_node.to_string(),
+            "<p>a&#xA0;x&#169; &#60; &#x000a9;</p>"
+        );
+
+        text_node.append_data("!").unwrap();
+        assert!(text_node.character_references().is_empty());
+        assert_eq!(
+            document_node.to_string(),
+            "<p>a\u{A0}x\u{A9} &#60; \u{A9}!</p>"
+        );
+    }
+
+    #[test]
+    fn test_validate_namespace_uris() {
+        let xml = r#"<root xmlns:ex="http://example.org/a b"/>"#;
+        assert!(read_xml(xml).is_ok());
+
+        let mut processing_options = ProcessingOptions::new();
+        processing_options.set_validate_namespace_uris();
+        let mut options = ParseOptions::new();
+        options.set_processing_options(processing_options);
+
+        assert!(matches!(
+            read_xml_with(xml, options.clone()).map_err(Error::into_kind),
+            Err(Error::DOMError(DOMError::Namespace))
+        ));
+        assert!(read_xml_with(
+            r#"<root xmlns="relative" xmlns:ex="urn:example"/>"#,
+            options
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_strict_names() {
+        let xml = r#"<xmlns:root xmlns:xmlns="urn:example"/>"#;
+        assert!(read_xml(xml).is_ok());
+        assert!(read_xml("<xml-root/>").is_ok());
+
+        let mut processing_options = ProcessingOptions::new();
+        processing_options.set_strict_names();
+        let mut options = ParseOptions::new();
+        options.set_processing_options(processing_options);
+
+        assert!(matches!(
+            read_xml_with(xml, options.clone()).map_err(Error::into_kind),
+            Err(Error::InvalidName {
+                source: DOMError::Namespace,
+                ..
+            })
+        ));
+        assert!(matches!(
+            read_xml_with("<xml-root/>", options.clone()).map_err(Error::into_kind),
+            Err(Error::InvalidName {
+                source: DOMError::InvalidCharacter,
+                ..
+            })
+        ));
+        let (document_node, _) = read_xml_with(
+            r#"<root xmlns="urn:example" xmlns:ex="urn:example" xml:lang="en"/>"#,
+            options,
+        )
+        .unwrap();
+        let document = as_document(&document_node).unwrap();
+        let root_node = document.document_element().unwrap();
+        assert_eq!(root_node.attributes().len(), 3);
+    }
+
+    #[test]
+    fn test_fail_on_invalid_character() {
+        let mut processing_options = ProcessingOptions::new();
+        processing_options.set_fail_on_invalid_character();
+        let mut options = ParseOptions::new();
+        options.set_processing_options(processing_options);
+
+        // errors creating attributes are reported with the attribute name.
+        assert!(matches!(
+            read_xml_with("<root a=\"\u{1}\"/>", options.clone()).map_err(Error::into_kind),
+            Err(Error::InvalidName {
+                source: DOMError::InvalidCharacter,
+                ..
+            })
+        ));
+        for xml in [
+            "<root>\u{1}</root>",
+            "<root><![CDATA[\u{1}]]></root>",
+            "<root><?pi \u{1}?></root>",
+        ] {
+            assert!(
+                matches!(
+                    read_xml_with(xml, options.clone()).map_err(Error::into_kind),
+                    Err(Error::DOMError(DOMError::InvalidCharacter))
+                ),
+                "{:?}",
+                xml
+            );
+        }
+        assert!(read_xml_with("<root>\u{1}</root>", ParseOptions::new()).is_ok());
+    }
+
+    #[test]
+    fn test_declared_version_characters() {
+        let text = |xml: &str| {
+            let document_node = read_xml(xml).unwrap();
+            let document = as_document(&document_node).unwrap();
+            let root_node = document.document_element().unwrap();
+            root_node.first_child().unwrap().node_value().unwrap()
+        };
+        // references to restricted characters are only allowed in XML 1.1, and the restricted
+        // characters only as references.
+        assert_eq!(
+            text("<?xml version=\"1.1\"?><a>&#x1;\u{85}</a>"),
+            "\u{1}\u{85}"
+        );
+        assert_eq!(
+            text("<?xml version=\"1.0\"?><a>\u{80}\u{1F600}</a>"),
+            "\u{80}\u{1F600}"
+        );
+        for xml in [
+            "<?xml version=\"1.0\"?><a>&#x1;</a>",
+            "<?xml version=\"1.0\"?><a b=\"\u{1}\"/>",
+            "<?xml version=\"1.0\"?><a><!--\u{1}--></a>",
+            "<?xml version=\"1.1\"?><a>\u{1}</a>",
+            "<?xml version=\"1.1\"?><a><![CDATA[\u{80}]]></a>",
+        ] {
+            assert!(
+                matches!(
+                    read_xml(xml).map_err(Error::into_kind),
+                    Err(Error::InvalidCharacter)
+                ),
+                "{:?}",
+                xml
+            );
+        }
+        // without a declaration the characters are not checked.
+        assert_eq!(text("<a>\u{1}</a>"), "\u{1}");
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        for xml in ["<?xml version=\"2.0\"?><r/>", "<?xml version=\"one\"?><r/>"] {
+            assert!(
+                matches!(
+                    read_xml(xml).map_err(Error::into_kind),
+                    Err(Error::Malformed)
+                ),
+                "{:?}",
+                xml
+            );
+            let failure = read_xml_recovering(xml, ParseOptions::new()).unwrap_err();
+            assert!(
+                matches!(failure.error.into_kind(), Error::Malformed),
+                "{:?}",
+                xml
+            );
+            assert!(failure.partial_document.child_nodes().is_empty());
+        }
+
+        let mut sink = sink::DomSink::new();
+        sink.declaration("2.0", None, None);
+        sink.start_element("r", &[]);
+        sink.end_element("r");
+        assert!(matches!(
+            sink.finish().map_err(Error::into_kind),
+            Err(Error::Malformed)
+        ));
+    }
+
+    #[test]
+    fn test_parse_filters() {
+        use crate::level2::ext::Namespaced;
+        use filter::{
+            DropComments, DropElements, DropProcessingInstructions, ParseFilter, RewriteNamespaces,
+        };
+
+        #[derive(Debug)]
+        struct DropNotes;
+
+        impl ParseFilter for DropNotes {
+            fn keep_comment(&self, data: &str) -> bool {
+                !data.trim().starts_with("note")
+            }
+        }
+
+        let xml = r#"<?pi first?><root xmlns:old="urn:v1"><!-- note: a --><!-- keep -->
+<?pi data?><script><b>nested<script/></b></script>one<drop/> two<old:item/></root>"#;
+
+        let mut options = ParseOptions::new();
+        options.set_collect_metrics();
+        options.add_filter(DropNotes);
+        options.add_filter(DropProcessingInstructions);
+        options.add_filter(DropElements::new(&["script", "drop"]));
+        options.add_filter(RewriteNamespaces::new().rewrite("urn:v1", "urn:v2"));
+        let (document_node, metrics) = read_xml_with(xml, options.clone()).unwrap();
+        let document = as_document(&document_node).unwrap();
+        assert_eq!(document_node.child_nodes().len(), 1);
+        let root_node = document.document_element().unwrap();
+        assert_eq!(
+            root_node.to_string(),
+            r#"<root xmlns:old="urn:v2"><!-- keep -->onetwo<old:item></old:item></root>"#
+        );
+        assert_eq!(
+            root_node.lookup_namespace_uri(Some("old")),
+            Some("urn:v2".to_string())
+        );
+        // the root, its namespace attribute, one comment, merged text, and one element.
+        assert_eq!(metrics.nodes_created(), 5);
+        assert_eq!(options, options.clone());
+
+        options.add_filter(DropComments);
+        let (document_node, _) = read_xml_with(xml, options).unwrap();
+        let document = as_document(&document_node).unwrap();
+        let root_node = document.document_element().unwrap();
+        assert_eq!(root_node.child_nodes().len(), 2);
+
+        let mut options = ParseOptions::new();
+        options.add_filter(DropElements::new(&["root"]));
+        let (document_node, _) = read_xml_with(xml, options.clone()).unwrap();
+        assert!(!document_node.child_nodes().iter().any(is_element));
+        assert!(read_xml_with("<root><drop><a></root>", options).is_err());
+    }
+
+    #[test]
+    fn test_children_hint() {
+        let mut state = ParserState::new(ParseOptions::new());
+        assert_eq!(state.children_hint("row"), 0);
+        state.element_started("row");
+        assert_eq!(state.children_hint("row"), 0);
+        state.element_ended("row", 4);
+        state.element_started("row");
+        state.element_ended("row", 1);
+        assert_eq!(state.children_hint("row"), 3);
+        state.element_started("row");
+        state.element_ended("row", 10 * MAX_CHILDREN_HINT);
+        assert_eq!(state.children_hint("row"), MAX_CHILDREN_HINT);
+
+        let xml = format!(
+            "<table>{}</table>",
+            "<row><cell a='1' b='2'/><cell/><cell/></row>".repeat(3)
+        );
+        let document_node = read_xml(xml).unwrap();
+        let document = as_document(&document_node).unwrap();
+        let last_row = document.document_element().unwrap().last_child().unwrap();
+        let ref_row = RefNode::borrow(&last_row);
+        assert_eq!(ref_row.i_child_nodes.len(), 3);
+        assert_eq!(ref_row.i_child_nodes.capacity(), 3);
+    }
+
+    #[test]
+    fn test_indexed_elements() {
+        use crate::level2::ext::IndexedElements;
+
+        let xml = r#"<codes><code name="a" id="1"/><group><code name="b" id="2"/>
+<code name="a" id="3"/></group><code id="4"/></codes>"#;
+
+        let mut options = ParseOptions::new();
+        options.add_index_attribute("name");
+        options.add_index_attribute("name");
+        options.add_index_attribute("missing");
+        assert_eq!(options.index_attributes(), ["name", "missing"]);
+        let (document_node, _) = read_xml_with(xml, options).unwrap();
+
+        let mut attributes = document_node.indexed_attributes();
+        attributes.sort();
+        assert_eq!(attributes, vec!["missing", "name"]);
+        assert!(document_node
+            .indexed_elements("missing")
+            .unwrap()
+            .is_empty());
+        assert!(document_node.indexed_elements("id").is_none());
+
+        let ids = |elements: &Vec<RefNode>| -> Vec<String> {
+            elements
+                .iter()
+                .map(|element| element.get_attribute("id").unwrap())
+                .collect()
+        };
+        let index = document_node.indexed_elements("name").unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(ids(&index["a"]), vec!["1", "3"]);
+        assert_eq!(ids(&index["b"]), vec!["2"]);
+
+        let mut group_node = index["b"][0].parent_node().unwrap();
+        let _safe_to_ignore = group_node.remove_child(index["b"][0].clone()).unwrap();
+        let mut renamed_node = index["a"][0].clone();
+        renamed_node.set_attribute("name", "c").unwrap();
+        let index = document_node.indexed_elements("name").unwrap();
+        assert_eq!(index.len(), 1);
+        assert_eq!(ids(&index["a"]), vec!["3"]);
+
+        let document_node = read_xml(xml).unwrap();
+        assert!(document_node.indexed_attributes().is_empty());
+    }
+
+    #[test]
+    fn test_external_entity() {
+        let document_node = get_implementation()
+            .create_document(None, Some("book"), None)
+            .unwrap();
+        let fragment_node = read_external_entity(
+            r#"<?xml encoding="UTF-8"?>Some <b>bold</b> text<!-- note --><p/>"#,
+            &document_node,
+        )
+        .unwrap();
+        assert_eq!(fragment_node.node_type(), NodeType::DocumentFragment);
+        let names: Vec<String> = fragment_node
+            .child_nodes()
+            .iter()
+            .map(|node| node.node_name().to_string())
+            .collect();
+        assert_eq!(names, vec!["#text", "b", "#text", "#comment", "p"]);
+
+        for xml in [
+            r#"<?xml version="1.0"?><p/>"#,
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><p/>"#,
+            "<p/><?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+            "<!DOCTYPE p><p/>",
+            "<p></q>",
+            "</p>",
+        ] {
+            assert!(
+                read_external_entity(xml, &document_node).is_err(),
+                "{}",
+                xml
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_fragment() {
+        let document_node =
+            read_xml(r#"<html xmlns:ex="urn:example"><body><p>before</p></body></html>"#).unwrap();
+        let document = as_document(&document_node).unwrap();
+        let mut body_node = document.get_elements_by_tag_name("body")[0].clone();
+
+        let mut options = ParseOptions::new();
+        options.set_strict_namespaces();
+        let (fragment_node, _) = read_fragment_with(
+            r#"<ex:item>one</ex:item> and <ex:item xml:lang="en">two</ex:item>"#,
+            &body_node,
+            options.clone(),
+        )
+        .unwrap();
+        assert_eq!(fragment_node.node_type(), NodeType::DocumentFragment);
+        assert_eq!(fragment_node.owner_document(), Some(document_node.clone()));
+        assert_eq!(fragment_node.child_nodes().len(), 3);
+        assert!(body_node.parent_node().is_some());
+
+        for child_node in fragment_node.child_nodes() {
+            let _safe_to_ignore = body_node.append_child(child_node).unwrap();
+        }
+        assert_eq!(
+            document.document_element().unwrap().to_string(),
+            r#"<html xmlns:ex="urn:example"><body><p>before</p><ex:item>one</ex:item>and<ex:item xml:lang="en">two</ex:item></body></html>"#
+        );
+
+        // the context of a text node is its parent element.
+        let text_node = body_node.first_child().unwrap().first_child().unwrap();
+        assert!(read_fragment_with("<ex:b/>", &text_node, options.clone()).is_ok());
+
+        // without a binding in scope the prefix is rejected.
+        assert!(read_fragment_with("<other:b/>", &body_node, options.clone()).is_err());
+        // the context of a document is its document element.
+        assert!(read_fragment_with("<ex:b/>", &document_node, options.clone()).is_ok());
+        let other_node = read_xml("<html/>").unwrap();
+        assert!(read_fragment_with("<ex:b/>", &other_node, options).is_err());
+        assert!(read_fragment("<ex:b/>", &other_node).is_ok());
+    }
+
+    #[test]
+    fn test_read_bytes() {
+        use crate::level2::ext::convert::as_document_decl;
+
+        let bytes = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><caf\xE9>cr\xE8me</caf\xE9>";
+        let document_node = read_bytes(bytes).unwrap();
+        let document = as_document_decl(&document_node).unwrap();
+        let root_node = document.document_element().unwrap();
+        assert_eq!(root_node.node_name().to_string(), "café");
+        assert_eq!(
+            root_node.first_child().unwrap().node_value(),
+            Some("crème".to_string())
+        );
+        assert_eq!(
+            document.xml_declaration().unwrap().encoding(),
+            Some("ISO-8859-1".to_string())
+        );
+
+        let bytes: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain("<a>é€</a>".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        let document_node = read_reader_bytes(&bytes[..]).unwrap();
+        assert_eq!(document_node.to_string(), "<a>é€</a>");
+
+        assert!(matches!(
+            read_bytes(b"<a>\xE9</a>"),
+            Err(Error::EncodingMismatch { position: 3, .. })
+        ));
+        assert!(matches!(
+            read_bytes(b"<?xml version=\"1.0\" encoding=\"KOI8-R\"?><a/>"),
+            Err(Error::UnsupportedEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn test_quick_xml_source() {
+        let mut source = QuickXmlSource::new(
+            r#"<?xml version="1.0"?><a x="1 &amp; 2"><!--c--><?p d?>t<![CDATA[<c>]]></a>"#
+                .as_bytes(),
+        );
+        source.set_trim_text(true);
+        let mut tokens = Vec::new();
+        loop {
+            let token = source.next_token().unwrap();
+            if token == XmlToken::Eof {
+                break;
+            }
+            tokens.push(token);
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                XmlToken::Declaration {
+                    version: Some("1.0".to_string()),
+                    encoding: None,
+                    standalone: None,
+                },
+                XmlToken::StartElement {
+                    name: "a".to_string(),
+                    attributes: vec![("x".to_string(), "1 &amp; 2".to_string())],
+                    empty: false,
+                },
+                XmlToken::Comment("c".to_string()),
+                XmlToken::ProcessingInstruction("p d".to_string()),
+                XmlToken::Text("t".to_string()),
+                XmlToken::CData("<c>".to_string()),
+                XmlToken::EndElement {
+                    name: "a".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_buffer_options() {
+        let xml = format!("<a><b>{}</b><c>small</c></a>", "x".repeat(100_000));
+        for mode in [
+            BufferMode::Single,
+            BufferMode::PerElement,
+            BufferMode::Automatic,
+        ] {
+            let mut buffer_options = BufferOptions::new();
+            buffer_options.set_mode(mode);
+            let parse = |buffer_options: BufferOptions| {
+                let mut options = ParseOptions::new();
+                options.set_buffer_options(buffer_options);
+                let mut source = QuickXmlSource::new(xml.as_bytes());
+                let (document_node, _) = read_source_with(&mut source, options).unwrap();
+                assert_eq!(document_node.to_string(), xml);
+                source.retained_capacity()
+            };
+            assert!(parse(buffer_options) >= 100_000, "{:?}", mode);
+
+            // each of the buffers, one for each depth, is shrunk after use.
+            buffer_options.set_max_retained_capacity(1024);
+            assert!(parse(buffer_options) <= 3 * 1024, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_custom_source() {
+        #[derive(Debug)]
+        struct Failing {
+            read: u64,
+        }
+
+        impl XmlTokenSource for Failing {
+            fn set_trim_text(&mut self, _trim: bool) {}
+
+            fn next_token(&mut self) -> Result<XmlToken> {
+                self.read += 1;
+                match self.read {
+                    1 => Ok(XmlToken::StartElement {
+                        name: "a".to_string(),
+                        attributes: Vec::new(),
+                        empty: false,
+                    }),
+                    _ => Err(Error::Source("unexpected input".into())),
+                }
+            }
+
+            fn token_position(&self) -> Position {
+                self.position()
+            }
+
+            fn position(&self) -> Position {
+                Position::new(self.read * 10, 1, self.read * 10 + 1)
+            }
+        }
+
+        let failure = read_source_with(&mut Failing { read: 0 }, Default::default()).unwrap_err();
+        assert!(matches!(failure.kind(), Error::Source(_)));
+        assert_eq!(failure.position().map(|position| position.byte()), Some(20));
+    }
+
+    ///
+    /// A token source returning a fixed list of tokens, each 10 bytes long.
+    ///
+    #[derive(Debug)]
+    struct TokenList {
+        tokens: Vec<XmlToken>,
+        read: u64,
+    }
+
+    impl XmlTokenSource for TokenList {
+        fn set_trim_text(&mut self, _trim: bool) {}
+
+        fn next_token(&mut self) -> Result<XmlToken> {
+            self.read += 1;
+            Ok(self
+                .tokens
+                .get(self.read as usize - 1)
+                .cloned()
+                .unwrap_or(XmlToken::Eof))
+        }
+
+        fn token_position(&self) -> Position {
+            Position::new((self.read - 1) * 10, 1, (self.read - 1) * 10 + 1)
+        }
+
+        fn position(&self) -> Position {
+            Position::new(self.read * 10, 1, self.read * 10 + 1)
+        }
+    }
+
+    #[test]
+    fn test_custom_source_end_tags() {
+        let start = |name: &str| XmlToken::StartElement {
+            name: name.to_string(),
+            attributes: Vec::new(),
+            empty: false,
+        };
+        let end = |name: &str| XmlToken::EndElement {
+            name: name.to_string(),
+        };
+        let parse = |tokens: Vec<XmlToken>, options: ParseOptions| {
+            read_source_with(&mut TokenList { tokens, read: 0 }, options)
+        };
+
+        let (document_node, _) = parse(
+            vec![start("a"), start("b"), end("b"), end("a")],
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(document_node.to_string(), "<a><b></b></a>");
+
+        let failure = parse(
+            vec![start("a"), start("b"), end("c"), end("a")],
+            Default::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(failure.kind(), Error::Malformed));
+        assert_eq!(failure.position().map(|position| position.byte()), Some(20));
+
+        let failure = parse(vec![start("a"), end("a"), end("a")], Default::default()).unwrap_err();
+        assert!(matches!(failure.kind(), Error::Malformed));
+        assert_eq!(failure.position().map(|position| position.byte()), Some(20));
+
+        // the end tags of elements dropped by a filter are also checked.
+        let mut options = ParseOptions::new();
+        options.add_filter(filter::DropElements::new(&["b"]));
+        let failure = parse(
+            vec![
+                start("a"),
+                start("b"),
+                start("c"),
+                end("b"),
+                end("b"),
+                end("a"),
+            ],
+            options,
+        )
+        .unwrap_err();
+        assert!(matches!(failure.kind(), Error::Malformed));
+        assert_eq!(failure.position().map(|position| position.byte()), Some(30));
+    }
+
+    const INVALID_NOTE: &str = r#"<!DOCTYPE note [
+<!ELEMENT note (to, body)>
+<!ELEMENT to (#PCDATA)>
+<!ELEMENT body (#PCDATA)>
+<!ATTLIST note priority (low|high) #REQUIRED>
+]>
+<note priority="urgent">
+  <body>Hello</body>
+</note>"#;
+
+    #[test]
+    fn test_validate_dtd_while_parsing() {
+        let mut options = ParseOptions::new();
+        options.add_validator(DtdValidator::new());
+        let (document_node, report) = validate_xml(INVALID_NOTE, options).unwrap();
+        assert_eq!(
+            report.to_string(),
+            "the value of the attribute \"priority\" is not allowed (priority) at line 7, column 1\n\
+             the content of the element does not match (to,body) (note) at line 9, column 1"
+        );
+        // the same problems are found validating the completed document.
+        assert_eq!(
+            document_node.validate_against_dtd().unwrap().len(),
+            report.len()
+        );
+
+        let mut options = ParseOptions::new();
+        options.add_validator(DtdValidator::new());
+        let (_, report) = validate_xml("<note/>", options).unwrap();
+        assert_eq!(
+            report.to_string(),
+            "the document has no document type at line 1, column 1"
+        );
+    }
+
+    #[test]
+    fn test_validate_fail_on_invalid() {
+        let mut options = ParseOptions::new();
+        options.add_validator(DtdValidator::new());
+        let (_, metrics) = read_xml_with(INVALID_NOTE, options.clone()).unwrap();
+        assert_eq!(metrics, ParserMetrics::default());
+
+        options.set_fail_on_invalid();
+        let error = read_xml_with(INVALID_NOTE, options).unwrap_err();
+        assert!(matches!(error.kind(), Error::Invalid(_)));
+        assert_eq!(error.position(), Some(Position::new(143, 7, 1)));
+        assert_eq!(
+            error.source_snippet(INVALID_NOTE).unwrap().lines().next(),
+            Some(r#"<note priority="urgent">"#)
+        );
+    }
+
+    #[test]
+    fn test_custom_validator() {
+        #[derive(Debug, Default)]
+        struct Events(RefCell<Vec<String>>);
+
+        impl ParseValidator for Rc<Events> {
+            fn element_started(&self, element: &RefNode, _: &mut ValidationReport) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("<{}>", element.node_name()));
+            }
+            fn element_ended(&self, element: &RefNode, report: &mut ValidationReport) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("</{}>", element.node_name()));
+                if element.text_content().is_some_and(|text| text.is_empty()) {
+                    report.report(element, "empty element");
+                }
+            }
+            fn document_ended(&self, _: &RefNode, _: &mut ValidationReport) {
+                self.0.borrow_mut().push("end".to_string());
+            }
+        }
+
+        let events = Rc::new(Events::default());
+        let mut options = ParseOptions::new();
+        options.add_validator(events.clone());
+        let (_, report) = validate_xml("<a><b>text</b><c/></a>", options).unwrap();
+        assert_eq!(
+            events.0.borrow().join(" "),
+            "<a> <b> </b> <c> </c> </a> end"
+        );
+        assert_eq!(report.len(), 1);
+        assert_eq!(report.violations()[0].node.node_name().to_string(), "c");
+        assert_eq!(report.violations()[0].position, Position::new(14, 1, 15));
+    }
+
     #[test]
     fn test_its_complicated() {
         test_good_xml(
@@ -0,0 +1,450 @@
+/*!
+Provides the [`ParseOptions`](struct.ParseOptions.html) structure used by
+[`read_xml_with`](../fn.read_xml_with.html) to control the behavior of the parser.
+
+# Example
+
+```rust
+use xml_dom::parser::{read_xml_with, ParseOptions};
+
+let mut options = ParseOptions::new();
+options.set_collect_metrics();
+
+let (_document, metrics) = read_xml_with("<xml><child/></xml>", options).unwrap();
+assert_eq!(metrics.nodes_created(), 2);
+```
+
+*/
+
+use crate::level2::ext::ProcessingOptions;
+use crate::parser::dtd::DtdCache;
+use crate::parser::filter::ParseFilter;
+use crate::parser::security::SecurityPolicy;
+use crate::parser::validator::ParseValidator;
+use std::rc::Rc;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This type encapsulates a set of options that a client can set that affect the parsing of
+/// documents. The default for `ParseOptions` is that none of the options are set, which results
+/// in the same behavior as [`read_xml`](../fn.read_xml.html).
+///
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    collect_metrics: bool,
+    dtd_cache: Option<Rc<DtdCache>>,
+    preserve_whitespace: bool,
+    discard_comments: bool,
+    coalesce_cdata: bool,
+    strict_namespaces: bool,
+    max_depth: Option<usize>,
+    track_positions: bool,
+    track_spans: bool,
+    preserve_character_references: bool,
+    unknown_entities: UnknownEntities,
+    processing_options: ProcessingOptions,
+    security_policy: SecurityPolicy,
+    filters: Vec<Rc<dyn ParseFilter>>,
+    validators: Vec<Rc<dyn ParseValidator>>,
+    fail_on_invalid: bool,
+    index_attributes: Vec<String>,
+    buffer_options: BufferOptions,
+}
+
+///
+/// Determines how the parser reuses the buffers into which it reads markup, see
+/// [`BufferOptions`](struct.BufferOptions.html).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BufferMode {
+    /// A single buffer is reused for every token; it grows to the size of the largest token read.
+    #[default]
+    Single,
+    /// A buffer is kept for each depth of element nesting, and reused for every token read at
+    /// that depth; a large text node only grows the buffer for the depth at which it appears.
+    PerElement,
+    /// Start with a single buffer, and change to a buffer per depth of element nesting once a
+    /// token is read that is more than sixteen times the mean size of those read before it.
+    Automatic,
+}
+
+///
+/// The options that determine how the parser reuses the buffers into which it reads markup. The
+/// default is a single buffer, which is never shrunk; for a long-running process that parses the
+/// occasional very large document, setting a maximum retained capacity releases the memory used
+/// for a large token once it has been read.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BufferOptions {
+    mode: BufferMode,
+    max_retained_capacity: Option<usize>,
+}
+
+///
+/// Determines how the parser handles a reference to an entity other than the predefined entities
+/// (`&lt;` and so on), which the parser does not expand, see
+/// [`set_unknown_entities`](struct.ParseOptions.html#method.set_unknown_entities).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownEntities {
+    /// Fail with an error; this is the behavior of [`read_xml`](../fn.read_xml.html).
+    #[default]
+    Error,
+    /// Keep the reference, for example `&foo;`, literally in the text.
+    Keep,
+    /// Replace the reference with the replacement character `U+FFFD`, and log a warning naming
+    /// the entity.
+    Replace,
+    /// Create an `EntityReference` node, between the text before and after the reference. In
+    /// attribute values the reference is kept literally, as for `Keep`.
+    Reference,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl PartialEq for ParseOptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.collect_metrics == other.collect_metrics
+            && self.preserve_whitespace == other.preserve_whitespace
+            && self.discard_comments == other.discard_comments
+            && self.coalesce_cdata == other.coalesce_cdata
+            && self.strict_namespaces == other.strict_namespaces
+            && self.max_depth == other.max_depth
+            && self.track_positions == other.track_positions
+            && self.track_spans == other.track_spans
+            && self.preserve_character_references == other.preserve_character_references
+            && self.unknown_entities == other.unknown_entities
+            && self.processing_options == other.processing_options
+            && self.security_policy == other.security_policy
+            && match (&self.dtd_cache, &other.dtd_cache) {
+                (None, None) => true,
+                (Some(lhs), Some(rhs)) => Rc::ptr_eq(lhs, rhs),
+                _ => false,
+            }
+            && self.filters.len() == other.filters.len()
+            && self
+                .filters
+                .iter()
+                .zip(&other.filters)
+                .all(|(lhs, rhs)| Rc::ptr_eq(lhs, rhs))
+            && self.validators.len() == other.validators.len()
+            && self
+                .validators
+                .iter()
+                .zip(&other.validators)
+                .all(|(lhs, rhs)| Rc::ptr_eq(lhs, rhs))
+            && self.fail_on_invalid == other.fail_on_invalid
+            && self.index_attributes == other.index_attributes
+            && self.buffer_options == other.buffer_options
+    }
+}
+
+impl Eq for ParseOptions {}
+
+impl ParseOptions {
+    ///
+    /// Construct a new `ParseOptions` instance with all options off.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Returns `true` if the parser will collect [`ParserMetrics`](../metrics/struct.ParserMetrics.html)
+    /// while parsing, else `false`.
+    ///
+    pub fn has_collect_metrics(&self) -> bool {
+        self.collect_metrics
+    }
+    ///
+    /// Collect counts and timings while parsing, these are returned from `read_xml_with`; when
+    /// not set the returned metrics are all zero.
+    ///
+    pub fn set_collect_metrics(&mut self) {
+        self.collect_metrics = true
+    }
+    ///
+    /// Returns the cache used to retrieve external DTDs, if one has been set.
+    ///
+    pub fn dtd_cache(&self) -> Option<&Rc<DtdCache>> {
+        self.dtd_cache.as_ref()
+    }
+    ///
+    /// Retrieve the external DTD named by a document's `<!DOCTYPE>` declaration using the shared
+    /// `cache`, adding its entity and notation declarations to the `DocumentType` node; when not
+    /// set external DTDs are not retrieved.
+    ///
+    pub fn set_dtd_cache(&mut self, cache: Rc<DtdCache>) {
+        self.dtd_cache = Some(cache)
+    }
+    ///
+    /// Returns `true` if whitespace in text content will be preserved, else `false`.
+    ///
+    pub fn has_preserve_whitespace(&self) -> bool {
+        self.preserve_whitespace
+    }
+    ///
+    /// Preserve leading and trailing whitespace in text content, including whitespace-only text
+    /// between elements; by default this is trimmed, and whitespace-only text is discarded, which
+    /// loses significant whitespace in mixed-content documents.
+    ///
+    pub fn set_preserve_whitespace(&mut self) {
+        self.preserve_whitespace = true
+    }
+    ///
+    /// Returns `true` if comments will be discarded rather than added to the document, else
+    /// `false`.
+    ///
+    pub fn has_discard_comments(&self) -> bool {
+        self.discard_comments
+    }
+    ///
+    /// Do not create `Comment` nodes for comments in the document.
+    ///
+    pub fn set_discard_comments(&mut self) {
+        self.discard_comments = true
+    }
+    ///
+    /// Returns `true` if CDATA sections will be added as text, else `false`.
+    ///
+    pub fn has_coalesce_cdata(&self) -> bool {
+        self.coalesce_cdata
+    }
+    ///
+    /// Add the content of CDATA sections as text, merged with any adjacent text, rather than
+    /// creating `CDataSection` nodes.
+    ///
+    pub fn set_coalesce_cdata(&mut self) {
+        self.coalesce_cdata = true
+    }
+    ///
+    /// Returns `true` if the parser will reject unbound namespace prefixes, else `false`.
+    ///
+    pub fn has_strict_namespaces(&self) -> bool {
+        self.strict_namespaces
+    }
+    ///
+    /// Fail with a namespace error if an element or attribute name uses a prefix that has not
+    /// been declared by an `xmlns:prefix` attribute on the element or one of its ancestors.
+    ///
+    pub fn set_strict_namespaces(&mut self) {
+        self.strict_namespaces = true
+    }
+    ///
+    /// Returns the maximum nesting depth of elements, if one has been set.
+    ///
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+    ///
+    /// Fail if elements are nested more than `max_depth` deep, the document element being at
+    /// depth 1; by default there is no limit.
+    ///
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = Some(max_depth)
+    }
+    ///
+    /// Returns `true` if the parser will record the position at which each node started, else
+    /// `false`.
+    ///
+    pub fn has_track_positions(&self) -> bool {
+        self.track_positions
+    }
+    ///
+    /// Record the position in the input, byte offset, line, and column, at which each node
+    /// started; these are returned by the
+    /// [`SourcePosition`](../../level2/ext/trait.SourcePosition.html) trait.
+    ///
+    pub fn set_track_positions(&mut self) {
+        self.track_positions = true
+    }
+    ///
+    /// Returns `true` if the parser will record the spans of the tags, names, and attribute values
+    /// from which elements and attributes were created, else `false`.
+    ///
+    pub fn has_track_spans(&self) -> bool {
+        self.track_spans
+    }
+    ///
+    /// Record the spans of the input from which each node was created: the start and end tags,
+    /// and name, of elements, the name and value of attributes, and the markup of other nodes;
+    /// these are returned by the [`SourceSpans`](../../level2/ext/trait.SourceSpans.html) trait,
+    /// and used by the [`preserve`](../../level2/ext/preserve/index.html) writer.
+    ///
+    pub fn set_track_spans(&mut self) {
+        self.track_spans = true
+    }
+    ///
+    /// Returns `true` if the parser will record the numeric character references in text
+    /// content, else `false`.
+    ///
+    pub fn has_preserve_character_references(&self) -> bool {
+        self.preserve_character_references
+    }
+    ///
+    /// Record each numeric character reference in text content, such as `&#xA0;`, so that the
+    /// text is written with the reference in its original form rather than the character; see
+    /// the [`CharacterReferences`](../../level2/ext/trait.CharacterReferences.html) trait.
+    /// References in attribute values are not recorded.
+    ///
+    pub fn set_preserve_character_references(&mut self) {
+        self.preserve_character_references = true
+    }
+    ///
+    /// Returns how references to unknown entities are handled.
+    ///
+    pub fn unknown_entities(&self) -> UnknownEntities {
+        self.unknown_entities
+    }
+    ///
+    /// Handle references to unknown entities, in text content and attribute values, as set by
+    /// `unknown_entities`; this allows lenient ingestion of documents that use entities, such as
+    /// `&nbsp;`, declared in a DTD that is not available.
+    ///
+    pub fn set_unknown_entities(&mut self, unknown_entities: UnknownEntities) {
+        self.unknown_entities = unknown_entities
+    }
+    ///
+    /// Returns the processing options used to create the parsed document.
+    ///
+    pub fn processing_options(&self) -> &ProcessingOptions {
+        &self.processing_options
+    }
+    ///
+    /// Create the parsed document with `options`, these also govern the construction of the
+    /// document by the parser; for example with
+    /// [`set_strict_names`](../../level2/ext/options/struct.ProcessingOptions.html#method.set_strict_names)
+    /// element and attribute names are checked against the Namespaces in XML recommendation.
+    ///
+    pub fn set_processing_options(&mut self, options: ProcessingOptions) {
+        self.processing_options = options
+    }
+    ///
+    /// Returns the security policy applied while parsing.
+    ///
+    pub fn security_policy(&self) -> &SecurityPolicy {
+        &self.security_policy
+    }
+    ///
+    /// Apply the hardening options, and limits, in `policy` while parsing; see the
+    /// [`security`](../security/index.html) module.
+    ///
+    pub fn set_security_policy(&mut self, policy: SecurityPolicy) {
+        self.security_policy = policy
+    }
+    ///
+    /// Returns the filters applied to content while parsing, in the order they were added.
+    ///
+    pub fn filters(&self) -> &[Rc<dyn ParseFilter>] {
+        &self.filters
+    }
+    ///
+    /// Add `filter` to those applied to content while parsing; content is kept only if all the
+    /// filters keep it, see the [`filter`](../filter/index.html) module.
+    ///
+    pub fn add_filter(&mut self, filter: impl ParseFilter + 'static) {
+        self.filters.push(Rc::new(filter))
+    }
+    ///
+    /// Returns the validators applied to the document while parsing, in the order they were
+    /// added.
+    ///
+    pub fn validators(&self) -> &[Rc<dyn ParseValidator>] {
+        &self.validators
+    }
+    ///
+    /// Add `validator` to those applied to the document while parsing, see the
+    /// [`validator`](../validator/index.html) module.
+    ///
+    pub fn add_validator(&mut self, validator: impl ParseValidator + 'static) {
+        self.validators.push(Rc::new(validator))
+    }
+    ///
+    /// Returns `true` if parsing will stop at the first violation reported by a validator, else
+    /// `false`.
+    ///
+    pub fn has_fail_on_invalid(&self) -> bool {
+        self.fail_on_invalid
+    }
+    ///
+    /// Stop parsing at the first violation reported by one of the validators, failing with
+    /// `Error::Invalid` at the position it was found; by default violations are collected and
+    /// parsing continues.
+    ///
+    pub fn set_fail_on_invalid(&mut self) {
+        self.fail_on_invalid = true
+    }
+    ///
+    /// Returns the names of the attributes by which the parser indexes elements.
+    ///
+    pub fn index_attributes(&self) -> &[String] {
+        &self.index_attributes
+    }
+    ///
+    /// Index the elements with an attribute with the qualified name `name` by its value, while
+    /// parsing; this avoids a second pass over a large document to build lookup tables. The
+    /// index is returned by the
+    /// [`IndexedElements`](../../level2/ext/trait.IndexedElements.html) trait.
+    ///
+    pub fn add_index_attribute(&mut self, name: &str) {
+        if !self
+            .index_attributes
+            .iter()
+            .any(|existing| existing == name)
+        {
+            self.index_attributes.push(name.to_string())
+        }
+    }
+    ///
+    /// Returns the options for the buffers into which markup is read.
+    ///
+    pub fn buffer_options(&self) -> &BufferOptions {
+        &self.buffer_options
+    }
+    ///
+    /// Read markup into buffers reused as set by `options`; these are passed to the token source
+    /// before the first token is read.
+    ///
+    pub fn set_buffer_options(&mut self, options: BufferOptions) {
+        self.buffer_options = options
+    }
+}
+
+impl BufferOptions {
+    ///
+    /// Construct a new `BufferOptions` instance with a single buffer, and no maximum capacity.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Returns how buffers are reused.
+    ///
+    pub fn mode(&self) -> BufferMode {
+        self.mode
+    }
+    ///
+    /// Reuse buffers as set by `mode`.
+    ///
+    pub fn set_mode(&mut self, mode: BufferMode) {
+        self.mode = mode
+    }
+    ///
+    /// Returns the capacity, in bytes, above which a buffer is shrunk after use, if one has been
+    /// set.
+    ///
+    pub fn max_retained_capacity(&self) -> Option<usize> {
+        self.max_retained_capacity
+    }
+    ///
+    /// Shrink a buffer to `max_retained_capacity` bytes once the token read into it is no longer
+    /// needed, if it has grown larger; by default buffers are never shrunk.
+    ///
+    pub fn set_max_retained_capacity(&mut self, max_retained_capacity: usize) {
+        self.max_retained_capacity = Some(max_retained_capacity)
+    }
+}
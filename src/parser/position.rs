@@ -0,0 +1,186 @@
+/*!
+Provides the [`Position`](struct.Position.html) structure, attached to the errors returned by
+the parser, identifying where in the input the error was detected; and, when
+[`ParseOptions::set_track_positions`](../options/struct.ParseOptions.html#method.set_track_positions)
+is set, to each node created by the parser.
+
+# Example
+
+```rust
+use xml_dom::parser::read_xml;
+
+let xml = "<root>\n  <child>\n</root>";
+let error = read_xml(xml).unwrap_err();
+let position = error.position().unwrap();
+assert_eq!((position.line(), position.column()), (3, 8));
+assert_eq!(error.source_snippet(xml).unwrap(), "</root>\n       ^");
+```
+*/
+
+use std::io::{BufRead, Read, Result as IoResult};
+
+pub use crate::level2::ext::position::{Position, Span};
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Wraps the reader given to quick-xml, recording the offset of the start of each line as the
+/// input is consumed, so that a byte offset can be turned into a line and column. It also records
+/// the first byte, other than whitespace, consumed since the last call to `mark`; this is the
+/// start of the next event read by quick-xml when it is trimming whitespace. Note that quick-xml
+/// consumes the `<` that ends a text event, so the markup following text starts before the mark.
+///
+#[derive(Debug)]
+pub(crate) struct PositionTracker<B: BufRead> {
+    inner: B,
+    consumed: u64,
+    line_starts: Vec<u64>,
+    last_byte: Option<u8>,
+    mark: u64,
+    mark_after_open: bool,
+    content_start: Option<u64>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<B: BufRead> PositionTracker<B> {
+    pub(crate) fn new(inner: B) -> Self {
+        Self {
+            inner,
+            consumed: 0,
+            line_starts: vec![0],
+            last_byte: None,
+            mark: 0,
+            mark_after_open: false,
+            content_start: None,
+        }
+    }
+
+    ///
+    /// Returns the position of `byte`, which must not be beyond the input consumed so far.
+    ///
+    pub(crate) fn position(&self, byte: u64) -> Position {
+        let line = self.line_starts.partition_point(|start| *start <= byte);
+        Position::new(byte, line as u64, byte - self.line_starts[line - 1] + 1)
+    }
+
+    ///
+    /// Mark the current offset, typically before reading the next event.
+    ///
+    pub(crate) fn mark(&mut self) {
+        self.mark = self.consumed;
+        self.mark_after_open = self.last_byte == Some(b'<');
+        self.content_start = None;
+    }
+
+    ///
+    /// Returns the position of the last mark or, if `skip_whitespace` is `true`, of the first
+    /// byte other than whitespace consumed since the mark; unless the byte before the mark was
+    /// `<`, in which case that is the start of the markup.
+    ///
+    pub(crate) fn marked_position(&self, skip_whitespace: bool) -> Position {
+        if self.mark_after_open {
+            self.position(self.mark - 1)
+        } else if skip_whitespace {
+            self.position(self.content_start.unwrap_or(self.consumed))
+        } else {
+            self.position(self.mark)
+        }
+    }
+}
+
+impl<B: BufRead> Read for PositionTracker<B> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        let available = self.fill_buf()?;
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.consume(count);
+        Ok(count)
+    }
+}
+
+impl<B: BufRead> BufRead for PositionTracker<B> {
+    fn fill_buf(&mut self) -> IoResult<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Ok(buffer) = self.inner.fill_buf() {
+            let buffer = &buffer[..amt.min(buffer.len())];
+            if let Some(byte) = buffer.last() {
+                self.last_byte = Some(*byte);
+            }
+            for (index, byte) in buffer.iter().enumerate() {
+                if *byte == b'\n' {
+                    self.line_starts.push(self.consumed + index as u64 + 1);
+                }
+                if self.content_start.is_none() && !matches!(byte, b' ' | b'\t' | b'\r' | b'\n') {
+                    self.content_start = Some(self.consumed + index as u64);
+                }
+            }
+        }
+        self.consumed += amt as u64;
+        self.inner.consume(amt)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_tracker() {
+        let input = "ab\ncd\r\n\nef";
+        let mut tracker = PositionTracker::new(input.as_bytes());
+        let mut text = String::new();
+        let _safe_to_ignore = tracker.read_to_string(&mut text).unwrap();
+        assert_eq!(text, input);
+
+        let position = tracker.position(0);
+        assert_eq!((position.line(), position.column()), (1, 1));
+        let position = tracker.position(2);
+        assert_eq!((position.line(), position.column()), (1, 3));
+        let position = tracker.position(3);
+        assert_eq!((position.line(), position.column()), (2, 1));
+        let position = tracker.position(8);
+        assert_eq!((position.line(), position.column()), (4, 1));
+        assert_eq!(position.to_string(), "line 4, column 1");
+    }
+
+    #[test]
+    fn test_snippet() {
+        let input = "<a>\r\n  <é>x</b>\n</a>";
+        let mut tracker = PositionTracker::new(input.as_bytes());
+        let _safe_to_ignore = tracker.read_to_end(&mut Vec::new()).unwrap();
+        let position = tracker.position(input.find("</b>").unwrap() as u64);
+        assert_eq!((position.line(), position.column()), (2, 8));
+        assert_eq!(position.snippet(input).unwrap(), "  <é>x</b>\n      ^");
+        assert!(tracker.position(100).snippet("").is_none());
+    }
+
+    #[test]
+    fn test_marked_position() {
+        let mut tracker = PositionTracker::new("<a>\n  <b/>".as_bytes());
+        let mut buffer = [0u8; 3];
+        tracker.read_exact(&mut buffer).unwrap();
+        tracker.mark();
+        let _safe_to_ignore = tracker.read_to_end(&mut Vec::new()).unwrap();
+        assert_eq!(tracker.marked_position(false).byte(), 3);
+        let position = tracker.marked_position(true);
+        assert_eq!((position.line(), position.column()), (2, 3));
+
+        let mut tracker = PositionTracker::new("<a>x<b/>".as_bytes());
+        let mut buffer = [0u8; 5];
+        tracker.read_exact(&mut buffer).unwrap();
+        tracker.mark();
+        assert_eq!(tracker.marked_position(true).byte(), 4);
+    }
+}
@@ -0,0 +1,265 @@
+/*!
+Provides the [`SecurityPolicy`](struct.SecurityPolicy.html) used to parse documents from untrusted
+sources, and the [`audit_xml`](fn.audit_xml.html) function that reports the risky constructs
+found while parsing.
+
+The parser never expands entity references, and only retrieves an external DTD when a
+[`DtdCache`](../dtd/struct.DtdCache.html) has been set in the
+[`ParseOptions`](../options/struct.ParseOptions.html). However, a document may still declare
+external entities, whose `Entity` nodes a client might later resolve, or reference an external
+DTD that would be retrieved through a cache shared with trusted documents; and a document may be
+arbitrarily large or deeply nested. A `SecurityPolicy` bundles the switches that neutralize these
+constructs, and limits on the size of the document, so that they can be applied together; the
+[`untrusted`](struct.SecurityPolicy.html#method.untrusted) preset sets all of them.
+
+# Example
+
+```rust
+use xml_dom::parser::security::{audit_xml, RiskyConstruct, SecurityPolicy};
+use xml_dom::parser::ParseOptions;
+
+let mut options = ParseOptions::new();
+options.set_security_policy(SecurityPolicy::untrusted());
+
+let (_, report) = audit_xml(
+    r#"<!DOCTYPE data [<!ENTITY secret SYSTEM "file:///etc/passwd">]><data/>"#,
+    options,
+).unwrap();
+assert!(!report.is_clean());
+assert_eq!(
+    report.neutralized().map(|finding| &finding.construct).collect::<Vec<_>>(),
+    vec![&RiskyConstruct::ExternalEntity("secret".to_string())]
+);
+```
+
+*/
+
+use crate::level2::RefNode;
+use crate::parser::{ParseOptions, Result};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::BufRead;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This type encapsulates the hardening options applied when parsing a document. The default for
+/// `SecurityPolicy` is that none of the options are set, which results in the same behavior as
+/// when no policy is set.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SecurityPolicy {
+    discard_external_entities: bool,
+    ignore_external_dtd: bool,
+    max_depth: Option<usize>,
+    max_nodes: Option<usize>,
+}
+
+///
+/// A construct, found while parsing, that may be used to attack the parser or its client.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RiskyConstruct {
+    /// The document has a `<!DOCTYPE>` declaration.
+    DocumentType,
+    /// The document type references an external DTD, with this system identifier.
+    ExternalDtd(String),
+    /// An external entity, with this name, was declared in the internal subset or the external
+    /// DTD.
+    ExternalEntity(String),
+}
+
+///
+/// A risky construct found while parsing, and whether it was neutralized.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecurityFinding {
+    /// The construct that was found.
+    pub construct: RiskyConstruct,
+    /// `true` if the construct had no effect on the parsed document, either because of the
+    /// `SecurityPolicy` or because the parser would not have acted on it.
+    pub neutralized: bool,
+}
+
+///
+/// The result of [`audit_xml`](fn.audit_xml.html), listing each risky construct found, in
+/// document order.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SecurityReport {
+    findings: Vec<SecurityFinding>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Parse the provided string into a DOM structure using the provided options, as
+/// [`read_xml_with`](../fn.read_xml_with.html) does, returning the document and a report of the
+/// risky constructs found while parsing.
+///
+pub fn audit_xml(xml: impl AsRef<str>, options: ParseOptions) -> Result<(RefNode, SecurityReport)> {
+    super::inner_read_audited(
+        &mut super::source::QuickXmlSource::new(xml.as_ref().as_bytes()),
+        options,
+    )
+    .map(|(document, _, report, _)| (document, report))
+    .map_err(|failure| failure.error)
+}
+
+///
+/// Parse the provided reader into a DOM structure using the provided options; see
+/// [`audit_xml`](fn.audit_xml.html).
+///
+pub fn audit_reader<B: BufRead>(
+    reader: B,
+    options: ParseOptions,
+) -> Result<(RefNode, SecurityReport)> {
+    super::inner_read_audited(&mut super::source::QuickXmlSource::new(reader), options)
+        .map(|(document, _, report, _)| (document, report))
+        .map_err(|failure| failure.error)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl SecurityPolicy {
+    ///
+    /// Construct a new `SecurityPolicy` instance with all options off.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Construct a conservative policy for documents from untrusted sources; external entities
+    /// are discarded, external DTDs are not retrieved, elements may be nested at most 256 deep,
+    /// and at most 1,000,000 nodes may be created.
+    ///
+    pub fn untrusted() -> Self {
+        Self {
+            discard_external_entities: true,
+            ignore_external_dtd: true,
+            max_depth: Some(256),
+            max_nodes: Some(1_000_000),
+        }
+    }
+    ///
+    /// Returns `true` if external entity declarations will be discarded, else `false`.
+    ///
+    pub fn has_discard_external_entities(&self) -> bool {
+        self.discard_external_entities
+    }
+    ///
+    /// Do not create `Entity` nodes for external entities, those declared with a `SYSTEM` or
+    /// `PUBLIC` identifier, so that a client can not be led to resolve them.
+    ///
+    pub fn set_discard_external_entities(&mut self) {
+        self.discard_external_entities = true
+    }
+    ///
+    /// Returns `true` if external DTDs will not be retrieved, else `false`.
+    ///
+    pub fn has_ignore_external_dtd(&self) -> bool {
+        self.ignore_external_dtd
+    }
+    ///
+    /// Do not retrieve the external DTD named by the document type, even if a `DtdCache` has
+    /// been set in the `ParseOptions`.
+    ///
+    pub fn set_ignore_external_dtd(&mut self) {
+        self.ignore_external_dtd = true
+    }
+    ///
+    /// Returns the maximum nesting depth of elements, if one has been set.
+    ///
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+    ///
+    /// Fail if elements are nested more than `max_depth` deep; if a depth is also set in the
+    /// `ParseOptions` the smaller of the two applies.
+    ///
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = Some(max_depth)
+    }
+    ///
+    /// Returns the maximum number of nodes the parser may create, if one has been set.
+    ///
+    pub fn max_nodes(&self) -> Option<usize> {
+        self.max_nodes
+    }
+    ///
+    /// Fail if parsing would create more than `max_nodes` nodes, including attributes.
+    ///
+    pub fn set_max_nodes(&mut self, max_nodes: usize) {
+        self.max_nodes = Some(max_nodes)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for RiskyConstruct {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            RiskyConstruct::DocumentType => write!(f, "document type declaration"),
+            RiskyConstruct::ExternalDtd(system_id) => write!(f, "external DTD {:?}", system_id),
+            RiskyConstruct::ExternalEntity(name) => write!(f, "external entity `{}`", name),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for SecurityFinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.neutralized {
+            write!(f, "{} (neutralized)", self.construct)
+        } else {
+            write!(f, "{}", self.construct)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for SecurityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let lines: Vec<String> = self
+            .findings
+            .iter()
+            .map(SecurityFinding::to_string)
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl SecurityReport {
+    ///
+    /// Returns `true` if no risky constructs were found, else `false`.
+    ///
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+    ///
+    /// Returns every risky construct found, in document order.
+    ///
+    pub fn findings(&self) -> &Vec<SecurityFinding> {
+        &self.findings
+    }
+    ///
+    /// Returns the risky constructs that were neutralized.
+    ///
+    pub fn neutralized(&self) -> impl Iterator<Item = &SecurityFinding> {
+        self.findings.iter().filter(|finding| finding.neutralized)
+    }
+
+    pub(crate) fn push(&mut self, construct: RiskyConstruct, neutralized: bool) {
+        self.findings.push(SecurityFinding {
+            construct,
+            neutralized,
+        })
+    }
+}
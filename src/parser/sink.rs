@@ -0,0 +1,338 @@
+/*!
+Provides the [`DomSink`](struct.DomSink.html) structure, a push interface to the DOM builder so
+that an event-based parser, such as [xml-rs](https://crates.io/crates/xml-rs) or a bridge to a
+SAX parser, may build a document without the quick-xml tokenizer.
+
+The parser calls `start_element`, `characters`, `end_element`, and so on, in document order, then
+calls [`finish`](struct.DomSink.html#method.finish) to build the document. Text and attribute
+values are pushed as the parser reports them, with references already replaced; the document is
+built by the same builder as the `read_*` functions, so the
+[`ParseOptions`](../options/struct.ParseOptions.html) given to
+[`finish_with`](struct.DomSink.html#method.finish_with) apply, and the structure of the events is
+checked in the same way. Errors in the structure, such as an end tag that does not match its start
+tag, are returned by `finish`, at the position set by
+[`set_position`](struct.DomSink.html#method.set_position) before the offending event was pushed.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::parser::sink::DomSink;
+
+let mut sink = DomSink::new();
+sink.declaration("1.0", Some("UTF-8"), None);
+sink.start_element("greeting", &[("lang", "en")]);
+sink.characters("Hello & welcome");
+sink.comment(" a comment ");
+sink.end_element("greeting");
+
+let document_node = sink.finish().unwrap();
+let document = as_document(&document_node).unwrap();
+let root_node = document.document_element().unwrap();
+assert_eq!(root_node.get_attribute("lang"), Some("en".to_string()));
+assert_eq!(
+    root_node.first_child().unwrap().node_value(),
+    Some("Hello & welcome".to_string())
+);
+```
+
+*/
+
+use crate::level2::RefNode;
+use crate::parser::source::{XmlToken, XmlTokenSource};
+use crate::parser::{read_source_with, Error, ParseOptions, ParserMetrics, Position, Result};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Receives the events of an event-based parser, in document order, and builds a document from
+/// them.
+///
+#[derive(Clone, Debug)]
+pub struct DomSink {
+    tokens: Vec<(XmlToken, Position)>,
+    position: Position,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the tokens pushed to a sink to the builder.
+///
+#[derive(Debug)]
+struct PushedTokens {
+    tokens: std::vec::IntoIter<(XmlToken, Position)>,
+    trimming: bool,
+    position: Position,
+    // the names of the elements started and not yet ended.
+    open: Vec<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for DomSink {
+    fn default() -> Self {
+        Self {
+            tokens: Vec::new(),
+            position: Position::new(0, 1, 1),
+        }
+    }
+}
+
+impl DomSink {
+    ///
+    /// Construct a new, empty, sink.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+    ///
+    /// Set the position in the input of the events pushed from now on; this is recorded on the
+    /// nodes created from them when positions are tracked, and attached to errors detected while
+    /// building the document. By default every event is at line 1, column 1.
+    ///
+    pub fn set_position(&mut self, position: Position) {
+        self.position = position;
+    }
+    ///
+    /// Push the XML declaration.
+    ///
+    pub fn declaration(&mut self, version: &str, encoding: Option<&str>, standalone: Option<bool>) {
+        self.push(XmlToken::Declaration {
+            version: Some(version.to_string()),
+            encoding: encoding.map(str::to_string),
+            standalone,
+        });
+    }
+    ///
+    /// Push the document type declaration, `text` is everything between `<!DOCTYPE` and the
+    /// closing `>`, including any internal subset.
+    ///
+    pub fn document_type(&mut self, text: &str) {
+        self.push(XmlToken::DocumentType(text.to_string()));
+    }
+    ///
+    /// Push a start tag, with the qualified names and values of its attributes in the order
+    /// written; namespace declarations are included as attributes.
+    ///
+    pub fn start_element(&mut self, name: &str, attributes: &[(&str, &str)]) {
+        self.push(XmlToken::StartElement {
+            name: name.to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(name, value)| (name.to_string(), escape_references(value)))
+                .collect(),
+            empty: false,
+        });
+    }
+    ///
+    /// Push an end tag; an end tag must be pushed for every start tag, including those of empty
+    /// elements.
+    ///
+    pub fn end_element(&mut self, name: &str) {
+        self.push(XmlToken::EndElement {
+            name: name.to_string(),
+        });
+    }
+    ///
+    /// Push character data, outside any CDATA section.
+    ///
+    pub fn characters(&mut self, text: &str) {
+        self.push(XmlToken::Text(escape_references(text)));
+    }
+    ///
+    /// Push the content of a CDATA section.
+    ///
+    pub fn cdata(&mut self, text: &str) {
+        self.push(XmlToken::CData(text.to_string()));
+    }
+    ///
+    /// Push the content of a comment.
+    ///
+    pub fn comment(&mut self, text: &str) {
+        self.push(XmlToken::Comment(text.to_string()));
+    }
+    ///
+    /// Push a processing instruction.
+    ///
+    pub fn processing_instruction(&mut self, target: &str, data: Option<&str>) {
+        self.push(XmlToken::ProcessingInstruction(match data {
+            None => target.to_string(),
+            Some(data) => format!("{} {}", target, data),
+        }));
+    }
+    ///
+    /// Build a document from the events pushed; if the result is OK, the result returned can be
+    /// safely assumed to be a `Document` node.
+    ///
+    pub fn finish(self) -> Result<RefNode> {
+        self.finish_with(Default::default())
+            .map(|(document, _)| document)
+    }
+    ///
+    /// Build a document from the events pushed using the provided options, also returning the
+    /// metrics collected; see [`finish`](#method.finish).
+    ///
+    pub fn finish_with(self, options: ParseOptions) -> Result<(RefNode, ParserMetrics)> {
+        let mut source = PushedTokens {
+            tokens: self.tokens.into_iter(),
+            trimming: false,
+            position: self.position,
+            open: Vec::new(),
+        };
+        read_source_with(&mut source, options)
+    }
+
+    fn push(&mut self, token: XmlToken) {
+        self.tokens.push((token, self.position));
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl XmlTokenSource for PushedTokens {
+    fn set_trim_text(&mut self, trim: bool) {
+        self.trimming = trim;
+    }
+
+    fn next_token(&mut self) -> Result<XmlToken> {
+        for (token, position) in self.tokens.by_ref() {
+            self.position = position;
+            match token {
+                XmlToken::Text(text) if self.trimming => {
+                    let trimmed = text.trim_matches(|c| matches!(c, ' ' | '\t' | '\r' | '\n'));
+                    if !trimmed.is_empty() {
+                        return Ok(XmlToken::Text(trimmed.to_string()));
+                    }
+                }
+                XmlToken::StartElement {
+                    ref name,
+                    empty: false,
+                    ..
+                } => {
+                    self.open.push(name.clone());
+                    return Ok(token);
+                }
+                XmlToken::EndElement { ref name } => {
+                    // the builder relies on the tokenizer to match end tags to start tags.
+                    if self.open.pop().as_ref() != Some(name) {
+                        error!("end tag '{}' does not match the open element", name);
+                        return Error::Malformed.into();
+                    }
+                    return Ok(token);
+                }
+                token => return Ok(token),
+            }
+        }
+        Ok(XmlToken::Eof)
+    }
+
+    fn token_position(&self) -> Position {
+        self.position
+    }
+
+    fn position(&self) -> Position {
+        self.position
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The builder replaces references in text and attribute values, so a `&` in the text reported by
+/// the parser is written as the predefined entity; `<` does not need to be escaped in a token.
+///
+fn escape_references(text: &str) -> String {
+    text.replace('&', "&amp;")
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level2::ext::SourcePosition;
+    use crate::parser::read_xml;
+
+    fn push_example(sink: &mut DomSink) {
+        sink.declaration("1.0", None, Some(true));
+        sink.processing_instruction("xml-stylesheet", Some("href=\"style.xsl\""));
+        sink.start_element(
+            "rdf:RDF",
+            &[("xmlns:rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#")],
+        );
+        sink.characters("\n  ");
+        sink.set_position(Position::new(80, 2, 3));
+        sink.start_element("rdf:Description", &[("rdf:about", "a&b <c>")]);
+        sink.characters("some ");
+        sink.cdata("<raw>");
+        sink.characters(" text");
+        sink.end_element("rdf:Description");
+        sink.characters("\n");
+        sink.end_element("rdf:RDF");
+        sink.comment(" the end ");
+    }
+
+    #[test]
+    fn test_sink_matches_parser() {
+        let mut sink = DomSink::new();
+        push_example(&mut sink);
+        let document_node = sink.finish().unwrap();
+
+        let parsed_node = read_xml(
+            "<?xml version=\"1.0\" standalone=\"yes\"?>\
+             <?xml-stylesheet href=\"style.xsl\"?>\
+             <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n  \
+             <rdf:Description rdf:about=\"a&amp;b &lt;c>\">some <![CDATA[<raw>]]> text\
+             </rdf:Description>\n</rdf:RDF><!-- the end -->",
+        )
+        .unwrap();
+        assert_eq!(document_node.to_string(), parsed_node.to_string());
+
+        let description_node = document_node.child_nodes()[1].first_child().unwrap();
+        assert_eq!(description_node.child_nodes().len(), 3);
+    }
+
+    #[test]
+    fn test_sink_options() {
+        let mut sink = DomSink::new();
+        push_example(&mut sink);
+        let mut options = ParseOptions::new();
+        options.set_preserve_whitespace();
+        options.set_discard_comments();
+        options.set_track_positions();
+        let (document_node, _) = sink.finish_with(options).unwrap();
+
+        assert_eq!(document_node.child_nodes().len(), 2);
+        let root_node = document_node.child_nodes()[1].clone();
+        assert_eq!(root_node.child_nodes().len(), 3);
+        let description_node = root_node.child_nodes()[1].clone();
+        assert_eq!(
+            description_node.source_position(),
+            Some(Position::new(80, 2, 3))
+        );
+    }
+
+    #[test]
+    fn test_sink_mismatched_end() {
+        let mut sink = DomSink::new();
+        sink.start_element("root", &[]);
+        sink.set_position(Position::new(6, 1, 7));
+        sink.end_element("other");
+
+        let error = sink.finish().unwrap_err();
+        assert!(matches!(error.kind(), Error::Malformed));
+        assert_eq!(error.position(), Some(Position::new(6, 1, 7)));
+    }
+}
@@ -0,0 +1,431 @@
+/*!
+Provides the [`XmlTokenSource`](trait.XmlTokenSource.html) trait, the boundary between the
+tokenizer that reads markup and the builder that constructs the DOM from it. The builder consumes
+[`XmlToken`](enum.XmlToken.html) values, and handles everything above the level of markup itself:
+entity references, namespaces, DTDs, filters, limits, and positions; so an alternative tokenizer
+need only implement this trait to be used with
+[`read_source_with`](../fn.read_source_with.html).
+
+The [`QuickXmlSource`](struct.QuickXmlSource.html) tokenizer, using the
+[quick-xml](https://crates.io/crates/quick-xml) crate, is used by all of the other `read_*`
+functions.
+
+# Example
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::convert::as_document;
+use xml_dom::parser::read_source_with;
+use xml_dom::parser::source::{XmlToken, XmlTokenSource};
+use xml_dom::parser::{Position, Result};
+
+///
+/// Produces a fixed sequence of tokens.
+///
+struct Tokens(std::vec::IntoIter<XmlToken>);
+
+impl XmlTokenSource for Tokens {
+    fn set_trim_text(&mut self, _trim: bool) {}
+
+    fn next_token(&mut self) -> Result<XmlToken> {
+        Ok(self.0.next().unwrap_or(XmlToken::Eof))
+    }
+
+    fn token_position(&self) -> Position {
+        self.position()
+    }
+
+    fn position(&self) -> Position {
+        Position::new(0, 1, 1)
+    }
+}
+
+let mut source = Tokens(
+    vec![
+        XmlToken::StartElement {
+            name: "greeting".to_string(),
+            attributes: vec![("lang".to_string(), "en".to_string())],
+            empty: false,
+        },
+        XmlToken::Text("Hello &amp; welcome".to_string()),
+        XmlToken::EndElement {
+            name: "greeting".to_string(),
+        },
+    ]
+    .into_iter(),
+);
+
+let (document_node, _) = read_source_with(&mut source, Default::default()).unwrap();
+let document = as_document(&document_node).unwrap();
+let root_node = document.document_element().unwrap();
+assert_eq!(root_node.get_attribute("lang"), Some("en".to_string()));
+assert_eq!(
+    root_node.first_child().unwrap().node_value(),
+    Some("Hello & welcome".to_string())
+);
+```
+
+*/
+
+use crate::parser::options::{BufferMode, BufferOptions};
+use crate::parser::position::{Position, PositionTracker, Span};
+use crate::parser::{unquote, Result};
+use quick_xml::encoding::Decoder;
+use quick_xml::events::{BytesDecl, BytesStart, Event};
+use quick_xml::reader::Reader;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::io::BufRead;
+use std::ops::Range;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A unit of markup read by an [`XmlTokenSource`](trait.XmlTokenSource.html). Text, and attribute
+/// values, are as they were written in the input, the builder replaces entity and character
+/// references in them.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XmlToken {
+    /// An XML declaration, or the text declaration of an external entity, in which the version
+    /// is optional.
+    Declaration {
+        /// The value of the `version` pseudo-attribute.
+        version: Option<String>,
+        /// The value of the `encoding` pseudo-attribute.
+        encoding: Option<String>,
+        /// The value of the `standalone` pseudo-attribute, `true` if it is `yes`.
+        standalone: Option<bool>,
+    },
+    /// A document type declaration, the text between `<!DOCTYPE` and the closing `>`.
+    DocumentType(String),
+    /// A start tag, or an empty element tag if `empty` is `true`; no `EndElement` follows an empty
+    /// element tag.
+    StartElement {
+        /// The qualified name of the element.
+        name: String,
+        /// The qualified names, and unquoted values, of the attributes in the order written.
+        attributes: Vec<(String, String)>,
+        /// `true` if this is an empty element tag.
+        empty: bool,
+    },
+    /// An end tag.
+    EndElement {
+        /// The qualified name of the element.
+        name: String,
+    },
+    /// Character data, outside any CDATA section.
+    Text(String),
+    /// The content of a CDATA section.
+    CData(String),
+    /// The content of a comment.
+    Comment(String),
+    /// The content of a processing instruction, the target followed by any data.
+    ProcessingInstruction(String),
+    /// The end of the input; the builder does not read any more tokens after this.
+    Eof,
+}
+
+///
+/// Implemented by a tokenizer that reads markup for the DOM builder. The builder reads tokens
+/// until it reads [`XmlToken::Eof`](enum.XmlToken.html#variant.Eof), or the source returns an
+/// error; errors should be returned as one of the variants of
+/// [`Error`](../enum.Error.html), tokenizers other than quick-xml may use
+/// [`Error::Source`](../enum.Error.html#variant.Source).
+///
+pub trait XmlTokenSource {
+    ///
+    /// Called before the first token is read. If `trim` is `true` the source removes whitespace
+    /// from the start and end of `Text` tokens, and does not return those with only whitespace.
+    ///
+    fn set_trim_text(&mut self, trim: bool);
+    ///
+    /// Called before the first token is read, with the options for the buffers into which the
+    /// source reads markup; the default ignores them, as a source need not use buffers.
+    ///
+    fn set_buffer_options(&mut self, _options: BufferOptions) {}
+    ///
+    /// Read the next token from the input.
+    ///
+    fn next_token(&mut self) -> Result<XmlToken>;
+    ///
+    /// Returns the position in the input at which the last token read started, after any
+    /// whitespace that was trimmed. This is recorded on the nodes created for the token when
+    /// positions are tracked.
+    ///
+    fn token_position(&self) -> Position;
+    ///
+    /// Returns the position in the input the source has read up to; this is attached to errors
+    /// detected by the builder.
+    ///
+    fn position(&self) -> Position;
+    ///
+    /// Returns the spans of the name, and of the value between the quotes, of each attribute of
+    /// the last token read, in the order of its `attributes`, if it was a `StartElement`. This is
+    /// only called when spans are tracked; the default returns no spans, in which case none are
+    /// recorded for attributes.
+    ///
+    fn attribute_spans(&self) -> Vec<(Span, Span)> {
+        Vec::new()
+    }
+}
+
+///
+/// The token source used by the `read_*` functions, reading UTF-8 input with the
+/// [quick-xml](https://crates.io/crates/quick-xml) crate.
+///
+pub struct QuickXmlSource<B: BufRead> {
+    reader: Reader<PositionTracker<B>>,
+    // the first buffer is used for every token, unless there is a buffer per element depth.
+    buffers: Vec<Vec<u8>>,
+    // the index of the buffer holding the last token read.
+    current: usize,
+    depth: usize,
+    buffer_options: BufferOptions,
+    per_element: bool,
+    tokens_read: u64,
+    bytes_read: u64,
+    trimming: bool,
+    // `true` if the last token read was a start tag, and so its content is in the buffer.
+    start_tag: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// In `BufferMode::Automatic`, a token this many times the mean size of those before it changes
+/// to a buffer per element depth.
+///
+const AUTOMATIC_SIZE_RATIO: u64 = 16;
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<B: BufRead> Debug for QuickXmlSource<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("QuickXmlSource")
+            .field("position", &self.position())
+            .field("trimming", &self.trimming)
+            .field("retained_capacity", &self.retained_capacity())
+            .finish()
+    }
+}
+
+impl<B: BufRead> XmlTokenSource for QuickXmlSource<B> {
+    fn set_trim_text(&mut self, trim: bool) {
+        self.reader.config_mut().trim_text(trim);
+        self.trimming = trim;
+    }
+
+    fn set_buffer_options(&mut self, options: BufferOptions) {
+        self.per_element = options.mode() == BufferMode::PerElement;
+        self.buffer_options = options;
+    }
+
+    fn next_token(&mut self) -> Result<XmlToken> {
+        self.release_buffer();
+        self.reader.get_mut().mark();
+        self.current = if self.per_element { self.depth } else { 0 };
+        if self.buffers.len() <= self.current {
+            self.buffers.resize_with(self.current + 1, Vec::new);
+        }
+        let buffer = &mut self.buffers[self.current];
+        buffer.clear();
+        let start = self.reader.buffer_position();
+        let event = self.reader.read_event_into(buffer)?;
+        let decoder = self.reader.decoder();
+        self.start_tag = matches!(event, Event::Start(_) | Event::Empty(_));
+        match event {
+            Event::Start(_) => self.depth += 1,
+            Event::End(_) => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+        let length = self.reader.buffer_position() - start;
+        if self.buffer_options.mode() == BufferMode::Automatic
+            && !self.per_element
+            && self.tokens_read > 0
+            && length > AUTOMATIC_SIZE_RATIO * (self.bytes_read / self.tokens_read).max(1)
+        {
+            self.per_element = true;
+        }
+        self.tokens_read += 1;
+        self.bytes_read += length;
+        Ok(match event {
+            Event::Decl(ev) => declaration(decoder, ev)?,
+            Event::DocType(ev) => XmlToken::DocumentType(decoder.decode(ev.as_ref())?.to_string()),
+            Event::Start(ev) => start_element(decoder, ev, false)?,
+            Event::Empty(ev) => start_element(decoder, ev, true)?,
+            Event::End(ev) => XmlToken::EndElement {
+                name: decoder.decode(ev.name().into_inner())?.to_string(),
+            },
+            Event::Text(ev) => XmlToken::Text(decoder.decode(ev.as_ref())?.to_string()),
+            Event::CData(ev) => {
+                XmlToken::CData(decoder.decode(ev.into_inner().as_ref())?.to_string())
+            }
+            Event::Comment(ev) => XmlToken::Comment(ev.unescape()?.to_string()),
+            Event::PI(ev) => {
+                XmlToken::ProcessingInstruction(decoder.decode(ev.as_ref())?.to_string())
+            }
+            Event::Eof => XmlToken::Eof,
+        })
+    }
+
+    fn token_position(&self) -> Position {
+        self.reader.get_ref().marked_position(self.trimming)
+    }
+
+    fn position(&self) -> Position {
+        self.reader
+            .get_ref()
+            .position(self.reader.buffer_position())
+    }
+
+    fn attribute_spans(&self) -> Vec<(Span, Span)> {
+        if !self.start_tag {
+            return Vec::new();
+        }
+        // the buffer holds the content of the tag, following its `<`.
+        let tracker = self.reader.get_ref();
+        let start = self.token_position().byte() + 1;
+        let span = |range: Range<usize>| {
+            Span::new(
+                tracker.position(start + range.start as u64),
+                tracker.position(start + range.end as u64),
+            )
+        };
+        attribute_ranges(&self.buffers[self.current])
+            .into_iter()
+            .map(|(name, value)| (span(name), span(value)))
+            .collect()
+    }
+}
+
+impl<B: BufRead> QuickXmlSource<B> {
+    ///
+    /// Construct a new source reading from `input`, which must be UTF-8 text.
+    ///
+    pub fn new(input: B) -> Self {
+        Self {
+            reader: Reader::from_reader(PositionTracker::new(input)),
+            buffers: vec![Vec::new()],
+            current: 0,
+            depth: 0,
+            buffer_options: Default::default(),
+            per_element: false,
+            tokens_read: 0,
+            bytes_read: 0,
+            trimming: false,
+            start_tag: false,
+        }
+    }
+    ///
+    /// Returns the total capacity, in bytes, of the buffers kept by this source for reading
+    /// markup.
+    ///
+    pub fn retained_capacity(&self) -> usize {
+        self.buffers.iter().map(|buffer| buffer.capacity()).sum()
+    }
+
+    // shrink the buffer holding the last token read, if it has grown too large.
+    fn release_buffer(&mut self) {
+        if let Some(max_retained_capacity) = self.buffer_options.max_retained_capacity() {
+            let buffer = &mut self.buffers[self.current];
+            if buffer.capacity() > max_retained_capacity {
+                buffer.clear();
+                buffer.shrink_to(max_retained_capacity);
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn declaration(decoder: Decoder, ev: BytesDecl<'_>) -> Result<XmlToken> {
+    // the version is optional in the text declaration of an external entity.
+    let version = match ev.version() {
+        Ok(version) => Some(unquote(decoder.decode(&version)?.to_string())?),
+        Err(_) => None,
+    };
+    let encoding = match ev.encoding() {
+        Some(encoding) => Some(decoder.decode(&encoding?)?.to_string()),
+        None => None,
+    };
+    let standalone = match ev.standalone() {
+        Some(standalone) => Some(decoder.decode(&standalone?)? == "yes"),
+        None => None,
+    };
+    Ok(XmlToken::Declaration {
+        version,
+        encoding,
+        standalone,
+    })
+}
+
+fn start_element(decoder: Decoder, ev: BytesStart<'_>, empty: bool) -> Result<XmlToken> {
+    let mut attributes = Vec::with_capacity(ev.attributes().with_checks(false).count());
+    for attribute in ev.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        attributes.push((
+            decoder.decode(attribute.key.into_inner())?.to_string(),
+            decoder.decode(&attribute.value)?.to_string(),
+        ));
+    }
+    Ok(XmlToken::StartElement {
+        name: decoder.decode(ev.name().into_inner())?.to_string(),
+        attributes,
+        empty,
+    })
+}
+
+///
+/// Returns the byte ranges of the name, and of the value between the quotes, of each attribute in
+/// `tag`, the content of a start tag following its `<`.
+///
+pub(crate) fn attribute_ranges(tag: &[u8]) -> Vec<(Range<usize>, Range<usize>)> {
+    let is_space = |byte: &u8| matches!(byte, b' ' | b'\t' | b'\r' | b'\n');
+    let skip_space = |mut index: usize| {
+        while tag.get(index).is_some_and(is_space) {
+            index += 1;
+        }
+        index
+    };
+    let mut ranges = Vec::new();
+    let mut index = tag
+        .iter()
+        .position(|byte| is_space(byte) || *byte == b'/')
+        .unwrap_or(tag.len());
+    loop {
+        index = skip_space(index);
+        let name_start = index;
+        while tag
+            .get(index)
+            .is_some_and(|byte| !is_space(byte) && !matches!(byte, b'=' | b'/'))
+        {
+            index += 1;
+        }
+        let name_end = index;
+        index = skip_space(index);
+        if name_start == name_end || tag.get(index) != Some(&b'=') {
+            return ranges;
+        }
+        index = skip_space(index + 1);
+        let quote = match tag.get(index) {
+            Some(quote @ (b'"' | b'\'')) => *quote,
+            _ => return ranges,
+        };
+        let value_start = index + 1;
+        match tag[value_start..].iter().position(|byte| *byte == quote) {
+            None => return ranges,
+            Some(length) => {
+                ranges.push((name_start..name_end, value_start..value_start + length));
+                index = value_start + length + 1;
+            }
+        }
+    }
+}
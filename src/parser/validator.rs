@@ -0,0 +1,290 @@
+/*!
+Provides the [`ParseValidator`](trait.ParseValidator.html) trait, used to validate a document as
+it is parsed rather than in a second pass over the completed DOM. Validators are added to the
+[`ParseOptions`](../options/struct.ParseOptions.html) with
+[`add_validator`](../options/struct.ParseOptions.html#method.add_validator), and each violation
+they report is recorded with the position in the input of the markup being parsed when it was
+found; the start tag for a problem with an element's attributes, the end tag for a problem with
+its content.
+
+The functions [`validate_xml`](fn.validate_xml.html) and
+[`validate_reader`](fn.validate_reader.html) return the document, and a
+[`ValidationReport`](struct.ValidationReport.html) of the violations found. With
+[`ParseOptions::set_fail_on_invalid`](../options/struct.ParseOptions.html#method.set_fail_on_invalid)
+parsing instead stops at the first violation, with the error
+[`Error::Invalid`](../enum.Error.html#variant.Invalid) at its position; this is also the only way
+violations are reported by the other parsing functions.
+
+The validator [`DtdValidator`](struct.DtdValidator.html) checks elements against the declarations
+in the document's internal subset, as the
+[`DtdValidation`](../../level2/ext/trait.DtdValidation.html) trait does for a completed document.
+
+# Example
+
+```rust
+use xml_dom::parser::validator::{validate_xml, DtdValidator};
+use xml_dom::parser::ParseOptions;
+
+let mut options = ParseOptions::new();
+options.add_validator(DtdValidator::new());
+
+let (_, report) = validate_xml(
+    r#"<!DOCTYPE note [
+<!ELEMENT note (body)>
+<!ELEMENT body (#PCDATA)>
+]>
+<note><body>Hello</body><body/></note>"#,
+    options,
+)
+.unwrap();
+assert_eq!(report.len(), 1);
+assert_eq!(report.violations()[0].position.line(), 5);
+assert_eq!(report.violations()[0].position.column(), 32);
+```
+
+*/
+
+use crate::level2::convert::{as_document, is_document};
+use crate::level2::ext::dtd::{DtdRules, DtdViolation};
+use crate::level2::node_impl::{RefNode, WeakRefNode};
+use crate::parser::{ParseOptions, Position, Result};
+use std::cell::RefCell;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::io::BufRead;
+use std::rc::Rc;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Implemented by a validator applied to a document as it is parsed. Each method is called once
+/// the corresponding part of the document has been added to the DOM, and reports any problems it
+/// finds to `report`; by default nothing is checked.
+///
+pub trait ParseValidator: Debug {
+    ///
+    /// Called once the start tag of `element` has been read; its attributes have been added, but
+    /// none of its content.
+    ///
+    fn element_started(&self, _element: &RefNode, _report: &mut ValidationReport) {}
+    ///
+    /// Called once the end tag of `element` has been read, or directly after `element_started`
+    /// for an empty element; all of its content has been added.
+    ///
+    fn element_ended(&self, _element: &RefNode, _report: &mut ValidationReport) {}
+    ///
+    /// Called once the whole of `document` has been read.
+    ///
+    fn document_ended(&self, _document: &RefNode, _report: &mut ValidationReport) {}
+}
+
+///
+/// A single problem reported by a validator while parsing.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseViolation {
+    /// The node found to be invalid.
+    pub node: RefNode,
+    /// A description of the problem.
+    pub message: String,
+    /// The position in the input of the markup being parsed when the problem was found.
+    pub position: Position,
+}
+
+///
+/// The result of [`validate_xml`](fn.validate_xml.html), listing each violation reported by the
+/// validators, in the order they were found.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationReport {
+    violations: Vec<ParseViolation>,
+    position: Position,
+}
+
+///
+/// A validator that checks each element against the element type and attribute list declarations
+/// in the internal subset of the document's type; a document without a document type is reported
+/// as invalid.
+///
+#[derive(Debug, Default)]
+pub struct DtdValidator {
+    rules: RefCell<Option<(WeakRefNode, Option<Rc<DtdRules>>)>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Parse the provided string into a DOM structure using the provided options, as
+/// [`read_xml_with`](../fn.read_xml_with.html) does, returning the document and a report of the
+/// violations found by the validators in the options.
+///
+pub fn validate_xml(
+    xml: impl AsRef<str>,
+    options: ParseOptions,
+) -> Result<(RefNode, ValidationReport)> {
+    super::inner_read_audited(
+        &mut super::source::QuickXmlSource::new(xml.as_ref().as_bytes()),
+        options,
+    )
+    .map(|(document, _, _, report)| (document, report))
+    .map_err(|failure| failure.error)
+}
+
+///
+/// Parse the provided reader into a DOM structure using the provided options; see
+/// [`validate_xml`](fn.validate_xml.html).
+///
+pub fn validate_reader<B: BufRead>(
+    reader: B,
+    options: ParseOptions,
+) -> Result<(RefNode, ValidationReport)> {
+    super::inner_read_audited(&mut super::source::QuickXmlSource::new(reader), options)
+        .map(|(document, _, _, report)| (document, report))
+        .map_err(|failure| failure.error)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ParseViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} at {}", self.message, self.position)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let lines: Vec<String> = self
+            .violations
+            .iter()
+            .map(ParseViolation::to_string)
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl ValidationReport {
+    ///
+    /// Returns the number of violations reported.
+    ///
+    pub fn len(&self) -> usize {
+        self.violations.len()
+    }
+    ///
+    /// Returns `true` if no violations were reported, else `false`.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.violations.is_empty()
+    }
+    ///
+    /// Returns every violation reported, in the order they were found.
+    ///
+    pub fn violations(&self) -> &[ParseViolation] {
+        &self.violations
+    }
+    ///
+    /// Report that `node` is invalid, described by `message`, at the position of the markup
+    /// being parsed.
+    ///
+    pub fn report(&mut self, node: &RefNode, message: impl Into<String>) {
+        self.violations.push(ParseViolation {
+            node: node.clone(),
+            message: message.into(),
+            position: self.position,
+        })
+    }
+
+    pub(crate) fn new(position: Position) -> Self {
+        Self {
+            violations: Default::default(),
+            position,
+        }
+    }
+
+    pub(crate) fn set_position(&mut self, position: Position) {
+        self.position = position
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ParseValidator for DtdValidator {
+    fn element_started(&self, element: &RefNode, report: &mut ValidationReport) {
+        let is_document_element = element
+            .parent_node()
+            .is_some_and(|parent| is_document(&parent));
+        let mut violations = Vec::new();
+        match self.rules(element) {
+            None if is_document_element => {
+                report.report(element, "the document has no document type");
+            }
+            None => {}
+            Some(rules) => {
+                if is_document_element {
+                    rules.check_document_element(element, &mut violations);
+                }
+                rules.check_attributes(element, &mut violations);
+            }
+        }
+        report_all(report, violations);
+    }
+
+    fn element_ended(&self, element: &RefNode, report: &mut ValidationReport) {
+        let mut violations = Vec::new();
+        if let Some(rules) = self.rules(element) {
+            rules.check_element(element, &mut violations);
+        }
+        report_all(report, violations);
+    }
+}
+
+impl DtdValidator {
+    ///
+    /// Construct a new validator.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    ///
+    /// Returns the declarations of the document containing `element`, collected when the first
+    /// element of that document is validated; `None` if it has no document type, or its internal
+    /// subset cannot be parsed.
+    ///
+    fn rules(&self, element: &RefNode) -> Option<Rc<DtdRules>> {
+        let document_node = element.owner_document()?;
+        let mut cached = self.rules.borrow_mut();
+        if let Some((document, rules)) = &*cached {
+            if document
+                .clone()
+                .upgrade()
+                .is_some_and(|document| document == document_node)
+            {
+                return rules.clone();
+            }
+        }
+        let rules = as_document(&document_node)
+            .ok()
+            .and_then(|document| document.doc_type())
+            .and_then(|doc_type| DtdRules::new(&doc_type).ok())
+            .map(Rc::new);
+        *cached = Some((document_node.clone().downgrade(), rules.clone()));
+        rules
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn report_all(report: &mut ValidationReport, violations: Vec<DtdViolation>) {
+    for violation in violations {
+        report.report(&violation.node, violation.to_string());
+    }
+}
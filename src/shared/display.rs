@@ -1,58 +1,339 @@
+use crate::diagnostics::NAMESPACE;
 use crate::level2::convert::*;
 use crate::level2::ext::convert::{as_document_decl, RefDocumentDecl};
+use crate::level2::ext::trait_impls::{is_element_content, is_space_preserved, space_handling};
+use crate::level2::ext::writer::{
+    node_id, node_path, InternalSubset, NamespacePlacement, OutputEncoding, WriterOptions,
+};
+use crate::level2::ext::{CharacterReference, CharacterReferences, XmlDecl, XmlVersion};
 use crate::level2::*;
+use crate::shared::name::Name;
 use crate::shared::syntax::*;
-use std::fmt::{Formatter, Result as FmtResult};
+use crate::shared::text::{
+    escape, is_xml_11_reference_char, relax_escapes, to_entity_hex, SpaceHandling,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{Result as FmtResult, Write};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The namespace bindings in scope while writing, used by the `normalize_namespaces` option.
+/// Each binding maps a prefix, or `None` for the default namespace, to a namespace URI, or `None`
+/// where the default namespace has been undeclared; each element written pushes a frame that is
+/// popped when the element ends. With the `indent` option the scope also records whether white
+/// space is preserved within each element being written.
+///
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NamespaceScope {
+    bindings: Vec<(Option<String>, Option<String>)>,
+    frames: Vec<usize>,
+    space_preserved: Vec<bool>,
+}
 
 // ------------------------------------------------------------------------------------------------
 // Public Functions
 // ------------------------------------------------------------------------------------------------
 
-pub(crate) fn fmt_element(element: RefElement<'_>, f: &mut Formatter<'_>) -> FmtResult {
-    write!(f, "{}{}", XML_ELEMENT_START_START, element.node_name())?;
-    for attr in element.attributes().values() {
-        write!(f, " {}", attr)?;
+pub(crate) fn fmt_element(
+    node: &RefNode,
+    options: &WriterOptions,
+    scope: &mut NamespaceScope,
+    f: &mut dyn Write,
+) -> FmtResult {
+    let element = as_element(node).unwrap();
+    if options.has_xhtml_compatible() && is_xhtml_void(element) && !element.has_child_nodes() {
+        let _safe_to_ignore = fmt_element_open(element, options, scope, f)?;
+        scope.pop();
+        return write!(f, "{}", XHTML_EMPTY_ELEMENT_END);
+    }
+    let name = fmt_element_start(element, options, scope, f)?;
+    match xhtml_raw_text(element, options) {
+        Some((start, text, end)) => write!(f, "{}{}{}", start, text, end)?,
+        None => match options.indent() {
+            Some(indent) => fmt_indented_children(node, indent, options, scope, f)?,
+            None => {
+                for child in element.child_nodes() {
+                    fmt_node_in(&child, options, scope, f)?;
+                }
+            }
+        },
     }
+    fmt_element_end(&name, scope, f)
+}
+
+///
+/// Write the start tag of `element`, returning the name written so that the same name is used
+/// in the end tag; with the `normalize_namespaces` option the prefix may differ from the
+/// element's own.
+///
+pub(crate) fn fmt_element_start(
+    element: RefElement<'_>,
+    options: &WriterOptions,
+    scope: &mut NamespaceScope,
+    f: &mut dyn Write,
+) -> std::result::Result<String, std::fmt::Error> {
+    let name = fmt_element_open(element, options, scope, f)?;
     write!(f, "{}", XML_ELEMENT_START_END)?;
-    for child in element.child_nodes() {
-        write!(f, "{}", child)?;
+    Ok(name)
+}
+
+///
+/// Write the start tag of `element`, as `fmt_element_start`, without the closing `>`.
+///
+fn fmt_element_open(
+    element: RefElement<'_>,
+    options: &WriterOptions,
+    scope: &mut NamespaceScope,
+    f: &mut dyn Write,
+) -> std::result::Result<String, std::fmt::Error> {
+    scope.push();
+    let attributes: Vec<RefNode> = element
+        .attributes()
+        .values()
+        .filter(|attr| {
+            !options.has_omit_unspecified_attributes()
+                || as_attribute(attr).map(|a| a.specified()).unwrap_or(true)
+        })
+        .collect();
+    if !options.has_normalize_namespaces() {
+        let name = element.node_name().to_string();
+        check_encodable(options, &name)?;
+        write!(f, "{}{}", XML_ELEMENT_START_START, name)?;
+        for attr in &attributes {
+            write!(f, " ")?;
+            fmt_node(attr, options, f)?;
+        }
+        fmt_xhtml_namespace(element, options, scope, f)?;
+        return Ok(name);
+    }
+
+    let is_hoisting = options.namespace_placement() == NamespacePlacement::DocumentElement;
+    let is_outermost = scope.frames.len() == 1;
+    let attributes: Vec<RefNode> = if is_hoisting && !is_outermost {
+        // declarations already made by an ancestor, as they were hoisted, are not repeated.
+        attributes
+            .into_iter()
+            .filter(|attr| match declared_binding(attr) {
+                Some((prefix, uri)) => scope.lookup(prefix.as_deref()) != Some(uri.as_deref()),
+                None => true,
+            })
+            .collect()
+    } else {
+        attributes
+    };
+
+    // the declarations on the element itself are in scope for its own name and attributes.
+    for attr in &attributes {
+        if let Some((prefix, uri)) = declared_binding(attr) {
+            scope.bind(prefix, uri);
+        }
     }
+    let mut declarations: Vec<(Option<String>, String)> = Vec::new();
+    if is_hoisting && is_outermost {
+        for (prefix, uris) in namespace_bindings(element) {
+            match uris.as_slice() {
+                [Some(uri)] if !scope.is_bound_here(prefix.as_deref()) => {
+                    scope.bind(prefix.clone(), Some(uri.clone()));
+                    declarations.push((prefix, uri.clone()));
+                }
+                [_, _, ..] => warn!(
+                    "{}: prefix {:?} is bound to more than one namespace, it is declared where it is used",
+                    NAMESPACE, prefix
+                ),
+                _ => {}
+            }
+        }
+    }
+    let element_name = element.node_name();
+    let element_prefix = match element_name.namespace_uri() {
+        Some(uri) => scope.ensure(
+            element_name.prefix().as_deref(),
+            uri,
+            false,
+            &mut declarations,
+        ),
+        None => {
+            if element_name.prefix().is_none()
+                && scope.lookup(None).flatten().is_some()
+                && !scope.is_bound_here(None)
+            {
+                scope.bind(None, None);
+                declarations.push((None, String::new()));
+            }
+            element_name.prefix().clone()
+        }
+    };
+    let mut renamed: Vec<(RefNode, Option<String>)> = Vec::new();
+    for attr in attributes {
+        let attr_name = attr.node_name();
+        let prefix = match attr_name.namespace_uri() {
+            Some(uri) if !attr_name.is_namespace_declaration() => {
+                scope.ensure(attr_name.prefix().as_deref(), uri, true, &mut declarations)
+            }
+            _ => attr_name.prefix().clone(),
+        };
+        renamed.push((attr, prefix));
+    }
+
+    let name = qualified_name(element_prefix.as_deref(), element_name.local_name());
+    check_encodable(options, &name)?;
+    write!(f, "{}{}", XML_ELEMENT_START_START, name)?;
+    for (prefix, uri) in declarations {
+        match prefix {
+            None => write!(
+                f,
+                " {}=\"{}\"",
+                XMLNS_NS_ATTRIBUTE,
+                escaped(options, escape(uri), true)
+            )?,
+            Some(prefix) => {
+                check_encodable(options, &prefix)?;
+                write!(
+                    f,
+                    " {}{}{}=\"{}\"",
+                    XMLNS_NS_ATTRIBUTE,
+                    XML_NS_SEPARATOR,
+                    prefix,
+                    escaped(options, escape(uri), true)
+                )?
+            }
+        }
+    }
+    for (attr, prefix) in renamed {
+        let attr_name = attr.node_name();
+        if prefix == *attr_name.prefix() {
+            write!(f, " ")?;
+            fmt_node(&attr, options, f)?;
+        } else {
+            let name = qualified_name(prefix.as_deref(), attr_name.local_name());
+            check_encodable(options, &name)?;
+            let value = as_attribute(&attr).ok().and_then(|a| a.value());
+            write!(
+                f,
+                " {}=\"{}\"",
+                name,
+                version_references(&attr, &escaped(options, value.unwrap_or_default(), true))
+            )?;
+        }
+    }
+    fmt_xhtml_namespace(element, options, scope, f)?;
+    Ok(name)
+}
+
+pub(crate) fn fmt_element_end(
+    name: &str,
+    scope: &mut NamespaceScope,
+    f: &mut dyn Write,
+) -> FmtResult {
+    scope.pop();
     write!(
         f,
         "{}{}{}",
-        XML_ELEMENT_END_START,
-        element.node_name(),
-        XML_ELEMENT_END_END
+        XML_ELEMENT_END_START, name, XML_ELEMENT_END_END
     )
 }
 
-pub(crate) fn fmt_attribute(attribute: RefAttribute<'_>, f: &mut Formatter<'_>) -> FmtResult {
+pub(crate) fn fmt_attribute(
+    attribute: RefAttribute<'_>,
+    options: &WriterOptions,
+    f: &mut dyn Write,
+) -> FmtResult {
+    check_encodable(options, &attribute.node_name().to_string())?;
+    let value = escaped(options, attribute.value().unwrap_or_default(), true);
     write!(
         f,
         "{}=\"{}\"",
         attribute.node_name(),
-        attribute.value().unwrap_or_default()
+        version_references(attribute, &value)
     )
 }
 
-pub(crate) fn fmt_text(character_data: RefCharacterData<'_>, f: &mut Formatter<'_>) -> FmtResult {
-    match character_data.data() {
-        None => Ok(()),
-        Some(data) => write!(f, "{}", data),
+pub(crate) fn fmt_text(
+    character_data: RefCharacterData<'_>,
+    references: &[CharacterReference],
+    options: &WriterOptions,
+    f: &mut dyn Write,
+) -> FmtResult {
+    match (character_data.data(), character_data.node_value()) {
+        (None, _) | (_, None) => Ok(()),
+        (Some(data), _) if references.is_empty() => write!(
+            f,
+            "{}",
+            version_references(character_data, &escaped(options, data, false))
+        ),
+        (_, Some(value)) => {
+            // write each recorded reference in place of its character, and escape the rest.
+            let mut last = 0;
+            for reference in references {
+                let offset = reference.offset();
+                let in_place = value
+                    .get(offset..)
+                    .is_some_and(|rest| rest.starts_with(reference.character()));
+                if offset < last || !in_place {
+                    continue;
+                }
+                let text = escaped(options, escape(&value[last..offset]), false);
+                write!(
+                    f,
+                    "{}{}",
+                    version_references(character_data, &text),
+                    reference.reference()
+                )?;
+                last = offset + reference.character().len_utf8();
+            }
+            let text = escaped(options, escape(&value[last..]), false);
+            write!(f, "{}", version_references(character_data, &text))
+        }
     }
 }
 
-pub(crate) fn fmt_cdata(character_data: RefCharacterData<'_>, f: &mut Formatter<'_>) -> FmtResult {
-    match character_data.data() {
-        None => Ok(()),
-        Some(data) => write!(f, "{} {} {}", XML_CDATA_START, data, XML_CDATA_END),
+pub(crate) fn fmt_cdata(
+    character_data: RefCharacterData<'_>,
+    options: &WriterOptions,
+    f: &mut dyn Write,
+) -> FmtResult {
+    let data = match character_data.data() {
+        None => return Ok(()),
+        Some(data) => data,
+    };
+    let xml_11 = data.chars().any(is_xml_11_reference_char)
+        && document_version(character_data) == Some(XmlVersion::V11);
+    let is_literal = |c: char| options.can_write(c) && !(xml_11 && is_xml_11_reference_char(c));
+    if data.chars().all(is_literal) {
+        write!(f, "{} {} {}", XML_CDATA_START, data, XML_CDATA_END)
+    } else {
+        // references are not recognized within a CDATA section, so end the section before
+        // each character that cannot be encoded, or written literally, and start a new one
+        // after it.
+        write!(f, "{} ", XML_CDATA_START)?;
+        for c in data.chars() {
+            if is_literal(c) {
+                f.write_char(c)?;
+            } else {
+                write!(
+                    f,
+                    "{}{}{}",
+                    XML_CDATA_END,
+                    to_entity_hex(c),
+                    XML_CDATA_START
+                )?;
+            }
+        }
+        write!(f, " {}", XML_CDATA_END)
     }
 }
 
 pub(crate) fn fmt_processing_instruction(
     pi: RefProcessingInstruction<'_>,
-    f: &mut Formatter<'_>,
+    options: &WriterOptions,
+    f: &mut dyn Write,
 ) -> FmtResult {
+    check_encodable(options, &pi.target())?;
+    check_encodable(options, &pi.data().unwrap_or_default())?;
     match pi.data() {
         None => write!(f, "{}{}{}", XML_PI_START, pi.target(), XML_PI_END),
         Some(data) => write!(f, "{}{} {}{}", XML_PI_START, pi.target(), data, XML_PI_END),
@@ -61,28 +342,73 @@ pub(crate) fn fmt_processing_instruction(
 
 pub(crate) fn fmt_comment(
     character_data: RefCharacterData<'_>,
-    f: &mut Formatter<'_>,
+    options: &WriterOptions,
+    f: &mut dyn Write,
 ) -> FmtResult {
     match character_data.data() {
         None => Ok(()),
-        Some(data) => write!(f, "{}{}{}", XML_COMMENT_START, data, XML_COMMENT_END),
+        Some(data) => {
+            check_encodable(options, &data)?;
+            write!(f, "{}{}{}", XML_COMMENT_START, data, XML_COMMENT_END)
+        }
     }
 }
 
-pub(crate) fn fmt_document(document: RefDocumentDecl<'_>, f: &mut Formatter<'_>) -> FmtResult {
-    if let Some(xml_declaration) = &document.xml_declaration() {
-        write!(f, "{}", xml_declaration)?;
+pub(crate) fn fmt_document(
+    document: RefDocumentDecl<'_>,
+    options: &WriterOptions,
+    f: &mut dyn Write,
+) -> FmtResult {
+    match (document.xml_declaration(), options.encoding()) {
+        (Some(xml_declaration), Some(encoding))
+            if xml_declaration
+                .encoding()
+                .and_then(|name| OutputEncoding::from_name(&name))
+                != Some(encoding) =>
+        {
+            // the declaration must advertise the encoding actually written.
+            write!(
+                f,
+                "{}",
+                XmlDecl::new(
+                    xml_declaration.version(),
+                    Some(encoding.to_string()),
+                    xml_declaration.standalone(),
+                )
+            )?;
+        }
+        (Some(xml_declaration), _) => write!(f, "{}", xml_declaration)?,
+        (None, Some(encoding)) if encoding != OutputEncoding::Utf8 => write!(
+            f,
+            "{}",
+            XmlDecl::new(XmlVersion::V10, Some(encoding.to_string()), None)
+        )?,
+        (None, _) => {}
     }
+    let mut is_first = document.xml_declaration().is_none();
+    let mut separator = |f: &mut dyn Write| {
+        if options.indent().is_some() && !is_first {
+            writeln!(f)?;
+        }
+        is_first = false;
+        Ok(())
+    };
     if let Some(doc_type) = &document.doc_type() {
-        write!(f, "{}", doc_type)?;
+        separator(f)?;
+        fmt_node(doc_type, options, f)?;
     }
     for child in document.child_nodes() {
-        write!(f, "{}", child)?;
+        separator(f)?;
+        fmt_node(&child, options, f)?;
     }
     Ok(())
 }
 
-pub(crate) fn fmt_document_type(doc_type: RefDocumentType<'_>, f: &mut Formatter<'_>) -> FmtResult {
+pub(crate) fn fmt_document_type(
+    doc_type: RefDocumentType<'_>,
+    options: &WriterOptions,
+    f: &mut dyn Write,
+) -> FmtResult {
     write!(f, "{} {}", XML_DOCTYPE_START, doc_type.node_name())?;
     if let Some(id) = &doc_type.public_id() {
         write!(f, " {} \"{}\"", XML_DOCTYPE_PUBLIC, id)?;
@@ -90,17 +416,31 @@ pub(crate) fn fmt_document_type(doc_type: RefDocumentType<'_>, f: &mut Formatter
     if let Some(id) = &doc_type.system_id() {
         write!(f, " {} \"{}\"", XML_DOCTYPE_SYSTEM, id)?;
     }
-    if (doc_type.entities().len() + doc_type.notations().len() > 0)
-        || doc_type.internal_subset().is_some()
-    {
+    let internal_subset = match options.internal_subset() {
+        InternalSubset::Regenerated => None,
+        _ => doc_type.internal_subset(),
+    };
+    //
+    // The internal subset, where there is one, already declares the entities and notations.
+    //
+    let (entities, notations) = match (options.internal_subset(), &internal_subset) {
+        (InternalSubset::Verbatim, _) | (InternalSubset::Preferred, Some(_)) => {
+            (Vec::new(), Vec::new())
+        }
+        _ => (
+            sorted_by_name(doc_type.entities()),
+            sorted_by_name(doc_type.notations()),
+        ),
+    };
+    if (entities.len() + notations.len() > 0) || internal_subset.is_some() {
         write!(f, "{}", XML_DOCTYPE_ENTITY_START)?;
-        for (_, entity) in doc_type.entities() {
-            write!(f, "{}", entity)?;
+        for entity in entities {
+            fmt_node(&entity, options, f)?;
         }
-        for (_, notation) in doc_type.notations() {
-            write!(f, "{}", notation)?;
+        for notation in notations {
+            fmt_node(&notation, options, f)?;
         }
-        if let Some(internal_subset) = doc_type.internal_subset() {
+        if let Some(internal_subset) = internal_subset {
             write!(f, "{}", internal_subset)?;
         }
         write!(f, "{}", XML_DOCTYPE_ENTITY_END)?;
@@ -110,16 +450,17 @@ pub(crate) fn fmt_document_type(doc_type: RefDocumentType<'_>, f: &mut Formatter
 
 pub(crate) fn fmt_document_fragment(
     fragment: RefDocumentFragment<'_>,
-    f: &mut Formatter<'_>,
+    options: &WriterOptions,
+    f: &mut dyn Write,
 ) -> FmtResult {
     write!(f, "{}{} ", XML_CDATA_START, fragment.node_name())?;
     for child in fragment.child_nodes() {
-        write!(f, "{}", child)?;
+        fmt_node(&child, options, f)?;
     }
     write!(f, "{}", XML_CDATA_END)
 }
 
-pub(crate) fn fmt_entity(entity: RefEntity<'_>, f: &mut Formatter<'_>) -> FmtResult {
+pub(crate) fn fmt_entity(entity: RefEntity<'_>, f: &mut dyn Write) -> FmtResult {
     write!(f, "{} {}", XML_ENTITY_START, entity.node_name())?;
     if entity.public_id().is_none() && entity.system_id().is_none() {
         write!(f, " \"{}\"", entity.node_value().unwrap_or_default())?;
@@ -139,8 +480,10 @@ pub(crate) fn fmt_entity(entity: RefEntity<'_>, f: &mut Formatter<'_>) -> FmtRes
 
 pub(crate) fn fmt_entity_reference(
     entity_ref: RefEntityReference<'_>,
-    f: &mut Formatter<'_>,
+    options: &WriterOptions,
+    f: &mut dyn Write,
 ) -> FmtResult {
+    check_encodable(options, &entity_ref.node_name().to_string())?;
     write!(
         f,
         "{}{}{}",
@@ -150,7 +493,7 @@ pub(crate) fn fmt_entity_reference(
     )
 }
 
-pub(crate) fn fmt_notation(notation: RefNotation<'_>, f: &mut Formatter<'_>) -> FmtResult {
+pub(crate) fn fmt_notation(notation: RefNotation<'_>, f: &mut dyn Write) -> FmtResult {
     write!(f, "{} {}", XML_NOTATION_START, notation.node_name())?;
     if let Some(public_id) = notation.public_id() {
         write!(f, " {} \"{}\"", XML_DOCTYPE_PUBLIC, public_id)?;
@@ -163,21 +506,415 @@ pub(crate) fn fmt_notation(notation: RefNotation<'_>, f: &mut Formatter<'_>) ->
     write!(f, "{}", XML_NOTATION_END)
 }
 
-pub(crate) fn fmt_node(node: &RefNode, f: &mut Formatter<'_>) -> FmtResult {
+pub(crate) fn fmt_node(node: &RefNode, options: &WriterOptions, f: &mut dyn Write) -> FmtResult {
+    fmt_node_in(node, options, &mut NamespaceScope::default(), f)
+}
+
+///
+/// Write `node` within the namespace declarations made by the enclosing elements written so far,
+/// as recorded in `scope`.
+///
+pub(crate) fn fmt_node_in(
+    node: &RefNode,
+    options: &WriterOptions,
+    scope: &mut NamespaceScope,
+    f: &mut dyn Write,
+) -> FmtResult {
     match node.node_type() {
-        NodeType::Element => fmt_element(as_element(node).unwrap(), f),
-        NodeType::Attribute => fmt_attribute(as_attribute(node).unwrap(), f),
-        NodeType::Text => fmt_text(as_character_data(node).unwrap(), f),
-        NodeType::CData => fmt_cdata(as_character_data(node).unwrap(), f),
+        NodeType::Element => {
+            if options.has_annotate_elements() {
+                write!(
+                    f,
+                    "{} id={} path={} {}",
+                    XML_COMMENT_START,
+                    node_id(node),
+                    node_path(node),
+                    XML_COMMENT_END
+                )?;
+            }
+            fmt_element(node, options, scope, f)
+        }
+        NodeType::Attribute => fmt_attribute(as_attribute(node).unwrap(), options, f),
+        NodeType::Text => fmt_text(
+            as_character_data(node).unwrap(),
+            &node.character_references(),
+            options,
+            f,
+        ),
+        NodeType::CData => fmt_cdata(as_character_data(node).unwrap(), options, f),
         NodeType::ProcessingInstruction => {
-            fmt_processing_instruction(as_processing_instruction(node).unwrap(), f)
+            fmt_processing_instruction(as_processing_instruction(node).unwrap(), options, f)
+        }
+        NodeType::Comment => fmt_comment(as_character_data(node).unwrap(), options, f),
+        NodeType::Document => fmt_document(as_document_decl(node).unwrap(), options, f),
+        NodeType::DocumentType => fmt_document_type(as_document_type(node).unwrap(), options, f),
+        NodeType::DocumentFragment => {
+            fmt_document_fragment(as_document_fragment(node).unwrap(), options, f)
         }
-        NodeType::Comment => fmt_comment(as_character_data(node).unwrap(), f),
-        NodeType::Document => fmt_document(as_document_decl(node).unwrap(), f),
-        NodeType::DocumentType => fmt_document_type(as_document_type(node).unwrap(), f),
-        NodeType::DocumentFragment => fmt_document_fragment(as_document_fragment(node).unwrap(), f),
         NodeType::Entity => fmt_entity(as_entity(node).unwrap(), f),
-        NodeType::EntityReference => fmt_entity_reference(as_entity_reference(node).unwrap(), f),
+        NodeType::EntityReference => {
+            fmt_entity_reference(as_entity_reference(node).unwrap(), options, f)
+        }
         NodeType::Notation => fmt_notation(as_notation(node).unwrap(), f),
     }
 }
+
+///
+/// Returns each prefix, or `None` for the default namespace, used by `element` and its
+/// descendants, in the order first used, with the namespaces it is bound to; `None` is the
+/// absence of a namespace, as for an unprefixed element without one. A prefix bound to more than
+/// one namespace cannot be declared once for the whole tree.
+///
+pub(crate) fn namespace_bindings(
+    element: RefElement<'_>,
+) -> Vec<(Option<String>, Vec<Option<String>>)> {
+    let mut bindings: Vec<(Option<String>, Vec<Option<String>>)> = Vec::new();
+    add_namespace_bindings(element, &mut bindings);
+    bindings
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl NamespaceScope {
+    fn push(&mut self) {
+        self.frames.push(self.bindings.len());
+    }
+
+    fn pop(&mut self) {
+        if let Some(length) = self.frames.pop() {
+            self.bindings.truncate(length);
+        }
+    }
+
+    fn bind(&mut self, prefix: Option<String>, uri: Option<String>) {
+        self.bindings.push((prefix, uri));
+    }
+
+    ///
+    /// Returns `None` if `prefix` is not bound, else the namespace it is bound to.
+    ///
+    fn lookup(&self, prefix: Option<&str>) -> Option<Option<&str>> {
+        if prefix == Some(XML_NS_ATTRIBUTE) {
+            return Some(Some(XML_NS_URI));
+        }
+        self.bindings
+            .iter()
+            .rev()
+            .find(|(bound, _)| bound.as_deref() == prefix)
+            .map(|(_, uri)| uri.as_deref())
+    }
+
+    fn is_bound_here(&self, prefix: Option<&str>) -> bool {
+        let frame = self.frames.last().copied().unwrap_or_default();
+        self.bindings[frame..]
+            .iter()
+            .any(|(bound, _)| bound.as_deref() == prefix)
+    }
+
+    ///
+    /// Returns a prefix, other than the default, currently bound to `uri`.
+    ///
+    fn prefix_for(&self, uri: &str) -> Option<String> {
+        self.bindings
+            .iter()
+            .rev()
+            .filter_map(|(bound, _)| bound.as_deref())
+            .find(|prefix| self.lookup(Some(prefix)) == Some(Some(uri)))
+            .map(String::from)
+    }
+
+    ///
+    /// Returns the prefix to write for a name in the namespace `uri`, preferring `prefix`. If no
+    /// suitable prefix is in scope one is declared, added to `declarations`; either `prefix` if
+    /// it is not already declared on this element, or a new prefix of the form `ns1`, `ns2`, ...
+    /// Attributes cannot use the default namespace.
+    ///
+    fn ensure(
+        &mut self,
+        prefix: Option<&str>,
+        uri: &str,
+        is_attribute: bool,
+        declarations: &mut Vec<(Option<String>, String)>,
+    ) -> Option<String> {
+        if uri == XML_NS_URI {
+            return Some(XML_NS_ATTRIBUTE.to_string());
+        }
+        if prefix.is_some() || !is_attribute {
+            if self.lookup(prefix) == Some(Some(uri)) {
+                return prefix.map(String::from);
+            }
+            if !self.is_bound_here(prefix) && prefix != Some(XML_NS_ATTRIBUTE) {
+                self.bind(prefix.map(String::from), Some(uri.to_string()));
+                declarations.push((prefix.map(String::from), uri.to_string()));
+                return prefix.map(String::from);
+            }
+        }
+        if let Some(prefix) = self.prefix_for(uri) {
+            return Some(prefix);
+        }
+        if !is_attribute && self.lookup(None) == Some(Some(uri)) {
+            return None;
+        }
+        let prefix = (1..)
+            .map(|n| format!("ns{}", n))
+            .find(|prefix| self.lookup(Some(prefix)).is_none())
+            .unwrap();
+        self.bind(Some(prefix.clone()), Some(uri.to_string()));
+        declarations.push((Some(prefix.clone()), uri.to_string()));
+        Some(prefix)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the prefix, and namespace, declared by `attr` if it is an `xmlns` attribute; an empty
+/// namespace undeclares the default namespace.
+///
+fn declared_binding(attr: &RefNode) -> Option<(Option<String>, Option<String>)> {
+    let attr_name = attr.node_name();
+    if !attr_name.is_namespace_declaration() {
+        return None;
+    }
+    let prefix = attr_name
+        .prefix()
+        .as_ref()
+        .map(|_| attr_name.local_name().clone());
+    let uri = as_attribute(attr)
+        .ok()
+        .and_then(|a| a.value())
+        .filter(|uri| !uri.is_empty());
+    Some((prefix, uri))
+}
+
+fn add_namespace_bindings(
+    element: RefElement<'_>,
+    bindings: &mut Vec<(Option<String>, Vec<Option<String>>)>,
+) {
+    let mut add = |prefix: Option<String>, uri: Option<String>| match bindings
+        .iter_mut()
+        .find(|(bound, _)| *bound == prefix)
+    {
+        Some((_, uris)) if !uris.contains(&uri) => uris.push(uri),
+        Some(_) => {}
+        None => bindings.push((prefix, vec![uri])),
+    };
+    let name = element.node_name();
+    match name.namespace_uri() {
+        Some(uri) if uri != XML_NS_URI => add(name.prefix().clone(), Some(uri.clone())),
+        None if name.prefix().is_none() => add(None, None),
+        _ => {}
+    }
+    for (attr_name, attr) in element.attributes() {
+        match (
+            declared_binding(&attr),
+            attr_name.namespace_uri(),
+            attr_name.prefix(),
+        ) {
+            (Some((prefix, uri)), _, _) => add(prefix, uri),
+            (None, Some(uri), Some(prefix)) if uri != XML_NS_URI => {
+                add(Some(prefix.clone()), Some(uri.clone()))
+            }
+            _ => {}
+        }
+    }
+    for child_node in element.child_nodes() {
+        if let Ok(child) = as_element(&child_node) {
+            add_namespace_bindings(child, bindings);
+        }
+    }
+}
+
+///
+/// Returns the version of XML declared by the document that owns `node`, if any.
+///
+fn document_version<N: Node<NodeRef = RefNode> + ?Sized>(node: &N) -> Option<XmlVersion> {
+    let document_node = node.owner_document()?;
+    let xml_declaration = as_document_decl(&document_node).ok()?.xml_declaration();
+    xml_declaration.map(|xml_declaration| xml_declaration.version())
+}
+
+///
+/// In a document that declares XML 1.1 the restricted characters, and those that would be read
+/// as line breaks, must be written as character references; `text` is returned with these
+/// replaced.
+///
+fn version_references<'a, N: Node<NodeRef = RefNode> + ?Sized>(
+    node: &N,
+    text: &'a str,
+) -> Cow<'a, str> {
+    if !text.chars().any(is_xml_11_reference_char)
+        || document_version(node) != Some(XmlVersion::V11)
+    {
+        return Cow::Borrowed(text);
+    }
+    let mut replaced = String::with_capacity(text.len());
+    for c in text.chars() {
+        if is_xml_11_reference_char(c) {
+            replaced.push_str(&to_entity_hex(c));
+        } else {
+            replaced.push(c);
+        }
+    }
+    Cow::Owned(replaced)
+}
+
+///
+/// Characters that cannot be written literally, in the output encoding or with the
+/// `escape_non_ascii` option, may only be replaced by references in content, anywhere else they
+/// are an error.
+///
+fn check_encodable(options: &WriterOptions, s: &str) -> FmtResult {
+    if s.chars().all(|c| options.can_write(c)) {
+        Ok(())
+    } else {
+        Err(std::fmt::Error)
+    }
+}
+
+///
+/// Returns the escaped `text` of character data, or of an attribute value, with only the
+/// references that are required if the `minimal_escaping` option is set.
+///
+fn escaped(options: &WriterOptions, text: String, in_attribute: bool) -> String {
+    if options.has_minimal_escaping() {
+        relax_escapes(&text, in_attribute)
+    } else {
+        text
+    }
+}
+
+///
+/// With the `indent` option, write the children of `element` each on a new line, indented for
+/// its depth, where it has element content; white space text between the children is replaced
+/// by the indentation. Within the scope of an `xml:space="preserve"` attribute, and where there
+/// is text content, the children are written as they are.
+///
+fn fmt_indented_children(
+    element: &RefNode,
+    indent: &str,
+    options: &WriterOptions,
+    scope: &mut NamespaceScope,
+    f: &mut dyn Write,
+) -> FmtResult {
+    let space_preserved = match space_handling(element) {
+        Some(handling) => handling == SpaceHandling::Preserve,
+        None => match scope.space_preserved.last() {
+            Some(space_preserved) => *space_preserved,
+            None => is_space_preserved(element),
+        },
+    };
+    scope.space_preserved.push(space_preserved);
+    let is_indented = !space_preserved && is_element_content(element);
+    let depth = scope.frames.len();
+    for child in element.child_nodes() {
+        if is_indented {
+            if is_text(&child) {
+                continue;
+            }
+            write!(f, "\n{}", indent.repeat(depth))?;
+        }
+        fmt_node_in(&child, options, scope, f)?;
+    }
+    if is_indented {
+        write!(f, "\n{}", indent.repeat(depth.saturating_sub(1)))?;
+    }
+    let _safe_to_ignore = scope.space_preserved.pop();
+    Ok(())
+}
+
+///
+/// Returns `true` if `element` is one of the XHTML elements that is always empty, and so may be
+/// written with the minimized `<br />` syntax.
+///
+fn is_xhtml_void(element: RefElement<'_>) -> bool {
+    let name = element.node_name();
+    matches!(name.namespace_uri().as_deref(), None | Some(XHTML_NS_URI))
+        && XHTML_VOID_ELEMENTS.contains(&name.local_name().as_str())
+}
+
+///
+/// With the `xhtml_compatible` option, add the XHTML namespace declaration to an `html` element
+/// that has no parent element, unless it is already declared.
+///
+fn fmt_xhtml_namespace(
+    element: RefElement<'_>,
+    options: &WriterOptions,
+    scope: &NamespaceScope,
+    f: &mut dyn Write,
+) -> FmtResult {
+    let name = element.node_name();
+    if options.has_xhtml_compatible()
+        && name.prefix().is_none()
+        && name.local_name() == XHTML_ELEMENT_HTML
+        && matches!(name.namespace_uri().as_deref(), None | Some(XHTML_NS_URI))
+        && !element
+            .parent_node()
+            .map(|p| is_element(&p))
+            .unwrap_or_default()
+        && !element.has_attribute(XMLNS_NS_ATTRIBUTE)
+        && scope.lookup(None).flatten() != Some(XHTML_NS_URI)
+    {
+        write!(f, " {}=\"{}\"", XMLNS_NS_ATTRIBUTE, XHTML_NS_URI)?;
+    }
+    Ok(())
+}
+
+///
+/// With the `xhtml_compatible` option, returns the content of a `script` or `style` element
+/// whose children are only text and CDATA sections, to be written unescaped as legacy HTML
+/// renderers expect; if the content contains `<` or `&` it is wrapped in a CDATA section that
+/// is itself within script or style comments. The content is split around any `]]>`.
+///
+fn xhtml_raw_text(
+    element: RefElement<'_>,
+    options: &WriterOptions,
+) -> Option<(&'static str, String, &'static str)> {
+    if !options.has_xhtml_compatible() {
+        return None;
+    }
+    let name = element.node_name();
+    if !matches!(name.namespace_uri().as_deref(), None | Some(XHTML_NS_URI)) {
+        return None;
+    }
+    let (start, end) = match name.local_name().as_str() {
+        XHTML_ELEMENT_SCRIPT => (XHTML_SCRIPT_CDATA_START, XHTML_SCRIPT_CDATA_END),
+        XHTML_ELEMENT_STYLE => (XHTML_STYLE_CDATA_START, XHTML_STYLE_CDATA_END),
+        _ => return None,
+    };
+    let mut text = String::new();
+    for child in element.child_nodes() {
+        match child.node_type() {
+            NodeType::Text | NodeType::CData => {
+                text.push_str(&child.node_value().unwrap_or_default())
+            }
+            _ => return None,
+        }
+    }
+    if text.contains([XML_ESC_LT_CHAR, XML_ESC_AMP_CHAR]) {
+        let text = text.replace(
+            XML_CDATA_END,
+            &format!("]]{}{}>", XML_CDATA_END, XML_CDATA_START),
+        );
+        Some((start, text, end))
+    } else {
+        Some((XML_EMPTY, text.replace(XML_CDATA_END, "]]&gt;"), XML_EMPTY))
+    }
+}
+
+fn qualified_name(prefix: Option<&str>, local_name: &str) -> String {
+    match prefix {
+        None => local_name.to_string(),
+        Some(prefix) => format!("{}{}{}", prefix, XML_NS_SEPARATOR, local_name),
+    }
+}
+
+fn sorted_by_name(nodes: HashMap<Name, RefNode>) -> Vec<RefNode> {
+    let mut nodes: Vec<(Name, RefNode)> = nodes.into_iter().collect();
+    nodes.sort_by_key(|(name, _)| name.to_string());
+    nodes.into_iter().map(|(_, node)| node).collect()
+}
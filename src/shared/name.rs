@@ -167,6 +167,33 @@ impl Name {
         })
     }
 
+    ///
+    /// Check this name against the additional constraints of the Namespaces in XML
+    /// recommendation, used by documents with the `strict_names` processing option.
+    ///
+    pub(crate) fn check_strict(&self, is_attribute: bool) -> Result<()> {
+        let is_reserved = |part: &str| part.to_ascii_lowercase().starts_with(XML_NS_ATTRIBUTE);
+        match &self.prefix {
+            Some(prefix) if prefix == XML_NS_ATTRIBUTE => {}
+            Some(prefix) if prefix == XMLNS_NS_ATTRIBUTE && is_attribute => {}
+            Some(prefix) if prefix == XMLNS_NS_ATTRIBUTE => {
+                warn!("the prefix `{}` may only be used by attributes", prefix);
+                return Err(Error::Namespace);
+            }
+            Some(prefix) if is_reserved(prefix) => {
+                warn!("the prefix `{}` is reserved", prefix);
+                return Err(Error::Namespace);
+            }
+            None if self.local_name == XMLNS_NS_ATTRIBUTE && is_attribute => {}
+            None if is_reserved(&self.local_name) => {
+                warn!("the name `{}` is reserved", self.local_name);
+                return Err(Error::InvalidCharacter);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     fn check_part(part: impl AsRef<str>) -> Result<String> {
         let part = part.as_ref();
         if part.is_empty() {
@@ -307,6 +334,19 @@ impl Name {
                 || self.prefix == Some(xmlns_attribute))
     }
 
+    ///
+    /// Is this named as an `xmlns` attribute, either with the `xmlns` namespace or, as when set
+    /// using `set_attribute` or read by the parser, without one.
+    ///
+    pub(crate) fn is_namespace_declaration(&self) -> bool {
+        self.is_namespace_attribute()
+            || (self.namespace_uri.is_none()
+                && match &self.prefix {
+                    None => self.local_name == XMLNS_NS_ATTRIBUTE,
+                    Some(prefix) => prefix == XMLNS_NS_ATTRIBUTE,
+                })
+    }
+
     ///
     /// Construct a name for an `xmlns` attribute.
     ///
@@ -1,5 +1,18 @@
+/*!
+The shared, mutable, cell each node is held in; an `Rc<RefCell<…>>`, or with the `threadsafe`
+feature an `Arc<RwLock<…>>`. The methods have the names of those of `RefCell`, and the locks
+ignore poisoning as a `RefCell` has none.
+*/
+
+#[cfg(not(feature = "threadsafe"))]
 use std::cell::{Ref, RefCell, RefMut};
-use std::rc::{Rc, Weak};
+#[cfg(not(feature = "threadsafe"))]
+use std::rc::{Rc as Shared, Weak};
+#[cfg(feature = "threadsafe")]
+use std::sync::{
+    Arc as Shared, PoisonError, RwLock as RefCell, RwLockReadGuard as Ref,
+    RwLockWriteGuard as RefMut, Weak,
+};
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
@@ -8,7 +21,7 @@ use std::rc::{Rc, Weak};
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct RcRefCell<T: Sized> {
-    inner: Rc<RefCell<T>>,
+    inner: Shared<RefCell<T>>,
 }
 
 #[doc(hidden)]
@@ -17,6 +30,25 @@ pub struct WeakRefCell<T: Sized> {
     inner: Weak<RefCell<T>>,
 }
 
+///
+/// Implemented by the values held in an `RcRefCell`, to be told each time they are mutably
+/// borrowed, and so may be changed.
+///
+#[doc(hidden)]
+pub trait Mutable {
+    fn will_change(&self);
+}
+
+///
+/// A mutable cell within a node, such as a cache; a `RefCell`, or with the `threadsafe` feature
+/// a `RwLock`.
+///
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub(crate) struct InnerCell<T> {
+    inner: RefCell<T>,
+}
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
@@ -32,7 +64,7 @@ impl<T> Clone for RcRefCell<T> {
 impl<T> PartialEq for RcRefCell<T> {
     fn eq(&self, other: &Self) -> bool {
         // RefNodes are equal if the two Rc point to the same RefCell.
-        Rc::ptr_eq(&self.inner, &other.inner)
+        Shared::ptr_eq(&self.inner, &other.inner)
     }
 }
 
@@ -41,33 +73,107 @@ impl<T> PartialEq for RcRefCell<T> {
 impl<T> RcRefCell<T> {
     pub fn new(value: T) -> Self {
         Self {
-            inner: Rc::new(RefCell::new(value)),
+            inner: Shared::new(RefCell::new(value)),
         }
     }
 
-    pub fn as_inner(&self) -> &Rc<RefCell<T>> {
+    pub fn as_inner(&self) -> &Shared<RefCell<T>> {
         &self.inner
     }
 
+    pub(crate) fn as_ptr(&self) -> *const RefCell<T> {
+        Shared::as_ptr(&self.inner)
+    }
+
+    pub(crate) fn strong_count(&self) -> usize {
+        Shared::strong_count(&self.inner)
+    }
+
+    #[cfg(feature = "internals")]
+    pub(crate) fn weak_count(&self) -> usize {
+        Shared::weak_count(&self.inner)
+    }
+
+    pub fn downgrade(self) -> WeakRefCell<T> {
+        WeakRefCell {
+            inner: Shared::downgrade(&self.inner),
+        }
+    }
+}
+
+#[cfg(not(feature = "threadsafe"))]
+impl<T> RcRefCell<T> {
     pub fn unwrap(self) -> T {
-        match Rc::try_unwrap(self.inner) {
+        match Shared::try_unwrap(self.inner) {
             Ok(ref_cell) => ref_cell.into_inner(),
             _ => panic!("could not unwrap the std::rc::Rc value"),
         }
     }
 
-    pub fn downgrade(self) -> WeakRefCell<T> {
-        WeakRefCell {
-            inner: Rc::downgrade(&self.inner),
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.inner.borrow()
+    }
+
+    pub(crate) fn try_borrow(&self) -> Option<Ref<'_, T>> {
+        self.inner.try_borrow().ok()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn borrow_mut_unmarked(&self) -> RefMut<'_, T> {
+        self.inner.borrow_mut()
+    }
+}
+
+#[cfg(feature = "threadsafe")]
+impl<T> RcRefCell<T> {
+    pub fn unwrap(self) -> T {
+        match Shared::try_unwrap(self.inner) {
+            Ok(lock) => lock.into_inner().unwrap_or_else(PoisonError::into_inner),
+            _ => panic!("could not unwrap the std::sync::Arc value"),
         }
     }
 
     pub fn borrow(&self) -> Ref<'_, T> {
-        self.inner.borrow()
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn try_borrow(&self) -> Option<Ref<'_, T>> {
+        self.inner.try_read().ok()
     }
 
+    #[cfg(test)]
+    pub(crate) fn borrow_mut_unmarked(&self) -> RefMut<'_, T> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(not(feature = "threadsafe"))]
+impl<T: Mutable> RcRefCell<T> {
     pub fn borrow_mut(&self) -> RefMut<'_, T> {
-        self.inner.borrow_mut()
+        let ref_mut = self.inner.borrow_mut();
+        ref_mut.will_change();
+        ref_mut
+    }
+
+    pub(crate) fn try_borrow_mut(&self) -> Option<RefMut<'_, T>> {
+        let ref_mut = self.inner.try_borrow_mut().ok()?;
+        ref_mut.will_change();
+        Some(ref_mut)
+    }
+}
+
+#[cfg(feature = "threadsafe")]
+impl<T: Mutable> RcRefCell<T> {
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        let ref_mut = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        ref_mut.will_change();
+        ref_mut
+    }
+
+    pub(crate) fn try_borrow_mut(&self) -> Option<RefMut<'_, T>> {
+        let ref_mut = self.inner.try_write().ok()?;
+        ref_mut.will_change();
+        Some(ref_mut)
     }
 }
 
@@ -88,11 +194,65 @@ impl<T> WeakRefCell<T> {
         &self.inner
     }
 
+    pub(crate) fn is_dropped(&self) -> bool {
+        self.inner.strong_count() == 0
+    }
+
     pub fn upgrade(self) -> Option<RcRefCell<T>> {
         self.inner.upgrade().map(|inner| RcRefCell { inner })
     }
 }
 
+// ------------------------------------------------------------------------------------------------
+
+impl<T: Clone> Clone for InnerCell<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.borrow().clone())
+    }
+}
+
+#[cfg(not(feature = "threadsafe"))]
+impl<T> InnerCell<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self {
+            inner: RefCell::new(value),
+        }
+    }
+
+    pub(crate) fn borrow(&self) -> Ref<'_, T> {
+        self.inner.borrow()
+    }
+
+    pub(crate) fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.inner.borrow_mut()
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+}
+
+#[cfg(feature = "threadsafe")]
+impl<T> InnerCell<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self {
+            inner: RefCell::new(value),
+        }
+    }
+
+    pub(crate) fn borrow(&self) -> Ref<'_, T> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // Unit Tests
 // ------------------------------------------------------------------------------------------------
@@ -118,6 +278,10 @@ mod tests {
         }
     }
 
+    impl Mutable for Node {
+        fn will_change(&self) {}
+    }
+
     #[test]
     fn test_ref_aliasing() {
         let node = Node::new("name-1");
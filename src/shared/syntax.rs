@@ -19,7 +19,7 @@ pub(crate) const XML_CDATA_END: &str = "]]>";
 
 pub(crate) const XML_DOCTYPE_START: &str = "<!DOCTYPE";
 pub(crate) const XML_DOCTYPE_END: &str = ">";
-pub(crate) const XML_DOCTYPE_ENTITY_START: &str = "[";
+pub(crate) const XML_DOCTYPE_ENTITY_START: &str = " [";
 pub(crate) const XML_DOCTYPE_ENTITY_END: &str = "]";
 pub(crate) const XML_DOCTYPE_PUBLIC: &str = "PUBLIC";
 pub(crate) const XML_DOCTYPE_SYSTEM: &str = "SYSTEM";
@@ -83,6 +83,36 @@ pub(crate) const XML_NS_ATTR_SPACE_PRESERVE: &str = "preserve";
 pub(crate) const XMLNS_NS_URI: &str = "http://www.w3.org/2000/xmlns/";
 pub(crate) const XMLNS_NS_ATTRIBUTE: &str = "xmlns";
 
+// ------------------------------------------------------------------------------------------------
+// XML Schema Instance Support
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) const XSI_NS_URI: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+pub(crate) const XSI_NS_ATTR_NIL: &str = "nil";
+
+// ------------------------------------------------------------------------------------------------
+// XHTML Support
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) const XHTML_NS_URI: &str = "http://www.w3.org/1999/xhtml";
+
+pub(crate) const XHTML_ELEMENT_HTML: &str = "html";
+pub(crate) const XHTML_ELEMENT_SCRIPT: &str = "script";
+pub(crate) const XHTML_ELEMENT_STYLE: &str = "style";
+
+pub(crate) const XHTML_VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "basefont", "br", "col", "embed", "frame", "hr", "img", "input", "isindex",
+    "link", "meta", "param", "source", "track", "wbr",
+];
+
+pub(crate) const XHTML_EMPTY_ELEMENT_END: &str = " />";
+
+pub(crate) const XHTML_SCRIPT_CDATA_START: &str = "//<![CDATA[\n";
+pub(crate) const XHTML_SCRIPT_CDATA_END: &str = "\n//]]>";
+pub(crate) const XHTML_STYLE_CDATA_START: &str = "/*<![CDATA[*/";
+pub(crate) const XHTML_STYLE_CDATA_END: &str = "/*]]>*/";
+
 // ------------------------------------------------------------------------------------------------
 // DOM Node Names
 // ------------------------------------------------------------------------------------------------
@@ -1,3 +1,4 @@
+use crate::level2::ext::XmlVersion;
 use crate::shared::syntax::*;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::str::FromStr;
@@ -84,7 +85,8 @@ pub(crate) fn normalize_attribute_value(
                 // TODO: this does not yet deal with entity references.
                 //
                 let replacement = match resolver.resolve(a_match.as_str()) {
-                    None => panic!("unknown entity reference {}", a_match.as_str()),
+                    // references to unknown entities are kept, as the parser may be asked to do.
+                    None => a_match.as_str().to_string(),
                     Some(replacement) => {
                         normalize_attribute_value(&replacement, resolver, is_cdata)
                     }
@@ -201,6 +203,52 @@ pub(crate) fn escape(input: impl AsRef<str>) -> String {
     result
 }
 
+///
+/// Replace the references written by [`escape`](fn.escape.html) with the characters themselves
+/// where they are not required; in character data only `&`, `<`, and the `>` of `]]>` must be
+/// escaped, and in an attribute value delimited by double quotes only `&`, `<`, and `"`.
+///
+pub(crate) fn relax_escapes(escaped: &str, in_attribute: bool) -> String {
+    let mut result = String::with_capacity(escaped.len());
+    let mut rest = escaped;
+    while let Some(start) = rest.find(XML_NUMBERED_ENTITYREF_START) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(XML_ENTITYREF_END) {
+            None => break,
+            Some(end) => end + 1,
+        };
+        let reference = &rest[..end];
+        let relaxed = [XML_ESC_APOS_CHAR, XML_ESC_GT_CHAR, XML_ESC_QUOT_CHAR]
+            .into_iter()
+            .find(|c| reference == to_entity(*c))
+            .filter(|c| match *c {
+                XML_ESC_QUOT_CHAR => !in_attribute,
+                XML_ESC_GT_CHAR => !result.ends_with("]]"),
+                _ => true,
+            });
+        match relaxed {
+            Some(c) => result.push(c),
+            None => result.push_str(reference),
+        }
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+///
+/// Returns `true` if `input` contains characters that must be escaped in character data, that is
+/// `&`, `<`, or the CDATA-section-close delimiter `]]>`; content without these can be written as
+/// a text node rather than a CDATA section.
+///
+pub(crate) fn requires_cdata(input: impl AsRef<str>) -> bool {
+    let input = input.as_ref();
+    input.contains(XML_ESC_AMP_CHAR)
+        || input.contains(XML_ESC_LT_CHAR)
+        || input.contains(XML_CDATA_END)
+}
+
 pub(crate) fn to_entity(c: char) -> String {
     format!(
         "{}{}{}",
@@ -208,11 +256,10 @@ pub(crate) fn to_entity(c: char) -> String {
     )
 }
 
-#[allow(dead_code)]
 pub(crate) fn to_entity_hex(c: char) -> String {
     format!(
         "{}{:X}{}",
-        XML_HEX_NUMBERED_ENTITYREF_START, c as u16, XML_ENTITYREF_END
+        XML_HEX_NUMBERED_ENTITYREF_START, c as u32, XML_ENTITYREF_END
     )
 }
 
@@ -261,14 +308,13 @@ fn char_from_entity(entity: impl AsRef<str>) -> String {
 /// [#x10FFFE-#x10FFFF].
 /// ```
 ///
-#[allow(dead_code)]
 pub(crate) fn is_xml_10_char(c: char) -> bool {
     c == '\u{0009}'
         || c == '\u{000A}'
         || c == '\u{000D}'
         || ('\u{0020}'..='\u{D7FF}').contains(&c)
         || ('\u{E000}'..='\u{FFFD}').contains(&c)
-        || ('\u{10000}'..='\u{10FFF}').contains(&c)
+        || ('\u{10000}'..='\u{10FFFF}').contains(&c)
 }
 
 #[allow(dead_code)]
@@ -288,7 +334,6 @@ pub(crate) fn is_xml_10_restricted_char(c: char) -> bool {
 ///                 /* any Unicode character, excluding the surrogate blocks, FFFE, and FFFF. */
 /// ```
 ///
-#[allow(dead_code)]
 pub(crate) fn is_xml_11_char(c: char) -> bool {
     //
     // below ranges are always valid for XML 1.1 documents
@@ -296,7 +341,7 @@ pub(crate) fn is_xml_11_char(c: char) -> bool {
     //
     ('\u{0001}'..='\u{D7FF}').contains(&c)
         || ('\u{E000}'..='\u{FFFD}').contains(&c)
-        || ('\u{10000}'..='\u{10FFF}').contains(&c)
+        || ('\u{10000}'..='\u{10FFFF}').contains(&c)
 }
 
 ///
@@ -306,7 +351,6 @@ pub(crate) fn is_xml_11_char(c: char) -> bool {
 /// RestrictedChar  ::=  #x1-#x8] | [#xB-#xC] | [#xE-#x1F] | [#x7F-#x84] | [#x86-#x9F]
 /// ```
 ///
-#[allow(dead_code)]
 pub(crate) fn is_xml_11_restricted_char(c: char) -> bool {
     //
     // below ranges are always valid for XML 1.1 documents
@@ -319,6 +363,39 @@ pub(crate) fn is_xml_11_restricted_char(c: char) -> bool {
         || ('\u{86}'..='\u{9F}').contains(&c)
 }
 
+///
+/// Returns `true` if `c` is a `Char` in the given version of XML, and so may be the target of a
+/// character reference.
+///
+#[allow(dead_code)]
+pub(crate) fn is_xml_char(version: XmlVersion, c: char) -> bool {
+    match version {
+        XmlVersion::V10 => is_xml_10_char(c),
+        XmlVersion::V11 => is_xml_11_char(c),
+    }
+}
+
+///
+/// Returns `true` if `c` may appear literally in a document of the given version of XML; in XML
+/// 1.1 the restricted characters may only appear as character references.
+///
+#[allow(dead_code)]
+pub(crate) fn is_xml_literal_char(version: XmlVersion, c: char) -> bool {
+    match version {
+        XmlVersion::V10 => is_xml_10_char(c),
+        XmlVersion::V11 => is_xml_11_char(c) && !is_xml_11_restricted_char(c),
+    }
+}
+
+///
+/// Returns `true` if `c` must be written as a character reference in an XML 1.1 document; this
+/// includes the restricted characters, and `#x85` and `#x2028` which would otherwise be read as
+/// line breaks (§2.11).
+///
+pub(crate) fn is_xml_11_reference_char(c: char) -> bool {
+    is_xml_11_restricted_char(c) || c == '\u{85}' || c == '\u{2028}'
+}
+
 ///
 /// S (white space) consists of one or more space (#x20) characters, carriage returns, line feeds,
 /// or tabs.
@@ -346,6 +423,9 @@ pub(crate) fn is_xml_space(c: char) -> bool {
 ///                      [#x10000-#xEFFFF]
 /// ```
 ///
+/// The name productions are the same in XML 1.0 (Fifth Edition) and XML 1.1, so names are checked
+/// in the same way whichever version a document declares.
+///
 #[allow(dead_code)]
 pub(crate) fn is_xml_name_start_char(c: char) -> bool {
     c == ':'
@@ -425,6 +505,162 @@ pub(crate) fn is_xml_nmtokens(s: impl AsRef<str>) -> bool {
     !s.is_empty() && s.split(' ').all(is_xml_nmtoken)
 }
 
+///
+/// From RFC 3987 §2.2 [ABNF for IRI References and IRIs](https://www.rfc-editor.org/rfc/rfc3987#section-2.2),
+/// an IRI reference is either an IRI or a relative reference. This checks the characters of the
+/// reference; ASCII characters must be either unreserved, reserved, or part of a percent-encoded
+/// octet, and non-ASCII characters must not be control or white space characters. Only one `#`,
+/// introducing the fragment, is allowed.
+///
+pub(crate) fn is_iri_reference(s: impl AsRef<str>) -> bool {
+    let s = s.as_ref();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        let is_valid = match c {
+            '%' => {
+                chars.next().is_some_and(|c| c.is_ascii_hexdigit())
+                    && chars.next().is_some_and(|c| c.is_ascii_hexdigit())
+            }
+            c if c.is_ascii() => c.is_ascii_alphanumeric() || "-._~:/?#[]@!$&'()*+,;=".contains(c),
+            c => !c.is_control() && !c.is_whitespace(),
+        };
+        if !is_valid {
+            return false;
+        }
+    }
+    s.matches('#').count() <= 1
+}
+
+///
+/// Returns `true` if the IRI reference `s` is absolute, that is it begins with a scheme.
+///
+/// ```ebnf
+/// scheme   ::=  ALPHA ( ALPHA | DIGIT | "+" | "-" | "." )* ":"
+/// ```
+///
+pub(crate) fn is_absolute_iri(s: impl AsRef<str>) -> bool {
+    match s.as_ref().split_once(':') {
+        None => false,
+        Some((scheme, _)) => {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+    }
+}
+
+///
+/// From RFC 5646 §2.1 [Syntax](https://www.rfc-editor.org/rfc/rfc5646#section-2.1), returns
+/// `true` if `s` is a well-formed BCP 47 language tag. This checks the syntax only, the subtags are
+/// not checked against the IANA language subtag registry.
+///
+/// ```ebnf
+/// langtag       ::= language ("-" script)? ("-" region)? ("-" variant)* ("-" extension)*
+///                   ("-" privateuse)?
+/// language      ::= 2*3ALPHA ("-" extlang)? | 4ALPHA | 5*8ALPHA
+/// extlang       ::= 3ALPHA *2("-" 3ALPHA)
+/// script        ::= 4ALPHA
+/// region        ::= 2ALPHA | 3DIGIT
+/// variant       ::= 5*8alphanum | (DIGIT 3alphanum)
+/// extension     ::= singleton 1*("-" (2*8alphanum))
+/// privateuse    ::= "x" 1*("-" (1*8alphanum))
+/// ```
+///
+pub(crate) fn is_language_tag(s: impl AsRef<str>) -> bool {
+    let s = s.as_ref();
+    if IRREGULAR_LANGUAGE_TAGS
+        .iter()
+        .any(|tag| tag.eq_ignore_ascii_case(s))
+    {
+        return true;
+    }
+    let subtags: Vec<&str> = s.split('-').collect();
+    if subtags
+        .iter()
+        .any(|subtag| subtag.is_empty() || subtag.len() > 8 || !is_alphanumeric(subtag))
+    {
+        return false;
+    }
+    let is_alpha = |subtag: &str, min: usize, max: usize| {
+        (min..=max).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphabetic())
+    };
+    let is_private_use =
+        |subtags: &[&str]| subtags.len() > 1 && subtags[0].eq_ignore_ascii_case("x");
+    if is_private_use(&subtags) {
+        return true;
+    }
+
+    let mut rest = &subtags[..];
+    // language, with up to three extended language subtags.
+    match rest.first() {
+        Some(language) if is_alpha(language, 2, 3) => {
+            rest = &rest[1..];
+            let mut extlangs = 0;
+            while extlangs < 3 && rest.first().is_some_and(|subtag| is_alpha(subtag, 3, 3)) {
+                rest = &rest[1..];
+                extlangs += 1;
+            }
+        }
+        Some(language) if is_alpha(language, 4, 8) => rest = &rest[1..],
+        _ => return false,
+    }
+    if rest.first().is_some_and(|subtag| is_alpha(subtag, 4, 4)) {
+        rest = &rest[1..];
+    }
+    if rest.first().is_some_and(|subtag| {
+        is_alpha(subtag, 2, 2) || (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit()))
+    }) {
+        rest = &rest[1..];
+    }
+    while rest.first().is_some_and(|subtag| {
+        subtag.len() >= 5 || (subtag.len() == 4 && subtag.starts_with(|c: char| c.is_ascii_digit()))
+    }) {
+        rest = &rest[1..];
+    }
+    let mut singletons: Vec<String> = Vec::new();
+    while rest.len() > 1 && rest[0].len() == 1 && !rest[0].eq_ignore_ascii_case("x") {
+        let singleton = rest[0].to_ascii_lowercase();
+        if singletons.contains(&singleton) {
+            return false;
+        }
+        singletons.push(singleton);
+        let count = rest[1..]
+            .iter()
+            .take_while(|subtag| subtag.len() >= 2)
+            .count();
+        if count == 0 {
+            return false;
+        }
+        rest = &rest[1 + count..];
+    }
+    rest.is_empty() || is_private_use(rest)
+}
+
+fn is_alphanumeric(s: &str) -> bool {
+    s.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+const IRREGULAR_LANGUAGE_TAGS: [&str; 17] = [
+    "en-GB-oed",
+    "i-ami",
+    "i-bnn",
+    "i-default",
+    "i-enochian",
+    "i-hak",
+    "i-klingon",
+    "i-lux",
+    "i-mingo",
+    "i-navajo",
+    "i-pwn",
+    "i-tao",
+    "i-tay",
+    "i-tsu",
+    "sgn-BE-FR",
+    "sgn-BE-NL",
+    "sgn-CH-DE",
+];
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
@@ -471,6 +707,32 @@ mod tests {
     use std::borrow::Borrow;
     use std::collections::HashMap;
 
+    #[test]
+    fn test_requires_cdata() {
+        assert!(!requires_cdata("plain text > more"));
+        assert!(!requires_cdata("]] >"));
+        assert!(requires_cdata("a < b"));
+        assert!(requires_cdata("a && b"));
+        assert!(requires_cdata("x]]>y"));
+    }
+
+    #[test]
+    fn test_relax_escapes() {
+        let escaped = escape(r#"a & 'b' < "c" > ]]>"#);
+        assert_eq!(
+            relax_escapes(&escaped, false),
+            r#"a &#38; 'b' &#60; "c" > ]]&#62;"#
+        );
+        assert_eq!(
+            relax_escapes(&escaped, true),
+            r#"a &#38; 'b' &#60; &#34;c&#34; > ]]&#62;"#
+        );
+        assert_eq!(
+            relax_escapes("&#x20AC; &#38;#39;", false),
+            "&#x20AC; &#38;#39;"
+        );
+    }
+
     #[test]
     fn test_space_handling_default() {
         let sh = SpaceHandling::default();
@@ -599,4 +861,62 @@ mod tests {
             "$, £, €, and ¥"
         );
     }
+
+    #[test]
+    fn test_iri_references() {
+        assert!(is_iri_reference("http://example.org/ns#"));
+        assert!(is_iri_reference("urn:isbn:0451450523"));
+        assert!(is_iri_reference("http://example.org/%C3%A9t%C3%A9"));
+        assert!(is_iri_reference("http://example.org/été"));
+        assert!(is_iri_reference("../relative/path"));
+        assert!(!is_iri_reference("http://example.org/a b"));
+        assert!(!is_iri_reference("http://example.org/<ns>"));
+        assert!(!is_iri_reference("http://example.org/%G1"));
+        assert!(!is_iri_reference("http://example.org/#a#b"));
+
+        assert!(is_absolute_iri("http://example.org/ns"));
+        assert!(is_absolute_iri("urn:example"));
+        assert!(is_absolute_iri("x-private+v1.0:thing"));
+        assert!(!is_absolute_iri("../relative/path"));
+        assert!(!is_absolute_iri("example.org/ns"));
+        assert!(!is_absolute_iri("1http://example.org"));
+        assert!(!is_absolute_iri("/a:b"));
+    }
+
+    #[test]
+    fn test_language_tags() {
+        for tag in [
+            "en",
+            "en-US",
+            "zh-Hant-TW",
+            "sr-Latn-RS",
+            "es-419",
+            "de-CH-1901",
+            "sl-rozaj-biske",
+            "zh-yue-HK",
+            "en-US-u-islamcal",
+            "en-a-bbb-x-a-ccc",
+            "x-whatever",
+            "qaa-Qaaa-QM-x-southern",
+            "i-klingon",
+            "art-lojban",
+        ] {
+            assert!(is_language_tag(tag), "{}", tag);
+        }
+        for tag in [
+            "",
+            "e",
+            "en-",
+            "en_US",
+            "123",
+            "en-US-u",
+            "de-419-DE",
+            "a-DE",
+            "ar-a-aaa-b-bbb-a-ccc",
+            "en-toolongsubtag",
+            "x",
+        ] {
+            assert!(!is_language_tag(tag), "{}", tag);
+        }
+    }
 }
@@ -1,4 +1,9 @@
-use xml_dom::level2::convert::{as_attribute, as_attribute_mut, as_document, as_element_mut};
+use xml_dom::level2::convert::{
+    as_attribute, as_attribute_mut, as_document, as_document_mut, as_element_mut,
+};
+use xml_dom::level2::ext::dom_impl as ext_dom_impl;
+use xml_dom::level2::ext::writer::{node_to_string, WriterOptions};
+use xml_dom::level2::ext::IdAttributes;
 use xml_dom::level2::*;
 pub mod common;
 
@@ -230,16 +235,344 @@ fn test_normalization_character_entities() {
 }
 
 #[test]
-#[should_panic]
 fn test_normalization_entity_not_found() {
     let document_node = common::create_empty_rdf_document();
     let document = as_document(&document_node).unwrap();
     let mut element_node = document.document_element().unwrap();
     let element = as_element_mut(&mut element_node).unwrap();
 
+    // references to undeclared entities are kept.
     element.set_attribute("test", "hello&pound;world").unwrap();
     assert_eq!(
         element.get_attribute("test"),
-        Some("hello£world".to_string())
+        Some("hello&#38;pound;world".to_string())
     );
 }
+
+#[test]
+fn test_value_children() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+
+    let mut attribute_node =
+        ext_dom_impl::create_defaulted_attribute(document_node.clone(), "test", "hello ").unwrap();
+    let _safe_to_ignore = element_node
+        .set_attribute_node(attribute_node.clone())
+        .unwrap();
+    let _safe_to_ignore = attribute_node
+        .append_child(document.create_entity_reference("pound").unwrap())
+        .unwrap();
+    let text_node = attribute_node
+        .append_child(document.create_text_node(" world"))
+        .unwrap();
+    assert!(as_attribute(&attribute_node).unwrap().specified());
+    assert_eq!(attribute_node.child_nodes().len(), 3);
+    assert_eq!(
+        element_node.get_attribute("test"),
+        Some("hello &#38;pound; world".to_string())
+    );
+
+    let _safe_to_ignore = attribute_node.remove_child(text_node).unwrap();
+    assert_eq!(
+        as_attribute(&attribute_node).unwrap().value(),
+        Some("hello &#38;pound;".to_string())
+    );
+
+    common::sub_test("test_value_children", "not allowed");
+    assert!(attribute_node
+        .append_child(document.create_comment("no"))
+        .is_err());
+    assert!(attribute_node
+        .append_child(document.create_element("no").unwrap())
+        .is_err());
+}
+
+#[test]
+fn test_value_children_ids() {
+    let mut document_node = common::create_empty_rdf_document();
+    document_node.register_id_attribute(None, "key").unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+    element_node.set_attribute("key", "one").unwrap();
+    assert!(document.get_element_by_id("one").is_some());
+
+    let mut attribute_node = element_node.get_attribute_node("key").unwrap();
+    let _safe_to_ignore = attribute_node
+        .append_child(document.create_text_node("-two"))
+        .unwrap();
+    assert!(document.get_element_by_id("one").is_none());
+    assert_eq!(document.get_element_by_id("one-two"), Some(element_node));
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_value_entity_resolution() {
+    use xml_dom::parser::{read_xml_with, ParseOptions, UnknownEntities};
+
+    let xml = r#"<!DOCTYPE root [<!ENTITY co "Example Co">]><root title="T"/>"#;
+    let mut options = ParseOptions::new();
+    options.set_unknown_entities(UnknownEntities::Reference);
+    let (document_node, _) = read_xml_with(xml, options).unwrap();
+    let document = as_document(&document_node).unwrap();
+    let element_node = document.document_element().unwrap();
+    let mut attribute_node = element_node.get_attribute_node("title").unwrap();
+    let _safe_to_ignore = attribute_node
+        .append_child(document.create_text_node(" by "))
+        .unwrap();
+    let _safe_to_ignore = attribute_node
+        .append_child(document.create_entity_reference("co").unwrap())
+        .unwrap();
+    assert_eq!(
+        element_node.get_attribute("title"),
+        Some("T by Example Co".to_string())
+    );
+}
+
+#[test]
+fn test_specified() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let node = document.create_attribute_with("test", "some data").unwrap();
+    let attribute = as_attribute(&node).unwrap();
+    assert!(attribute.specified());
+
+    let mut node =
+        ext_dom_impl::create_defaulted_attribute(document_node.clone(), "test", "default").unwrap();
+    let attribute = as_attribute_mut(&mut node).unwrap();
+    assert!(!attribute.specified());
+    assert_eq!(attribute.value(), Some("default".to_string()));
+
+    assert!(attribute.set_value("default").is_ok());
+    assert!(attribute.specified());
+
+    let node =
+        ext_dom_impl::create_defaulted_attribute(document_node.clone(), "test", "default").unwrap();
+    let cloned = node.clone_node(false).unwrap();
+    assert!(as_attribute(&cloned).unwrap().specified());
+}
+
+#[test]
+fn test_omit_unspecified_attributes() {
+    let mut document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let root = as_element_mut(&mut root_node).unwrap();
+
+    let defaulted =
+        ext_dom_impl::create_defaulted_attribute(document_node.clone(), "version", "1.0").unwrap();
+    assert!(root.set_attribute_node(defaulted).is_ok());
+
+    let options = WriterOptions::new();
+    let all_xml = node_to_string(&document_node, &options);
+    assert!(all_xml.contains("version=\"1.0\""));
+    assert_eq!(all_xml, document_node.to_string());
+
+    let mut options = WriterOptions::new();
+    options.set_omit_unspecified_attributes();
+    let specified_xml = node_to_string(&document_node, &options);
+    assert!(!specified_xml.contains("version=\"1.0\""));
+
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let root = as_element_mut(&mut root_node).unwrap();
+    assert!(root.set_attribute("version", "1.0").is_ok());
+    let specified_xml = node_to_string(&document_node, &options);
+    assert!(specified_xml.contains("version=\"1.0\""));
+}
+
+#[test]
+fn test_attributes_in_ns() {
+    const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node
+        .set_attribute_ns(XLINK_NS, "xlink:type", "simple")
+        .unwrap();
+    root_node
+        .set_attribute_ns(XLINK_NS, "xlink:href", "http://example.org/")
+        .unwrap();
+    root_node
+        .set_attribute_ns(common::RDF_NS, "rdf:about", "http://example.org/")
+        .unwrap();
+    root_node.set_attribute("type", "plain").unwrap();
+
+    let names: Vec<String> = root_node
+        .attributes_in_ns(XLINK_NS)
+        .iter()
+        .map(|attribute| attribute.node_name().to_string())
+        .collect();
+    assert_eq!(names, vec!["xlink:href", "xlink:type"]);
+    assert_eq!(root_node.attributes_in_ns(common::RDF_NS).len(), 1);
+    assert!(root_node.attributes_in_ns(common::DC_NS).is_empty());
+}
+
+#[test]
+fn test_many_attributes() {
+    const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for index in 0..500 {
+        root_node
+            .set_attribute(&format!("attr-{}", index), &index.to_string())
+            .unwrap();
+        root_node
+            .set_attribute_ns(XLINK_NS, &format!("xlink:attr-{}", index), "link")
+            .unwrap();
+    }
+    assert_eq!(root_node.attributes().len(), 1000);
+    assert_eq!(root_node.get_attribute("attr-250"), Some("250".to_string()));
+    assert_eq!(
+        root_node.get_attribute("xlink:attr-250"),
+        Some("link".to_string())
+    );
+    assert_eq!(
+        root_node.get_attribute_ns(XLINK_NS, "attr-250"),
+        Some("link".to_string())
+    );
+    assert!(root_node.has_attribute_ns(XLINK_NS, "attr-499"));
+    assert!(!root_node.has_attribute_ns(XLINK_NS, "attr-500"));
+
+    root_node.remove_attribute_ns(XLINK_NS, "attr-250").unwrap();
+    assert!(!root_node.has_attribute("xlink:attr-250"));
+    assert!(root_node.has_attribute("attr-250"));
+    root_node.remove_attribute("attr-250").unwrap();
+    assert!(root_node.get_attribute_node("attr-250").is_none());
+    assert_eq!(root_node.attributes().len(), 998);
+
+    let clone_node = root_node.clone_node(true).unwrap();
+    assert_eq!(
+        clone_node.get_attribute_ns(XLINK_NS, "attr-1"),
+        Some("link".to_string())
+    );
+}
+
+#[test]
+fn test_attribute_order() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element = document.create_element("element").unwrap();
+    for name in ["z", "a", "m", "b"] {
+        element.set_attribute(name, name).unwrap();
+    }
+    element.set_attribute("a", "changed").unwrap();
+    element.remove_attribute("m").unwrap();
+    element.set_attribute("m", "m").unwrap();
+
+    let names: Vec<String> = element
+        .attributes()
+        .keys()
+        .map(|name| name.to_string())
+        .collect();
+    assert_eq!(names, vec!["z", "a", "b", "m"]);
+    assert_eq!(
+        element.to_string(),
+        r#"<element z="z" a="changed" b="b" m="m"></element>"#
+    );
+
+    let clone_node = element.clone_node(false).unwrap();
+    assert_eq!(
+        clone_node.attributes().keys().collect::<Vec<Name>>(),
+        element.attributes().keys().collect::<Vec<Name>>()
+    );
+}
+
+#[test]
+fn test_named_attribute_map() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element = document.create_element("element").unwrap();
+    let mut attributes = element.attributes();
+    assert!(attributes.is_empty());
+
+    let first = document.create_attribute_with("first", "1").unwrap();
+    assert_eq!(attributes.set_named_item(first.clone()), Ok(None));
+    assert_eq!(element.get_attribute("first"), Some("1".to_string()));
+    assert_eq!(attributes.length(), 1);
+
+    let replacement = document.create_attribute_with("first", "2").unwrap();
+    assert_eq!(
+        attributes.set_named_item(replacement.clone()),
+        Ok(Some(first))
+    );
+    assert_eq!(attributes.item(0), Some(replacement.clone()));
+
+    let title = document
+        .create_attribute_ns(common::DC_NS, "dc:title")
+        .unwrap();
+    assert_eq!(attributes.set_named_item_ns(title.clone()), Ok(None));
+    assert_eq!(
+        attributes.get_named_item_ns(common::DC_NS, "title"),
+        Some(title.clone())
+    );
+    assert_eq!(
+        attributes.remove_named_item_ns(common::DC_NS, "title"),
+        Ok(title)
+    );
+
+    assert_eq!(attributes.remove_named_item("first"), Ok(replacement));
+    assert!(!element.has_attribute("first"));
+    assert_eq!(attributes.remove_named_item("first"), Err(Error::NotFound));
+    assert_eq!(
+        attributes.set_named_item(document.create_element("not-an-attribute").unwrap()),
+        Err(Error::HierarchyRequest)
+    );
+
+    // the attributes of a node that is not an element are readonly.
+    let mut text_attributes = document.create_text_node("text").attributes();
+    assert_eq!(
+        text_attributes.set_named_item(document.create_attribute("name").unwrap()),
+        Err(Error::NoModificationAllowed)
+    );
+
+    element.set_attribute("last", "3").unwrap();
+    assert_eq!(attributes.len(), 1);
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_parsed_attribute_order() {
+    let xml = r#"<root zeta="1" alpha="2" xmlns:x="urn:x" x:mid="3" beta="4"/>"#;
+    let document_node = xml_dom::parser::read_xml(xml).unwrap();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    let names: Vec<String> = root_node
+        .attributes()
+        .into_iter()
+        .map(|(name, _)| name.to_string())
+        .collect();
+    assert_eq!(names, vec!["zeta", "alpha", "xmlns:x", "x:mid", "beta"]);
+    assert_eq!(
+        root_node.to_string(),
+        r#"<root zeta="1" alpha="2" xmlns:x="urn:x" x:mid="3" beta="4"></root>"#
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_attribute_order_round_trip() {
+    let xml = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10" viewBox="0 0 10 10">"#,
+        r#"<rect y="1" x="2" style="fill:red" id="r" height="3" width="4" class="box"></rect>"#,
+        r#"<g xmlns:x="urn:x" transform="scale(2)" x:z="1" x:a="2"></g>"#,
+        r#"</svg>"#
+    );
+    let document_node = xml_dom::parser::read_xml(xml).unwrap();
+    assert_eq!(document_node.to_string(), xml);
+    assert_eq!(
+        node_to_string(&document_node, &WriterOptions::new()),
+        document_node.to_string()
+    );
+
+    let reparsed_node = xml_dom::parser::read_xml(document_node.to_string()).unwrap();
+    assert_eq!(reparsed_node.to_string(), xml);
+
+    let copy_node = document_node.clone_node(true).unwrap();
+    assert_eq!(copy_node.to_string(), xml);
+}
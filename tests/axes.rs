@@ -0,0 +1,177 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::convert::{as_document, as_document_mut, as_element, as_element_mut};
+use xml_dom::level2::ext::{Axes, NodeIterators, TypedChildren};
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+pub mod common;
+
+const XML: &str = r#"<a><b id="1"><c/><d/></b><e><f/></e><g/></a>"#;
+
+fn names(nodes: impl Iterator<Item = RefNode>) -> Vec<String> {
+    nodes.map(|node| node.node_name().to_string()).collect()
+}
+
+fn element(document_node: &RefNode, name: &str) -> RefNode {
+    let document = as_document(document_node).unwrap();
+    document.get_elements_by_tag_name(name).remove(0)
+}
+
+#[test]
+fn test_ancestor_axes() {
+    let document_node = read_xml(XML).unwrap();
+    let c = element(&document_node, "c");
+    assert_eq!(names(c.ancestor()), vec!["b", "a", "#document"]);
+    assert_eq!(
+        names(c.ancestor_or_self()),
+        vec!["c", "b", "a", "#document"]
+    );
+
+    let b = element(&document_node, "b");
+    let id = as_element(&b).unwrap().get_attribute_node("id").unwrap();
+    assert_eq!(names(id.ancestor()), vec!["b", "a", "#document"]);
+}
+
+#[test]
+fn test_descendant_axes() {
+    let document_node = read_xml(XML).unwrap();
+    let a = element(&document_node, "a");
+    assert_eq!(names(a.descendant()), vec!["b", "c", "d", "e", "f", "g"]);
+    assert_eq!(
+        names(a.descendant_or_self()),
+        vec!["a", "b", "c", "d", "e", "f", "g"]
+    );
+    assert_eq!(names(a.child()), vec!["b", "e", "g"]);
+}
+
+#[test]
+fn test_sibling_axes() {
+    let document_node = read_xml(XML).unwrap();
+    let e = element(&document_node, "e");
+    assert_eq!(names(e.following_sibling()), vec!["g"]);
+    assert_eq!(names(e.preceding_sibling()), vec!["b"]);
+
+    let a = element(&document_node, "a");
+    assert_eq!(a.following_sibling().count(), 0);
+}
+
+#[test]
+fn test_node_iterators() {
+    let document_node = read_xml(XML).unwrap();
+    let a = element(&document_node, "a");
+    assert_eq!(names(a.children()), vec!["b", "e", "g"]);
+    assert_eq!(names(a.descendants()), vec!["b", "c", "d", "e", "f", "g"]);
+
+    let e = element(&document_node, "e");
+    assert_eq!(names(e.ancestors()), vec!["a", "#document"]);
+    assert_eq!(names(e.following_siblings()), vec!["g"]);
+    assert_eq!(names(e.preceding_siblings()), vec!["b"]);
+
+    let f = element(&document_node, "f");
+    assert_eq!(f.children().count(), 0);
+    assert_eq!(
+        names(
+            document_node
+                .descendants()
+                .filter(|node| node.ancestors().count() == 2)
+        ),
+        vec!["b", "e", "g"]
+    );
+}
+
+#[test]
+fn test_following_and_preceding() {
+    let document_node = read_xml(XML).unwrap();
+    let d = element(&document_node, "d");
+    assert_eq!(names(d.following()), vec!["e", "f", "g"]);
+    assert_eq!(names(d.preceding()), vec!["c"]);
+
+    let f = element(&document_node, "f");
+    assert_eq!(names(f.following()), vec!["g"]);
+    assert_eq!(names(f.preceding()), vec!["d", "c", "b"]);
+
+    let b = element(&document_node, "b");
+    let id = as_element(&b).unwrap().get_attribute_node("id").unwrap();
+    assert_eq!(names(id.following()), vec!["c", "d", "e", "f", "g"]);
+    assert_eq!(id.preceding().count(), 0);
+}
+
+#[test]
+fn test_axes_partition_document() {
+    let document_node = read_xml(XML).unwrap();
+    let all = document_node.descendant_or_self().count();
+    for node in document_node.descendant_or_self() {
+        let partition = node.ancestor().count()
+            + node.descendant().count()
+            + node.following().count()
+            + node.preceding().count()
+            + 1;
+        assert_eq!(partition, all);
+    }
+}
+
+#[test]
+fn test_attribute_axis() {
+    let document_node = read_xml(XML).unwrap();
+    let b = element(&document_node, "b");
+    assert_eq!(names(b.attribute_axis()), vec!["id"]);
+    assert_eq!(element(&document_node, "c").attribute_axis().count(), 0);
+}
+
+#[test]
+fn test_namespace_axis() {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("a"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut a = document.document_element().unwrap();
+    let mut b = document.create_element("b").unwrap();
+    let mut c = document.create_element("c").unwrap();
+    {
+        let a_element = as_element_mut(&mut a).unwrap();
+        a_element
+            .set_attribute_ns(common::XMLNS_NS, "xmlns", "urn:default")
+            .unwrap();
+        a_element
+            .set_attribute_ns(common::XMLNS_NS, "xmlns:p", "urn:p")
+            .unwrap();
+        let b_element = as_element_mut(&mut b).unwrap();
+        b_element
+            .set_attribute_ns(common::XMLNS_NS, "xmlns", "")
+            .unwrap();
+    }
+    let _ = a.append_child(c.clone()).unwrap();
+    let _ = a.append_child(b.clone()).unwrap();
+    let d = b
+        .append_child(document.create_element("d").unwrap())
+        .unwrap();
+    let _ = c.append_child(document.create_text_node("text")).unwrap();
+
+    let namespaces: Vec<(Option<String>, String)> = c.namespace_axis().collect();
+    assert_eq!(
+        namespaces,
+        vec![
+            (None, "urn:default".to_string()),
+            (Some("p".to_string()), "urn:p".to_string()),
+            (Some("xml".to_string()), common::XML_NS_URI.to_string()),
+        ]
+    );
+
+    assert!(d.namespace_axis().all(|(prefix, _)| prefix.is_some()));
+    assert_eq!(document_node.namespace_axis().count(), 0);
+}
+
+#[test]
+fn test_typed_children() {
+    let document_node = read_xml("<a>one<b/><!--two--><c/>three<![CDATA[four]]></a>").unwrap();
+    let a = element(&document_node, "a");
+    assert_eq!(names(a.children_elements()), vec!["b", "c"]);
+    let text: Vec<Option<String>> = a.children_text().map(|node| node.node_value()).collect();
+    assert_eq!(
+        text,
+        vec![Some("one".to_string()), Some("three".to_string())]
+    );
+    assert_eq!(a.children_of_type(NodeType::Comment).count(), 1);
+    assert_eq!(a.children_of_type(NodeType::CData).count(), 1);
+    assert_eq!(element(&document_node, "b").children_elements().count(), 0);
+}
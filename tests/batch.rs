@@ -0,0 +1,126 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::dom_impl as ext_dom_impl;
+use xml_dom::level2::ext::{Batch, ProcessingOptions};
+use xml_dom::level2::*;
+
+pub mod common;
+
+const XMLNS_NS: &str = "http://www.w3.org/2000/xmlns/";
+
+#[test]
+fn test_batch_applied() {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("rules"), None)
+        .unwrap();
+    let document_ref = document_node.clone();
+    let document = as_document(&document_ref).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let old_node = document.create_element("old").unwrap();
+    let _safe_to_ignore = root_node.append_child(old_node.clone()).unwrap();
+    let new_node = document.create_element("new").unwrap();
+    let text_node = document.create_text_node("draft");
+
+    let result = document_node.batch(|tx| {
+        tx.insert_before(&root_node, new_node.clone(), Some(old_node.clone()));
+        tx.remove_child(&root_node, old_node.clone());
+        tx.set_attribute(&new_node, "xml:id", "r1");
+        tx.append_child(&new_node, text_node.clone());
+        tx.set_node_value(&text_node, "final");
+        assert_eq!(tx.len(), 5);
+    });
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        root_node.to_string(),
+        r#"<rules><new xml:id="r1">final</new></rules>"#
+    );
+    assert_eq!(document.get_element_by_id("r1"), Some(new_node));
+}
+
+#[test]
+fn test_batch_rolled_back() {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("rules"), None)
+        .unwrap();
+    let document_ref = document_node.clone();
+    let document = as_document(&document_ref).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("status", "active").unwrap();
+    let mut first_node = document.create_element("rule").unwrap();
+    first_node.set_attribute("xml:id", "r1").unwrap();
+    let _safe_to_ignore = root_node.append_child(first_node.clone()).unwrap();
+    let text_node = document.create_text_node("first");
+    let _safe_to_ignore = first_node.append_child(text_node.clone()).unwrap();
+    let second_node = document.create_element("rule").unwrap();
+    let before = root_node.to_string();
+
+    let other_node = get_implementation()
+        .create_document(None, Some("other"), None)
+        .unwrap();
+    let result = document_node.batch(|tx| {
+        tx.remove_attribute(&root_node, "status");
+        tx.set_node_value(&text_node, "changed");
+        tx.remove_child(&root_node, first_node.clone());
+        tx.append_child(&root_node, second_node.clone());
+        tx.set_attribute(&second_node, "xml:id", "r1");
+        tx.set_attribute(&first_node, "xml:id", "r2");
+        tx.append_child(&document_ref, document.create_text_node("not allowed"));
+        tx.set_attribute(&other_node, "status", "ignored");
+    });
+    let violations = result.unwrap_err();
+    assert_eq!(
+        violations
+            .iter()
+            .map(|violation| (violation.operation, violation.error.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Some(6), Error::HierarchyRequest),
+            (Some(7), Error::WrongDocument),
+        ]
+    );
+    assert_eq!(violations[1].node, other_node);
+
+    common::sub_test("test_batch_rolled_back", "restored");
+    assert_eq!(root_node.to_string(), before);
+    assert_eq!(
+        root_node.get_attribute("status"),
+        Some("active".to_string())
+    );
+    assert_eq!(first_node.parent_node(), Some(root_node.clone()));
+    assert!(second_node.parent_node().is_none());
+    assert_eq!(document.get_element_by_id("r1"), Some(first_node));
+    assert!(document.get_element_by_id("r2").is_none());
+}
+
+#[test]
+fn test_batch_namespace_violation() {
+    let mut options = ProcessingOptions::new();
+    options.set_add_namespaces();
+    let mut document_node = ext_dom_impl::get_implementation_ext()
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap();
+    let document_ref = document_node.clone();
+    let document = as_document(&document_ref).unwrap();
+    let root_node = document.document_element().unwrap();
+    let child_node = document
+        .create_element_ns("http://example.org/two", "ex:child")
+        .unwrap();
+
+    let result = document_node.batch(|tx| {
+        tx.set_attribute_ns(&root_node, XMLNS_NS, "xmlns:ex", "http://example.org/one");
+        tx.append_child(&root_node, child_node.clone());
+    });
+    let violations = result.unwrap_err();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].operation, None);
+    assert_eq!(violations[0].node, child_node);
+    assert_eq!(violations[0].error, Error::Namespace);
+
+    common::sub_test("test_batch_namespace_violation", "restored");
+    assert!(root_node.child_nodes().is_empty());
+    assert!(root_node.get_attribute("xmlns:ex").is_none());
+    let result = document_node.batch(|tx| {
+        tx.set_attribute_ns(&root_node, XMLNS_NS, "xmlns:ex", "http://example.org/two");
+        tx.append_child(&root_node, child_node.clone());
+    });
+    assert_eq!(result, Ok(()));
+}
@@ -0,0 +1,104 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::canonical::{CanonicalElement, CanonicalNode, ExpandedName};
+use xml_dom::level2::*;
+
+const XMLNS: &str = "http://www.w3.org/2000/xmlns/";
+
+fn create_document(namespace_uri: &str, root_name: &str) -> RefNode {
+    get_implementation()
+        .create_document(Some(namespace_uri), Some(root_name), None)
+        .unwrap()
+}
+
+#[test]
+fn test_canonical_ignores_prefixes() {
+    let first_node = create_document("urn:a", "a:root");
+    let first = as_document(&first_node).unwrap();
+    let mut root_node = first.document_element().unwrap();
+    root_node
+        .set_attribute_ns(XMLNS, "xmlns:a", "urn:a")
+        .unwrap();
+    root_node.set_attribute("plain", "1").unwrap();
+    let mut child_node = root_node
+        .append_child(first.create_element_ns("urn:b", "b:child").unwrap())
+        .unwrap();
+    child_node
+        .set_attribute_ns("urn:a", "a:flag", "yes")
+        .unwrap();
+    let _safe_to_ignore = child_node
+        .append_child(first.create_text_node("one "))
+        .unwrap();
+    let _safe_to_ignore = child_node
+        .append_child(first.create_cdata_section("& two").unwrap())
+        .unwrap();
+
+    let second_node = create_document("urn:a", "x:root");
+    let second = as_document(&second_node).unwrap();
+    let mut root_node = second.document_element().unwrap();
+    root_node.set_attribute("plain", "1").unwrap();
+    let mut child_node = root_node
+        .append_child(second.create_element_ns("urn:b", "y:child").unwrap())
+        .unwrap();
+    child_node
+        .set_attribute_ns("urn:a", "z:flag", "yes")
+        .unwrap();
+    let _safe_to_ignore = child_node
+        .append_child(second.create_text_node("one & two"))
+        .unwrap();
+
+    let canonical = CanonicalElement::from_node(&first_node).unwrap();
+    assert_eq!(
+        canonical,
+        CanonicalElement::from_node(&second_node).unwrap()
+    );
+    assert_eq!(canonical.name, ExpandedName::new(Some("urn:a"), "root"));
+    assert_eq!(canonical.attributes.len(), 1);
+    match &canonical.children[0] {
+        CanonicalNode::Element(child) => {
+            assert_eq!(
+                child.children,
+                vec![CanonicalNode::Text("one & two".to_string())]
+            );
+        }
+        _ => panic!("expected an element"),
+    }
+    assert_eq!(
+        canonical.prefixes(),
+        vec![
+            ("urn:a".to_string(), "ns1".to_string()),
+            ("urn:b".to_string(), "ns2".to_string()),
+        ]
+    );
+    assert_eq!(
+        canonical.to_string(),
+        "<ns1:root xmlns:ns1=\"urn:a\" xmlns:ns2=\"urn:b\" plain=\"1\">\
+         <ns2:child ns1:flag=\"yes\">one &#38; two</ns2:child></ns1:root>"
+    );
+
+    let round_trip = canonical.to_document().unwrap();
+    assert_eq!(CanonicalElement::from_node(&round_trip).unwrap(), canonical);
+}
+
+#[test]
+fn test_canonical_errors() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    assert!(CanonicalElement::from_node(&document.create_comment("c")).is_err());
+
+    root_node.set_attribute("u:attr", "1").unwrap();
+    assert_eq!(
+        CanonicalElement::from_node(&root_node),
+        Err(Error::Namespace)
+    );
+    root_node
+        .set_attribute_ns(XMLNS, "xmlns:u", "urn:u")
+        .unwrap();
+    let canonical = CanonicalElement::from_node(&root_node).unwrap();
+    assert_eq!(
+        canonical.attributes.keys().next(),
+        Some(&ExpandedName::new(Some("urn:u"), "attr"))
+    );
+}
@@ -0,0 +1,66 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::collect::{Children, Fragment};
+use xml_dom::level2::*;
+
+fn create_document() -> RefNode {
+    get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap()
+}
+
+#[test]
+fn test_collect_fragment() {
+    let document_node = create_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let fragment: Fragment = (0..3)
+        .map(|index| document.create_text_node(&index.to_string()))
+        .collect();
+    assert_eq!(fragment.len(), 3);
+    let fragment_node = fragment.into_node().unwrap();
+    assert_eq!(fragment_node.node_type(), NodeType::DocumentFragment);
+    assert_eq!(fragment_node.owner_document(), Some(document_node.clone()));
+    assert_eq!(fragment_node.child_nodes().len(), 3);
+
+    let mut fragment = Fragment::new();
+    assert!(fragment.is_empty());
+    assert_eq!(fragment.clone().into_node(), Err(Error::InvalidState));
+    fragment.clone().append_to(&mut root_node).unwrap();
+
+    fragment.extend(vec![document.create_element("a").unwrap()]);
+    fragment.extend(vec![fragment_node]);
+    fragment.append_to(&mut root_node).unwrap();
+    assert_eq!(root_node.to_string(), "<root><a></a>012</root>");
+
+    let fragment: Fragment = vec![document_node.clone()].into_iter().collect();
+    assert!(fragment.into_node().is_err());
+}
+
+#[test]
+fn test_extend_children() {
+    let document_node = create_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let mut children = Children::new(&mut root_node);
+    children.extend(
+        ["a", "b"]
+            .iter()
+            .map(|name| document.create_element(name).unwrap()),
+    );
+    children.extend(vec![document.create_comment("c")]);
+    children.finish().unwrap();
+    assert_eq!(root_node.to_string(), "<root><a></a><b></b><!--c--></root>");
+
+    // the first error is kept, and later nodes are ignored.
+    let mut other_node = document.create_element("other").unwrap();
+    let mut children = Children::new(&mut other_node);
+    children.extend(vec![
+        document.create_element("d").unwrap(),
+        document_node.clone(),
+    ]);
+    children.extend(vec![document.create_element("e").unwrap()]);
+    assert_eq!(children.finish(), Err(Error::HierarchyRequest));
+    assert!(!other_node.has_child_nodes());
+}
@@ -0,0 +1,132 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{DocumentPosition, NodeComparison};
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+const XML: &str = r#"<a><b id="1" x="2"><c/><d/></b><e><f/></e><g/></a>"#;
+
+fn element(document_node: &RefNode, name: &str) -> RefNode {
+    let document = as_document(document_node).unwrap();
+    document.get_elements_by_tag_name(name).remove(0)
+}
+
+#[test]
+fn test_is_same_node() {
+    let document_node = read_xml(XML).unwrap();
+    let b = element(&document_node, "b");
+    assert!(b.is_same_node(&element(&document_node, "b")));
+    assert!(!b.is_same_node(&element(&document_node, "e")));
+    assert!(!b.is_same_node(&b.clone_node(true).unwrap()));
+}
+
+#[test]
+fn test_is_equal_node() {
+    let document_node = read_xml(XML).unwrap();
+    let other_node = read_xml(XML).unwrap();
+    assert!(document_node.is_equal_node(&other_node));
+
+    let b = element(&document_node, "b");
+    assert!(b.is_equal_node(&b.clone_node(true).unwrap()));
+    assert!(!b.is_equal_node(&b.clone_node(false).unwrap()));
+    assert!(!b.is_equal_node(&element(&document_node, "e")));
+
+    // attribute order is not significant, attribute values are.
+    let reordered_node = read_xml(r#"<b x="2" id="1"><c/><d/></b>"#).unwrap();
+    assert!(b.is_equal_node(&element(&reordered_node, "b")));
+    let changed_node = read_xml(r#"<b id="1" x="3"><c/><d/></b>"#).unwrap();
+    assert!(!b.is_equal_node(&element(&changed_node, "b")));
+
+    let mut other_b = element(&other_node, "b");
+    other_b.remove_attribute("x").unwrap();
+    assert!(!document_node.is_equal_node(&other_node));
+
+    let with_document_type = read_xml(r#"<!DOCTYPE a SYSTEM "a.dtd"><a/>"#).unwrap();
+    assert!(!with_document_type.is_equal_node(&read_xml("<a/>").unwrap()));
+    assert!(
+        with_document_type.is_equal_node(&read_xml(r#"<!DOCTYPE a SYSTEM "a.dtd"><a/>"#).unwrap())
+    );
+}
+
+#[test]
+fn test_compare_document_position() {
+    let document_node = read_xml(XML).unwrap();
+    let a = element(&document_node, "a");
+    let b = element(&document_node, "b");
+    let c = element(&document_node, "c");
+    let f = element(&document_node, "f");
+
+    assert!(b.compare_document_position(&b).is_empty());
+    assert_eq!(
+        b.compare_document_position(&a),
+        DocumentPosition::CONTAINS | DocumentPosition::PRECEDING
+    );
+    assert_eq!(
+        b.compare_document_position(&c),
+        DocumentPosition::CONTAINED_BY | DocumentPosition::FOLLOWING
+    );
+    assert_eq!(
+        document_node.compare_document_position(&f),
+        DocumentPosition::CONTAINED_BY | DocumentPosition::FOLLOWING
+    );
+    assert_eq!(c.compare_document_position(&f), DocumentPosition::FOLLOWING);
+    assert_eq!(f.compare_document_position(&c), DocumentPosition::PRECEDING);
+    assert_eq!(DocumentPosition::PRECEDING.bits(), 0x02);
+}
+
+#[test]
+fn test_compare_document_position_attributes() {
+    let document_node = read_xml(XML).unwrap();
+    let b = element(&document_node, "b");
+    let c = element(&document_node, "c");
+    let id = b.get_attribute_node("id").unwrap();
+    let x = b.get_attribute_node("x").unwrap();
+
+    assert_eq!(
+        id.compare_document_position(&b),
+        DocumentPosition::CONTAINS | DocumentPosition::PRECEDING
+    );
+    assert_eq!(
+        b.compare_document_position(&id),
+        DocumentPosition::CONTAINED_BY | DocumentPosition::FOLLOWING
+    );
+    assert_eq!(
+        id.compare_document_position(&c),
+        DocumentPosition::FOLLOWING
+    );
+    assert_eq!(
+        c.compare_document_position(&id),
+        DocumentPosition::PRECEDING
+    );
+
+    let position = id.compare_document_position(&x);
+    assert!(position.contains(DocumentPosition::IMPLEMENTATION_SPECIFIC));
+    assert!(position.contains(DocumentPosition::FOLLOWING));
+    assert!(x
+        .compare_document_position(&id)
+        .contains(DocumentPosition::PRECEDING));
+}
+
+#[test]
+fn test_compare_document_position_disconnected() {
+    let document_node = read_xml(XML).unwrap();
+    let other_node = read_xml(XML).unwrap();
+    let b = element(&document_node, "b");
+    let other_b = element(&other_node, "b");
+
+    let position = b.compare_document_position(&other_b);
+    assert!(position
+        .contains(DocumentPosition::DISCONNECTED | DocumentPosition::IMPLEMENTATION_SPECIFIC));
+    let reverse = other_b.compare_document_position(&b);
+    assert_ne!(
+        position & (DocumentPosition::PRECEDING | DocumentPosition::FOLLOWING),
+        reverse & (DocumentPosition::PRECEDING | DocumentPosition::FOLLOWING)
+    );
+
+    let document = as_document(&document_node).unwrap();
+    let orphan = document.create_element("orphan").unwrap();
+    assert!(orphan
+        .compare_document_position(&b)
+        .contains(DocumentPosition::DISCONNECTED));
+}
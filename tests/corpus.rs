@@ -0,0 +1,187 @@
+#![cfg(feature = "quick_parser")]
+
+// Parses each of the real-world documents in `tests/fixtures/corpus`, with strict namespaces,
+// and compares a summary of its invariants (node counts, depth, IDs, and namespaces) with the
+// golden file of the same name with the `.golden` extension; the summary must also be unchanged
+// when the document is written and parsed again. To add a
+// document to the corpus add its `.xml` file and create its golden file by running:
+//
+//     CORPUS_UPDATE_GOLDEN=1 cargo test --test corpus
+//
+// Review the golden files written before committing them; a change to a golden file is a
+// change to what the parser, or serializer, produces for that document.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::Namespaced;
+use xml_dom::level2::*;
+use xml_dom::parser::{read_xml_with, ParseOptions, Result as ParseResult};
+
+// ------------------------------------------------------------------------------------------------
+// Harness
+// ------------------------------------------------------------------------------------------------
+
+const UPDATE_GOLDEN: &str = "CORPUS_UPDATE_GOLDEN";
+
+fn corpus_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/corpus")
+}
+
+fn corpus_documents() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(corpus_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "xml"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn is_namespace_declaration(name: &Name) -> bool {
+    name.is_namespace_attribute()
+        || match name.prefix() {
+            None => name.local_name() == "xmlns",
+            Some(prefix) => prefix == "xmlns",
+        }
+}
+
+///
+/// A summary of the invariants of a document, one per line, written in a stable order so that
+/// it may be compared with a golden file.
+///
+fn summarize(document_node: &RefNode) -> String {
+    let document = as_document(document_node).unwrap();
+    let mut node_counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut element_namespaces: BTreeMap<String, usize> = BTreeMap::new();
+    let mut attribute_count = 0;
+    let mut max_depth = 0;
+    let mut ids = Vec::new();
+    let mut declarations = Vec::new();
+
+    let mut pending = vec![(document_node.clone(), 0)];
+    while let Some((node, depth)) = pending.pop() {
+        *node_counts
+            .entry(format!("{:?}", node.node_type()))
+            .or_default() += 1;
+        max_depth = max_depth.max(depth);
+        if node.node_type() == NodeType::Element {
+            let namespace_uri = node
+                .namespace_uri()
+                .or_else(|| node.lookup_namespace_uri(node.prefix().as_deref()));
+            let _ = element_namespaces
+                .entry(namespace_uri.unwrap_or_else(|| "(none)".to_string()))
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+            for (name, attribute_node) in node.attributes() {
+                let value = attribute_node.value().unwrap_or_default();
+                if is_namespace_declaration(&name) {
+                    declarations.push(format!(
+                        "namespace {} = {} on {}",
+                        name,
+                        value,
+                        node.node_name()
+                    ));
+                } else {
+                    attribute_count += 1;
+                    if name.local_name() == "id" {
+                        let registered = document.get_element_by_id(&value) == Some(node.clone());
+                        ids.push(format!(
+                            "id {} = {} on {} (registered: {})",
+                            name,
+                            value,
+                            node.node_name(),
+                            registered
+                        ));
+                    }
+                }
+            }
+        }
+        let mut children = node.child_nodes();
+        children.reverse();
+        pending.extend(
+            children
+                .into_iter()
+                .map(|child_node| (child_node, depth + 1)),
+        );
+    }
+
+    let mut lines = Vec::new();
+    for (node_type, count) in node_counts {
+        lines.push(format!("nodes {} = {}", node_type, count));
+    }
+    lines.push(format!("attributes = {}", attribute_count));
+    lines.push(format!("depth = {}", max_depth));
+    for (namespace_uri, count) in element_namespaces {
+        lines.push(format!("elements in {} = {}", namespace_uri, count));
+    }
+    lines.extend(declarations);
+    lines.extend(ids);
+    lines.join("\n") + "\n"
+}
+
+fn parse(xml: &str) -> ParseResult<RefNode> {
+    let mut options = ParseOptions::new();
+    options.set_strict_namespaces();
+    read_xml_with(xml, options).map(|(document_node, _)| document_node)
+}
+
+///
+/// Parse the document at `path`, check that it round-trips, and compare its summary with the
+/// golden file; returns a description of each problem found.
+///
+fn check_document(path: &Path, update_golden: bool) -> Vec<String> {
+    let name = path.file_name().unwrap().to_string_lossy().to_string();
+    let mut problems = Vec::new();
+
+    let document_node = match parse(&fs::read_to_string(path).unwrap()) {
+        Ok(document_node) => document_node,
+        Err(e) => return vec![format!("{}: failed to parse: {}", name, e)],
+    };
+    let summary = summarize(&document_node);
+
+    match parse(&document_node.to_string()) {
+        Ok(reparsed_node) => {
+            if summarize(&reparsed_node) != summary {
+                problems.push(format!("{}: summary changed when round-tripped", name));
+            }
+        }
+        Err(e) => problems.push(format!("{}: failed to parse its own output: {}", name, e)),
+    }
+
+    let golden_path = path.with_extension("golden");
+    if update_golden {
+        fs::write(&golden_path, &summary).unwrap();
+    } else {
+        match fs::read_to_string(&golden_path) {
+            Ok(golden) if golden == summary => {}
+            Ok(golden) => problems.push(format!(
+                "{}: summary differs from golden file\n--- expected\n{}--- actual\n{}",
+                name, golden, summary
+            )),
+            Err(_) => problems.push(format!(
+                "{}: no golden file, run with {} set to create it",
+                name, UPDATE_GOLDEN
+            )),
+        }
+    }
+    problems
+}
+
+// ------------------------------------------------------------------------------------------------
+// Tests
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn test_corpus_documents() {
+    let update_golden = std::env::var_os(UPDATE_GOLDEN).is_some();
+    let documents = corpus_documents();
+    assert!(!documents.is_empty());
+
+    let problems: Vec<String> = documents
+        .iter()
+        .flat_map(|path| check_document(path, update_golden))
+        .collect();
+    assert!(problems.is_empty(), "\n{}", problems.join("\n"));
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "cycle_detection")]
+
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::cycles::find_reference_cycles;
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_no_cycles() {
+    let document_node = common::create_example_rdf_document();
+    assert!(find_reference_cycles(&document_node).is_empty());
+}
+
+#[test]
+fn test_child_cycle() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let _ = root_node
+        .append_child(document.create_element("other").unwrap())
+        .unwrap();
+    let _ = root_node
+        .append_child(document.create_element("child").unwrap())
+        .unwrap();
+    let mut child_node = root_node
+        .append_child(document.create_element("child").unwrap())
+        .unwrap();
+
+    let _ = child_node
+        .append_child(document.create_text_node("text"))
+        .unwrap();
+
+    // Nothing prevents appending an ancestor, so this introduces a cycle.
+    let _ = child_node.append_child(root_node.clone()).unwrap();
+
+    // the index is the position among the siblings with the same name.
+    let cycles = find_reference_cycles(&root_node);
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(cycles[0].from, "/root/child[2]/root[1]");
+    assert_eq!(cycles[0].to, "/root");
+    assert_eq!(cycles[0].to_string(), "/root/child[2]/root[1] -> /root");
+
+    // Break the cycle so that the nodes may be dropped.
+    let _ = child_node.remove_child(root_node).unwrap();
+}
@@ -0,0 +1,164 @@
+use std::str::FromStr;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::diff::{apply, diff, DomEdit};
+use xml_dom::level2::ext::NodeComparison;
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_diff_equal_trees() {
+    let document_node = common::create_example_rdf_document();
+    let other_node = common::create_example_rdf_document();
+    assert!(diff(&document_node, &other_node).is_empty());
+    assert!(diff(&document_node, &document_node).is_empty());
+}
+
+#[test]
+fn test_diff_applied_to_copy() {
+    let from_node = common::create_example_rdf_document();
+    let mut copy_node = from_node.clone_node(true).unwrap();
+    let to_node = common::create_example_rdf_document();
+
+    let root_node = to_node.document_element().unwrap();
+    let mut description_node = root_node.first_child().unwrap();
+    let creator_node = description_node.first_child().unwrap();
+    let _ = description_node.remove_child(creator_node).unwrap();
+    description_node.set_attribute("status", "draft").unwrap();
+
+    let edits = diff(&from_node, &to_node);
+    assert_eq!(
+        edits,
+        vec![
+            DomEdit::SetAttribute {
+                path: vec![0, 0],
+                name: Name::from_str("status").unwrap(),
+                value: "draft".to_string(),
+            },
+            DomEdit::RemoveNode {
+                path: vec![0, 0, 0]
+            },
+        ]
+    );
+    apply(&edits, &mut copy_node).unwrap();
+    assert!(copy_node.is_equal_node(&to_node));
+    assert!(!from_node.is_equal_node(&to_node));
+}
+
+#[test]
+fn test_apply_missing_path() {
+    let mut document_node = common::create_example_rdf_document();
+    let edits = vec![DomEdit::RemoveNode { path: vec![0, 7] }];
+    assert_eq!(apply(&edits, &mut document_node), Err(Error::NotFound));
+    let edits = vec![DomEdit::RemoveNode { path: vec![] }];
+    assert_eq!(apply(&edits, &mut document_node), Err(Error::NotFound));
+}
+
+#[test]
+fn test_diff_detached_root() {
+    let document_node = get_implementation()
+        .create_document(None, Some("doc"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut from_root = document.create_element("x").unwrap();
+    let to_root = document.create_element("y").unwrap();
+
+    let edits = diff(&from_root, &to_root);
+    assert_eq!(
+        edits
+            .iter()
+            .map(|edit| edit.to_string())
+            .collect::<Vec<String>>(),
+        vec!["replace / with <y></y>"]
+    );
+    apply(&edits, &mut from_root).unwrap();
+    assert_eq!(from_root.node_name().to_string(), "y");
+    assert!(from_root.parent_node().is_none());
+    assert_ne!(from_root, to_root);
+    assert!(diff(&from_root, &to_root).is_empty());
+}
+
+#[cfg(feature = "quick_parser")]
+mod parsed {
+    use super::*;
+    use xml_dom::parser::read_xml;
+
+    ///
+    /// Diff the two documents, and check that applying the edits to the first makes it equal to
+    /// the second; returns the edits, as strings.
+    ///
+    fn check_diff(from: &str, to: &str) -> Vec<String> {
+        let mut from_node = read_xml(from).unwrap();
+        let to_node = read_xml(to).unwrap();
+        let edits = diff(&from_node, &to_node);
+        apply(&edits, &mut from_node).unwrap();
+        assert!(
+            from_node.is_equal_node(&to_node),
+            "{} != {}",
+            from_node,
+            to_node
+        );
+        assert!(diff(&from_node, &to_node).is_empty());
+        edits.iter().map(|edit| edit.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diff_attributes() {
+        assert_eq!(
+            check_diff(
+                r#"<a xmlns:x="urn:x" x:one="1" two="2" three="3"/>"#,
+                r#"<a xmlns:x="urn:x" x:one="one" three="3" four="a &amp; b"/>"#,
+            ),
+            vec![
+                "remove /0/@two",
+                "set /0/@x:one to \"one\"",
+                "set /0/@four to \"a & b\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_children() {
+        assert_eq!(
+            check_diff(
+                "<a><b/><c/><d/><e/></a>",
+                "<a><x/><b/><d/><e>text</e><f/></a>",
+            ),
+            vec![
+                "insert <x></x> at /0/0",
+                "remove /0/2",
+                "insert text at /0/3/0",
+                "insert <f></f> at /0/4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_replace() {
+        assert_eq!(
+            check_diff(
+                "<a><b>one</b><!-- note --><?pi data?></a>",
+                "<a><c>one</c><!-- changed --><?pi other?></a>",
+            ),
+            vec![
+                "replace /0/0 with <c>one</c>",
+                "change text /0/1 to \" changed \"",
+                "change text /0/2 to \"other\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_root() {
+        let from_node = read_xml("<a/>").unwrap();
+        let to_node = read_xml("<b/>").unwrap();
+        let mut from_root = from_node.first_child().unwrap();
+        let to_root = to_node.first_child().unwrap();
+
+        let edits = diff(&from_root, &to_root);
+        assert_eq!(edits.len(), 1);
+        apply(&edits, &mut from_root).unwrap();
+        assert_eq!(from_root.node_name().to_string(), "b");
+        assert_eq!(from_node.to_string(), "<b></b>");
+    }
+}
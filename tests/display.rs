@@ -3,8 +3,13 @@ use xml_dom::level2::convert::{
 };
 use xml_dom::level2::ext::convert::as_document_decl_mut;
 use xml_dom::level2::ext::dom_impl as ext_dom_impl;
+use xml_dom::level2::ext::writer::{
+    namespace_conflicts, node_id, node_path, node_to_string, serialize_to_bytes,
+    write_document_with_encoding, write_node, IncrementalWriter, NamespaceConflict,
+    NamespacePlacement, OutputEncoding, WriterOptions,
+};
 use xml_dom::level2::ext::{XmlDecl, XmlVersion};
-use xml_dom::level2::get_implementation;
+use xml_dom::level2::{get_implementation, Element, Node, RefNode};
 
 pub mod common;
 
@@ -334,3 +339,632 @@ fn test_display_notation() {
         "<!NOTATION name PUBLIC \"foo-bar\" \"file-name.xml\">"
     );
 }
+
+#[test]
+fn test_display_annotated_elements() {
+    let document_node = get_implementation()
+        .create_document(None, Some("a"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut last_node = None;
+    for name in ["b", "c", "b"] {
+        last_node = Some(
+            root_node
+                .append_child(document.create_element(name).unwrap())
+                .unwrap(),
+        );
+    }
+    let last_node = last_node.unwrap();
+    assert_eq!(node_path(&last_node), "/a/b[2]");
+    assert_ne!(node_id(&last_node), node_id(&root_node));
+
+    let mut options = WriterOptions::new();
+    options.set_annotate_elements();
+    let result = node_to_string(&document_node, &options);
+    assert!(result.starts_with(&format!("<!-- id={} path=/a --><a>", node_id(&root_node))));
+    assert!(result.contains(&format!(
+        "<!-- id={} path=/a/b[2] --><b></b></a>",
+        node_id(&last_node)
+    )));
+    assert!(result.contains("path=/a/c[1] --><c></c>"));
+}
+
+#[test]
+fn test_display_normalized_namespaces() {
+    let document_node = get_implementation()
+        .create_document(Some(common::DC_NS), Some("dc:feed"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut item_node = root_node
+        .append_child(
+            document
+                .create_element_ns(common::RDF_NS, "rdf:item")
+                .unwrap(),
+        )
+        .unwrap();
+    item_node
+        .set_attribute_ns(common::DC_NS, "dc:title", "One")
+        .unwrap();
+    let _safe_to_ignore = item_node
+        .append_child(document.create_element_ns("urn:d", "d").unwrap())
+        .unwrap()
+        .append_child(document.create_element("plain").unwrap())
+        .unwrap();
+    let unchanged = document_node.to_string();
+
+    let mut options = WriterOptions::new();
+    options.set_normalize_namespaces();
+    assert_eq!(
+        node_to_string(&document_node, &options),
+        format!(
+            r#"<dc:feed xmlns:dc="{}"><rdf:item xmlns:rdf="{}" dc:title="One"><d xmlns="urn:d"><plain xmlns=""></plain></d></rdf:item></dc:feed>"#,
+            common::DC_NS,
+            common::RDF_NS
+        )
+    );
+    assert_eq!(document_node.to_string(), unchanged);
+
+    common::sub_test("test_display_normalized_namespaces", "declared");
+    root_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:rdf", common::RDF_NS)
+        .unwrap();
+    let result = node_to_string(&document_node, &options);
+    assert!(result.contains(r#"<rdf:item dc:title="One">"#));
+    assert!(result.contains(&format!(r#" xmlns:dc="{}""#, common::DC_NS)));
+
+    common::sub_test("test_display_normalized_namespaces", "conflict");
+    let mut element_node = document.create_element_ns("urn:a", "p:a").unwrap();
+    element_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:p", "urn:b")
+        .unwrap();
+    assert_eq!(
+        node_to_string(&element_node, &options),
+        r#"<ns1:a xmlns:ns1="urn:a" xmlns:p="urn:b"></ns1:a>"#
+    );
+
+    common::sub_test("test_display_normalized_namespaces", "incremental");
+    let mut writer = IncrementalWriter::new(Vec::new(), options);
+    writer.start_element(&element_node).unwrap();
+    writer
+        .write_node(&document.create_element_ns("urn:a", "p:b").unwrap())
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(writer.finish().unwrap()).unwrap(),
+        r#"<ns1:a xmlns:ns1="urn:a" xmlns:p="urn:b"><p:b xmlns:p="urn:a"></p:b></ns1:a>"#
+    );
+}
+
+#[test]
+fn test_display_hoisted_namespaces() {
+    let document_node = get_implementation()
+        .create_document(Some(common::DC_NS), Some("dc:feed"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut item_node = root_node
+        .append_child(
+            document
+                .create_element_ns(common::RDF_NS, "rdf:item")
+                .unwrap(),
+        )
+        .unwrap();
+    item_node
+        .set_attribute_ns(common::DC_NS, "dc:title", "One")
+        .unwrap();
+    item_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:rdf", common::RDF_NS)
+        .unwrap();
+    let _safe_to_ignore = item_node
+        .append_child(document.create_element_ns("urn:d", "d").unwrap())
+        .unwrap()
+        .append_child(document.create_element("plain").unwrap())
+        .unwrap();
+
+    let mut options = WriterOptions::new();
+    options.set_normalize_namespaces();
+    options.set_namespace_placement(NamespacePlacement::DocumentElement);
+    assert_eq!(
+        node_to_string(&document_node, &options),
+        format!(
+            r#"<dc:feed xmlns:dc="{}" xmlns:rdf="{}"><rdf:item dc:title="One"><d xmlns="urn:d"><plain xmlns=""></plain></d></rdf:item></dc:feed>"#,
+            common::DC_NS,
+            common::RDF_NS
+        )
+    );
+    assert_eq!(
+        namespace_conflicts(&document_node),
+        vec![NamespaceConflict {
+            prefix: None,
+            namespace_uris: vec![Some("urn:d".to_string()), None],
+        }]
+    );
+
+    common::sub_test("test_display_hoisted_namespaces", "conflict");
+    let mut other_node = item_node
+        .append_child(document.create_element_ns("urn:b", "dc:other").unwrap())
+        .unwrap();
+    other_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:dc", "urn:b")
+        .unwrap();
+    // the conflicting prefix is declared where it is used, after those hoisted.
+    assert_eq!(namespace_conflicts(&root_node).len(), 2);
+    assert_eq!(
+        node_to_string(&root_node, &options),
+        format!(
+            r#"<dc:feed xmlns:rdf="{}" xmlns:dc="{}"><rdf:item dc:title="One"><d xmlns="urn:d"><plain xmlns=""></plain></d><dc:other xmlns:dc="urn:b"></dc:other></rdf:item></dc:feed>"#,
+            common::RDF_NS,
+            common::DC_NS
+        )
+    );
+    assert!(namespace_conflicts(&document.create_comment("c")).is_empty());
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_display_internal_subset() {
+    use xml_dom::level2::ext::writer::InternalSubset;
+
+    let subset = r#"
+  <!NOTATION gif SYSTEM "image/gif">
+  <!ENTITY writer  "Donald Duck.">
+  <!ENTITY copy "(c)">
+"#;
+    let document_node =
+        xml_dom::parser::read_xml(format!("<!DOCTYPE note [{}]><note/>", subset)).unwrap();
+    let doc_type_node = as_document(&document_node).unwrap().doc_type().unwrap();
+
+    common::sub_test("test_display_internal_subset", "verbatim");
+    let mut options = WriterOptions::new();
+    assert_eq!(options.internal_subset(), InternalSubset::Preferred);
+    options.set_internal_subset(InternalSubset::Verbatim);
+    assert_eq!(
+        node_to_string(&doc_type_node, &options),
+        format!("<!DOCTYPE note [{}]>", subset)
+    );
+
+    common::sub_test("test_display_internal_subset", "regenerated");
+    options.set_internal_subset(InternalSubset::Regenerated);
+    assert_eq!(
+        node_to_string(&doc_type_node, &options),
+        r#"<!DOCTYPE note [<!ENTITY copy "(c)"><!ENTITY writer "Donald Duck."><!NOTATION gif SYSTEM "image/gif">]>"#
+    );
+
+    common::sub_test("test_display_internal_subset", "preferred");
+    assert_eq!(
+        doc_type_node.to_string(),
+        format!("<!DOCTYPE note [{}]>", subset)
+    );
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_display_document_type_round_trip() {
+    for xml in [
+        r#"<!DOCTYPE r [<!ENTITY e "x">]><r></r>"#,
+        r#"<!DOCTYPE r SYSTEM "r.dtd" [<!ENTITY e "x"><!ENTITY d "y"><!NOTATION gif SYSTEM "image/gif">]><r></r>"#,
+        r#"<!DOCTYPE r SYSTEM "r.dtd"><r></r>"#,
+        "<!DOCTYPE r [\n  <!ELEMENT r (#PCDATA)>\n  <!-- note -->\n]><r></r>",
+    ] {
+        let document_node = xml_dom::parser::read_xml(xml).unwrap();
+        assert_eq!(document_node.to_string(), xml);
+    }
+}
+
+#[test]
+fn test_display_indented() {
+    let document_node = get_implementation()
+        .create_document(None, Some("doc"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let append = |parent: &mut RefNode, child: RefNode| parent.append_child(child).unwrap();
+
+    let _ = append(&mut root_node, document.create_text_node("\n"));
+    let mut list_node = append(&mut root_node, document.create_element("list").unwrap());
+    let _ = append(&mut list_node, document.create_element("item").unwrap());
+    let _ = append(&mut list_node, document.create_comment(" end "));
+    let mut mixed_node = append(&mut root_node, document.create_element("p").unwrap());
+    let _ = append(&mut mixed_node, document.create_text_node("some "));
+    let _ = append(&mut mixed_node, document.create_element("b").unwrap());
+    let mut preserve_node = append(&mut root_node, document.create_element("pre").unwrap());
+    preserve_node
+        .set_attribute("xml:space", "preserve")
+        .unwrap();
+    let mut line_node = append(&mut preserve_node, document.create_element("line").unwrap());
+    let _ = append(&mut line_node, document.create_element("a").unwrap());
+    let mut inner_node = append(
+        &mut preserve_node,
+        document.create_element("inner").unwrap(),
+    );
+    inner_node.set_attribute("xml:space", "default").unwrap();
+    let _ = append(&mut inner_node, document.create_element("c").unwrap());
+
+    let mut options = WriterOptions::new();
+    options.set_indent("\t");
+    assert_eq!(options.indent(), Some("\t"));
+    assert_eq!(
+        node_to_string(&document_node, &options),
+        "<doc>\n\t<list>\n\t\t<item></item>\n\t\t<!-- end -->\n\t</list>\n\t<p>some <b></b></p>\n\t<pre xml:space=\"preserve\"><line><a></a></line><inner xml:space=\"default\">\n\t\t\t<c></c>\n\t\t</inner></pre>\n</doc>"
+    );
+
+    // white space preserved by an ancestor of the node written is inherited.
+    assert_eq!(node_to_string(&line_node, &options), "<line><a></a></line>");
+    assert_eq!(
+        node_to_string(&list_node, &options),
+        "<list>\n\t<item></item>\n\t<!-- end -->\n</list>"
+    );
+    assert_eq!(WriterOptions::new().indent(), None);
+}
+
+fn write_with_encoding(encoding: &str, data: &str) -> std::io::Result<Vec<u8>> {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("doc"), None)
+        .unwrap();
+    let document = as_document_decl_mut(&mut document_node).unwrap();
+    document
+        .set_xml_declaration(XmlDecl::new(
+            XmlVersion::V10,
+            Some(encoding.to_string()),
+            None,
+        ))
+        .unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("note", data).unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node(data))
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_cdata_section(data).unwrap())
+        .unwrap();
+    let mut bytes: Vec<u8> = Vec::new();
+    write_document_with_encoding(&document_node, &WriterOptions::new(), &mut bytes)?;
+    Ok(bytes)
+}
+
+#[test]
+fn test_write_document_with_encoding() {
+    let bytes = write_with_encoding("ISO-8859-1", "é€").unwrap();
+    assert_eq!(
+        bytes,
+        b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><doc note=\"\xE9&#x20AC;\">\xE9&#x20AC;<![CDATA[ \xE9]]>&#x20AC;<![CDATA[ ]]></doc>"
+    );
+
+    common::sub_test("test_write_document_with_encoding", "US-ASCII");
+    let bytes = write_with_encoding("us-ascii", "é").unwrap();
+    assert_eq!(
+        bytes,
+        br#"<?xml version="1.0" encoding="us-ascii"?><doc note="&#xE9;">&#xE9;<![CDATA[ ]]>&#xE9;<![CDATA[ ]]></doc>"#
+    );
+
+    common::sub_test("test_write_document_with_encoding", "UTF-16");
+    let bytes = write_with_encoding("UTF-16", "é€").unwrap();
+    assert_eq!(&bytes[..4], &[0xFE, 0xFF, 0x00, b'<']);
+    let units: Vec<u16> = bytes[2..]
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .collect();
+    assert!(String::from_utf16(&units)
+        .unwrap()
+        .contains(">é€<![CDATA[ é€ ]]>"));
+
+    common::sub_test("test_write_document_with_encoding", "errors");
+    let error = write_with_encoding("EBCDIC", "text").unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+
+    let mut document_node = get_implementation()
+        .create_document(None, Some("doc"), None)
+        .unwrap();
+    let document = as_document_decl_mut(&mut document_node).unwrap();
+    document
+        .set_xml_declaration(XmlDecl::new(
+            XmlVersion::V10,
+            Some("US-ASCII".to_string()),
+            None,
+        ))
+        .unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_comment("café"))
+        .unwrap();
+    let error =
+        write_document_with_encoding(&document_node, &WriterOptions::new(), &mut Vec::<u8>::new())
+            .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_display_escaping() {
+    let document_node = get_implementation()
+        .create_document(None, Some("doc"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("note", r#"'é' & "x" > y"#).unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node(r#"'é' < "€" ]]> z"#))
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_cdata_section("é").unwrap())
+        .unwrap();
+
+    let mut options = WriterOptions::new();
+    options.set_escape_non_ascii();
+    assert_eq!(
+        node_to_string(&root_node, &options),
+        "<doc note=\"&#39;&#xE9;&#39; &#38; &#34;x&#34; &#62; y\">\
+         &#39;&#xE9;&#39; &#60; &#34;&#x20AC;&#34; ]]&#62; z<![CDATA[ ]]>&#xE9;<![CDATA[ ]]></doc>"
+    );
+
+    common::sub_test("test_display_escaping", "minimal");
+    let mut options = WriterOptions::new();
+    options.set_minimal_escaping();
+    assert_eq!(
+        node_to_string(&root_node, &options),
+        r#"<doc note="'é' &#38; &#34;x&#34; > y">'é' &#60; "€" ]]&#62; z<![CDATA[ é ]]></doc>"#
+    );
+
+    common::sub_test("test_display_escaping", "both");
+    options.set_escape_non_ascii();
+    assert_eq!(
+        node_to_string(&root_node, &options),
+        r#"<doc note="'&#xE9;' &#38; &#34;x&#34; > y">'&#xE9;' &#60; "&#x20AC;" ]]&#62; z<![CDATA[ ]]>&#xE9;<![CDATA[ ]]></doc>"#
+    );
+
+    let _safe_to_ignore = root_node
+        .append_child(document.create_comment("café"))
+        .unwrap();
+    let mut buffer = String::new();
+    assert!(write_node(&root_node, &options, &mut buffer).is_err());
+}
+
+#[test]
+fn test_serialize_to_bytes() {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("doc"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node("é€"))
+        .unwrap();
+    let options = WriterOptions::new();
+
+    let bytes = serialize_to_bytes(&document_node, OutputEncoding::Utf8, &options).unwrap();
+    assert_eq!(bytes, "<doc>é€</doc>".as_bytes());
+    let bytes = serialize_to_bytes(&document_node, OutputEncoding::Iso8859_1, &options).unwrap();
+    assert_eq!(
+        bytes,
+        b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><doc>\xE9&#x20AC;</doc>"
+    );
+    let bytes = serialize_to_bytes(&root_node, OutputEncoding::UsAscii, &options).unwrap();
+    assert_eq!(bytes, b"<doc>&#xE9;&#x20AC;</doc>");
+
+    common::sub_test("test_serialize_to_bytes", "declared");
+    as_document_decl_mut(&mut document_node)
+        .unwrap()
+        .set_xml_declaration(XmlDecl::new(
+            XmlVersion::V11,
+            Some("latin1".to_string()),
+            Some(true),
+        ))
+        .unwrap();
+    let bytes = serialize_to_bytes(&document_node, OutputEncoding::Iso8859_1, &options).unwrap();
+    assert!(bytes.starts_with(br#"<?xml version="1.1" encoding="latin1" standalone="yes"?>"#));
+    let bytes = serialize_to_bytes(&document_node, OutputEncoding::Utf16, &options).unwrap();
+    let units: Vec<u16> = bytes[2..]
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .collect();
+    assert_eq!(
+        String::from_utf16(&units).unwrap(),
+        r#"<?xml version="1.1" encoding="UTF-16" standalone="yes"?><doc>é€</doc>"#
+    );
+
+    common::sub_test("test_serialize_to_bytes", "errors");
+    let document = as_document(&document_node).unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_comment("€"))
+        .unwrap();
+    let error =
+        serialize_to_bytes(&document_node, OutputEncoding::Iso8859_1, &options).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_incremental_writer() {
+    let document_type = get_implementation()
+        .create_document_type("feed", None, Some("feed.dtd"))
+        .unwrap();
+    let document_node = get_implementation()
+        .create_document(
+            Some(common::DC_NS),
+            Some("dc:feed"),
+            Some(document_type.clone()),
+        )
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:dc", common::DC_NS)
+        .unwrap();
+
+    let mut writer = IncrementalWriter::new(Vec::new(), WriterOptions::new());
+    writer
+        .write_declaration(&XmlDecl::new(XmlVersion::V10, None, None))
+        .unwrap();
+    writer.write_node(&document_type).unwrap();
+    writer.start_element(&root_node).unwrap();
+    assert_eq!(
+        String::from_utf8(writer.get_ref().clone()).unwrap(),
+        format!(
+            r#"<?xml version="1.0"?><!DOCTYPE feed SYSTEM "feed.dtd"><dc:feed xmlns:dc="{}">"#,
+            common::DC_NS
+        )
+    );
+
+    common::sub_test("test_incremental_writer", "children");
+    for title in ["one", "two"] {
+        let mut entry_node = document.create_element("entry").unwrap();
+        let _safe_to_ignore = entry_node
+            .append_child(document.create_text_node(title))
+            .unwrap();
+        writer.write_node(&entry_node).unwrap();
+    }
+    let group_node = document.create_element("group").unwrap();
+    writer.start_element(&group_node).unwrap();
+    writer
+        .write_node(&document.create_comment("nested"))
+        .unwrap();
+    assert_eq!(writer.depth(), 2);
+    writer.end_element().unwrap();
+    assert_eq!(writer.depth(), 1);
+    let written = String::from_utf8(writer.finish().unwrap()).unwrap();
+    assert!(written
+        .ends_with("<entry>one</entry><entry>two</entry><group><!--nested--></group></dc:feed>"));
+
+    common::sub_test("test_incremental_writer", "errors");
+    let mut writer = IncrementalWriter::new(Vec::new(), WriterOptions::new());
+    assert!(writer
+        .start_element(&document.create_text_node("text"))
+        .is_err());
+    assert!(writer.end_element().is_err());
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_external_entity_round_trip() {
+    use xml_dom::level2::ext::writer::external_entity_to_string;
+    use xml_dom::parser::{read_external_entity, read_xml};
+
+    let document_node = read_xml(
+        r#"<?xml version="1.1"?><book><chapter n="1"><title>One</title>Text.</chapter></book>"#,
+    )
+    .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let chapter_node = document.get_elements_by_tag_name("chapter")[0].clone();
+    let entity = external_entity_to_string(&chapter_node, &WriterOptions::new());
+    assert_eq!(
+        entity,
+        r#"<?xml version="1.1" encoding="UTF-8"?><chapter n="1"><title>One</title>Text.</chapter>"#
+    );
+
+    common::sub_test("test_external_entity_round_trip", "read");
+    let master_node = get_implementation()
+        .create_document(None, Some("book"), None)
+        .unwrap();
+    let master = as_document(&master_node).unwrap();
+    let fragment_node = read_external_entity(&entity, &master_node).unwrap();
+    assert_eq!(fragment_node.owner_document(), Some(master_node.clone()));
+    assert_eq!(
+        external_entity_to_string(&fragment_node, &WriterOptions::new()),
+        r#"<?xml version="1.0" encoding="UTF-8"?><chapter n="1"><title>One</title>Text.</chapter>"#
+    );
+
+    common::sub_test("test_external_entity_round_trip", "include");
+    let mut root_node = master.document_element().unwrap();
+    for child_node in fragment_node.child_nodes() {
+        let _safe_to_ignore = root_node.append_child(child_node).unwrap();
+    }
+    assert_eq!(
+        root_node.to_string(),
+        r#"<book><chapter n="1"><title>One</title>Text.</chapter></book>"#
+    );
+    let chapter_node = root_node.first_child().unwrap();
+    assert_eq!(chapter_node.parent_node(), Some(root_node.clone()));
+}
+
+#[test]
+fn test_display_xhtml_compatible() {
+    const XHTML_NS: &str = "http://www.w3.org/1999/xhtml";
+    let document_node = get_implementation()
+        .create_document(Some(XHTML_NS), Some("html"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut head_node = root_node
+        .append_child(document.create_element_ns(XHTML_NS, "head").unwrap())
+        .unwrap();
+    let mut meta_node = head_node
+        .append_child(document.create_element_ns(XHTML_NS, "meta").unwrap())
+        .unwrap();
+    meta_node.set_attribute("charset", "UTF-8").unwrap();
+    let _safe_to_ignore = head_node
+        .append_child(document.create_element_ns(XHTML_NS, "script").unwrap())
+        .unwrap()
+        .append_child(document.create_text_node("if (a < b && c) { run(); }"))
+        .unwrap();
+    let _safe_to_ignore = head_node
+        .append_child(document.create_element_ns(XHTML_NS, "style").unwrap())
+        .unwrap()
+        .append_child(document.create_text_node("p > em { color: red }"))
+        .unwrap();
+    let mut body_node = root_node
+        .append_child(document.create_element_ns(XHTML_NS, "body").unwrap())
+        .unwrap();
+    let _safe_to_ignore = body_node
+        .append_child(document.create_element_ns(XHTML_NS, "p").unwrap())
+        .unwrap();
+    let _safe_to_ignore = body_node
+        .append_child(document.create_element_ns(XHTML_NS, "br").unwrap())
+        .unwrap();
+    let unchanged = document_node.to_string();
+
+    let mut options = WriterOptions::new();
+    options.set_xhtml_compatible();
+    assert_eq!(
+        node_to_string(&document_node, &options),
+        format!(
+            "<html xmlns=\"{}\"><head><meta charset=\"UTF-8\" /><script>//<![CDATA[\nif (a < b && c) {{ run(); }}\n//]]></script><style>p > em {{ color: red }}</style></head><body><p></p><br /></body></html>",
+            XHTML_NS
+        )
+    );
+    assert_eq!(document_node.to_string(), unchanged);
+
+    // the namespace declaration is only added once.
+    options.set_normalize_namespaces();
+    assert_eq!(
+        node_to_string(&body_node, &options),
+        format!(r#"<body xmlns="{}"><p></p><br /></body>"#, XHTML_NS)
+    );
+    root_node.set_attribute("xmlns", XHTML_NS).unwrap();
+    assert!(node_to_string(&root_node, &WriterOptions::new())
+        .starts_with(&format!(r#"<html xmlns="{}"><head>"#, XHTML_NS)));
+    assert!(node_to_string(&root_node, &options)
+        .starts_with(&format!(r#"<html xmlns="{}"><head>"#, XHTML_NS)));
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_display_xml_11_references() {
+    use xml_dom::parser::read_xml;
+
+    let document_node =
+        read_xml("<?xml version=\"1.1\"?><doc a=\"&#x1;\">&#x2;\u{C0}</doc>").unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    assert_eq!(root_node.get_attribute("a"), Some("\u{1}".to_string()));
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node("\u{85}\u{2028}"))
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_cdata_section("x\u{7F}y").unwrap())
+        .unwrap();
+    let xml = document_node.to_string();
+    assert_eq!(
+        xml,
+        "<?xml version=\"1.1\"?><doc a=\"&#x1;\">&#x2;\u{C0}&#x85;&#x2028;<![CDATA[ x]]>&#x7F;<![CDATA[y ]]></doc>"
+    );
+    assert!(read_xml(&xml).is_ok());
+
+    common::sub_test("test_display_xml_11_references", "xml_10");
+    let mut document_node = document_node;
+    as_document_decl_mut(&mut document_node)
+        .unwrap()
+        .set_xml_declaration(XmlDecl::new(XmlVersion::V10, None, None))
+        .unwrap();
+    assert!(document_node
+        .to_string()
+        .ends_with("<doc a=\"\u{1}\">\u{2}\u{C0}\u{85}\u{2028}<![CDATA[ x\u{7F}y ]]></doc>"));
+}
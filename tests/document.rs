@@ -3,7 +3,13 @@ use xml_dom::level2::convert::{
     as_attribute, as_cdata_section, as_comment, as_document, as_document_fragment, as_document_mut,
     as_element, as_entity_reference, as_processing_instruction, as_text,
 };
-use xml_dom::level2::{get_implementation, Error, Name};
+use xml_dom::level2::ext::convert::as_document_decl_mut;
+use xml_dom::level2::ext::dom_impl::get_implementation_ext;
+use xml_dom::level2::ext::{
+    ElementContentWhitespace, Namespaced, NormalizationOptions, NormalizeDocument,
+    ProcessingOptions, XmlDecl, XmlVersion,
+};
+use xml_dom::level2::{get_implementation, Element, Error, Name, Node, NodeType, RefNode};
 
 pub mod common;
 
@@ -129,6 +135,32 @@ fn test_create_element() {
     assert!(!element.has_child_nodes());
 }
 
+#[test]
+fn test_create_element_with_capacity() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut node = document.create_element_with_capacity("test", 2, 1).unwrap();
+    let expected_name = Name::from_str("test").unwrap();
+    assert_eq!(node.node_name(), expected_name);
+    assert!(node.owner_document().is_some());
+    assert!(!node.has_attributes());
+    assert!(!node.has_child_nodes());
+
+    // the hints do not limit the element.
+    for index in 0..4 {
+        node.set_attribute(&format!("a{}", index), "value").unwrap();
+        let _safe_to_ignore = node
+            .append_child(document.create_element("child").unwrap())
+            .unwrap();
+    }
+    assert_eq!(node.attributes().len(), 4);
+    assert_eq!(node.child_nodes().len(), 4);
+
+    assert!(document
+        .create_element_with_capacity("!test", 0, 0)
+        .is_err());
+}
+
 #[test]
 fn test_create_element_ns() {
     let document_node = common::create_empty_rdf_document();
@@ -146,6 +178,235 @@ fn test_create_element_ns() {
     assert!(!element.has_child_nodes());
 }
 
+#[test]
+fn test_create_element_ns_with_prefix() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let node = document
+        .create_element_ns_with_prefix(common::DC_NS, "title", Some("dc"))
+        .unwrap();
+    let expected_name = Name::new_ns(common::DC_NS, "dc:title").unwrap();
+    assert_eq!(node.node_name(), expected_name);
+    assert_eq!(
+        node.lookup_namespace_uri(Some("dc")),
+        Some(common::DC_NS.to_string())
+    );
+    assert_eq!(
+        node.to_string(),
+        format!(r#"<dc:title xmlns:dc="{}"></dc:title>"#, common::DC_NS)
+    );
+
+    let node = document
+        .create_element_ns_with_prefix(common::DC_NS, "title", None)
+        .unwrap();
+    assert_eq!(node.prefix(), None);
+    assert_eq!(node.namespace_uri(), Some(common::DC_NS.to_string()));
+    assert!(node.is_default_namespace(Some(common::DC_NS)));
+    assert_eq!(
+        node.to_string(),
+        format!(r#"<title xmlns="{}"></title>"#, common::DC_NS)
+    );
+
+    // the xml prefix is bound without a declaration.
+    let node = document
+        .create_element_ns_with_prefix("http://www.w3.org/XML/1998/namespace", "note", Some("xml"))
+        .unwrap();
+    assert_eq!(node.to_string(), "<xml:note></xml:note>");
+
+    assert_eq!(
+        document
+            .create_element_ns_with_prefix(common::DC_NS, "title", Some("xml"))
+            .err(),
+        Some(Error::Namespace)
+    );
+    assert!(document
+        .create_element_ns_with_prefix(common::DC_NS, "!title", Some("dc"))
+        .is_err());
+}
+
+#[test]
+fn test_create_strict_names() {
+    let mut options = ProcessingOptions::new();
+    options.set_strict_names();
+    let document_node = get_implementation_ext()
+        .create_document_with_options(None, None, None, options)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+
+    assert!(document.create_element("root").is_ok());
+    assert!(document.create_element("xml:root").is_ok());
+    assert_eq!(
+        document.create_element("XmlRoot").err(),
+        Some(Error::InvalidCharacter)
+    );
+    assert_eq!(
+        document.create_element("xmlns:root").err(),
+        Some(Error::Namespace)
+    );
+    assert!(document.create_element("xsl:root").is_ok());
+    assert_eq!(
+        document.create_element("xmlx:root").err(),
+        Some(Error::Namespace)
+    );
+
+    assert!(document.create_attribute("xmlns").is_ok());
+    assert!(document.create_attribute("xmlns:ex").is_ok());
+    assert!(document.create_attribute_with("xml:lang", "en").is_ok());
+    assert_eq!(
+        document.create_attribute("xmlbase").err(),
+        Some(Error::InvalidCharacter)
+    );
+
+    // without the option only the XML name production is checked.
+    let document_node = get_implementation()
+        .create_document(None, None, None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    assert!(document.create_element("XmlRoot").is_ok());
+    assert_eq!(
+        document.create_element("1root").err(),
+        Some(Error::InvalidCharacter)
+    );
+}
+
+#[test]
+fn test_create_validate_namespace_uris() {
+    let mut options = ProcessingOptions::new();
+    options.set_validate_namespace_uris();
+    let document_node = get_implementation_ext()
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+
+    assert!(document
+        .create_element_ns("http://example.org/ns#", "ex:root")
+        .is_ok());
+    // relative namespace URIs are deprecated, but allowed.
+    assert!(document.create_element_ns("ns/relative", "ex:root").is_ok());
+    assert_eq!(
+        document
+            .create_element_ns("http://example.org/a b", "ex:root")
+            .err(),
+        Some(Error::Namespace)
+    );
+    assert_eq!(
+        document
+            .create_attribute_ns("http://example.org/<ns>", "ex:attr")
+            .err(),
+        Some(Error::Namespace)
+    );
+
+    let mut root_node = document.document_element().unwrap();
+    assert!(root_node
+        .set_attribute("xmlns:ex", "http://example.org/été")
+        .is_ok());
+    assert!(root_node.set_attribute("xmlns", "").is_ok());
+    assert_eq!(
+        root_node.set_attribute("xmlns:bad", "urn:a#b#c"),
+        Err(Error::Namespace)
+    );
+    assert!(!root_node.has_attribute("xmlns:bad"));
+
+    // without the option namespace URIs are not checked.
+    let document_node = get_implementation()
+        .create_document(None, None, None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    assert!(document
+        .create_element_ns("http://example.org/a b", "ex:root")
+        .is_ok());
+}
+
+#[test]
+fn test_fail_on_invalid_character() {
+    let mut options = ProcessingOptions::new();
+    options.set_fail_on_invalid_character();
+    let document_node = get_implementation_ext()
+        .create_document_with_options(None, Some("root"), None, options.clone())
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+
+    assert_eq!(
+        document.create_cdata_section("bad \u{1}").err(),
+        Some(Error::InvalidCharacter)
+    );
+    assert_eq!(
+        document
+            .create_processing_instruction("pi", Some("bad \u{1}"))
+            .err(),
+        Some(Error::InvalidCharacter)
+    );
+
+    let mut root_node = document.document_element().unwrap();
+    assert_eq!(
+        root_node.set_attribute("a", "bad \u{1}"),
+        Err(Error::InvalidCharacter)
+    );
+    assert!(!root_node.has_attribute("a"));
+    assert!(root_node.set_attribute("a", "good").is_ok());
+
+    let text_node = document.create_text_node("bad \u{1}");
+    assert_eq!(
+        root_node.append_child(text_node).err(),
+        Some(Error::InvalidCharacter)
+    );
+    assert!(!root_node.has_child_nodes());
+
+    let mut text_node = document.create_text_node("good");
+    assert!(root_node.append_child(text_node.clone()).is_ok());
+    assert_eq!(
+        text_node.set_node_value("bad \u{1}"),
+        Err(Error::InvalidCharacter)
+    );
+
+    // the character is allowed in an XML 1.1 document.
+    let mut document_node = get_implementation_ext()
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap();
+    let mut_document = as_document_decl_mut(&mut document_node).unwrap();
+    mut_document
+        .set_xml_declaration(XmlDecl::new(XmlVersion::V11, None, None))
+        .unwrap();
+    assert!(mut_document.create_cdata_section("ok \u{1}").is_ok());
+}
+
+#[test]
+fn test_fail_on_duplicate_attribute() {
+    const NS: &str = "http://example.org/ns#";
+    let mut options = ProcessingOptions::new();
+    options.set_fail_on_duplicate_attribute();
+    let document_node = get_implementation_ext()
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    assert!(root_node.set_attribute_ns(NS, "a:x", "1").is_ok());
+    assert_eq!(
+        root_node.set_attribute_ns(NS, "b:x", "2"),
+        Err(Error::InUseAttribute)
+    );
+    // replacing the same attribute is not a duplicate.
+    assert!(root_node.set_attribute_ns(NS, "a:x", "3").is_ok());
+
+    assert!(root_node.set_attribute("p:y", "1").is_ok());
+    assert_eq!(
+        root_node.set_attribute_ns(NS, "p:y", "2"),
+        Err(Error::InUseAttribute)
+    );
+    assert_eq!(root_node.attributes().len(), 2);
+
+    // without the option the second attribute is added.
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    assert!(root_node.set_attribute_ns(NS, "a:x", "1").is_ok());
+    assert!(root_node.set_attribute_ns(NS, "b:x", "2").is_ok());
+}
+
 #[test]
 fn test_create_processing_instruction() {
     let document_node = common::create_empty_rdf_document();
@@ -265,6 +526,52 @@ fn test_get_elements_ns_all_descriptions() {
     assert_eq!(elements.len(), 2);
 }
 
+#[test]
+fn test_get_elements_with_element_index() {
+    let mut options = ProcessingOptions::new();
+    options.set_use_element_index();
+    let indexed_node = common::create_example_rdf_document_options(options);
+    let plain_node = common::create_example_rdf_document();
+    let indexed = as_document(&indexed_node).unwrap();
+    let plain = as_document(&plain_node).unwrap();
+
+    let names = |elements: Vec<RefNode>| {
+        elements
+            .iter()
+            .map(|element| element.node_name().to_string())
+            .collect::<Vec<String>>()
+    };
+    for tag_name in &["*", "dc:creator", "rdf:Description", "dc:created"] {
+        assert_eq!(
+            names(indexed.get_elements_by_tag_name(tag_name)),
+            names(plain.get_elements_by_tag_name(tag_name))
+        );
+    }
+    for (namespace_uri, local_name) in &[
+        (common::DC_NS, "*"),
+        (common::RDF_NS, "*"),
+        ("*", "Description"),
+        (common::DC_NS, "creator"),
+        ("*", "*"),
+    ] {
+        assert_eq!(
+            names(indexed.get_elements_by_tag_name_ns(namespace_uri, local_name)),
+            names(plain.get_elements_by_tag_name_ns(namespace_uri, local_name))
+        );
+    }
+
+    // the index is discarded when the tree changes.
+    let mut root_node = indexed.document_element().unwrap();
+    let mut description_node = root_node.first_child().unwrap();
+    let _safe_to_ignore = description_node
+        .append_child(indexed.create_element("dc:creator").unwrap())
+        .unwrap();
+    assert_eq!(indexed.get_elements_by_tag_name("dc:creator").len(), 2);
+    let _safe_to_ignore = root_node.remove_child(description_node).unwrap();
+    assert_eq!(indexed.get_elements_by_tag_name("dc:creator").len(), 0);
+    assert_eq!(indexed.get_elements_by_tag_name("*").len(), 1);
+}
+
 #[test]
 fn test_only_one_root() {
     let implementation = get_implementation();
@@ -319,3 +626,216 @@ fn test_replace_root() {
     let expected_name = Name::from_str("should_work").unwrap();
     assert_eq!(element.node_name(), expected_name);
 }
+
+#[test]
+fn test_import_node() {
+    let source_node = common::create_example_rdf_document();
+    let source = as_document(&source_node).unwrap();
+    let description_node = source.get_elements_by_tag_name("rdf:Description").remove(0);
+
+    let mut target_node = get_implementation()
+        .create_document(Some(common::RDF_NS), Some("rdf:RDF"), None)
+        .unwrap();
+    let mut target_root_node = as_document(&target_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+
+    let imported_node = as_document_mut(&mut target_node)
+        .unwrap()
+        .import_node(description_node.clone(), true)
+        .unwrap();
+    assert_ne!(imported_node, description_node);
+    assert!(imported_node.parent_node().is_none());
+    assert_eq!(imported_node.owner_document(), Some(target_node.clone()));
+    assert_eq!(imported_node.to_string(), description_node.to_string());
+    for child_node in imported_node.child_nodes() {
+        assert_eq!(child_node.owner_document(), Some(target_node.clone()));
+        assert_eq!(child_node.parent_node(), Some(imported_node.clone()));
+    }
+    for attribute_node in imported_node.attributes().values() {
+        assert_eq!(attribute_node.owner_document(), Some(target_node.clone()));
+    }
+
+    // the imported node may be inserted into the target document.
+    assert!(target_root_node.append_child(imported_node).is_ok());
+    assert_eq!(description_node.owner_document(), Some(source_node.clone()));
+    assert_eq!(description_node.parent_node(), source.document_element());
+
+    // a shallow import copies attributes but not children.
+    let source_root_node = source.document_element().unwrap();
+    let imported_node = as_document_mut(&mut target_node)
+        .unwrap()
+        .import_node(source_root_node.clone(), false)
+        .unwrap();
+    assert!(imported_node.child_nodes().is_empty());
+    assert_eq!(imported_node.attributes().len(), 3);
+    let imported = as_element(&imported_node).unwrap();
+    assert_eq!(imported.get_attribute("id"), Some("main".to_string()));
+    assert_eq!(
+        as_attribute(&imported.get_attribute_node("id").unwrap())
+            .unwrap()
+            .owner_element(),
+        Some(imported_node.clone())
+    );
+}
+
+#[test]
+fn test_import_node_unsupported() {
+    let source_node = common::create_empty_rdf_document();
+    let mut target_node = common::create_empty_rdf_document();
+    let target = as_document_mut(&mut target_node).unwrap();
+
+    assert_eq!(
+        target.import_node(source_node.clone(), true),
+        Err(Error::NotSupported)
+    );
+
+    let reference_node = as_document(&source_node)
+        .unwrap()
+        .create_entity_reference("name")
+        .unwrap();
+    let imported_node = target.import_node(reference_node, true).unwrap();
+    assert_eq!(imported_node.node_type(), NodeType::EntityReference);
+    assert_eq!(imported_node.owner_document(), Some(target_node.clone()));
+}
+
+#[test]
+fn test_normalize_document() {
+    let mut document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut child_node = root_node
+        .append_child(document.create_element("child").unwrap())
+        .unwrap();
+    let _ = child_node
+        .append_child(document.create_text_node("one, "))
+        .unwrap();
+    let _ = child_node
+        .append_child(document.create_comment("ignore me"))
+        .unwrap();
+    let _ = child_node
+        .append_child(document.create_cdata_section("two, ").unwrap())
+        .unwrap();
+    let _ = child_node
+        .append_child(document.create_text_node(""))
+        .unwrap();
+    let _ = child_node
+        .append_child(document.create_cdata_section("<three>").unwrap())
+        .unwrap();
+
+    let options = NormalizationOptions::new();
+    document_node.normalize_document(&options).unwrap();
+    assert_eq!(child_node.child_nodes().len(), 4);
+
+    let mut options = NormalizationOptions::new();
+    options.set_discard_comments();
+    options.set_convert_cdata_to_text();
+    document_node.normalize_document(&options).unwrap();
+
+    let children = child_node.child_nodes();
+    assert_eq!(children.len(), 2);
+    assert_eq!(children[0].node_type(), NodeType::Text);
+    assert_eq!(children[0].node_value(), Some("one, two, ".to_string()));
+    assert_eq!(children[1].node_type(), NodeType::CData);
+}
+
+#[test]
+fn test_element_content_whitespace() {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("list"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let append = |parent: &mut RefNode, child: RefNode| parent.append_child(child).unwrap();
+
+    let indent_node = append(&mut root_node, document.create_text_node("\n  "));
+    let mut item_node = append(&mut root_node, document.create_element("item").unwrap());
+    let space_node = append(&mut item_node, document.create_text_node(" "));
+    let mut preserve_node = append(&mut root_node, document.create_element("pre").unwrap());
+    preserve_node
+        .set_attribute("xml:space", "preserve")
+        .unwrap();
+    let preserved_node = append(&mut preserve_node, document.create_text_node("\n"));
+    let mut inner_node = append(&mut preserve_node, document.create_element("b").unwrap());
+    inner_node.set_attribute("xml:space", "default").unwrap();
+    let _ = append(&mut inner_node, document.create_element("i").unwrap());
+    let inner_space_node = append(&mut inner_node, document.create_text_node("\t"));
+    let mut mixed_node = append(&mut root_node, document.create_element("mixed").unwrap());
+    let _ = append(&mut mixed_node, document.create_text_node("text"));
+    let mixed_space_node = append(&mut mixed_node, document.create_text_node(" "));
+    let _ = append(&mut mixed_node, document.create_element("b").unwrap());
+    let empty_node = append(&mut root_node, document.create_text_node(""));
+    let text_node = document.create_text_node("\r\n");
+    let cdata_node = append(&mut root_node, document.create_cdata_section(" ").unwrap());
+
+    assert!(indent_node.is_whitespace());
+    assert!(empty_node.is_whitespace());
+    assert!(!mixed_node.first_child().unwrap().is_whitespace());
+
+    assert!(indent_node.is_element_content_whitespace());
+    assert!(empty_node.is_element_content_whitespace());
+    assert!(inner_space_node.is_element_content_whitespace());
+    assert!(!space_node.is_element_content_whitespace());
+    assert!(!preserved_node.is_element_content_whitespace());
+    assert!(!mixed_space_node.is_element_content_whitespace());
+    assert!(!cdata_node.is_element_content_whitespace());
+    assert!(text_node.is_whitespace());
+    assert!(!text_node.is_element_content_whitespace());
+
+    let mut options = NormalizationOptions::new();
+    options.set_discard_element_content_whitespace();
+    assert!(options.has_discard_element_content_whitespace());
+    document_node.normalize_document(&options).unwrap();
+    assert_eq!(
+        root_node.to_string(),
+        "<list><item> </item><pre xml:space=\"preserve\">\n<b xml:space=\"default\"><i></i></b></pre><mixed>text <b></b></mixed><![CDATA[   ]]></list>"
+    );
+}
+
+#[test]
+fn test_normalize_document_namespaces() {
+    const EX: &str = "http://example.org/ns";
+    const OTHER: &str = "http://example.org/other";
+
+    let mut document_node = get_implementation()
+        .create_document(Some(EX), Some("ex:root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:ex", EX)
+        .unwrap();
+    let mut child_node = root_node
+        .append_child(document.create_element_ns(EX, "ex:child").unwrap())
+        .unwrap();
+    child_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:ex", EX)
+        .unwrap();
+    child_node
+        .set_attribute_ns(OTHER, "local", "value")
+        .unwrap();
+    let plain_node = child_node
+        .append_child(document.create_element("plain").unwrap())
+        .unwrap();
+
+    let mut options = NormalizationOptions::new();
+    options.set_normalize_namespaces();
+    document_node.normalize_document(&options).unwrap();
+
+    assert_eq!(root_node.get_attribute("xmlns:ex"), Some(EX.to_string()));
+    assert!(!child_node.has_attribute("xmlns:ex"));
+    assert_eq!(
+        child_node.get_attribute("xmlns:NS1"),
+        Some(OTHER.to_string())
+    );
+    assert_eq!(
+        child_node.get_attribute("NS1:local"),
+        Some("value".to_string())
+    );
+    assert_eq!(
+        child_node.get_attribute_ns(OTHER, "local"),
+        Some("value".to_string())
+    );
+    assert!(!plain_node.has_attributes());
+}
@@ -0,0 +1,308 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::dtd::{
+    parse_internal_subset, AttributeType, ContentParticle, ContentSpec, DefaultDecl,
+    DtdDeclaration, DtdViolationKind, ElementDecl, EntityDecl, Occurrence, Particle,
+};
+use xml_dom::level2::ext::{DtdDeclarations, DtdValidation};
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+const INTERNAL_SUBSET: &str = r#"
+  <!-- a letter -->
+  <!ELEMENT letter (to+, (para | list)*, signature?)>
+  <!ELEMENT to (#PCDATA)>
+  <!ELEMENT para (#PCDATA | em | strong)*>
+  <!ELEMENT signature EMPTY>
+  <!ELEMENT list ANY>
+  <!ATTLIST letter
+      id       ID                #REQUIRED
+      lang     NMTOKEN           "en"
+      format   NOTATION (pdf|ps) #IMPLIED
+      version  CDATA             #FIXED '1.0'>
+  <!ATTLIST letter lang CDATA "fr" kind (formal|casual) "formal">
+  <!ENTITY % common SYSTEM "common.ent">
+  %common;
+  <!ENTITY sender "A. N. Other">
+  <!ENTITY logo SYSTEM "logo.pdf" NDATA pdf>
+  <!NOTATION pdf PUBLIC "-//Adobe//PDF">
+  <?editor folding?>
+"#;
+
+fn name(name: &str, occurrence: Occurrence) -> ContentParticle {
+    ContentParticle {
+        particle: Particle::Name(name.to_string()),
+        occurrence,
+    }
+}
+
+#[test]
+fn test_parse_internal_subset() {
+    let declarations = parse_internal_subset(INTERNAL_SUBSET).unwrap();
+    assert_eq!(declarations.len(), 14);
+    assert_eq!(
+        declarations[0],
+        DtdDeclaration::Comment(" a letter ".to_string())
+    );
+    assert_eq!(
+        declarations[1],
+        DtdDeclaration::Element(ElementDecl {
+            name: "letter".to_string(),
+            content: ContentSpec::Children(ContentParticle {
+                particle: Particle::Sequence(vec![
+                    name("to", Occurrence::OneOrMore),
+                    ContentParticle {
+                        particle: Particle::Choice(vec![
+                            name("para", Occurrence::Once),
+                            name("list", Occurrence::Once),
+                        ]),
+                        occurrence: Occurrence::ZeroOrMore,
+                    },
+                    name("signature", Occurrence::Optional),
+                ]),
+                occurrence: Occurrence::Once,
+            }),
+        })
+    );
+    assert!(matches!(
+        &declarations[3],
+        DtdDeclaration::Element(decl) if decl.content == ContentSpec::Mixed(vec!["em".to_string(), "strong".to_string()])
+    ));
+    match &declarations[6] {
+        DtdDeclaration::AttributeList(decl) => {
+            assert_eq!(decl.element_name, "letter");
+            assert_eq!(decl.attributes.len(), 4);
+            assert_eq!(decl.attributes[0].attribute_type, AttributeType::Id);
+            assert_eq!(decl.attributes[0].default, DefaultDecl::Required);
+            assert_eq!(
+                decl.attributes[2].attribute_type,
+                AttributeType::Notation(vec!["pdf".to_string(), "ps".to_string()])
+            );
+            assert_eq!(
+                decl.attributes[3].default,
+                DefaultDecl::Fixed("1.0".to_string())
+            );
+        }
+        other => panic!("expected an attribute list, not {:?}", other),
+    }
+    assert!(matches!(
+        &declarations[8],
+        DtdDeclaration::ParameterEntity(decl) if decl.system_id.as_deref() == Some("common.ent")
+    ));
+    assert_eq!(
+        declarations[9],
+        DtdDeclaration::ParameterEntityReference("common".to_string())
+    );
+    assert_eq!(
+        declarations[11],
+        DtdDeclaration::Entity(EntityDecl {
+            name: "logo".to_string(),
+            value: None,
+            public_id: None,
+            system_id: Some("logo.pdf".to_string()),
+            notation_name: Some("pdf".to_string()),
+        })
+    );
+    assert_eq!(
+        declarations[13],
+        DtdDeclaration::ProcessingInstruction("editor folding".to_string())
+    );
+}
+
+#[test]
+fn test_declarations_round_trip() {
+    let declarations = parse_internal_subset(INTERNAL_SUBSET).unwrap();
+    let written: Vec<String> = declarations.iter().map(|d| d.to_string()).collect();
+    assert_eq!(
+        written[1],
+        "<!ELEMENT letter (to+,(para|list)*,signature?)>"
+    );
+    assert_eq!(written[3], "<!ELEMENT para (#PCDATA|em|strong)*>");
+    assert_eq!(
+        written[6],
+        r#"<!ATTLIST letter id ID #REQUIRED lang NMTOKEN "en" format NOTATION (pdf|ps) #IMPLIED version CDATA #FIXED "1.0">"#
+    );
+    assert_eq!(written[12], r#"<!NOTATION pdf PUBLIC "-//Adobe//PDF">"#);
+
+    let reparsed = parse_internal_subset(&written.join("\n")).unwrap();
+    assert_eq!(reparsed, declarations);
+    assert_eq!(
+        "<!ENTITY  quote 'say \"hi\"' >"
+            .parse::<DtdDeclaration>()
+            .unwrap()
+            .to_string(),
+        r#"<!ENTITY quote 'say "hi"'>"#
+    );
+}
+
+#[test]
+fn test_malformed_internal_subset() {
+    for text in [
+        "<!ELEMENT a>",
+        "<!ELEMENT a (b|c,d)>",
+        "<!ELEMENT a (#PCDATA|b)>",
+        "<!ATTLIST a b CDATA>",
+        "<!ATTLIST a b FOO #IMPLIED>",
+        "<!ENTITY a SYSTEM>",
+        "<!ENTITY % a SYSTEM 'a' NDATA b>",
+        "<!NOTATION a PUBLIC>",
+        "<!-- unterminated",
+        "<![INCLUDE[ <!ELEMENT a ANY> ]]>",
+        "text",
+    ] {
+        assert!(parse_internal_subset(text).is_err(), "{}", text);
+    }
+}
+
+#[test]
+fn test_document_type_declarations() {
+    let document_node = read_xml(format!(
+        "<!DOCTYPE letter [{}]><letter id=\"l1\"><to>You</to></letter>",
+        INTERNAL_SUBSET
+    ))
+    .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut doc_type = document.doc_type().unwrap();
+
+    assert_eq!(doc_type.declarations().unwrap().len(), 14);
+    assert_eq!(
+        doc_type.element_declaration("signature").unwrap().content,
+        ContentSpec::Empty
+    );
+    assert!(doc_type.element_declaration("missing").is_none());
+
+    let attributes = doc_type.attribute_declarations("letter");
+    let names: Vec<&str> = attributes.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, vec!["id", "lang", "format", "version", "kind"]);
+    assert_eq!(attributes[1].attribute_type, AttributeType::NmToken);
+    assert_eq!(
+        doc_type.default_attributes("letter"),
+        vec![
+            ("lang".to_string(), "en".to_string()),
+            ("version".to_string(), "1.0".to_string()),
+            ("kind".to_string(), "formal".to_string()),
+        ]
+    );
+
+    let declarations: Vec<DtdDeclaration> = doc_type
+        .declarations()
+        .unwrap()
+        .into_iter()
+        .filter(|d| matches!(d, DtdDeclaration::Element(_)))
+        .collect();
+    doc_type.set_declarations(&declarations[..1]).unwrap();
+    assert_eq!(
+        doc_type.internal_subset(),
+        Some("\n<!ELEMENT letter (to+,(para|list)*,signature?)>\n".to_string())
+    );
+    assert_eq!(doc_type.declarations().unwrap(), declarations[..1].to_vec());
+
+    doc_type.set_declarations(&[]).unwrap();
+    assert!(doc_type.internal_subset().is_none());
+    assert!(document_node.declarations().is_err());
+}
+
+fn validate(content: &str) -> Vec<(String, DtdViolationKind)> {
+    let document_node = read_xml(format!(
+        "<!DOCTYPE letter [{}\n<!ELEMENT em (#PCDATA)>]>{}",
+        INTERNAL_SUBSET, content
+    ))
+    .unwrap();
+    document_node
+        .validate_against_dtd()
+        .unwrap()
+        .into_iter()
+        .map(|violation| (violation.node.node_name().to_string(), violation.kind))
+        .collect()
+}
+
+#[test]
+fn test_validate_valid_document() {
+    assert_eq!(
+        validate(
+            r#"<letter id="l1" kind=" casual " version="1.0">
+  <to>You</to><to>Them</to>
+  <para>Hello <em>there</em></para>
+  <list><anything/></list>
+  <para/>
+  <signature/>
+</letter>"#
+        ),
+        vec![("anything".to_string(), DtdViolationKind::UndeclaredElement)]
+    );
+}
+
+#[test]
+fn test_validate_content_models() {
+    let content = "(to+,(para|list)*,signature?)".to_string();
+    for invalid in [
+        "<letter id='l1'/>",
+        "<letter id='l1'><para/><to/></letter>",
+        "<letter id='l1'><to/><signature/><para/></letter>",
+        "<letter id='l1'><to/>text</letter>",
+        "<letter id='l1'><to/><![CDATA[ ]]></letter>",
+    ] {
+        assert_eq!(
+            validate(invalid),
+            vec![(
+                "letter".to_string(),
+                DtdViolationKind::InvalidContent(content.clone())
+            )],
+            "{}",
+            invalid
+        );
+    }
+    assert_eq!(
+        validate("<letter id='l1'><to/><para><to/></para><signature>x</signature></letter>"),
+        vec![
+            (
+                "para".to_string(),
+                DtdViolationKind::InvalidContent("(#PCDATA|em|strong)*".to_string())
+            ),
+            (
+                "signature".to_string(),
+                DtdViolationKind::InvalidContent("EMPTY".to_string())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_validate_attributes() {
+    assert_eq!(
+        validate(r#"<letter kind="rude" version="2.0" format="ps" extra="1"><to/></letter>"#),
+        vec![
+            (
+                "kind".to_string(),
+                DtdViolationKind::InvalidAttributeValue("kind".to_string())
+            ),
+            (
+                "version".to_string(),
+                DtdViolationKind::InvalidAttributeValue("version".to_string())
+            ),
+            (
+                "extra".to_string(),
+                DtdViolationKind::UndeclaredAttribute("extra".to_string())
+            ),
+            (
+                "letter".to_string(),
+                DtdViolationKind::MissingAttribute("id".to_string())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_validate_document_element() {
+    assert_eq!(
+        validate("<to/>"),
+        vec![(
+            "to".to_string(),
+            DtdViolationKind::DocumentElementType("letter".to_string())
+        )]
+    );
+
+    let document_node = read_xml("<letter/>").unwrap();
+    assert!(document_node.validate_against_dtd().is_err());
+}
@@ -3,7 +3,8 @@
 //
 
 use xml_dom::level2::convert::{as_attribute_mut, as_document, as_element, as_element_mut};
-use xml_dom::level2::RefNode;
+use xml_dom::level2::ext::{CDataContent, InnerXml};
+use xml_dom::level2::{CharacterData, Node, RefNode};
 
 pub mod common;
 
@@ -122,3 +123,102 @@ fn test_get_attribute() {
     assert!(element.get_attribute_ns(common::DC_NS, "three").is_none());
     assert!(element.get_attribute_ns(common::XMLNS_NS, "two").is_none());
 }
+
+#[test]
+fn test_set_cdata() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+    let _ = element_node
+        .append_child(document.create_text_node("replaced"))
+        .unwrap();
+
+    element_node.set_cdata("if (a < b && c) {}").unwrap();
+    assert_eq!(element_node.child_nodes().len(), 1);
+    let sections = element_node.cdata_sections();
+    assert_eq!(sections.len(), 1);
+    assert_eq!(sections[0].data(), Some("if (a < b && c) {}".to_string()));
+}
+
+#[test]
+fn test_set_cdata_split_end() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+
+    element_node.set_cdata("a]]>b]]>").unwrap();
+    let sections: Vec<Option<String>> = element_node
+        .cdata_sections()
+        .iter()
+        .map(|section| section.data())
+        .collect();
+    assert_eq!(
+        sections,
+        vec![
+            Some("a]]".to_string()),
+            Some(">b]]".to_string()),
+            Some(">".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_inner_and_outer_xml() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+    assert_eq!(element_node.inner_xml(), "");
+    assert_eq!(element_node.outer_xml(), "<rdf:RDF></rdf:RDF>");
+
+    let mut child_node = element_node
+        .append_child(document.create_element("item").unwrap())
+        .unwrap();
+    child_node.set_attribute("n", "1").unwrap();
+    let _ = element_node
+        .append_child(document.create_text_node("a < b"))
+        .unwrap();
+    assert_eq!(element_node.inner_xml(), r#"<item n="1"></item>a &#60; b"#);
+    assert_eq!(
+        element_node.outer_xml(),
+        r#"<rdf:RDF><item n="1"></item>a &#60; b</rdf:RDF>"#
+    );
+    assert_eq!(child_node.outer_xml(), child_node.to_string());
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_set_inner_xml() {
+    use xml_dom::level2::Error;
+
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+    let _ = element_node
+        .append_child(document.create_text_node("replaced"))
+        .unwrap();
+
+    element_node
+        .set_inner_xml(r#"<rdf:Description rdf:about="x"/><!-- note -->tail &amp; end"#)
+        .unwrap();
+    assert_eq!(element_node.child_nodes().len(), 3);
+    assert_eq!(
+        element_node.inner_xml(),
+        r#"<rdf:Description rdf:about="x"></rdf:Description><!-- note -->tail &#38; end"#
+    );
+    let description_node = element_node.first_child().unwrap();
+    assert_eq!(description_node.parent_node(), Some(element_node.clone()));
+    assert_eq!(
+        description_node.owner_document(),
+        Some(document_node.clone())
+    );
+
+    let before = element_node.inner_xml();
+    assert_eq!(element_node.set_inner_xml("<open>"), Err(Error::Syntax));
+    assert_eq!(element_node.inner_xml(), before);
+
+    element_node.set_inner_xml("").unwrap();
+    assert!(!element_node.has_child_nodes());
+
+    let mut text_node = document.create_text_node("text");
+    assert_eq!(text_node.set_inner_xml("<a/>"), Err(Error::InvalidState));
+}
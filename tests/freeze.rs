@@ -0,0 +1,89 @@
+use xml_dom::level2::convert::{as_attribute_mut, as_document};
+use xml_dom::level2::ext::freeze::{freeze, is_frozen, thaw};
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_frozen_subtree_is_read_only() {
+    let document_node = common::create_example_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut description_node = root_node.first_child().unwrap();
+    let mut title_node = description_node.child_nodes()[1].clone();
+    let mut text_node = title_node.first_child().unwrap();
+    let mut attribute_node = title_node.get_attribute_node("xml:id").unwrap();
+
+    let token = freeze(&mut description_node);
+    assert!(is_frozen(&description_node));
+    assert!(is_frozen(&text_node));
+    assert!(is_frozen(&attribute_node));
+    assert!(!is_frozen(&root_node));
+
+    assert_eq!(
+        description_node.append_child(document.create_element("dc:subject").unwrap()),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        description_node.remove_child(title_node.clone()),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        title_node.set_attribute("lang", "en"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        text_node.append_data("!"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        as_attribute_mut(&mut attribute_node)
+            .unwrap()
+            .set_value("other"),
+        Err(Error::NoModificationAllowed)
+    );
+    // moving a frozen node modifies its frozen parent.
+    assert_eq!(
+        root_node.append_child(title_node.clone()),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        text_node.node_value(),
+        Some("A Guide to Growing Roses".to_string())
+    );
+
+    // the unfrozen parent may still be modified.
+    assert!(root_node
+        .append_child(document.create_element("rdf:Description").unwrap())
+        .is_ok());
+
+    // clones are not frozen.
+    let mut clone_node = title_node.clone_node(true).unwrap();
+    assert!(!is_frozen(&clone_node));
+    assert!(clone_node.set_attribute("lang", "en").is_ok());
+
+    thaw(token);
+    assert!(!is_frozen(&text_node));
+    assert!(text_node.append_data("!").is_ok());
+    assert!(title_node.set_attribute("lang", "en").is_ok());
+}
+
+#[test]
+fn test_nested_freezes() {
+    let document_node = common::create_example_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut description_node = root_node.first_child().unwrap();
+
+    let inner_token = freeze(&mut description_node);
+    let outer_token = freeze(&mut root_node);
+    assert!(is_frozen(&root_node));
+
+    thaw(outer_token);
+    assert!(!is_frozen(&root_node));
+    // still covered by the inner freeze.
+    assert!(is_frozen(&description_node));
+
+    thaw(inner_token);
+    assert!(!is_frozen(&description_node));
+}
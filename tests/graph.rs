@@ -0,0 +1,64 @@
+#![cfg(feature = "graph")]
+
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::graph::{to_dot, to_graph, EdgeKind};
+use xml_dom::level2::*;
+
+#[test]
+fn test_graph_structure() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("id", "main").unwrap();
+    let mut child_node = root_node
+        .append_child(document.create_element("child").unwrap())
+        .unwrap();
+    let _ = child_node
+        .append_child(document.create_text_node("Hello"))
+        .unwrap();
+    let _ = root_node
+        .append_child(document.create_comment("note"))
+        .unwrap();
+
+    let graph = to_graph(&document_node);
+    assert_eq!(graph.node_count(), 6);
+    assert_eq!(graph.edge_count(), 5);
+
+    let labels: Vec<String> = graph
+        .node_weights()
+        .map(|label| label.to_string())
+        .collect();
+    assert_eq!(
+        labels,
+        vec![
+            "#document",
+            "root",
+            "id = \"main\"",
+            "child",
+            "#comment = \"note\"",
+            "#text = \"Hello\"",
+        ]
+    );
+    assert_eq!(
+        graph
+            .edge_weights()
+            .filter(|kind| **kind == EdgeKind::Attribute)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_dot_output() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+
+    let dot = to_dot(&root_node);
+    assert!(dot.starts_with("digraph {"));
+    assert!(dot.contains("label = \"root\""));
+}
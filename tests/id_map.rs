@@ -1,5 +1,5 @@
-use xml_dom::level2::convert::{as_document, as_element, as_element_mut};
-use xml_dom::level2::ext::ProcessingOptions;
+use xml_dom::level2::convert::{as_attribute_mut, as_document, as_element, as_element_mut};
+use xml_dom::level2::ext::{IdAttributes, ProcessingOptions};
 use xml_dom::level2::*;
 
 pub mod common;
@@ -96,3 +96,118 @@ fn test_no_duplicates() {
     assert!(result.is_err());
     assert_eq!(result.err().unwrap(), Error::Syntax);
 }
+
+#[test]
+fn test_get_element_by_id_maintained() {
+    let document_node = get_implementation()
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut section_node = document.create_element("section").unwrap();
+    let mut item_node = document.create_element("item").unwrap();
+    item_node.set_attribute("xml:id", "c1").unwrap();
+    let item_node = section_node.append_child(item_node).unwrap();
+    let mut section_node = root_node.append_child(section_node).unwrap();
+    assert_eq!(document.get_element_by_id("c1"), Some(item_node.clone()));
+
+    common::sub_test("test_get_element_by_id_maintained", "set_attribute");
+    let mut item_node = item_node;
+    item_node.set_attribute("xml:id", "c2").unwrap();
+    assert!(document.get_element_by_id("c1").is_none());
+    assert_eq!(document.get_element_by_id("c2"), Some(item_node.clone()));
+    item_node.set_attribute("xml:id", "c2").unwrap();
+    assert_eq!(document.get_element_by_id("c2"), Some(item_node.clone()));
+
+    common::sub_test("test_get_element_by_id_maintained", "set_value");
+    let mut attribute_node = item_node.get_attribute_node("xml:id").unwrap();
+    let attribute = as_attribute_mut(&mut attribute_node).unwrap();
+    attribute.set_value("c3").unwrap();
+    assert!(document.get_element_by_id("c2").is_none());
+    assert_eq!(document.get_element_by_id("c3"), Some(item_node.clone()));
+
+    common::sub_test("test_get_element_by_id_maintained", "remove_attribute");
+    item_node.remove_attribute("xml:id").unwrap();
+    assert!(document.get_element_by_id("c3").is_none());
+    item_node.set_attribute("xml:id", "c1").unwrap();
+    assert_eq!(document.get_element_by_id("c1"), Some(item_node.clone()));
+
+    common::sub_test("test_get_element_by_id_maintained", "remove_child");
+    let removed_node = root_node.remove_child(section_node.clone()).unwrap();
+    assert!(document.get_element_by_id("c1").is_none());
+    let mut other_node = document.create_element("item").unwrap();
+    other_node.set_attribute("xml:id", "c1").unwrap();
+    assert_eq!(document.get_element_by_id("c1"), Some(other_node.clone()));
+
+    common::sub_test("test_get_element_by_id_maintained", "insert duplicate");
+    assert_eq!(
+        root_node.append_child(removed_node.clone()),
+        Err(Error::Syntax)
+    );
+    assert!(removed_node.parent_node().is_none());
+    other_node.remove_attribute("xml:id").unwrap();
+    let _safe_to_ignore = root_node.append_child(removed_node).unwrap();
+    assert_eq!(document.get_element_by_id("c1"), Some(item_node.clone()));
+
+    common::sub_test("test_get_element_by_id_maintained", "dropped");
+    let _safe_to_ignore = section_node.remove_child(item_node.clone()).unwrap();
+    drop(item_node);
+    assert!(document.get_element_by_id("c1").is_none());
+}
+
+#[test]
+fn test_register_id_attribute() {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    let document_ref = document_node.clone();
+    let document = as_document(&document_ref).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut item_node = document.create_element("item").unwrap();
+    item_node.set_attribute("key", "c1").unwrap();
+    let item_node = root_node.append_child(item_node).unwrap();
+    assert!(document.get_element_by_id("c1").is_none());
+
+    document_node.register_id_attribute(None, "key").unwrap();
+    assert!(document_node.is_registered_id_attribute(None, "key"));
+    assert_eq!(document.get_element_by_id("c1"), Some(item_node));
+
+    common::sub_test("test_register_id_attribute", "set later");
+    let mut other_node = document.create_element("item").unwrap();
+    other_node.set_attribute("key", "c2").unwrap();
+    assert_eq!(document.get_element_by_id("c2"), Some(other_node.clone()));
+    assert_eq!(other_node.set_attribute("key", "c1"), Err(Error::Syntax));
+
+    common::sub_test("test_register_id_attribute", "namespaced");
+    let ns = "http://example.org/schema";
+    document_node
+        .register_id_attribute(Some(ns), "ref")
+        .unwrap();
+    let mut ns_node = document.create_element("other").unwrap();
+    ns_node.set_attribute("ref", "plain").unwrap();
+    ns_node.set_attribute_ns(ns, "s:ref", "n1").unwrap();
+    assert!(document.get_element_by_id("plain").is_none());
+    assert_eq!(document.get_element_by_id("n1"), Some(ns_node));
+}
+
+#[test]
+fn test_register_id_attribute_duplicate() {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    let document_ref = document_node.clone();
+    let document = as_document(&document_ref).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for _ in 0..2 {
+        let mut item_node = document.create_element("item").unwrap();
+        item_node.set_attribute("key", "c1").unwrap();
+        let _safe_to_ignore = root_node.append_child(item_node).unwrap();
+    }
+
+    assert_eq!(
+        document_node.register_id_attribute(None, "key"),
+        Err(Error::Syntax)
+    );
+    assert!(!document_node.is_registered_id_attribute(None, "key"));
+    assert!(document.get_element_by_id("c1").is_none());
+}
@@ -1,6 +1,10 @@
 use std::str::FromStr;
-use xml_dom::level2::convert::{as_document, as_document_type, as_element};
-use xml_dom::level2::{get_implementation, Name};
+use xml_dom::level2::convert::{
+    as_document, as_document_mut, as_document_type, as_element, as_element_mut,
+};
+use xml_dom::level2::ext::dom_impl::{create_internal_entity, create_notation};
+use xml_dom::level2::ext::{DocumentTypeExt, ProcessingOptions};
+use xml_dom::level2::{get_implementation, DOMImplementation, Error, Implementation, Name};
 
 pub mod common;
 
@@ -88,3 +92,162 @@ fn test_create_document_type() {
     let stored_doc_type = document.doc_type().unwrap();
     assert_eq!(&document_type_node, &stored_doc_type);
 }
+
+#[test]
+fn test_independent_implementation_defaults() {
+    let mut options = ProcessingOptions::new();
+    options.set_assume_ids();
+    let implementation = Implementation::with_default_options(options.clone());
+    assert_eq!(implementation.default_options(), options);
+    assert_ne!(implementation, Implementation::new());
+
+    let mut document_node = implementation
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let _safe_to_ignore = as_element_mut(&mut root_node)
+        .unwrap()
+        .set_attribute("id", "root-id");
+    let document = as_document_mut(&mut document_node).unwrap();
+    assert!(document.get_element_by_id("root-id").is_some());
+
+    let shared_document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let mut root_node = as_document(&shared_document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let _safe_to_ignore = as_element_mut(&mut root_node)
+        .unwrap()
+        .set_attribute("id", "root-id");
+    let document = as_document(&shared_document_node).unwrap();
+    assert!(document.get_element_by_id("root-id").is_none());
+}
+
+#[test]
+fn test_document_remembers_implementation() {
+    let mut options = ProcessingOptions::new();
+    options.set_assume_ids();
+    let implementation = Implementation::with_default_options(options);
+
+    let document_node = implementation.create_document(None, None, None).unwrap();
+    let document = as_document(&document_node).unwrap();
+
+    let other_document_node = document
+        .implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let mut root_node = as_document(&other_document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let _safe_to_ignore = as_element_mut(&mut root_node)
+        .unwrap()
+        .set_attribute("id", "root-id");
+    let other_document = as_document(&other_document_node).unwrap();
+    assert!(other_document.get_element_by_id("root-id").is_some());
+}
+
+#[test]
+fn test_create_document_attaches_document_type() {
+    let implementation = get_implementation();
+    let document_type_node = implementation
+        .create_document_type("note", None, None)
+        .unwrap();
+    assert!(document_type_node.owner_document().is_none());
+
+    let document_node = implementation
+        .create_document(None, Some("note"), Some(document_type_node.clone()))
+        .unwrap();
+    assert_eq!(
+        document_type_node.owner_document(),
+        Some(document_node.clone())
+    );
+    assert_eq!(
+        document_type_node.parent_node(),
+        Some(document_node.clone())
+    );
+
+    let reused = implementation.create_document(None, Some("note"), Some(document_type_node));
+    assert_eq!(reused.err(), Some(Error::WrongDocument));
+}
+
+#[test]
+fn test_document_type_add_entity_and_notation() {
+    let implementation = get_implementation();
+    let document_type_node = implementation
+        .create_document_type("note", None, None)
+        .unwrap();
+    let document_node = implementation
+        .create_document(None, Some("note"), Some(document_type_node))
+        .unwrap();
+    let mut document_type_node = as_document(&document_node).unwrap().doc_type().unwrap();
+
+    let entity = create_internal_entity(document_node.clone(), "writer", "Donald").unwrap();
+    assert_eq!(document_type_node.add_entity(entity.clone()), Ok(None));
+    let replacement = create_internal_entity(document_node.clone(), "writer", "Duck").unwrap();
+    assert_eq!(
+        document_type_node.add_entity(replacement.clone()),
+        Ok(Some(entity))
+    );
+    let notation = create_notation(document_node.clone(), "gif", None, Some("image/gif")).unwrap();
+    assert_eq!(document_type_node.add_notation(notation.clone()), Ok(None));
+
+    let document_type = as_document_type(&document_type_node).unwrap();
+    assert_eq!(document_type.entities().len(), 1);
+    assert_eq!(
+        document_type
+            .entities()
+            .get(&Name::from_str("writer").unwrap()),
+        Some(&replacement)
+    );
+    assert_eq!(document_type.notations().len(), 1);
+
+    assert_eq!(
+        document_type_node.add_entity(notation),
+        Err(Error::HierarchyRequest)
+    );
+    let other_document_node = implementation.create_document(None, None, None).unwrap();
+    let other_entity = create_internal_entity(other_document_node.clone(), "other", "x").unwrap();
+    assert_eq!(
+        document_type_node.add_entity(other_entity),
+        Err(Error::WrongDocument)
+    );
+}
+
+#[test]
+fn test_document_type_set_internal_subset() {
+    let implementation = get_implementation();
+    let document_type_node = implementation
+        .create_document_type("note", None, None)
+        .unwrap();
+    let document_node = implementation
+        .create_document(None, Some("note"), Some(document_type_node))
+        .unwrap();
+    let mut document_type_node = as_document(&document_node).unwrap().doc_type().unwrap();
+
+    document_type_node
+        .set_internal_subset(Some("<!ELEMENT note (#PCDATA)>"))
+        .unwrap();
+    assert_eq!(
+        as_document_type(&document_type_node)
+            .unwrap()
+            .internal_subset(),
+        Some("<!ELEMENT note (#PCDATA)>".to_string())
+    );
+    assert_eq!(
+        document_node.to_string(),
+        "<!DOCTYPE note [<!ELEMENT note (#PCDATA)>]><note></note>"
+    );
+
+    assert_eq!(
+        document_type_node.set_internal_subset(Some("<!ELEMENT note")),
+        Err(Error::Syntax)
+    );
+    document_type_node.set_internal_subset(None).unwrap();
+    assert_eq!(document_node.to_string(), "<!DOCTYPE note><note></note>");
+}
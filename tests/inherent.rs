@@ -0,0 +1,37 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::{get_implementation, Node, NodeType, RefNode};
+
+fn create_document() -> RefNode {
+    get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap()
+}
+
+#[test]
+fn test_inherent_methods() {
+    // the Node trait is only used, explicitly, to append a child.
+    let document_node = create_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+    root_node.set_attribute("id", "r").unwrap();
+    let mut item_node = document.create_element("item").unwrap();
+    item_node.set_attribute("n", "1").unwrap();
+    let _safe_to_ignore = Node::append_child(&mut root_node, item_node.clone()).unwrap();
+
+    assert_eq!(root_node.node_type(), NodeType::Element);
+    assert_eq!(root_node.tag_name(), "root");
+    assert_eq!(root_node.get_attribute("id"), Some("r".to_string()));
+    assert!(root_node.has_attribute("id"));
+    assert!(root_node.has_attributes());
+    assert_eq!(root_node.first_child(), Some(item_node.clone()));
+    assert_eq!(item_node.parent_node(), Some(root_node.clone()));
+    assert_eq!(item_node.owner_document(), Some(document_node.clone()));
+
+    // the same name on Element and Document is resolved by node type.
+    assert_eq!(document_node.get_elements_by_tag_name("item").len(), 1);
+    assert_eq!(root_node.get_elements_by_tag_name("item").len(), 1);
+
+    let attribute_node = item_node.get_attribute_node("n").unwrap();
+    assert_eq!(attribute_node.value(), Some("1".to_string()));
+    assert_eq!(attribute_node.owner_element(), Some(item_node));
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "serde_json")]
+
+use serde_json::json;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::json::{to_json_value, JsonMappingOptions};
+use xml_dom::level2::*;
+
+pub mod common;
+
+fn build_document() -> RefNode {
+    let document_node = get_implementation()
+        .create_document(None, Some("log"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("level", "info").unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node("\n  "))
+        .unwrap();
+    let mut entry_node = root_node
+        .append_child(document.create_element("entry").unwrap())
+        .unwrap();
+    let _safe_to_ignore = entry_node
+        .append_child(document.create_text_node("a < b"))
+        .unwrap();
+    let _safe_to_ignore = entry_node
+        .append_child(document.create_comment("dropped"))
+        .unwrap();
+    let _safe_to_ignore = entry_node
+        .append_child(document.create_cdata_section(" & c").unwrap())
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_element("empty").unwrap())
+        .unwrap();
+    document_node
+}
+
+#[test]
+fn test_default_mapping() {
+    let document_node = build_document();
+    assert_eq!(
+        to_json_value(&document_node, JsonMappingOptions::new()),
+        json!({ "log": { "@level": "info", "entry": "a < b & c", "empty": null } })
+    );
+
+    common::sub_test("test_default_mapping", "nodes");
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    let entry_node = root_node.child_nodes()[1].clone();
+    assert_eq!(
+        to_json_value(&entry_node, JsonMappingOptions::new()),
+        json!({ "entry": "a < b & c" })
+    );
+    assert_eq!(
+        to_json_value(
+            &entry_node.first_child().unwrap(),
+            JsonMappingOptions::new()
+        ),
+        json!("a < b")
+    );
+    let attribute_node = root_node.get_attribute_node("level").unwrap();
+    assert_eq!(
+        to_json_value(&attribute_node, JsonMappingOptions::new()),
+        json!("info")
+    );
+    let mut fragment_node = document.create_document_fragment().unwrap();
+    let _safe_to_ignore = fragment_node.append_child(entry_node).unwrap();
+    assert_eq!(
+        to_json_value(&fragment_node, JsonMappingOptions::new()),
+        json!({ "entry": "a < b & c" })
+    );
+}
+
+#[test]
+fn test_mapping_options() {
+    let document_node = build_document();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node("done"))
+        .unwrap();
+
+    let mut options = JsonMappingOptions::new();
+    options.set_attribute_prefix("_");
+    options.set_text_key("$");
+    options.set_always_array();
+    assert_eq!(options.attribute_prefix(), "_");
+    assert_eq!(options.text_key(), "$");
+    assert!(options.has_always_array());
+    assert_eq!(
+        to_json_value(&document_node, options),
+        json!({
+            "log": {
+                "_level": "info",
+                "entry": ["a < b & c"],
+                "empty": [null],
+                "$": "\n  done"
+            }
+        })
+    );
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_parsed_mapping() {
+    let document_node = xml_dom::parser::read_xml(
+        r#"<!DOCTYPE feed><feed><?pi?><item n="1">Hi &amp;<![CDATA[ bye]]></item><item n="2"/><item/></feed>"#,
+    )
+    .unwrap();
+    assert_eq!(
+        to_json_value(&document_node, JsonMappingOptions::new()),
+        json!({ "feed": { "item": [{ "@n": "1", "#text": "Hi & bye" }, { "@n": "2" }, null] } })
+    );
+}
@@ -0,0 +1,78 @@
+use xml_dom::level2::convert::{as_document, as_element};
+use xml_dom::level2::ext::Language;
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_language_inherited() {
+    let document_node = get_implementation()
+        .create_document(None, Some("book"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("xml:lang", "en").unwrap();
+    let mut chapter_node = document.create_element("chapter").unwrap();
+    chapter_node.set_attribute("title", "Un").unwrap();
+    let _safe_to_ignore = root_node.append_child(chapter_node.clone()).unwrap();
+    let text_node = document.create_text_node("Text");
+    let _safe_to_ignore = chapter_node.append_child(text_node.clone()).unwrap();
+
+    assert!(document_node.language().is_none());
+    assert_eq!(root_node.language(), Some("en".to_string()));
+    assert_eq!(text_node.language(), Some("en".to_string()));
+
+    common::sub_test("test_language_inherited", "override");
+    chapter_node.set_language("fr-CA").unwrap();
+    assert_eq!(text_node.language(), Some("fr-CA".to_string()));
+    let title_node = as_element(&chapter_node)
+        .unwrap()
+        .get_attribute_node("title")
+        .unwrap();
+    assert_eq!(title_node.language(), Some("fr-CA".to_string()));
+    assert_eq!(as_element(&chapter_node).unwrap().attributes().len(), 2);
+
+    common::sub_test("test_language_inherited", "replace");
+    root_node.set_language("en-GB").unwrap();
+    assert_eq!(root_node.attributes().len(), 1);
+    assert_eq!(
+        root_node.get_attribute("xml:lang"),
+        Some("en-GB".to_string())
+    );
+
+    common::sub_test("test_language_inherited", "undeclared");
+    chapter_node.set_language("").unwrap();
+    assert!(text_node.language().is_none());
+
+    common::sub_test("test_language_inherited", "invalid");
+    assert_eq!(chapter_node.set_language("en_US"), Err(Error::Syntax));
+    assert!(text_node.clone().set_language("en").is_err());
+}
+
+#[test]
+fn test_text_in_language() {
+    let document_node = get_implementation()
+        .create_document(None, Some("labels"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_language("en").unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_text_node("Color"))
+        .unwrap();
+    for (language, text) in [("en-GB", "Colour"), ("de", "Farbe"), ("", "?")] {
+        let mut label_node = document.create_element("label").unwrap();
+        label_node.set_language(language).unwrap();
+        let _safe_to_ignore = label_node
+            .append_child(document.create_text_node(text))
+            .unwrap();
+        let _safe_to_ignore = root_node.append_child(label_node).unwrap();
+    }
+
+    assert_eq!(root_node.text_in_language("en"), "ColorColour");
+    assert_eq!(root_node.text_in_language("EN-gb"), "Colour");
+    assert_eq!(root_node.text_in_language("e"), "");
+    assert_eq!(root_node.text_in_language("de"), "Farbe");
+    assert_eq!(root_node.text_in_language("*"), "ColorColourFarbe");
+    assert_eq!(document_node.text_in_language("de-DE"), "");
+}
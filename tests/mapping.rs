@@ -0,0 +1,101 @@
+#![cfg(feature = "derive")]
+
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::mapping::{FromXmlDom, ToXmlDom};
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[derive(Debug, PartialEq, ToXmlDom, FromXmlDom)]
+struct Title {
+    #[xml(attribute, rename = "xml:lang")]
+    lang: Option<String>,
+    #[xml(text)]
+    text: String,
+}
+
+#[derive(Debug, PartialEq, ToXmlDom, FromXmlDom)]
+struct Book {
+    #[xml(attribute)]
+    id: u32,
+    title: Title,
+    #[xml(rename = "author")]
+    authors: Vec<String>,
+    r#type: Option<String>,
+    in_print: bool,
+}
+
+fn book() -> Book {
+    Book {
+        id: 42,
+        title: Title {
+            lang: Some("en".to_string()),
+            text: "A Guide to Growing Roses".to_string(),
+        },
+        authors: vec!["Rose Gardener".to_string(), "Jane Doe".to_string()],
+        r#type: None,
+        in_print: true,
+    }
+}
+
+#[test]
+fn test_to_xml_dom() {
+    let document_node = book().to_document("book").unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        "<book id=\"42\"><title xml:lang=\"en\">A Guide to Growing Roses</title>\
+         <author>Rose Gardener</author><author>Jane Doe</author>\
+         <in_print>true</in_print></book>"
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let book = book();
+    let document_node = book.to_document("book").unwrap();
+    assert_eq!(Book::from_document(&document_node).unwrap(), book);
+
+    common::sub_test("test_round_trip", "element");
+    let document = as_document(&document_node).unwrap();
+    let title_node = book.title.to_element(&document_node, "name").unwrap();
+    assert_eq!(title_node.node_name().to_string(), "name");
+    assert_eq!(title_node.owner_document(), Some(document_node.clone()));
+    assert_eq!(Title::from_element(&title_node).unwrap(), book.title);
+    assert!(document.document_element().is_some());
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_mixed_processing() {
+    let mut document_node = xml_dom::parser::read_xml(
+        r#"<book id="7"><title>Roses</title><!-- untyped --><type>paperback</type><in_print>false</in_print><extra/></book>"#,
+    )
+    .unwrap();
+    let mut book = Book::from_document(&document_node).unwrap();
+    assert_eq!(book.id, 7);
+    assert_eq!(book.title.lang, None);
+    assert!(book.authors.is_empty());
+    assert_eq!(book.r#type, Some("paperback".to_string()));
+    assert!(!book.in_print);
+
+    common::sub_test("test_mixed_processing", "replace_element");
+    book.authors.push("Anonymous".to_string());
+    let document = as_document(&document_node).unwrap();
+    let old_node = document.document_element().unwrap();
+    let new_node = book.to_element(&document_node, "book").unwrap();
+    let _ = document_node.replace_child(new_node, old_node).unwrap();
+    assert_eq!(Book::from_document(&document_node).unwrap(), book);
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_from_xml_dom_errors() {
+    let document_node = xml_dom::parser::read_xml(r#"<book><title>Roses</title></book>"#).unwrap();
+    assert_eq!(Book::from_document(&document_node), Err(Error::NotFound));
+
+    let document_node = xml_dom::parser::read_xml(
+        r#"<book id="seven"><title>Roses</title><in_print>true</in_print></book>"#,
+    )
+    .unwrap();
+    assert_eq!(Book::from_document(&document_node), Err(Error::Syntax));
+}
@@ -0,0 +1,69 @@
+use xml_dom::level2::ext::matcher::matches;
+use xml_dom::tree;
+
+pub mod common;
+
+#[test]
+fn test_matching_tree() {
+    let document_node = common::create_example_rdf_document();
+
+    let report = matches(
+        &document_node,
+        &tree! {
+            "rdf:RDF"(about = _, "xmlns:rdf" = "http://www.w3.org/1999/02/22-rdf-syntax-ns#") => [
+                "rdf:Description" => [
+                    "dc:creator"(text = "Rose Bush"),
+                    "dc:title"(id = "title", text = "A Guide to Growing Roses"),
+                    "dc:Description"(id = _),
+                    "dc:date"
+                ]
+            ]
+        },
+    );
+    assert!(report.is_match(), "{}", report);
+}
+
+#[test]
+fn test_mismatch_report() {
+    let document_node = common::create_example_rdf_document();
+
+    let report = matches(
+        &document_node,
+        &tree! {
+            "rdf:RDF"(version = _) => [
+                "rdf:Description" => [
+                    "dc:creator"(text = "Rose"),
+                    "dc:subject",
+                    "dc:Description"(id = "main"),
+                ]
+            ]
+        },
+    );
+    assert!(!report.is_match());
+    let messages: Vec<String> = report
+        .mismatches()
+        .iter()
+        .map(|mismatch| mismatch.to_string())
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            "/rdf:RDF: missing attribute `version`",
+            "/rdf:RDF/rdf:Description[1]: expected 3 child elements, found 4",
+            "/rdf:RDF/rdf:Description[1]/dc:creator[1]: expected text \"Rose\", found \"Rose Bush\"",
+            "/rdf:RDF/rdf:Description[1]/dc:title[2]: expected element `dc:subject`, found `dc:title`",
+            "/rdf:RDF/rdf:Description[1]/dc:Description[3]: expected attribute `id` to be \"main\", found \"description\"",
+        ]
+    );
+}
+
+#[test]
+fn test_no_document_element() {
+    let document_node = common::create_empty_rdf_document();
+    assert!(matches(&document_node, &tree! { "rdf:RDF" => [] }).is_match());
+    assert!(!matches(
+        &document_node,
+        &tree! { "rdf:RDF" => [ "rdf:Description" ] }
+    )
+    .is_match());
+}
@@ -0,0 +1,190 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::freeze::{freeze, thaw};
+use xml_dom::level2::ext::merge::{merge, MergePolicy};
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_merge_into_empty_document() {
+    let mut base_node = get_implementation()
+        .create_document(None, None, None)
+        .unwrap();
+    let overlay_node = common::create_example_rdf_document();
+    merge(&mut base_node, &overlay_node, &MergePolicy::new()).unwrap();
+    assert_eq!(base_node.to_string(), overlay_node.to_string());
+
+    // the document element is a copy owned by the base document.
+    let root_node = as_document(&base_node).unwrap().document_element().unwrap();
+    assert_eq!(root_node.owner_document(), Some(base_node.clone()));
+}
+
+#[test]
+fn test_merge_same_document() {
+    let mut base_node = common::create_example_rdf_document();
+    let overlay_node = common::create_example_rdf_document();
+    merge(&mut base_node, &overlay_node, &MergePolicy::new()).unwrap();
+    assert_eq!(base_node.to_string(), overlay_node.to_string());
+}
+
+#[test]
+fn test_merge_mismatched() {
+    let mut base_node = common::create_example_rdf_document();
+    let overlay_node = get_implementation()
+        .create_document(None, Some("config"), None)
+        .unwrap();
+    assert_eq!(
+        merge(&mut base_node, &overlay_node, &MergePolicy::new()),
+        Err(Error::InvalidModification)
+    );
+
+    let mut root_node = as_document(&base_node).unwrap().document_element().unwrap();
+    assert_eq!(
+        merge(&mut root_node, &overlay_node, &MergePolicy::new()),
+        Err(Error::InvalidState)
+    );
+}
+
+#[test]
+fn test_merge_frozen() {
+    let mut base_node = get_implementation()
+        .create_document(None, Some("config"), None)
+        .unwrap();
+    let overlay_node = get_implementation()
+        .create_document(None, Some("config"), None)
+        .unwrap();
+    let mut overlay_root = as_document(&overlay_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    overlay_root.set_attribute("version", "2").unwrap();
+
+    let mut root_node = as_document(&base_node).unwrap().document_element().unwrap();
+    let token = freeze(&mut root_node);
+    assert_eq!(
+        merge(&mut base_node, &overlay_node, &MergePolicy::new()),
+        Err(Error::NoModificationAllowed)
+    );
+    thaw(token);
+    merge(&mut base_node, &overlay_node, &MergePolicy::new()).unwrap();
+    assert_eq!(root_node.get_attribute("version"), Some("2".to_string()));
+}
+
+#[cfg(feature = "quick_parser")]
+mod parsed {
+    use super::*;
+    use xml_dom::level2::ext::merge::{AttributeMerge, TextMerge};
+    use xml_dom::parser::read_xml;
+
+    fn merged(base: &str, overlay: &str, policy: &MergePolicy) -> String {
+        let mut base_node = read_xml(base).unwrap();
+        let overlay_node = read_xml(overlay).unwrap();
+        merge(&mut base_node, &overlay_node, policy).unwrap();
+        base_node.to_string()
+    }
+
+    #[test]
+    fn test_merge_attributes() {
+        let base = r#"<a x="1" y="2"/>"#;
+        let overlay = r#"<a y="two" z="three"/>"#;
+        let mut policy = MergePolicy::new();
+        assert_eq!(
+            merged(base, overlay, &policy),
+            r#"<a x="1" y="two" z="three"></a>"#
+        );
+        policy.set_attributes(AttributeMerge::Base);
+        assert_eq!(
+            merged(base, overlay, &policy),
+            r#"<a x="1" y="2" z="three"></a>"#
+        );
+        policy.set_attributes(AttributeMerge::Replace);
+        assert_eq!(
+            merged(base, overlay, &policy),
+            r#"<a y="two" z="three"></a>"#
+        );
+    }
+
+    #[test]
+    fn test_merge_namespaced_attributes() {
+        assert_eq!(
+            merged(
+                r#"<a xmlns:x="urn:x" x:one="1"/>"#,
+                r#"<a xmlns:x="urn:x" x:one="one"/>"#,
+                &MergePolicy::new(),
+            ),
+            r#"<a xmlns:x="urn:x" x:one="one"></a>"#
+        );
+    }
+
+    #[test]
+    fn test_merge_text() {
+        let base = "<a><b>one<!-- note -->two</b><c>three</c></a>";
+        let overlay = "<a><b>2</b><c/><d>4</d></a>";
+        let mut policy = MergePolicy::new();
+        assert_eq!(
+            merged(base, overlay, &policy),
+            "<a><b>2<!-- note --></b><c>three</c><d>4</d></a>"
+        );
+        policy.set_text(TextMerge::Concatenate);
+        assert_eq!(
+            merged(base, overlay, &policy),
+            "<a><b>one<!-- note -->two2</b><c>three</c><d>4</d></a>"
+        );
+        policy.set_text(TextMerge::Base);
+        assert_eq!(
+            merged(base, overlay, &policy),
+            "<a><b>one<!-- note -->two</b><c>three</c><d>4</d></a>"
+        );
+    }
+
+    #[test]
+    fn test_merge_children_in_order() {
+        assert_eq!(
+            merged(
+                "<list><item>a</item><item>b</item><other/></list>",
+                "<list><item/><item>B</item><item>C</item></list>",
+                &MergePolicy::new(),
+            ),
+            "<list><item>a</item><item>B</item><other></other><item>C</item></list>"
+        );
+    }
+
+    #[test]
+    fn test_merge_key_attributes() {
+        let mut policy = MergePolicy::new();
+        policy.add_key_attribute("id");
+        policy.add_key_attribute("name");
+        policy.add_key_attribute("id");
+        assert_eq!(policy.key_attributes(), &["id", "name"]);
+        assert_eq!(
+            merged(
+                r#"<c><p name="x" v="1"/><p id="1" name="y"/><p/></c>"#,
+                r#"<c><p id="1" v="2"/><p name="x" v="3"/><p name="z"/><p v="4"/></c>"#,
+                &policy,
+            ),
+            r#"<c><p name="x" v="3"></p><p id="1" name="y" v="2"></p><p v="4"></p><p name="z"></p></c>"#
+        );
+    }
+
+    #[test]
+    fn test_merge_elements() {
+        let mut base_node = read_xml("<a><b><c/></b></a>").unwrap();
+        let overlay_node = read_xml(r#"<b k="v"><d/></b>"#).unwrap();
+        let mut b_node = as_document(&base_node)
+            .unwrap()
+            .document_element()
+            .unwrap()
+            .first_child()
+            .unwrap();
+        let overlay_root = as_document(&overlay_node)
+            .unwrap()
+            .document_element()
+            .unwrap();
+        merge(&mut b_node, &overlay_root, &MergePolicy::new()).unwrap();
+        assert_eq!(
+            base_node.to_string(),
+            r#"<a><b k="v"><c></c><d></d></b></a>"#
+        );
+        assert!(merge(&mut base_node, &overlay_root, &MergePolicy::new()).is_err());
+    }
+}
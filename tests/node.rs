@@ -1,5 +1,7 @@
 use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::convert as ext_convert;
 use xml_dom::level2::ext::dom_impl as ext_dom_impl;
+use xml_dom::level2::ext::{ProcessingOptions, Restructure, TextContent};
 use xml_dom::level2::*;
 
 pub mod common;
@@ -99,7 +101,7 @@ fn test_insert_child_node() {
         assert!(new_node.parent_node().is_some());
         assert!(new_node.owner_document().is_some());
         compare_node_names(
-            &mut_root.child_nodes(),
+            mut_root.child_nodes(),
             &[
                 "child-1",
                 "child-2",
@@ -121,7 +123,7 @@ fn test_insert_child_node() {
         assert!(new_node.parent_node().is_some());
         assert!(new_node.owner_document().is_some());
         compare_node_names(
-            &mut_root.child_nodes(),
+            mut_root.child_nodes(),
             &[
                 "inserted-2",
                 "child-1",
@@ -143,7 +145,7 @@ fn test_insert_child_node() {
         assert!(new_node.parent_node().is_some());
         assert!(new_node.owner_document().is_some());
         compare_node_names(
-            &mut_root.child_nodes(),
+            mut_root.child_nodes(),
             &[
                 "inserted-2",
                 "child-1",
@@ -186,7 +188,7 @@ fn test_replace_child_node() {
         let result = mut_root.replace_child(new_child_node, mid_node.clone());
         assert!(result.is_ok());
         compare_node_names(
-            &mut_root.child_nodes(),
+            mut_root.child_nodes(),
             &["child-1", "child-2", "inserted-1", "child-4", "child-5"],
         );
     }
@@ -198,7 +200,7 @@ fn test_replace_child_node() {
         let result = mut_root.replace_child(new_child_node, first_node.clone());
         assert!(result.is_ok());
         compare_node_names(
-            &mut_root.child_nodes(),
+            mut_root.child_nodes(),
             &["inserted-2", "child-2", "inserted-1", "child-4", "child-5"],
         );
     }
@@ -210,7 +212,7 @@ fn test_replace_child_node() {
         let result = mut_root.replace_child(new_child_node, last_node.clone());
         assert!(result.is_ok());
         compare_node_names(
-            &mut_root.child_nodes(),
+            mut_root.child_nodes(),
             &[
                 "inserted-2",
                 "child-2",
@@ -249,7 +251,7 @@ fn test_remove_child_node() {
         let result = mut_root.remove_child(mid_node.clone());
         assert!(result.is_ok());
         compare_node_names(
-            &mut_root.child_nodes(),
+            mut_root.child_nodes(),
             &["child-1", "child-2", "child-4", "child-5"],
         );
     }
@@ -259,7 +261,7 @@ fn test_remove_child_node() {
         let first_node = child_nodes.first().unwrap();
         let result = mut_root.remove_child(first_node.clone());
         assert!(result.is_ok());
-        compare_node_names(&mut_root.child_nodes(), &["child-2", "child-4", "child-5"]);
+        compare_node_names(mut_root.child_nodes(), &["child-2", "child-4", "child-5"]);
     }
 
     {
@@ -267,7 +269,7 @@ fn test_remove_child_node() {
         let last_node = child_nodes.last().unwrap();
         let result = mut_root.remove_child(last_node.clone());
         assert!(result.is_ok());
-        compare_node_names(&mut_root.child_nodes(), &["child-2", "child-4"]);
+        compare_node_names(mut_root.child_nodes(), &["child-2", "child-4"]);
     }
 
     {
@@ -339,9 +341,359 @@ fn test_previous_sibling() {
 }
 
 #[test]
-#[ignore]
-fn test_clone_node() {
-    unimplemented!()
+fn test_clone_node_element() {
+    let mut options = ProcessingOptions::new();
+    options.set_add_namespaces();
+    let document_node = ext_dom_impl::get_implementation_ext()
+        .create_document_with_options(Some("http://example.org/"), Some("root"), None, options)
+        .unwrap();
+    let ref_document = as_document(&document_node).unwrap();
+    let mut root_node = ref_document.document_element().unwrap();
+    let mut element_node = append_element_node(&mut root_node, "element");
+    {
+        let element = as_element_mut(&mut element_node).unwrap();
+        element.set_attribute("name", "value").unwrap();
+        element
+            .set_attribute_ns(
+                "http://www.w3.org/2000/xmlns/",
+                "xmlns:ex",
+                "http://example.org/ns",
+            )
+            .unwrap();
+    }
+    let mut child_node = append_element_node(&mut element_node, "child");
+    let _safe_to_ignore = append_text_node(&mut child_node, "text");
+
+    common::sub_test("test_clone_node_element", "shallow");
+    let shallow_node = element_node.clone_node(false).unwrap();
+    assert_ne!(shallow_node, element_node);
+    assert!(shallow_node.parent_node().is_none());
+    assert_eq!(shallow_node.owner_document(), Some(document_node.clone()));
+    assert!(shallow_node.child_nodes().is_empty());
+    let shallow = as_element(&shallow_node).unwrap();
+    assert_eq!(shallow.get_attribute("name"), Some("value".to_string()));
+    assert_eq!(
+        ext_convert::as_element_namespaced(&shallow_node)
+            .unwrap()
+            .get_namespace(Some("ex")),
+        Some("http://example.org/ns".to_string())
+    );
+    let attribute_node = shallow.get_attribute_node("name").unwrap();
+    assert_ne!(
+        attribute_node,
+        as_element(&element_node)
+            .unwrap()
+            .get_attribute_node("name")
+            .unwrap()
+    );
+    assert_eq!(
+        as_attribute(&attribute_node).unwrap().owner_element(),
+        Some(shallow_node.clone())
+    );
+
+    common::sub_test("test_clone_node_element", "deep");
+    let mut deep_node = element_node.clone_node(true).unwrap();
+    assert_eq!(deep_node.attributes().len(), 2);
+    let deep_child_node = deep_node.first_child().unwrap();
+    assert_ne!(deep_child_node, child_node);
+    assert_eq!(deep_child_node.parent_node(), Some(deep_node.clone()));
+    let deep_text_node = deep_child_node.first_child().unwrap();
+    assert_eq!(deep_text_node.parent_node(), Some(deep_child_node.clone()));
+    assert_eq!(deep_text_node.node_value(), Some("text".to_string()));
+
+    common::sub_test("test_clone_node_element", "independent");
+    as_element_mut(&mut deep_node)
+        .unwrap()
+        .set_attribute("name", "changed")
+        .unwrap();
+    let _safe_to_ignore = append_element_node(&mut deep_node, "added");
+    assert_eq!(
+        as_element(&element_node).unwrap().get_attribute("name"),
+        Some("value".to_string())
+    );
+    assert_eq!(element_node.child_nodes().len(), 1);
+    assert!(root_node.append_child(deep_node).is_ok());
+}
+
+#[test]
+fn test_clone_node_character_data() {
+    let document_node = get_implementation()
+        .create_document(Some("http://example.org/"), Some("root"), None)
+        .unwrap();
+    let ref_document = as_document(&document_node).unwrap();
+    let mut root_node = ref_document.document_element().unwrap();
+
+    let nodes = vec![
+        ref_document.create_text_node("text"),
+        ref_document.create_cdata_section("<cdata>").unwrap(),
+        ref_document.create_comment("comment"),
+        ref_document
+            .create_processing_instruction("target", Some("data"))
+            .unwrap(),
+    ];
+    for node in nodes {
+        let node = root_node.append_child(node).unwrap();
+        common::sub_test(
+            "test_clone_node_character_data",
+            &node.node_name().to_string(),
+        );
+        for deep in [false, true] {
+            let clone_node = node.clone_node(deep).unwrap();
+            assert_ne!(clone_node, node);
+            assert_eq!(clone_node.node_type(), node.node_type());
+            assert_eq!(clone_node.node_name(), node.node_name());
+            assert_eq!(clone_node.node_value(), node.node_value());
+            assert!(clone_node.parent_node().is_none());
+            assert_eq!(clone_node.owner_document(), Some(document_node.clone()));
+        }
+    }
+}
+
+#[test]
+fn test_clone_node_attribute() {
+    let document_node = get_implementation()
+        .create_document(Some("http://example.org/"), Some("root"), None)
+        .unwrap();
+    let ref_document = as_document(&document_node).unwrap();
+    let mut root_node = ref_document.document_element().unwrap();
+    as_element_mut(&mut root_node)
+        .unwrap()
+        .set_attribute("name", "value")
+        .unwrap();
+    let attribute_node = as_element(&root_node)
+        .unwrap()
+        .get_attribute_node("name")
+        .unwrap();
+
+    // the value of an attribute is copied even by a shallow clone.
+    let clone_node = attribute_node.clone_node(false).unwrap();
+    let attribute = as_attribute(&clone_node).unwrap();
+    assert_eq!(attribute.value(), Some("value".to_string()));
+    assert!(attribute.owner_element().is_none());
+    assert!(attribute.specified());
+    assert_eq!(clone_node.owner_document(), Some(document_node.clone()));
+}
+
+#[test]
+fn test_clone_node_fragment_and_reference() {
+    let document_node = get_implementation()
+        .create_document(Some("http://example.org/"), Some("root"), None)
+        .unwrap();
+    let ref_document = as_document(&document_node).unwrap();
+
+    let mut fragment_node = ref_document.create_document_fragment().unwrap();
+    for name in ["first", "second"] {
+        let _safe_to_ignore = fragment_node
+            .append_child(ref_document.create_element(name).unwrap())
+            .unwrap();
+    }
+    assert!(fragment_node
+        .clone_node(false)
+        .unwrap()
+        .child_nodes()
+        .is_empty());
+    let clone_node = fragment_node.clone_node(true).unwrap();
+    assert_eq!(clone_node.node_type(), NodeType::DocumentFragment);
+    compare_node_names(clone_node.child_nodes(), &["first", "second"]);
+    for child_node in clone_node.child_nodes() {
+        assert_eq!(child_node.parent_node(), Some(clone_node.clone()));
+    }
+
+    let reference_node = ref_document.create_entity_reference("name").unwrap();
+    let clone_node = reference_node.clone_node(true).unwrap();
+    assert_eq!(clone_node.node_type(), NodeType::EntityReference);
+    assert_eq!(clone_node.node_name().to_string(), "name");
+}
+
+#[test]
+fn test_clone_node_document_type() {
+    let document_node = get_implementation()
+        .create_document(Some("http://example.org/"), Some("root"), None)
+        .unwrap();
+
+    let entity_node =
+        ext_dom_impl::create_entity(document_node.clone(), "entity", None, Some("entity.xml"))
+            .unwrap();
+    let clone_node = entity_node.clone_node(false).unwrap();
+    assert_eq!(clone_node.node_type(), NodeType::Entity);
+    assert_eq!(
+        as_entity(&clone_node).unwrap().system_id(),
+        Some("entity.xml".to_string())
+    );
+
+    let notation_node =
+        ext_dom_impl::create_notation(document_node.clone(), "notation", Some("public"), None)
+            .unwrap();
+    let clone_node = notation_node.clone_node(false).unwrap();
+    assert_eq!(clone_node.node_type(), NodeType::Notation);
+    assert_eq!(
+        as_notation(&clone_node).unwrap().public_id(),
+        Some("public".to_string())
+    );
+
+    let document_type_node = get_implementation()
+        .create_document_type("root", Some("public"), Some("system.dtd"))
+        .unwrap();
+    let clone_node = document_type_node.clone_node(true).unwrap();
+    assert_ne!(clone_node, document_type_node);
+    let document_type = as_document_type(&clone_node).unwrap();
+    assert_eq!(document_type.public_id(), Some("public".to_string()));
+    assert_eq!(document_type.system_id(), Some("system.dtd".to_string()));
+}
+
+#[test]
+fn test_clone_node_document() {
+    let document_type_node = get_implementation()
+        .create_document_type("root", None, Some("system.dtd"))
+        .unwrap();
+    let document_node = get_implementation()
+        .create_document(
+            Some("http://example.org/"),
+            Some("root"),
+            Some(document_type_node.clone()),
+        )
+        .unwrap();
+    let ref_document = as_document(&document_node).unwrap();
+    let mut root_node = ref_document.document_element().unwrap();
+    let mut element_node = append_element_node(&mut root_node, "element");
+    as_element_mut(&mut element_node)
+        .unwrap()
+        .set_attribute("xml:id", "e1")
+        .unwrap();
+
+    common::sub_test("test_clone_node_document", "shallow");
+    let shallow_node = document_node.clone_node(false).unwrap();
+    let shallow = as_document(&shallow_node).unwrap();
+    assert!(shallow.document_element().is_none());
+    assert!(shallow.doc_type().is_none());
+
+    common::sub_test("test_clone_node_document", "deep");
+    let deep_node = document_node.clone_node(true).unwrap();
+    let deep = as_document(&deep_node).unwrap();
+    assert_eq!(deep_node.to_string(), document_node.to_string());
+    let deep_root_node = deep.document_element().unwrap();
+    assert_ne!(deep_root_node, root_node);
+    assert_eq!(deep_root_node.owner_document(), Some(deep_node.clone()));
+    assert_eq!(deep_root_node.parent_node(), Some(deep_node.clone()));
+
+    let deep_document_type_node = deep.doc_type().unwrap();
+    assert_ne!(deep_document_type_node, document_type_node);
+    assert_eq!(
+        deep_document_type_node.owner_document(),
+        Some(deep_node.clone())
+    );
+
+    let deep_element_node = deep.get_element_by_id("e1").unwrap();
+    assert_ne!(deep_element_node, element_node);
+    assert_eq!(
+        deep_element_node.parent_node(),
+        Some(deep_root_node.clone())
+    );
+    let attribute_node = as_element(&deep_element_node)
+        .unwrap()
+        .get_attribute_node("xml:id")
+        .unwrap();
+    assert_eq!(attribute_node.owner_document(), Some(deep_node.clone()));
+    assert_eq!(ref_document.get_element_by_id("e1"), Some(element_node));
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_clone_node_document_type_declarations() {
+    let document_node = xml_dom::parser::read_xml(
+        r#"<?xml version="1.0"?>
+<!DOCTYPE root [
+  <!ENTITY name "My Name">
+  <!NOTATION gif SYSTEM "image/gif">
+]>
+<root/>"#,
+    )
+    .unwrap();
+    let document_type_node = as_document(&document_node).unwrap().doc_type().unwrap();
+
+    let clone_node = document_type_node.clone_node(true).unwrap();
+    let document_type = as_document_type(&clone_node).unwrap();
+    assert_eq!(document_type.entities().len(), 1);
+    assert_eq!(document_type.notations().len(), 1);
+    let original = as_document_type(&document_type_node).unwrap();
+    for (name, entity_node) in document_type.entities() {
+        assert_ne!(Some(entity_node), original.entities().get(&name).cloned());
+    }
+
+    let clone_node = document_type_node.clone_node(false).unwrap();
+    assert!(as_document_type(&clone_node).unwrap().entities().is_empty());
+}
+
+#[test]
+fn test_append_children() {
+    let document_node = get_implementation()
+        .create_document(Some("http://example.org/"), Some("root"), None)
+        .unwrap();
+    let ref_document = as_document(&document_node).unwrap();
+    let mut root_node = ref_document.document_element().unwrap();
+    let existing_node = append_element_node(&mut root_node, "existing");
+
+    let mut fragment_node = ref_document.create_document_fragment().unwrap();
+    let _safe_to_ignore = fragment_node
+        .append_child(ref_document.create_element("in-fragment").unwrap())
+        .unwrap();
+    let new_children: Vec<RefNode> = (1..=3)
+        .map(|index| {
+            ref_document
+                .create_element(&format!("child-{}", index))
+                .unwrap()
+        })
+        .chain(vec![fragment_node.clone(), existing_node.clone()])
+        .collect();
+
+    assert!(root_node.append_children(new_children).is_ok());
+    compare_node_names(
+        root_node.child_nodes(),
+        &["child-1", "child-2", "child-3", "in-fragment", "existing"],
+    );
+    for child_node in root_node.child_nodes() {
+        assert_eq!(child_node.parent_node(), Some(root_node.clone()));
+    }
+    assert!(fragment_node.child_nodes().is_empty());
+}
+
+#[test]
+fn test_append_children_validated() {
+    let document_node = get_implementation()
+        .create_document(Some("http://example.org/"), Some("root"), None)
+        .unwrap();
+    let ref_document = as_document(&document_node).unwrap();
+    let mut root_node = ref_document.document_element().unwrap();
+    let mut child_node = append_element_node(&mut root_node, "child");
+
+    // nothing is appended if any node is invalid.
+    let new_node = ref_document.create_element("new").unwrap();
+    let result = child_node.append_children(vec![new_node.clone(), root_node.clone()]);
+    assert_eq!(result, Err(Error::HierarchyRequest));
+    assert!(new_node.parent_node().is_none());
+    assert!(child_node.child_nodes().is_empty());
+
+    let result = child_node.append_children(vec![new_node.clone(), new_node.clone()]);
+    assert_eq!(result, Err(Error::HierarchyRequest));
+
+    let attribute_node = ref_document.create_attribute("attribute").unwrap();
+    let result = child_node.append_children(vec![new_node.clone(), attribute_node]);
+    assert_eq!(result, Err(Error::HierarchyRequest));
+
+    let other_document_node = get_implementation()
+        .create_document(None, None, None)
+        .unwrap();
+    let other_node = as_document(&other_document_node)
+        .unwrap()
+        .create_element("other")
+        .unwrap();
+    let result = child_node.append_children(vec![new_node.clone(), other_node]);
+    assert_eq!(result, Err(Error::WrongDocument));
+
+    let mut document_node = document_node.clone();
+    let result = document_node.append_children(vec![new_node.clone()]);
+    assert_eq!(result, Err(Error::HierarchyRequest));
+    assert!(child_node.child_nodes().is_empty());
 }
 
 #[test]
@@ -397,6 +749,60 @@ fn test_normalize_empty() {
     }
 }
 
+#[test]
+fn test_normalize_cdata() {
+    let options = ProcessingOptions::new();
+    let (_document_node, mut root_node) = make_cdata_document(options);
+
+    root_node.normalize();
+
+    compare_node_names(
+        root_node.child_nodes(),
+        &[
+            "#text",
+            "#cdata-section",
+            "#cdata-section",
+            "#cdata-section",
+            "#cdata-section",
+        ],
+    );
+}
+
+#[test]
+fn test_normalize_merge_cdata() {
+    let mut options = ProcessingOptions::new();
+    options.set_merge_cdata_sections();
+    let (_document_node, mut root_node) = make_cdata_document(options);
+
+    root_node.normalize();
+
+    compare_node_names(root_node.child_nodes(), &["#text", "#cdata-section"]);
+    let last_node = root_node.last_child().unwrap();
+    assert_eq!(
+        last_node.node_value(),
+        Some("plain textx < y]]>more".to_string())
+    );
+}
+
+#[test]
+fn test_normalize_convert_cdata() {
+    let mut options = ProcessingOptions::new();
+    options.set_convert_cdata_to_text();
+    let (_document_node, mut root_node) = make_cdata_document(options);
+
+    root_node.normalize();
+
+    compare_node_names(
+        root_node.child_nodes(),
+        &["#text", "#cdata-section", "#text"],
+    );
+    let first_node = root_node.first_child().unwrap();
+    assert_eq!(
+        first_node.node_value(),
+        Some("text-1plain text".to_string())
+    );
+}
+
 #[test]
 fn test_wrong_document() {
     let document_1_node = get_implementation()
@@ -493,6 +899,183 @@ fn test_parent(document: RefNode, parent_type: NodeType, allowed: impl AsRef<[No
     }
 }
 
+#[test]
+fn test_node_list_accessors() {
+    let document_node = make_sibling_document();
+    let ref_document = as_document(&document_node).unwrap();
+    let root_node = ref_document.document_element().unwrap();
+
+    let children = root_node.child_nodes();
+    assert_eq!(children.length(), 5);
+    assert_eq!(children.item(0).unwrap().node_name().to_string(), "child-1");
+    assert_eq!(children.item(4).unwrap().node_name().to_string(), "child-5");
+    assert!(children.item(5).is_none());
+}
+
+#[test]
+fn test_text_content() {
+    let document_node = get_implementation()
+        .create_document(None, Some("p"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut bold_node = document.create_element("b").unwrap();
+    let _safe_to_ignore = bold_node
+        .append_child(document.create_cdata_section("bold").unwrap())
+        .unwrap();
+    root_node
+        .append_children(vec![
+            document.create_text_node("Some "),
+            bold_node.clone(),
+            document.create_comment("a comment"),
+            document
+                .create_processing_instruction("pi", Some("data"))
+                .unwrap(),
+            document.create_text_node(" text."),
+        ])
+        .unwrap();
+    root_node.set_attribute("title", "A title").unwrap();
+
+    assert_eq!(
+        root_node.text_content(),
+        Some("Some bold text.".to_string())
+    );
+    assert_eq!(bold_node.text_content(), Some("bold".to_string()));
+    let comment_node = root_node.child_nodes()[2].clone();
+    assert_eq!(comment_node.text_content(), Some("a comment".to_string()));
+    let attribute_node = root_node.get_attribute_node("title").unwrap();
+    assert_eq!(attribute_node.text_content(), Some("A title".to_string()));
+    assert_eq!(document_node.text_content(), None);
+
+    common::sub_test("test_text_content", "set_text_content");
+    let mut attribute_node = attribute_node;
+    attribute_node.set_text_content("New title").unwrap();
+    assert_eq!(
+        root_node.get_attribute("title"),
+        Some("New title".to_string())
+    );
+    let mut comment_node = comment_node;
+    comment_node.set_text_content("changed").unwrap();
+    assert_eq!(comment_node.node_value(), Some("changed".to_string()));
+
+    root_node.set_text_content("Plain text").unwrap();
+    assert_eq!(root_node.child_nodes().len(), 1);
+    assert_eq!(root_node.text_content(), Some("Plain text".to_string()));
+    root_node.set_text_content("").unwrap();
+    assert!(root_node.child_nodes().is_empty());
+
+    let mut document_node = document_node.clone();
+    document_node.set_text_content("ignored").unwrap();
+    assert_eq!(document_node.child_nodes().len(), 1);
+
+    let mut reference_node = document.create_entity_reference("amp").unwrap();
+    assert_eq!(
+        reference_node.set_text_content("&"),
+        Err(Error::NoModificationAllowed)
+    );
+}
+
+#[test]
+fn test_wrap_and_unwrap() {
+    let document_node = get_implementation()
+        .create_document(None, Some("p"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut first_node = append_text_node(&mut root_node, "one ");
+    let _ = append_element_node(&mut root_node, "br");
+    let _ = append_text_node(&mut root_node, " two");
+
+    let mut span_node = first_node
+        .wrap_with(document.create_element("span").unwrap())
+        .unwrap();
+    assert_eq!(
+        root_node.to_string(),
+        "<p><span>one </span><br></br> two</p>"
+    );
+    assert_eq!(first_node.parent_node(), Some(span_node.clone()));
+
+    common::sub_test("test_wrap_and_unwrap", "document_element");
+    let mut body_node = root_node
+        .wrap_with(document.create_element("body").unwrap())
+        .unwrap();
+    assert_eq!(document.document_element(), Some(body_node.clone()));
+    assert_eq!(body_node.unwrap_element(), Err(Error::HierarchyRequest));
+
+    common::sub_test("test_wrap_and_unwrap", "unwrap");
+    let children = root_node.unwrap_element().unwrap();
+    assert_eq!(children.len(), 3);
+    assert_eq!(root_node.parent_node(), None);
+    assert!(root_node.child_nodes().is_empty());
+    assert_eq!(
+        body_node.to_string(),
+        "<body><span>one </span><br></br> two</body>"
+    );
+    assert_eq!(
+        span_node.unwrap_element().unwrap(),
+        vec![first_node.clone()]
+    );
+    assert_eq!(body_node.to_string(), "<body>one <br></br> two</body>");
+
+    common::sub_test("test_wrap_and_unwrap", "errors");
+    let mut detached_node = document.create_element("detached").unwrap();
+    assert_eq!(
+        detached_node.wrap_with(document.create_element("span").unwrap()),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(detached_node.unwrap_element(), Err(Error::HierarchyRequest));
+    assert_eq!(
+        first_node.wrap_with(document.create_comment("not an element")),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(first_node.unwrap_element(), Err(Error::HierarchyRequest));
+    let other_node = get_implementation()
+        .create_document(None, Some("other"), None)
+        .unwrap();
+    assert_eq!(
+        first_node.wrap_with(
+            as_document(&other_node)
+                .unwrap()
+                .create_element("span")
+                .unwrap()
+        ),
+        Err(Error::WrongDocument)
+    );
+    assert_eq!(first_node.parent_node(), Some(body_node));
+}
+
+#[test]
+fn test_named_node_map_accessors() {
+    let document_node = common::create_example_rdf_document();
+    let ref_document = as_document(&document_node).unwrap();
+    let root_node = ref_document.document_element().unwrap();
+
+    let attributes = root_node.attributes();
+    assert_eq!(attributes.length(), 3);
+    for index in 0..attributes.length() {
+        assert!(attributes.item(index).is_some());
+    }
+    assert!(attributes.item(3).is_none());
+
+    let id_attribute = attributes.get_named_item("id").unwrap();
+    assert_eq!(
+        as_attribute(&id_attribute).unwrap().value(),
+        Some("main".to_string())
+    );
+    assert!(attributes.get_named_item("not-here").is_none());
+
+    let about_attribute = attributes
+        .get_named_item_ns(common::RDF_NS, "about")
+        .unwrap();
+    assert_eq!(
+        as_attribute(&about_attribute).unwrap().value(),
+        Some("http://media.example.com/audio/guide.ra".to_string())
+    );
+    assert!(attributes
+        .get_named_item_ns(common::DC_NS, "about")
+        .is_none());
+}
+
 fn append_element_node(parent_node: &mut RefNode, name: &str) -> RefNode {
     let mut_parent = as_element_mut(parent_node).unwrap();
 
@@ -517,6 +1100,20 @@ fn append_text_node(parent_node: &mut RefNode, content: &str) -> RefNode {
     new_text_node
 }
 
+fn make_cdata_document(options: ProcessingOptions) -> (RefNode, RefNode) {
+    let mut document_node = ext_dom_impl::get_implementation_ext()
+        .create_document_with_options(Some("http://example.org/"), Some("root"), None, options)
+        .unwrap();
+    let mut_document = as_document_mut(&mut document_node).unwrap();
+    let mut root_node = mut_document.document_element().unwrap();
+    let _safe_to_ignore = append_text_node(&mut root_node, "text-1");
+    for data in ["plain text", "x < y]]", ">", "more"] {
+        let new_node = mut_document.create_cdata_section(data).unwrap();
+        let _safe_to_ignore = root_node.append_child(new_node).unwrap();
+    }
+    (document_node, root_node)
+}
+
 fn make_sibling_document() -> RefNode {
     let document_node = get_implementation()
         .create_document(Some("http://example.org/"), Some("root"), None)
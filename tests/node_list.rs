@@ -0,0 +1,116 @@
+use xml_dom::level2::convert::{as_document, as_document_mut};
+use xml_dom::level2::ext::LiveCollections;
+use xml_dom::level2::*;
+
+pub mod common;
+
+fn names(list: &impl NodeList<NodeRef = RefNode>) -> Vec<String> {
+    (0..list.length())
+        .map(|index| list.item(index).unwrap().node_name().to_string())
+        .collect()
+}
+
+#[test]
+fn test_live_child_nodes() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let children = root_node.child_node_list();
+    let snapshot = root_node.child_nodes();
+    let first = root_node
+        .append_child(document.create_element("a").unwrap())
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_element("b").unwrap())
+        .unwrap();
+    assert_eq!(snapshot.length(), 0);
+    assert_eq!(names(&children), vec!["a", "b"]);
+
+    let _safe_to_ignore = root_node.remove_child(first).unwrap();
+    assert_eq!(names(&children), vec!["b"]);
+    assert!(children.item(1).is_none());
+    assert_eq!(children.snapshot(), root_node.child_nodes());
+}
+
+#[test]
+fn test_live_elements_by_tag_name() {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let items = document_node.elements_by_tag_name("item");
+    let all = document_node.elements_by_tag_name("*");
+
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut group_node = root_node
+        .append_child(document.create_element("group").unwrap())
+        .unwrap();
+    let _safe_to_ignore = group_node
+        .append_child(document.create_element("item").unwrap())
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_element("item").unwrap())
+        .unwrap();
+
+    assert_eq!(items.length(), 2);
+    assert_eq!(names(&all), vec!["root", "group", "item", "item"]);
+    assert_eq!(all.snapshot(), document.get_elements_by_tag_name("*"));
+    assert_eq!(
+        items.item(0),
+        Some(group_node.first_child().unwrap()),
+        "items are in document order"
+    );
+
+    // the list follows a replacement of the document element.
+    let new_root = document.create_element("item").unwrap();
+    let _safe_to_ignore = document_node.replace_child(new_root, root_node).unwrap();
+    assert_eq!(items.length(), 1);
+    assert_eq!(names(&all), vec!["item"]);
+
+    // an element's own list includes the element itself, as does `get_elements_by_tag_name`.
+    let group_items = group_node.elements_by_tag_name("group");
+    assert_eq!(group_items.length(), 1);
+}
+
+#[test]
+fn test_live_elements_by_tag_name_ns() {
+    let document_node = get_implementation()
+        .create_document(Some(common::DC_NS), Some("dc:root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let titles = root_node.elements_by_tag_name_ns(common::DC_NS, "title");
+    let any = root_node.elements_by_tag_name_ns("*", "title");
+
+    let _safe_to_ignore = root_node
+        .append_child(
+            document
+                .create_element_ns(common::DC_NS, "dc:title")
+                .unwrap(),
+        )
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(
+            document
+                .create_element_ns(common::RDF_NS, "rdf:title")
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(names(&titles), vec!["dc:title"]);
+    assert_eq!(names(&any), vec!["dc:title", "rdf:title"]);
+}
+
+#[test]
+fn test_live_list_dropped() {
+    let list = {
+        let document_node = get_implementation()
+            .create_document(None, Some("root"), None)
+            .unwrap();
+        document_node.elements_by_tag_name("root")
+    };
+    assert_eq!(list.length(), 0);
+    assert!(list.item(0).is_none());
+}
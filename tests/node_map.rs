@@ -0,0 +1,73 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::NodeMap;
+use xml_dom::level2::*;
+
+#[test]
+fn test_node_identity() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let first = root_node
+        .append_child(document.create_element("child").unwrap())
+        .unwrap();
+    let second = root_node
+        .append_child(document.create_element("child").unwrap())
+        .unwrap();
+
+    let mut map: NodeMap<usize> = NodeMap::new();
+    assert!(map.is_empty());
+    assert_eq!(map.insert(&first, 1), None);
+    assert_eq!(map.insert(&second, 2), None);
+    assert_eq!(map.len(), 2);
+
+    // equal names and values, but distinct nodes.
+    assert_eq!(map.get(&first), Some(&1));
+    assert_eq!(map.get(&second), Some(&2));
+    assert!(!map.contains_key(&root_node));
+
+    // a clone of the reference is the same node.
+    assert_eq!(map.insert(&first.clone(), 10), Some(1));
+    *map.get_mut(&second).unwrap() += 10;
+    assert_eq!(map.get(&second), Some(&12));
+
+    assert_eq!(map.remove(&first), Some(10));
+    assert!(!map.contains_key(&first));
+    assert_eq!(map.len(), 1);
+
+    let entries: Vec<(RefNode, &usize)> = map.iter().collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].0, second);
+}
+
+#[test]
+fn test_dropped_nodes() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+
+    let mut map: NodeMap<&str> = NodeMap::new();
+    let _ = map.insert(&root_node, "root");
+    {
+        let orphan = document.create_element("orphan").unwrap();
+        let _ = map.insert(&orphan, "orphan");
+        assert_eq!(map.len(), 2);
+    }
+
+    // the map does not keep the orphan alive.
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.iter().count(), 1);
+    assert_eq!(map.purge(), 1);
+    assert_eq!(map.purge(), 0);
+
+    // a new node never matches the dropped node's entry.
+    let replacement = document.create_element("orphan").unwrap();
+    assert!(!map.contains_key(&replacement));
+    assert_eq!(map.get(&root_node), Some(&"root"));
+
+    map.clear();
+    assert!(map.is_empty());
+}
@@ -0,0 +1,224 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::convert::{as_document, as_element, as_element_mut};
+use xml_dom::level2::ext::preserve::{preserving_to_string, LineEnding, PreserveOptions};
+use xml_dom::level2::*;
+use xml_dom::parser::{read_xml_with, ParseOptions};
+
+const XML: &str = r#"<?xml version = '1.0'?>
+<!-- a comment before the document type -->
+<!DOCTYPE catalog [ <!ENTITY  owner "me"> ]>
+<catalog   xmlns:x="urn:x" >
+  <book id='b1'  x:lang = "en"  >
+     <title>First &amp; <![CDATA[<best>]]></title>
+     <?sort by='title'?>
+  </book>
+  <book id="b2"/>
+	<empty></empty >
+</catalog>
+"#;
+
+fn parse(xml: &str, trim: bool) -> RefNode {
+    let mut options = ParseOptions::new();
+    options.set_track_spans();
+    if !trim {
+        options.set_preserve_whitespace();
+    }
+    read_xml_with(xml, options).unwrap().0
+}
+
+fn root(document_node: &RefNode) -> RefNode {
+    // the document element follows any comments.
+    document_node
+        .child_nodes()
+        .into_iter()
+        .find(|child_node| child_node.node_type() == NodeType::Element)
+        .unwrap()
+}
+
+fn elements(document_node: &RefNode, tag_name: &str) -> Vec<RefNode> {
+    let root_node = root(document_node);
+    let mut elements = as_element(&root_node)
+        .unwrap()
+        .get_elements_by_tag_name(tag_name);
+    if root_node.node_name().to_string() == tag_name {
+        elements.insert(0, root_node);
+    }
+    elements
+}
+
+#[test]
+fn test_unmodified_round_trip() {
+    for trim in [false, true] {
+        let document_node = parse(XML, trim);
+        assert_eq!(
+            preserving_to_string(&document_node, XML, &PreserveOptions::new()),
+            XML
+        );
+        let catalog_node = root(&document_node);
+        let catalog_xml = preserving_to_string(&catalog_node, XML, &PreserveOptions::new());
+        assert!(catalog_xml.starts_with("<catalog   xmlns:x=\"urn:x\" >\n  <book id='b1'"));
+        assert!(catalog_xml.ends_with("\t<empty></empty >\n</catalog>"));
+    }
+}
+
+#[test]
+fn test_without_spans() {
+    let document_node = xml_dom::parser::read_xml(XML).unwrap();
+    assert_eq!(
+        preserving_to_string(&document_node, XML, &PreserveOptions::new()),
+        document_node.to_string()
+    );
+}
+
+#[test]
+fn test_edit_attributes() {
+    for trim in [false, true] {
+        let document_node = parse(XML, trim);
+        let mut book_node = elements(&document_node, "book")[0].clone();
+        book_node.set_attribute("id", "b'1").unwrap();
+        book_node.remove_attribute("x:lang").unwrap();
+        book_node.set_attribute("year", "2024").unwrap();
+        let mut book_node = elements(&document_node, "book")[1].clone();
+        book_node.remove_attribute("id").unwrap();
+
+        let expected = XML
+            .replace(
+                r#"<book id='b1'  x:lang = "en"  >"#,
+                r#"<book id='b&#39;1' year="2024"  >"#,
+            )
+            .replace(r#"<book id="b2"/>"#, "<book/>");
+        assert_eq!(
+            preserving_to_string(&document_node, XML, &PreserveOptions::new()),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_edit_content() {
+    for trim in [false, true] {
+        let document_node = parse(XML, trim);
+        let book_nodes = elements(&document_node, "book");
+
+        // change the text of the title, the CDATA section is kept.
+        let title_node = elements(&document_node, "title").remove(0);
+        let mut text_node = title_node.first_child().unwrap();
+        // when trimmed the space before the CDATA section is kept from the input.
+        let value = if trim { "Second &" } else { "Second & " };
+        text_node.set_node_value(value).unwrap();
+
+        // give the empty element tag content, and remove the processing instruction.
+        let document = as_document(&document_node).unwrap();
+        let mut book_node = book_nodes[1].clone();
+        let _safe_to_ignore = book_node
+            .append_child(document.create_text_node("<new>"))
+            .unwrap();
+        let mut first_book_node = book_nodes[0].clone();
+        let pi_node = first_book_node
+            .child_nodes()
+            .into_iter()
+            .find(|child_node| child_node.node_type() == NodeType::ProcessingInstruction)
+            .unwrap();
+        let _safe_to_ignore = first_book_node.remove_child(pi_node).unwrap();
+
+        let output = preserving_to_string(&document_node, XML, &PreserveOptions::new());
+        assert!(output.contains("<title>Second &#38; <![CDATA[<best>]]></title>"));
+        assert!(output.contains(r#"<book id="b2">&#60;new&#62;</book>"#));
+        assert!(!output.contains("<?sort"));
+        assert!(output.starts_with("<?xml version = '1.0'?>\n<!-- a comment"));
+        assert!(output.ends_with("\t<empty></empty >\n</catalog>\n"));
+    }
+}
+
+#[test]
+fn test_renamed_element() {
+    let document_node = parse(XML, false);
+    let document = as_document(&document_node).unwrap();
+    let mut catalog_node = root(&document_node);
+    let mut empty_node = elements(&document_node, "empty").remove(0);
+    let mut full_node = document.create_element("full").unwrap();
+    let _safe_to_ignore = full_node
+        .append_child(document.create_comment(" moved "))
+        .unwrap();
+    let _safe_to_ignore = catalog_node
+        .replace_child(full_node, empty_node.clone())
+        .unwrap();
+    as_element_mut(&mut empty_node)
+        .unwrap()
+        .set_attribute("unused", "yes")
+        .unwrap();
+
+    assert_eq!(
+        preserving_to_string(&document_node, XML, &PreserveOptions::new()),
+        XML.replace("<empty></empty >", "<full><!-- moved --></full>")
+    );
+}
+
+#[test]
+fn test_indent_inserted() {
+    let xml = "<list>\n    <item>1</item>\n    <item>2</item>\n</list>";
+    for trim in [false, true] {
+        let document_node = parse(xml, trim);
+        let document = as_document(&document_node).unwrap();
+        let mut list_node = document.document_element().unwrap();
+        let items = elements(&document_node, "item");
+
+        let mut new_node = document.create_element("item").unwrap();
+        let _safe_to_ignore = new_node
+            .append_child(document.create_text_node("new"))
+            .unwrap();
+        let _safe_to_ignore = list_node
+            .insert_before(new_node, Some(items[1].clone()))
+            .unwrap();
+        let last_node = document.create_element("last").unwrap();
+        let _safe_to_ignore = list_node.append_child(last_node).unwrap();
+
+        let unindented = preserving_to_string(&document_node, xml, &PreserveOptions::new());
+        assert!(unindented.contains("<item>new</item>"));
+        assert!(unindented.contains("<last></last>"));
+
+        let mut options = PreserveOptions::new();
+        options.set_indent_inserted();
+        assert_eq!(
+            preserving_to_string(&document_node, xml, &options),
+            "<list>\n    <item>1</item>\n    <item>new</item>\n    <item>2</item>\n    <last></last>\n</list>"
+        );
+
+        // without whitespace text nodes, a removed element leaves its neighbours on their own
+        // lines.
+        if trim {
+            let _safe_to_ignore = list_node.remove_child(items[0].clone()).unwrap();
+            let output = preserving_to_string(&document_node, xml, &options);
+            assert!(output.starts_with("<list>\n    <item>new</item>\n    <item>2</item>"));
+        }
+    }
+}
+
+#[test]
+fn test_line_endings() {
+    let xml = "<notes>\r\n  <note>a\r\nb</note>\r\n</notes>\r\n";
+    let document_node = parse(xml, false);
+    let document = as_document(&document_node).unwrap();
+    let mut notes_node = document.document_element().unwrap();
+    let mut note_node = elements(&document_node, "note").remove(0);
+    let mut text_node = note_node.first_child().unwrap();
+    text_node.set_node_value("c\nd").unwrap();
+    note_node.set_attribute("n", "1").unwrap();
+
+    assert_eq!(
+        preserving_to_string(&document_node, xml, &PreserveOptions::new()),
+        "<notes>\r\n  <note n=\"1\">c\r\nd</note>\r\n</notes>\r\n"
+    );
+
+    let mut options = PreserveOptions::new();
+    options.set_line_ending(LineEnding::Lf);
+    options.set_indent_inserted();
+    let _safe_to_ignore = notes_node
+        .append_child(document.create_comment("x\r\ny"))
+        .unwrap();
+    assert_eq!(
+        preserving_to_string(&document_node, xml, &options),
+        "<notes>\r\n  <note n=\"1\">c\nd</note>\r\n  <!--x\ny-->\n</notes>\r\n"
+    );
+}
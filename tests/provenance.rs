@@ -0,0 +1,115 @@
+use xml_dom::level2::convert::as_document_mut;
+use xml_dom::level2::ext::dom_impl::get_implementation_ext;
+use xml_dom::level2::ext::{NodeProvenance, Operation, ProcessingOptions};
+use xml_dom::level2::*;
+
+pub mod common;
+
+fn tracking_options() -> ProcessingOptions {
+    let mut options = ProcessingOptions::new();
+    options.set_track_provenance();
+    options
+}
+
+#[test]
+fn test_clone_provenance() {
+    let document_node = common::create_example_rdf_document_options(tracking_options());
+    let root_node = document_node.document_element().unwrap();
+    let description_node = root_node.first_child().unwrap();
+    let title_node = description_node.child_nodes()[1].clone();
+
+    let copy_node = description_node.clone_node(true).unwrap();
+    let provenance = copy_node.provenance().unwrap();
+    assert_eq!(provenance.operation(), &Operation::Clone);
+    assert_eq!(provenance.source(), Some(description_node.clone()));
+    assert_eq!(provenance.source_path(), "/rdf:RDF/rdf:Description[1]");
+    assert_eq!(
+        provenance.to_string(),
+        format!(
+            "clone of /rdf:RDF/rdf:Description[1] (id {})",
+            provenance.source_id()
+        )
+    );
+
+    // descendants record their own sources, text is not recorded.
+    let copy_title_node = copy_node.child_nodes()[1].clone();
+    assert_eq!(
+        copy_title_node.provenance().unwrap().source(),
+        Some(title_node.clone())
+    );
+    assert!(copy_title_node
+        .first_child()
+        .unwrap()
+        .provenance()
+        .is_none());
+
+    // a copy of a copy has a chain back to the original.
+    let second_copy_node = copy_node.clone_node(false).unwrap();
+    let chain = second_copy_node.provenance_chain();
+    assert_eq!(chain.len(), 2);
+    assert_eq!(chain[0].source(), Some(copy_node.clone()));
+    assert_eq!(chain[1].source(), Some(description_node.clone()));
+
+    // the path is kept once the source has been dropped.
+    drop(copy_node);
+    let provenance = second_copy_node.provenance().unwrap();
+    assert!(provenance.source().is_none());
+    assert_eq!(provenance.source_path(), "/rdf:Description");
+}
+
+#[test]
+fn test_import_provenance() {
+    let source_node = common::create_example_rdf_document();
+    let source_root_node = source_node.document_element().unwrap();
+
+    let mut document_node = get_implementation()
+        .create_document(None, Some("copy"), None)
+        .unwrap();
+    let imported_node = as_document_mut(&mut document_node)
+        .unwrap()
+        .import_node(source_root_node.clone(), false)
+        .unwrap();
+    assert!(imported_node.provenance().is_none());
+
+    let mut document_node = get_implementation_ext()
+        .create_document_with_options(None, Some("copy"), None, tracking_options())
+        .unwrap();
+    let imported_node = as_document_mut(&mut document_node)
+        .unwrap()
+        .import_node(source_root_node.clone(), false)
+        .unwrap();
+    let provenance = imported_node.provenance().unwrap();
+    assert_eq!(provenance.operation(), &Operation::Import);
+    assert_eq!(provenance.source(), Some(source_root_node));
+}
+
+#[test]
+fn test_record_provenance() {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let root_node = document_node.document_element().unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut summary_node = document.create_element("summary").unwrap();
+    assert!(summary_node
+        .clone_node(false)
+        .unwrap()
+        .provenance()
+        .is_none());
+
+    summary_node
+        .record_provenance(&root_node, Operation::Transform("summarize".to_string()))
+        .unwrap();
+    let provenance = summary_node.provenance().unwrap();
+    assert_eq!(
+        provenance.operation(),
+        &Operation::Transform("summarize".to_string())
+    );
+    assert_eq!(provenance.source_path(), "/root");
+
+    let mut text_node = document.create_text_node("text");
+    assert_eq!(
+        text_node.record_provenance(&root_node, Operation::Clone),
+        Err(Error::InvalidState)
+    );
+}
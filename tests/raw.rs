@@ -0,0 +1,136 @@
+#![cfg(feature = "internals")]
+
+use xml_dom::level2::convert::{as_document, as_document_mut};
+use xml_dom::level2::ext::raw::{inspect, inspect_tree, RawExtension, WeakRefState};
+use xml_dom::level2::ext::writer::node_id;
+use xml_dom::level2::ext::IdAttributes;
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_inspect() {
+    let mut document_node = get_implementation()
+        .create_document(Some(common::DC_NS), Some("dc:root"), None)
+        .unwrap();
+    document_node.register_id_attribute(None, "key").unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("key", "r").unwrap();
+    root_node
+        .set_attribute_ns(common::XMLNS_NS, "xmlns:dc", common::DC_NS)
+        .unwrap();
+    let text_node = root_node
+        .append_child(document.create_text_node("text"))
+        .unwrap();
+
+    let raw = inspect(&document_node);
+    assert_eq!(raw.id, node_id(&document_node));
+    assert_eq!(raw.node_type, NodeType::Document);
+    assert_eq!(raw.parent_node, WeakRefState::Unset);
+    assert_eq!(raw.child_count, 1);
+    match raw.extension {
+        RawExtension::Document {
+            has_document_type,
+            id_map_entries,
+            dropped_id_map_entries,
+            id_attributes,
+            ..
+        } => {
+            assert!(!has_document_type);
+            assert_eq!(id_map_entries, 1);
+            assert_eq!(dropped_id_map_entries, 0);
+            assert_eq!(id_attributes, vec![(None, "key".to_string())]);
+        }
+        extension => panic!("unexpected extension {:?}", extension),
+    }
+
+    let raw = inspect(&root_node);
+    assert_eq!(raw.name, "dc:root");
+    assert_eq!(raw.parent_node, WeakRefState::Live(node_id(&document_node)));
+    assert_eq!(
+        raw.owner_document,
+        WeakRefState::Live(node_id(&document_node))
+    );
+    assert_eq!(raw.frozen, 0);
+    assert!(raw.position.is_none());
+    // held by the document, and by `root_node`.
+    assert_eq!(raw.strong_count, 2);
+    match raw.extension {
+        RawExtension::Element {
+            attribute_count,
+            namespaces,
+            ..
+        } => {
+            assert_eq!(attribute_count, 2);
+            assert_eq!(
+                namespaces,
+                vec![(Some("dc".to_string()), common::DC_NS.to_string())]
+            );
+        }
+        extension => panic!("unexpected extension {:?}", extension),
+    }
+
+    let attribute_node = root_node.get_attribute_node("key").unwrap();
+    let raw = inspect(&attribute_node);
+    assert!(matches!(
+        raw.extension,
+        RawExtension::Attribute {
+            specified: true,
+            owner_element: WeakRefState::Live(id),
+            ..
+        } if id == node_id(&root_node)
+    ));
+
+    let raw = inspect(&text_node);
+    assert_eq!(raw.value, Some("text".to_string()));
+    assert!(matches!(raw.extension, RawExtension::None));
+}
+
+#[test]
+fn test_inspect_dropped() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let element_node = {
+        let document = as_document(&document_node).unwrap();
+        document.create_element("orphan").unwrap()
+    };
+    drop(document_node);
+    assert_eq!(inspect(&element_node).owner_document, WeakRefState::Dropped);
+}
+
+#[test]
+fn test_inspect_tree() {
+    let document_type = get_implementation()
+        .create_document_type("root", None, Some("root.dtd"))
+        .unwrap();
+    let mut document_node = get_implementation()
+        .create_document(None, Some("root"), Some(document_type))
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("b", "2").unwrap();
+    root_node.set_attribute("a", "1").unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_comment("note"))
+        .unwrap();
+
+    let names: Vec<String> = inspect_tree(&document_node)
+        .into_iter()
+        .map(|raw| raw.name)
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "#document",
+            "root",
+            "root",
+            "a",
+            "#text",
+            "b",
+            "#text",
+            "#comment"
+        ]
+    );
+}
@@ -0,0 +1,170 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::freeze::{freeze, thaw};
+use xml_dom::level2::ext::redact::{redact, Redaction, RedactionRule};
+use xml_dom::level2::*;
+
+const CFG_NS: &str = "urn:example:config";
+
+fn create_config_document() -> RefNode {
+    let document_node = get_implementation()
+        .create_document(None, Some("config"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node
+        .set_attribute_ns("http://www.w3.org/2000/xmlns/", "xmlns:cfg", CFG_NS)
+        .unwrap();
+
+    let mut database_node = root_node
+        .append_child(document.create_element("database").unwrap())
+        .unwrap();
+    database_node.set_attribute("user", "admin").unwrap();
+    database_node
+        .set_attribute_ns(CFG_NS, "cfg:password", "hunter2")
+        .unwrap();
+
+    let mut secret_node = root_node
+        .append_child(document.create_element("secret").unwrap())
+        .unwrap();
+    secret_node.set_attribute("kind", "api-key").unwrap();
+    let mut inner_node = secret_node
+        .append_child(document.create_element("secret").unwrap())
+        .unwrap();
+    let _ = inner_node
+        .append_child(document.create_text_node("0123456789abcdef"))
+        .unwrap();
+
+    let _ = root_node
+        .append_child(document.create_element("empty").unwrap())
+        .unwrap();
+    document_node
+}
+
+#[test]
+fn test_redact_attributes_by_local_name() {
+    let mut document_node = create_config_document();
+    let report = redact(&mut document_node, &[RedactionRule::attribute("password")]).unwrap();
+
+    assert_eq!(
+        report.redactions(),
+        &[Redaction {
+            path: "/config/database[1]".to_string(),
+            attribute: Some("cfg:password".to_string()),
+            rule: 0,
+        }]
+    );
+    let database_node = document_node
+        .document_element()
+        .unwrap()
+        .first_child()
+        .unwrap();
+    assert_eq!(
+        database_node.get_attribute("cfg:password"),
+        Some("[REDACTED]".to_string())
+    );
+    assert_eq!(
+        database_node.get_attribute("user"),
+        Some("admin".to_string())
+    );
+}
+
+#[test]
+fn test_redact_elements() {
+    let mut document_node = create_config_document();
+    let report = redact(
+        &mut document_node,
+        &[
+            RedactionRule::element("secret").with_placeholder("***"),
+            RedactionRule::element("empty"),
+        ],
+    )
+    .unwrap();
+
+    // the nested secret is removed with the content of its parent, and the empty element has
+    // nothing to redact.
+    assert_eq!(report.to_string(), "/config/secret[1]: redacted by rule 0");
+    let secret_node = document_node.document_element().unwrap().child_nodes()[1].clone();
+    assert_eq!(
+        secret_node.to_string(),
+        r#"<secret kind="api-key">***</secret>"#
+    );
+}
+
+#[test]
+fn test_redact_first_rule_wins() {
+    let mut document_node = create_config_document();
+    let report = redact(
+        &mut document_node,
+        &[
+            RedactionRule::element("database")
+                .with_attribute("user")
+                .with_placeholder("nobody"),
+            RedactionRule::attribute("user"),
+            RedactionRule::attribute("kind"),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(
+        report.to_string(),
+        "/config/database[1]/@user: redacted by rule 0\n/config/secret[1]/@kind: redacted by rule 2"
+    );
+    let database_node = document_node
+        .document_element()
+        .unwrap()
+        .first_child()
+        .unwrap();
+    assert_eq!(
+        database_node.get_attribute("user"),
+        Some("nobody".to_string())
+    );
+}
+
+#[test]
+fn test_redact_nothing() {
+    let mut document_node = create_config_document();
+    let before = document_node.to_string();
+    let report = redact(&mut document_node, &[RedactionRule::attribute("token")]).unwrap();
+    assert!(report.is_empty());
+    assert_eq!(document_node.to_string(), before);
+}
+
+#[test]
+fn test_redact_frozen() {
+    let mut document_node = create_config_document();
+    let mut database_node = document_node
+        .document_element()
+        .unwrap()
+        .first_child()
+        .unwrap();
+
+    let token = freeze(&mut database_node);
+    assert_eq!(
+        redact(&mut document_node, &[RedactionRule::attribute("password")]),
+        Err(Error::NoModificationAllowed)
+    );
+    thaw(token);
+    assert_eq!(
+        redact(&mut document_node, &[RedactionRule::attribute("password")])
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[cfg(feature = "selectors")]
+#[test]
+fn test_redact_selector() {
+    use xml_dom::level2::ext::selectors::Selector;
+
+    let mut document_node = create_config_document();
+    let report = redact(
+        &mut document_node,
+        &[RedactionRule::selector(
+            Selector::new("config > secret[kind]").unwrap(),
+        )],
+    )
+    .unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report.redactions()[0].path, "/config/secret[1]");
+}
@@ -0,0 +1,168 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::dtd::AttributeType;
+use xml_dom::level2::ext::schema::TypeProvider;
+use xml_dom::level2::ext::sync::Shared;
+use xml_dom::level2::ext::validate::{check_document, Violation, ViolationKind};
+use xml_dom::level2::ext::{NormalizationOptions, NormalizeDocument, SchemaTypes};
+use xml_dom::level2::*;
+
+const XMLNS: &str = "http://www.w3.org/2000/xmlns/";
+const XSI: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+#[derive(Debug)]
+struct Provider;
+
+impl TypeProvider for Provider {
+    fn attribute_type(&self, element_name: &Name, attribute_name: &Name) -> Option<AttributeType> {
+        match (
+            element_name.local_name().as_str(),
+            attribute_name.local_name().as_str(),
+        ) {
+            (_, "key") => Some(AttributeType::Id),
+            ("link", "to") => Some(AttributeType::IdRefs),
+            ("link", "kind") => Some(AttributeType::NmToken),
+            _ => None,
+        }
+    }
+
+    fn is_nillable(&self, element_name: &Name) -> bool {
+        element_name.local_name() == "optional"
+    }
+}
+
+fn create_document() -> RefNode {
+    get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap()
+}
+
+#[test]
+fn test_type_provider_ids() {
+    let mut document_node = create_document();
+    let document_ref = document_node.clone();
+    let document = as_document(&document_ref).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut first_node = root_node
+        .append_child(document.create_element("item").unwrap())
+        .unwrap();
+    first_node.set_attribute("key", "one").unwrap();
+    assert!(document_node.type_provider().is_none());
+    assert!(document.get_element_by_id("one").is_none());
+
+    document_node
+        .set_type_provider(Some(Shared::new(Provider)))
+        .unwrap();
+    assert!(document_node.type_provider().is_some());
+    assert_eq!(document.get_element_by_id("one"), Some(first_node.clone()));
+
+    // attributes set later are typed as they are added.
+    let mut second_node = root_node
+        .append_child(document.create_element("item").unwrap())
+        .unwrap();
+    second_node.set_attribute("key", "two").unwrap();
+    assert_eq!(document.get_element_by_id("two"), Some(second_node.clone()));
+    assert!(second_node.set_attribute("key", "one").is_err());
+
+    // removing the provider removes the IDs it typed.
+    document_node.set_type_provider(None).unwrap();
+    assert!(document.get_element_by_id("one").is_none());
+    second_node.set_attribute("key", "one").unwrap();
+    assert!(document_node
+        .set_type_provider(Some(Shared::new(Provider)))
+        .is_err());
+    assert!(document_node.type_provider().is_none());
+
+    let mut element = document.create_element("item").unwrap();
+    assert!(element
+        .set_type_provider(Some(Shared::new(Provider)))
+        .is_err());
+}
+
+#[test]
+fn test_datatype_normalization() {
+    let mut document_node = create_document();
+    let document_ref = document_node.clone();
+    let document = as_document(&document_ref).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute_ns(XMLNS, "xmlns:xsi", XSI).unwrap();
+    let mut link_node = root_node
+        .append_child(document.create_element("link").unwrap())
+        .unwrap();
+    link_node.set_attribute("to", "a  b").unwrap();
+    link_node.set_attribute("kind", "x  y").unwrap();
+    link_node.set_attribute("label", "y  z").unwrap();
+    let mut optional_node = root_node
+        .append_child(document.create_element("optional").unwrap())
+        .unwrap();
+    optional_node.set_attribute("xsi:nil", "true").unwrap();
+    let _safe_to_ignore = optional_node
+        .append_child(document.create_text_node("  "))
+        .unwrap();
+
+    let mut options = NormalizationOptions::new();
+    options.set_datatype_normalization();
+    document_node.normalize_document(&options).unwrap();
+    assert_eq!(link_node.get_attribute("to"), Some("a  b".to_string()));
+    assert!(optional_node.has_child_nodes());
+
+    document_node
+        .set_type_provider(Some(Shared::new(Provider)))
+        .unwrap();
+    document_node.normalize_document(&options).unwrap();
+    assert_eq!(link_node.get_attribute("to"), Some("a b".to_string()));
+    assert_eq!(link_node.get_attribute("kind"), Some("x y".to_string()));
+    assert_eq!(link_node.get_attribute("label"), Some("y  z".to_string()));
+    assert!(!optional_node.has_child_nodes());
+}
+
+#[test]
+fn test_check_document_types() {
+    let mut document_node = create_document();
+    let document_ref = document_node.clone();
+    let document = as_document(&document_ref).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute_ns(XMLNS, "xmlns:xsi", XSI).unwrap();
+    root_node.set_attribute("key", "r").unwrap();
+    let mut link_node = root_node
+        .append_child(document.create_element("link").unwrap())
+        .unwrap();
+    link_node.set_attribute("to", "r missing").unwrap();
+    let mut item_node = root_node
+        .append_child(document.create_element("item").unwrap())
+        .unwrap();
+    item_node.set_attribute_ns(XSI, "xsi:nil", "true").unwrap();
+    let mut optional_node = root_node
+        .append_child(document.create_element("optional").unwrap())
+        .unwrap();
+    optional_node.set_attribute("xsi:nil", "1").unwrap();
+    let _safe_to_ignore = optional_node
+        .append_child(document.create_text_node("text"))
+        .unwrap();
+    let mut empty_node = root_node
+        .append_child(document.create_element("optional").unwrap())
+        .unwrap();
+    empty_node.set_attribute("xsi:nil", "true").unwrap();
+    assert!(check_document(&document_node).is_empty());
+
+    document_node
+        .set_type_provider(Some(Shared::new(Provider)))
+        .unwrap();
+    let to_node = link_node.get_attribute_node("to").unwrap();
+    assert_eq!(
+        check_document(&document_node),
+        vec![
+            Violation {
+                node: item_node,
+                kind: ViolationKind::NotNillable,
+            },
+            Violation {
+                node: optional_node,
+                kind: ViolationKind::NilWithContent,
+            },
+            Violation {
+                node: to_node,
+                kind: ViolationKind::DanglingIdRef("missing".to_string()),
+            },
+        ]
+    );
+}
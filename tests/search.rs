@@ -0,0 +1,65 @@
+#![cfg(feature = "quick_parser")]
+
+use regex::Regex;
+use xml_dom::level2::convert::{as_attribute, as_document, as_element};
+use xml_dom::level2::ext::Search;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+pub mod common;
+
+const XML: &str = r#"<people>
+  <person email="ann@example.com" phone="555-0100">Ann, 555-0101</person>
+  <person email="bob@example.org"><![CDATA[Bob <555-0102>]]><!-- 555-0103 --></person>
+</people>"#;
+
+#[test]
+fn test_find_text() {
+    let document_node = read_xml(XML).unwrap();
+    let phone = Regex::new(r"\d{3}-\d{4}").unwrap();
+
+    let matches = document_node.find_text(&phone);
+    let texts: Vec<&str> = matches.iter().map(|m| m.text.as_str()).collect();
+    assert_eq!(texts, vec!["555-0101", "555-0102"]);
+    assert_eq!(matches[0].node.node_type(), NodeType::Text);
+    assert_eq!((matches[0].start, matches[0].end), (5, 13));
+    assert_eq!(matches[1].node.node_type(), NodeType::CData);
+    assert_eq!((matches[1].start, matches[1].end), (5, 13));
+
+    common::sub_test("test_find_text", "subtree");
+    let document = as_document(&document_node).unwrap();
+    let bob = document.get_elements_by_tag_name("person").remove(1);
+    assert_eq!(bob.find_text(&phone).len(), 1);
+    assert!(bob.find_text(&Regex::new("Ann").unwrap()).is_empty());
+}
+
+#[test]
+fn test_find_attribute_values() {
+    let document_node = read_xml(XML).unwrap();
+
+    let matches = document_node.find_attribute_values(&Regex::new(r"@[\w.]+").unwrap());
+    let texts: Vec<&str> = matches.iter().map(|m| m.text.as_str()).collect();
+    assert_eq!(texts, vec!["@example.com", "@example.org"]);
+    assert_eq!(matches[0].node.node_type(), NodeType::Attribute);
+    assert_eq!(matches[0].node.node_name().to_string(), "email");
+    assert_eq!((matches[0].start, matches[0].end), (3, 15));
+
+    common::sub_test("test_find_attribute_values", "sorted_by_name");
+    let matches = document_node.find_attribute_values(&Regex::new(r"\d|@").unwrap());
+    let names: Vec<String> = matches
+        .iter()
+        .take(2)
+        .map(|m| m.node.node_name().to_string())
+        .collect();
+    assert_eq!(names, vec!["email", "phone"]);
+
+    common::sub_test("test_find_attribute_values", "owner_element");
+    let owner_element = as_attribute(&matches[0].node)
+        .unwrap()
+        .owner_element()
+        .unwrap();
+    assert_eq!(
+        as_element(&owner_element).unwrap().get_attribute("phone"),
+        Some("555-0100".to_string())
+    );
+}
@@ -0,0 +1,90 @@
+#![cfg(all(feature = "selectors", feature = "quick_parser"))]
+
+use xml_dom::level2::ext::selectors::Selector;
+use xml_dom::level2::ext::QuerySelector;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+const CATALOG: &str = r#"<catalog xmlns:x="urn:x">
+  <section id="s1" class="main wide">
+    <item id="i1" class="new" lang="en-GB" title="first item"/>
+    <group>
+      <item id="i2" lang="en" title="second"/>
+      <x:item id="i3" class="new old"/>
+    </group>
+  </section>
+  <section id="s2" role="new">
+    <item id="i4" lang="fr" title="fourth item"/>
+  </section>
+</catalog>"#;
+
+fn ids(nodes: &[RefNode]) -> Vec<String> {
+    nodes
+        .iter()
+        .map(|node| node.get_attribute("id").unwrap_or_default())
+        .collect()
+}
+
+#[test]
+fn test_query_selector_all() {
+    let document_node = read_xml(CATALOG).unwrap();
+    let query = |selectors: &str| ids(&document_node.query_selector_all(selectors).unwrap());
+
+    assert_eq!(query("item"), vec!["i1", "i2", "i4"]);
+    assert_eq!(query("x\\:item"), vec!["i3"]);
+    assert_eq!(query("#i2"), vec!["i2"]);
+    assert_eq!(query(".new"), vec!["i1", "i3"]);
+    assert_eq!(query("section.main.wide > *"), vec!["i1", ""]);
+    assert_eq!(query("section item"), vec!["i1", "i2", "i4"]);
+    assert_eq!(query("section > item"), vec!["i1", "i4"]);
+    assert_eq!(query("catalog > section group > *"), vec!["i2", "i3"]);
+    assert_eq!(query("[lang]"), vec!["i1", "i2", "i4"]);
+    assert_eq!(query("[lang=en]"), vec!["i2"]);
+    assert_eq!(query("[lang|=en]"), vec!["i1", "i2"]);
+    assert_eq!(query("[title~=item]"), vec!["i1", "i4"]);
+    assert_eq!(query("[title^='fir']"), vec!["i1"]);
+    assert_eq!(query("[title$=\"item\"]"), vec!["i1", "i4"]);
+    assert_eq!(query("[title*=con]"), vec!["i2"]);
+    assert_eq!(query("[title^='']"), Vec::<String>::new());
+    assert_eq!(query("#i4, #i1, .old"), vec!["i1", "i3", "i4"]);
+    assert!(query("catalog > item").is_empty());
+}
+
+#[test]
+fn test_query_selector_scope() {
+    let document_node = read_xml(CATALOG).unwrap();
+    let section_node = document_node.query_selector("#s2").unwrap().unwrap();
+
+    // ancestors outside the scope still match, but the scope itself is not selected.
+    assert_eq!(
+        ids(&section_node.query_selector_all("catalog item").unwrap()),
+        vec!["i4"]
+    );
+    assert!(section_node.query_selector("section").unwrap().is_none());
+    assert_eq!(
+        section_node
+            .query_selector("*")
+            .unwrap()
+            .and_then(|node| node.get_attribute("id")),
+        Some("i4".to_string())
+    );
+
+    assert_eq!(document_node.query_selector("item >"), Err(Error::Syntax));
+    assert_eq!(document_node.query_selector_all(""), Err(Error::Syntax));
+}
+
+#[test]
+fn test_selector_class_attribute() {
+    let document_node = read_xml(CATALOG).unwrap();
+
+    let mut selector = Selector::new(".new").unwrap();
+    assert_eq!(selector.class_attribute(), "class");
+    selector.set_class_attribute("role");
+    assert_eq!(selector.class_attribute(), "role");
+    assert_eq!(ids(&selector.select_all(&document_node)), vec!["s2"]);
+
+    let item_node = document_node.query_selector("#i1").unwrap().unwrap();
+    assert!(Selector::new("section > .new").unwrap().matches(&item_node));
+    assert!(!selector.matches(&item_node));
+    assert!(!Selector::new("*").unwrap().matches(&document_node));
+}
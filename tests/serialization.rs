@@ -0,0 +1,130 @@
+#![cfg(feature = "serde")]
+
+use serde::de::DeserializeSeed;
+use xml_dom::level2::convert::{as_document, as_document_type};
+use xml_dom::level2::ext::serialization::NodeSeed;
+use xml_dom::level2::ext::IdAttributes;
+use xml_dom::level2::*;
+
+pub mod common;
+
+#[test]
+fn test_document_round_trip() {
+    let mut document_node = common::create_example_rdf_document();
+    document_node.register_id_attribute(None, "key").unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("key", "root").unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_cdata_section("<raw>").unwrap())
+        .unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document.create_comment("note"))
+        .unwrap();
+
+    let bytes = bincode::serialize(&document_node).unwrap();
+    let copy_node: RefNode = bincode::deserialize(&bytes).unwrap();
+    let copy = as_document(&copy_node).unwrap();
+    let copy_root_node = copy.document_element().unwrap();
+    assert_eq!(
+        copy_root_node.attributes().len(),
+        root_node.attributes().len()
+    );
+    assert_eq!(
+        copy_root_node.get_attribute("key"),
+        Some("root".to_string())
+    );
+    assert_eq!(
+        copy_root_node.child_nodes()[0].to_string(),
+        root_node.child_nodes()[0].to_string()
+    );
+    assert_eq!(
+        copy_root_node.child_nodes()[1].to_string(),
+        "<![CDATA[ <raw> ]]>"
+    );
+    assert!(copy_node.is_registered_id_attribute(None, "key"));
+
+    common::sub_test("test_document_round_trip", "ids");
+    let title_node = copy.get_element_by_id("title").unwrap();
+    assert_eq!(title_node.owner_document(), Some(copy_node.clone()));
+    assert!(title_node.parent_node().is_some());
+    assert_eq!(copy.get_element_by_id("root"), Some(copy_root_node));
+
+    common::sub_test("test_document_round_trip", "deterministic");
+    assert_eq!(bincode::serialize(&copy_node).unwrap(), bytes);
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_parsed_round_trip() {
+    use xml_dom::parser::read_xml;
+
+    let xml = r#"<?xml version="1.1" encoding="UTF-8"?><!DOCTYPE note SYSTEM "note.dtd" [<!ENTITY copy "(c)">]><?style sheet?><note xmlns:ex="urn:example"><ex:item ex:kind="memo"/>Text &amp; more<![CDATA[<raw>]]><!--comment--></note>"#;
+    let document_node = read_xml(xml).unwrap();
+    let json = serde_json::to_string(&document_node).unwrap();
+    let copy_node: RefNode = serde_json::from_str(&json).unwrap();
+    assert_eq!(copy_node.to_string(), document_node.to_string());
+
+    let copy = as_document(&copy_node).unwrap();
+    let doc_type_node = copy.doc_type().unwrap();
+    let doc_type = as_document_type(&doc_type_node).unwrap();
+    assert_eq!(doc_type.system_id(), Some("note.dtd".to_string()));
+    assert_eq!(doc_type.entities().len(), 1);
+}
+
+#[test]
+fn test_node_seed() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.create_element("item").unwrap();
+    element_node.set_attribute("xml:id", "one").unwrap();
+    let json = serde_json::to_string(&element_node).unwrap();
+
+    let other_node = get_implementation()
+        .create_document(None, Some("other"), None)
+        .unwrap();
+    let other = as_document(&other_node).unwrap();
+    let mut deserializer = serde_json::Deserializer::from_str(&json);
+    let copy_node = NodeSeed::new(&other_node)
+        .deserialize(&mut deserializer)
+        .unwrap();
+    assert_eq!(copy_node.owner_document(), Some(other_node.clone()));
+    assert!(other.get_element_by_id("one").is_none());
+
+    let mut root_node = other.document_element().unwrap();
+    let _safe_to_ignore = root_node.append_child(copy_node.clone()).unwrap();
+    assert_eq!(other.get_element_by_id("one"), Some(copy_node));
+
+    common::sub_test("test_node_seed", "document");
+    let json = serde_json::to_string(&document_node).unwrap();
+    let mut deserializer = serde_json::Deserializer::from_str(&json);
+    assert!(NodeSeed::new(&other_node)
+        .deserialize(&mut deserializer)
+        .is_err());
+}
+
+#[test]
+fn test_invalid_data() {
+    let document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let element_node = document.create_element("item").unwrap();
+
+    // only documents deserialize without a seed.
+    let json = serde_json::to_string(&element_node).unwrap();
+    assert!(serde_json::from_str::<RefNode>(&json).is_err());
+
+    // names are checked.
+    let json = serde_json::to_string(&document_node).unwrap();
+    assert!(serde_json::from_str::<RefNode>(&json).is_ok());
+    let bad_json = json.replace(r#""name":"root""#, r#""name":"1root""#);
+    assert_ne!(bad_json, json);
+    assert!(serde_json::from_str::<RefNode>(&bad_json).is_err());
+
+    // the hierarchy is checked.
+    let bad_json = json.replace(r#""node_type":"Element""#, r#""node_type":"Comment""#);
+    assert!(serde_json::from_str::<RefNode>(&bad_json).is_err());
+}
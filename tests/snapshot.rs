@@ -0,0 +1,120 @@
+use std::thread;
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::{DocumentSnapshot, Snapshot};
+use xml_dom::level2::*;
+
+pub mod common;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_snapshot_is_send_and_sync() {
+    assert_send_sync::<Snapshot>();
+
+    let document_node = common::create_example_rdf_document();
+    let snapshot = document_node.snapshot().unwrap();
+    let expected = document_node.to_string();
+    let published = thread::spawn(move || snapshot.to_string()).join().unwrap();
+    assert_eq!(published, expected);
+}
+
+#[test]
+fn test_snapshot_shares_unchanged_subtrees() {
+    let document_node = common::create_example_rdf_document();
+    let first = document_node.snapshot().unwrap();
+    let second = document_node.snapshot().unwrap();
+    assert_eq!(first, second);
+
+    // change the text of the title, the second child of the description.
+    let root_node = document_node.document_element().unwrap();
+    let description_node = root_node.first_child().unwrap();
+    let title_node = description_node.child_nodes()[1].clone();
+    let mut text_node = title_node.first_child().unwrap();
+    text_node.set_node_value("Changed").unwrap();
+    let third = document_node.snapshot().unwrap();
+    assert_ne!(second, third);
+
+    let second_description = &second.document_element().unwrap().child_nodes()[0];
+    let third_description = &third.document_element().unwrap().child_nodes()[0];
+    assert_ne!(second_description, third_description);
+    for (index, (before, after)) in second_description
+        .child_nodes()
+        .iter()
+        .zip(third_description.child_nodes())
+        .enumerate()
+    {
+        assert_eq!(before == after, index != 1);
+    }
+    assert_ne!(
+        second_description.child_nodes()[1].text_content(),
+        "Changed"
+    );
+    assert_eq!(third_description.child_nodes()[1].text_content(), "Changed");
+}
+
+#[test]
+fn test_snapshot_history() {
+    let document_node = get_implementation()
+        .create_document(None, Some("log"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let mut history: Vec<Snapshot> = Vec::new();
+    for index in 0..3 {
+        let mut entry_node = document.create_element("entry").unwrap();
+        entry_node.set_attribute("n", &index.to_string()).unwrap();
+        let _ = root_node.append_child(entry_node).unwrap();
+        history.push(document_node.snapshot().unwrap());
+    }
+
+    // each entry is shared by every snapshot taken after it was added.
+    let last_entries = history[2].document_element().unwrap().child_nodes();
+    for (count, snapshot) in history.iter().enumerate() {
+        let entries = snapshot.document_element().unwrap().child_nodes();
+        assert_eq!(entries.len(), count + 1);
+        assert_eq!(entries, &last_entries[..=count]);
+    }
+
+    let restored_node = history[0].to_ref_node().unwrap();
+    assert_eq!(
+        restored_node.to_string(),
+        r#"<log><entry n="0"></entry></log>"#
+    );
+    let restored_root = as_document(&restored_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(restored_root.get_attribute("n"), None);
+    assert_eq!(
+        restored_root.first_child().unwrap().get_attribute("n"),
+        Some("0".to_string())
+    );
+}
+
+#[test]
+fn test_snapshot_not_a_document() {
+    let document_node = common::create_example_rdf_document();
+    let root_node = document_node.document_element().unwrap();
+    assert_eq!(root_node.snapshot(), Err(Error::InvalidState));
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_snapshot_prolog() {
+    use xml_dom::parser::read_xml;
+
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?><!DOCTYPE note SYSTEM "note.dtd"><!-- a note --><note xmlns:x="urn:x" x:kind="memo"><![CDATA[ <b> ]]><?app data?></note>"#;
+    let document_node = read_xml(xml).unwrap();
+    let snapshot = document_node.snapshot().unwrap();
+
+    assert!(snapshot.xml_declaration().is_some());
+    assert_eq!(snapshot.doc_type().unwrap().node_name().to_string(), "note");
+    let note = snapshot.document_element().unwrap();
+    assert_eq!(note.get_attribute("x:kind"), Some("memo"));
+    assert_eq!(note.child_nodes().len(), 2);
+    assert_eq!(
+        snapshot.to_ref_node().unwrap().to_string(),
+        document_node.to_string()
+    );
+}
@@ -0,0 +1,116 @@
+#![cfg(all(feature = "threadsafe", feature = "quick_parser"))]
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use xml_dom::level2::convert::{as_document, as_document_type, as_element_mut};
+use xml_dom::level2::ext::schema::TypeProvider;
+use xml_dom::level2::ext::sync::{ArcNode, Shared};
+use xml_dom::level2::ext::{DocumentDecl, SchemaTypes, TextContent};
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+fn is_send_and_sync<T: Send + Sync>() {}
+
+const DOCUMENT: &str = r#"<?xml version="1.0" standalone="yes"?>
+<!DOCTYPE list [<!ENTITY owner "Donald Duck."><!NOTATION gif SYSTEM "image/gif">]>
+<list xml:id="main"><item n="1">a</item><item n="2">b &amp; c</item></list>"#;
+
+#[derive(Debug)]
+struct Provider;
+
+impl TypeProvider for Provider {}
+
+#[test]
+fn test_arc_node_is_ref_node() {
+    is_send_and_sync::<ArcNode>();
+    is_send_and_sync::<RefNode>();
+
+    // parsed on another thread, and returned.
+    let document_node: ArcNode = thread::spawn(|| read_xml(DOCUMENT).unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(document_node.node_type(), NodeType::Document);
+
+    let handles: Vec<_> = (0..2)
+        .map(|index| {
+            let document_node = document_node.clone();
+            thread::spawn(move || {
+                let document = as_document(&document_node).unwrap();
+                let items = document.get_elements_by_tag_name("item");
+                (
+                    items[index].get_attribute("n").unwrap(),
+                    items[index].text_content().unwrap(),
+                )
+            })
+        })
+        .collect();
+    let items: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            ("1".to_string(), "a".to_string()),
+            ("2".to_string(), "b & c".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_arc_node_keeps_document_state() {
+    let document_node = read_xml(DOCUMENT).unwrap();
+    let shared_node = document_node.clone();
+    thread::spawn(move || {
+        let document = as_document(&shared_node).unwrap();
+        assert!(shared_node.xml_declaration().is_some());
+        let document_type_node = document.doc_type().unwrap();
+        let document_type = as_document_type(&document_type_node).unwrap();
+        assert_eq!(document_type.entities().len(), 1);
+        assert_eq!(document_type.notations().len(), 1);
+        let root_node = document.get_element_by_id("main").unwrap();
+        assert_eq!(Some(root_node), document.document_element());
+    })
+    .join()
+    .unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        r#"<?xml version="1.0" standalone="yes"?><!DOCTYPE list [<!ENTITY owner "Donald Duck."><!NOTATION gif SYSTEM "image/gif">]><list xml:id="main"><item n="1">a</item><item n="2">b &#38; c</item></list>"#
+    );
+}
+
+#[test]
+fn test_arc_node_edit() {
+    let mut document_node = read_xml(DOCUMENT).unwrap();
+    document_node
+        .set_type_provider(Some(Shared::new(Provider)))
+        .unwrap();
+
+    // changes made by several threads are serialized by the caller.
+    let shared_node = Arc::new(Mutex::new(document_node.clone()));
+    let handles: Vec<_> = (0..4)
+        .map(|index| {
+            let shared_node = shared_node.clone();
+            thread::spawn(move || {
+                let document_node = shared_node.lock().unwrap();
+                let document = as_document(&document_node).unwrap();
+                let mut root_node = document.document_element().unwrap();
+                as_element_mut(&mut root_node)
+                    .unwrap()
+                    .set_attribute(&format!("a{}", index), "v")
+                    .unwrap();
+                let _safe_to_ignore = root_node
+                    .append_child(document.create_comment(&index.to_string()))
+                    .unwrap();
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    assert_eq!(root_node.attributes().len(), 5);
+    assert_eq!(root_node.child_nodes().len(), 6);
+}
@@ -0,0 +1,95 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::freeze::{freeze, thaw};
+use xml_dom::level2::ext::{DocumentTeardown, NodeLifetime};
+use xml_dom::level2::*;
+
+pub mod common;
+
+// deep enough to overflow the stack below if nodes were dropped recursively.
+const DEEP_NESTING: usize = 5_000;
+const SMALL_STACK: usize = 256 * 1024;
+
+#[test]
+fn test_drop_deeply_nested_document() {
+    let dropped = std::thread::Builder::new()
+        .stack_size(SMALL_STACK)
+        .spawn(|| {
+            let document_node = get_implementation()
+                .create_document(None, Some("root"), None)
+                .unwrap();
+            let document = as_document(&document_node).unwrap();
+            let mut root_node = document.document_element().unwrap();
+
+            // built from the bottom up, so that appending doesn't walk the ancestors of the
+            // parent.
+            let mut nested_node = document.create_element("leaf").unwrap();
+            for _ in 0..DEEP_NESTING {
+                let mut parent_node = document.create_element("nested").unwrap();
+                let _safe_to_ignore = parent_node.append_child(nested_node).unwrap();
+                nested_node = parent_node;
+            }
+            let _safe_to_ignore = root_node.append_child(nested_node).unwrap();
+
+            drop(root_node);
+            drop(document_node);
+        })
+        .unwrap()
+        .join();
+    assert!(dropped.is_ok());
+}
+
+#[test]
+fn test_detach_all() {
+    let mut document_node = common::create_example_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    let description_node = root_node.first_child().unwrap();
+    let title_node = description_node.child_nodes()[1].clone();
+    let text_node = title_node.first_child().unwrap();
+    let attribute_node = title_node.get_attribute_node("xml:id").unwrap();
+
+    document_node.detach_all().unwrap();
+    assert!(!document_node.has_child_nodes());
+    assert!(document_node.document_element().is_none());
+    for node in &[&root_node, &description_node, &title_node, &text_node] {
+        assert!(node.parent_node().is_none());
+        assert!(!node.has_child_nodes());
+    }
+    assert_eq!(attribute_node.owner_element(), Some(title_node.clone()));
+    assert!(document_node.get_element_by_id("title").is_none());
+
+    assert!(!document_node.is_orphaned());
+    assert!(!title_node.is_orphaned());
+    drop(document_node);
+    assert!(title_node.is_orphaned());
+    assert!(attribute_node.is_orphaned());
+    assert!(title_node.owner_document().is_none());
+}
+
+#[test]
+fn test_detach_all_frozen() {
+    let mut document_node = common::create_example_rdf_document();
+    let mut root_node = document_node.document_element().unwrap();
+    let mut description_node = root_node.first_child().unwrap();
+
+    let token = freeze(&mut description_node);
+    assert_eq!(
+        document_node.detach_all(),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(root_node.parent_node(), Some(document_node.clone()));
+    assert_eq!(description_node.parent_node(), Some(root_node.clone()));
+
+    thaw(token);
+    document_node.detach_all().unwrap();
+    assert!(description_node.parent_node().is_none());
+    assert_eq!(root_node.detach_all(), Err(Error::InvalidState));
+}
+
+#[test]
+fn test_unowned_node_is_not_orphaned() {
+    let document_type_node = get_implementation()
+        .create_document_type("root", None, None)
+        .unwrap();
+    assert!(!document_type_node.is_orphaned());
+}
@@ -0,0 +1,123 @@
+use xml_dom::level2::convert::{as_attribute_mut, as_document};
+use xml_dom::level2::ext::dom_impl::get_implementation_ext;
+use xml_dom::level2::ext::{Axes, ProcessingOptions, TreeSizes};
+use xml_dom::level2::*;
+
+fn create_document(track: bool) -> RefNode {
+    let mut options = ProcessingOptions::new();
+    if track {
+        options.set_track_tree_sizes();
+    }
+    get_implementation_ext()
+        .create_document_with_options(None, Some("root"), None, options)
+        .unwrap()
+}
+
+fn assert_sizes(node: &RefNode) {
+    for node in node.descendant_or_self() {
+        let mut depth = 0;
+        let mut parent_node = node.parent_node();
+        while let Some(node) = parent_node {
+            depth += 1;
+            parent_node = node.parent_node();
+        }
+        assert_eq!(node.depth(), depth, "depth of {}", node.node_name());
+        assert_eq!(
+            node.subtree_size(),
+            node.descendant_or_self().count(),
+            "subtree size of {}",
+            node.node_name()
+        );
+    }
+}
+
+#[test]
+fn test_tree_sizes_maintained() {
+    for track in [true, false] {
+        let document_node = create_document(track);
+        let document = as_document(&document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+
+        let mut list_node = root_node
+            .append_child(document.create_element("list").unwrap())
+            .unwrap();
+        for index in 0..3 {
+            let mut item_node = list_node
+                .append_child(document.create_element("item").unwrap())
+                .unwrap();
+            let _safe_to_ignore = item_node
+                .append_child(document.create_text_node(&index.to_string()))
+                .unwrap();
+        }
+        assert_eq!(document_node.subtree_size(), 9);
+        assert_eq!(list_node.first_child().unwrap().depth(), 3);
+        assert_sizes(&document_node);
+
+        // move a subtree deeper, then replace and remove nodes.
+        let mut other_node = root_node
+            .insert_before(document.create_element("other").unwrap(), None)
+            .unwrap();
+        let mut wrapper_node = other_node
+            .append_child(document.create_element("wrapper").unwrap())
+            .unwrap();
+        let _safe_to_ignore = wrapper_node.append_child(list_node.clone()).unwrap();
+        assert_eq!(list_node.depth(), 4);
+        assert_eq!(other_node.subtree_size(), 9);
+        assert_sizes(&document_node);
+
+        let first_node = list_node.first_child().unwrap();
+        let _safe_to_ignore = list_node
+            .replace_child(document.create_comment("gone"), first_node.clone())
+            .unwrap();
+        assert_eq!(first_node.depth(), 0);
+        assert_eq!(first_node.subtree_size(), 2);
+        let last_node = list_node.last_child().unwrap();
+        let _safe_to_ignore = list_node.remove_child(last_node).unwrap();
+        assert_eq!(document_node.subtree_size(), 8);
+        assert_sizes(&document_node);
+
+        // fragments, and appending several children at once.
+        let mut fragment_node = document.create_document_fragment().unwrap();
+        let _safe_to_ignore = fragment_node.append_child(first_node.clone()).unwrap();
+        assert_eq!(fragment_node.subtree_size(), 3);
+        root_node
+            .append_children(vec![
+                fragment_node.clone(),
+                document.create_text_node("end"),
+            ])
+            .unwrap();
+        assert_eq!(fragment_node.subtree_size(), 1);
+        assert_eq!(first_node.depth(), 2);
+        assert_sizes(&document_node);
+
+        let clone_node = root_node.clone_node(true).unwrap();
+        assert_eq!(clone_node.subtree_size(), root_node.subtree_size());
+        assert_sizes(&clone_node);
+
+        let mut attribute_node = document.create_attribute_with("a", "1").unwrap();
+        assert_eq!(attribute_node.subtree_size(), 2);
+        as_attribute_mut(&mut attribute_node)
+            .unwrap()
+            .set_value("2")
+            .unwrap();
+        assert_eq!(attribute_node.subtree_size(), 2);
+        assert_eq!(attribute_node.first_child().unwrap().depth(), 1);
+    }
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_tree_sizes_parsed() {
+    let mut processing_options = ProcessingOptions::new();
+    processing_options.set_track_tree_sizes();
+    let mut options = xml_dom::parser::ParseOptions::new();
+    options.set_processing_options(processing_options);
+    let (document_node, _) =
+        xml_dom::parser::read_xml_with("<!DOCTYPE a><a><b><c/>text</b><!--d--><b/></a>", options)
+            .unwrap();
+
+    assert_eq!(document_node.subtree_size(), 7);
+    let document = as_document(&document_node).unwrap();
+    assert_eq!(document.doc_type().unwrap().depth(), 1);
+    assert_sizes(&document_node);
+}
@@ -0,0 +1,106 @@
+use xml_dom::level2::convert::{as_document, as_element_mut};
+use xml_dom::level2::ext::validate::{check_document, Violation, ViolationKind};
+use xml_dom::level2::*;
+
+#[test]
+fn test_check_valid_document() {
+    let document_node = get_implementation()
+        .create_document(Some("urn:x"), Some("x:doc"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("xmlns:x", "urn:x").unwrap();
+    root_node.set_attribute("xml:lang", "en").unwrap();
+    let mut item_node = root_node
+        .append_child(document.create_element_ns("urn:x", "x:item").unwrap())
+        .unwrap();
+    as_element_mut(&mut item_node)
+        .unwrap()
+        .set_attribute_ns("urn:x", "x:n", "1")
+        .unwrap();
+    let _safe_to_ignore = item_node
+        .append_child(document.create_cdata_section("a ]] > b").unwrap())
+        .unwrap();
+
+    assert_eq!(check_document(&document_node), Vec::new());
+    assert_eq!(check_document(&item_node), Vec::new());
+}
+
+#[test]
+fn test_check_unbound_prefixes() {
+    let document_node = get_implementation()
+        .create_document(None, Some("doc"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("xmlns:y", "urn:y").unwrap();
+    let mut item_node = root_node
+        .append_child(document.create_element_ns("urn:x", "x:item").unwrap())
+        .unwrap();
+    item_node.set_attribute("y:n", "1").unwrap();
+    // an empty value undeclares the prefix for the element's own attributes.
+    let mut inner_node = item_node
+        .append_child(document.create_element("inner").unwrap())
+        .unwrap();
+    inner_node.set_attribute("xmlns:y", "").unwrap();
+    inner_node.set_attribute("y:n", "2").unwrap();
+
+    let violations = check_document(&document_node);
+    assert_eq!(
+        violations
+            .iter()
+            .map(|violation| (
+                violation.node.node_name().to_string(),
+                violation.kind.clone()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "x:item".to_string(),
+                ViolationKind::UnboundPrefix("x".to_string())
+            ),
+            (
+                "y:n".to_string(),
+                ViolationKind::UnboundPrefix("y".to_string())
+            ),
+        ]
+    );
+    assert_eq!(
+        violations[0].to_string(),
+        "the prefix \"x\" is not declared (x:item)"
+    );
+    assert_eq!(check_document(&inner_node), violations[1..].to_vec());
+}
+
+#[test]
+fn test_check_cdata_end() {
+    let document_node = get_implementation()
+        .create_document(None, Some("doc"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let cdata_node = root_node
+        .append_child(document.create_cdata_section("if (a[b[0]]> 1)").unwrap())
+        .unwrap();
+
+    assert_eq!(
+        check_document(&document_node),
+        vec![Violation {
+            node: cdata_node,
+            kind: ViolationKind::CDataEnd,
+        }]
+    );
+}
+
+#[test]
+#[cfg(feature = "quick_parser")]
+fn test_check_parsed_document() {
+    let document_node = xml_dom::parser::read_xml(r#"<doc><p:item p:n="1"/></doc>"#).unwrap();
+    let violations = check_document(&document_node);
+    assert_eq!(violations.len(), 2);
+    assert_eq!(
+        violations[0].kind,
+        ViolationKind::UnboundPrefix("p".to_string())
+    );
+    assert_eq!(violations[1].node.node_name().to_string(), "p:n");
+}
@@ -0,0 +1,360 @@
+#![cfg(feature = "quick_parser")]
+
+// Runs the W3C XML conformance test suite (xmlconf) against the parser, in both strict and
+// lenient modes, and produces a conformance report. The suite is not part of this repository,
+// download and unpack it from <https://www.w3.org/XML/Test/> and then run:
+//
+//     XMLCONF_DIR=/path/to/xmlconf cargo test --test xmlconf -- --ignored --nocapture
+//
+// The following environment variables are also read:
+//
+// * `XMLCONF_REPORT`, a file to which the report is written as well as to stdout.
+// * `XMLCONF_BASELINE`, a file listing the tests, one `mode:id` per line, that are known to
+//   pass; the run fails if any of them no longer passes.
+// * `XMLCONF_UPDATE_BASELINE`, if set, the baseline file is rewritten with the tests that pass.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::ProcessingOptions;
+use xml_dom::level2::*;
+use xml_dom::parser::dtd::Dtd;
+use xml_dom::parser::{read_reader_with, read_xml, ParseOptions};
+
+// ------------------------------------------------------------------------------------------------
+// Harness
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Strict,
+    Lenient,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Expected {
+    WellFormed,
+    NotWellFormed,
+    Either,
+}
+
+#[derive(Clone, Debug)]
+struct TestCase {
+    id: String,
+    test_type: String,
+    path: PathBuf,
+    namespaces: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Outcome {
+    Pass,
+    Fail(String),
+    Skipped(String),
+}
+
+#[derive(Debug, Default)]
+struct Report {
+    catalog_errors: Vec<String>,
+    results: Vec<(Mode, TestCase, Outcome)>,
+}
+
+const MODES: [Mode; 2] = [Mode::Strict, Mode::Lenient];
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::Strict => write!(f, "strict"),
+            Mode::Lenient => write!(f, "lenient"),
+        }
+    }
+}
+
+impl TestCase {
+    fn expected(&self) -> Expected {
+        match self.test_type.as_str() {
+            // a non-validating parser only reports well-formedness errors.
+            "valid" | "invalid" => Expected::WellFormed,
+            "not-wf" => Expected::NotWellFormed,
+            _ => Expected::Either,
+        }
+    }
+
+    fn parse_options(&self, mode: Mode) -> ParseOptions {
+        let mut options = ParseOptions::new();
+        if mode == Mode::Strict {
+            let mut processing_options = ProcessingOptions::new();
+            processing_options.set_strict_names();
+            if self.namespaces {
+                processing_options.set_validate_namespace_uris();
+                options.set_strict_namespaces();
+            }
+            options.set_processing_options(processing_options);
+        }
+        options
+    }
+
+    fn run(&self, mode: Mode) -> Outcome {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) => return Outcome::Skipped(format!("{}: {}", self.path.display(), e)),
+        };
+        let options = self.parse_options(mode);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            read_reader_with(&bytes[..], options).map(|_| ())
+        }));
+        match (self.expected(), result) {
+            (_, Err(_)) => Outcome::Fail("parser panicked".to_string()),
+            (Expected::WellFormed, Ok(Err(e))) => Outcome::Fail(format!("rejected: {}", e)),
+            (Expected::NotWellFormed, Ok(Ok(_))) => Outcome::Fail("accepted".to_string()),
+            _ => Outcome::Pass,
+        }
+    }
+}
+
+impl Report {
+    fn passed(&self) -> BTreeSet<String> {
+        self.results
+            .iter()
+            .filter(|(_, _, outcome)| *outcome == Outcome::Pass)
+            .map(|(mode, case, _)| format!("{}:{}", mode, case.id))
+            .collect()
+    }
+
+    fn count(&self, mode: Mode, test_type: Option<&str>) -> (usize, usize, usize) {
+        let mut counts = (0, 0, 0);
+        for (_, _, outcome) in self.results.iter().filter(|(result_mode, case, _)| {
+            *result_mode == mode && test_type.is_none_or(|t| case.test_type == t)
+        }) {
+            match outcome {
+                Outcome::Pass => counts.0 += 1,
+                Outcome::Fail(_) => counts.1 += 1,
+                Outcome::Skipped(_) => counts.2 += 1,
+            }
+        }
+        counts
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "XML conformance report")?;
+        for error in &self.catalog_errors {
+            writeln!(f, "catalog error: {}", error)?;
+        }
+        for mode in MODES {
+            let (passed, failed, skipped) = self.count(mode, None);
+            writeln!(
+                f,
+                "{}: {} passed, {} failed, {} skipped",
+                mode, passed, failed, skipped
+            )?;
+            for test_type in ["valid", "invalid", "not-wf", "error"] {
+                let (passed, failed, skipped) = self.count(mode, Some(test_type));
+                writeln!(
+                    f,
+                    "  {:<8} {} passed, {} failed, {} skipped",
+                    test_type, passed, failed, skipped
+                )?;
+            }
+        }
+        for (mode, case, outcome) in &self.results {
+            match outcome {
+                Outcome::Pass => {}
+                Outcome::Fail(reason) => writeln!(
+                    f,
+                    "FAIL {}:{} ({}) {}",
+                    mode, case.id, case.test_type, reason
+                )?,
+                Outcome::Skipped(reason) => writeln!(
+                    f,
+                    "SKIP {}:{} ({}) {}",
+                    mode, case.id, case.test_type, reason
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// The master catalog includes each test collection's catalog as an external entity, the
+/// declarations are read from its internal subset as the parser does not expand them.
+///
+fn load_catalog(root: &Path, report: &mut Report) -> Vec<TestCase> {
+    let master_path = root.join("xmlconf.xml");
+    let master = match fs::read_to_string(&master_path) {
+        Ok(master) => master,
+        Err(e) => {
+            report
+                .catalog_errors
+                .push(format!("{}: {}", master_path.display(), e));
+            return Vec::default();
+        }
+    };
+    let internal_subset = master
+        .find("<!DOCTYPE")
+        .and_then(|start| {
+            let rest = &master[start..];
+            let open = rest.find('[')?;
+            let close = rest.find("]>")?;
+            rest.get(open + 1..close)
+        })
+        .unwrap_or_default();
+
+    let mut cases = Vec::default();
+    for entity in Dtd::parse(internal_subset).entities() {
+        let catalog_path = match &entity.system_id {
+            Some(system_id) => root.join(system_id),
+            None => continue,
+        };
+        let document_node = match fs::read_to_string(&catalog_path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| read_xml(text).map_err(|e| e.to_string()))
+        {
+            Ok(document_node) => document_node,
+            Err(e) => {
+                report
+                    .catalog_errors
+                    .push(format!("{}: {}", catalog_path.display(), e));
+                continue;
+            }
+        };
+        let base = catalog_path.parent().unwrap_or(root);
+        if let Some(catalog_node) = as_document(&document_node).unwrap().document_element() {
+            collect_cases(&catalog_node, base, &mut cases);
+        }
+    }
+    cases
+}
+
+fn collect_cases(catalog_node: &RefNode, base: &Path, cases: &mut Vec<TestCase>) {
+    let base = match catalog_node.get_attribute("xml:base") {
+        Some(xml_base) => base.join(xml_base),
+        None => base.to_path_buf(),
+    };
+    for child_node in catalog_node.child_nodes() {
+        if child_node.node_type() != NodeType::Element {
+            continue;
+        }
+        match child_node.node_name().to_string().as_str() {
+            "TESTCASES" => collect_cases(&child_node, &base, cases),
+            "TEST" => {
+                if let (Some(id), Some(test_type), Some(uri)) = (
+                    child_node.get_attribute("ID"),
+                    child_node.get_attribute("TYPE"),
+                    child_node.get_attribute("URI"),
+                ) {
+                    cases.push(TestCase {
+                        id,
+                        test_type,
+                        path: base.join(uri),
+                        namespaces: child_node.get_attribute("NAMESPACE").as_deref() != Some("no"),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn run_suite(root: &Path) -> Report {
+    let mut report = Report::default();
+    let cases = load_catalog(root, &mut report);
+    for mode in MODES {
+        for case in &cases {
+            let outcome = case.run(mode);
+            report.results.push((mode, case.clone(), outcome));
+        }
+    }
+    report
+}
+
+// ------------------------------------------------------------------------------------------------
+// Tests
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+#[ignore]
+fn test_xmlconf_suite() {
+    let root = match std::env::var_os("XMLCONF_DIR") {
+        Some(root) => PathBuf::from(root),
+        None => {
+            println!("XMLCONF_DIR is not set, skipping the conformance suite");
+            return;
+        }
+    };
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let report = run_suite(&root);
+    std::panic::set_hook(previous_hook);
+
+    let text = report.to_string();
+    println!("{}", text);
+    if let Some(report_path) = std::env::var_os("XMLCONF_REPORT") {
+        fs::write(report_path, &text).unwrap();
+    }
+    assert!(report.catalog_errors.is_empty());
+
+    if let Some(baseline_path) = std::env::var_os("XMLCONF_BASELINE") {
+        let passed = report.passed();
+        if std::env::var_os("XMLCONF_UPDATE_BASELINE").is_some() {
+            let lines: Vec<&str> = passed.iter().map(String::as_str).collect();
+            fs::write(baseline_path, lines.join("\n") + "\n").unwrap();
+        } else {
+            let baseline = fs::read_to_string(baseline_path).unwrap();
+            let regressions: Vec<&str> = baseline
+                .lines()
+                .map(str::trim)
+                .filter(|id| !id.is_empty() && !passed.contains(*id))
+                .collect();
+            assert!(
+                regressions.is_empty(),
+                "tests no longer passing: {:?}",
+                regressions
+            );
+        }
+    }
+}
+
+#[test]
+fn test_xmlconf_harness() {
+    let root = std::env::temp_dir().join(format!("xml_dom_xmlconf_{}", std::process::id()));
+    fs::create_dir_all(root.join("sample/files")).unwrap();
+    fs::write(
+        root.join("xmlconf.xml"),
+        r#"<!DOCTYPE TESTSUITE SYSTEM "testcases.dtd" [
+<!ENTITY sample SYSTEM "sample/sample.xml">
+]>
+<TESTSUITE PROFILE="sample">&sample;</TESTSUITE>"#,
+    )
+    .unwrap();
+    fs::write(
+        root.join("sample/sample.xml"),
+        r#"<TESTCASES PROFILE="sample" xml:base="files/">
+<TEST TYPE="valid" ID="wf" URI="wf.xml">well-formed</TEST>
+<TEST TYPE="not-wf" ID="not-wf" URI="not-wf.xml">not well-formed</TEST>
+<TEST TYPE="not-wf" ID="ns" URI="ns.xml" NAMESPACE="no">undeclared prefix</TEST>
+<TEST TYPE="valid" ID="missing" URI="missing.xml">missing</TEST>
+</TESTCASES>"#,
+    )
+    .unwrap();
+    fs::write(root.join("sample/files/wf.xml"), "<doc><a/></doc>").unwrap();
+    fs::write(root.join("sample/files/not-wf.xml"), "<doc><a></doc>").unwrap();
+    fs::write(root.join("sample/files/ns.xml"), "<doc><p:a/></doc>").unwrap();
+
+    let report = run_suite(&root);
+    fs::remove_dir_all(&root).unwrap();
+    assert!(report.catalog_errors.is_empty());
+    assert_eq!(report.results.len(), 8);
+    assert_eq!(
+        report.passed().into_iter().collect::<Vec<_>>(),
+        vec!["lenient:not-wf", "lenient:wf", "strict:not-wf", "strict:wf"]
+    );
+    assert_eq!(report.count(Mode::Strict, None), (2, 1, 1));
+    assert_eq!(report.count(Mode::Strict, Some("not-wf")), (1, 1, 0));
+    assert!(report.to_string().contains("SKIP lenient:missing (valid)"));
+}
@@ -0,0 +1,129 @@
+use xml_dom::level2::convert::as_document;
+use xml_dom::level2::ext::mapping::XmlValue;
+use xml_dom::level2::ext::xsd::Decimal;
+use xml_dom::level2::ext::TypedContent;
+use xml_dom::level2::*;
+
+pub mod common;
+
+fn create_element() -> (RefNode, RefNode) {
+    let document_node = get_implementation()
+        .create_document(None, Some("data"), None)
+        .unwrap();
+    let element = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    (document_node, element)
+}
+
+#[test]
+fn test_double_lexical_forms() {
+    assert_eq!(1.5_f64.to_xml_value(), "1.5");
+    assert_eq!(1e21_f64.to_xml_value(), "1000000000000000000000");
+    assert_eq!(f64::INFINITY.to_xml_value(), "INF");
+    assert_eq!(f64::NEG_INFINITY.to_xml_value(), "-INF");
+    assert_eq!(f32::NAN.to_xml_value(), "NaN");
+    assert_eq!(0.1_f32.to_xml_value(), "0.1");
+
+    assert_eq!(f64::from_xml_value(" 1.5E3 "), Ok(1500.0));
+    assert_eq!(f64::from_xml_value("-INF"), Ok(f64::NEG_INFINITY));
+    assert!(f64::from_xml_value("NaN").unwrap().is_nan());
+    assert_eq!(f64::from_xml_value("inf"), Err(Error::Syntax));
+    assert_eq!(f64::from_xml_value("nan"), Err(Error::Syntax));
+    assert_eq!(f64::from_xml_value("1,5"), Err(Error::Syntax));
+}
+
+#[test]
+fn test_decimal_lexical_forms() {
+    assert_eq!(
+        Decimal::new(1e21).unwrap().to_xml_value(),
+        "1000000000000000000000"
+    );
+    assert_eq!(Decimal::new(-0.001).unwrap().to_xml_value(), "-0.001");
+    assert_eq!(Decimal::new(f64::NAN), Err(Error::Syntax));
+
+    assert_eq!(Decimal::from_xml_value("+.5").unwrap().value(), 0.5);
+    assert_eq!(Decimal::from_xml_value("12.").unwrap().value(), 12.0);
+    assert_eq!(Decimal::from_xml_value("1e5"), Err(Error::Syntax));
+    assert_eq!(Decimal::from_xml_value("INF"), Err(Error::Syntax));
+    assert_eq!(Decimal::from_xml_value("."), Err(Error::Syntax));
+}
+
+#[test]
+fn test_boolean_lexical_forms() {
+    assert_eq!(bool::from_xml_value("1"), Ok(true));
+    assert_eq!(bool::from_xml_value("false"), Ok(false));
+    assert_eq!(bool::from_xml_value("True"), Err(Error::Syntax));
+}
+
+#[test]
+fn test_typed_content() {
+    let (_document_node, mut element) = create_element();
+    element.set_attribute_value("count", &42_u32).unwrap();
+    element.set_attribute_value("ratio", &0.25_f64).unwrap();
+    element.set_text_value(&true).unwrap();
+    assert_eq!(element.get_attribute("count"), Some("42".to_string()));
+    assert_eq!(element.get_attribute("ratio"), Some("0.25".to_string()));
+    assert_eq!(
+        element.child_nodes()[0].node_value(),
+        Some("true".to_string())
+    );
+    assert_eq!(element.attribute_value::<u32>("count"), Ok(Some(42)));
+    assert_eq!(element.attribute_value::<u32>("missing"), Ok(None));
+    assert_eq!(element.attribute_value::<u32>("ratio"), Err(Error::Syntax));
+    assert_eq!(element.text_value::<bool>(), Ok(Some(true)));
+
+    common::sub_test("test_typed_content", "replace text");
+    element.set_text_value(&Decimal::new(9.5).unwrap()).unwrap();
+    assert_eq!(element.child_nodes().len(), 1);
+    assert_eq!(
+        element.text_value::<Decimal>().unwrap().unwrap().value(),
+        9.5
+    );
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn test_chrono_lexical_forms() {
+    use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+    let utc = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+    assert_eq!(utc.to_xml_value(), "2024-01-02T03:04:05Z");
+    assert_eq!(
+        DateTime::<Utc>::from_xml_value("2024-01-02T04:04:05+01:00"),
+        Ok(utc)
+    );
+
+    let offset = FixedOffset::east_opt(5400)
+        .unwrap()
+        .with_ymd_and_hms(2024, 1, 2, 3, 4, 5)
+        .unwrap();
+    assert_eq!(offset.to_xml_value(), "2024-01-02T03:04:05+01:30");
+    assert_eq!(
+        DateTime::<FixedOffset>::from_xml_value(&offset.to_xml_value()),
+        Ok(offset)
+    );
+    assert_eq!(
+        DateTime::<FixedOffset>::from_xml_value("2024-01-02T03:04:05"),
+        Err(Error::Syntax)
+    );
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+    let time = NaiveTime::from_hms_milli_opt(3, 4, 5, 500).unwrap();
+    let naive = NaiveDateTime::new(date, time);
+    assert_eq!(naive.to_xml_value(), "2024-01-02T03:04:05.500");
+    assert_eq!(
+        NaiveDateTime::from_xml_value("2024-01-02T03:04:05.5"),
+        Ok(naive)
+    );
+    assert_eq!(date.to_xml_value(), "2024-01-02");
+    assert_eq!(NaiveDate::from_xml_value("2024-01-02"), Ok(date));
+    assert_eq!(NaiveTime::from_xml_value("03:04:05.5"), Ok(time));
+    assert_eq!(NaiveDate::from_xml_value("02/01/2024"), Err(Error::Syntax));
+
+    common::sub_test("test_chrono_lexical_forms", "typed_content");
+    let (_document_node, mut element) = create_element();
+    element.set_attribute_value("created", &utc).unwrap();
+    assert_eq!(element.attribute_value("created"), Ok(Some(utc)));
+}
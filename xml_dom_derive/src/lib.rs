@@ -0,0 +1,336 @@
+/*!
+Provides the derive macros `ToXmlDom` and `FromXmlDom`, which map the fields of a struct to the
+attributes, text, and child elements of an [xml_dom](https://crates.io/crates/xml_dom) element.
+This crate is not intended to be used directly, enable the `derive` feature of `xml_dom` and use
+the macros re-exported from the `xml_dom::level2::ext::mapping` module.
+
+# Field Mapping
+
+Only structs with named fields are supported. Each field is mapped according to its `xml`
+attribute, and the name of the attribute or child element is the name of the field unless it is
+given with `rename`.
+
+| Field attribute                | Mapping                                         |
+|--------------------------------|-------------------------------------------------|
+| _none_                         | a child element, `<name>...</name>`             |
+| `#[xml(attribute)]`            | an attribute of the element, `name="..."`       |
+| `#[xml(text)]`                 | the text content of the element                 |
+| `#[xml(rename = "dc:title")]`  | use the given name rather than the field name   |
+
+A field of type `Option<T>` may be absent, and a child element field of type `Vec<T>` maps to
+every child element with the field's name. Attribute and text fields must have a type that
+implements `XmlValue`, child element fields a type that implements `ToXmlDom` or `FromXmlDom`;
+every `XmlValue` type is mapped as an element containing only its text.
+
+# Example
+
+```rust,ignore
+use xml_dom::level2::ext::mapping::{FromXmlDom, ToXmlDom};
+
+#[derive(Debug, PartialEq, ToXmlDom, FromXmlDom)]
+struct Note {
+    #[xml(attribute)]
+    id: u32,
+    #[xml(rename = "to")]
+    recipients: Vec<String>,
+    heading: Option<String>,
+    body: String,
+}
+
+let note = Note {
+    id: 1,
+    recipients: vec!["Tove".to_string()],
+    heading: None,
+    body: "Don't forget me this weekend!".to_string(),
+};
+let document_node = note.to_document("note").unwrap();
+assert_eq!(
+    document_node.to_string(),
+    r#"<note id="1"><to>Tove</to><body>Don't forget me this weekend!</body></note>"#
+);
+assert_eq!(Note::from_document(&document_node).unwrap(), note);
+```
+
+*/
+
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    rust_2018_idioms,
+    unreachable_pub,
+    unsafe_code,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results
+)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Fields, GenericArgument, Ident, LitStr, PathArguments,
+    Type,
+};
+
+// ------------------------------------------------------------------------------------------------
+// Public Macros
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Derive an implementation of `ToXmlDom` that writes each field of the struct as an attribute,
+/// the text content, or a child element of the new element.
+///
+#[proc_macro_derive(ToXmlDom, attributes(xml))]
+pub fn derive_to_xml_dom(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_xml_dom(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+///
+/// Derive an implementation of `FromXmlDom` that reads each field of the struct from an
+/// attribute, the text content, or the child elements of an element.
+///
+#[proc_macro_derive(FromXmlDom, attributes(xml))]
+pub fn derive_from_xml_dom(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_xml_dom(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FieldKind {
+    Element,
+    Attribute,
+    Text,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Multiplicity {
+    One,
+    Optional,
+    Many,
+}
+
+#[derive(Debug)]
+struct MappedField {
+    ident: Ident,
+    name: String,
+    kind: FieldKind,
+    multiplicity: Multiplicity,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn to_xml_dom(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = mapped_fields(input, "ToXmlDom")?;
+    let writes = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let name = &field.name;
+        let write = |value: TokenStream2| match field.kind {
+            FieldKind::Attribute => quote! {
+                ::xml_dom::level2::ext::mapping::set_attribute(&mut element, #name, #value)?;
+            },
+            FieldKind::Text => quote! {
+                ::xml_dom::level2::ext::mapping::append_text(document, &mut element, #value)?;
+            },
+            FieldKind::Element => quote! {
+                ::xml_dom::level2::ext::mapping::append_element(
+                    document, &mut element, #name, #value
+                )?;
+            },
+        };
+        match field.multiplicity {
+            Multiplicity::One => write(quote! { &self.#ident }),
+            Multiplicity::Optional => {
+                let write = write(quote! { value });
+                quote! {
+                    if let Some(value) = &self.#ident {
+                        #write
+                    }
+                }
+            }
+            Multiplicity::Many => {
+                let write = write(quote! { value });
+                quote! {
+                    for value in &self.#ident {
+                        #write
+                    }
+                }
+            }
+        }
+    });
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::xml_dom::level2::ext::mapping::ToXmlDom for #ident #type_generics
+        #where_clause
+        {
+            fn to_element(
+                &self,
+                document: &::xml_dom::level2::RefNode,
+                name: &str,
+            ) -> ::xml_dom::level2::Result<::xml_dom::level2::RefNode> {
+                let mut element = ::xml_dom::level2::ext::mapping::create_element(document, name)?;
+                #(#writes)*
+                Ok(element)
+            }
+        }
+    })
+}
+
+fn from_xml_dom(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = mapped_fields(input, "FromXmlDom")?;
+    let reads = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let name = &field.name;
+        let read = match (field.kind, field.multiplicity) {
+            (FieldKind::Attribute, Multiplicity::One) => quote! {
+                ::xml_dom::level2::ext::mapping::required(
+                    ::xml_dom::level2::ext::mapping::attribute(element, #name)?,
+                    #name,
+                )?
+            },
+            (FieldKind::Attribute, _) => quote! {
+                ::xml_dom::level2::ext::mapping::attribute(element, #name)?
+            },
+            (FieldKind::Text, Multiplicity::One) => quote! {
+                ::xml_dom::level2::ext::mapping::text(element)?
+            },
+            (FieldKind::Text, _) => quote! {
+                ::xml_dom::level2::ext::mapping::optional_text(element)?
+            },
+            (FieldKind::Element, Multiplicity::One) => quote! {
+                ::xml_dom::level2::ext::mapping::required(
+                    ::xml_dom::level2::ext::mapping::child(element, #name)?,
+                    #name,
+                )?
+            },
+            (FieldKind::Element, Multiplicity::Optional) => quote! {
+                ::xml_dom::level2::ext::mapping::child(element, #name)?
+            },
+            (FieldKind::Element, Multiplicity::Many) => quote! {
+                ::xml_dom::level2::ext::mapping::children(element, #name)?
+            },
+        };
+        quote! { #ident: #read, }
+    });
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::xml_dom::level2::ext::mapping::FromXmlDom for #ident #type_generics
+        #where_clause
+        {
+            fn from_element(
+                element: &::xml_dom::level2::RefNode,
+            ) -> ::xml_dom::level2::Result<Self> {
+                Ok(Self {
+                    #(#reads)*
+                })
+            }
+        }
+    })
+}
+
+fn mapped_fields(input: &DeriveInput, derive: &str) -> syn::Result<Vec<MappedField>> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    format!(
+                        "{} can only be derived for structs with named fields",
+                        derive
+                    ),
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                format!("{} can only be derived for structs", derive),
+            ))
+        }
+    };
+
+    let mut mapped = Vec::new();
+    for field in fields {
+        let ident = field.ident.clone().unwrap();
+        let mut name = ident.unraw().to_string();
+        let mut kind = FieldKind::Element;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("xml"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("attribute") {
+                    kind = FieldKind::Attribute;
+                } else if meta.path.is_ident("text") {
+                    kind = FieldKind::Text;
+                } else if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                } else {
+                    return Err(meta.error("expected `attribute`, `text`, or `rename`"));
+                }
+                Ok(())
+            })?;
+        }
+        let multiplicity = multiplicity(&field.ty);
+        if multiplicity == Multiplicity::Many && kind != FieldKind::Element {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "only child element fields may be a `Vec`",
+            ));
+        }
+        if kind == FieldKind::Text && mapped.iter().any(|f: &MappedField| f.kind == kind) {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                "only one field may be mapped to the element's text",
+            ));
+        }
+        mapped.push(MappedField {
+            ident,
+            name,
+            kind,
+            multiplicity,
+        });
+    }
+    Ok(mapped)
+}
+
+///
+/// The multiplicity is determined by the syntax of the field's type alone, so a type alias for
+/// `Option` or `Vec` is treated as a single value.
+///
+fn multiplicity(ty: &Type) -> Multiplicity {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                if arguments.args.len() == 1
+                    && matches!(arguments.args.first(), Some(GenericArgument::Type(_)))
+                {
+                    if segment.ident == "Option" {
+                        return Multiplicity::Optional;
+                    } else if segment.ident == "Vec" {
+                        return Multiplicity::Many;
+                    }
+                }
+            }
+        }
+    }
+    Multiplicity::One
+}